@@ -24,7 +24,13 @@ use std::os::unix::fs::MetadataExt;
 use std::path::Path;
 use std::time::Duration;
 
-use kanidm_proto::constants::{APPLICATION_JSON, ATTR_NAME};
+use kanidm_proto::constants::{
+    APPLICATION_JSON, ATTR_ACCESS_REQUEST_APPROVER, ATTR_ACCESS_REQUEST_DURATION,
+    ATTR_AUTH_PASSWORD_MINIMUM_LENGTH, ATTR_AUTH_SESSION_EXPIRY, ATTR_AUTOMOUNT_INFORMATION,
+    ATTR_AUTOMOUNT_KEY, ATTR_AUTOMOUNT_MAP, ATTR_CREDENTIAL_TYPE_ALLOWED, ATTR_DISPLAYNAME,
+    ATTR_ENTRY_MANAGED_BY, ATTR_MEMBER_EXPIRING, ATTR_NAME, ATTR_PRIVILEGE_EXPIRY,
+    ATTR_SUDO_NOPASSWD,
+};
 use kanidm_proto::v1::*;
 use reqwest::header::CONTENT_TYPE;
 use reqwest::Response;
@@ -1532,6 +1538,86 @@ impl KanidmClient {
         .await
     }
 
+    /// Add members to a group until the rfc3339 `expiry` time, after which the server will
+    /// remove them.
+    pub async fn idm_group_add_expiring_members(
+        &self,
+        id: &str,
+        members: &[&str],
+        expiry: &str,
+    ) -> Result<(), ClientError> {
+        let m: Vec<_> = members
+            .iter()
+            .map(|v| format!("{},{}", v, expiry))
+            .collect();
+        self.perform_post_request(
+            &format!("/v1/group/{}/_attr/{}", id, ATTR_MEMBER_EXPIRING),
+            m,
+        )
+        .await
+    }
+
+    pub async fn idm_group_remove_expiring_members(
+        &self,
+        group: &str,
+        members: &[&str],
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request_with_body(
+            &format!("/v1/group/{}/_attr/{}", group, ATTR_MEMBER_EXPIRING),
+            &members,
+        )
+        .await
+    }
+
+    pub async fn idm_group_set_entry_managed_by(
+        &self,
+        id: &str,
+        entry_manager: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/group/{}/_attr/{}", id, ATTR_ENTRY_MANAGED_BY),
+            vec![entry_manager.to_string()],
+        )
+        .await
+    }
+
+    pub async fn idm_group_access_request_set_approvers(
+        &self,
+        id: &str,
+        approvers: &[&str],
+    ) -> Result<(), ClientError> {
+        let m: Vec<_> = approvers.iter().map(|v| (*v).to_string()).collect();
+        self.perform_put_request(
+            &format!("/v1/group/{}/_attr/{}", id, ATTR_ACCESS_REQUEST_APPROVER),
+            m,
+        )
+        .await
+    }
+
+    pub async fn idm_group_access_request_set_duration(
+        &self,
+        id: &str,
+        duration: u32,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/group/{}/_attr/{}", id, ATTR_ACCESS_REQUEST_DURATION),
+            vec![duration.to_string()],
+        )
+        .await
+    }
+
+    pub async fn idm_group_access_request_create(
+        &self,
+        id: &str,
+        justification: &str,
+    ) -> Result<Uuid, ClientError> {
+        let create = AccessRequestCreate {
+            justification: justification.to_string(),
+        };
+        self.perform_post_request(&format!("/v1/group/{}/_access_request", id), create)
+            .await
+    }
+
     pub async fn idm_group_purge_members(&self, id: &str) -> Result<(), ClientError> {
         self.perform_delete_request(&format!("/v1/group/{}/_attr/member", id))
             .await
@@ -1552,6 +1638,66 @@ impl KanidmClient {
             .await
     }
 
+    pub async fn idm_group_account_policy_enable(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_post_request(
+            &format!("/v1/group/{}/_attr/class", id),
+            vec!["account_policy".to_string()],
+        )
+        .await
+    }
+
+    pub async fn idm_group_account_policy_credential_type_allowed(
+        &self,
+        id: &str,
+        credential_types: &[&str],
+    ) -> Result<(), ClientError> {
+        let m: Vec<_> = credential_types.iter().map(|v| (*v).to_string()).collect();
+        self.perform_put_request(
+            &format!("/v1/group/{}/_attr/{}", id, ATTR_CREDENTIAL_TYPE_ALLOWED),
+            m,
+        )
+        .await
+    }
+
+    pub async fn idm_group_account_policy_password_minimum_length(
+        &self,
+        id: &str,
+        length: u32,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!(
+                "/v1/group/{}/_attr/{}",
+                id, ATTR_AUTH_PASSWORD_MINIMUM_LENGTH
+            ),
+            vec![length.to_string()],
+        )
+        .await
+    }
+
+    pub async fn idm_group_account_policy_authsession_expiry(
+        &self,
+        id: &str,
+        expiry: u32,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/group/{}/_attr/{}", id, ATTR_AUTH_SESSION_EXPIRY),
+            vec![expiry.to_string()],
+        )
+        .await
+    }
+
+    pub async fn idm_group_account_policy_privilege_expiry(
+        &self,
+        id: &str,
+        expiry: u32,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/group/{}/_attr/{}", id, ATTR_PRIVILEGE_EXPIRY),
+            vec![expiry.to_string()],
+        )
+        .await
+    }
+
     pub async fn idm_group_delete(&self, id: &str) -> Result<(), ClientError> {
         self.perform_delete_request(&format!("/v1/group/{}", id))
             .await
@@ -1744,6 +1890,23 @@ impl KanidmClient {
             .await
     }
 
+    pub async fn idm_account_unix_cred_change(
+        &self,
+        id: &str,
+        current_cred: &str,
+        new_cred: &str,
+    ) -> Result<(), ClientError> {
+        let req = UnixPasswordChangeRequest {
+            current: current_cred.to_string(),
+            new: new_cred.to_string(),
+        };
+        self.perform_post_request(
+            &format!("/v1/account/{}/_unix/_credential/_change", id),
+            req,
+        )
+        .await
+    }
+
     // == generic ssh key handlers
     pub async fn idm_account_get_ssh_pubkey(
         &self,
@@ -1846,4 +2009,313 @@ impl KanidmClient {
         self.perform_post_request(&format!("/v1/recycle_bin/{}/_revive", id), ())
             .await
     }
+
+    // ==== replication conflicts
+    pub async fn system_replication_conflict_list(
+        &self,
+    ) -> Result<Vec<ReplicationConflict>, ClientError> {
+        self.perform_get_request("/v1/system/replication/conflicts")
+            .await
+    }
+
+    pub async fn system_replication_conflict_get(
+        &self,
+        id: &str,
+    ) -> Result<ReplicationConflict, ClientError> {
+        self.perform_get_request(&format!("/v1/system/replication/conflicts/{}", id))
+            .await
+    }
+
+    pub async fn system_replication_conflict_resolve(
+        &self,
+        id: &str,
+        resolution: ReplicationConflictResolution,
+    ) -> Result<(), ClientError> {
+        self.perform_post_request(
+            &format!("/v1/system/replication/conflicts/{}/_resolve", id),
+            resolution,
+        )
+        .await
+    }
+
+    // ==== access requests
+    pub async fn idm_access_request_list(&self) -> Result<Vec<AccessRequest>, ClientError> {
+        self.perform_get_request("/v1/access_request").await
+    }
+
+    pub async fn idm_access_request_approve(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_post_request(&format!("/v1/access_request/{}/_approve", id), ())
+            .await
+    }
+
+    pub async fn idm_access_request_deny(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_post_request(&format!("/v1/access_request/{}/_deny", id), ())
+            .await
+    }
+
+    // ==== access reviews
+    pub async fn idm_access_review_create(
+        &self,
+        name: &str,
+        filter: Filter,
+        expiry: &str,
+    ) -> Result<Uuid, ClientError> {
+        let create = AccessReviewCreate {
+            name: name.to_string(),
+            filter,
+            expiry: expiry.to_string(),
+        };
+        self.perform_post_request("/v1/access_review", create).await
+    }
+
+    pub async fn idm_access_review_list(&self) -> Result<Vec<AccessReview>, ClientError> {
+        self.perform_get_request("/v1/access_review").await
+    }
+
+    pub async fn idm_access_review_report(
+        &self,
+        id: &str,
+    ) -> Result<AccessReviewReport, ClientError> {
+        self.perform_get_request(&format!("/v1/access_review/{}/_report", id))
+            .await
+    }
+
+    pub async fn idm_access_review_decide(
+        &self,
+        id: &str,
+        item: Uuid,
+        decision: AccessReviewDecision,
+    ) -> Result<(), ClientError> {
+        self.perform_post_request(
+            &format!("/v1/access_review/{}/_decide", id),
+            AccessReviewDecide { item, decision },
+        )
+        .await
+    }
+
+    // ==== sudo rules
+    pub async fn idm_sudo_rule_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/sudo_rule").await
+    }
+
+    pub async fn idm_sudo_rule_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
+        self.perform_get_request(&format!("/v1/sudo_rule/{}", id))
+            .await
+    }
+
+    pub async fn idm_sudo_rule_create(&self, name: &str) -> Result<(), ClientError> {
+        let mut new_rule = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_rule
+            .attrs
+            .insert(ATTR_NAME.to_string(), vec![name.to_string()]);
+        self.perform_post_request("/v1/sudo_rule", new_rule).await
+    }
+
+    pub async fn idm_sudo_rule_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/sudo_rule/{}", id))
+            .await
+    }
+
+    /// Add values to one of the sudo_user, sudo_host, sudo_command or sudo_runas attributes.
+    pub async fn idm_sudo_rule_add_attr(
+        &self,
+        id: &str,
+        attr: &str,
+        values: &[&str],
+    ) -> Result<(), ClientError> {
+        let v: Vec<_> = values.iter().map(|v| (*v).to_string()).collect();
+        self.perform_post_request(&format!("/v1/sudo_rule/{}/_attr/{}", id, attr), v)
+            .await
+    }
+
+    pub async fn idm_sudo_rule_remove_attr(
+        &self,
+        id: &str,
+        attr: &str,
+        values: &[&str],
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request_with_body(
+            &format!("/v1/sudo_rule/{}/_attr/{}", id, attr),
+            &values,
+        )
+        .await
+    }
+
+    pub async fn idm_sudo_rule_set_nopasswd(
+        &self,
+        id: &str,
+        nopasswd: bool,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/sudo_rule/{}/_attr/{}", id, ATTR_SUDO_NOPASSWD),
+            vec![nopasswd.to_string()],
+        )
+        .await
+    }
+
+    /// Retrieve the sudo rules in the form that unix hosts evaluate them.
+    pub async fn idm_sudo_rule_unix_list(&self) -> Result<Vec<UnixSudoRule>, ClientError> {
+        self.perform_get_request("/v1/sudo_rule/_unix").await
+    }
+
+    // ==== hosts
+    pub async fn idm_host_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/host").await
+    }
+
+    pub async fn idm_host_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
+        self.perform_get_request(&format!("/v1/host/{}", id)).await
+    }
+
+    /// Create a host. Hosts are service accounts, so their api tokens are managed with
+    /// the service account api token functions.
+    pub async fn idm_host_create(&self, name: &str, displayname: &str) -> Result<(), ClientError> {
+        let mut new_host = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_host
+            .attrs
+            .insert(ATTR_NAME.to_string(), vec![name.to_string()]);
+        new_host
+            .attrs
+            .insert(ATTR_DISPLAYNAME.to_string(), vec![displayname.to_string()]);
+        self.perform_post_request("/v1/host", new_host).await
+    }
+
+    pub async fn idm_host_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/host/{}", id))
+            .await
+    }
+
+    // ==== hbac rules
+    pub async fn idm_hbac_rule_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/hbac_rule").await
+    }
+
+    pub async fn idm_hbac_rule_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
+        self.perform_get_request(&format!("/v1/hbac_rule/{}", id))
+            .await
+    }
+
+    pub async fn idm_hbac_rule_create(&self, name: &str) -> Result<(), ClientError> {
+        let mut new_rule = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_rule
+            .attrs
+            .insert(ATTR_NAME.to_string(), vec![name.to_string()]);
+        self.perform_post_request("/v1/hbac_rule", new_rule).await
+    }
+
+    pub async fn idm_hbac_rule_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/hbac_rule/{}", id))
+            .await
+    }
+
+    /// Add values to one of the hbac_user, hbac_host or hbac_service attributes.
+    pub async fn idm_hbac_rule_add_attr(
+        &self,
+        id: &str,
+        attr: &str,
+        values: &[&str],
+    ) -> Result<(), ClientError> {
+        let v: Vec<_> = values.iter().map(|v| (*v).to_string()).collect();
+        self.perform_post_request(&format!("/v1/hbac_rule/{}/_attr/{}", id, attr), v)
+            .await
+    }
+
+    pub async fn idm_hbac_rule_remove_attr(
+        &self,
+        id: &str,
+        attr: &str,
+        values: &[&str],
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request_with_body(
+            &format!("/v1/hbac_rule/{}/_attr/{}", id, attr),
+            &values,
+        )
+        .await
+    }
+
+    /// Retrieve the host access rules that apply on this host. This must be called
+    /// with the api token of a host.
+    pub async fn idm_hbac_rule_unix_list(&self) -> Result<Vec<UnixHbacRule>, ClientError> {
+        self.perform_get_request("/v1/hbac_rule/_unix").await
+    }
+
+    // ==== automount
+    pub async fn idm_automount_map_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/automount_map").await
+    }
+
+    pub async fn idm_automount_map_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
+        self.perform_get_request(&format!("/v1/automount_map/{}", id))
+            .await
+    }
+
+    pub async fn idm_automount_map_create(&self, name: &str) -> Result<(), ClientError> {
+        let mut new_map = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_map
+            .attrs
+            .insert(ATTR_NAME.to_string(), vec![name.to_string()]);
+        self.perform_post_request("/v1/automount_map", new_map)
+            .await
+    }
+
+    pub async fn idm_automount_map_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/automount_map/{}", id))
+            .await
+    }
+
+    /// Retrieve the automount maps and their keys in the form that unix hosts consume.
+    pub async fn idm_automount_map_unix_list(&self) -> Result<Vec<UnixAutomountMap>, ClientError> {
+        self.perform_get_request("/v1/automount_map/_unix").await
+    }
+
+    pub async fn idm_automount_key_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/automount").await
+    }
+
+    pub async fn idm_automount_key_create(
+        &self,
+        map: &str,
+        key: &str,
+        information: &str,
+    ) -> Result<(), ClientError> {
+        let mut new_key = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_key
+            .attrs
+            .insert(ATTR_AUTOMOUNT_MAP.to_string(), vec![map.to_string()]);
+        new_key
+            .attrs
+            .insert(ATTR_AUTOMOUNT_KEY.to_string(), vec![key.to_string()]);
+        new_key.attrs.insert(
+            ATTR_AUTOMOUNT_INFORMATION.to_string(),
+            vec![information.to_string()],
+        );
+        self.perform_post_request("/v1/automount", new_key).await
+    }
+
+    pub async fn idm_automount_key_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/automount/{}", id))
+            .await
+    }
+
+    pub async fn idm_automount_key_set_information(
+        &self,
+        id: &str,
+        information: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/automount/{}/_attr/{}", id, ATTR_AUTOMOUNT_INFORMATION),
+            vec![information.to_string()],
+        )
+        .await
+    }
 }
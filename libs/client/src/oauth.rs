@@ -1,7 +1,9 @@
 use crate::{ClientError, KanidmClient};
 use kanidm_proto::constants::{
     ATTR_DISPLAYNAME, ATTR_OAUTH2_ALLOW_INSECURE_CLIENT_DISABLE_PKCE,
-    ATTR_OAUTH2_JWT_LEGACY_CRYPTO_ENABLE, ATTR_OAUTH2_RS_NAME, ATTR_OAUTH2_RS_ORIGIN,
+    ATTR_OAUTH2_DEVICE_FLOW_ENABLE, ATTR_OAUTH2_JWT_LEGACY_CRYPTO_ENABLE,
+    ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES, ATTR_OAUTH2_RS_NAME, ATTR_OAUTH2_RS_ORIGIN,
+    ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE, ATTR_UUID,
 };
 use kanidm_proto::internal::ImageValue;
 use kanidm_proto::v1::Entry;
@@ -176,6 +178,104 @@ impl KanidmClient {
             .await
     }
 
+    pub async fn idm_oauth2_rs_update_client_credentials_scopes(
+        &self,
+        id: &str,
+        scopes: Vec<&str>,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES.to_string(),
+            scopes.into_iter().map(str::to_string).collect(),
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_delete_client_credentials_scopes(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES.to_string(),
+            Vec::new(),
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    /// Resource servers don't have a name that can be resolved by the server, so look
+    /// up the current token exchange sources and the uuid of the source to update.
+    async fn idm_oauth2_rs_token_exchange_sources(
+        &self,
+        id: &str,
+        source: &str,
+    ) -> Result<(Vec<String>, String), ClientError> {
+        let current = self
+            .idm_oauth2_rs_get(id)
+            .await?
+            .ok_or(ClientError::EmptyResponse)?
+            .attrs
+            .remove(ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE)
+            .unwrap_or_default();
+
+        let source_uuid = self
+            .idm_oauth2_rs_get(source)
+            .await?
+            .and_then(|mut e| e.attrs.remove(ATTR_UUID))
+            .and_then(|mut v| v.pop())
+            .ok_or(ClientError::EmptyResponse)?;
+
+        Ok((current, source_uuid))
+    }
+
+    pub async fn idm_oauth2_rs_add_token_exchange_source(
+        &self,
+        id: &str,
+        source: &str,
+    ) -> Result<(), ClientError> {
+        let (mut sources, source_uuid) = self
+            .idm_oauth2_rs_token_exchange_sources(id, source)
+            .await?;
+        if !sources.contains(&source_uuid) {
+            sources.push(source_uuid);
+        }
+
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs
+            .attrs
+            .insert(ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE.to_string(), sources);
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_remove_token_exchange_source(
+        &self,
+        id: &str,
+        source: &str,
+    ) -> Result<(), ClientError> {
+        let (mut sources, source_uuid) = self
+            .idm_oauth2_rs_token_exchange_sources(id, source)
+            .await?;
+        sources.retain(|s| s != &source_uuid);
+
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs
+            .attrs
+            .insert(ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE.to_string(), sources);
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
     pub async fn idm_oauth2_rs_delete(&self, id: &str) -> Result<(), ClientError> {
         self.perform_delete_request(["/v1/oauth2/", id].concat().as_str())
             .await
@@ -297,6 +397,30 @@ impl KanidmClient {
             .await
     }
 
+    pub async fn idm_oauth2_rs_enable_device_flow(&self, id: &str) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            ATTR_OAUTH2_DEVICE_FLOW_ENABLE.to_string(),
+            vec!["true".to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_disable_device_flow(&self, id: &str) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            ATTR_OAUTH2_DEVICE_FLOW_ENABLE.to_string(),
+            vec!["false".to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
     pub async fn idm_oauth2_rs_prefer_short_username(&self, id: &str) -> Result<(), ClientError> {
         let mut update_oauth2_rs = Entry {
             attrs: BTreeMap::new(),
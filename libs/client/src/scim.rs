@@ -1,5 +1,7 @@
 use crate::{ClientError, KanidmClient};
-use kanidm_proto::scim_v1::{ScimSyncRequest, ScimSyncState};
+use kanidm_proto::scim_v1::{
+    ScimSyncRequest, ScimSyncState, ScimSyncWriteback, ScimSyncWritebackAck,
+};
 
 impl KanidmClient {
     pub async fn scim_v1_sync_status(&self) -> Result<ScimSyncState, ClientError> {
@@ -13,4 +15,16 @@ impl KanidmClient {
         self.perform_post_request("/scim/v1/Sync", scim_sync_request)
             .await
     }
+
+    pub async fn scim_v1_sync_writeback(&self) -> Result<ScimSyncWriteback, ClientError> {
+        self.perform_get_request("/scim/v1/Sync/Writeback").await
+    }
+
+    pub async fn scim_v1_sync_writeback_ack(
+        &self,
+        ack: &ScimSyncWritebackAck,
+    ) -> Result<(), ClientError> {
+        self.perform_post_request("/scim/v1/Sync/Writeback", ack)
+            .await
+    }
 }
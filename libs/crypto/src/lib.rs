@@ -1151,6 +1151,60 @@ impl Password {
         }
     }
 
+    /// Encode this password as an LDAP userPassword value, in the formats that we are able to
+    /// import from OpenLDAP and 389-ds. Hashes that are bound to a TPM, or that have no LDAP
+    /// representation, can not be exported.
+    pub fn to_ldap_userpassword(&self) -> Option<String> {
+        // OpenLDAP's pbkdf2 module uses passlib's adapted base64.
+        let ab64 = |v: &[u8]| general_purpose::STANDARD_NO_PAD.encode(v).replace('+', ".");
+
+        match &self.material {
+            Kdf::TPM_ARGON2ID { .. } | Kdf::NT_MD4(_) => None,
+            Kdf::ARGON2ID {
+                m_cost,
+                t_cost,
+                p_cost,
+                version,
+                salt,
+                key,
+            } => Some(format!(
+                "{{ARGON2}}$argon2id$v={}$m={},t={},p={}${}${}",
+                version,
+                m_cost,
+                t_cost,
+                p_cost,
+                general_purpose::STANDARD_NO_PAD.encode(salt),
+                general_purpose::STANDARD_NO_PAD.encode(key)
+            )),
+            Kdf::PBKDF2(cost, salt, hash) => Some(format!(
+                "{{PBKDF2-SHA256}}{}${}${}",
+                cost,
+                ab64(salt),
+                ab64(hash)
+            )),
+            Kdf::PBKDF2_SHA1(cost, salt, hash) => Some(format!(
+                "{{PBKDF2-SHA1}}{}${}${}",
+                cost,
+                ab64(salt),
+                ab64(hash)
+            )),
+            Kdf::PBKDF2_SHA512(cost, salt, hash) => Some(format!(
+                "{{PBKDF2-SHA512}}{}${}${}",
+                cost,
+                ab64(salt),
+                ab64(hash)
+            )),
+            Kdf::SSHA512(salt, hash) => {
+                let mut sh = hash.clone();
+                sh.extend_from_slice(salt);
+                Some(format!(
+                    "{{SSHA512}}{}",
+                    general_purpose::STANDARD.encode(sh)
+                ))
+            }
+        }
+    }
+
     pub fn requires_upgrade(&self) -> bool {
         match &self.material {
             Kdf::ARGON2ID {
@@ -1324,6 +1378,28 @@ mod tests {
         assert!(r.verify(password).unwrap_or(false));
     }
 
+    #[test]
+    fn test_password_to_ldap_userpassword() {
+        let p = CryptoPolicy::minimum();
+        let password = "password";
+
+        let c = Password::new_argon2id(&p, password).unwrap();
+        let ldap_pw = c.to_ldap_userpassword().expect("Failed to export");
+        assert!(ldap_pw.starts_with("{ARGON2}$argon2id$"));
+        let r = Password::try_from(ldap_pw.as_str()).expect("Failed to parse");
+        assert!(r.verify(password).unwrap_or(false));
+
+        let c = Password::new_pbkdf2(&p, password).unwrap();
+        let ldap_pw = c.to_ldap_userpassword().expect("Failed to export");
+        assert!(ldap_pw.starts_with("{PBKDF2-SHA256}"));
+        let r = Password::try_from(ldap_pw.as_str()).expect("Failed to parse");
+        assert!(r.verify(password).unwrap_or(false));
+
+        let im_pw = "{SSHA512}JwrSUHkI7FTAfHRVR6KoFlSN0E3dmaQWARjZ+/UsShYlENOqDtFVU77HJLLrY2MuSp0jve52+pwtdVl2QUAHukQ0XUf5LDtM";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert_eq!(r.to_ldap_userpassword().as_deref(), Some(im_pw));
+    }
+
     /*
      * wbrown - 20221104 - I tried to programmatically enable the legacy provider, but
      * it consistently "did nothing at all", meaning we have to rely on users to enable
@@ -6,8 +6,9 @@ use tracing_forest::util::*;
 use tracing_forest::Tag;
 
 pub mod macros;
+pub mod otel;
 
-pub use {tracing, tracing_forest, tracing_subscriber};
+pub use {tracing, tracing_forest, tracing_opentelemetry, tracing_subscriber};
 
 pub fn test_init() {
     // tracing_subscriber::fmt::try_init()
@@ -0,0 +1,245 @@
+//! Export of tracing spans to an OpenTelemetry collector with OTLP.
+//!
+//! Each request to the server has an event id, which is the uuid of its root span. So that
+//! logs and traces can be correlated, the event id is used as the trace id of the exported
+//! spans. If the request was made as part of an existing trace, the event id is instead taken
+//! from the trace id of the caller.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use opentelemetry::trace::{
+    SpanContext, SpanId, TraceContextExt, TraceError, TraceFlags, TraceId, TraceState,
+};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::Tracer;
+use opentelemetry_sdk::Resource;
+use tracing::Span;
+use tracing_opentelemetry::{OpenTelemetrySpanExt, OtelData};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+use uuid::Uuid;
+
+/// The name of the http header that carries a W3C trace context.
+pub const TRACEPARENT: &str = "traceparent";
+
+const OTLP_EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Start a batching exporter that sends spans to the OTLP gRPC collector at `endpoint`.
+/// This must be called from within a tokio runtime.
+pub fn otlp_tracer(endpoint: &str, service_name: &str) -> Result<Tracer, TraceError> {
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint)
+                .with_timeout(OTLP_EXPORT_TIMEOUT),
+        )
+        .with_trace_config(
+            opentelemetry_sdk::trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                service_name.to_string(),
+            )])),
+        )
+        .install_batch(opentelemetry_sdk::runtime::Tokio)
+}
+
+/// Flush any spans that are yet to be exported, and stop the exporter.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+/// A W3C trace context as sent by a caller in the `traceparent` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceParent {
+    pub trace_id: [u8; 16],
+    pub parent_id: [u8; 8],
+    pub flags: u8,
+}
+
+impl TraceParent {
+    /// The event id of a request that continues this trace.
+    pub fn eventid(&self) -> Uuid {
+        Uuid::from_bytes(self.trace_id)
+    }
+}
+
+fn lower_hex(s: &str, len: usize) -> Option<u128> {
+    if s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        u128::from_str_radix(s, 16).ok()
+    } else {
+        None
+    }
+}
+
+impl FromStr for TraceParent {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.trim().split('-');
+        let version = fields.next().and_then(|v| lower_hex(v, 2)).ok_or(())?;
+        let trace_id = fields.next().and_then(|v| lower_hex(v, 32)).ok_or(())?;
+        let parent_id = fields.next().and_then(|v| lower_hex(v, 16)).ok_or(())?;
+        let flags = fields.next().and_then(|v| lower_hex(v, 2)).ok_or(())?;
+
+        // Version ff is invalid, and version 00 has no further fields. Later versions may
+        // append fields that we are required to ignore.
+        if version == 0xff || (version == 0 && fields.next().is_some()) {
+            return Err(());
+        }
+
+        // All zero ids are invalid.
+        if trace_id == 0 || parent_id == 0 {
+            return Err(());
+        }
+
+        Ok(TraceParent {
+            trace_id: trace_id.to_be_bytes(),
+            parent_id: (parent_id as u64).to_be_bytes(),
+            flags: flags as u8,
+        })
+    }
+}
+
+/// Make a new root span part of the trace of a remote caller.
+pub fn set_span_parent(span: &Span, parent: &TraceParent) {
+    let span_context = SpanContext::new(
+        TraceId::from_bytes(parent.trace_id),
+        SpanId::from_bytes(parent.parent_id),
+        TraceFlags::new(parent.flags),
+        true,
+        TraceState::default(),
+    );
+    span.set_parent(Context::new().with_remote_span_context(span_context));
+}
+
+/// Set the trace id of a new root span to the event id. This must be called before any
+/// child spans are created. If spans are not being exported, this does nothing.
+pub fn set_span_trace_id(span: &Span, eventid: Uuid) {
+    span.with_subscriber(|(id, dispatch)| {
+        let Some(registry) = dispatch.downcast_ref::<Registry>() else {
+            return;
+        };
+        if let Some(span_ref) = registry.span(id) {
+            if let Some(data) = span_ref.extensions_mut().get_mut::<OtelData>() {
+                data.builder.trace_id = Some(TraceId::from_bytes(eventid.into_bytes()));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    const TEST_TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    // The provider must outlive the subscriber, as the tracer only holds a weak reference.
+    fn test_subscriber() -> (TracerProvider, impl tracing::Subscriber + Send + Sync) {
+        let provider = TracerProvider::builder().build();
+        let tracer = provider.tracer("test");
+        let subscriber =
+            Registry::default().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        (provider, subscriber)
+    }
+
+    fn span_trace_id(span: &Span) -> TraceId {
+        span.context().span().span_context().trace_id()
+    }
+
+    #[test]
+    fn test_traceparent_parse() {
+        let tp: TraceParent = TEST_TRACEPARENT.parse().expect("invalid traceparent");
+        assert_eq!(
+            tp.trace_id,
+            0x4bf92f3577b34da6a3ce929d0e0e4736u128.to_be_bytes()
+        );
+        assert_eq!(tp.parent_id, 0x00f067aa0ba902b7u64.to_be_bytes());
+        assert_eq!(tp.flags, 1);
+        assert_eq!(
+            tp.eventid(),
+            Uuid::parse_str("4bf92f35-77b3-4da6-a3ce-929d0e0e4736").expect("invalid uuid")
+        );
+
+        // Surrounding whitespace is ignored.
+        assert_eq!(
+            format!(" {}\t", TEST_TRACEPARENT).parse::<TraceParent>(),
+            Ok(tp)
+        );
+
+        // Later versions may append fields.
+        assert_eq!(
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-future".parse::<TraceParent>(),
+            Ok(TraceParent { flags: 0, ..tp })
+        );
+    }
+
+    #[test]
+    fn test_traceparent_parse_invalid() {
+        for invalid in [
+            "",
+            "00",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            // Version 00 has no further fields.
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-future",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            // Only lower case hex is valid.
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1",
+            "00-+bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+        ] {
+            assert_eq!(invalid.parse::<TraceParent>(), Err(()), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_set_span_trace_id() {
+        let (_provider, subscriber) = test_subscriber();
+        let eventid = Uuid::new_v4();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request");
+            set_span_trace_id(&span, eventid);
+            let child = span.in_scope(|| tracing::info_span!("child"));
+
+            let trace_id = TraceId::from_bytes(eventid.into_bytes());
+            assert_eq!(span_trace_id(&span), trace_id);
+            assert_eq!(span_trace_id(&child), trace_id);
+        });
+    }
+
+    #[test]
+    fn test_set_span_parent() {
+        let (_provider, subscriber) = test_subscriber();
+        let tp: TraceParent = TEST_TRACEPARENT.parse().expect("invalid traceparent");
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request");
+            set_span_parent(&span, &tp);
+            let child = span.in_scope(|| tracing::info_span!("child"));
+
+            // The trace id of the caller is our event id.
+            let trace_id = TraceId::from_bytes(tp.eventid().into_bytes());
+            assert_eq!(span_trace_id(&span), trace_id);
+            assert_eq!(span_trace_id(&child), trace_id);
+        });
+    }
+
+    #[test]
+    fn test_set_span_trace_id_without_export() {
+        // Without an OpenTelemetry layer there is nothing to set.
+        tracing::subscriber::with_default(Registry::default(), || {
+            let span = tracing::info_span!("request");
+            set_span_trace_id(&span, Uuid::new_v4());
+            assert_eq!(span_trace_id(&span), TraceId::INVALID);
+        });
+    }
+}
@@ -49,7 +49,10 @@ install -Dm644 examples/systemd/kanidm-unixd-tasks.service "${pkgdir}/usr/lib/sy
 
 # NB., the debian style lib dir and security dir
 install -Dm755 target/release/libnss_kanidm.so "${pkgdir}/usr/lib/x86_64-linux-gnu/libnss_kanidm.so.2"
+install -Dm755 target/release/libnss_kanidm.so "${pkgdir}/usr/lib/x86_64-linux-gnu/libsubid_kanidm.so"
+install -Dm755 target/release/libnss_kanidm.so "${pkgdir}/usr/lib/x86_64-linux-gnu/sssd/modules/libsss_autofs.so"
 install -Dm755 target/release/libpam_kanidm.so "${pkgdir}/usr/lib/x86_64-linux-gnu/security/pam_kanidm.so"
+install -Dm755 target/release/libsudo_kanidm.so "${pkgdir}/usr/lib/x86_64-linux-gnu/sudo/sudo_kanidm.so"
 
 # install kanidm unix utilities
 install -Dm755 target/release/kanidm_ssh_authorizedkeys "${pkgdir}/usr/local/sbin/kanidm_ssh_authorizedkeys"
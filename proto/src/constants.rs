@@ -16,6 +16,7 @@ pub const VALID_IMAGE_UPLOAD_CONTENT_TYPES: [&str; 5] = [
 ];
 
 pub const APPLICATION_JSON: &str = "application/json";
+pub const APPLICATION_SCIM_JSON: &str = "application/scim+json";
 
 /// The "system" path for Kanidm client config
 pub const DEFAULT_CLIENT_CONFIG_PATH: &str = "/etc/kanidm/config";
@@ -29,6 +30,21 @@ pub const DEFAULT_LDAP_ADDRESS: &str = "127.0.0.1:636";
 pub const DEFAULT_LDAP_LOCALHOST: &str = "localhost:636";
 
 /// IF YOU CHANGE THESE VALUES YOU BREAK EVERYTHING
+pub const ATTR_ACCESS_REQUEST_APPROVER: &str = "access_request_approver";
+pub const ATTR_ACCESS_REQUEST_DECIDED_BY: &str = "access_request_decided_by";
+pub const ATTR_ACCESS_REQUEST_DURATION: &str = "access_request_duration";
+pub const ATTR_ACCESS_REQUEST_GROUP: &str = "access_request_group";
+pub const ATTR_ACCESS_REQUEST_JUSTIFICATION: &str = "access_request_justification";
+pub const ATTR_ACCESS_REQUEST_REQUESTER: &str = "access_request_requester";
+pub const ATTR_ACCESS_REQUEST_STATE: &str = "access_request_state";
+pub const ATTR_ACCESS_REVIEW_CAMPAIGN: &str = "access_review_campaign";
+pub const ATTR_ACCESS_REVIEW_DECIDED_BY: &str = "access_review_decided_by";
+pub const ATTR_ACCESS_REVIEW_DECISION: &str = "access_review_decision";
+pub const ATTR_ACCESS_REVIEW_EXPIRY: &str = "access_review_expiry";
+pub const ATTR_ACCESS_REVIEW_GROUP: &str = "access_review_group";
+pub const ATTR_ACCESS_REVIEW_MEMBER: &str = "access_review_member";
+pub const ATTR_ACCESS_REVIEW_REVIEWER: &str = "access_review_reviewer";
+pub const ATTR_ACCESS_REVIEW_STATE: &str = "access_review_state";
 pub const ATTR_ACCOUNT_EXPIRE: &str = "account_expire";
 pub const ATTR_ACCOUNT_VALID_FROM: &str = "account_valid_from";
 pub const ATTR_ACCOUNT: &str = "account";
@@ -46,13 +62,18 @@ pub const ATTR_API_TOKEN_SESSION: &str = "api_token_session";
 pub const ATTR_ATTR: &str = "attr";
 pub const ATTR_ATTRIBUTENAME: &str = "attributename";
 pub const ATTR_ATTRIBUTETYPE: &str = "attributetype";
+pub const ATTR_AUTH_PASSWORD_MINIMUM_LENGTH: &str = "auth_password_minimum_length";
 pub const ATTR_AUTH_SESSION_EXPIRY: &str = "authsession_expiry";
+pub const ATTR_AUTOMOUNT_INFORMATION: &str = "automount_information";
+pub const ATTR_AUTOMOUNT_KEY: &str = "automount_key";
+pub const ATTR_AUTOMOUNT_MAP: &str = "automount_map";
 pub const ATTR_BADLIST_PASSWORD: &str = "badlist_password";
 pub const ATTR_CLAIM: &str = "claim";
 pub const ATTR_CLASS: &str = "class";
 pub const ATTR_CLASSNAME: &str = "classname";
 pub const ATTR_CN: &str = "cn";
 pub const ATTR_COOKIE_PRIVATE_KEY: &str = "cookie_private_key";
+pub const ATTR_CREDENTIAL_TYPE_ALLOWED: &str = "credential_type_allowed";
 pub const ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN: &str = "credential_update_intent_token";
 pub const ATTR_DESCRIPTION: &str = "description";
 pub const ATTR_DEVICEKEYS: &str = "devicekeys";
@@ -69,10 +90,14 @@ pub const ATTR_DOMAIN: &str = "domain";
 pub const ATTR_DYNGROUP_FILTER: &str = "dyngroup_filter";
 pub const ATTR_DYNGROUP: &str = "dyngroup";
 pub const ATTR_DYNMEMBER: &str = "dynmember";
+pub const ATTR_LDAP_AUTOMOUNT_INFORMATION: &str = "automountinformation";
+pub const ATTR_LDAP_AUTOMOUNT_KEY: &str = "automountkey";
+pub const ATTR_LDAP_AUTOMOUNT_MAP_NAME: &str = "automountmapname";
 pub const ATTR_LDAP_EMAIL_ADDRESS: &str = "emailaddress";
 pub const ATTR_EMAIL_ALTERNATIVE: &str = "emailalternative";
 pub const ATTR_EMAIL_PRIMARY: &str = "emailprimary";
 pub const ATTR_EMAIL: &str = "email";
+pub const ATTR_ENTRY_MANAGED_BY: &str = "entry_managed_by";
 pub const ATTR_ENTRYDN: &str = "entrydn";
 pub const ATTR_ENTRYUUID: &str = "entryuuid";
 pub const ATTR_LDAP_KEYS: &str = "keys";
@@ -82,6 +107,9 @@ pub const ATTR_FERNET_PRIVATE_KEY_STR: &str = "fernet_private_key_str";
 pub const ATTR_GIDNUMBER: &str = "gidnumber";
 pub const ATTR_GRANT_UI_HINT: &str = "grant_ui_hint";
 pub const ATTR_GROUP: &str = "group";
+pub const ATTR_HBAC_HOST: &str = "hbac_host";
+pub const ATTR_HBAC_SERVICE: &str = "hbac_service";
+pub const ATTR_HBAC_USER: &str = "hbac_user";
 pub const ATTR_ID_VERIFICATION_ECKEY: &str = "id_verification_eckey";
 pub const ATTR_IMAGE: &str = "image";
 pub const ATTR_INDEX: &str = "index";
@@ -95,6 +123,7 @@ pub const ATTR_MAIL: &str = "mail";
 pub const ATTR_MAY: &str = "may";
 pub const ATTR_MEMBER: &str = "member";
 pub const ATTR_MEMBEROF: &str = "memberof";
+pub const ATTR_MEMBER_EXPIRING: &str = "member_expiring";
 pub const ATTR_MULTIVALUE: &str = "multivalue";
 pub const ATTR_MUST: &str = "must";
 pub const ATTR_NAME_HISTORY: &str = "name_history";
@@ -105,15 +134,26 @@ pub const ATTR_NSUNIQUEID: &str = "nsuniqueid";
 pub const ATTR_OAUTH2_ALLOW_INSECURE_CLIENT_DISABLE_PKCE: &str =
     "oauth2_allow_insecure_client_disable_pkce";
 pub const ATTR_OAUTH2_CONSENT_SCOPE_MAP: &str = "oauth2_consent_scope_map";
+pub const ATTR_OAUTH2_DEVICE_ACCOUNT: &str = "oauth2_device_account";
+pub const ATTR_OAUTH2_DEVICE_CLIENT: &str = "oauth2_device_client";
+pub const ATTR_OAUTH2_DEVICE_EXPIRY: &str = "oauth2_device_expiry";
+pub const ATTR_OAUTH2_DEVICE_FLOW_ENABLE: &str = "oauth2_device_flow_enable";
+pub const ATTR_OAUTH2_DEVICE_PARENT_EXPIRY: &str = "oauth2_device_parent_expiry";
+pub const ATTR_OAUTH2_DEVICE_PARENT_SESSION: &str = "oauth2_device_parent_session";
+pub const ATTR_OAUTH2_DEVICE_SCOPE: &str = "oauth2_device_scope";
+pub const ATTR_OAUTH2_DEVICE_STATE: &str = "oauth2_device_state";
+pub const ATTR_OAUTH2_DEVICE_USER_CODE: &str = "oauth2_device_user_code";
 pub const ATTR_OAUTH2_JWT_LEGACY_CRYPTO_ENABLE: &str = "oauth2_jwt_legacy_crypto_enable";
 pub const ATTR_OAUTH2_PREFER_SHORT_USERNAME: &str = "oauth2_prefer_short_username";
 pub const ATTR_OAUTH2_RS_BASIC_SECRET: &str = "oauth2_rs_basic_secret";
+pub const ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES: &str = "oauth2_rs_client_credentials_scopes";
 pub const ATTR_OAUTH2_RS_IMPLICIT_SCOPES: &str = "oauth2_rs_implicit_scopes";
 pub const ATTR_OAUTH2_RS_NAME: &str = "oauth2_rs_name";
 pub const ATTR_OAUTH2_RS_ORIGIN_LANDING: &str = "oauth2_rs_origin_landing";
 pub const ATTR_OAUTH2_RS_ORIGIN: &str = "oauth2_rs_origin";
 pub const ATTR_OAUTH2_RS_SCOPE_MAP: &str = "oauth2_rs_scope_map";
 pub const ATTR_OAUTH2_RS_SUP_SCOPE_MAP: &str = "oauth2_rs_sup_scope_map";
+pub const ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE: &str = "oauth2_rs_token_exchange_source";
 pub const ATTR_OAUTH2_RS_TOKEN_KEY: &str = "oauth2_rs_token_key";
 pub const ATTR_OAUTH2_SESSION: &str = "oauth2_session";
 pub const ATTR_OBJECTCLASS: &str = "objectclass";
@@ -133,6 +173,12 @@ pub const ATTR_SCOPE: &str = "scope";
 pub const ATTR_SELF: &str = "self";
 pub const ATTR_SOURCE_UUID: &str = "source_uuid";
 pub const ATTR_SPN: &str = "spn";
+pub const ATTR_SUBID_RANGE_START: &str = "subid_range_start";
+pub const ATTR_SUDO_COMMAND: &str = "sudo_command";
+pub const ATTR_SUDO_HOST: &str = "sudo_host";
+pub const ATTR_SUDO_NOPASSWD: &str = "sudo_nopasswd";
+pub const ATTR_SUDO_RUNAS: &str = "sudo_runas";
+pub const ATTR_SUDO_USER: &str = "sudo_user";
 pub const ATTR_SUPPLEMENTS: &str = "supplements";
 pub const ATTR_LDAP_SSHPUBLICKEY: &str = "sshpublickey";
 pub const ATTR_SSH_PUBLICKEY: &str = "ssh_publickey";
@@ -144,6 +190,7 @@ pub const ATTR_SYNC_EXTERNAL_ID: &str = "sync_external_id";
 pub const ATTR_SYNC_EXTERNAL_UUID: &str = "sync_external_uuid";
 pub const ATTR_SYNC_PARENT_UUID: &str = "sync_parent_uuid";
 pub const ATTR_SYNC_TOKEN_SESSION: &str = "sync_token_session";
+pub const ATTR_SYNC_WRITEBACK_COOKIE: &str = "sync_writeback_cookie";
 pub const ATTR_SYNC_YIELD_AUTHORITY: &str = "sync_yield_authority";
 pub const ATTR_SYNTAX: &str = "syntax";
 pub const ATTR_SYSTEMEXCLUDES: &str = "systemexcludes";
@@ -167,6 +214,8 @@ pub const OAUTH2_SCOPE_OPENID: &str = "openid";
 pub const OAUTH2_SCOPE_READ: &str = "read";
 pub const OAUTH2_SCOPE_SUPPLEMENT: &str = "supplement";
 
+pub const OAUTH2_TOKEN_TYPE_ACCESS_TOKEN: &str = "urn:ietf:params:oauth:token-type:access_token";
+
 pub const LDAP_ATTR_CN: &str = "cn";
 pub const LDAP_ATTR_EMAIL_ALTERNATIVE: &str = "emailalternative";
 pub const LDAP_ATTR_EMAIL_PRIMARY: &str = "emailprimary";
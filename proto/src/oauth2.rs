@@ -90,6 +90,28 @@ pub enum GrantTypeReq {
         #[serde_as(as = "Option<StringWithSeparator::<SpaceSeparator, String>>")]
         scope: Option<BTreeSet<String>>,
     },
+    /// rfc6749 section 4.4 - the client requests an access token on its own
+    /// behalf, authenticating with its client credentials.
+    ClientCredentials {
+        #[serde_as(as = "Option<StringWithSeparator::<SpaceSeparator, String>>")]
+        scope: Option<BTreeSet<String>>,
+    },
+    /// rfc8628 section 3.4 - the device polls for the result of the user
+    /// approving (or denying) the device authorisation.
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
+    DeviceCode { device_code: String },
+    /// rfc8693 section 2.1 - the client exchanges a token that was issued to it
+    /// for a token to a different resource server, acting on behalf of the subject.
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:token-exchange")]
+    TokenExchange {
+        subject_token: String,
+        subject_token_type: String,
+        requested_token_type: Option<String>,
+        /// The client_id of the resource server the token is for.
+        audience: Option<String>,
+        #[serde_as(as = "Option<StringWithSeparator::<SpaceSeparator, String>>")]
+        scope: Option<BTreeSet<String>>,
+    },
 }
 
 #[skip_serializing_none]
@@ -113,6 +135,34 @@ impl From<GrantTypeReq> for AccessTokenRequest {
     }
 }
 
+/// rfc8628 section 3.1 - a device requests to start the device authorisation flow.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeviceAuthorizationRequest {
+    // REQUIRED, if the client is not authenticating with the
+    //  authorization server with basic auth.
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    #[serde_as(as = "Option<StringWithSeparator::<SpaceSeparator, String>>")]
+    pub scope: Option<BTreeSet<String>>,
+}
+
+/// rfc8628 section 3.2 - the codes the device displays to the user, and then
+/// uses to poll the token endpoint.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeviceAuthorizationResponse {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: Url,
+    pub verification_uri_complete: Option<Url>,
+    // seconds.
+    pub expires_in: u32,
+    // seconds.
+    pub interval: Option<u32>,
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TokenRevokeRequest {
@@ -139,6 +189,17 @@ pub struct AccessTokenResponse {
     pub scope: Option<String>,
     /// Oidc puts the token here.
     pub id_token: Option<String>,
+    /// rfc8693 section 2.2.1 - the type of token that was issued by a token exchange.
+    pub issued_token_type: Option<String>,
+}
+
+/// rfc8693 section 4.1 - the party that has been delegated the right to act on behalf
+/// of the subject of a token. Prior actors in the delegation chain are nested.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ActorClaim {
+    pub sub: String,
+    pub act: Option<Box<ActorClaim>>,
 }
 
 #[skip_serializing_none]
@@ -165,6 +226,7 @@ pub struct AccessTokenIntrospectResponse {
     pub aud: Option<String>,
     pub iss: Option<String>,
     pub jti: Option<String>,
+    pub act: Option<ActorClaim>,
 }
 
 impl AccessTokenIntrospectResponse {
@@ -182,6 +244,7 @@ impl AccessTokenIntrospectResponse {
             aud: None,
             iss: None,
             jti: None,
+            act: None,
         }
     }
 }
@@ -211,6 +274,11 @@ pub enum GrantType {
     #[serde(rename = "authorization_code")]
     AuthorisationCode,
     Implicit,
+    ClientCredentials,
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
+    DeviceCode,
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:token-exchange")]
+    TokenExchange,
 }
 
 fn grant_types_supported_default() -> Vec<GrantType> {
@@ -293,6 +361,8 @@ pub struct OidcDiscoveryResponse {
     pub userinfo_endpoint: Option<Url>,
     pub jwks_uri: Url,
     pub registration_endpoint: Option<Url>,
+    // https://datatracker.ietf.org/doc/html/rfc8628#section-4
+    pub device_authorization_endpoint: Option<Url>,
     pub scopes_supported: Option<Vec<String>>,
     // https://datatracker.ietf.org/doc/html/rfc6749#section-3.1.1
     pub response_types_supported: Vec<ResponseType>,
@@ -364,4 +434,36 @@ mod tests {
 
         println!("{:?}", serde_json::to_string(&atr).expect("JSON failure"));
     }
+
+    #[test]
+    fn test_oauth2_access_token_req_device_code() {
+        let atr: AccessTokenRequest = serde_json::from_str(
+            r#"{"grant_type":"urn:ietf:params:oauth:grant-type:device_code","device_code":"abcd","client_id":"demo"}"#,
+        )
+        .expect("Failed to decode device code request");
+
+        assert!(matches!(
+            atr.grant_type,
+            GrantTypeReq::DeviceCode { device_code } if device_code == "abcd"
+        ));
+        assert!(atr.client_id.as_deref() == Some("demo"));
+    }
+
+    #[test]
+    fn test_oauth2_access_token_req_token_exchange() {
+        let atr: AccessTokenRequest = serde_json::from_str(
+            r#"{"grant_type":"urn:ietf:params:oauth:grant-type:token-exchange","subject_token":"abcd","subject_token_type":"urn:ietf:params:oauth:token-type:access_token","audience":"downstream","scope":"read write"}"#,
+        )
+        .expect("Failed to decode token exchange request");
+
+        assert!(matches!(
+            atr.grant_type,
+            GrantTypeReq::TokenExchange {
+                subject_token,
+                audience: Some(audience),
+                scope: Some(scope),
+                ..
+            } if subject_token == "abcd" && audience == "downstream" && scope.len() == 2
+        ));
+    }
 }
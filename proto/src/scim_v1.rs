@@ -8,8 +8,8 @@ pub use scim_proto::user::MultiValueAttr;
 use scim_proto::*;
 
 use crate::constants::{
-    ATTR_DESCRIPTION, ATTR_DISPLAYNAME, ATTR_GIDNUMBER, ATTR_LOGINSHELL, ATTR_MAIL, ATTR_MEMBER,
-    ATTR_NAME, ATTR_SSH_PUBLICKEY,
+    ATTR_ACCOUNT_EXPIRE, ATTR_ACCOUNT_VALID_FROM, ATTR_DESCRIPTION, ATTR_DISPLAYNAME,
+    ATTR_GIDNUMBER, ATTR_LOGINSHELL, ATTR_MAIL, ATTR_MEMBER, ATTR_NAME, ATTR_SSH_PUBLICKEY,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -53,6 +53,40 @@ impl ScimSyncRequest {
     }
 }
 
+/// Changes made in Kanidm to synchronised entries, that can be written back to the
+/// external source. Only attributes that the sync agreement has yielded authority over
+/// are included, as all other attributes are controlled by the external source.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScimSyncWriteback {
+    /// The cookie of the last acknowledged writeback that these changes follow from.
+    pub from_cookie: Option<Base64UrlSafeData>,
+    /// The cookie to acknowledge once these changes have been written to the external source.
+    pub to_cookie: Base64UrlSafeData,
+    pub entries: Vec<ScimSyncWritebackEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScimSyncWritebackEntry {
+    pub id: Uuid,
+    pub external_id: Option<String>,
+    /// The attributes that changed, and their values. An attribute with no values
+    /// was removed.
+    pub attrs: BTreeMap<String, ScimSyncWritebackAttr>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScimSyncWritebackAttr {
+    pub values: Vec<String>,
+    pub changed: time::OffsetDateTime,
+}
+
+/// Acknowledge that changes have been written back, so that they are not sent again.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScimSyncWritebackAck {
+    pub from_cookie: Option<Base64UrlSafeData>,
+    pub to_cookie: Base64UrlSafeData,
+}
+
 pub const SCIM_ALGO: &str = "algo";
 pub const SCIM_DIGITS: &str = "digits";
 pub const SCIM_SECRET: &str = "secret";
@@ -141,6 +175,10 @@ pub struct ScimSyncPerson {
     pub login_shell: Option<String>,
     pub mail: Vec<MultiValueAttr>,
     pub ssh_publickey: Vec<ScimSshPubKey>,
+    /// An rfc3339 datetime after which the account may not authenticate.
+    pub account_expire: Option<String>,
+    /// An rfc3339 datetime before which the account may not authenticate.
+    pub account_valid_from: Option<String>,
 }
 
 // Need to allow this because clippy is broken and doesn't realise scimentry is out of crate
@@ -159,6 +197,8 @@ impl Into<ScimEntry> for ScimSyncPerson {
             login_shell,
             mail,
             ssh_publickey,
+            account_expire,
+            account_valid_from,
         } = self;
 
         let schemas = if gidnumber.is_some() {
@@ -184,6 +224,8 @@ impl Into<ScimEntry> for ScimSyncPerson {
         set_option_string!(attrs, ATTR_LOGINSHELL, login_shell);
         set_multi_complex!(attrs, ATTR_MAIL, mail);
         set_multi_complex!(attrs, ATTR_SSH_PUBLICKEY, ssh_publickey); // with the underscore
+        set_option_string!(attrs, ATTR_ACCOUNT_EXPIRE, account_expire);
+        set_option_string!(attrs, ATTR_ACCOUNT_VALID_FROM, account_valid_from);
 
         ScimEntry {
             schemas,
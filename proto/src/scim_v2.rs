@@ -0,0 +1,828 @@
+//! Types for the SCIM 2.0 provisioning interface, as described by
+//! [RFC 7643](https://www.rfc-editor.org/rfc/rfc7643) (core schema) and
+//! [RFC 7644](https://www.rfc-editor.org/rfc/rfc7644) (protocol).
+//!
+//! Unlike [scim_v1](crate::scim_v1), which is used by sync accounts to push changes from an
+//! external source, these types are used to read and write users and groups in kanidm as a
+//! SCIM service provider.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_with::skip_serializing_none;
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+pub const SCIM_SCHEMA_USER: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const SCIM_SCHEMA_GROUP: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const SCIM_SCHEMA_SERVICE_PROVIDER_CONFIG: &str =
+    "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+pub const SCIM_SCHEMA_RESOURCE_TYPE: &str = "urn:ietf:params:scim:schemas:core:2.0:ResourceType";
+pub const SCIM_SCHEMA_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Schema";
+pub const SCIM_SCHEMA_LIST_RESPONSE: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const SCIM_SCHEMA_PATCH_OP: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const SCIM_SCHEMA_ERROR: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+    pub resource_type: String,
+    pub location: Option<String>,
+    /// The weak entity tag of the resource, derived from the change id of its last
+    /// modification.
+    pub version: Option<String>,
+}
+
+/// A value of a multi-valued attribute such as `emails`, `groups` or `members`.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct ScimMultiValue {
+    pub value: String,
+    pub display: Option<String>,
+    #[serde(rename = "$ref")]
+    pub ref_: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub primary: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct ScimName {
+    pub formatted: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    pub id: Option<Uuid>,
+    pub external_id: Option<String>,
+    pub user_name: Option<String>,
+    pub display_name: Option<String>,
+    pub name: Option<ScimName>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emails: Vec<ScimMultiValue>,
+    /// The groups this user is a member of. This is read only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<ScimMultiValue>,
+    pub active: Option<bool>,
+    pub meta: Option<ScimMeta>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroup {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    pub id: Option<Uuid>,
+    pub external_id: Option<String>,
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<ScimMultiValue>,
+    pub meta: Option<ScimMeta>,
+}
+
+/// A user or group. As every attribute of a resource is optional this can not be
+/// deserialised unambiguously, so requests are decoded by the endpoint's resource type.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ScimResource {
+    User(ScimUser),
+    Group(ScimGroup),
+}
+
+impl ScimResource {
+    pub fn id(&self) -> Option<Uuid> {
+        match self {
+            ScimResource::User(u) => u.id,
+            ScimResource::Group(g) => g.id,
+        }
+    }
+
+    pub fn meta(&self) -> Option<&ScimMeta> {
+        match self {
+            ScimResource::User(u) => u.meta.as_ref(),
+            ScimResource::Group(g) => g.meta.as_ref(),
+        }
+    }
+}
+
+/// The query parameters of a list request. Sorting and attribute projection are not
+/// supported, and are ignored if supplied.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListRequest {
+    pub filter: Option<String>,
+    pub start_index: Option<usize>,
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListResponse<T> {
+    pub schemas: Vec<String>,
+    pub total_results: usize,
+    pub start_index: usize,
+    pub items_per_page: usize,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ScimPatchRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimPatchOperation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ScimPatchOperation {
+    pub op: ScimPatchOp,
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScimPatchOp {
+    Add,
+    Remove,
+    Replace,
+}
+
+impl FromStr for ScimPatchOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Some clients capitalise the operation, so this is matched case insensitively.
+        match s.to_lowercase().as_str() {
+            "add" => Ok(ScimPatchOp::Add),
+            "remove" => Ok(ScimPatchOp::Remove),
+            "replace" => Ok(ScimPatchOp::Replace),
+            _ => Err(format!("invalid patch operation {}", s)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ScimPatchOp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        ScimPatchOp::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimErrorResponse {
+    pub schemas: Vec<String>,
+    /// The HTTP status code, which RFC 7644 requires to be a string.
+    pub status: String,
+    pub scim_type: Option<String>,
+    pub detail: Option<String>,
+}
+
+impl ScimErrorResponse {
+    pub fn new(status: u16, scim_type: Option<&str>, detail: Option<String>) -> Self {
+        ScimErrorResponse {
+            schemas: vec![SCIM_SCHEMA_ERROR.to_string()],
+            status: status.to_string(),
+            scim_type: scim_type.map(str::to_string),
+            detail,
+        }
+    }
+}
+
+// ==== Discovery ====
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ScimSupported {
+    pub supported: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimBulkSupported {
+    pub supported: bool,
+    pub max_operations: usize,
+    pub max_payload_size: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimFilterSupported {
+    pub supported: bool,
+    pub max_results: usize,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimAuthenticationScheme {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub name: String,
+    pub description: String,
+    pub spec_uri: Option<String>,
+    pub documentation_uri: Option<String>,
+    pub primary: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimServiceProviderConfig {
+    pub schemas: Vec<String>,
+    pub documentation_uri: Option<String>,
+    pub patch: ScimSupported,
+    pub bulk: ScimBulkSupported,
+    pub filter: ScimFilterSupported,
+    pub change_password: ScimSupported,
+    pub sort: ScimSupported,
+    pub etag: ScimSupported,
+    pub authentication_schemes: Vec<ScimAuthenticationScheme>,
+    pub meta: Option<ScimMeta>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimResourceTypeDefinition {
+    pub schemas: Vec<String>,
+    pub id: String,
+    pub name: String,
+    pub endpoint: String,
+    pub description: Option<String>,
+    pub schema: String,
+    pub meta: Option<ScimMeta>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimSchemaAttribute {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub multi_valued: bool,
+    pub description: Option<String>,
+    pub required: bool,
+    pub case_exact: bool,
+    pub mutability: String,
+    pub returned: String,
+    pub uniqueness: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_attributes: Vec<ScimSchemaAttribute>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimSchemaDefinition {
+    pub schemas: Vec<String>,
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub attributes: Vec<ScimSchemaAttribute>,
+    pub meta: Option<ScimMeta>,
+}
+
+// ==== Filters ====
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScimFilterError {
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    UnterminatedString,
+    InvalidString,
+    InvalidAttributePath(String),
+    InvalidOperator(String),
+    InvalidValue(String),
+}
+
+impl fmt::Display for ScimFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScimFilterError::UnexpectedEnd => write!(f, "unexpected end of filter"),
+            ScimFilterError::UnexpectedToken(t) => write!(f, "unexpected token {}", t),
+            ScimFilterError::UnterminatedString => write!(f, "unterminated string"),
+            ScimFilterError::InvalidString => write!(f, "invalid string"),
+            ScimFilterError::InvalidAttributePath(p) => write!(f, "invalid attribute path {}", p),
+            ScimFilterError::InvalidOperator(o) => write!(f, "invalid operator {}", o),
+            ScimFilterError::InvalidValue(v) => write!(f, "invalid comparison value {}", v),
+        }
+    }
+}
+
+/// A path to an attribute, optionally qualified by the schema URN, such as `userName`,
+/// `name.formatted` or `urn:ietf:params:scim:schemas:core:2.0:User:emails.value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScimAttrPath {
+    pub urn: Option<String>,
+    pub attr: String,
+    pub sub_attr: Option<String>,
+}
+
+impl ScimAttrPath {
+    pub fn new(attr: &str, sub_attr: Option<&str>) -> Self {
+        ScimAttrPath {
+            urn: None,
+            attr: attr.to_string(),
+            sub_attr: sub_attr.map(str::to_string),
+        }
+    }
+}
+
+fn is_attr_name(s: &str) -> bool {
+    // $ref is the only name that does not begin with an alphabetic character.
+    s == "$ref"
+        || (s.starts_with(|c: char| c.is_ascii_alphabetic())
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+}
+
+impl FromStr for ScimAttrPath {
+    type Err = ScimFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The attribute name can not contain a ':', so the urn is everything before the last one.
+        let has_urn = s
+            .get(..4)
+            .map(|prefix| prefix.eq_ignore_ascii_case("urn:"))
+            .unwrap_or(false);
+        let (urn, path) = if has_urn {
+            s.rsplit_once(':')
+                .map(|(urn, path)| (Some(urn.to_string()), path))
+                .ok_or_else(|| ScimFilterError::InvalidAttributePath(s.to_string()))?
+        } else {
+            (None, s)
+        };
+
+        let (attr, sub_attr) = match path.split_once('.') {
+            Some((attr, sub_attr)) => (attr, Some(sub_attr)),
+            None => (path, None),
+        };
+
+        if !is_attr_name(attr) || !sub_attr.map(is_attr_name).unwrap_or(true) {
+            return Err(ScimFilterError::InvalidAttributePath(s.to_string()));
+        }
+
+        Ok(ScimAttrPath {
+            urn,
+            attr: attr.to_string(),
+            sub_attr: sub_attr.map(str::to_string),
+        })
+    }
+}
+
+impl fmt::Display for ScimAttrPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(urn) = &self.urn {
+            write!(f, "{}:", urn)?;
+        }
+        write!(f, "{}", self.attr)?;
+        if let Some(sub_attr) = &self.sub_attr {
+            write!(f, ".{}", sub_attr)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScimCompareOp {
+    Equal,
+    NotEqual,
+    Contains,
+    StartsWith,
+    EndsWith,
+    GreaterThan,
+    GreaterOrEqual,
+    LessThan,
+    LessOrEqual,
+}
+
+impl FromStr for ScimCompareOp {
+    type Err = ScimFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "eq" => Ok(ScimCompareOp::Equal),
+            "ne" => Ok(ScimCompareOp::NotEqual),
+            "co" => Ok(ScimCompareOp::Contains),
+            "sw" => Ok(ScimCompareOp::StartsWith),
+            "ew" => Ok(ScimCompareOp::EndsWith),
+            "gt" => Ok(ScimCompareOp::GreaterThan),
+            "ge" => Ok(ScimCompareOp::GreaterOrEqual),
+            "lt" => Ok(ScimCompareOp::LessThan),
+            "le" => Ok(ScimCompareOp::LessOrEqual),
+            _ => Err(ScimFilterError::InvalidOperator(s.to_string())),
+        }
+    }
+}
+
+/// A filter as defined by RFC 7644 section 3.4.2.2. Comparison values are held in their
+/// string form, so `true`, `42` and `"42"` are all compared as the same value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScimFilter {
+    Present(ScimAttrPath),
+    Compare(ScimAttrPath, ScimCompareOp, String),
+    /// A filter applied to the values of a complex attribute, such as
+    /// `emails[value ew "example.com"]`.
+    ValuePath(ScimAttrPath, Box<ScimFilter>),
+    And(Vec<ScimFilter>),
+    Or(Vec<ScimFilter>),
+    Not(Box<ScimFilter>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Word(String),
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::OpenBracket => write!(f, "["),
+            Token::CloseBracket => write!(f, "]"),
+            Token::Word(w) => write!(f, "{}", w),
+            Token::Quoted(q) => write!(f, "{:?}", q),
+        }
+    }
+}
+
+fn tokenise(s: &str) -> Result<Vec<Token>, ScimFilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '[' => tokens.push(Token::OpenBracket),
+            ']' => tokens.push(Token::CloseBracket),
+            '"' => {
+                // Find the end of the string, and then let serde decode the escapes.
+                let mut escaped = false;
+                let mut end = None;
+                for (jdx, c) in chars.by_ref() {
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => {
+                            end = Some(jdx);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                let end = end.ok_or(ScimFilterError::UnterminatedString)?;
+                let value: String = serde_json::from_str(&s[idx..=end])
+                    .map_err(|_| ScimFilterError::InvalidString)?;
+                tokens.push(Token::Quoted(value));
+            }
+            c => {
+                let mut end = idx + c.len_utf8();
+                while let Some((jdx, c)) = chars.peek().copied() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '"') {
+                        break;
+                    }
+                    end = jdx + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push(Token::Word(s[idx..end].to_string()));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct FilterParser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl FilterParser {
+    fn new(tokens: Vec<Token>) -> Self {
+        FilterParser {
+            tokens: tokens.into_iter().peekable(),
+        }
+    }
+
+    fn next(&mut self) -> Result<Token, ScimFilterError> {
+        self.tokens.next().ok_or(ScimFilterError::UnexpectedEnd)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ScimFilterError> {
+        match self.next()? {
+            t if t == expected => Ok(()),
+            t => Err(ScimFilterError::UnexpectedToken(t.to_string())),
+        }
+    }
+
+    fn next_is_keyword(&mut self, keyword: &str) -> bool {
+        matches!(self.tokens.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<ScimFilter, ScimFilterError> {
+        let mut terms = vec![self.parse_and()?];
+        while self.next_is_keyword("or") {
+            self.tokens.next();
+            terms.push(self.parse_and()?);
+        }
+        Ok(match terms.pop() {
+            Some(term) if terms.is_empty() => term,
+            Some(term) => {
+                terms.push(term);
+                ScimFilter::Or(terms)
+            }
+            None => return Err(ScimFilterError::UnexpectedEnd),
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<ScimFilter, ScimFilterError> {
+        let mut terms = vec![self.parse_term()?];
+        while self.next_is_keyword("and") {
+            self.tokens.next();
+            terms.push(self.parse_term()?);
+        }
+        Ok(match terms.pop() {
+            Some(term) if terms.is_empty() => term,
+            Some(term) => {
+                terms.push(term);
+                ScimFilter::And(terms)
+            }
+            None => return Err(ScimFilterError::UnexpectedEnd),
+        })
+    }
+
+    fn parse_term(&mut self) -> Result<ScimFilter, ScimFilterError> {
+        match self.next()? {
+            Token::Open => {
+                let f = self.parse_or()?;
+                self.expect(Token::Close)?;
+                Ok(f)
+            }
+            Token::Word(w)
+                if w.eq_ignore_ascii_case("not") && self.tokens.peek() == Some(&Token::Open) =>
+            {
+                self.tokens.next();
+                let f = self.parse_or()?;
+                self.expect(Token::Close)?;
+                Ok(ScimFilter::Not(Box::new(f)))
+            }
+            Token::Word(w) => self.parse_attr_exp(&w),
+            t => Err(ScimFilterError::UnexpectedToken(t.to_string())),
+        }
+    }
+
+    fn parse_attr_exp(&mut self, path: &str) -> Result<ScimFilter, ScimFilterError> {
+        let path = ScimAttrPath::from_str(path)?;
+        match self.next()? {
+            Token::OpenBracket => {
+                let f = self.parse_or()?;
+                self.expect(Token::CloseBracket)?;
+                Ok(ScimFilter::ValuePath(path, Box::new(f)))
+            }
+            Token::Word(op) if op.eq_ignore_ascii_case("pr") => Ok(ScimFilter::Present(path)),
+            Token::Word(op) => {
+                let op = ScimCompareOp::from_str(&op)?;
+                let value = match self.next()? {
+                    Token::Quoted(v) => v,
+                    Token::Word(v) if v == "true" || v == "false" || v.parse::<f64>().is_ok() => v,
+                    // This includes null, which is the same as "not present".
+                    Token::Word(v) => return Err(ScimFilterError::InvalidValue(v)),
+                    t => return Err(ScimFilterError::UnexpectedToken(t.to_string())),
+                };
+                Ok(ScimFilter::Compare(path, op, value))
+            }
+            t => Err(ScimFilterError::UnexpectedToken(t.to_string())),
+        }
+    }
+
+    fn end(&mut self) -> Result<(), ScimFilterError> {
+        match self.tokens.next() {
+            None => Ok(()),
+            Some(t) => Err(ScimFilterError::UnexpectedToken(t.to_string())),
+        }
+    }
+}
+
+impl FromStr for ScimFilter {
+    type Err = ScimFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = FilterParser::new(tokenise(s)?);
+        let f = parser.parse_or()?;
+        parser.end()?;
+        Ok(f)
+    }
+}
+
+/// The target of a patch operation, such as `displayName` or `members[value eq "..."]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScimPatchPath {
+    pub attr: ScimAttrPath,
+    pub value_filter: Option<ScimFilter>,
+}
+
+impl FromStr for ScimPatchPath {
+    type Err = ScimFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = FilterParser::new(tokenise(s)?);
+
+        let mut attr = match parser.next()? {
+            Token::Word(w) => ScimAttrPath::from_str(&w)?,
+            t => return Err(ScimFilterError::UnexpectedToken(t.to_string())),
+        };
+
+        let value_filter = if parser.tokens.peek() == Some(&Token::OpenBracket) {
+            parser.tokens.next();
+            let f = parser.parse_or()?;
+            parser.expect(Token::CloseBracket)?;
+
+            // A sub attribute may follow the value filter, such as `emails[type eq "work"].value`.
+            if let Some(Token::Word(w)) = parser.tokens.peek() {
+                match w.strip_prefix('.') {
+                    Some(sub_attr) if attr.sub_attr.is_none() && is_attr_name(sub_attr) => {
+                        attr.sub_attr = Some(sub_attr.to_string());
+                        parser.tokens.next();
+                    }
+                    _ => return Err(ScimFilterError::InvalidAttributePath(s.to_string())),
+                }
+            }
+            Some(f)
+        } else {
+            None
+        };
+
+        parser.end()?;
+        Ok(ScimPatchPath { attr, value_filter })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scim_filter_parse() {
+        assert_eq!(
+            ScimFilter::from_str(r#"userName eq "bjensen""#),
+            Ok(ScimFilter::Compare(
+                ScimAttrPath::new("userName", None),
+                ScimCompareOp::Equal,
+                "bjensen".to_string()
+            ))
+        );
+
+        assert_eq!(
+            ScimFilter::from_str("urn:ietf:params:scim:schemas:core:2.0:User:name.formatted PR"),
+            Ok(ScimFilter::Present(ScimAttrPath {
+                urn: Some(SCIM_SCHEMA_USER.to_string()),
+                attr: "name".to_string(),
+                sub_attr: Some("formatted".to_string()),
+            }))
+        );
+
+        // And binds tighter than or.
+        assert_eq!(
+            ScimFilter::from_str(
+                r#"title pr or userType eq "Employee" and not (emails co "example.org")"#
+            ),
+            Ok(ScimFilter::Or(vec![
+                ScimFilter::Present(ScimAttrPath::new("title", None)),
+                ScimFilter::And(vec![
+                    ScimFilter::Compare(
+                        ScimAttrPath::new("userType", None),
+                        ScimCompareOp::Equal,
+                        "Employee".to_string()
+                    ),
+                    ScimFilter::Not(Box::new(ScimFilter::Compare(
+                        ScimAttrPath::new("emails", None),
+                        ScimCompareOp::Contains,
+                        "example.org".to_string()
+                    ))),
+                ]),
+            ]))
+        );
+
+        assert_eq!(
+            ScimFilter::from_str(r#"emails[type eq "work" and value ew "\"x\".com"]"#),
+            Ok(ScimFilter::ValuePath(
+                ScimAttrPath::new("emails", None),
+                Box::new(ScimFilter::And(vec![
+                    ScimFilter::Compare(
+                        ScimAttrPath::new("type", None),
+                        ScimCompareOp::Equal,
+                        "work".to_string()
+                    ),
+                    ScimFilter::Compare(
+                        ScimAttrPath::new("value", None),
+                        ScimCompareOp::EndsWith,
+                        "\"x\".com".to_string()
+                    ),
+                ]))
+            ))
+        );
+
+        assert_eq!(
+            ScimFilter::from_str("active eq true"),
+            Ok(ScimFilter::Compare(
+                ScimAttrPath::new("active", None),
+                ScimCompareOp::Equal,
+                "true".to_string()
+            ))
+        );
+
+        assert_eq!(
+            ScimFilter::from_str("userName eq null"),
+            Err(ScimFilterError::InvalidValue("null".to_string()))
+        );
+        assert_eq!(
+            ScimFilter::from_str(r#"userName is "a""#),
+            Err(ScimFilterError::InvalidOperator("is".to_string()))
+        );
+        assert_eq!(
+            ScimFilter::from_str(r#"(userName eq "a""#),
+            Err(ScimFilterError::UnexpectedEnd)
+        );
+        assert_eq!(
+            ScimFilter::from_str(r#"userName eq "a"#),
+            Err(ScimFilterError::UnterminatedString)
+        );
+        assert!(ScimFilter::from_str(r#"userName eq "a" "b""#).is_err());
+    }
+
+    #[test]
+    fn test_scim_patch_path_parse() {
+        assert_eq!(
+            ScimPatchPath::from_str("displayName"),
+            Ok(ScimPatchPath {
+                attr: ScimAttrPath::new("displayName", None),
+                value_filter: None,
+            })
+        );
+
+        assert_eq!(
+            ScimPatchPath::from_str(r#"members[value eq "2819c223"]"#),
+            Ok(ScimPatchPath {
+                attr: ScimAttrPath::new("members", None),
+                value_filter: Some(ScimFilter::Compare(
+                    ScimAttrPath::new("value", None),
+                    ScimCompareOp::Equal,
+                    "2819c223".to_string()
+                )),
+            })
+        );
+
+        assert_eq!(
+            ScimPatchPath::from_str(r#"emails[type eq "work"].value"#),
+            Ok(ScimPatchPath {
+                attr: ScimAttrPath::new("emails", Some("value")),
+                value_filter: Some(ScimFilter::Compare(
+                    ScimAttrPath::new("type", None),
+                    ScimCompareOp::Equal,
+                    "work".to_string()
+                )),
+            })
+        );
+
+        assert!(ScimPatchPath::from_str("members[value eq \"a\"] extra").is_err());
+    }
+
+    #[test]
+    fn test_scim_patch_request_deserialise() {
+        let req: Result<ScimPatchRequest, _> = serde_json::from_str(
+            r#"{
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [
+                    {"op": "Replace", "path": "active", "value": false},
+                    {"op": "remove", "path": "members[value eq \"abc\"]"}
+                ]
+            }"#,
+        );
+
+        assert!(matches!(
+            req.as_ref().map(|r| r.operations.iter().map(|o| o.op).collect::<Vec<_>>()),
+            Ok(ops) if ops == [ScimPatchOp::Replace, ScimPatchOp::Remove]
+        ));
+    }
+}
@@ -15,7 +15,10 @@ use webauthn_rs_proto::{
     RequestChallengeResponse,
 };
 
-use crate::constants::{ATTR_GROUP, ATTR_LDAP_SSHPUBLICKEY};
+use crate::constants::{
+    ATTR_AUTOMOUNT_INFORMATION, ATTR_AUTOMOUNT_KEY, ATTR_GROUP, ATTR_HBAC_SERVICE, ATTR_HBAC_USER,
+    ATTR_LDAP_SSHPUBLICKEY, ATTR_SUDO_COMMAND, ATTR_SUDO_NOPASSWD, ATTR_SUDO_RUNAS, ATTR_SUDO_USER,
+};
 
 // These proto implementations are here because they have public definitions
 
@@ -598,6 +601,20 @@ pub struct GroupUnixExtend {
     pub gidnumber: Option<u32>,
 }
 
+/// A range of subordinate ids that an account may map into user namespaces. The same
+/// range is used for both subordinate uids and gids.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct UnixSubidRange {
+    pub start: u32,
+    pub count: u32,
+}
+
+impl fmt::Display for UnixSubidRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start, self.count)
+    }
+}
+
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UnixUserToken {
@@ -612,6 +629,8 @@ pub struct UnixUserToken {
     // The default value of bool is false.
     #[serde(default)]
     pub valid: bool,
+    #[serde(default)]
+    pub subid_range: Option<UnixSubidRange>,
 }
 
 impl fmt::Display for UnixUserToken {
@@ -625,6 +644,9 @@ impl fmt::Display for UnixUserToken {
             Some(s) => writeln!(f, "shell: {}", s)?,
             None => writeln!(f, "shell: <none>")?,
         }
+        if let Some(range) = &self.subid_range {
+            writeln!(f, "subid_range: {}", range)?;
+        }
         self.sshkeys
             .iter()
             .try_for_each(|s| writeln!(f, "{}: {}", ATTR_LDAP_SSHPUBLICKEY, s))?;
@@ -634,6 +656,97 @@ impl fmt::Display for UnixUserToken {
     }
 }
 
+/// A sudo rule as it is provided to the host that it applies on, so that the host can decide
+/// if an account may run a command with elevated privileges. Only the rules that name the
+/// requesting host, or a group it is a member of, are provided.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct UnixSudoRule {
+    pub name: String,
+    pub uuid: Uuid,
+    /// The uuids of the accounts and groups this rule applies to.
+    pub users: Vec<Uuid>,
+    /// The commands that may be run. "ALL" permits any command.
+    pub commands: Vec<String>,
+    /// The accounts that commands may be run as. If empty, only root is permitted.
+    pub runas: Vec<String>,
+    /// If false, the account must reauthenticate before the command is run.
+    #[serde(default)]
+    pub nopasswd: bool,
+}
+
+impl fmt::Display for UnixSudoRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---")?;
+        writeln!(f, "name: {}", self.name)?;
+        writeln!(f, "uuid: {}", self.uuid)?;
+        self.users
+            .iter()
+            .try_for_each(|u| writeln!(f, "{}: {}", ATTR_SUDO_USER, u))?;
+        self.commands
+            .iter()
+            .try_for_each(|c| writeln!(f, "{}: {}", ATTR_SUDO_COMMAND, c))?;
+        self.runas
+            .iter()
+            .try_for_each(|r| writeln!(f, "{}: {}", ATTR_SUDO_RUNAS, r))?;
+        writeln!(f, "{}: {}", ATTR_SUDO_NOPASSWD, self.nopasswd)
+    }
+}
+
+/// A host access rule as it is provided to the host that it applies on. Only the rules
+/// that name the requesting host, or a group it is a member of, are provided.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct UnixHbacRule {
+    pub name: String,
+    pub uuid: Uuid,
+    /// The uuids of the accounts and groups this rule applies to.
+    pub users: Vec<Uuid>,
+    /// The PAM services this rule allows. "ALL" matches any service.
+    pub services: Vec<String>,
+}
+
+impl fmt::Display for UnixHbacRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---")?;
+        writeln!(f, "name: {}", self.name)?;
+        writeln!(f, "uuid: {}", self.uuid)?;
+        self.users
+            .iter()
+            .try_for_each(|u| writeln!(f, "{}: {}", ATTR_HBAC_USER, u))?;
+        self.services
+            .iter()
+            .try_for_each(|s| writeln!(f, "{}: {}", ATTR_HBAC_SERVICE, s))
+    }
+}
+
+/// An entry of an automount map, such as the home directory of a user.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct UnixAutomountEntry {
+    /// The key that is looked up, such as a mount point or "*".
+    pub key: String,
+    /// The mount options and location, as it would appear in a map file.
+    pub information: String,
+}
+
+/// An automount map with its entries, as it is provided to unix hosts.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct UnixAutomountMap {
+    pub name: String,
+    pub uuid: Uuid,
+    pub entries: Vec<UnixAutomountEntry>,
+}
+
+impl fmt::Display for UnixAutomountMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---")?;
+        writeln!(f, "name: {}", self.name)?;
+        writeln!(f, "uuid: {}", self.uuid)?;
+        self.entries.iter().try_for_each(|e| {
+            writeln!(f, "{}: {}", ATTR_AUTOMOUNT_KEY, e.key)?;
+            writeln!(f, "  {}: {}", ATTR_AUTOMOUNT_INFORMATION, e.information)
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct AccountUnixExtend {
@@ -644,6 +757,15 @@ pub struct AccountUnixExtend {
     pub shell: Option<String>,
 }
 
+/// An account changing its own unix password. The current unix password authenticates the
+/// change, so no session of the account is required.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UnixPasswordChangeRequest {
+    pub current: String,
+    pub new: String,
+}
+
 /*
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AccountOrgPersonExtend {
@@ -1211,6 +1333,323 @@ impl WhoamiResponse {
     }
 }
 
+/// The value of an attribute on an entry involved in a replication conflict, and the change
+/// that last wrote it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReplicationConflictAttr {
+    pub values: Vec<String>,
+    pub cid: String,
+    /// The server that originated the change.
+    pub server_uuid: Uuid,
+}
+
+/// The replication state of an entry involved in a conflict.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReplicationConflictEntry {
+    pub uuid: Uuid,
+    /// The change that created this entry.
+    pub cid: String,
+    /// The server that created this entry.
+    pub server_uuid: Uuid,
+    pub attrs: BTreeMap<String, ReplicationConflictAttr>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReplicationConflict {
+    /// The entry that was moved aside into the conflict state.
+    pub conflict: ReplicationConflictEntry,
+    /// The entries that the conflict entry was in conflict with.
+    pub source_uuids: Vec<Uuid>,
+    /// The entries from source_uuids that are still live.
+    pub live: Vec<ReplicationConflictEntry>,
+}
+
+impl fmt::Display for ReplicationConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---")?;
+        writeln!(f, "conflict: {}", self.conflict.uuid)?;
+        for source_uuid in self.source_uuids.iter() {
+            writeln!(f, "source_uuid: {}", source_uuid)?;
+        }
+
+        let entries: Vec<_> = std::iter::once(&self.conflict)
+            .chain(self.live.iter())
+            .collect();
+
+        for entry in entries.iter() {
+            writeln!(
+                f,
+                "entry: {} created by {} ({})",
+                entry.uuid, entry.server_uuid, entry.cid
+            )?;
+        }
+
+        let attrs: BTreeSet<&String> = entries
+            .iter()
+            .flat_map(|entry| entry.attrs.keys())
+            .collect();
+
+        for attr in attrs {
+            writeln!(f, "{}:", attr)?;
+            for entry in entries.iter() {
+                match entry.attrs.get(attr) {
+                    Some(state) => writeln!(
+                        f,
+                        "  {} (changed by {}): {}",
+                        entry.uuid,
+                        state.server_uuid,
+                        state.values.join(", ")
+                    )?,
+                    None => writeln!(f, "  {}: <absent>", entry.uuid)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How an administrator has chosen to resolve a replication conflict.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplicationConflictResolution {
+    /// The live entry wins, and the conflict entry is discarded.
+    Discard,
+    /// The conflict entry wins, and its attributes replace those of the live entry it
+    /// conflicted with. The conflict entry is then discarded.
+    Accept,
+    /// The named attributes of the conflict entry replace those of the live entry it
+    /// conflicted with. The conflict entry is then discarded.
+    Merge { attrs: Vec<String> },
+    /// The conflict entry is returned to a live state as an entry in its own right.
+    Revive,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessRequestState {
+    Pending,
+    Approved,
+    Denied,
+}
+
+impl AccessRequestState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessRequestState::Pending => "pending",
+            AccessRequestState::Approved => "approved",
+            AccessRequestState::Denied => "denied",
+        }
+    }
+}
+
+impl fmt::Display for AccessRequestState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AccessRequestState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(AccessRequestState::Pending),
+            "approved" => Ok(AccessRequestState::Approved),
+            "denied" => Ok(AccessRequestState::Denied),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A request by an account to be granted membership of a group.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessRequest {
+    pub uuid: Uuid,
+    /// The spn of the group that membership is requested of.
+    pub group: String,
+    /// The spn of the account that made the request.
+    pub requester: String,
+    pub justification: String,
+    pub state: AccessRequestState,
+    /// The spn of the account that approved or denied the request.
+    pub decided_by: Option<String>,
+    /// If the account viewing this request is able to approve or deny it.
+    pub can_decide: bool,
+}
+
+impl fmt::Display for AccessRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---")?;
+        writeln!(f, "uuid: {}", self.uuid)?;
+        writeln!(f, "group: {}", self.group)?;
+        writeln!(f, "requester: {}", self.requester)?;
+        writeln!(f, "justification: {}", self.justification)?;
+        writeln!(f, "state: {}", self.state)?;
+        if let Some(decided_by) = &self.decided_by {
+            writeln!(f, "decided_by: {}", decided_by)?;
+        }
+        if self.can_decide {
+            writeln!(f, "can_decide: true")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessRequestCreate {
+    pub justification: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessReviewState {
+    Open,
+    Closed,
+}
+
+impl AccessReviewState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessReviewState::Open => "open",
+            AccessReviewState::Closed => "closed",
+        }
+    }
+}
+
+impl fmt::Display for AccessReviewState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AccessReviewState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(AccessReviewState::Open),
+            "closed" => Ok(AccessReviewState::Closed),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessReviewDecision {
+    Pending,
+    Keep,
+    Remove,
+}
+
+impl AccessReviewDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessReviewDecision::Pending => "pending",
+            AccessReviewDecision::Keep => "keep",
+            AccessReviewDecision::Remove => "remove",
+        }
+    }
+}
+
+impl fmt::Display for AccessReviewDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AccessReviewDecision {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(AccessReviewDecision::Pending),
+            "keep" => Ok(AccessReviewDecision::Keep),
+            "remove" => Ok(AccessReviewDecision::Remove),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A campaign to review the memberships of a set of groups.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessReview {
+    pub uuid: Uuid,
+    pub name: String,
+    /// The rfc3339 time at which the review closes.
+    pub expiry: String,
+    pub state: AccessReviewState,
+    /// The number of review items visible to this account that are yet to be decided.
+    pub pending: usize,
+}
+
+impl fmt::Display for AccessReview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---")?;
+        writeln!(f, "uuid: {}", self.uuid)?;
+        writeln!(f, "name: {}", self.name)?;
+        writeln!(f, "expiry: {}", self.expiry)?;
+        writeln!(f, "state: {}", self.state)?;
+        writeln!(f, "pending: {}", self.pending)
+    }
+}
+
+/// The review of a single membership of a group.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessReviewItem {
+    pub uuid: Uuid,
+    /// The spn of the group.
+    pub group: String,
+    /// The spn of the member.
+    pub member: String,
+    /// The spns of the accounts and groups assigned to review this membership.
+    pub reviewers: Vec<String>,
+    pub decision: AccessReviewDecision,
+    /// The spn of the account that decided this item.
+    pub decided_by: Option<String>,
+    /// If the account viewing this item is able to decide it.
+    pub can_decide: bool,
+}
+
+impl fmt::Display for AccessReviewItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---")?;
+        writeln!(f, "uuid: {}", self.uuid)?;
+        writeln!(f, "group: {}", self.group)?;
+        writeln!(f, "member: {}", self.member)?;
+        writeln!(f, "reviewers: {}", self.reviewers.join(", "))?;
+        writeln!(f, "decision: {}", self.decision)?;
+        if let Some(decided_by) = &self.decided_by {
+            writeln!(f, "decided_by: {}", decided_by)?;
+        }
+        if self.can_decide {
+            writeln!(f, "can_decide: true")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessReviewReport {
+    pub review: AccessReview,
+    pub items: Vec<AccessReviewItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessReviewCreate {
+    pub name: String,
+    /// Selects the groups whose memberships are reviewed.
+    pub filter: Filter,
+    /// The rfc3339 time at which the review closes.
+    pub expiry: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessReviewDecide {
+    pub item: Uuid,
+    pub decision: AccessReviewDecision,
+}
+
 // Simple string value provision.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SingleStringRequest {
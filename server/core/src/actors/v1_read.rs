@@ -6,9 +6,11 @@ use std::sync::Arc;
 
 use kanidm_proto::internal::{AppLink, IdentifyUserRequest, IdentifyUserResponse, ImageValue};
 use kanidm_proto::v1::{
-    ApiToken, AuthIssueSession, AuthRequest, BackupCodesView, CURequest, CUSessionToken, CUStatus,
-    CredentialStatus, Entry as ProtoEntry, OperationError, RadiusAuthToken, SearchRequest,
-    SearchResponse, UatStatus, UnixGroupToken, UnixUserToken, UserAuthToken, WhoamiResponse,
+    AccessRequest, AccessReview, AccessReviewReport, ApiToken, AuthIssueSession, AuthRequest,
+    BackupCodesView, CURequest, CUSessionToken, CUStatus, CredentialStatus, Entry as ProtoEntry,
+    OperationError, RadiusAuthToken, ReplicationConflict, SearchRequest, SearchResponse, UatStatus,
+    UnixAutomountMap, UnixGroupToken, UnixHbacRule, UnixSudoRule, UnixUserToken, UserAuthToken,
+    WhoamiResponse,
 };
 use kanidmd_lib::idm::identityverification::{
     IdentifyUserDisplayCodeEvent, IdentifyUserStartEvent, IdentifyUserSubmitCodeEvent,
@@ -29,7 +31,7 @@ use kanidmd_lib::{
         AuthEvent, AuthResult, CredentialStatusEvent, RadiusAuthTokenEvent, ReadBackupCodeEvent,
         UnixGroupTokenEvent, UnixUserAuthEvent, UnixUserTokenEvent,
     },
-    idm::ldap::{LdapBoundToken, LdapResponseState, LdapServer},
+    idm::ldap::{LdapBoundToken, LdapResponseState, LdapServer, LdapWriteRequest},
     idm::oauth2::{
         AccessTokenIntrospectRequest, AccessTokenIntrospectResponse, AuthorisationRequest,
         AuthoriseResponse, JwkKeySet, Oauth2Error, OidcDiscoveryResponse, OidcToken,
@@ -484,6 +486,105 @@ impl QueryServerReadV1 {
         }
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_repl_conflict_list(
+        &self,
+        uat: Option<String>,
+        eventid: Uuid,
+    ) -> Result<Vec<ReplicationConflict>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_read.qs_read.repl_conflict_list(&ident)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_request_list(
+        &self,
+        uat: Option<String>,
+        eventid: Uuid,
+    ) -> Result<Vec<AccessRequest>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_read.access_request_list(&ident)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_review_list(
+        &self,
+        uat: Option<String>,
+        eventid: Uuid,
+    ) -> Result<Vec<AccessReview>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_read.access_review_list(&ident)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_review_report(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        eventid: Uuid,
+    ) -> Result<AccessReviewReport, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        let review = idms_prox_read
+            .qs_read
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        idms_prox_read.access_review_report(&ident, review)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_repl_conflict_get(
+        &self,
+        uat: Option<String>,
+        id: String,
+        eventid: Uuid,
+    ) -> Result<ReplicationConflict, OperationError> {
+        let target = Uuid::parse_str(&id).map_err(|_| {
+            request_error!(%id, "replication conflict id is not a uuid");
+            OperationError::NoMatchingEntries
+        })?;
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_read.qs_read.repl_conflict_get(&ident, target)
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -734,6 +835,72 @@ impl QueryServerReadV1 {
         idms_prox_read.get_unixgrouptoken(&rate)
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_internalunixsudoruleread(
+        &self,
+        uat: Option<String>,
+        eventid: Uuid,
+    ) -> Result<Vec<UnixSudoRule>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        idms_prox_read.list_unix_sudo_rules(&ident)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_internalunixhbacruleread(
+        &self,
+        uat: Option<String>,
+        eventid: Uuid,
+    ) -> Result<Vec<UnixHbacRule>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        idms_prox_read.list_unix_hbac_rules(&ident)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_internalunixautomountmapread(
+        &self,
+        uat: Option<String>,
+        eventid: Uuid,
+    ) -> Result<Vec<UnixAutomountMap>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        idms_prox_read.list_unix_automount_maps(&ident)
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1350,6 +1517,34 @@ impl QueryServerReadV1 {
         idms_prox_read.check_oauth2_authorisation(&ident, &uat, &auth_req, ct)
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_device_user_code(
+        &self,
+        uat: Option<String>,
+        user_code: String,
+        eventid: Uuid,
+    ) -> Result<AuthoriseResponse, Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let (ident, uat) = idms_prox_read
+            .validate_and_parse_uat(uat.as_deref(), ct)
+            .and_then(|uat| {
+                idms_prox_read
+                    .process_uat_to_identity(&uat, ct)
+                    .map(|ident| (ident, uat))
+            })
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                Oauth2Error::AuthenticationRequired
+            })?;
+
+        idms_prox_read.check_oauth2_device_user_code(&ident, &uat, &user_code, ct)
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1506,24 +1701,40 @@ impl QueryServerReadV1 {
         eventid: Uuid,
         protomsg: LdapMsg,
         uat: Option<LdapBoundToken>,
+        ip_addr: IpAddr,
     ) -> Option<LdapResponseState> {
-        let res = match ServerOps::try_from(protomsg) {
-            Ok(server_op) => self
-                .ldap
-                .do_op(&self.idms, server_op, uat, eventid)
-                .await
-                .unwrap_or_else(|e| {
-                    admin_error!("do_op failed -> {:?}", e);
-                    LdapResponseState::Disconnect(DisconnectionNotice::gen(
-                        LdapResultCode::Other,
-                        format!("Internal Server Error {:?}", &eventid).as_str(),
-                    ))
-                }),
-            Err(_) => LdapResponseState::Disconnect(DisconnectionNotice::gen(
-                LdapResultCode::ProtocolError,
-                format!("Invalid Request {:?}", &eventid).as_str(),
-            )),
-        };
+        let source = Source::Ldaps(ip_addr);
+        // Controls are not retained by the conversion to a server op, so take them first.
+        let ctrl = protomsg.ctrl.clone();
+        // Writes are not server ops, so they are decoded first and anything else is
+        // handed back to be processed as a read.
+        let res = match LdapWriteRequest::try_from(protomsg) {
+            Ok(write_req) => {
+                self.ldap
+                    .do_write_op(&self.idms, write_req, uat, source)
+                    .await
+            }
+            Err(protomsg) => match ServerOps::try_from(protomsg) {
+                Ok(server_op) => {
+                    self.ldap
+                        .do_op(&self.idms, server_op, uat, &ctrl, eventid, source)
+                        .await
+                }
+                Err(_) => {
+                    return Some(LdapResponseState::Disconnect(DisconnectionNotice::gen(
+                        LdapResultCode::ProtocolError,
+                        format!("Invalid Request {:?}", &eventid).as_str(),
+                    )))
+                }
+            },
+        }
+        .unwrap_or_else(|e| {
+            admin_error!("do_op failed -> {:?}", e);
+            LdapResponseState::Disconnect(DisconnectionNotice::gen(
+                LdapResultCode::Other,
+                format!("Internal Server Error {:?}", &eventid).as_str(),
+            ))
+        });
         Some(res)
     }
 }
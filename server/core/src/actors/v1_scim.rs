@@ -4,9 +4,24 @@ use crate::{QueryServerReadV1, QueryServerWriteV1};
 use kanidmd_lib::idm::scim::{
     GenerateScimSyncTokenEvent, ScimSyncFinaliseEvent, ScimSyncTerminateEvent, ScimSyncUpdateEvent,
 };
+use kanidmd_lib::idm::scim_v2::ScimResourceType;
 use kanidmd_lib::idm::server::IdmServerTransaction;
 
-use kanidm_proto::scim_v1::{ScimSyncRequest, ScimSyncState};
+use kanidm_proto::scim_v1::{
+    ScimSyncRequest, ScimSyncState, ScimSyncWriteback, ScimSyncWritebackAck,
+};
+use kanidm_proto::scim_v2::{
+    ScimListRequest, ScimListResponse, ScimPatchRequest, ScimResource, ScimResourceTypeDefinition,
+    ScimSchemaDefinition, ScimServiceProviderConfig,
+};
+
+/// SCIM resources are only addressed by their uuid.
+fn scim_v2_parse_id(id: &str) -> Result<Uuid, OperationError> {
+    Uuid::parse_str(id).map_err(|_| {
+        request_error!(%id, "SCIM resource id is not a uuid");
+        OperationError::NoMatchingEntries
+    })
+}
 
 impl QueryServerWriteV1 {
     #[instrument(
@@ -175,6 +190,122 @@ impl QueryServerWriteV1 {
             .scim_sync_apply(&sse, &changes, ct)
             .and_then(|r| idms_prox_write.commit().map(|_| r))
     }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_sync_writeback_ack(
+        &self,
+        bearer: Option<String>,
+        ack: ScimSyncWritebackAck,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+
+        let ident =
+            idms_prox_write.validate_and_parse_sync_token_to_ident(bearer.as_deref(), ct)?;
+
+        let sse = ScimSyncUpdateEvent { ident };
+
+        idms_prox_write
+            .scim_sync_writeback_ack(&sse, &ack)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_create(
+        &self,
+        uat: Option<String>,
+        rtype: ScimResourceType,
+        resource: ScimResource,
+        eventid: Uuid,
+    ) -> Result<ScimResource, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_write
+            .scim_v2_create(&ident, rtype, &resource, ct)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_replace(
+        &self,
+        uat: Option<String>,
+        rtype: ScimResourceType,
+        id: String,
+        resource: ScimResource,
+        if_match: Option<String>,
+        eventid: Uuid,
+    ) -> Result<ScimResource, OperationError> {
+        let target = scim_v2_parse_id(&id)?;
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_write
+            .scim_v2_replace(&ident, rtype, target, &resource, if_match.as_deref(), ct)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_patch(
+        &self,
+        uat: Option<String>,
+        rtype: ScimResourceType,
+        id: String,
+        patch: ScimPatchRequest,
+        if_match: Option<String>,
+        eventid: Uuid,
+    ) -> Result<ScimResource, OperationError> {
+        let target = scim_v2_parse_id(&id)?;
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_write
+            .scim_v2_patch(&ident, rtype, target, &patch, if_match.as_deref(), ct)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_delete(
+        &self,
+        uat: Option<String>,
+        rtype: ScimResourceType,
+        id: String,
+        if_match: Option<String>,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let target = scim_v2_parse_id(&id)?;
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_write
+            .scim_v2_delete(&ident, rtype, target, if_match.as_deref(), ct)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
 }
 
 impl QueryServerReadV1 {
@@ -195,4 +326,100 @@ impl QueryServerReadV1 {
 
         idms_prox_read.scim_sync_get_state(&ident)
     }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_sync_writeback(
+        &self,
+        bearer: Option<String>,
+        eventid: Uuid,
+    ) -> Result<ScimSyncWriteback, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+
+        let ident = idms_prox_read.validate_and_parse_sync_token_to_ident(bearer.as_deref(), ct)?;
+
+        idms_prox_read.scim_sync_get_writeback(&ident)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_search(
+        &self,
+        uat: Option<String>,
+        rtype: ScimResourceType,
+        req: ScimListRequest,
+        eventid: Uuid,
+    ) -> Result<ScimListResponse<ScimResource>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_read.scim_v2_search(&ident, rtype, &req, ct)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_get(
+        &self,
+        uat: Option<String>,
+        rtype: ScimResourceType,
+        id: String,
+        eventid: Uuid,
+    ) -> Result<ScimResource, OperationError> {
+        let target = scim_v2_parse_id(&id)?;
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_read.scim_v2_get(&ident, rtype, target, ct)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_service_provider_config(
+        &self,
+        eventid: Uuid,
+    ) -> ScimServiceProviderConfig {
+        let idms_prox_read = self.idms.proxy_read().await;
+        idms_prox_read.scim_v2_service_provider_config()
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_resource_types(
+        &self,
+        eventid: Uuid,
+    ) -> ScimListResponse<ScimResourceTypeDefinition> {
+        let idms_prox_read = self.idms.proxy_read().await;
+        idms_prox_read.scim_v2_resource_types()
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_schemas(
+        &self,
+        eventid: Uuid,
+    ) -> ScimListResponse<ScimSchemaDefinition> {
+        let idms_prox_read = self.idms.proxy_read().await;
+        idms_prox_read.scim_v2_schemas()
+    }
 }
@@ -2,9 +2,10 @@ use std::{iter, sync::Arc};
 
 use kanidm_proto::internal::ImageValue;
 use kanidm_proto::v1::{
-    AccountUnixExtend, CUIntentToken, CUSessionToken, CUStatus, CreateRequest, DeleteRequest,
-    Entry as ProtoEntry, GroupUnixExtend, Modify as ProtoModify, ModifyList as ProtoModifyList,
-    ModifyRequest, OperationError,
+    AccessRequestCreate, AccessReviewCreate, AccessReviewDecide, AccountUnixExtend, CUIntentToken,
+    CUSessionToken, CUStatus, CreateRequest, DeleteRequest, Entry as ProtoEntry, GroupUnixExtend,
+    Modify as ProtoModify, ModifyList as ProtoModifyList, ModifyRequest, OperationError,
+    ReplicationConflictResolution, UnixPasswordChangeRequest,
 };
 use time::OffsetDateTime;
 use tracing::{info, instrument, span, trace, Instrument, Level};
@@ -12,8 +13,8 @@ use uuid::Uuid;
 
 use kanidmd_lib::{
     event::{
-        CreateEvent, DeleteEvent, ModifyEvent, PurgeRecycledEvent, PurgeTombstoneEvent,
-        ReviveRecycledEvent,
+        CloseExpiredAccessReviewsEvent, CreateEvent, DeleteEvent, ModifyEvent,
+        PurgeExpiredMembersEvent, PurgeRecycledEvent, PurgeTombstoneEvent, ReviveRecycledEvent,
     },
     filter::{Filter, FilterInvalid},
     idm::account::DestroySessionTokenEvent,
@@ -22,9 +23,13 @@ use kanidmd_lib::{
         InitCredentialUpdateIntentEvent,
     },
     idm::delayed::DelayedAction,
-    idm::event::{GeneratePasswordEvent, RegenerateRadiusSecretEvent, UnixPasswordChangeEvent},
+    idm::event::{
+        GeneratePasswordEvent, RegenerateRadiusSecretEvent, UnixPasswordChangeEvent,
+        UnixPasswordSelfChangeEvent, UnixUserAuthEvent,
+    },
     idm::oauth2::{
-        AccessTokenRequest, AccessTokenResponse, AuthorisePermitSuccess, Oauth2Error,
+        AccessTokenRequest, AccessTokenResponse, AuthorisePermitSuccess,
+        DeviceAuthorizationRequest, DeviceAuthorizationResponse, GrantTypeReq, Oauth2Error,
         TokenRevokeRequest,
     },
     idm::server::{IdmServer, IdmServerTransaction},
@@ -373,6 +378,135 @@ impl QueryServerWriteV1 {
             .and_then(|_| idms_prox_write.commit().map(|_| ()))
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_repl_conflict_resolve(
+        &self,
+        uat: Option<String>,
+        id: String,
+        resolution: ReplicationConflictResolution,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let target = Uuid::parse_str(&id).map_err(|_| {
+            request_error!(%id, "replication conflict id is not a uuid");
+            OperationError::NoMatchingEntries
+        })?;
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_write
+            .qs_write
+            .repl_conflict_resolve(&ident, target, &resolution)
+            .and_then(|_| idms_prox_write.commit())
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_request_create(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        create: AccessRequestCreate,
+        eventid: Uuid,
+    ) -> Result<Uuid, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        let group = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        idms_prox_write
+            .access_request_create(&ident, group, &create.justification)
+            .and_then(|request| idms_prox_write.commit().map(|_| request))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_request_decide(
+        &self,
+        uat: Option<String>,
+        id: String,
+        approve: bool,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let request = Uuid::parse_str(&id).map_err(|_| {
+            request_error!(%id, "access request id is not a uuid");
+            OperationError::NoMatchingEntries
+        })?;
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_write
+            .access_request_decide(&ident, request, approve)
+            .and_then(|_| idms_prox_write.commit())
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_review_create(
+        &self,
+        uat: Option<String>,
+        create: AccessReviewCreate,
+        eventid: Uuid,
+    ) -> Result<Uuid, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        idms_prox_write
+            .access_review_create(&ident, &create)
+            .and_then(|review| idms_prox_write.commit().map(|_| review))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_review_decide(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        decide: AccessReviewDecide,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write.validate_and_parse_token_to_ident(uat.as_deref(), ct)?;
+
+        let review = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        idms_prox_write
+            .access_review_decide(&ident, review, &decide)
+            .and_then(|_| idms_prox_write.commit())
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1180,6 +1314,55 @@ impl QueryServerWriteV1 {
             .map(|_| ())
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_idmaccountunixchangecred(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        req: UnixPasswordChangeRequest,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idm_auth = self.idms.auth().await;
+        let ident = idm_auth
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target_uuid = idm_auth
+            .qs_read
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_info!(err = ?e, "Error resolving as gidnumber continuing");
+                e
+            })?;
+
+        // The current unix password proves the identity of the account, which may then
+        // change its own unix password.
+        let uuae = UnixUserAuthEvent::from_parts(ident, target_uuid, req.current)?;
+        let token = idm_auth
+            .auth_unix(&uuae, ct)
+            .await
+            .and_then(|r| idm_auth.commit().map(|_| r))?;
+
+        let Some(token) = token else {
+            security_info!("Unix password change denied - incorrect password");
+            return Err(OperationError::NotAuthenticated);
+        };
+
+        let pce = UnixPasswordSelfChangeEvent::from_unix_auth(&token, req.new);
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        idms_prox_write
+            .set_unix_account_password_self(&pce)
+            .and_then(|_| idms_prox_write.commit())
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub async fn handle_oauth2_rs_image_delete(
         &self,
@@ -1520,13 +1703,28 @@ impl QueryServerWriteV1 {
         eventid: Uuid,
     ) -> Result<AccessTokenResponse, Oauth2Error> {
         let ct = duration_from_epoch_now();
+
+        // Devices that poll too often are turned away before they can contend for the write
+        // transaction.
+        if let GrantTypeReq::DeviceCode { device_code } = &token_req.grant_type {
+            if !self.idms.oauth2_device_poll_allowed(device_code, ct).await {
+                return Err(Oauth2Error::SlowDown);
+            }
+        }
+
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
         // Now we can send to the idm server for authorisation checking.
         let resp =
             idms_prox_write.check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct);
 
+        // The device code grant removes expired, denied and collected device authorisations,
+        // so these errors must also be committed.
         match &resp {
-            Err(Oauth2Error::InvalidGrant) | Ok(_) => {
+            Err(Oauth2Error::InvalidGrant)
+            | Err(Oauth2Error::AuthorizationPending)
+            | Err(Oauth2Error::ExpiredToken)
+            | Err(Oauth2Error::AccessDenied)
+            | Ok(_) => {
                 idms_prox_write.commit().map_err(Oauth2Error::ServerError)?;
             }
             _ => {}
@@ -1535,6 +1733,89 @@ impl QueryServerWriteV1 {
         resp
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_device_authorisation(
+        &self,
+        client_authz: Option<String>,
+        device_req: DeviceAuthorizationRequest,
+        eventid: Uuid,
+    ) -> Result<DeviceAuthorizationResponse, Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        idms_prox_write
+            .check_oauth2_device_authorisation(client_authz.as_deref(), &device_req, ct)
+            .and_then(|r| {
+                idms_prox_write
+                    .commit()
+                    .map(|()| r)
+                    .map_err(Oauth2Error::ServerError)
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_device_permit(
+        &self,
+        uat: Option<String>,
+        consent_req: String,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let (ident, uat) = idms_prox_write
+            .validate_and_parse_uat(uat.as_deref(), ct)
+            .and_then(|uat| {
+                idms_prox_write
+                    .process_uat_to_identity(&uat, ct)
+                    .map(|ident| (ident, uat))
+            })
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        idms_prox_write
+            .check_oauth2_device_authorise_permit(&ident, &uat, &consent_req, ct)
+            .and_then(|()| idms_prox_write.commit())
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_device_reject(
+        &self,
+        uat: Option<String>,
+        consent_req: String,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let (ident, uat) = idms_prox_write
+            .validate_and_parse_uat(uat.as_deref(), ct)
+            .and_then(|uat| {
+                idms_prox_write
+                    .process_uat_to_identity(&uat, ct)
+                    .map(|ident| (ident, uat))
+            })
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        idms_prox_write
+            .check_oauth2_device_authorise_reject(&ident, &uat, &consent_req, ct)
+            .and_then(|()| idms_prox_write.commit())
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1602,6 +1883,53 @@ impl QueryServerWriteV1 {
         }
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?msg.eventid)
+    )]
+    pub async fn handle_purgeexpiredmembersevent(&self, msg: PurgeExpiredMembersEvent) {
+        trace!(?msg, "Begin purge expired members event");
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let res = idms_prox_write
+            .qs_write
+            .purge_expired_members()
+            .and_then(|_| idms_prox_write.commit());
+
+        match res {
+            Ok(()) => {
+                debug!("Purge expired group members success");
+            }
+            Err(err) => {
+                error!(?err, "Unable to purge expired group members");
+            }
+        }
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?msg.eventid)
+    )]
+    pub async fn handle_closeexpiredaccessreviewsevent(&self, msg: CloseExpiredAccessReviewsEvent) {
+        trace!(?msg, "Begin close expired access reviews event");
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let res = idms_prox_write
+            .access_review_close_expired()
+            .and_then(|_| idms_prox_write.commit());
+
+        match res {
+            Ok(()) => {
+                debug!("Close expired access reviews success");
+            }
+            Err(err) => {
+                error!(?err, "Unable to close expired access reviews");
+            }
+        }
+    }
+
     pub(crate) async fn handle_delayedaction(&self, da: DelayedAction) {
         let eventid = Uuid::new_v4();
         let span = span!(Level::INFO, "process_delayed_action", uuid = ?eventid);
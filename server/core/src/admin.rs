@@ -6,6 +6,7 @@ use futures::{SinkExt, StreamExt};
 use kanidm_lib_crypto::serialise::x509b64;
 use kanidm_utils_users::get_current_uid;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::io;
 use std::path::Path;
@@ -23,16 +24,45 @@ pub enum AdminTaskRequest {
     ShowReplicationCertificate,
     RenewReplicationCertificate,
     RefreshReplicationConsumer,
+    ShowReplicationStatus,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum AdminTaskResponse {
     RecoverAccount { password: String },
     ShowReplicationCertificate { cert: String },
+    ShowReplicationStatus { status: ReplicationStatus },
     Success,
     Error,
 }
 
+/// The range of changes this server holds from a single origin server.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReplicationRuvStatus {
+    pub min: String,
+    pub max: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReplicationPartnerStatus {
+    pub origin: String,
+    pub mode: String,
+    pub last_consumed: Option<String>,
+    pub last_supplied: Option<String>,
+    pub last_failure: Option<String>,
+    /// The number of changes, by origin server, that the partner had not yet received when
+    /// it was last supplied.
+    pub lag: Option<BTreeMap<Uuid, usize>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReplicationStatus {
+    pub ruv: BTreeMap<Uuid, ReplicationRuvStatus>,
+    /// The number of conflict entries, by the server that originated the conflicting change.
+    pub conflicts: BTreeMap<Uuid, usize>,
+    pub partners: Vec<ReplicationPartnerStatus>,
+}
+
 #[derive(Default)]
 pub struct ClientCodec;
 
@@ -268,6 +298,31 @@ async fn replication_consumer_refresh(ctrl_tx: &mut mpsc::Sender<ReplCtrl>) -> A
     }
 }
 
+async fn show_replication_status(ctrl_tx: &mut mpsc::Sender<ReplCtrl>) -> AdminTaskResponse {
+    let (tx, rx) = oneshot::channel();
+
+    if ctrl_tx
+        .send(ReplCtrl::GetStatus { respond: tx })
+        .await
+        .is_err()
+    {
+        error!("replication control channel has shutdown");
+        return AdminTaskResponse::Error;
+    }
+
+    match rx.await {
+        Ok(Some(status)) => AdminTaskResponse::ShowReplicationStatus { status },
+        Ok(None) => {
+            error!("Unable to determine replication status. Please inspect the logs.");
+            AdminTaskResponse::Error
+        }
+        Err(_) => {
+            error!("replication control channel did not respond with status.");
+            AdminTaskResponse::Error
+        }
+    }
+}
+
 async fn handle_client(
     sock: UnixStream,
     server: &'static QueryServerWriteV1,
@@ -315,6 +370,13 @@ async fn handle_client(
                         AdminTaskResponse::Error
                     }
                 },
+                AdminTaskRequest::ShowReplicationStatus => match repl_ctrl_tx.as_mut() {
+                    Some(ctrl_tx) => show_replication_status(ctrl_tx).await,
+                    None => {
+                        error!("replication not configured, unable to display status.");
+                        AdminTaskResponse::Error
+                    }
+                },
             }
         }
         .instrument(nspan)
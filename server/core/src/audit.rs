@@ -0,0 +1,269 @@
+//! Audit sinks receive the structured audit events raised by the server and persist
+//! or forward them. Every event is always reported to the server log, and may also be
+//! sent to any number of sinks that are defined in the server configuration.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use kanidmd_lib::idm::audit::AuditEvent;
+
+use crate::config::AuditSinkConfig;
+
+/// A destination for audit events.
+pub trait AuditSink: Send {
+    /// A short description of the sink for log messages.
+    fn name(&self) -> String;
+
+    /// Persist or forward a single audit event, pre-serialised to json.
+    fn emit(&mut self, event: &AuditEvent, json: &str) -> io::Result<()>;
+}
+
+/// Reports events to the server log. This is always enabled.
+pub struct TracingAuditSink;
+
+impl AuditSink for TracingAuditSink {
+    fn name(&self) -> String {
+        "log".to_string()
+    }
+
+    fn emit(&mut self, _event: &AuditEvent, json: &str) -> io::Result<()> {
+        warn!(audit_event = %json);
+        Ok(())
+    }
+}
+
+/// Appends events as json lines to a file, rotating it once it grows too large.
+pub struct FileAuditSink {
+    path: PathBuf,
+    max_size: u64,
+    versions: usize,
+    file: File,
+    size: u64,
+}
+
+impl FileAuditSink {
+    pub fn new(path: &Path, max_size: u64, versions: usize) -> io::Result<Self> {
+        let file = Self::open(path)?;
+        let size = file.metadata()?.len();
+        Ok(FileAuditSink {
+            path: path.to_path_buf(),
+            max_size,
+            versions,
+            file,
+            size,
+        })
+    }
+
+    fn open(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated_path(&self, version: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", version));
+        PathBuf::from(name)
+    }
+
+    /// Move the current file to `path.1`, shifting older versions along and removing
+    /// the oldest.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.versions == 0 {
+            self.file = File::create(&self.path)?;
+            self.size = 0;
+            return Ok(());
+        }
+
+        let oldest = self.rotated_path(self.versions);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+
+        for version in (1..self.versions).rev() {
+            let from = self.rotated_path(version);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(version + 1))?;
+            }
+        }
+
+        fs::rename(&self.path, self.rotated_path(1))?;
+        self.file = Self::open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn name(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    fn emit(&mut self, _event: &AuditEvent, json: &str) -> io::Result<()> {
+        let line_len = json.len() as u64 + 1;
+        // Always write at least one event to a file, even if it alone exceeds the limit.
+        if self.size > 0 && self.size + line_len > self.max_size {
+            self.rotate()?;
+        }
+
+        writeln!(self.file, "{}", json)?;
+        self.size += line_len;
+        Ok(())
+    }
+}
+
+/// Sends events as syslog messages to a local unix datagram socket. This is understood
+/// by syslog daemons and by journald.
+pub struct SyslogAuditSink {
+    path: PathBuf,
+    socket: UnixDatagram,
+}
+
+// facility authpriv (10), severity notice (5)
+const SYSLOG_PRIORITY: u8 = 10 * 8 + 5;
+
+impl SyslogAuditSink {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(SyslogAuditSink {
+            path: path.to_path_buf(),
+            socket,
+        })
+    }
+}
+
+impl AuditSink for SyslogAuditSink {
+    fn name(&self) -> String {
+        format!("syslog {}", self.path.display())
+    }
+
+    fn emit(&mut self, _event: &AuditEvent, json: &str) -> io::Result<()> {
+        let msg = format!(
+            "<{}>kanidmd[{}]: {}",
+            SYSLOG_PRIORITY,
+            std::process::id(),
+            json
+        );
+        match self.socket.send(msg.as_bytes()) {
+            Ok(_) => Ok(()),
+            Err(_) => {
+                // The syslog daemon may have restarted, so reconnect once before failing.
+                self.socket = UnixDatagram::unbound()?;
+                self.socket.connect(&self.path)?;
+                self.socket.send(msg.as_bytes()).map(|_| ())
+            }
+        }
+    }
+}
+
+/// The configured sinks, shared with the blocking thread pool where events are written.
+pub type AuditSinks = Arc<Mutex<Vec<Box<dyn AuditSink>>>>;
+
+/// Create the sinks described by the configuration. The log sink is always first.
+pub fn build_audit_sinks(configs: &[AuditSinkConfig]) -> io::Result<AuditSinks> {
+    let mut sinks: Vec<Box<dyn AuditSink>> = vec![Box::new(TracingAuditSink)];
+
+    for config in configs {
+        let sink: Box<dyn AuditSink> = match config {
+            AuditSinkConfig::File {
+                path,
+                max_size,
+                versions,
+            } => Box::new(FileAuditSink::new(path, *max_size, *versions)?),
+            AuditSinkConfig::Syslog { path } => Box::new(SyslogAuditSink::new(path)?),
+        };
+        sinks.push(sink);
+    }
+
+    Ok(Arc::new(Mutex::new(sinks)))
+}
+
+/// Process a single audit event on the blocking thread pool, as the sinks write to files
+/// and sockets. This completes before returning so that events are emitted in order.
+pub async fn emit_audit_event_blocking(sinks: &AuditSinks, event: AuditEvent) {
+    let sinks = sinks.clone();
+    let res = tokio::task::spawn_blocking(move || match sinks.lock() {
+        Ok(mut sinks) => emit_audit_event(&mut sinks, &event),
+        Err(_) => error!(
+            ?event,
+            "Unable to submit audit event, the audit sinks are poisoned."
+        ),
+    })
+    .await;
+
+    if let Err(e) = res {
+        error!(err = ?e, "Unable to submit audit event to sinks.");
+    }
+}
+
+/// Process a single audit event, giving it to every sink.
+pub fn emit_audit_event(sinks: &mut [Box<dyn AuditSink>], event: &AuditEvent) {
+    let json = match serde_json::to_string(event) {
+        Ok(json) => json,
+        Err(e) => {
+            error!(err=?e, "Unable to process audit event to json.");
+            warn!(?event, json = false);
+            return;
+        }
+    };
+
+    for sink in sinks.iter_mut() {
+        if let Err(e) = sink.emit(event, &json) {
+            error!(err = ?e, sink = %sink.name(), "Unable to submit audit event to sink.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditSink, FileAuditSink};
+    use kanidmd_lib::idm::audit::{AuditEvent, AuditSource};
+    use kanidmd_lib::prelude::UUID_ADMIN;
+    use std::fs;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn test_audit_file_sink_rotation() {
+        sketching::test_init();
+
+        let dir = tempfile::tempdir();
+        assert!(dir.is_ok());
+        let Ok(dir) = dir else { return };
+        let path = dir.path().join("audit.log");
+
+        let event = AuditEvent::AuthenticationDenied {
+            source: AuditSource::Internal,
+            uuid: UUID_ADMIN,
+            spn: "admin@example.com".to_string(),
+            time: OffsetDateTime::UNIX_EPOCH,
+        };
+        let json = serde_json::to_string(&event).unwrap_or_default();
+        assert!(!json.is_empty());
+
+        // Each file has room for two events, and two old versions are kept.
+        let max_size = (json.len() as u64 + 1) * 2;
+        let sink = FileAuditSink::new(&path, max_size, 2);
+        assert!(sink.is_ok());
+        let Ok(mut sink) = sink else { return };
+
+        for _ in 0..7 {
+            assert!(sink.emit(&event, &json).is_ok());
+        }
+
+        let count_lines = |p: &std::path::Path| {
+            fs::read_to_string(p)
+                .map(|content| content.lines().count())
+                .unwrap_or(0)
+        };
+
+        // 7 events: the current file has 1, .1 and .2 have 2 each, the oldest 2 are gone.
+        assert_eq!(count_lines(&path), 1);
+        assert_eq!(count_lines(&dir.path().join("audit.log.1")), 2);
+        assert_eq!(count_lines(&dir.path().join("audit.log.2")), 2);
+        assert!(!dir.path().join("audit.log.3").exists());
+    }
+}
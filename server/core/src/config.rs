@@ -9,7 +9,7 @@ use std::fmt;
 use std::fs::File;
 use std::io::Read;
 use std::net::SocketAddr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use kanidm_proto::constants::DEFAULT_SERVER_ADDRESS;
@@ -39,6 +39,35 @@ fn default_online_backup_versions() -> usize {
     7
 }
 
+/// The configuration of a single audit sink from server.toml.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum AuditSinkConfig {
+    /// Append events as json lines to a file. When the file exceeds `max_size` bytes it
+    /// is rotated, retaining `versions` previous files.
+    #[serde(rename = "file")]
+    File {
+        path: PathBuf,
+        #[serde(default = "default_audit_file_max_size")]
+        max_size: u64,
+        #[serde(default = "default_audit_file_versions")]
+        versions: usize,
+    },
+    /// Send events as syslog messages to a unix datagram socket such as `/dev/log` or
+    /// `/run/systemd/journal/syslog`.
+    #[serde(rename = "syslog")]
+    Syslog { path: PathBuf },
+}
+
+fn default_audit_file_max_size() -> u64 {
+    // 64MiB
+    64 * 1024 * 1024
+}
+
+fn default_audit_file_versions() -> usize {
+    7
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct TlsConfiguration {
     pub chain: String,
@@ -65,12 +94,20 @@ pub enum RepNodeConfig {
         partner_cert: X509,
         automatic_refresh: bool,
     },
-    /*
+    // Accept changes pushed by a partner that we can't connect to, and allow it to pull.
+    #[serde(rename = "allow-push")]
     AllowPush {
+        #[serde(with = "x509b64")]
+        partner_cert: X509,
+        automatic_refresh: bool,
     },
+    // Connect to the partner to push our changes, and pull its changes in return.
+    #[serde(rename = "push")]
     Push {
+        #[serde(with = "x509b64")]
+        partner_cert: X509,
+        automatic_refresh: bool,
     },
-    */
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -90,6 +127,8 @@ pub struct ReplicationConfiguration {
 pub struct ServerConfig {
     pub bindaddress: Option<String>,
     pub ldapbindaddress: Option<String>,
+    /// If set, serve server metrics over plain http at /metrics on this address.
+    pub metricsbindaddress: Option<String>,
     pub adminbindpath: Option<String>,
     pub trust_x_forward_for: Option<bool>,
     // pub threads: Option<usize>,
@@ -103,12 +142,16 @@ pub struct ServerConfig {
     // TODO  -this should be URL
     pub origin: String,
     pub log_level: Option<LogLevel>,
+    /// If set, export request traces to the OpenTelemetry collector at this gRPC url.
+    pub otel_grpc_url: Option<String>,
     #[serde(default)]
     pub role: ServerRole,
     #[serde(default)]
     pub i_acknowledge_that_replication_is_in_development: bool,
     #[serde(rename = "replication")]
     pub repl_config: Option<ReplicationConfiguration>,
+    #[serde(default, rename = "audit_sink")]
+    pub audit_sinks: Vec<AuditSinkConfig>,
 }
 
 impl ServerConfig {
@@ -225,6 +268,7 @@ pub struct IntegrationReplConfig {
 pub struct Configuration {
     pub address: String,
     pub ldapaddress: Option<String>,
+    pub metricsaddress: Option<String>,
     pub adminbindpath: String,
     pub threads: usize,
     // db type later
@@ -246,6 +290,9 @@ pub struct Configuration {
     pub repl_config: Option<ReplicationConfiguration>,
     /// This allows internally setting some unsafe options for replication.
     pub integration_repl_config: Option<Box<IntegrationReplConfig>>,
+
+    /// Where audit events are sent in addition to the server log.
+    pub audit_sinks: Vec<AuditSinkConfig>,
 }
 
 impl fmt::Display for Configuration {
@@ -256,6 +303,10 @@ impl fmt::Display for Configuration {
             Some(la) => write!(f, "ldap address: {}, ", la),
             None => write!(f, "ldap address: disabled, "),
         }?;
+        match &self.metricsaddress {
+            Some(ma) => write!(f, "metrics address: {}, ", ma),
+            None => write!(f, "metrics address: disabled, "),
+        }?;
         write!(f, "origin: {} ", self.origin)?;
         write!(f, "admin bind path: {}, ", self.adminbindpath)?;
         write!(f, "thread count: {}, ", self.threads)?;
@@ -298,6 +349,7 @@ impl fmt::Display for Configuration {
                 write!(f, "replication: disabled, ")?;
             }
         }
+        write!(f, "audit sinks: {}", self.audit_sinks.len())?;
         Ok(())
     }
 }
@@ -307,6 +359,7 @@ impl Default for Configuration {
         Configuration {
             address: DEFAULT_SERVER_ADDRESS.to_string(),
             ldapaddress: None,
+            metricsaddress: None,
             adminbindpath: env!("KANIDM_ADMIN_BIND_PATH").to_string(),
             threads: std::thread::available_parallelism()
                 .map(|t| t.get())
@@ -329,6 +382,7 @@ impl Default for Configuration {
             role: ServerRole::WriteReplica,
             repl_config: None,
             integration_repl_config: None,
+            audit_sinks: Vec::new(),
         }
     }
 }
@@ -373,8 +427,14 @@ impl Configuration {
         self.update_tls(&sconfig.tls_chain, &sconfig.tls_key);
         self.update_bind(&sconfig.bindaddress);
         self.update_ldapbind(&sconfig.ldapbindaddress);
+        self.update_metricsbind(&sconfig.metricsbindaddress);
         self.update_online_backup(&sconfig.online_backup);
         self.update_log_level(&sconfig.log_level);
+        self.update_audit_sinks(&sconfig.audit_sinks);
+    }
+
+    pub fn update_audit_sinks(&mut self, sinks: &[AuditSinkConfig]) {
+        self.audit_sinks = sinks.to_vec();
     }
 
     pub fn update_trust_x_forward_for(&mut self, t: Option<bool>) {
@@ -404,6 +464,10 @@ impl Configuration {
         self.ldapaddress = l.clone();
     }
 
+    pub fn update_metricsbind(&mut self, m: &Option<String>) {
+        self.metricsaddress = m.clone();
+    }
+
     pub fn update_admin_bind_path(&mut self, p: &Option<String>) {
         if let Some(p) = p {
             self.adminbindpath = p.clone();
@@ -310,8 +310,6 @@ pub async fn create_https_server(
                 }
             }
         };
-        #[cfg(feature = "otel")]
-        opentelemetry::global::shutdown_tracer_provider();
         info!("Stopped {}", super::TaskName::HttpsServer);
     }))
 }
@@ -19,7 +19,8 @@ use kanidm_proto::oauth2::{AuthorisationResponse, OidcDiscoveryResponse};
 use kanidm_proto::v1::Entry as ProtoEntry;
 use kanidmd_lib::idm::oauth2::{
     AccessTokenIntrospectRequest, AccessTokenRequest, AuthorisationRequest, AuthorisePermitSuccess,
-    AuthoriseResponse, ErrorResponse, Oauth2Error, TokenRevokeRequest,
+    AuthoriseResponse, DeviceAuthorizationRequest, DeviceAuthorizationResponse, ErrorResponse,
+    Oauth2Error, TokenRevokeRequest,
 };
 use kanidmd_lib::prelude::f_eq;
 use kanidmd_lib::prelude::*;
@@ -726,6 +727,134 @@ pub async fn oauth2_token_post(
     }
 }
 
+// == Device Authorization Grant
+//
+// https://datatracker.ietf.org/doc/html/rfc8628
+//
+//  * Device Authorization Request / Response
+//                           oauth2_device_post
+//  * User Interaction       oauth2_device_authorise_post
+//                           oauth2_device_permit_post
+//                           oauth2_device_reject_post
+//  * Device Access Token    oauth2_token_post
+//
+//  Unlike the authorisation code flow, the device authorisation is held in memory
+//  of the Kanidm instance that issued it, as the device and the user interact with
+//  the server independently. In a HA setup both must reach the same instance.
+
+#[instrument(skip(state, kopid, headers), level = "DEBUG")]
+pub async fn oauth2_device_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    headers: HeaderMap,
+    Form(device_req): Form<DeviceAuthorizationRequest>,
+) -> Result<Json<DeviceAuthorizationResponse>, HTTPOauth2Error> {
+    // Get the authz header (if present). Public clients send their client_id in the form.
+    let client_authz = headers
+        .get("authorization")
+        .and_then(|hv| hv.to_str().ok())
+        .and_then(|h| h.split(' ').last())
+        .map(str::to_string);
+
+    match state
+        .qe_w_ref
+        .handle_oauth2_device_authorisation(client_authz, device_req, kopid.eventid)
+        .await
+    {
+        Ok(device_res) => Ok(Json(device_res)),
+        Err(e) => Err(HTTPOauth2Error(e)),
+    }
+}
+
+#[instrument(level = "debug", skip(state, kopid))]
+pub async fn oauth2_device_authorise_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(user_code): Json<String>,
+) -> Response<Body> {
+    let res = state
+        .qe_r_ref
+        .handle_oauth2_device_user_code(kopid.uat, user_code, kopid.eventid)
+        .await;
+
+    let status = match res {
+        Ok(AuthoriseResponse::ConsentRequested {
+            client_name,
+            scopes,
+            pii_scopes,
+            consent_token,
+        }) => {
+            #[allow(clippy::unwrap_used)]
+            let body = serde_json::to_string(&AuthorisationResponse::ConsentRequested {
+                client_name,
+                scopes,
+                pii_scopes,
+                consent_token,
+            })
+            .unwrap();
+            #[allow(clippy::unwrap_used)]
+            return Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, APPLICATION_JSON)
+                .body(body.into())
+                .unwrap();
+        }
+        // A device is never permitted without the user confirming it.
+        Ok(AuthoriseResponse::Permitted(_)) => StatusCode::INTERNAL_SERVER_ERROR,
+        Err(Oauth2Error::AuthenticationRequired) => {
+            // This will trigger our ui to auth and retry.
+            #[allow(clippy::unwrap_used)]
+            return Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .header(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"))
+                .body(Body::empty())
+                .unwrap();
+        }
+        // If scopes are not available for this account.
+        Err(Oauth2Error::AccessDenied) => StatusCode::FORBIDDEN,
+        // The user code doesn't exist, or has expired.
+        Err(Oauth2Error::InvalidGrant) => StatusCode::NOT_FOUND,
+        Err(e) => {
+            admin_error!(
+                "Unable to authorise device - Error ID: {:?} error: {}",
+                kopid.eventid,
+                &e.to_string()
+            );
+            StatusCode::BAD_REQUEST
+        }
+    };
+
+    #[allow(clippy::unwrap_used)]
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+pub async fn oauth2_device_permit_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(consent_req): Json<String>,
+) -> Response<Body> {
+    let res = state
+        .qe_w_ref
+        .handle_oauth2_device_permit(kopid.uat, consent_req, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn oauth2_device_reject_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(consent_req): Json<String>,
+) -> Response<Body> {
+    let res = state
+        .qe_w_ref
+        .handle_oauth2_device_reject(kopid.uat, consent_req, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 // // For future openid integration
 pub async fn oauth2_openid_discovery_get(
     State(state): State<ServerState>,
@@ -1004,6 +1133,12 @@ pub fn oauth2_route_setup(state: ServerState) -> Router<ServerState> {
             post(oauth2_token_introspect_post),
         )
         .route("/oauth2/token/revoke", post(oauth2_token_revoke_post))
+        // ⚠️  ⚠️   WARNING  ⚠️  ⚠️
+        // IF YOU CHANGE THESE VALUES YOU MUST UPDATE OIDC DISCOVERY URLS
+        .route("/oauth2/device", post(oauth2_device_post))
+        .route("/oauth2/device/authorise", post(oauth2_device_authorise_post))
+        .route("/oauth2/device/permit", post(oauth2_device_permit_post))
+        .route("/oauth2/device/reject", post(oauth2_device_reject_post))
         .merge(openid_router)
         .with_state(state)
         .layer(from_fn(super::middleware::caching::dont_cache_me))
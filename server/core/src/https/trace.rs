@@ -1,7 +1,9 @@
 //! Reimplementation of tower-http's DefaultMakeSpan that only runs at "INFO" level for our own needs.
 
 use http::Request;
+use sketching::otel::{self, TraceParent, TRACEPARENT};
 use tracing::{Level, Span};
+use uuid::Uuid;
 
 /// The default way Spans will be created for Trace.
 ///
@@ -24,12 +26,133 @@ impl Default for DefaultMakeSpanKanidmd {
 impl<B> tower_http::trace::MakeSpan<B> for DefaultMakeSpanKanidmd {
     #[instrument(name = "handle_request", skip_all)]
     fn make_span(&mut self, request: &Request<B>) -> Span {
-        tracing::span!(
+        // If the caller is part of a trace, continue it and use the trace id as our eventid.
+        let traceparent = request
+            .headers()
+            .get(TRACEPARENT)
+            .and_then(|hv| hv.to_str().ok())
+            .and_then(|hv| hv.parse::<TraceParent>().ok());
+
+        let eventid = traceparent
+            .as_ref()
+            .map(TraceParent::eventid)
+            .unwrap_or_else(Uuid::new_v4);
+
+        // The request is a root span, so that its trace id is the eventid rather than the
+        // trace id of the span this is called from.
+        let span = tracing::span!(
+            parent: None,
             Level::INFO,
             "request",
+            uuid = ?eventid,
             method = %request.method(),
             uri = %request.uri(),
             version = ?request.version(),
-        )
+        );
+
+        match traceparent {
+            Some(traceparent) => otel::set_span_parent(&span, &traceparent),
+            None => otel::set_span_trace_id(&span, eventid),
+        }
+
+        span
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{TraceContextExt, TraceId, TracerProvider as _};
+    use opentelemetry_sdk::trace::TracerProvider;
+    use std::sync::{Arc, Mutex};
+    use tower_http::trace::MakeSpan;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::{Layer, Registry};
+
+    /// Records the eventids of the request spans that are created.
+    #[derive(Clone, Default)]
+    struct EventIdLayer(Arc<Mutex<Vec<Uuid>>>);
+
+    impl Visit for EventIdLayer {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "uuid" {
+                if let (Ok(eventid), Ok(mut eventids)) =
+                    (Uuid::parse_str(&format!("{:?}", value)), self.0.lock())
+                {
+                    eventids.push(eventid);
+                }
+            }
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for EventIdLayer {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            attrs.record(&mut self.clone());
+        }
+    }
+
+    impl EventIdLayer {
+        fn last(&self) -> Option<Uuid> {
+            self.0.lock().ok()?.last().copied()
+        }
+    }
+
+    fn span_trace_id(span: &Span) -> TraceId {
+        span.context().span().span_context().trace_id()
+    }
+
+    fn make_request_span(traceparent: Option<&str>) -> Option<Span> {
+        let mut builder = Request::builder().uri("/status");
+        if let Some(traceparent) = traceparent {
+            builder = builder.header(TRACEPARENT, traceparent);
+        }
+        let request = builder.body(()).ok()?;
+        Some(DefaultMakeSpanKanidmd::new().make_span(&request))
+    }
+
+    #[test]
+    fn test_make_span_eventid_is_trace_id() {
+        // The provider must outlive the subscriber, as the tracer only holds a weak reference.
+        let provider = TracerProvider::builder().build();
+        let eventids = EventIdLayer::default();
+        let subscriber = Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")))
+            .with(eventids.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            // A request that continues a trace takes its eventid from the trace id.
+            let span = make_request_span(Some(
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            ));
+            assert!(span.is_some());
+            let Some(span) = span else { return };
+            assert_eq!(
+                eventids.last(),
+                Uuid::parse_str("4bf92f35-77b3-4da6-a3ce-929d0e0e4736").ok()
+            );
+            assert_eq!(
+                span_trace_id(&span).to_bytes(),
+                0x4bf92f3577b34da6a3ce929d0e0e4736u128.to_be_bytes()
+            );
+
+            // Otherwise the request begins a new trace with its eventid, even when made
+            // within an existing span.
+            let outer = tracing::info_span!("outer");
+            let _entered = outer.enter();
+            for traceparent in [None, Some("invalid")] {
+                let span = make_request_span(traceparent);
+                assert!(span.is_some());
+                let Some(span) = span else { return };
+                let eventid = eventids.last();
+                assert!(eventid.is_some());
+                let Some(eventid) = eventid else { return };
+
+                assert_eq!(span_trace_id(&span).to_bytes(), eventid.into_bytes());
+                assert_ne!(span_trace_id(&span), span_trace_id(&outer));
+            }
+        });
     }
 }
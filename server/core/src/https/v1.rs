@@ -15,10 +15,11 @@ use uuid::Uuid;
 
 use kanidm_proto::internal::IdentifyUserRequest;
 use kanidm_proto::v1::{
-    AccountUnixExtend, ApiTokenGenerate, AuthIssueSession, AuthRequest, AuthResponse,
-    AuthState as ProtoAuthState, CUIntentToken, CURequest, CUSessionToken, CreateRequest,
-    DeleteRequest, Entry as ProtoEntry, GroupUnixExtend, ModifyRequest, SearchRequest,
-    SingleStringRequest,
+    AccessRequestCreate, AccessReviewCreate, AccessReviewDecide, AccountUnixExtend,
+    ApiTokenGenerate, AuthIssueSession, AuthRequest, AuthResponse, AuthState as ProtoAuthState,
+    CUIntentToken, CURequest, CUSessionToken, CreateRequest, DeleteRequest, Entry as ProtoEntry,
+    GroupUnixExtend, ModifyRequest, ReplicationConflictResolution, SearchRequest,
+    SingleStringRequest, UnixPasswordChangeRequest,
 };
 use kanidmd_lib::idm::event::AuthResult;
 use kanidmd_lib::idm::AuthState;
@@ -979,6 +980,19 @@ pub async fn account_put_id_unix_credential(
     to_axum_response(res)
 }
 
+pub async fn account_post_id_unix_credential_change(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(obj): Json<UnixPasswordChangeRequest>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_idmaccountunixchangecred(kopid.uat, id, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 pub async fn account_delete_id_unix_credential(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
@@ -1111,6 +1125,309 @@ pub async fn group_get_id_unix_token(
     to_axum_response(res)
 }
 
+pub async fn sudo_rule_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::SudoRule.into()));
+    json_rest_event_get(state, None, filter, kopid).await
+}
+
+pub async fn sudo_rule_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes = vec!["sudo_rule".to_string(), "object".to_string()];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn sudo_rule_get_unix(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_internalunixsudoruleread(kopid.uat, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn sudo_rule_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::SudoRule.into()));
+    json_rest_event_get_id(state, id, filter, None, kopid).await
+}
+
+pub async fn sudo_rule_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::SudoRule.into()));
+    json_rest_event_delete_id(state, id, filter, kopid).await
+}
+
+pub async fn sudo_rule_id_get_attr(
+    State(state): State<ServerState>,
+    Path((id, attr)): Path<(String, String)>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::SudoRule.into()));
+    json_rest_event_get_id_attr(state, id, attr, filter, kopid).await
+}
+
+pub async fn sudo_rule_id_post_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(values): Json<Vec<String>>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::SudoRule.into()));
+    json_rest_event_post_id_attr(state, id, attr, filter, values, kopid).await
+}
+
+pub async fn sudo_rule_id_put_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(values): Json<Vec<String>>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::SudoRule.into()));
+    json_rest_event_put_id_attr(state, id, attr, filter, values, kopid).await
+}
+
+pub async fn sudo_rule_id_delete_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    values: Option<Json<Vec<String>>>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::SudoRule.into()));
+    let values = values.map(|v| v.0);
+    json_rest_event_delete_id_attr(state, id, attr, filter, values, kopid).await
+}
+
+pub async fn host_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Host.into()));
+    json_rest_event_get(state, None, filter, kopid).await
+}
+
+pub async fn host_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes: Vec<String> = vec![
+        EntryClass::Host.into(),
+        EntryClass::ServiceAccount.into(),
+        EntryClass::Account.into(),
+        EntryClass::Object.into(),
+    ];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn host_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Host.into()));
+    json_rest_event_get_id(state, id, filter, None, kopid).await
+}
+
+pub async fn host_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Host.into()));
+    json_rest_event_delete_id(state, id, filter, kopid).await
+}
+
+pub async fn hbac_rule_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::HbacRule.into()));
+    json_rest_event_get(state, None, filter, kopid).await
+}
+
+pub async fn hbac_rule_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes: Vec<String> = vec![EntryClass::HbacRule.into(), EntryClass::Object.into()];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn hbac_rule_get_unix(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_internalunixhbacruleread(kopid.uat, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn hbac_rule_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::HbacRule.into()));
+    json_rest_event_get_id(state, id, filter, None, kopid).await
+}
+
+pub async fn hbac_rule_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::HbacRule.into()));
+    json_rest_event_delete_id(state, id, filter, kopid).await
+}
+
+pub async fn hbac_rule_id_get_attr(
+    State(state): State<ServerState>,
+    Path((id, attr)): Path<(String, String)>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::HbacRule.into()));
+    json_rest_event_get_id_attr(state, id, attr, filter, kopid).await
+}
+
+pub async fn hbac_rule_id_post_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(values): Json<Vec<String>>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::HbacRule.into()));
+    json_rest_event_post_id_attr(state, id, attr, filter, values, kopid).await
+}
+
+pub async fn hbac_rule_id_put_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(values): Json<Vec<String>>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::HbacRule.into()));
+    json_rest_event_put_id_attr(state, id, attr, filter, values, kopid).await
+}
+
+pub async fn hbac_rule_id_delete_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    values: Option<Json<Vec<String>>>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::HbacRule.into()));
+    let values = values.map(|v| v.0);
+    json_rest_event_delete_id_attr(state, id, attr, filter, values, kopid).await
+}
+
+pub async fn automount_map_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::AutomountMap.into()));
+    json_rest_event_get(state, None, filter, kopid).await
+}
+
+pub async fn automount_map_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes: Vec<String> = vec![EntryClass::AutomountMap.into(), EntryClass::Object.into()];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn automount_map_get_unix(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_internalunixautomountmapread(kopid.uat, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn automount_map_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::AutomountMap.into()));
+    json_rest_event_get_id(state, id, filter, None, kopid).await
+}
+
+pub async fn automount_map_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::AutomountMap.into()));
+    json_rest_event_delete_id(state, id, filter, kopid).await
+}
+
+pub async fn automount_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Automount.into()));
+    json_rest_event_get(state, None, filter, kopid).await
+}
+
+pub async fn automount_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes: Vec<String> = vec![EntryClass::Automount.into(), EntryClass::Object.into()];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn automount_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Automount.into()));
+    json_rest_event_get_id(state, id, filter, None, kopid).await
+}
+
+pub async fn automount_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Automount.into()));
+    json_rest_event_delete_id(state, id, filter, kopid).await
+}
+
+pub async fn automount_id_put_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(values): Json<Vec<String>>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Automount.into()));
+    json_rest_event_put_id_attr(state, id, attr, filter, values, kopid).await
+}
+
 pub async fn domain_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
@@ -1281,6 +1598,138 @@ pub async fn recycle_bin_revive_id_post(
     to_axum_response(res)
 }
 
+pub async fn replication_conflict_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_repl_conflict_list(kopid.uat, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn replication_conflict_id_get(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_repl_conflict_get(kopid.uat, id, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn replication_conflict_id_resolve_post(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+    Json(resolution): Json<ReplicationConflictResolution>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_repl_conflict_resolve(kopid.uat, id, resolution, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn group_id_access_request_post(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+    Json(create): Json<AccessRequestCreate>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_request_create(kopid.uat, id, create, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_access_request_list(kopid.uat, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_id_approve_post(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_request_decide(kopid.uat, id, true, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_id_deny_post(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_request_decide(kopid.uat, id, false, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_review_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_access_review_list(kopid.uat, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_review_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(create): Json<AccessReviewCreate>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_review_create(kopid.uat, create, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_review_id_report_get(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_access_review_report(kopid.uat, id, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_review_id_decide_post(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+    Json(decide): Json<AccessReviewDecide>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_review_decide(kopid.uat, id, decide, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 pub async fn applinks_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
@@ -1634,6 +2083,10 @@ pub fn router(state: ServerState) -> Router<ServerState> {
             "/v1/account/:id/_unix/_auth",
             post(account_post_id_unix_auth),
         )
+        .route(
+            "/v1/account/:id/_unix/_credential/_change",
+            post(account_post_id_unix_credential_change),
+        )
         .route(
             "/v1/account/:id/_unix/_token",
             post(account_id_unix_token).get(account_id_unix_token), // TODO: make this cacheable
@@ -1685,6 +2138,74 @@ pub fn router(state: ServerState) -> Router<ServerState> {
                 .put(group_id_put_attr)
                 .post(group_id_post_attr),
         )
+        .route("/v1/sudo_rule", get(sudo_rule_get).post(sudo_rule_post))
+        .route("/v1/sudo_rule/_unix", get(sudo_rule_get_unix))
+        .route(
+            "/v1/sudo_rule/:id",
+            get(sudo_rule_id_get).delete(sudo_rule_id_delete),
+        )
+        .route(
+            "/v1/sudo_rule/:id/_attr/:attr",
+            delete(sudo_rule_id_delete_attr)
+                .get(sudo_rule_id_get_attr)
+                .put(sudo_rule_id_put_attr)
+                .post(sudo_rule_id_post_attr),
+        )
+        .route("/v1/host", get(host_get).post(host_post))
+        .route("/v1/host/:id", get(host_id_get).delete(host_id_delete))
+        .route("/v1/hbac_rule", get(hbac_rule_get).post(hbac_rule_post))
+        .route("/v1/hbac_rule/_unix", get(hbac_rule_get_unix))
+        .route(
+            "/v1/hbac_rule/:id",
+            get(hbac_rule_id_get).delete(hbac_rule_id_delete),
+        )
+        .route(
+            "/v1/hbac_rule/:id/_attr/:attr",
+            delete(hbac_rule_id_delete_attr)
+                .get(hbac_rule_id_get_attr)
+                .put(hbac_rule_id_put_attr)
+                .post(hbac_rule_id_post_attr),
+        )
+        .route(
+            "/v1/automount_map",
+            get(automount_map_get).post(automount_map_post),
+        )
+        .route("/v1/automount_map/_unix", get(automount_map_get_unix))
+        .route(
+            "/v1/automount_map/:id",
+            get(automount_map_id_get).delete(automount_map_id_delete),
+        )
+        .route("/v1/automount", get(automount_get).post(automount_post))
+        .route(
+            "/v1/automount/:id",
+            get(automount_id_get).delete(automount_id_delete),
+        )
+        .route("/v1/automount/:id/_attr/:attr", put(automount_id_put_attr))
+        .route(
+            "/v1/group/:id/_access_request",
+            post(group_id_access_request_post),
+        )
+        .route("/v1/access_request", get(access_request_get))
+        .route(
+            "/v1/access_request/:id/_approve",
+            post(access_request_id_approve_post),
+        )
+        .route(
+            "/v1/access_request/:id/_deny",
+            post(access_request_id_deny_post),
+        )
+        .route(
+            "/v1/access_review",
+            get(access_review_get).post(access_review_post),
+        )
+        .route(
+            "/v1/access_review/:id/_report",
+            get(access_review_id_report_get),
+        )
+        .route(
+            "/v1/access_review/:id/_decide",
+            post(access_review_id_decide_post),
+        )
         .with_state(state.clone())
         .route("/v1/system", get(system_get))
         .route(
@@ -1700,6 +2221,18 @@ pub fn router(state: ServerState) -> Router<ServerState> {
             "/v1/recycle_bin/:id/_revive",
             post(recycle_bin_revive_id_post),
         )
+        .route(
+            "/v1/system/replication/conflicts",
+            get(replication_conflict_get),
+        )
+        .route(
+            "/v1/system/replication/conflicts/:id",
+            get(replication_conflict_id_get),
+        )
+        .route(
+            "/v1/system/replication/conflicts/:id/_resolve",
+            post(replication_conflict_id_resolve_post),
+        )
         // .route("/v1/access_profile", get(|| async { "TODO" }))
         // .route("/v1/access_profile/:id", get(|| async { "TODO" }))
         // .route(
@@ -1,13 +1,22 @@
 use super::middleware::KOpId;
 use super::{to_axum_response, ServerState};
-use axum::extract::{Path, State};
-use axum::response::IntoResponse;
+use axum::extract::{Path, Query, State};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
 use axum_auth::AuthBearer;
-use kanidm_proto::scim_v1::ScimSyncRequest;
-use kanidm_proto::v1::Entry as ProtoEntry;
+use http::header::{CONTENT_TYPE, ETAG, IF_MATCH, LOCATION};
+use http::{HeaderMap, StatusCode};
+use hyper::Body;
+use kanidm_proto::constants::APPLICATION_SCIM_JSON;
+use kanidm_proto::scim_v1::{ScimSyncRequest, ScimSyncWritebackAck};
+use kanidm_proto::scim_v2::{
+    ScimErrorResponse, ScimGroup, ScimListRequest, ScimPatchRequest, ScimResource, ScimUser,
+};
+use kanidm_proto::v1::{Entry as ProtoEntry, PluginError};
+use kanidmd_lib::idm::scim_v2::ScimResourceType;
 use kanidmd_lib::prelude::*;
+use serde::Serialize;
 
 use super::v1::{
     json_rest_event_get, json_rest_event_get_id, json_rest_event_get_id_attr, json_rest_event_post,
@@ -132,6 +141,31 @@ async fn scim_sync_get(
     to_axum_response(res)
 }
 
+async fn scim_sync_writeback_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    AuthBearer(bearer): AuthBearer,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_scim_sync_writeback(Some(bearer), kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+async fn scim_sync_writeback_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    AuthBearer(bearer): AuthBearer,
+    Json(ack): Json<ScimSyncWritebackAck>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_scim_sync_writeback_ack(Some(bearer), ack, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 pub async fn sync_account_id_get_attr(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
@@ -151,6 +185,352 @@ pub async fn sync_account_id_put_attr(
     json_rest_event_put_id_attr(state, id, attr, filter, values, kopid).await
 }
 
+/// Build a SCIM response. Errors are returned as SCIM error documents rather than the
+/// OperationError used by the rest of the api, as SCIM clients expect this format.
+fn scim_v2_response<T: Serialize>(
+    res: Result<T, OperationError>,
+    status: StatusCode,
+    version: Option<&str>,
+    location: Option<&str>,
+) -> Response<Body> {
+    match res.map(|v| serde_json::to_string(&v)) {
+        Ok(Ok(body)) => {
+            let mut response = Response::builder()
+                .status(status)
+                .header(CONTENT_TYPE, APPLICATION_SCIM_JSON);
+            if let Some(version) = version {
+                response = response.header(ETAG, version);
+            }
+            if let Some(location) = location {
+                response = response.header(LOCATION, location);
+            }
+            #[allow(clippy::unwrap_used)]
+            response.body(Body::from(body)).unwrap()
+        }
+        Ok(Err(err)) => {
+            error!(?err, "Failed to serialise response");
+            scim_v2_error_response(OperationError::SerdeJsonError)
+        }
+        Err(e) => scim_v2_error_response(e),
+    }
+}
+
+fn scim_v2_error_response(e: OperationError) -> Response<Body> {
+    debug!("OperationError: {:?}", e);
+    let (status, scim_type) = match &e {
+        OperationError::NotAuthenticated | OperationError::SessionExpired => {
+            (StatusCode::UNAUTHORIZED, None)
+        }
+        OperationError::SystemProtectedObject | OperationError::AccessDenied => {
+            (StatusCode::FORBIDDEN, None)
+        }
+        OperationError::NoMatchingEntries => (StatusCode::NOT_FOUND, None),
+        OperationError::FilterGeneration => (StatusCode::BAD_REQUEST, Some("invalidFilter")),
+        OperationError::InvalidAttributeName(_) => (StatusCode::BAD_REQUEST, Some("invalidPath")),
+        OperationError::InvalidAttribute(_)
+        | OperationError::InvalidRequestState
+        | OperationError::SchemaViolation(_) => (StatusCode::BAD_REQUEST, Some("invalidValue")),
+        OperationError::SystemProtectedAttribute => (StatusCode::BAD_REQUEST, Some("mutability")),
+        OperationError::ModifyAssertionFailed => (StatusCode::PRECONDITION_FAILED, None),
+        OperationError::Plugin(PluginError::AttrUnique(_)) => {
+            (StatusCode::CONFLICT, Some("uniqueness"))
+        }
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, None),
+    };
+
+    let error = ScimErrorResponse::new(status.as_u16(), scim_type, Some(format!("{:?}", e)));
+
+    #[allow(clippy::unwrap_used)]
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, APPLICATION_SCIM_JSON)
+        .body(Body::from(
+            serde_json::to_string(&error).unwrap_or_default(),
+        ))
+        .unwrap()
+}
+
+fn scim_v2_resource_response(
+    res: Result<ScimResource, OperationError>,
+    status: StatusCode,
+) -> Response<Body> {
+    let meta = res.as_ref().ok().and_then(|r| r.meta()).cloned();
+    let version = meta.as_ref().and_then(|m| m.version.as_deref());
+    // The location is only returned for a new resource.
+    let location = meta
+        .as_ref()
+        .and_then(|m| m.location.as_deref())
+        .filter(|_| status == StatusCode::CREATED);
+    scim_v2_response(res, status, version, location)
+}
+
+fn scim_v2_if_match(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(IF_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+async fn scim_v2_search(
+    state: ServerState,
+    kopid: KOpId,
+    rtype: ScimResourceType,
+    req: ScimListRequest,
+) -> Response<Body> {
+    let res = state
+        .qe_r_ref
+        .handle_scim_v2_search(kopid.uat, rtype, req, kopid.eventid)
+        .await;
+    scim_v2_response(res, StatusCode::OK, None, None)
+}
+
+async fn scim_v2_create(
+    state: ServerState,
+    kopid: KOpId,
+    rtype: ScimResourceType,
+    resource: ScimResource,
+) -> Response<Body> {
+    let res = state
+        .qe_w_ref
+        .handle_scim_v2_create(kopid.uat, rtype, resource, kopid.eventid)
+        .await;
+    scim_v2_resource_response(res, StatusCode::CREATED)
+}
+
+async fn scim_v2_id_get(
+    state: ServerState,
+    kopid: KOpId,
+    rtype: ScimResourceType,
+    id: String,
+) -> Response<Body> {
+    let res = state
+        .qe_r_ref
+        .handle_scim_v2_get(kopid.uat, rtype, id, kopid.eventid)
+        .await;
+    scim_v2_resource_response(res, StatusCode::OK)
+}
+
+async fn scim_v2_id_put(
+    state: ServerState,
+    kopid: KOpId,
+    rtype: ScimResourceType,
+    id: String,
+    headers: HeaderMap,
+    resource: ScimResource,
+) -> Response<Body> {
+    let res = state
+        .qe_w_ref
+        .handle_scim_v2_replace(
+            kopid.uat,
+            rtype,
+            id,
+            resource,
+            scim_v2_if_match(&headers),
+            kopid.eventid,
+        )
+        .await;
+    scim_v2_resource_response(res, StatusCode::OK)
+}
+
+async fn scim_v2_id_patch(
+    state: ServerState,
+    kopid: KOpId,
+    rtype: ScimResourceType,
+    id: String,
+    headers: HeaderMap,
+    patch: ScimPatchRequest,
+) -> Response<Body> {
+    let res = state
+        .qe_w_ref
+        .handle_scim_v2_patch(
+            kopid.uat,
+            rtype,
+            id,
+            patch,
+            scim_v2_if_match(&headers),
+            kopid.eventid,
+        )
+        .await;
+    scim_v2_resource_response(res, StatusCode::OK)
+}
+
+async fn scim_v2_id_delete(
+    state: ServerState,
+    kopid: KOpId,
+    rtype: ScimResourceType,
+    id: String,
+    headers: HeaderMap,
+) -> Response<Body> {
+    let res = state
+        .qe_w_ref
+        .handle_scim_v2_delete(
+            kopid.uat,
+            rtype,
+            id,
+            scim_v2_if_match(&headers),
+            kopid.eventid,
+        )
+        .await;
+    match res {
+        #[allow(clippy::unwrap_used)]
+        Ok(()) => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap(),
+        Err(e) => scim_v2_error_response(e),
+    }
+}
+
+async fn scim_v2_users_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Query(req): Query<ScimListRequest>,
+) -> Response<Body> {
+    scim_v2_search(state, kopid, ScimResourceType::User, req).await
+}
+
+async fn scim_v2_users_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(user): Json<ScimUser>,
+) -> Response<Body> {
+    scim_v2_create(
+        state,
+        kopid,
+        ScimResourceType::User,
+        ScimResource::User(user),
+    )
+    .await
+}
+
+async fn scim_v2_users_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> Response<Body> {
+    scim_v2_id_get(state, kopid, ScimResourceType::User, id).await
+}
+
+async fn scim_v2_users_id_put(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(user): Json<ScimUser>,
+) -> Response<Body> {
+    let resource = ScimResource::User(user);
+    scim_v2_id_put(state, kopid, ScimResourceType::User, id, headers, resource).await
+}
+
+async fn scim_v2_users_id_patch(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(patch): Json<ScimPatchRequest>,
+) -> Response<Body> {
+    scim_v2_id_patch(state, kopid, ScimResourceType::User, id, headers, patch).await
+}
+
+async fn scim_v2_users_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response<Body> {
+    scim_v2_id_delete(state, kopid, ScimResourceType::User, id, headers).await
+}
+
+async fn scim_v2_groups_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Query(req): Query<ScimListRequest>,
+) -> Response<Body> {
+    scim_v2_search(state, kopid, ScimResourceType::Group, req).await
+}
+
+async fn scim_v2_groups_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(group): Json<ScimGroup>,
+) -> Response<Body> {
+    scim_v2_create(
+        state,
+        kopid,
+        ScimResourceType::Group,
+        ScimResource::Group(group),
+    )
+    .await
+}
+
+async fn scim_v2_groups_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> Response<Body> {
+    scim_v2_id_get(state, kopid, ScimResourceType::Group, id).await
+}
+
+async fn scim_v2_groups_id_put(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(group): Json<ScimGroup>,
+) -> Response<Body> {
+    let resource = ScimResource::Group(group);
+    scim_v2_id_put(state, kopid, ScimResourceType::Group, id, headers, resource).await
+}
+
+async fn scim_v2_groups_id_patch(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(patch): Json<ScimPatchRequest>,
+) -> Response<Body> {
+    scim_v2_id_patch(state, kopid, ScimResourceType::Group, id, headers, patch).await
+}
+
+async fn scim_v2_groups_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response<Body> {
+    scim_v2_id_delete(state, kopid, ScimResourceType::Group, id, headers).await
+}
+
+async fn scim_v2_service_provider_config_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> Response<Body> {
+    let res = state
+        .qe_r_ref
+        .handle_scim_v2_service_provider_config(kopid.eventid)
+        .await;
+    scim_v2_response(Ok(res), StatusCode::OK, None, None)
+}
+
+async fn scim_v2_resource_types_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> Response<Body> {
+    let res = state
+        .qe_r_ref
+        .handle_scim_v2_resource_types(kopid.eventid)
+        .await;
+    scim_v2_response(Ok(res), StatusCode::OK, None, None)
+}
+
+async fn scim_v2_schemas_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> Response<Body> {
+    let res = state.qe_r_ref.handle_scim_v2_schemas(kopid.eventid).await;
+    scim_v2_response(Ok(res), StatusCode::OK, None, None)
+}
+
 async fn scim_sink_get() -> impl IntoResponse {
     r#"
     <!DOCTYPE html>
@@ -254,6 +634,47 @@ pub fn scim_route_setup() -> Router<ServerState> {
         //
         //                            POST                   Send a sync update
         //
+        //  Sync     /Sync/Writeback  GET                    Retrieve changes made
+        //                                                   in kanidm to yielded
+        //                                                   attributes of synced
+        //                                                   entries
+        //
+        //                            POST                   Acknowledge that the
+        //                                                   changes were written
+        //                                                   back
+        //
         .route("/scim/v1/Sync", post(scim_sync_post).get(scim_sync_get))
+        .route(
+            "/scim/v1/Sync/Writeback",
+            post(scim_sync_writeback_post).get(scim_sync_writeback_get),
+        )
         .route("/scim/v1/Sink", get(scim_sink_get))
+        .route(
+            "/scim/v2/Users",
+            get(scim_v2_users_get).post(scim_v2_users_post),
+        )
+        .route(
+            "/scim/v2/Users/:id",
+            get(scim_v2_users_id_get)
+                .put(scim_v2_users_id_put)
+                .patch(scim_v2_users_id_patch)
+                .delete(scim_v2_users_id_delete),
+        )
+        .route(
+            "/scim/v2/Groups",
+            get(scim_v2_groups_get).post(scim_v2_groups_post),
+        )
+        .route(
+            "/scim/v2/Groups/:id",
+            get(scim_v2_groups_id_get)
+                .put(scim_v2_groups_id_put)
+                .patch(scim_v2_groups_id_patch)
+                .delete(scim_v2_groups_id_delete),
+        )
+        .route(
+            "/scim/v2/ServiceProviderConfig",
+            get(scim_v2_service_provider_config_get),
+        )
+        .route("/scim/v2/ResourceTypes", get(scim_v2_resource_types_get))
+        .route("/scim/v2/Schemas", get(scim_v2_schemas_get))
 }
@@ -17,7 +17,10 @@ use crate::CoreAction;
 use crate::actors::v1_read::QueryServerReadV1;
 use crate::actors::v1_write::QueryServerWriteV1;
 use kanidmd_lib::constants::PURGE_FREQUENCY;
-use kanidmd_lib::event::{OnlineBackupEvent, PurgeRecycledEvent, PurgeTombstoneEvent};
+use kanidmd_lib::event::{
+    CloseExpiredAccessReviewsEvent, OnlineBackupEvent, PurgeExpiredMembersEvent,
+    PurgeRecycledEvent, PurgeTombstoneEvent,
+};
 
 pub(crate) struct IntervalActor;
 
@@ -43,6 +46,14 @@ impl IntervalActor {
                         server
                             .handle_purgerecycledevent(PurgeRecycledEvent::new())
                             .await;
+                        server
+                            .handle_purgeexpiredmembersevent(PurgeExpiredMembersEvent::new())
+                            .await;
+                        server
+                            .handle_closeexpiredaccessreviewsevent(
+                                CloseExpiredAccessReviewsEvent::new(),
+                            )
+                            .await;
                     }
                 }
             }
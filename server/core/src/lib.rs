@@ -27,11 +27,13 @@ extern crate kanidmd_lib;
 
 pub mod actors;
 pub mod admin;
+pub mod audit;
 pub mod config;
 mod crypto;
 mod https;
 mod interval;
 mod ldaps;
+mod metrics;
 mod repl;
 mod utils;
 
@@ -738,6 +740,7 @@ pub(crate) enum TaskName {
     HttpsServer,
     IntervalActor,
     LdapActor,
+    MetricsServer,
     Replication,
 }
 
@@ -754,6 +757,7 @@ impl Display for TaskName {
                 TaskName::HttpsServer => "HTTPS Server",
                 TaskName::IntervalActor => "Interval Actor",
                 TaskName::LdapActor => "LDAP Acceptor Actor",
+                TaskName::MetricsServer => "Metrics Server",
                 TaskName::Replication => "Replication",
             }
             .to_string()
@@ -942,6 +946,14 @@ pub async fn create_server_core(
 
     let mut broadcast_rx = broadcast_tx.subscribe();
 
+    let audit_sinks = match audit::build_audit_sinks(&config.audit_sinks) {
+        Ok(sinks) => sinks,
+        Err(e) => {
+            error!("Unable to setup audit sinks -> {:?}", e);
+            return Err(());
+        }
+    };
+
     let auditd_handle = tokio::spawn(async move {
         loop {
             tokio::select! {
@@ -951,16 +963,11 @@ pub async fn create_server_core(
                     }
                 }
                 audit_event = idms_audit.audit_rx().recv() => {
-                    match serde_json::to_string(&audit_event) {
-                        Ok(audit_event) => {
-                            warn!(%audit_event);
-                        }
-                        Err(e) => {
-                            error!(err=?e, "Unable to process audit event to json.");
-                            warn!(?audit_event, json=false);
-                        }
+                    match audit_event {
+                        Some(audit_event) => audit::emit_audit_event_blocking(&audit_sinks, audit_event).await,
+                        // Channel has closed, stop the task.
+                        None => break,
                     }
-
                 }
             }
         }
@@ -1035,6 +1042,24 @@ pub async fn create_server_core(
         }
     };
 
+    let maybe_metrics_handle = match &config.metricsaddress {
+        Some(ma) if !config_test => {
+            // ⚠️  only start the sockets and listeners in non-config-test modes.
+            let h = metrics::create_metrics_server(
+                ma.as_str(),
+                maybe_repl_ctrl_tx.clone(),
+                broadcast_tx.subscribe(),
+            )
+            .await?;
+            Some(h)
+        }
+        Some(_) => None,
+        None => {
+            debug!("Metrics not requested, skipping");
+            None
+        }
+    };
+
     let maybe_http_acceptor_handle = if config_test {
         admin_info!("This config rocks! 🪨 ");
         None
@@ -1106,6 +1131,10 @@ pub async fn create_server_core(
         handles.push((TaskName::Replication, repl_handle))
     }
 
+    if let Some(metrics_handle) = maybe_metrics_handle {
+        handles.push((TaskName::MetricsServer, metrics_handle))
+    }
+
     Ok(CoreHandle {
         clean_shutdown: false,
         tx: broadcast_tx,
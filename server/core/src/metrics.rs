@@ -0,0 +1,254 @@
+//! An optional plain http listener that exposes the internal metrics of the server for
+//! collection by Prometheus, or any other system that understands the OpenMetrics format.
+//!
+//! This is served on a separate address to the main web server so that it does not need
+//! to be exposed to clients, and can be restricted to the monitoring network.
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use http::header::CONTENT_TYPE;
+use kanidmd_lib::metrics::{OpenMetricsWriter, METRICS};
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
+
+use crate::admin::ReplicationStatus;
+use crate::repl::ReplCtrl;
+use crate::CoreAction;
+
+/// How long the replication status is reused between scrapes. Gathering it reads the
+/// database, so this bounds the load that frequent scrapes place on the server.
+const REPL_STATUS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+type ReplStatusCache = Option<(Instant, Option<Arc<ReplicationStatus>>)>;
+
+#[derive(Clone)]
+struct MetricsState {
+    repl_ctrl_tx: Option<mpsc::Sender<ReplCtrl>>,
+    repl_status_cache: Arc<Mutex<ReplStatusCache>>,
+}
+
+impl MetricsState {
+    fn new(repl_ctrl_tx: Option<mpsc::Sender<ReplCtrl>>) -> Self {
+        MetricsState {
+            repl_ctrl_tx,
+            repl_status_cache: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    async fn repl_status(&self) -> Option<Arc<ReplicationStatus>> {
+        let repl_ctrl_tx = self.repl_ctrl_tx.as_ref()?;
+
+        // The lock is held while the status is requested, so that concurrent scrapes
+        // wait for the one request rather than each making their own.
+        let mut cache = self.repl_status_cache.lock().await;
+        if let Some((fetched_at, status)) = cache.as_ref() {
+            if fetched_at.elapsed() < REPL_STATUS_CACHE_TTL {
+                return status.clone();
+            }
+        }
+
+        let status = repl_status(repl_ctrl_tx).await.map(Arc::new);
+        *cache = Some((Instant::now(), status.clone()));
+        status
+    }
+}
+
+async fn repl_status(repl_ctrl_tx: &mpsc::Sender<ReplCtrl>) -> Option<ReplicationStatus> {
+    let (tx, rx) = oneshot::channel();
+
+    if repl_ctrl_tx
+        .send(ReplCtrl::GetStatus { respond: tx })
+        .await
+        .is_err()
+    {
+        error!("replication control channel has shutdown");
+        return None;
+    }
+
+    rx.await.ok().flatten()
+}
+
+fn render_repl_status(w: &mut OpenMetricsWriter, status: &ReplicationStatus) {
+    w.family(
+        "kanidm_replication_lag",
+        "gauge",
+        "Changes by origin server that a partner had not received when it was last supplied.",
+    );
+    for partner in status.partners.iter() {
+        for (s_uuid, lag) in partner.lag.iter().flatten() {
+            w.sample(
+                "kanidm_replication_lag",
+                &[
+                    ("partner", partner.origin.as_str()),
+                    ("server", s_uuid.to_string().as_str()),
+                ],
+                lag,
+            );
+        }
+    }
+
+    w.family(
+        "kanidm_replication_conflicts",
+        "gauge",
+        "Replication conflict entries by the server that originated the conflicting change.",
+    );
+    for (s_uuid, count) in status.conflicts.iter() {
+        w.sample(
+            "kanidm_replication_conflicts",
+            &[("server", s_uuid.to_string().as_str())],
+            count,
+        );
+    }
+}
+
+async fn metrics_get(State(state): State<MetricsState>) -> impl IntoResponse {
+    let mut w = OpenMetricsWriter::new();
+    METRICS.render(&mut w);
+
+    if let Some(status) = state.repl_status().await {
+        render_repl_status(&mut w, &status);
+    }
+
+    (
+        [(CONTENT_TYPE, OpenMetricsWriter::CONTENT_TYPE)],
+        w.finish(),
+    )
+}
+
+pub(crate) async fn create_metrics_server(
+    address: &str,
+    repl_ctrl_tx: Option<mpsc::Sender<ReplCtrl>>,
+    mut rx: broadcast::Receiver<CoreAction>,
+) -> Result<tokio::task::JoinHandle<()>, ()> {
+    let addr = SocketAddr::from_str(address).map_err(|e| {
+        error!("Could not parse metrics address {} -> {:?}", address, e);
+    })?;
+
+    let listener = std::net::TcpListener::bind(addr).map_err(|e| {
+        error!("Could not bind to metrics address {} -> {:?}", address, e);
+    })?;
+
+    let server = axum::Server::from_tcp(listener).map_err(|e| {
+        error!("Could not create metrics server {} -> {:?}", address, e);
+    })?;
+
+    let app = Router::new()
+        .route("/metrics", get(metrics_get))
+        .with_state(MetricsState::new(repl_ctrl_tx));
+
+    info!("Starting metrics interface http://{}/metrics ...", address);
+
+    Ok(tokio::spawn(async move {
+        let res = server
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(async move {
+                // Any message, or the channel closing, is a shutdown.
+                let _ = rx.recv().await;
+            })
+            .await;
+        if let Err(err) = res {
+            error!("Metrics server exited with {:?}", err);
+        }
+        info!("Stopped {}", super::TaskName::MetricsServer);
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::ReplicationPartnerStatus;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    fn test_repl_status(s_uuid: Uuid) -> ReplicationStatus {
+        ReplicationStatus {
+            ruv: BTreeMap::new(),
+            conflicts: BTreeMap::from([(s_uuid, 2)]),
+            partners: vec![ReplicationPartnerStatus {
+                origin: "repl://b.example.com:8444".to_string(),
+                mode: "pull".to_string(),
+                last_consumed: None,
+                last_supplied: None,
+                last_failure: None,
+                lag: Some(BTreeMap::from([(s_uuid, 5)])),
+            }],
+        }
+    }
+
+    async fn scrape(state: &MetricsState) -> Option<String> {
+        let response = metrics_get(State(state.clone())).await.into_response();
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).map(|v| v.as_bytes()),
+            Some(OpenMetricsWriter::CONTENT_TYPE.as_bytes())
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.ok()?;
+        String::from_utf8(body.to_vec()).ok()
+    }
+
+    #[tokio::test]
+    async fn test_metrics_get_without_replication() {
+        let doc = scrape(&MetricsState::new(None)).await;
+        assert!(doc.is_some());
+        let Some(doc) = doc else { return };
+
+        assert!(doc
+            .lines()
+            .any(|l| l == "# TYPE kanidm_auth_attempts counter"));
+        assert!(!doc.contains("kanidm_replication"));
+        assert!(doc.ends_with("# EOF\n"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_get_replication_status_cached() {
+        let s_uuid = Uuid::new_v4();
+        let (repl_ctrl_tx, mut repl_ctrl_rx) = mpsc::channel(4);
+
+        // Stand in for the replication task, counting how often the status is requested.
+        let repl_task = tokio::spawn(async move {
+            let mut requests = 0;
+            while let Some(ctrl) = repl_ctrl_rx.recv().await {
+                if let ReplCtrl::GetStatus { respond } = ctrl {
+                    requests += 1;
+                    let _ = respond.send(Some(test_repl_status(s_uuid)));
+                }
+            }
+            requests
+        });
+
+        let state = MetricsState::new(Some(repl_ctrl_tx));
+
+        for _ in 0..3 {
+            let doc = scrape(&state).await;
+            assert!(doc.is_some());
+            let Some(doc) = doc else { return };
+
+            let conflicts = format!("kanidm_replication_conflicts{{server=\"{}\"}} 2", s_uuid);
+            let lag = format!(
+                "kanidm_replication_lag{{partner=\"repl://b.example.com:8444\",server=\"{}\"}} 5",
+                s_uuid
+            );
+            assert!(doc.lines().any(|l| l == conflicts));
+            assert!(doc.lines().any(|l| l == lag));
+        }
+
+        // Once expired, the next scrape requests the status again.
+        {
+            let mut cache = state.repl_status_cache.lock().await;
+            if let Some((fetched_at, _)) = cache.as_mut() {
+                if let Some(expired) = fetched_at.checked_sub(REPL_STATUS_CACHE_TTL) {
+                    *fetched_at = expired;
+                }
+            }
+        }
+        assert!(scrape(&state).await.is_some());
+
+        drop(state);
+        assert_eq!(repl_task.await.ok(), Some(2));
+    }
+}
@@ -5,18 +5,29 @@ use tokio_util::codec::{Decoder, Encoder};
 
 use kanidmd_lib::repl::proto::{ReplIncrementalContext, ReplRefreshContext, ReplRuvRange};
 
+/// Messages sent by the node that opened the connection. This is normally a consumer, but
+/// for push replication it is the supplier.
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ConsumerRequest {
     Ping,
     Incremental(ReplRuvRange),
     Refresh,
+    /// The supplier wishes to push changes, and needs the RUV of the consumer.
+    PushOffer,
+    PushIncremental(ReplIncrementalContext),
+    PushRefresh(ReplRefreshContext),
 }
 
+/// Messages sent by the node that accepted the connection.
 #[derive(Serialize, Deserialize, Debug)]
 pub enum SupplierResponse {
     Pong,
     Incremental(ReplIncrementalContext),
     Refresh(ReplRefreshContext),
+    PushRuvRange(ReplRuvRange),
+    PushApplied,
+    /// The consumer is unable to apply the pushed changes and must be refreshed.
+    PushRefreshRequired,
 }
 
 #[derive(Default)]
@@ -1,13 +1,16 @@
 use openssl::{
     pkey::{PKey, Private},
     ssl::{Ssl, SslAcceptor, SslConnector, SslMethod, SslVerifyMode},
-    x509::{store::X509StoreBuilder, X509},
+    x509::{store::X509StoreBuilder, X509Ref, X509},
 };
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
@@ -26,11 +29,13 @@ use uuid::Uuid;
 use futures_util::sink::SinkExt;
 use futures_util::stream::StreamExt;
 
+use kanidmd_lib::idm::audit::{AuditEvent, AuditSource};
 use kanidmd_lib::prelude::duration_from_epoch_now;
 use kanidmd_lib::prelude::IdmServer;
-use kanidmd_lib::repl::proto::ConsumerState;
+use kanidmd_lib::repl::proto::{ConsumerState, ReplRuvRange};
 use kanidmd_lib::server::QueryServerTransaction;
 
+use crate::admin::{ReplicationPartnerStatus, ReplicationRuvStatus, ReplicationStatus};
 use crate::config::RepNodeConfig;
 use crate::config::ReplicationConfiguration;
 use crate::CoreAction;
@@ -49,6 +54,9 @@ pub(crate) enum ReplCtrl {
     RefreshConsumer {
         respond: oneshot::Sender<mpsc::Receiver<()>>,
     },
+    GetStatus {
+        respond: oneshot::Sender<Option<ReplicationStatus>>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +65,50 @@ enum ReplConsumerCtrl {
     Refresh(Arc<Mutex<(bool, mpsc::Sender<()>)>>),
 }
 
+#[derive(Debug, Clone, Copy)]
+enum ReplTaskMode {
+    /// Connect to the partner and consume its changes.
+    Pull { automatic_refresh: bool },
+    /// Connect to the partner, supply our changes to it, and then consume its changes.
+    Push { automatic_refresh: bool },
+}
+
+/// A node that is allowed to connect to this server, identified by its certificate.
+#[derive(Debug, Clone)]
+struct ReplPeer {
+    origin: Url,
+    cert_der: Vec<u8>,
+    allow_pull: bool,
+    /// If the peer may push changes to us, and if so whether it may refresh this server.
+    allow_push: Option<bool>,
+}
+
+/// What this server knows of a replication partner, for status reporting.
+#[derive(Debug, Default)]
+struct ReplPartnerState {
+    /// When this server last consumed changes from the partner.
+    last_consumed: Option<Duration>,
+    /// When this server last supplied changes to the partner.
+    last_supplied: Option<Duration>,
+    /// When replication with the partner last failed.
+    last_failure: Option<Duration>,
+    /// The RUV the partner reported the last time it was supplied.
+    partner_ruv: Option<ReplRuvRange>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ReplPartners {
+    inner: Arc<Mutex<BTreeMap<Url, ReplPartnerState>>>,
+}
+
+impl ReplPartners {
+    async fn update<F: FnOnce(&mut ReplPartnerState, Duration)>(&self, origin: &Url, f: F) {
+        let ct = duration_from_epoch_now();
+        let mut inner = self.inner.lock().await;
+        f(inner.entry(origin.clone()).or_default(), ct)
+    }
+}
+
 pub(crate) async fn create_repl_server(
     idms: Arc<IdmServer>,
     repl_config: &ReplicationConfiguration,
@@ -159,6 +211,14 @@ async fn repl_consumer_connect_supplier(
     None
 }
 
+/// A refresh replaces the content of this server, so it is always audited.
+fn refresh_audit_event(supplier: SocketAddr, ct: Duration) -> AuditEvent {
+    AuditEvent::ReplicationRefresh {
+        source: AuditSource::Replication(supplier.ip()),
+        time: OffsetDateTime::UNIX_EPOCH + ct,
+    }
+}
+
 /// This returns the socket address that worked, so you can try that first next time
 #[instrument(level="info", skip(refresh_coord, tls_connector, idms), fields(uuid=Uuid::new_v4().to_string()))]
 async fn repl_run_consumer_refresh(
@@ -199,7 +259,7 @@ async fn repl_run_consumer_refresh(
                 // Success - return to bypass the error message.
                 changes
             }
-            SupplierResponse::Pong | SupplierResponse::Incremental(_) => {
+            _ => {
                 error!("Supplier Response contains invalid State");
                 return Err(());
             }
@@ -217,7 +277,12 @@ async fn repl_run_consumer_refresh(
         write_txn
             .qs_write
             .consumer_apply_refresh(&refresh)
-            .and_then(|cs| write_txn.commit().map(|()| cs))
+            .and_then(|cs| {
+                write_txn
+                    .qs_write
+                    .audit_event_on_commit(refresh_audit_event(addr, ct));
+                write_txn.commit().map(|()| cs)
+            })
             .map_err(|err| error!(?err, "Consumer was not able to apply refresh."))?;
     }
 
@@ -249,6 +314,19 @@ async fn repl_run_consumer(
         return None;
     };
 
+    repl_consumer_incremental(&mut supplier_conn, socket_addr, automatic_refresh, idms)
+        .await
+        .then_some(socket_addr)
+}
+
+/// Consume changes from a supplier over an established connection, refreshing this server if
+/// required and permitted. Returns true if this server is now up to date with the supplier.
+async fn repl_consumer_incremental<S: AsyncRead + AsyncWrite + Unpin>(
+    supplier_conn: &mut Framed<S, codec::ConsumerCodec>,
+    socket_addr: SocketAddr,
+    automatic_refresh: bool,
+    idms: &IdmServer,
+) -> bool {
     // Perform incremental.
     let consumer_ruv_range = {
         let mut read_txn = idms.proxy_read().await;
@@ -259,7 +337,7 @@ async fn repl_run_consumer(
                     ?err,
                     "consumer ruv range could not be accessed, unable to continue."
                 );
-                return None;
+                return false;
             }
         }
     };
@@ -269,7 +347,7 @@ async fn repl_run_consumer(
         .await
     {
         error!(?err, "consumer encode error, unable to continue.");
-        return None;
+        return false;
     }
 
     let changes = if let Some(codec_msg) = supplier_conn.next().await {
@@ -278,18 +356,18 @@ async fn repl_run_consumer(
                 // Success - return to bypass the error message.
                 changes
             }
-            Ok(SupplierResponse::Pong) | Ok(SupplierResponse::Refresh(_)) => {
+            Ok(_) => {
                 error!("Supplier Response contains invalid State");
-                return None;
+                return false;
             }
             Err(err) => {
                 error!(?err, "consumer decode error, unable to continue.");
-                return None;
+                return false;
             }
         }
     } else {
         error!("Connection closed");
-        return None;
+        return false;
     };
 
     // Now apply the changes if possible
@@ -304,7 +382,7 @@ async fn repl_run_consumer(
             Ok(state) => state,
             Err(err) => {
                 error!(?err, "consumer was not able to apply changes.");
-                return None;
+                return false;
             }
         }
     };
@@ -313,21 +391,21 @@ async fn repl_run_consumer(
         ConsumerState::Ok => {
             info!("Incremental Replication Success");
             // return to bypass the failure message.
-            return Some(socket_addr);
+            return true;
         }
         ConsumerState::RefreshRequired => {
             if automatic_refresh {
                 warn!("Consumer is out of date and must be refreshed. This will happen *now*.");
             } else {
                 error!("Consumer is out of date and must be refreshed. You must manually resolve this situation.");
-                return None;
+                return false;
             };
         }
     }
 
     if let Err(err) = supplier_conn.send(ConsumerRequest::Refresh).await {
         error!(?err, "consumer encode error, unable to continue.");
-        return None;
+        return false;
     }
 
     let refresh = if let Some(codec_msg) = supplier_conn.next().await {
@@ -336,18 +414,18 @@ async fn repl_run_consumer(
                 // Success - return to bypass the error message.
                 changes
             }
-            Ok(SupplierResponse::Pong) | Ok(SupplierResponse::Incremental(_)) => {
+            Ok(_) => {
                 error!("Supplier Response contains invalid State");
-                return None;
+                return false;
             }
             Err(err) => {
                 error!(?err, "consumer decode error, unable to continue.");
-                return None;
+                return false;
             }
         }
     } else {
         error!("Connection closed");
-        return None;
+        return false;
     };
 
     // Now apply the refresh if possible
@@ -356,14 +434,151 @@ async fn repl_run_consumer(
     if let Err(err) = write_txn
         .qs_write
         .consumer_apply_refresh(&refresh)
-        .and_then(|cs| write_txn.commit().map(|()| cs))
+        .and_then(|cs| {
+            write_txn
+                .qs_write
+                .audit_event_on_commit(refresh_audit_event(socket_addr, ct));
+            write_txn.commit().map(|()| cs)
+        })
     {
         error!(?err, "consumer was not able to apply refresh.");
-        return None;
+        return false;
     }
 
     warn!("Replication refresh was successful.");
-    Some(socket_addr)
+    true
+}
+
+/// Supply changes to a consumer that is unable to connect to this server, and then consume
+/// its changes over the same connection. Returns the RUV the consumer reported before it was
+/// supplied, and if its changes were then consumed.
+#[instrument(level="info", skip(tls_connector, idms), fields(eventid=Uuid::new_v4().to_string()))]
+async fn repl_run_push(
+    domain: &str,
+    sock_addrs: &[SocketAddr],
+    tls_connector: &SslConnector,
+    automatic_refresh: bool,
+    idms: &IdmServer,
+    consumer_conn_settings: &ConsumerConnSettings,
+) -> Option<(ReplRuvRange, bool)> {
+    let Some((socket_addr, mut partner_conn)) =
+        repl_consumer_connect_supplier(domain, sock_addrs, tls_connector, consumer_conn_settings)
+            .await
+    else {
+        return None;
+    };
+
+    let consumer_ruv_range = repl_supplier_push(&mut partner_conn, socket_addr, idms).await?;
+
+    let consumed =
+        repl_consumer_incremental(&mut partner_conn, socket_addr, automatic_refresh, idms).await;
+
+    Some((consumer_ruv_range, consumed))
+}
+
+async fn repl_push_response<S: AsyncRead + AsyncWrite + Unpin>(
+    consumer_conn: &mut Framed<S, codec::ConsumerCodec>,
+) -> Option<SupplierResponse> {
+    match consumer_conn.next().await {
+        Some(Ok(response)) => Some(response),
+        Some(Err(err)) => {
+            error!(?err, "supplier decode error, unable to continue.");
+            None
+        }
+        None => {
+            error!("Connection closed");
+            None
+        }
+    }
+}
+
+/// Push our changes to a consumer, refreshing it if the consumer requires and permits this.
+async fn repl_supplier_push<S: AsyncRead + AsyncWrite + Unpin>(
+    consumer_conn: &mut Framed<S, codec::ConsumerCodec>,
+    socket_addr: SocketAddr,
+    idms: &IdmServer,
+) -> Option<ReplRuvRange> {
+    if let Err(err) = consumer_conn.send(ConsumerRequest::PushOffer).await {
+        error!(?err, "supplier encode error, unable to continue.");
+        return None;
+    }
+
+    let consumer_ruv_range = match repl_push_response(consumer_conn).await? {
+        SupplierResponse::PushRuvRange(ruv_range) => ruv_range,
+        _ => {
+            error!("Consumer Response contains invalid State");
+            return None;
+        }
+    };
+
+    let changes = {
+        let mut read_txn = idms.proxy_read().await;
+        match read_txn
+            .qs_read
+            .supplier_provide_changes(consumer_ruv_range.clone())
+        {
+            Ok(changes) => changes,
+            Err(err) => {
+                error!(?err, "supplier provide changes failed.");
+                return None;
+            }
+        }
+    };
+
+    if let Err(err) = consumer_conn
+        .send(ConsumerRequest::PushIncremental(changes))
+        .await
+    {
+        error!(?err, "supplier encode error, unable to continue.");
+        return None;
+    }
+
+    match repl_push_response(consumer_conn).await? {
+        SupplierResponse::PushApplied => {
+            info!("Push Replication Success");
+            return Some(consumer_ruv_range);
+        }
+        SupplierResponse::PushRefreshRequired => {
+            warn!(
+                ?socket_addr,
+                "Consumer is out of date and must be refreshed. This will happen *now*."
+            );
+        }
+        _ => {
+            error!("Consumer Response contains invalid State");
+            return None;
+        }
+    }
+
+    let refresh = {
+        let mut read_txn = idms.proxy_read().await;
+        match read_txn.qs_read.supplier_provide_refresh() {
+            Ok(refresh) => refresh,
+            Err(err) => {
+                error!(?err, "supplier provide refresh failed.");
+                return None;
+            }
+        }
+    };
+
+    if let Err(err) = consumer_conn
+        .send(ConsumerRequest::PushRefresh(refresh))
+        .await
+    {
+        error!(?err, "supplier encode error, unable to continue.");
+        return None;
+    }
+
+    match repl_push_response(consumer_conn).await? {
+        SupplierResponse::PushApplied => {
+            warn!("Consumer refresh was successful.");
+            Some(consumer_ruv_range)
+        }
+        _ => {
+            error!("Consumer Response contains invalid State");
+            None
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -373,6 +588,7 @@ struct ConsumerConnSettings {
     replica_connect_timeout: Duration,
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn repl_task(
     origin: Url,
     client_key: PKey<Private>,
@@ -380,7 +596,8 @@ async fn repl_task(
     supplier_cert: X509,
     consumer_conn_settings: ConsumerConnSettings,
     mut task_rx: broadcast::Receiver<ReplConsumerCtrl>,
-    automatic_refresh: bool,
+    mode: ReplTaskMode,
+    partners: ReplPartners,
     idms: Arc<IdmServer>,
 ) {
     if origin.scheme() != "repl" {
@@ -498,15 +715,50 @@ async fn repl_task(
             }
             _ = repl_interval.tick() => {
                 // Interval passed, attempt a replication run.
-                repl_run_consumer(
-                    domain,
-                    &sorted_socket_addrs,
-                    &tls_connector,
-                    automatic_refresh,
-                    &idms,
-                    &consumer_conn_settings
-                )
-                .await;
+                match mode {
+                    ReplTaskMode::Pull { automatic_refresh } => {
+                        let result = repl_run_consumer(
+                            domain,
+                            &sorted_socket_addrs,
+                            &tls_connector,
+                            automatic_refresh,
+                            &idms,
+                            &consumer_conn_settings
+                        )
+                        .await;
+
+                        partners.update(&origin, |partner, ct| match result {
+                            Some(_) => partner.last_consumed = Some(ct),
+                            None => partner.last_failure = Some(ct),
+                        })
+                        .await;
+                    }
+                    ReplTaskMode::Push { automatic_refresh } => {
+                        let result = repl_run_push(
+                            domain,
+                            &sorted_socket_addrs,
+                            &tls_connector,
+                            automatic_refresh,
+                            &idms,
+                            &consumer_conn_settings
+                        )
+                        .await;
+
+                        partners.update(&origin, |partner, ct| match result {
+                            Some((partner_ruv, consumed)) => {
+                                partner.last_supplied = Some(ct);
+                                partner.partner_ruv = Some(partner_ruv);
+                                if consumed {
+                                    partner.last_consumed = Some(ct);
+                                } else {
+                                    partner.last_failure = Some(ct);
+                                }
+                            }
+                            None => partner.last_failure = Some(ct),
+                        })
+                        .await;
+                    }
+                }
             }
         }
     }
@@ -514,12 +766,65 @@ async fn repl_task(
     info!("Replica task for {} has stopped.", origin);
 }
 
+/// The nodes that may connect to this server, and what each of them is permitted to do.
+fn repl_peers(replication_node_map: &BTreeMap<Url, RepNodeConfig>) -> Vec<ReplPeer> {
+    replication_node_map
+        .iter()
+        .filter_map(|(origin, node)| {
+            let (cert, allow_push) = match node {
+                RepNodeConfig::MutualPull {
+                    partner_cert: cert,
+                    automatic_refresh: _,
+                }
+                | RepNodeConfig::AllowPull {
+                    consumer_cert: cert,
+                } => (cert, None),
+                // A partner that can push to us may also pull, since it is already able
+                // to write to our database.
+                RepNodeConfig::AllowPush {
+                    partner_cert,
+                    automatic_refresh,
+                } => (partner_cert, Some(*automatic_refresh)),
+                RepNodeConfig::Pull {
+                    supplier_cert: _,
+                    automatic_refresh: _,
+                }
+                | RepNodeConfig::Push {
+                    partner_cert: _,
+                    automatic_refresh: _,
+                } => return None,
+            };
+
+            match cert.to_der() {
+                Ok(cert_der) => Some(ReplPeer {
+                    origin: origin.clone(),
+                    cert_der,
+                    allow_pull: true,
+                    allow_push,
+                }),
+                Err(err) => {
+                    error!(?err, %origin, "CRITICAL, unable to encode partner certificate.");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Find the peer that presented this certificate.
+fn repl_peer_for_cert<'a>(peers: &'a [ReplPeer], cert: &X509Ref) -> Option<&'a ReplPeer> {
+    let cert_der = cert.to_der().ok()?;
+    peers.iter().find(|peer| peer.cert_der == cert_der)
+}
+
 #[instrument(level = "info", skip_all)]
 async fn handle_repl_conn(
     max_frame_bytes: usize,
     tcpstream: TcpStream,
     client_address: SocketAddr,
     tls_parms: SslAcceptor,
+    peers: Arc<Vec<ReplPeer>>,
+    partners: ReplPartners,
     idms: Arc<IdmServer>,
 ) {
     debug!(?client_address, "replication client connected 🛫");
@@ -537,7 +842,44 @@ async fn handle_repl_conn(
         error!(?err, "LDAP TLS accept error, disconnecting client");
         return;
     };
-    let (r, w) = tokio::io::split(tlsstream);
+
+    // The tls acceptor has already verified the peer certificate is one from our replication
+    // map, now determine which node it is so we know what it is permitted to do.
+    let Some(peer) = tlsstream
+        .ssl()
+        .peer_certificate()
+        .and_then(|cert| repl_peer_for_cert(&peers, &cert).cloned())
+    else {
+        error!(
+            ?client_address,
+            "replication client certificate is not a known partner, disconnecting client"
+        );
+        return;
+    };
+
+    repl_supplier_conn(
+        max_frame_bytes,
+        tlsstream,
+        client_address,
+        &peer,
+        &partners,
+        &idms,
+    )
+    .await;
+
+    debug!(?client_address, "replication client disconnected 🛬");
+}
+
+/// Respond to the requests of a connected peer, within what the peer is permitted to do.
+async fn repl_supplier_conn<S: AsyncRead + AsyncWrite>(
+    max_frame_bytes: usize,
+    stream: S,
+    client_address: SocketAddr,
+    peer: &ReplPeer,
+    partners: &ReplPartners,
+    idms: &IdmServer,
+) {
+    let (r, w) = tokio::io::split(stream);
     let mut r = FramedRead::new(r, codec::SupplierCodec::new(max_frame_bytes));
     let mut w = FramedWrite::new(w, codec::SupplierCodec::new(max_frame_bytes));
 
@@ -551,11 +893,16 @@ async fn handle_repl_conn(
                 }
             }
             Ok(ConsumerRequest::Incremental(consumer_ruv_range)) => {
+                if !peer.allow_pull {
+                    error!(origin = %peer.origin, "replication partner is not permitted to pull, disconnecting client");
+                    break;
+                }
+
                 let mut read_txn = idms.proxy_read().await;
 
                 let changes = match read_txn
                     .qs_read
-                    .supplier_provide_changes(consumer_ruv_range)
+                    .supplier_provide_changes(consumer_ruv_range.clone())
                 {
                     Ok(changes) => changes,
                     Err(err) => {
@@ -568,8 +915,20 @@ async fn handle_repl_conn(
                     error!(?err, "supplier encode error, unable to continue.");
                     break;
                 }
+
+                partners
+                    .update(&peer.origin, |partner, ct| {
+                        partner.last_supplied = Some(ct);
+                        partner.partner_ruv = Some(consumer_ruv_range);
+                    })
+                    .await;
             }
             Ok(ConsumerRequest::Refresh) => {
+                if !peer.allow_pull {
+                    error!(origin = %peer.origin, "replication partner is not permitted to pull, disconnecting client");
+                    break;
+                }
+
                 let mut read_txn = idms.proxy_read().await;
 
                 let changes = match read_txn.qs_read.supplier_provide_refresh() {
@@ -584,6 +943,109 @@ async fn handle_repl_conn(
                     error!(?err, "supplier encode error, unable to continue.");
                     break;
                 }
+
+                partners
+                    .update(&peer.origin, |partner, ct| partner.last_supplied = Some(ct))
+                    .await;
+            }
+            Ok(ConsumerRequest::PushOffer) => {
+                if peer.allow_push.is_none() {
+                    error!(origin = %peer.origin, "replication partner is not permitted to push, disconnecting client");
+                    break;
+                }
+
+                let ruv_range = {
+                    let mut read_txn = idms.proxy_read().await;
+                    match read_txn.qs_read.consumer_get_state() {
+                        Ok(ruv_range) => ruv_range,
+                        Err(err) => {
+                            error!(?err, "consumer ruv range could not be accessed.");
+                            break;
+                        }
+                    }
+                };
+
+                if let Err(err) = w.send(SupplierResponse::PushRuvRange(ruv_range)).await {
+                    error!(?err, "consumer encode error, unable to continue.");
+                    break;
+                }
+            }
+            Ok(ConsumerRequest::PushIncremental(changes)) => {
+                let Some(automatic_refresh) = peer.allow_push else {
+                    error!(origin = %peer.origin, "replication partner is not permitted to push, disconnecting client");
+                    break;
+                };
+
+                let consumer_state = {
+                    let ct = duration_from_epoch_now();
+                    let mut write_txn = idms.proxy_write(ct).await;
+                    match write_txn
+                        .qs_write
+                        .consumer_apply_changes(&changes)
+                        .and_then(|cs| write_txn.commit().map(|()| cs))
+                    {
+                        Ok(state) => state,
+                        Err(err) => {
+                            error!(?err, "consumer was not able to apply changes.");
+                            break;
+                        }
+                    }
+                };
+
+                let response = match consumer_state {
+                    ConsumerState::Ok => {
+                        info!("Incremental Replication Success");
+                        partners
+                            .update(&peer.origin, |partner, ct| partner.last_consumed = Some(ct))
+                            .await;
+                        SupplierResponse::PushApplied
+                    }
+                    ConsumerState::RefreshRequired if automatic_refresh => {
+                        warn!("Consumer is out of date and must be refreshed. This will happen *now*.");
+                        SupplierResponse::PushRefreshRequired
+                    }
+                    ConsumerState::RefreshRequired => {
+                        error!("Replication consumer is out of date and must be refreshed. Either enable automatic refresh for this partner, or manually refresh this server from it.");
+                        break;
+                    }
+                };
+
+                if let Err(err) = w.send(response).await {
+                    error!(?err, "consumer encode error, unable to continue.");
+                    break;
+                }
+            }
+            Ok(ConsumerRequest::PushRefresh(refresh)) => {
+                if peer.allow_push != Some(true) {
+                    error!(origin = %peer.origin, "replication partner is not permitted to refresh this server, disconnecting client");
+                    break;
+                }
+
+                let ct = duration_from_epoch_now();
+                let mut write_txn = idms.proxy_write(ct).await;
+                if let Err(err) = write_txn
+                    .qs_write
+                    .consumer_apply_refresh(&refresh)
+                    .and_then(|cs| {
+                        write_txn
+                            .qs_write
+                            .audit_event_on_commit(refresh_audit_event(client_address, ct));
+                        write_txn.commit().map(|()| cs)
+                    })
+                {
+                    error!(?err, "consumer was not able to apply refresh.");
+                    break;
+                }
+
+                warn!("Replication refresh was successful.");
+                partners
+                    .update(&peer.origin, |partner, ct| partner.last_consumed = Some(ct))
+                    .await;
+
+                if let Err(err) = w.send(SupplierResponse::PushApplied).await {
+                    error!(?err, "consumer encode error, unable to continue.");
+                    break;
+                }
             }
             Err(err) => {
                 error!(?err, "supplier decode error, unable to continue.");
@@ -591,8 +1053,97 @@ async fn handle_repl_conn(
             }
         }
     }
+}
 
-    debug!(?client_address, "replication client disconnected 🛬");
+fn repl_time_to_string(ts: Duration) -> String {
+    (OffsetDateTime::UNIX_EPOCH + ts)
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| ts.as_secs().to_string())
+}
+
+/// Summarise the replication state of this server and what we know of each partner.
+async fn repl_status(
+    idms: &IdmServer,
+    replication_node_map: &BTreeMap<Url, RepNodeConfig>,
+    partners: &ReplPartners,
+) -> Option<ReplicationStatus> {
+    let mut read_txn = idms.proxy_read().await;
+
+    let ranges = match read_txn.qs_read.consumer_get_state() {
+        Ok(ReplRuvRange::V1 { ranges, .. }) => ranges,
+        Err(err) => {
+            error!(?err, "Unable to access replication update vector");
+            return None;
+        }
+    };
+
+    let conflicts = match read_txn.qs_read.replication_conflict_counts() {
+        Ok(conflicts) => conflicts,
+        Err(err) => {
+            error!(?err, "Unable to count replication conflicts");
+            return None;
+        }
+    };
+
+    let partner_states = partners.inner.lock().await;
+    let mut partner_status = Vec::with_capacity(replication_node_map.len());
+
+    for (origin, node) in replication_node_map.iter() {
+        let mode = match node {
+            RepNodeConfig::AllowPull { .. } => "allow-pull",
+            RepNodeConfig::Pull { .. } => "pull",
+            RepNodeConfig::MutualPull { .. } => "mutual-pull",
+            RepNodeConfig::AllowPush { .. } => "allow-push",
+            RepNodeConfig::Push { .. } => "push",
+        };
+
+        let state = partner_states.get(origin);
+
+        let lag = match state.and_then(|state| state.partner_ruv.as_ref()) {
+            Some(partner_ruv) => match read_txn.qs_read.supplier_consumer_lag(partner_ruv) {
+                Ok(lag) => Some(lag),
+                Err(err) => {
+                    error!(?err, %origin, "Unable to determine partner replication lag");
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        partner_status.push(ReplicationPartnerStatus {
+            origin: origin.to_string(),
+            mode: mode.to_string(),
+            last_consumed: state
+                .and_then(|state| state.last_consumed)
+                .map(repl_time_to_string),
+            last_supplied: state
+                .and_then(|state| state.last_supplied)
+                .map(repl_time_to_string),
+            last_failure: state
+                .and_then(|state| state.last_failure)
+                .map(repl_time_to_string),
+            lag,
+        });
+    }
+
+    let ruv = ranges
+        .into_iter()
+        .map(|(s_uuid, range)| {
+            (
+                s_uuid,
+                ReplicationRuvStatus {
+                    min: repl_time_to_string(range.ts_min),
+                    max: repl_time_to_string(range.ts_max),
+                },
+            )
+        })
+        .collect();
+
+    Some(ReplicationStatus {
+        ruv,
+        conflicts,
+        partners: partner_status,
+    })
 }
 
 async fn repl_acceptor(
@@ -623,6 +1174,9 @@ async fn repl_acceptor(
     drop(task_rx1);
     let mut task_handles = VecDeque::new();
 
+    // The state of each partner is retained over reloads so it can be reported.
+    let partners = ReplPartners::default();
+
     // Create another broadcast to control the replication tasks and their need to reload.
 
     // Spawn a KRC communication task?
@@ -687,8 +1241,8 @@ async fn repl_acceptor(
 
         let mut client_certs = Vec::new();
 
-        // For each node in the map, either spawn a task to pull from that node,
-        // or setup the node as allowed to pull from us.
+        // For each node in the map, either spawn a task to pull from or push to that node,
+        // or setup the node as allowed to pull from or push to us.
         for (origin, node) in replication_node_map.iter() {
             // Setup client certs
             match node {
@@ -696,16 +1250,24 @@ async fn repl_acceptor(
                     partner_cert: consumer_cert,
                     automatic_refresh: _,
                 }
-                | RepNodeConfig::AllowPull { consumer_cert } => {
-                    client_certs.push(consumer_cert.clone())
+                | RepNodeConfig::AllowPull { consumer_cert }
+                | RepNodeConfig::AllowPush {
+                    partner_cert: consumer_cert,
+                    automatic_refresh: _,
+                } => {
+                    client_certs.push(consumer_cert.clone());
                 }
                 RepNodeConfig::Pull {
                     supplier_cert: _,
                     automatic_refresh: _,
+                }
+                | RepNodeConfig::Push {
+                    partner_cert: _,
+                    automatic_refresh: _,
                 } => {}
             };
 
-            match node {
+            let (supplier_cert, mode) = match node {
                 RepNodeConfig::MutualPull {
                     partner_cert: supplier_cert,
                     automatic_refresh,
@@ -713,27 +1275,50 @@ async fn repl_acceptor(
                 | RepNodeConfig::Pull {
                     supplier_cert,
                     automatic_refresh,
-                } => {
-                    let task_rx = task_tx.subscribe();
-
-                    let handle: JoinHandle<()> = tokio::spawn(repl_task(
-                        origin.clone(),
-                        server_key.clone(),
-                        server_cert.clone(),
-                        supplier_cert.clone(),
-                        consumer_conn_settings.clone(),
-                        task_rx,
-                        *automatic_refresh,
-                        idms.clone(),
-                    ));
-
-                    task_handles.push_back(handle);
-                    debug_assert!(task_handles.len() == task_tx.receiver_count());
-                }
-                RepNodeConfig::AllowPull { consumer_cert: _ } => {}
+                } => (
+                    supplier_cert,
+                    ReplTaskMode::Pull {
+                        automatic_refresh: *automatic_refresh,
+                    },
+                ),
+                RepNodeConfig::Push {
+                    partner_cert,
+                    automatic_refresh,
+                } => (
+                    partner_cert,
+                    ReplTaskMode::Push {
+                        automatic_refresh: *automatic_refresh,
+                    },
+                ),
+                RepNodeConfig::AllowPull { consumer_cert: _ }
+                | RepNodeConfig::AllowPush {
+                    partner_cert: _,
+                    automatic_refresh: _,
+                } => continue,
             };
+
+            let task_rx = task_tx.subscribe();
+
+            let handle: JoinHandle<()> = tokio::spawn(repl_task(
+                origin.clone(),
+                server_key.clone(),
+                server_cert.clone(),
+                supplier_cert.clone(),
+                consumer_conn_settings.clone(),
+                task_rx,
+                mode,
+                partners.clone(),
+                idms.clone(),
+            ));
+
+            task_handles.push_back(handle);
+            debug_assert!(task_handles.len() == task_tx.receiver_count());
         }
 
+        // Connecting peers are identified by their certificate, so that we can check what
+        // they are permitted to do.
+        let peers = Arc::new(repl_peers(&replication_node_map));
+
         // ⚠️  This section is critical to the security of replication
         //    Since replication relies on mTLS we MUST ensure these options
         //    are absolutely correct!
@@ -838,6 +1423,20 @@ async fn repl_acceptor(
                             // Start a reload.
                             continue 'event;
                         }
+                        ReplCtrl::GetStatus {
+                            respond
+                        } => {
+                            let span = debug_span!("supplier_accept_loop", uuid = ?eventid);
+                            let status = repl_status(&idms, &replication_node_map, &partners)
+                                .instrument(span)
+                                .await;
+
+                            if respond.send(status).is_err() {
+                                warn!("Replication status was requested, but requester disconnected");
+                            } else {
+                                trace!("Sent replication status via control channel");
+                            }
+                        }
                         ReplCtrl::RefreshConsumer {
                             respond
                         } => {
@@ -880,7 +1479,15 @@ async fn repl_acceptor(
                             // We don't care about the join handle here - once a client connects
                             // it sticks to whatever ssl settings it had at launch.
                             tokio::spawn(
-                                handle_repl_conn(max_frame_bytes, tcpstream, client_socket_addr, clone_tls_acceptor, clone_idms)
+                                handle_repl_conn(
+                                    max_frame_bytes,
+                                    tcpstream,
+                                    client_socket_addr,
+                                    clone_tls_acceptor,
+                                    peers.clone(),
+                                    partners.clone(),
+                                    clone_idms
+                                )
                             );
                         }
                         Err(e) => {
@@ -906,3 +1513,230 @@ async fn repl_acceptor(
 
     info!("Stopped {}", super::TaskName::Replication);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kanidm_lib_crypto::mtls::build_self_signed_server_and_client_identity;
+    use kanidmd_lib::prelude::{
+        Attribute, Entry, EntryClass, EntryInit, EntryNew, IdmServerAudit, IdmServerDelayed, Value,
+    };
+    use kanidmd_lib::testkit::setup_pair_test;
+
+    const MAX_FRAME_BYTES: usize = 268435456;
+
+    type IdmPair = (IdmServer, IdmServerDelayed, IdmServerAudit);
+
+    /// Two servers of the same domain, where the second has been refreshed from the first.
+    async fn setup_idm_pair() -> Option<(IdmPair, IdmPair)> {
+        let (qs_a, qs_b) = setup_pair_test().await;
+        let ct = duration_from_epoch_now();
+        qs_a.initialise_helper(ct).await.ok()?;
+        qs_b.initialise_helper(ct).await.ok()?;
+
+        let idms_a = IdmServer::new(qs_a, "https://idm.example.com").await.ok()?;
+        let idms_b = IdmServer::new(qs_b, "https://idm.example.com").await.ok()?;
+
+        let refresh = {
+            let mut read_txn = idms_a.0.proxy_read().await;
+            read_txn.qs_read.supplier_provide_refresh().ok()?
+        };
+
+        let mut write_txn = idms_b.0.proxy_write(ct).await;
+        write_txn
+            .qs_write
+            .consumer_apply_refresh(&refresh)
+            .and_then(|_| write_txn.commit())
+            .ok()?;
+
+        Some((idms_a, idms_b))
+    }
+
+    fn test_peer(allow_push: Option<bool>) -> Option<ReplPeer> {
+        Some(ReplPeer {
+            origin: Url::parse("repl://a.example.com:8444").ok()?,
+            cert_der: Vec::with_capacity(0),
+            allow_pull: true,
+            allow_push,
+        })
+    }
+
+    /// Push the changes of the supplier to the consumer over an in memory connection,
+    /// where the consumer is serving the supplier as `peer`.
+    async fn push(
+        supplier: &IdmServer,
+        consumer: &IdmServer,
+        peer: &ReplPeer,
+        partners: &ReplPartners,
+    ) -> Option<ReplRuvRange> {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let addr = SocketAddr::from(([127, 0, 0, 1], 8444));
+
+        let supplier_push = async move {
+            let mut consumer_conn = Framed::new(client, codec::ConsumerCodec::new(MAX_FRAME_BYTES));
+            // The connection is closed once the push completes, ending the consumer.
+            repl_supplier_push(&mut consumer_conn, addr, supplier).await
+        };
+
+        let (pushed, ()) = tokio::join!(
+            supplier_push,
+            repl_supplier_conn(MAX_FRAME_BYTES, server, addr, peer, partners, consumer)
+        );
+        pushed
+    }
+
+    async fn create_person(idms: &IdmServer, name: &str) -> Option<Uuid> {
+        let uuid = Uuid::new_v4();
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname(name)),
+            (Attribute::Uuid, Value::Uuid(uuid)),
+            (Attribute::DisplayName, Value::new_utf8s(name))
+        );
+
+        let mut write_txn = idms.proxy_write(duration_from_epoch_now()).await;
+        write_txn
+            .qs_write
+            .internal_create(vec![e1])
+            .and_then(|_| write_txn.commit())
+            .ok()?;
+        Some(uuid)
+    }
+
+    async fn exists(idms: &IdmServer, uuid: Uuid) -> bool {
+        let mut read_txn = idms.proxy_read().await;
+        read_txn.qs_read.internal_search_uuid(uuid).is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_repl_push() {
+        let pair = setup_idm_pair().await;
+        assert!(pair.is_some());
+        let Some(((idms_a, _delayed_a, _audit_a), (idms_b, _delayed_b, _audit_b))) = pair else {
+            return;
+        };
+        let Some(peer) = test_peer(Some(false)) else {
+            return;
+        };
+        let partners = ReplPartners::default();
+
+        let person = create_person(&idms_a, "testperson1").await;
+        assert!(person.is_some());
+        let Some(person) = person else { return };
+        assert!(!exists(&idms_b, person).await);
+
+        assert!(push(&idms_a, &idms_b, &peer, &partners).await.is_some());
+        assert!(exists(&idms_b, person).await);
+
+        // The consumer records that it consumed the changes of the supplier.
+        let last_consumed = partners
+            .inner
+            .lock()
+            .await
+            .get(&peer.origin)
+            .and_then(|partner| partner.last_consumed);
+        assert!(last_consumed.is_some());
+
+        // Pushing again when there are no changes succeeds.
+        assert!(push(&idms_a, &idms_b, &peer, &partners).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_repl_push_denied() {
+        let pair = setup_idm_pair().await;
+        assert!(pair.is_some());
+        let Some(((idms_a, _delayed_a, _audit_a), (idms_b, _delayed_b, _audit_b))) = pair else {
+            return;
+        };
+        // This peer may only pull, so the consumer disconnects when it offers to push.
+        let Some(peer) = test_peer(None) else {
+            return;
+        };
+        let partners = ReplPartners::default();
+
+        let person = create_person(&idms_a, "testperson1").await;
+        assert!(person.is_some());
+        let Some(person) = person else { return };
+
+        assert!(push(&idms_a, &idms_b, &peer, &partners).await.is_none());
+        assert!(!exists(&idms_b, person).await);
+        assert!(partners.inner.lock().await.is_empty());
+    }
+
+    #[test]
+    fn test_repl_peers() {
+        sketching::test_init();
+
+        let certs: Vec<_> = (0..5)
+            .filter_map(|_| {
+                build_self_signed_server_and_client_identity(Uuid::new_v4(), "localhost", 1)
+                    .ok()
+                    .map(|(_, cert)| cert)
+            })
+            .collect();
+        let [allow_pull, mutual_pull, allow_push, pull, push] = certs.as_slice() else {
+            assert_eq!(certs.len(), 5);
+            return;
+        };
+
+        let origin = |name: &str| Url::parse(&format!("repl://{}.example.com:8444", name)).ok();
+        let nodes: Option<BTreeMap<Url, RepNodeConfig>> = [
+            (
+                origin("allow-pull"),
+                RepNodeConfig::AllowPull {
+                    consumer_cert: allow_pull.clone(),
+                },
+            ),
+            (
+                origin("mutual-pull"),
+                RepNodeConfig::MutualPull {
+                    partner_cert: mutual_pull.clone(),
+                    automatic_refresh: false,
+                },
+            ),
+            (
+                origin("allow-push"),
+                RepNodeConfig::AllowPush {
+                    partner_cert: allow_push.clone(),
+                    automatic_refresh: true,
+                },
+            ),
+            (
+                origin("pull"),
+                RepNodeConfig::Pull {
+                    supplier_cert: pull.clone(),
+                    automatic_refresh: false,
+                },
+            ),
+            (
+                origin("push"),
+                RepNodeConfig::Push {
+                    partner_cert: push.clone(),
+                    automatic_refresh: false,
+                },
+            ),
+        ]
+        .into_iter()
+        .map(|(origin, node)| origin.map(|origin| (origin, node)))
+        .collect();
+        assert!(nodes.is_some());
+        let Some(nodes) = nodes else { return };
+
+        let peers = repl_peers(&nodes);
+        // Only the nodes that connect to us are peers.
+        assert_eq!(peers.len(), 3);
+
+        let permissions = |cert: &X509| {
+            repl_peer_for_cert(&peers, cert).map(|peer| (peer.allow_pull, peer.allow_push))
+        };
+
+        assert_eq!(permissions(allow_pull), Some((true, None)));
+        assert_eq!(permissions(mutual_pull), Some((true, None)));
+        assert_eq!(permissions(allow_push), Some((true, Some(true))));
+        // We connect to these nodes, so they are not able to connect to us.
+        assert_eq!(permissions(pull), None);
+        assert_eq!(permissions(push), None);
+    }
+}
@@ -40,6 +40,7 @@ use kanidmd_core::{
 use sketching::tracing_forest::traits::*;
 use sketching::tracing_forest::util::*;
 use sketching::tracing_forest::{self};
+use sketching::tracing_opentelemetry;
 use tokio::net::UnixStream;
 use tokio_util::codec::Framed;
 #[cfg(target_family = "windows")] // for windows builds
@@ -79,6 +80,7 @@ impl KanidmdOpt {
             }
             | KanidmdOpt::ShowReplicationCertificate { commonopts }
             | KanidmdOpt::RenewReplicationCertificate { commonopts }
+            | KanidmdOpt::ShowReplicationStatus { commonopts }
             | KanidmdOpt::RefreshReplicationConsumer { commonopts, .. } => commonopts,
             KanidmdOpt::RecoverAccount { commonopts, .. } => commonopts,
             KanidmdOpt::DbScan {
@@ -156,6 +158,31 @@ async fn submit_admin_req(path: &str, req: AdminTaskRequest, output_mode: Consol
                 info!(certificate = ?cert)
             }
         },
+        Some(Ok(AdminTaskResponse::ShowReplicationStatus { status })) => match output_mode {
+            ConsoleOutputMode::JSON => match serde_json::to_string(&status) {
+                Ok(status) => eprintln!("{}", status),
+                Err(err) => error!(?err, "Unable to serialise replication status"),
+            },
+            ConsoleOutputMode::Text => {
+                for (s_uuid, range) in status.ruv.iter() {
+                    info!(server_uuid = %s_uuid, min = %range.min, max = %range.max, "changes held");
+                }
+                for (s_uuid, count) in status.conflicts.iter() {
+                    info!(server_uuid = %s_uuid, %count, "replication conflicts");
+                }
+                for partner in status.partners.iter() {
+                    info!(
+                        origin = %partner.origin,
+                        mode = %partner.mode,
+                        last_consumed = ?partner.last_consumed,
+                        last_supplied = ?partner.last_supplied,
+                        last_failure = ?partner.last_failure,
+                        lag = ?partner.lag,
+                        "replication partner"
+                    );
+                }
+            }
+        },
         Some(Ok(AdminTaskResponse::Success)) => match output_mode {
             ConsoleOutputMode::JSON => {
                 eprintln!("\"success\"")
@@ -255,8 +282,23 @@ async fn kanidm_main() -> ExitCode {
     }
     .into();
 
+    // If configured, spans are also exported to an OpenTelemetry collector.
+    let maybe_tracer = match sconfig.as_ref().and_then(|val| val.otel_grpc_url.as_ref()) {
+        Some(otel_grpc_url) => match sketching::otel::otlp_tracer(otel_grpc_url, "kanidmd") {
+            Ok(tracer) => Some(tracer),
+            Err(e) => {
+                config_error.push(format!(
+                    "Unable to start OpenTelemetry export to {} - {:?}",
+                    otel_grpc_url, e
+                ));
+                None
+            }
+        },
+        None => None,
+    };
+
     // TODO: only send to stderr when we're not in a TTY
-    tracing_forest::worker_task()
+    let exit_code = tracing_forest::worker_task()
         .set_global(true)
         .set_tag(sketching::event_tagger)
         // Fall back to stderr
@@ -265,7 +307,9 @@ async fn kanidm_main() -> ExitCode {
 
         })
         .build_on(|subscriber|{
-            subscriber.with(log_filter)
+            subscriber.with(log_filter).with(
+                maybe_tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)),
+            )
         })
         .on(async {
             // Get information on the windows username
@@ -408,6 +452,7 @@ async fn kanidm_main() -> ExitCode {
                 // we aren't going to touch the DB so we can carry on
                 KanidmdOpt::ShowReplicationCertificate { .. }
                 | KanidmdOpt::RenewReplicationCertificate { .. }
+                | KanidmdOpt::ShowReplicationStatus { .. }
                 | KanidmdOpt::RefreshReplicationConsumer { .. }
                 | KanidmdOpt::RecoverAccount { .. }
                 | KanidmdOpt::HealthCheck(_) => (),
@@ -618,6 +663,16 @@ async fn kanidm_main() -> ExitCode {
                         output_mode,
                     ).await;
                 }
+                KanidmdOpt::ShowReplicationStatus {
+                    commonopts
+                } => {
+                    info!("Running show replication status ...");
+                    let output_mode: ConsoleOutputMode = commonopts.output_mode.to_owned().into();
+                    submit_admin_req(config.adminbindpath.as_str(),
+                        AdminTaskRequest::ShowReplicationStatus,
+                        output_mode,
+                    ).await;
+                }
                 KanidmdOpt::RefreshReplicationConsumer {
                     commonopts,
                     proceed
@@ -819,5 +874,10 @@ async fn kanidm_main() -> ExitCode {
             }
             ExitCode::SUCCESS
         })
-        .await
+        .await;
+
+    // Ensure any remaining spans are sent to the collector.
+    sketching::otel::shutdown();
+
+    exit_code
 }
@@ -164,6 +164,11 @@ enum KanidmdOpt {
         #[clap(flatten)]
         commonopts: CommonOpt,
     },
+    /// Display the replication state of this server and its partners
+    ShowReplicationStatus {
+        #[clap(flatten)]
+        commonopts: CommonOpt,
+    },
     /// Refresh this servers database content with the content from a supplier. This means
     /// that all local content will be deleted and replaced with the supplier content.
     RefreshReplicationConsumer {
@@ -624,6 +624,8 @@ pub enum DbValueSetV2 {
     IndexType(Vec<u16>),
     #[serde(rename = "RF")]
     Reference(Vec<Uuid>),
+    #[serde(rename = "RX")]
+    ReferenceExpiring(Vec<(Uuid, String)>),
     #[serde(rename = "JF")]
     JsonFilter(Vec<String>),
     #[serde(rename = "CR")]
@@ -678,6 +680,8 @@ pub enum DbValueSetV2 {
     Oauth2Session(Vec<DbValueOauth2Session>),
     #[serde(rename = "UH")]
     UiHint(Vec<u16>),
+    #[serde(rename = "CT")]
+    CredentialType(Vec<u16>),
     #[serde(rename = "TO")]
     TotpSecret(Vec<(String, DbTotpV1)>),
     #[serde(rename = "AT")]
@@ -701,6 +705,7 @@ impl DbValueSetV2 {
             DbValueSetV2::SyntaxType(set) => set.len(),
             DbValueSetV2::IndexType(set) => set.len(),
             DbValueSetV2::Reference(set) => set.len(),
+            DbValueSetV2::ReferenceExpiring(set) => set.len(),
             DbValueSetV2::JsonFilter(set) => set.len(),
             DbValueSetV2::Credential(set) => set.len(),
             DbValueSetV2::SecretValue(set) => set.len(),
@@ -729,6 +734,7 @@ impl DbValueSetV2 {
             DbValueSetV2::JwsKeyEs256(set) => set.len(),
             DbValueSetV2::JwsKeyRs256(set) => set.len(),
             DbValueSetV2::UiHint(set) => set.len(),
+            DbValueSetV2::CredentialType(set) => set.len(),
             DbValueSetV2::TotpSecret(set) => set.len(),
             DbValueSetV2::AuditLogString(set) => set.len(),
             DbValueSetV2::Image(set) => set.len(),
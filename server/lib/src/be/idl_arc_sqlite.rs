@@ -18,11 +18,13 @@ use crate::be::idl_sqlite::{
     IdlSqlite, IdlSqliteReadTransaction, IdlSqliteTransaction, IdlSqliteWriteTransaction,
 };
 use crate::be::idxkey::{
-    IdlCacheKey, IdlCacheKeyRef, IdlCacheKeyToRef, IdxKey, IdxKeyRef, IdxKeyToRef, IdxSlope,
+    IdlCacheKey, IdlCacheKeyRef, IdlCacheKeyToRef, IdxKey, IdxKeyRef, IdxKeyToRef, IdxRange,
+    IdxSlope,
 };
 use crate::be::keystorage::{KeyHandle, KeyHandleId};
 use crate::be::{BackendConfig, IdList, IdRawEntry};
 use crate::entry::{Entry, EntryCommitted, EntrySealed};
+use crate::metrics::{CacheKind, METRICS};
 use crate::prelude::*;
 use crate::value::{IndexType, Value};
 
@@ -90,15 +92,20 @@ macro_rules! get_identry {
         match $idl {
             IdList::Partial(idli) | IdList::PartialThreshold(idli) | IdList::Indexed(idli) => {
                 let mut nidl = IDLBitRange::new();
+                let mut misses = 0;
 
                 idli.into_iter().for_each(|i| {
                     // For all the id's in idl.
                     // is it in the cache?
                     match $self.entry_cache.get(&i) {
                         Some(eref) => result.push(eref.clone()),
-                        None => unsafe { nidl.push_id(i) },
+                        None => {
+                            misses += 1;
+                            unsafe { nidl.push_id(i) }
+                        }
                     }
                 });
+                METRICS.cache_lookup(CacheKind::Entry, result.len() as u64, misses);
 
                 if !nidl.is_empty() {
                     // Now, get anything from nidl that is needed.
@@ -118,13 +125,18 @@ macro_rules! get_identry {
                 // on miss to prevent scan/invalidation attacks.
                 let idli = (*$self.allids).clone();
                 let mut nidl = IDLBitRange::new();
+                let mut misses = 0;
 
                 (&idli)
                     .into_iter()
                     .for_each(|i| match $self.entry_cache.get(&i) {
                         Some(eref) => result.push(eref.clone()),
-                        None => unsafe { nidl.push_id(i) },
+                        None => {
+                            misses += 1;
+                            unsafe { nidl.push_id(i) }
+                        }
                     });
+                METRICS.cache_lookup(CacheKind::Entry, result.len() as u64, misses);
 
                 if !nidl.is_empty() {
                     // Now, get anything from nidl that is needed.
@@ -188,6 +200,7 @@ macro_rules! get_idl {
         let cache_r = $self.idl_cache.get(&cache_key as &dyn IdlCacheKeyToRef);
         // If hit, continue.
         if let Some(ref data) = cache_r {
+            METRICS.cache_lookup(CacheKind::Idl, 1, 0);
             trace!(
                 cached_index = ?$itype,
                 attr = ?$attr,
@@ -196,6 +209,7 @@ macro_rules! get_idl {
             return Ok(Some(data.as_ref().clone()));
         }
         // If miss, get from db *and* insert to the cache.
+        METRICS.cache_lookup(CacheKind::Idl, 0, 1);
         let db_r = $self.db.get_idl($attr, $itype, $idx_key)?;
         if let Some(ref idl) = db_r {
             let ncache_key = IdlCacheKey {
@@ -217,9 +231,11 @@ macro_rules! name2uuid {
         let cache_key = NameCacheKey::Name2Uuid($name.to_string());
         let cache_r = $self.name_cache.get(&cache_key);
         if let Some(NameCacheValue::U(uuid)) = cache_r {
+            METRICS.cache_lookup(CacheKind::Name, 1, 0);
             trace!(?uuid, "Got cached name2uuid");
             return Ok(Some(uuid.clone()));
         } else {
+            METRICS.cache_lookup(CacheKind::Name, 0, 1);
             trace!("Cache miss uuid for name2uuid");
         }
 
@@ -241,9 +257,11 @@ macro_rules! externalid2uuid {
         let cache_key = NameCacheKey::ExternalId2Uuid($name.to_string());
         let cache_r = $self.name_cache.get(&cache_key);
         if let Some(NameCacheValue::U(uuid)) = cache_r {
+            METRICS.cache_lookup(CacheKind::Name, 1, 0);
             trace!(?uuid, "Got cached externalid2uuid");
             return Ok(Some(uuid.clone()));
         } else {
+            METRICS.cache_lookup(CacheKind::Name, 0, 1);
             trace!("Cache miss uuid for externalid2uuid");
         }
 
@@ -265,9 +283,11 @@ macro_rules! uuid2spn {
         let cache_key = NameCacheKey::Uuid2Spn($uuid);
         let cache_r = $self.name_cache.get(&cache_key);
         if let Some(NameCacheValue::S(ref spn)) = cache_r {
+            METRICS.cache_lookup(CacheKind::Name, 1, 0);
             trace!(?spn, "Got cached uuid2spn");
             return Ok(Some(spn.as_ref().clone()));
         } else {
+            METRICS.cache_lookup(CacheKind::Name, 0, 1);
             trace!("Cache miss spn for uuid2spn");
         }
 
@@ -289,8 +309,10 @@ macro_rules! uuid2rdn {
         let cache_key = NameCacheKey::Uuid2Rdn($uuid);
         let cache_r = $self.name_cache.get(&cache_key);
         if let Some(NameCacheValue::R(ref rdn)) = cache_r {
+            METRICS.cache_lookup(CacheKind::Name, 1, 0);
             return Ok(Some(rdn.clone()));
         } else {
+            METRICS.cache_lookup(CacheKind::Name, 0, 1);
             trace!("Cache miss rdn for uuid2rdn");
         }
 
@@ -351,6 +373,14 @@ pub trait IdlArcSqliteTransaction {
         idx_key: &str,
     ) -> Result<Option<IDLBitRange>, OperationError>;
 
+    fn get_idl_range(
+        &mut self,
+        attr: &str,
+        itype: IndexType,
+        range: IdxRange,
+        idx_key: &str,
+    ) -> Result<Option<IDLBitRange>, OperationError>;
+
     fn get_db_s_uuid(&self) -> Result<Option<Uuid>, OperationError>;
 
     fn get_db_d_uuid(&self) -> Result<Option<Uuid>, OperationError>;
@@ -411,6 +441,25 @@ impl<'a> IdlArcSqliteTransaction for IdlArcSqliteReadTransaction<'a> {
         get_idl!(self, attr, itype, idx_key)
     }
 
+    #[instrument(level = "trace", skip_all)]
+    fn get_idl_range(
+        &mut self,
+        attr: &str,
+        itype: IndexType,
+        range: IdxRange,
+        idx_key: &str,
+    ) -> Result<Option<IDLBitRange>, OperationError> {
+        // A read can never observe uncommitted idls, so the db is always authoritative.
+        self.db
+            .get_idl_range(attr, itype, range, idx_key)
+            .map(|maybe_idls| {
+                maybe_idls.map(|idls| {
+                    idls.into_iter()
+                        .fold(IDLBitRange::new(), |acc, (_, idl)| acc | idl)
+                })
+            })
+    }
+
     fn get_db_s_uuid(&self) -> Result<Option<Uuid>, OperationError> {
         self.db.get_db_s_uuid()
     }
@@ -506,6 +555,41 @@ impl<'a> IdlArcSqliteTransaction for IdlArcSqliteWriteTransaction<'a> {
         get_idl!(self, attr, itype, idx_key)
     }
 
+    #[instrument(level = "trace", skip_all)]
+    fn get_idl_range(
+        &mut self,
+        attr: &str,
+        itype: IndexType,
+        range: IdxRange,
+        idx_key: &str,
+    ) -> Result<Option<IDLBitRange>, OperationError> {
+        let Some(db_idls) = self.db.get_idl_range(attr, itype, range, idx_key)? else {
+            return Ok(None);
+        };
+
+        let mut idls: BTreeMap<String, IDLBitRange> = db_idls.into_iter().collect();
+
+        // Idls written in this transaction are only in the cache until we commit, so
+        // they must replace their committed versions from the db. Idls removed in this
+        // transaction are cached as None, and must not be read from the db either.
+        self.idl_cache
+            .iter_dirty()
+            .filter(|(k, _)| k.a.as_str() == attr && k.i == itype && range.contains(&k.k, idx_key))
+            .for_each(|(k, maybe_idl)| match maybe_idl {
+                Some(idl) => {
+                    idls.insert(k.k.clone(), idl.as_ref().clone());
+                }
+                None => {
+                    idls.remove(&k.k);
+                }
+            });
+
+        Ok(Some(
+            idls.into_values()
+                .fold(IDLBitRange::new(), |acc, idl| acc | idl),
+        ))
+    }
+
     fn get_db_s_uuid(&self) -> Result<Option<Uuid>, OperationError> {
         self.db.get_db_s_uuid()
     }
@@ -850,6 +934,11 @@ impl<'a> IdlArcSqliteWriteTransaction<'a> {
 
         let mut data: HashMap<IdxKey, Vec<f64>> = HashMap::new();
         self.idl_cache.iter_dirty().for_each(|(k, maybe_idl)| {
+            // A range over an ordering index unions many keys, so the length of a single
+            // idl says nothing about its cost. These keep their default slope.
+            if k.i == IndexType::Ordering {
+                return;
+            }
             if let Some(idl) = maybe_idl {
                 let idl_len: u32 = idl.len().try_into().unwrap_or(u32::MAX);
                 // Convert to something we can use.
@@ -16,7 +16,7 @@ use uuid::Uuid;
 
 use crate::be::dbentry::{DbEntry, DbIdentSpn};
 use crate::be::dbvalue::DbCidV1;
-use crate::be::{BackendConfig, IdList, IdRawEntry, IdxKey, IdxSlope};
+use crate::be::{BackendConfig, IdList, IdRawEntry, IdxKey, IdxRange, IdxSlope};
 use crate::entry::{Entry, EntryCommitted, EntrySealed};
 use crate::prelude::*;
 use crate::value::{IndexType, Value};
@@ -284,6 +284,61 @@ pub(crate) trait IdlSqliteTransaction {
         Ok(Some(idl))
     }
 
+    /// Retrieve every key and idl of an ordering index that is within the range
+    /// relative to idx_key.
+    #[instrument(level = "trace", skip_all)]
+    fn get_idl_range(
+        &self,
+        attr: &str,
+        itype: IndexType,
+        range: IdxRange,
+        idx_key: &str,
+    ) -> Result<Option<Vec<(String, IDLBitRange)>>, OperationError> {
+        if !(self.exists_idx(attr, itype)?) {
+            debug!(
+                "IdlSqliteTransaction: Index {:?} {:?} not found",
+                itype, attr
+            );
+            return Ok(None);
+        }
+
+        let query = format!(
+            "SELECT key, idl FROM {}.idx_{}_{} WHERE key {} :idx_key",
+            self.get_db_name(),
+            itype.as_idx_str(),
+            attr,
+            range.as_sql_op()
+        );
+        let mut stmt = self.get_conn()?.prepare(&query).map_err(sqlite_error)?;
+
+        let idx_iter = stmt
+            .query_map(&[(":idx_key", &idx_key)], |row| {
+                Ok(KeyIdl {
+                    key: row.get(0)?,
+                    data: row.get(1)?,
+                })
+            })
+            .map_err(sqlite_error)?;
+        let idls = idx_iter
+            .map(|v| {
+                v.map_err(sqlite_error).and_then(|KeyIdl { key, data }| {
+                    serde_json::from_slice(data.as_slice())
+                        .map_err(serde_json_error)
+                        .map(|idl| (key, idl))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        trace!(
+            miss_index = ?itype,
+            attr = ?attr,
+            range = ?range,
+            keys = %idls.len(),
+        );
+
+        Ok(Some(idls))
+    }
+
     fn name2uuid(&mut self, name: &str) -> Result<Option<Uuid>, OperationError> {
         // The table exists - lets now get the actual index itself.
         let mut stmt = self
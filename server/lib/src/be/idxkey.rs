@@ -85,6 +85,33 @@ impl<'a> Hash for (dyn IdxKeyToRef + 'a) {
     }
 }
 
+// ===== idxrange ======
+
+/// The side of a key that a range lookup on an ordering index selects. Ordering
+/// index keys are strings that sort in the same order as the values they were
+/// generated from, so this is a simple string comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdxRange {
+    LessThan,
+    GreaterThan,
+}
+
+impl IdxRange {
+    pub fn as_sql_op(&self) -> &'static str {
+        match self {
+            IdxRange::LessThan => "<",
+            IdxRange::GreaterThan => ">",
+        }
+    }
+
+    pub fn contains(&self, key: &str, bound: &str) -> bool {
+        match self {
+            IdxRange::LessThan => key < bound,
+            IdxRange::GreaterThan => key > bound,
+        }
+    }
+}
+
 // ===== idlcachekey ======
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -41,7 +41,7 @@ mod idl_sqlite;
 pub(crate) mod idxkey;
 pub(crate) mod keystorage;
 
-pub(crate) use self::idxkey::{IdxKey, IdxKeyRef, IdxKeyToRef, IdxSlope};
+pub(crate) use self::idxkey::{IdxKey, IdxKeyRef, IdxKeyToRef, IdxRange, IdxSlope};
 use crate::be::idl_arc_sqlite::{
     IdlArcSqlite, IdlArcSqliteReadTransaction, IdlArcSqliteTransaction,
     IdlArcSqliteWriteTransaction,
@@ -255,6 +255,14 @@ pub trait BackendTransaction {
                     (IdList::AllIds, FilterPlan::SubUnindexed(attr.clone()))
                 }
             }
+            // These can never be indexed, so the entries must be checked by the filter test.
+            FilterResolved::SubPattern(attr, _, _) => (
+                IdList::AllIds,
+                FilterPlan::SubPatternUnindexed(attr.clone()),
+            ),
+            FilterResolved::EqRule(attr, _, _, _) => {
+                (IdList::AllIds, FilterPlan::EqRuleUnindexed(attr.clone()))
+            }
             FilterResolved::Pres(attr, idx) => {
                 if idx.is_some() {
                     // Get the idl for this
@@ -267,9 +275,50 @@ pub trait BackendTransaction {
                     (IdList::AllIds, FilterPlan::PresUnindexed(attr.clone()))
                 }
             }
-            FilterResolved::LessThan(attr, _subvalue, _idx) => {
-                // We have no process for indexing this right now.
-                (IdList::AllIds, FilterPlan::LessThanUnindexed(attr.clone()))
+            FilterResolved::LessThan(attr, subvalue, idx) => {
+                match (idx, subvalue.get_idx_ord_key()) {
+                    (Some(_), Some(idx_key)) => {
+                        // Get the union of the idls of every key below this one.
+                        match self.get_idlayer().get_idl_range(
+                            attr,
+                            IndexType::Ordering,
+                            IdxRange::LessThan,
+                            &idx_key,
+                        )? {
+                            Some(idl) => (
+                                IdList::Indexed(idl),
+                                FilterPlan::LessThanIndexed(attr.clone(), idx_key),
+                            ),
+                            None => (IdList::AllIds, FilterPlan::LessThanCorrupt(attr.clone())),
+                        }
+                    }
+                    // Schema believes this is not indexed, or the value has no order.
+                    _ => (IdList::AllIds, FilterPlan::LessThanUnindexed(attr.clone())),
+                }
+            }
+            FilterResolved::GreaterThan(attr, subvalue, idx) => {
+                match (idx, subvalue.get_idx_ord_key()) {
+                    (Some(_), Some(idx_key)) => {
+                        // Get the union of the idls of every key above this one.
+                        match self.get_idlayer().get_idl_range(
+                            attr,
+                            IndexType::Ordering,
+                            IdxRange::GreaterThan,
+                            &idx_key,
+                        )? {
+                            Some(idl) => (
+                                IdList::Indexed(idl),
+                                FilterPlan::GreaterThanIndexed(attr.clone(), idx_key),
+                            ),
+                            None => (IdList::AllIds, FilterPlan::GreaterThanCorrupt(attr.clone())),
+                        }
+                    }
+                    // Schema believes this is not indexed, or the value has no order.
+                    _ => (
+                        IdList::AllIds,
+                        FilterPlan::GreaterThanUnindexed(attr.clone()),
+                    ),
+                }
             }
             FilterResolved::Or(l, _) => {
                 // Importantly if this has no inner elements, this returns
@@ -1974,6 +2023,8 @@ fn get_idx_slope_default(ikey: &IdxKey) -> IdxSlope {
         (_, IndexType::Equality) => 45,
         (_, IndexType::SubString) => 90,
         (_, IndexType::Presence) => 90,
+        // A range may select most of the index, so assume it is about as good as presence.
+        (_, IndexType::Ordering) => 90,
     }
 }
 
@@ -2138,6 +2189,10 @@ mod tests {
                     attr: Attribute::TestNumber.into(),
                     itype: IndexType::Equality,
                 },
+                IdxKey {
+                    attr: Attribute::GidNumber.into(),
+                    itype: IndexType::Ordering,
+                },
             ];
 
             let be = Backend::new(BackendConfig::new_test("main"), idxmeta, false)
@@ -3379,6 +3434,125 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_be_index_search_ordering() {
+        sketching::test_init();
+
+        let idxmeta = vec![
+            IdxKey {
+                attr: Attribute::Uuid.into(),
+                itype: IndexType::Equality,
+            },
+            IdxKey {
+                attr: Attribute::GidNumber.into(),
+                itype: IndexType::Ordering,
+            },
+        ];
+
+        let be = Backend::new(BackendConfig::new_test("main"), idxmeta, false)
+            .expect("Failed to setup backend");
+
+        macro_rules! assert_indexed {
+            ($be:expr, $filt:expr, $expect:expr) => {{
+                let filt = filter_resolved!($filt);
+                let (r, _plan) = $be.filter2idl(filt.to_inner(), 0).unwrap();
+                match r {
+                    IdList::Indexed(idl) => {
+                        assert_eq!(idl, IDLBitRange::from_iter($expect));
+                    }
+                    _ => {
+                        panic!("");
+                    }
+                }
+            }};
+        }
+
+        let e1_uuid = uuid!("db237e8a-0079-4b8c-8a56-593b22aa44d1");
+
+        // Search while the idls are only in the write cache.
+        let mut be_txn = be.write().unwrap();
+        assert!(be_txn.reindex().is_ok());
+
+        let mut e1: Entry<EntryInit, EntryNew> = Entry::new();
+        e1.add_ava(Attribute::Uuid, Value::Uuid(e1_uuid));
+        e1.add_ava(Attribute::GidNumber, Value::Uint32(1000));
+        let e1 = e1.into_sealed_new();
+
+        let mut e2: Entry<EntryInit, EntryNew> = Entry::new();
+        e2.add_ava(
+            Attribute::Uuid,
+            Value::from("db237e8a-0079-4b8c-8a56-593b22aa44d2"),
+        );
+        e2.add_ava(Attribute::GidNumber, Value::Uint32(2000));
+        let e2 = e2.into_sealed_new();
+
+        let mut e3: Entry<EntryInit, EntryNew> = Entry::new();
+        e3.add_ava(
+            Attribute::Uuid,
+            Value::from("db237e8a-0079-4b8c-8a56-593b22aa44d3"),
+        );
+        let e3 = e3.into_sealed_new();
+
+        be_txn.create(&CID_ZERO, vec![e1, e2, e3]).unwrap();
+
+        assert_indexed!(
+            be_txn,
+            f_lt(Attribute::GidNumber, PartialValue::new_uint32(1500)),
+            vec![1]
+        );
+        assert_indexed!(
+            be_txn,
+            f_gt(Attribute::GidNumber, PartialValue::new_uint32(1500)),
+            vec![2]
+        );
+        assert_indexed!(
+            be_txn,
+            f_lt(Attribute::GidNumber, PartialValue::new_uint32(1000)),
+            vec![]
+        );
+        assert_indexed!(
+            be_txn,
+            f_gt(Attribute::GidNumber, PartialValue::new_uint32(999)),
+            vec![1, 2]
+        );
+        assert!(be_txn.commit().is_ok());
+
+        // Modify an entry, so that the cached idls must mask what was committed.
+        let mut be_txn = be.write().unwrap();
+        let filt = filter_resolved!(f_eq(Attribute::Uuid, PartialValue::Uuid(e1_uuid)));
+        let rset = be_txn.search(&Limits::unlimited(), &filt).unwrap();
+        let mut ce1 = rset[0].as_ref().clone().into_invalid();
+        ce1.purge_ava(Attribute::GidNumber);
+        ce1.add_ava(Attribute::GidNumber, Value::Uint32(3000));
+        let ce1 = ce1.into_sealed_committed();
+        be_txn.modify(&CID_ZERO, &rset, &[ce1]).unwrap();
+
+        assert_indexed!(
+            be_txn,
+            f_lt(Attribute::GidNumber, PartialValue::new_uint32(1500)),
+            vec![]
+        );
+        assert_indexed!(
+            be_txn,
+            f_gt(Attribute::GidNumber, PartialValue::new_uint32(1500)),
+            vec![1, 2]
+        );
+        assert!(be_txn.commit().is_ok());
+
+        // And now only from the db.
+        let mut be_txn = be.read().unwrap();
+        assert_indexed!(
+            be_txn,
+            f_lt(Attribute::GidNumber, PartialValue::new_uint32(2500)),
+            vec![2]
+        );
+        assert_indexed!(
+            be_txn,
+            f_gt(Attribute::GidNumber, PartialValue::new_uint32(2500)),
+            vec![1]
+        );
+    }
+
     #[test]
     fn test_be_index_search_missing() {
         run_test!(|be: &mut BackendWriteTransaction| {
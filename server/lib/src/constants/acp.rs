@@ -176,6 +176,88 @@ lazy_static! {
     };
 }
 
+lazy_static! {
+    pub static ref IDM_ADMINS_ACP_REPL_CONFLICT_V1: BuiltinAcp = BuiltinAcp {
+        uuid: UUID_IDM_ADMINS_ACP_REPL_CONFLICT_V1,
+        name: "idm_admins_acp_repl_conflict",
+        description: "Builtin IDM admin replication conflict search and discard permission.",
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlSearch,
+            EntryClass::AccessControlModify,
+        ],
+        receiver_group: UUID_SYSTEM_ADMINS,
+        target_scope: ProtoFilter::Eq(
+            Attribute::Class.to_string(),
+            EntryClass::Conflict.to_string()
+        ),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Name,
+            Attribute::Uuid,
+            Attribute::SourceUuid,
+            Attribute::LastModifiedCid,
+        ],
+        modify_removed_attrs: vec![Attribute::Class],
+        modify_classes: vec![EntryClass::Conflict],
+        ..Default::default()
+    };
+}
+
+lazy_static! {
+    pub static ref IDM_ACP_ACCESS_REVIEW_MANAGE_V1: BuiltinAcp = BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlCreate,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_access_review_manage",
+        uuid: UUID_IDM_ACP_ACCESS_REVIEW_MANAGE_V1,
+        description: "Builtin IDM Control for creating access reviews and viewing all of their items",
+        receiver_group: UUID_IDM_ADMINS,
+        target_scope: ProtoFilter::And(vec![
+            ProtoFilter::Or(vec![
+                match_class_filter!(EntryClass::AccessReview),
+                match_class_filter!(EntryClass::AccessReviewItem),
+            ]),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::AccessReviewExpiry,
+            Attribute::AccessReviewState,
+            Attribute::AccessReviewCampaign,
+            Attribute::AccessReviewGroup,
+            Attribute::AccessReviewMember,
+            Attribute::AccessReviewReviewer,
+            Attribute::AccessReviewDecision,
+            Attribute::AccessReviewDecidedBy,
+        ],
+        create_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::AccessReviewExpiry,
+            Attribute::AccessReviewState,
+            Attribute::AccessReviewCampaign,
+            Attribute::AccessReviewGroup,
+            Attribute::AccessReviewMember,
+            Attribute::AccessReviewReviewer,
+            Attribute::AccessReviewDecision,
+        ],
+        create_classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessReview,
+            EntryClass::AccessReviewItem,
+        ],
+        ..Default::default()
+    };
+}
+
 lazy_static! {
     pub static ref IDM_SELF_ACP_READ_V1: BuiltinAcp = BuiltinAcp {
         name: "idm_self_acp_read",
@@ -200,6 +282,7 @@ lazy_static! {
             Attribute::Mail,
             Attribute::RadiusSecret,
             Attribute::GidNumber,
+            Attribute::SubidRangeStart,
             Attribute::LoginShell,
             Attribute::Uuid,
             Attribute::SyncParentUuid,
@@ -344,9 +427,14 @@ lazy_static! {
             Attribute::Class,
             Attribute::MemberOf,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
             Attribute::DynMember,
             Attribute::Uuid,
             Attribute::GidNumber,
+            Attribute::SubidRangeStart,
             Attribute::LoginShell,
             Attribute::SshPublicKey,
         ],
@@ -647,6 +735,10 @@ lazy_static! {
             Attribute::Uuid,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
             Attribute::DynMember,
 
         ],
@@ -654,11 +746,19 @@ lazy_static! {
             Attribute::Name,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
         ],
         modify_removed_attrs: vec![
             Attribute::Name,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
         ],
         ..Default::default()
     };
@@ -977,17 +1077,29 @@ lazy_static! {
             Attribute::Uuid,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
             Attribute::DynMember,
         ],
         modify_removed_attrs: vec![
             Attribute::Name,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
         ],
         modify_present_attrs: vec![
             Attribute::Name,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
         ],
         ..Default::default()
     };
@@ -1239,6 +1351,10 @@ lazy_static! {
             Attribute::Name,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
         ],
         create_classes: vec![
             EntryClass::Object,
@@ -1307,6 +1423,10 @@ lazy_static! {
             Attribute::Name,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
         ],
         create_classes: vec![
             EntryClass::Object,
@@ -1437,6 +1557,54 @@ lazy_static! {
         ..Default::default()
 
     };
+
+    pub static ref IDM_ACP_GROUP_ACCOUNT_POLICY_MANAGE_PRIV_V1: BuiltinAcp = BuiltinAcp{
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlModify,
+            EntryClass::AccessControlSearch
+        ],
+        name: "idm_acp_group_account_policy_manage_priv",
+        uuid: UUID_IDM_ACP_GROUP_ACCOUNT_POLICY_MANAGE_PRIV_V1,
+        description: "Builtin IDM Control for managing account policy on groups",
+        receiver_group: UUID_SYSTEM_ADMINS,
+        // group not in Recycled, Tombstone. This does include HP groups as these are the
+        // most likely to need a stricter account policy.
+        target_scope: ProtoFilter::And(vec![
+            match_class_filter!(EntryClass::Group),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone()
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Name,
+            Attribute::Uuid,
+            Attribute::Spn,
+            Attribute::Description,
+            Attribute::CredentialTypeAllowed,
+            Attribute::AuthPasswordMinimumLength,
+            Attribute::AuthSessionExpiry,
+            Attribute::PrivilegeExpiry,
+        ],
+        modify_removed_attrs: vec![
+            Attribute::Class,
+            Attribute::CredentialTypeAllowed,
+            Attribute::AuthPasswordMinimumLength,
+            Attribute::AuthSessionExpiry,
+            Attribute::PrivilegeExpiry,
+        ],
+        modify_present_attrs: vec![
+            Attribute::Class,
+            Attribute::CredentialTypeAllowed,
+            Attribute::AuthPasswordMinimumLength,
+            Attribute::AuthSessionExpiry,
+            Attribute::PrivilegeExpiry,
+        ],
+        modify_classes: vec![
+            EntryClass::AccountPolicy,
+        ],
+        ..Default::default()
+    };
 }
 
 lazy_static! {
@@ -1519,6 +1687,10 @@ lazy_static! {
             Attribute::Spn,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
             Attribute::GidNumber,
         ],
         modify_removed_attrs: vec![
@@ -1617,6 +1789,10 @@ lazy_static! {
             Attribute::Spn,
             Attribute::Description,
             Attribute::Member,
+            Attribute::MemberExpiring,
+            Attribute::AccessRequestApprover,
+            Attribute::AccessRequestDuration,
+            Attribute::EntryManagedBy,
             Attribute::GidNumber,
         ],
         modify_removed_attrs: vec![
@@ -1661,6 +1837,7 @@ lazy_static! {
             Attribute::OAuth2RsOriginLanding,
             Attribute::OAuth2RsScopeMap,
             Attribute::OAuth2RsSupScopeMap,
+            Attribute::OAuth2RsClientCredentialsScopes,
             Attribute::OAuth2RsBasicSecret,
             Attribute::OAuth2RsTokenKey,
             Attribute::Es256PrivateKeyDer,
@@ -1668,6 +1845,8 @@ lazy_static! {
             Attribute::Rs256PrivateKeyDer,
             Attribute::OAuth2JwtLegacyCryptoEnable,
             Attribute::OAuth2PreferShortUsername,
+            Attribute::OAuth2DeviceFlowEnable,
+            Attribute::OAuth2RsTokenExchangeSource,
             Attribute::Image,
         ],
         modify_removed_attrs: vec![
@@ -1678,6 +1857,7 @@ lazy_static! {
             Attribute::OAuth2RsOriginLanding,
             Attribute::OAuth2RsScopeMap,
             Attribute::OAuth2RsSupScopeMap,
+            Attribute::OAuth2RsClientCredentialsScopes,
             Attribute::OAuth2RsBasicSecret,
             Attribute::OAuth2RsTokenKey,
             Attribute::Es256PrivateKeyDer,
@@ -1685,6 +1865,8 @@ lazy_static! {
             Attribute::Rs256PrivateKeyDer,
             Attribute::OAuth2JwtLegacyCryptoEnable,
             Attribute::OAuth2PreferShortUsername,
+            Attribute::OAuth2DeviceFlowEnable,
+            Attribute::OAuth2RsTokenExchangeSource,
             Attribute::Image,
         ],
         modify_present_attrs: vec![
@@ -1695,9 +1877,12 @@ lazy_static! {
             Attribute::OAuth2RsOriginLanding,
             Attribute::OAuth2RsSupScopeMap,
             Attribute::OAuth2RsScopeMap,
+            Attribute::OAuth2RsClientCredentialsScopes,
             Attribute::OAuth2AllowInsecureClientDisablePkce,
             Attribute::OAuth2JwtLegacyCryptoEnable,
             Attribute::OAuth2PreferShortUsername,
+            Attribute::OAuth2DeviceFlowEnable,
+            Attribute::OAuth2RsTokenExchangeSource,
             Attribute::Image,
         ],
         create_attrs: vec![
@@ -1709,9 +1894,12 @@ lazy_static! {
             Attribute::OAuth2RsOriginLanding,
             Attribute::OAuth2RsSupScopeMap,
             Attribute::OAuth2RsScopeMap,
+            Attribute::OAuth2RsClientCredentialsScopes,
             Attribute::OAuth2AllowInsecureClientDisablePkce,
             Attribute::OAuth2JwtLegacyCryptoEnable,
             Attribute::OAuth2PreferShortUsername,
+            Attribute::OAuth2DeviceFlowEnable,
+            Attribute::OAuth2RsTokenExchangeSource,
             Attribute::Image,
         ],
         create_classes: vec![
@@ -1792,6 +1980,7 @@ lazy_static! {
             Attribute::SyncCredentialPortal,
             Attribute::SyncYieldAuthority,
             Attribute::SyncCookie,
+            Attribute::SyncWritebackCookie,
         ],
         modify_removed_attrs: vec![
             Attribute::Name,
@@ -1800,6 +1989,7 @@ lazy_static! {
             Attribute::SyncTokenSession,
             Attribute::SyncCredentialPortal,
             Attribute::SyncCookie,
+            Attribute::SyncWritebackCookie,
             Attribute::SyncYieldAuthority,
         ],
         modify_present_attrs: vec![
@@ -1814,3 +2004,312 @@ lazy_static! {
         ..Default::default()
     };
 }
+
+lazy_static! {
+    pub static ref E_IDM_HP_ACP_SUDO_RULE_MANAGE_PRIV_V1: BuiltinAcp = BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlCreate,
+            EntryClass::AccessControlDelete,
+            EntryClass::AccessControlModify,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_hp_sudo_rule_manage_priv",
+        uuid: UUID_IDM_HP_ACP_SUDO_RULE_MANAGE_PRIV_V1,
+        description: "Builtin IDM Control for managing the sudo rules of unix hosts",
+        receiver_group: UUID_IDM_HP_SUDO_RULE_MANAGE_PRIV,
+        target_scope: ProtoFilter::And(vec![
+            match_class_filter!(EntryClass::SudoRule),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::SudoUser,
+            Attribute::SudoHost,
+            Attribute::SudoCommand,
+            Attribute::SudoRunas,
+            Attribute::SudoNopasswd,
+        ],
+        modify_removed_attrs: vec![
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::SudoUser,
+            Attribute::SudoHost,
+            Attribute::SudoCommand,
+            Attribute::SudoRunas,
+            Attribute::SudoNopasswd,
+        ],
+        modify_present_attrs: vec![
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::SudoUser,
+            Attribute::SudoHost,
+            Attribute::SudoCommand,
+            Attribute::SudoRunas,
+            Attribute::SudoNopasswd,
+        ],
+        create_attrs: vec![
+            Attribute::Class,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::SudoUser,
+            Attribute::SudoHost,
+            Attribute::SudoCommand,
+            Attribute::SudoRunas,
+            Attribute::SudoNopasswd,
+        ],
+        create_classes: vec![EntryClass::Object, EntryClass::SudoRule,],
+        ..Default::default()
+    };
+
+    pub static ref IDM_ACP_SUDO_RULE_READ_V1: BuiltinAcp = BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_sudo_rule_read",
+        uuid: UUID_IDM_ACP_SUDO_RULE_READ_V1,
+        description: "Builtin IDM Control for reading sudo rules - required by unix hosts to enforce them.",
+        receiver_group: UUID_IDM_ALL_HOSTS,
+        target_scope: ProtoFilter::And(vec![
+            match_class_filter!(EntryClass::SudoRule),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::SudoUser,
+            Attribute::SudoHost,
+            Attribute::SudoCommand,
+            Attribute::SudoRunas,
+            Attribute::SudoNopasswd,
+        ],
+        ..Default::default()
+    };
+}
+
+lazy_static! {
+    pub static ref E_IDM_HP_ACP_HOST_MANAGE_PRIV_V1: BuiltinAcp = BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlCreate,
+            EntryClass::AccessControlDelete,
+            EntryClass::AccessControlModify,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_hp_host_manage_priv",
+        uuid: UUID_IDM_HP_ACP_HOST_MANAGE_PRIV_V1,
+        description: "Builtin IDM Control for managing hosts and their api tokens",
+        receiver_group: UUID_IDM_HP_HBAC_MANAGE_PRIV,
+        // Host which is not in HP, Recycled, Tombstone
+        target_scope: ProtoFilter::And(vec![
+            match_class_filter!(EntryClass::Host),
+            ProtoFilter::AndNot(Box::new(FILTER_HP_OR_RECYCLED_OR_TOMBSTONE.clone())),
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::Spn,
+            Attribute::DisplayName,
+            Attribute::Description,
+            Attribute::MemberOf,
+            Attribute::ApiTokenSession,
+        ],
+        modify_removed_attrs: vec![
+            Attribute::Name,
+            Attribute::DisplayName,
+            Attribute::Description,
+            Attribute::ApiTokenSession,
+        ],
+        modify_present_attrs: vec![
+            Attribute::Name,
+            Attribute::DisplayName,
+            Attribute::Description,
+            Attribute::ApiTokenSession,
+        ],
+        create_attrs: vec![
+            Attribute::Class,
+            Attribute::Name,
+            Attribute::DisplayName,
+            Attribute::Description,
+        ],
+        create_classes: vec![
+            EntryClass::Object,
+            EntryClass::Account,
+            EntryClass::ServiceAccount,
+            EntryClass::Host,
+        ],
+        ..Default::default()
+    };
+
+    pub static ref E_IDM_HP_ACP_HBAC_RULE_MANAGE_PRIV_V1: BuiltinAcp = BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlCreate,
+            EntryClass::AccessControlDelete,
+            EntryClass::AccessControlModify,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_hp_hbac_rule_manage_priv",
+        uuid: UUID_IDM_HP_ACP_HBAC_RULE_MANAGE_PRIV_V1,
+        description: "Builtin IDM Control for managing the access control rules of unix hosts",
+        receiver_group: UUID_IDM_HP_HBAC_MANAGE_PRIV,
+        target_scope: ProtoFilter::And(vec![
+            match_class_filter!(EntryClass::HbacRule),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::HbacUser,
+            Attribute::HbacHost,
+            Attribute::HbacService,
+        ],
+        modify_removed_attrs: vec![
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::HbacUser,
+            Attribute::HbacHost,
+            Attribute::HbacService,
+        ],
+        modify_present_attrs: vec![
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::HbacUser,
+            Attribute::HbacHost,
+            Attribute::HbacService,
+        ],
+        create_attrs: vec![
+            Attribute::Class,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::HbacUser,
+            Attribute::HbacHost,
+            Attribute::HbacService,
+        ],
+        create_classes: vec![EntryClass::Object, EntryClass::HbacRule,],
+        ..Default::default()
+    };
+
+    pub static ref IDM_ACP_HBAC_RULE_READ_V1: BuiltinAcp = BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_hbac_rule_read",
+        uuid: UUID_IDM_ACP_HBAC_RULE_READ_V1,
+        description: "Builtin IDM Control for reading host access control rules - required by unix hosts to enforce them.",
+        receiver_group: UUID_IDM_ALL_HOSTS,
+        target_scope: ProtoFilter::And(vec![
+            match_class_filter!(EntryClass::HbacRule),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::HbacUser,
+            Attribute::HbacHost,
+            Attribute::HbacService,
+        ],
+        ..Default::default()
+    };
+}
+
+lazy_static! {
+    pub static ref E_IDM_HP_ACP_AUTOMOUNT_MANAGE_PRIV_V1: BuiltinAcp = BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlCreate,
+            EntryClass::AccessControlDelete,
+            EntryClass::AccessControlModify,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_hp_automount_manage_priv",
+        uuid: UUID_IDM_HP_ACP_AUTOMOUNT_MANAGE_PRIV_V1,
+        description: "Builtin IDM Control for managing the automount maps of unix hosts",
+        receiver_group: UUID_IDM_HP_AUTOMOUNT_MANAGE_PRIV,
+        target_scope: ProtoFilter::And(vec![
+            ProtoFilter::Or(vec![
+                match_class_filter!(EntryClass::AutomountMap),
+                match_class_filter!(EntryClass::Automount),
+            ]),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::AutomountMap,
+            Attribute::AutomountKey,
+            Attribute::AutomountInformation,
+        ],
+        modify_removed_attrs: vec![
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::AutomountKey,
+            Attribute::AutomountInformation,
+        ],
+        modify_present_attrs: vec![
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::AutomountKey,
+            Attribute::AutomountInformation,
+        ],
+        create_attrs: vec![
+            Attribute::Class,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::AutomountMap,
+            Attribute::AutomountKey,
+            Attribute::AutomountInformation,
+        ],
+        create_classes: vec![EntryClass::Object, EntryClass::AutomountMap, EntryClass::Automount,],
+        ..Default::default()
+    };
+
+    pub static ref IDM_ACP_AUTOMOUNT_READ_V1: BuiltinAcp = BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_automount_read",
+        uuid: UUID_IDM_ACP_AUTOMOUNT_READ_V1,
+        description: "Builtin IDM Control for reading automount maps - required by unix hosts to mount them.",
+        receiver_group: UUID_IDM_ALL_ACCOUNTS,
+        target_scope: ProtoFilter::And(vec![
+            ProtoFilter::Or(vec![
+                match_class_filter!(EntryClass::AutomountMap),
+                match_class_filter!(EntryClass::Automount),
+            ]),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+        ]),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::AutomountMap,
+            Attribute::AutomountKey,
+            Attribute::AutomountInformation,
+        ],
+        ..Default::default()
+    };
+}
@@ -36,6 +36,21 @@ fn test_valueattribute_round_trip() {
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Sequence, Hash)]
 pub enum Attribute {
+    AccessRequestApprover,
+    AccessRequestDecidedBy,
+    AccessRequestDuration,
+    AccessRequestGroup,
+    AccessRequestJustification,
+    AccessRequestRequester,
+    AccessRequestState,
+    AccessReviewCampaign,
+    AccessReviewDecidedBy,
+    AccessReviewDecision,
+    AccessReviewExpiry,
+    AccessReviewGroup,
+    AccessReviewMember,
+    AccessReviewReviewer,
+    AccessReviewState,
     Account,
     AccountExpire,
     AccountValidFrom,
@@ -53,13 +68,18 @@ pub enum Attribute {
     Attr,
     AttributeName,
     AttributeType,
+    AuthPasswordMinimumLength,
     AuthSessionExpiry,
+    AutomountInformation,
+    AutomountKey,
+    AutomountMap,
     BadlistPassword,
     Claim,
     Class,
     ClassName,
     Cn,
     CookiePrivateKey,
+    CredentialTypeAllowed,
     CredentialUpdateIntentToken,
     Description,
     DeviceKeys,
@@ -80,6 +100,7 @@ pub enum Attribute {
     EmailAlternative,
     EmailPrimary,
     EntryDn,
+    EntryManagedBy,
     EntryUuid,
     Es256PrivateKeyDer,
     Excludes,
@@ -87,6 +108,9 @@ pub enum Attribute {
     GidNumber,
     GrantUiHint,
     Group,
+    HbacHost,
+    HbacService,
+    HbacUser,
     IdVerificationEcKey,
     Image,
     Index,
@@ -94,6 +118,12 @@ pub enum Attribute {
     IpaSshPubKey,
     JwsEs256PrivateKey,
     LastModifiedCid,
+    /// An LDAP Compatible automountInformation
+    LdapAutomountInformation,
+    /// An LDAP Compatible automountKey
+    LdapAutomountKey,
+    /// An LDAP Compatible automountMapName
+    LdapAutomountMapName,
     /// An LDAP Compatible emailAddress
     LdapEmailAddress,
     /// An LDAP Compatible sshkeys virtual attribute
@@ -103,6 +133,7 @@ pub enum Attribute {
     Mail,
     May,
     Member,
+    MemberExpiring,
     MemberOf,
     MultiValue,
     Must,
@@ -112,15 +143,26 @@ pub enum Attribute {
     NsUniqueId,
     OAuth2AllowInsecureClientDisablePkce,
     OAuth2ConsentScopeMap,
+    OAuth2DeviceAccount,
+    OAuth2DeviceClient,
+    OAuth2DeviceExpiry,
+    OAuth2DeviceFlowEnable,
+    OAuth2DeviceParentExpiry,
+    OAuth2DeviceParentSession,
+    OAuth2DeviceScope,
+    OAuth2DeviceState,
+    OAuth2DeviceUserCode,
     OAuth2JwtLegacyCryptoEnable,
     OAuth2PreferShortUsername,
     OAuth2RsBasicSecret,
+    OAuth2RsClientCredentialsScopes,
     OAuth2RsImplicitScopes,
     OAuth2RsName,
     OAuth2RsOrigin,
     OAuth2RsOriginLanding,
     OAuth2RsScopeMap,
     OAuth2RsSupScopeMap,
+    OAuth2RsTokenExchangeSource,
     OAuth2RsTokenKey,
     OAuth2Session,
     ObjectClass,
@@ -141,6 +183,12 @@ pub enum Attribute {
     LdapSshPublicKey,
     /// The Kanidm-local ssh_publickey
     SshPublicKey,
+    SubidRangeStart,
+    SudoCommand,
+    SudoHost,
+    SudoNopasswd,
+    SudoRunas,
+    SudoUser,
     Supplements,
     SystemSupplements,
     SyncAllowed,
@@ -150,6 +198,7 @@ pub enum Attribute {
     SyncExternalId,
     SyncParentUuid,
     SyncTokenSession,
+    SyncWritebackCookie,
     SyncYieldAuthority,
     Syntax,
     SystemExcludes,
@@ -211,6 +260,21 @@ impl TryFrom<String> for Attribute {
     type Error = OperationError;
     fn try_from(val: String) -> Result<Self, OperationError> {
         let res = match val.as_str() {
+            ATTR_ACCESS_REQUEST_APPROVER => Attribute::AccessRequestApprover,
+            ATTR_ACCESS_REQUEST_DECIDED_BY => Attribute::AccessRequestDecidedBy,
+            ATTR_ACCESS_REQUEST_DURATION => Attribute::AccessRequestDuration,
+            ATTR_ACCESS_REQUEST_GROUP => Attribute::AccessRequestGroup,
+            ATTR_ACCESS_REQUEST_JUSTIFICATION => Attribute::AccessRequestJustification,
+            ATTR_ACCESS_REQUEST_REQUESTER => Attribute::AccessRequestRequester,
+            ATTR_ACCESS_REQUEST_STATE => Attribute::AccessRequestState,
+            ATTR_ACCESS_REVIEW_CAMPAIGN => Attribute::AccessReviewCampaign,
+            ATTR_ACCESS_REVIEW_DECIDED_BY => Attribute::AccessReviewDecidedBy,
+            ATTR_ACCESS_REVIEW_DECISION => Attribute::AccessReviewDecision,
+            ATTR_ACCESS_REVIEW_EXPIRY => Attribute::AccessReviewExpiry,
+            ATTR_ACCESS_REVIEW_GROUP => Attribute::AccessReviewGroup,
+            ATTR_ACCESS_REVIEW_MEMBER => Attribute::AccessReviewMember,
+            ATTR_ACCESS_REVIEW_REVIEWER => Attribute::AccessReviewReviewer,
+            ATTR_ACCESS_REVIEW_STATE => Attribute::AccessReviewState,
             ATTR_ACCOUNT => Attribute::Account,
             ATTR_ACCOUNT_EXPIRE => Attribute::AccountExpire,
             ATTR_ACCOUNT_VALID_FROM => Attribute::AccountValidFrom,
@@ -228,13 +292,18 @@ impl TryFrom<String> for Attribute {
             ATTR_ATTR => Attribute::Attr,
             ATTR_ATTRIBUTENAME => Attribute::AttributeName,
             ATTR_ATTRIBUTETYPE => Attribute::AttributeType,
+            ATTR_AUTH_PASSWORD_MINIMUM_LENGTH => Attribute::AuthPasswordMinimumLength,
             ATTR_AUTH_SESSION_EXPIRY => Attribute::AuthSessionExpiry,
+            ATTR_AUTOMOUNT_INFORMATION => Attribute::AutomountInformation,
+            ATTR_AUTOMOUNT_KEY => Attribute::AutomountKey,
+            ATTR_AUTOMOUNT_MAP => Attribute::AutomountMap,
             ATTR_BADLIST_PASSWORD => Attribute::BadlistPassword,
             ATTR_CLAIM => Attribute::Claim,
             ATTR_CLASS => Attribute::Class,
             ATTR_CLASSNAME => Attribute::ClassName,
             ATTR_CN => Attribute::Cn,
             ATTR_COOKIE_PRIVATE_KEY => Attribute::CookiePrivateKey,
+            ATTR_CREDENTIAL_TYPE_ALLOWED => Attribute::CredentialTypeAllowed,
             ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN => Attribute::CredentialUpdateIntentToken,
             ATTR_DESCRIPTION => Attribute::Description,
             ATTR_DEVICEKEYS => Attribute::DeviceKeys,
@@ -255,6 +324,7 @@ impl TryFrom<String> for Attribute {
             ATTR_EMAIL_ALTERNATIVE => Attribute::EmailAlternative,
             ATTR_EMAIL_PRIMARY => Attribute::EmailPrimary,
             ATTR_ENTRYDN => Attribute::EntryDn,
+            ATTR_ENTRY_MANAGED_BY => Attribute::EntryManagedBy,
             ATTR_ENTRYUUID => Attribute::EntryUuid,
             ATTR_ES256_PRIVATE_KEY_DER => Attribute::Es256PrivateKeyDer,
             ATTR_EXCLUDES => Attribute::Excludes,
@@ -262,6 +332,9 @@ impl TryFrom<String> for Attribute {
             ATTR_GIDNUMBER => Attribute::GidNumber,
             ATTR_GRANT_UI_HINT => Attribute::GrantUiHint,
             ATTR_GROUP => Attribute::Group,
+            ATTR_HBAC_HOST => Attribute::HbacHost,
+            ATTR_HBAC_SERVICE => Attribute::HbacService,
+            ATTR_HBAC_USER => Attribute::HbacUser,
             ATTR_ID_VERIFICATION_ECKEY => Attribute::IdVerificationEcKey,
             ATTR_IMAGE => Attribute::Image,
             ATTR_INDEX => Attribute::Index,
@@ -269,6 +342,9 @@ impl TryFrom<String> for Attribute {
             ATTR_IPASSHPUBKEY => Attribute::IpaSshPubKey,
             ATTR_JWS_ES256_PRIVATE_KEY => Attribute::JwsEs256PrivateKey,
             ATTR_LAST_MODIFIED_CID => Attribute::LastModifiedCid,
+            ATTR_LDAP_AUTOMOUNT_INFORMATION => Attribute::LdapAutomountInformation,
+            ATTR_LDAP_AUTOMOUNT_KEY => Attribute::LdapAutomountKey,
+            ATTR_LDAP_AUTOMOUNT_MAP_NAME => Attribute::LdapAutomountMapName,
             ATTR_LDAP_EMAIL_ADDRESS => Attribute::LdapEmailAddress,
             ATTR_LDAP_KEYS => Attribute::LdapKeys,
             ATTR_SSH_PUBLICKEY => Attribute::SshPublicKey,
@@ -277,6 +353,7 @@ impl TryFrom<String> for Attribute {
             ATTR_MAIL => Attribute::Mail,
             ATTR_MAY => Attribute::May,
             ATTR_MEMBER => Attribute::Member,
+            ATTR_MEMBER_EXPIRING => Attribute::MemberExpiring,
             ATTR_MEMBEROF => Attribute::MemberOf,
             ATTR_MULTIVALUE => Attribute::MultiValue,
             ATTR_MUST => Attribute::Must,
@@ -288,15 +365,26 @@ impl TryFrom<String> for Attribute {
                 Attribute::OAuth2AllowInsecureClientDisablePkce
             }
             ATTR_OAUTH2_CONSENT_SCOPE_MAP => Attribute::OAuth2ConsentScopeMap,
+            ATTR_OAUTH2_DEVICE_ACCOUNT => Attribute::OAuth2DeviceAccount,
+            ATTR_OAUTH2_DEVICE_CLIENT => Attribute::OAuth2DeviceClient,
+            ATTR_OAUTH2_DEVICE_EXPIRY => Attribute::OAuth2DeviceExpiry,
+            ATTR_OAUTH2_DEVICE_FLOW_ENABLE => Attribute::OAuth2DeviceFlowEnable,
+            ATTR_OAUTH2_DEVICE_PARENT_EXPIRY => Attribute::OAuth2DeviceParentExpiry,
+            ATTR_OAUTH2_DEVICE_PARENT_SESSION => Attribute::OAuth2DeviceParentSession,
+            ATTR_OAUTH2_DEVICE_SCOPE => Attribute::OAuth2DeviceScope,
+            ATTR_OAUTH2_DEVICE_STATE => Attribute::OAuth2DeviceState,
+            ATTR_OAUTH2_DEVICE_USER_CODE => Attribute::OAuth2DeviceUserCode,
             ATTR_OAUTH2_JWT_LEGACY_CRYPTO_ENABLE => Attribute::OAuth2JwtLegacyCryptoEnable,
             ATTR_OAUTH2_PREFER_SHORT_USERNAME => Attribute::OAuth2PreferShortUsername,
             ATTR_OAUTH2_RS_BASIC_SECRET => Attribute::OAuth2RsBasicSecret,
+            ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES => Attribute::OAuth2RsClientCredentialsScopes,
             ATTR_OAUTH2_RS_IMPLICIT_SCOPES => Attribute::OAuth2RsImplicitScopes,
             ATTR_OAUTH2_RS_NAME => Attribute::OAuth2RsName,
             ATTR_OAUTH2_RS_ORIGIN => Attribute::OAuth2RsOrigin,
             ATTR_OAUTH2_RS_ORIGIN_LANDING => Attribute::OAuth2RsOriginLanding,
             ATTR_OAUTH2_RS_SCOPE_MAP => Attribute::OAuth2RsScopeMap,
             ATTR_OAUTH2_RS_SUP_SCOPE_MAP => Attribute::OAuth2RsSupScopeMap,
+            ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE => Attribute::OAuth2RsTokenExchangeSource,
             ATTR_OAUTH2_RS_TOKEN_KEY => Attribute::OAuth2RsTokenKey,
             ATTR_OAUTH2_SESSION => Attribute::OAuth2Session,
             ATTR_OBJECTCLASS => Attribute::ObjectClass,
@@ -314,6 +402,12 @@ impl TryFrom<String> for Attribute {
             ATTR_SOURCE_UUID => Attribute::SourceUuid,
             ATTR_SPN => Attribute::Spn,
             ATTR_LDAP_SSHPUBLICKEY => Attribute::LdapSshPublicKey,
+            ATTR_SUBID_RANGE_START => Attribute::SubidRangeStart,
+            ATTR_SUDO_COMMAND => Attribute::SudoCommand,
+            ATTR_SUDO_HOST => Attribute::SudoHost,
+            ATTR_SUDO_NOPASSWD => Attribute::SudoNopasswd,
+            ATTR_SUDO_RUNAS => Attribute::SudoRunas,
+            ATTR_SUDO_USER => Attribute::SudoUser,
             ATTR_SUPPLEMENTS => Attribute::Supplements,
             ATTR_SYNC_ALLOWED => Attribute::SyncAllowed,
             ATTR_SYNC_CLASS => Attribute::SyncClass,
@@ -322,6 +416,7 @@ impl TryFrom<String> for Attribute {
             ATTR_SYNC_EXTERNAL_ID => Attribute::SyncExternalId,
             ATTR_SYNC_PARENT_UUID => Attribute::SyncParentUuid,
             ATTR_SYNC_TOKEN_SESSION => Attribute::SyncTokenSession,
+            ATTR_SYNC_WRITEBACK_COOKIE => Attribute::SyncWritebackCookie,
             ATTR_SYNC_YIELD_AUTHORITY => Attribute::SyncYieldAuthority,
             ATTR_SYNTAX => Attribute::Syntax,
             ATTR_SYSTEMEXCLUDES => Attribute::SystemExcludes,
@@ -362,6 +457,21 @@ impl TryFrom<String> for Attribute {
 impl From<Attribute> for &'static str {
     fn from(val: Attribute) -> Self {
         match val {
+            Attribute::AccessRequestApprover => ATTR_ACCESS_REQUEST_APPROVER,
+            Attribute::AccessRequestDecidedBy => ATTR_ACCESS_REQUEST_DECIDED_BY,
+            Attribute::AccessRequestDuration => ATTR_ACCESS_REQUEST_DURATION,
+            Attribute::AccessRequestGroup => ATTR_ACCESS_REQUEST_GROUP,
+            Attribute::AccessRequestJustification => ATTR_ACCESS_REQUEST_JUSTIFICATION,
+            Attribute::AccessRequestRequester => ATTR_ACCESS_REQUEST_REQUESTER,
+            Attribute::AccessRequestState => ATTR_ACCESS_REQUEST_STATE,
+            Attribute::AccessReviewCampaign => ATTR_ACCESS_REVIEW_CAMPAIGN,
+            Attribute::AccessReviewDecidedBy => ATTR_ACCESS_REVIEW_DECIDED_BY,
+            Attribute::AccessReviewDecision => ATTR_ACCESS_REVIEW_DECISION,
+            Attribute::AccessReviewExpiry => ATTR_ACCESS_REVIEW_EXPIRY,
+            Attribute::AccessReviewGroup => ATTR_ACCESS_REVIEW_GROUP,
+            Attribute::AccessReviewMember => ATTR_ACCESS_REVIEW_MEMBER,
+            Attribute::AccessReviewReviewer => ATTR_ACCESS_REVIEW_REVIEWER,
+            Attribute::AccessReviewState => ATTR_ACCESS_REVIEW_STATE,
             Attribute::Account => ATTR_ACCOUNT,
             Attribute::AccountExpire => ATTR_ACCOUNT_EXPIRE,
             Attribute::AccountValidFrom => ATTR_ACCOUNT_VALID_FROM,
@@ -379,13 +489,18 @@ impl From<Attribute> for &'static str {
             Attribute::Attr => ATTR_ATTR,
             Attribute::AttributeName => ATTR_ATTRIBUTENAME,
             Attribute::AttributeType => ATTR_ATTRIBUTETYPE,
+            Attribute::AuthPasswordMinimumLength => ATTR_AUTH_PASSWORD_MINIMUM_LENGTH,
             Attribute::AuthSessionExpiry => ATTR_AUTH_SESSION_EXPIRY,
+            Attribute::AutomountInformation => ATTR_AUTOMOUNT_INFORMATION,
+            Attribute::AutomountKey => ATTR_AUTOMOUNT_KEY,
+            Attribute::AutomountMap => ATTR_AUTOMOUNT_MAP,
             Attribute::BadlistPassword => ATTR_BADLIST_PASSWORD,
             Attribute::Claim => ATTR_CLAIM,
             Attribute::Class => ATTR_CLASS,
             Attribute::ClassName => ATTR_CLASSNAME,
             Attribute::Cn => ATTR_CN,
             Attribute::CookiePrivateKey => ATTR_COOKIE_PRIVATE_KEY,
+            Attribute::CredentialTypeAllowed => ATTR_CREDENTIAL_TYPE_ALLOWED,
             Attribute::CredentialUpdateIntentToken => ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN,
             Attribute::Description => ATTR_DESCRIPTION,
             Attribute::DeviceKeys => ATTR_DEVICEKEYS,
@@ -406,6 +521,7 @@ impl From<Attribute> for &'static str {
             Attribute::EmailAlternative => ATTR_EMAIL_ALTERNATIVE,
             Attribute::EmailPrimary => ATTR_EMAIL_PRIMARY,
             Attribute::EntryDn => ATTR_ENTRYDN,
+            Attribute::EntryManagedBy => ATTR_ENTRY_MANAGED_BY,
             Attribute::EntryUuid => ATTR_ENTRYUUID,
             Attribute::Es256PrivateKeyDer => ATTR_ES256_PRIVATE_KEY_DER,
             Attribute::Excludes => ATTR_EXCLUDES,
@@ -413,6 +529,9 @@ impl From<Attribute> for &'static str {
             Attribute::GidNumber => ATTR_GIDNUMBER,
             Attribute::GrantUiHint => ATTR_GRANT_UI_HINT,
             Attribute::Group => ATTR_GROUP,
+            Attribute::HbacHost => ATTR_HBAC_HOST,
+            Attribute::HbacService => ATTR_HBAC_SERVICE,
+            Attribute::HbacUser => ATTR_HBAC_USER,
             Attribute::IdVerificationEcKey => ATTR_ID_VERIFICATION_ECKEY,
             Attribute::Image => ATTR_IMAGE,
             Attribute::Index => ATTR_INDEX,
@@ -420,6 +539,9 @@ impl From<Attribute> for &'static str {
             Attribute::IpaSshPubKey => ATTR_IPASSHPUBKEY,
             Attribute::JwsEs256PrivateKey => ATTR_JWS_ES256_PRIVATE_KEY,
             Attribute::LastModifiedCid => ATTR_LAST_MODIFIED_CID,
+            Attribute::LdapAutomountInformation => ATTR_LDAP_AUTOMOUNT_INFORMATION,
+            Attribute::LdapAutomountKey => ATTR_LDAP_AUTOMOUNT_KEY,
+            Attribute::LdapAutomountMapName => ATTR_LDAP_AUTOMOUNT_MAP_NAME,
             Attribute::LdapEmailAddress => ATTR_LDAP_EMAIL_ADDRESS,
             Attribute::LdapKeys => ATTR_LDAP_KEYS,
             Attribute::LdapSshPublicKey => ATTR_LDAP_SSHPUBLICKEY,
@@ -428,6 +550,7 @@ impl From<Attribute> for &'static str {
             Attribute::Mail => ATTR_MAIL,
             Attribute::May => ATTR_MAY,
             Attribute::Member => ATTR_MEMBER,
+            Attribute::MemberExpiring => ATTR_MEMBER_EXPIRING,
             Attribute::MemberOf => ATTR_MEMBEROF,
             Attribute::MultiValue => ATTR_MULTIVALUE,
             Attribute::Must => ATTR_MUST,
@@ -439,15 +562,26 @@ impl From<Attribute> for &'static str {
                 ATTR_OAUTH2_ALLOW_INSECURE_CLIENT_DISABLE_PKCE
             }
             Attribute::OAuth2ConsentScopeMap => ATTR_OAUTH2_CONSENT_SCOPE_MAP,
+            Attribute::OAuth2DeviceAccount => ATTR_OAUTH2_DEVICE_ACCOUNT,
+            Attribute::OAuth2DeviceClient => ATTR_OAUTH2_DEVICE_CLIENT,
+            Attribute::OAuth2DeviceExpiry => ATTR_OAUTH2_DEVICE_EXPIRY,
+            Attribute::OAuth2DeviceFlowEnable => ATTR_OAUTH2_DEVICE_FLOW_ENABLE,
+            Attribute::OAuth2DeviceParentExpiry => ATTR_OAUTH2_DEVICE_PARENT_EXPIRY,
+            Attribute::OAuth2DeviceParentSession => ATTR_OAUTH2_DEVICE_PARENT_SESSION,
+            Attribute::OAuth2DeviceScope => ATTR_OAUTH2_DEVICE_SCOPE,
+            Attribute::OAuth2DeviceState => ATTR_OAUTH2_DEVICE_STATE,
+            Attribute::OAuth2DeviceUserCode => ATTR_OAUTH2_DEVICE_USER_CODE,
             Attribute::OAuth2JwtLegacyCryptoEnable => ATTR_OAUTH2_JWT_LEGACY_CRYPTO_ENABLE,
             Attribute::OAuth2PreferShortUsername => ATTR_OAUTH2_PREFER_SHORT_USERNAME,
             Attribute::OAuth2RsBasicSecret => ATTR_OAUTH2_RS_BASIC_SECRET,
+            Attribute::OAuth2RsClientCredentialsScopes => ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES,
             Attribute::OAuth2RsImplicitScopes => ATTR_OAUTH2_RS_IMPLICIT_SCOPES,
             Attribute::OAuth2RsName => ATTR_OAUTH2_RS_NAME,
             Attribute::OAuth2RsOrigin => ATTR_OAUTH2_RS_ORIGIN,
             Attribute::OAuth2RsOriginLanding => ATTR_OAUTH2_RS_ORIGIN_LANDING,
             Attribute::OAuth2RsScopeMap => ATTR_OAUTH2_RS_SCOPE_MAP,
             Attribute::OAuth2RsSupScopeMap => ATTR_OAUTH2_RS_SUP_SCOPE_MAP,
+            Attribute::OAuth2RsTokenExchangeSource => ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE,
             Attribute::OAuth2RsTokenKey => ATTR_OAUTH2_RS_TOKEN_KEY,
             Attribute::OAuth2Session => ATTR_OAUTH2_SESSION,
             Attribute::ObjectClass => ATTR_OBJECTCLASS,
@@ -465,6 +599,12 @@ impl From<Attribute> for &'static str {
             Attribute::SourceUuid => ATTR_SOURCE_UUID,
             Attribute::Spn => ATTR_SPN,
             Attribute::SshPublicKey => ATTR_SSH_PUBLICKEY,
+            Attribute::SubidRangeStart => ATTR_SUBID_RANGE_START,
+            Attribute::SudoCommand => ATTR_SUDO_COMMAND,
+            Attribute::SudoHost => ATTR_SUDO_HOST,
+            Attribute::SudoNopasswd => ATTR_SUDO_NOPASSWD,
+            Attribute::SudoRunas => ATTR_SUDO_RUNAS,
+            Attribute::SudoUser => ATTR_SUDO_USER,
             Attribute::Supplements => ATTR_SUPPLEMENTS,
             Attribute::SyncAllowed => ATTR_SYNC_ALLOWED,
             Attribute::SyncClass => ATTR_SYNC_CLASS,
@@ -473,6 +613,7 @@ impl From<Attribute> for &'static str {
             Attribute::SyncExternalId => ATTR_SYNC_EXTERNAL_ID,
             Attribute::SyncParentUuid => ATTR_SYNC_PARENT_UUID,
             Attribute::SyncTokenSession => ATTR_SYNC_TOKEN_SESSION,
+            Attribute::SyncWritebackCookie => ATTR_SYNC_WRITEBACK_COOKIE,
             Attribute::SyncYieldAuthority => ATTR_SYNC_YIELD_AUTHORITY,
             Attribute::Syntax => ATTR_SYNTAX,
             Attribute::SystemExcludes => ATTR_SYSTEMEXCLUDES,
@@ -542,13 +683,19 @@ impl<'a> serde::Deserialize<'a> for Attribute {
 
 #[derive(Copy, Clone, Debug)]
 pub enum EntryClass {
+    AccessRequest,
+    AccessReview,
+    AccessReviewItem,
     AccessControlCreate,
     AccessControlDelete,
     AccessControlModify,
     AccessControlProfile,
     AccessControlSearch,
     Account,
+    AccountPolicy,
     AttributeType,
+    Automount,
+    AutomountMap,
     Class,
     ClassType,
     Conflict,
@@ -556,7 +703,10 @@ pub enum EntryClass {
     DynGroup,
     ExtensibleObject,
     Group,
+    HbacRule,
+    Host,
     MemberOf,
+    OAuth2DeviceAuthorisation,
     OAuth2ResourceServer,
     OAuth2ResourceServerBasic,
     OAuth2ResourceServerPublic,
@@ -568,6 +718,7 @@ pub enum EntryClass {
     Recycled,
     Service,
     ServiceAccount,
+    SudoRule,
     SyncAccount,
     SyncObject,
     Tombstone,
@@ -583,12 +734,19 @@ impl From<EntryClass> for &'static str {
     fn from(val: EntryClass) -> Self {
         match val {
             EntryClass::AccessControlCreate => "access_control_create",
+            EntryClass::AccessRequest => "access_request",
+            EntryClass::AccessReview => "access_review",
+            EntryClass::AccessReviewItem => "access_review_item",
             EntryClass::AccessControlDelete => "access_control_delete",
             EntryClass::AccessControlModify => "access_control_modify",
             EntryClass::AccessControlProfile => "access_control_profile",
             EntryClass::AccessControlSearch => "access_control_search",
             EntryClass::Account => "account",
+            EntryClass::AccountPolicy => "account_policy",
             EntryClass::AttributeType => "attributetype",
+            // These match the LDAP object classes that autofs searches for.
+            EntryClass::Automount => "automount",
+            EntryClass::AutomountMap => "automountmap",
             EntryClass::Class => ATTR_CLASS,
             EntryClass::ClassType => "classtype",
             EntryClass::Conflict => "conflict",
@@ -596,7 +754,10 @@ impl From<EntryClass> for &'static str {
             EntryClass::DynGroup => ATTR_DYNGROUP,
             EntryClass::ExtensibleObject => "extensibleobject",
             EntryClass::Group => ATTR_GROUP,
+            EntryClass::HbacRule => "hbac_rule",
+            EntryClass::Host => "host",
             EntryClass::MemberOf => "memberof",
+            EntryClass::OAuth2DeviceAuthorisation => "oauth2_device_authorisation",
             EntryClass::OAuth2ResourceServer => "oauth2_resource_server",
             EntryClass::OAuth2ResourceServerBasic => "oauth2_resource_server_basic",
             EntryClass::OAuth2ResourceServerPublic => "oauth2_resource_server_public",
@@ -608,6 +769,7 @@ impl From<EntryClass> for &'static str {
             EntryClass::Recycled => "recycled",
             EntryClass::Service => "service",
             EntryClass::ServiceAccount => "service_account",
+            EntryClass::SudoRule => "sudo_rule",
             EntryClass::SyncAccount => "sync_account",
             EntryClass::SyncObject => "sync_object",
             EntryClass::System => "system",
@@ -1095,6 +1257,39 @@ lazy_static! {
         ..Default::default()
     };
 
+    /// Builtin IDM Group for managing the sudo rules that are served to unix hosts.
+    pub static ref IDM_HP_SUDO_RULE_MANAGE_PRIV: BuiltinGroup = BuiltinGroup {
+        name: "idm_hp_sudo_rule_manage_priv",
+        description: "Builtin IDM Group for managing the sudo rules that are served to unix hosts.",
+        uuid: UUID_IDM_HP_SUDO_RULE_MANAGE_PRIV,
+        members: vec![
+            UUID_SYSTEM_ADMINS,
+        ],
+        ..Default::default()
+    };
+
+    /// Builtin IDM Group for managing hosts and the access control rules of unix hosts.
+    pub static ref IDM_HP_HBAC_MANAGE_PRIV: BuiltinGroup = BuiltinGroup {
+        name: "idm_hp_hbac_manage_priv",
+        description: "Builtin IDM Group for managing hosts and the access control rules of unix hosts.",
+        uuid: UUID_IDM_HP_HBAC_MANAGE_PRIV,
+        members: vec![
+            UUID_SYSTEM_ADMINS,
+        ],
+        ..Default::default()
+    };
+
+    /// Builtin IDM Group for managing the automount maps that are served to unix hosts.
+    pub static ref IDM_HP_AUTOMOUNT_MANAGE_PRIV: BuiltinGroup = BuiltinGroup {
+        name: "idm_hp_automount_manage_priv",
+        description: "Builtin IDM Group for managing the automount maps that are served to unix hosts.",
+        uuid: UUID_IDM_HP_AUTOMOUNT_MANAGE_PRIV,
+        members: vec![
+            UUID_SYSTEM_ADMINS,
+        ],
+        ..Default::default()
+    };
+
     /// Builtin IDM Group for extending high privilege accounts to be people.
     pub static ref IDM_ALL_PERSONS: BuiltinGroup = BuiltinGroup {
         name: "idm_all_persons",
@@ -1124,6 +1319,19 @@ lazy_static! {
         ..Default::default()
     };
 
+    /// Builtin IDM dynamic group containing all hosts.
+    pub static ref IDM_ALL_HOSTS: BuiltinGroup = BuiltinGroup {
+        name: "idm_all_hosts",
+        description: "Builtin IDM dynamic group containing all hosts, which may read the unix policy that applies to them.",
+        uuid: UUID_IDM_ALL_HOSTS,
+        members: Vec::new(),
+        dyngroup: true,
+        dyngroup_filter: Some(
+            Filter::Eq(Attribute::Class.to_string(), EntryClass::Host.to_string()),
+        ),
+        ..Default::default()
+    };
+
 
     pub static ref IDM_UI_ENABLE_EXPERIMENTAL_FEATURES: BuiltinGroup = BuiltinGroup {
         name: "idm_ui_enable_experimental_features",
@@ -1177,6 +1385,9 @@ lazy_static! {
             UUID_IDM_RADIUS_SECRET_READ_PRIV_V1,
             UUID_IDM_HP_SERVICE_ACCOUNT_INTO_PERSON_MIGRATE_PRIV,
             UUID_IDM_HP_SYNC_ACCOUNT_MANAGE_PRIV,
+            UUID_IDM_HP_SUDO_RULE_MANAGE_PRIV,
+            UUID_IDM_HP_HBAC_MANAGE_PRIV,
+            UUID_IDM_HP_AUTOMOUNT_MANAGE_PRIV,
             UUID_IDM_HIGH_PRIVILEGE,
         ],
         dyngroup: false,
@@ -1193,7 +1404,7 @@ lazy_static! {
 Attribute::Description,
             Value::new_utf8s("System (local) info and metadata object.")
         ),
-        (Attribute::Version, Value::Uint32(14))
+        (Attribute::Version, Value::Uint32(16))
     );
 
     pub static ref E_DOMAIN_INFO_V1: EntryInitNew = entry_init!(
@@ -17,7 +17,7 @@ pub use crate::constants::values::*;
 use std::time::Duration;
 
 // Increment this as we add new schema types and values!!!
-pub const SYSTEM_INDEX_VERSION: i64 = 30;
+pub const SYSTEM_INDEX_VERSION: i64 = 41;
 
 /*
  * domain functional levels
@@ -81,6 +81,8 @@ pub const DEFAULT_AUTH_SESSION_EXPIRY: u32 = 86400;
 pub const DEFAULT_AUTH_SESSION_LIMITED_EXPIRY: u32 = 3600;
 // Default - privileges last for 10 minutes.
 pub const DEFAULT_AUTH_PRIVILEGE_EXPIRY: u32 = 600;
+// Default - approved access requests grant membership for 1 hour.
+pub const DEFAULT_ACCESS_REQUEST_DURATION: u32 = 3600;
 // Default - oauth refresh tokens last for 16 hours.
 pub const OAUTH_REFRESH_TOKEN_EXPIRY: u64 = 3600 * 8;
 
@@ -92,3 +94,13 @@ pub const GRACE_WINDOW: Duration = Duration::from_secs(300);
 /// How long access tokens should last. This is NOT the length
 /// of the refresh token, which is bound to the issuing session.
 pub const OAUTH2_ACCESS_TOKEN_EXPIRY: u32 = 15 * 60;
+
+/// How long a device has for the user to approve an OAuth2 device authorisation.
+pub const OAUTH2_DEVICE_CODE_EXPIRY: u32 = 10 * 60;
+
+/// The minimum number of seconds a device must wait between polls of the token endpoint.
+pub const OAUTH2_DEVICE_CODE_INTERVAL: u32 = 5;
+
+/// The number of subordinate uids and gids allocated to each posix account. This is the
+/// number that rootless container runtimes expect by default.
+pub const SUBID_RANGE_SIZE: u32 = 65536;
@@ -176,7 +176,7 @@ pub static ref SCHEMA_ATTR_GIDNUMBER: SchemaAttribute = SchemaAttribute {
     name: Attribute::GidNumber.into(),
     description: "The groupid (uid) number of a group or account.to_string(). This is the same value as the UID number on posix accounts for security reasons.".to_string(),
 
-    index: vec![IndexType::Equality],
+    index: vec![IndexType::Equality, IndexType::Ordering],
     unique: true,
     sync_allowed: true,
     syntax: SyntaxType::Uint32,
@@ -211,6 +211,25 @@ pub static ref SCHEMA_ATTR_AUTH_PRIVILEGE_EXPIRY: SchemaAttribute = SchemaAttrib
     ..Default::default()
 };
 
+pub static ref SCHEMA_ATTR_CREDENTIAL_TYPE_ALLOWED: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_CREDENTIAL_TYPE_ALLOWED,
+    name: Attribute::CredentialTypeAllowed.into(),
+
+    description: "The types of credential that members of an account policy group may authenticate with.".to_string(),
+    multivalue: true,
+    syntax: SyntaxType::CredentialType,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_LENGTH: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_LENGTH,
+    name: Attribute::AuthPasswordMinimumLength.into(),
+
+    description: "The minimum length of a password for members of an account policy group.".to_string(),
+    syntax: SyntaxType::Uint32,
+    ..Default::default()
+};
+
 pub static ref SCHEMA_ATTR_LOGINSHELL: SchemaAttribute = SchemaAttribute {
     uuid: UUID_SCHEMA_ATTR_LOGINSHELL,
     name: Attribute::LoginShell.into(),
@@ -248,6 +267,7 @@ pub static ref SCHEMA_ATTR_ACCOUNT_EXPIRE: SchemaAttribute = SchemaAttribute {
     name: Attribute::AccountExpire.into(),
     description: "The datetime after which this accounnt no longer may authenticate.to_string().".to_string(),
 
+    index: vec![IndexType::Ordering],
     sync_allowed: true,
     syntax: SyntaxType::DateTime,
     ..Default::default()
@@ -258,6 +278,7 @@ pub static ref SCHEMA_ATTR_ACCOUNT_VALID_FROM: SchemaAttribute = SchemaAttribute
     name: Attribute::AccountValidFrom.into(),
     description: "The datetime after which this account may commence authenticating.to_string().".to_string(),
 
+    index: vec![IndexType::Ordering],
     sync_allowed: true,
     syntax: SyntaxType::DateTime,
     ..Default::default()
@@ -344,6 +365,16 @@ pub static ref SCHEMA_ATTR_OAUTH2_RS_IMPLICIT_SCOPES: SchemaAttribute = SchemaAt
     ..Default::default()
 };
 
+pub static ref SCHEMA_ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES,
+    name: Attribute::OAuth2RsClientCredentialsScopes.into(),
+    description: "The scopes an oauth2 resource server may be granted for itself with the client credentials grant".to_string(),
+
+    multivalue: true,
+    syntax: SyntaxType::OauthScope,
+    ..Default::default()
+};
+
 pub static ref SCHEMA_ATTR_OAUTH2_CONSENT_SCOPE_MAP: SchemaAttribute = SchemaAttribute {
     uuid: UUID_SCHEMA_ATTR_OAUTH2_CONSENT_SCOPE_MAP,
     name: Attribute::OAuth2ConsentScopeMap.into(),
@@ -411,6 +442,103 @@ pub static ref SCHEMA_ATTR_OAUTH2_JWT_LEGACY_CRYPTO_ENABLE: SchemaAttribute = Sc
     ..Default::default()
 };
 
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_FLOW_ENABLE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_FLOW_ENABLE,
+    name: Attribute::OAuth2DeviceFlowEnable.into(),
+    description: "Allows the OAuth2 device authorisation grant to be used with this client".to_string(),
+
+    syntax: SyntaxType::Boolean,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_USER_CODE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_USER_CODE,
+    name: Attribute::OAuth2DeviceUserCode.into(),
+    description: "The code that a user enters to approve an OAuth2 device authorisation".to_string(),
+
+    index: vec![IndexType::Equality],
+    unique: true,
+    syntax: SyntaxType::Utf8StringInsensitive,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_CLIENT: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_CLIENT,
+    name: Attribute::OAuth2DeviceClient.into(),
+    description: "The OAuth2 client that started a device authorisation".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_SCOPE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_SCOPE,
+    name: Attribute::OAuth2DeviceScope.into(),
+    description: "The scopes requested by, or once approved granted to, an OAuth2 device".to_string(),
+
+    multivalue: true,
+    syntax: SyntaxType::OauthScope,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_EXPIRY: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_EXPIRY,
+    name: Attribute::OAuth2DeviceExpiry.into(),
+    description: "The time at which an OAuth2 device authorisation can no longer be used".to_string(),
+
+    syntax: SyntaxType::DateTime,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_STATE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_STATE,
+    name: Attribute::OAuth2DeviceState.into(),
+    description: "If an OAuth2 device authorisation is pending, approved or denied".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::Utf8StringInsensitive,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_ACCOUNT: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_ACCOUNT,
+    name: Attribute::OAuth2DeviceAccount.into(),
+    description: "The account that approved an OAuth2 device authorisation".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_PARENT_SESSION: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_PARENT_SESSION,
+    name: Attribute::OAuth2DeviceParentSession.into(),
+    description: "The session of the account that approved an OAuth2 device authorisation".to_string(),
+
+    syntax: SyntaxType::Uuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_DEVICE_PARENT_EXPIRY: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_DEVICE_PARENT_EXPIRY,
+    name: Attribute::OAuth2DeviceParentExpiry.into(),
+    description: "The expiry of the session that approved an OAuth2 device authorisation".to_string(),
+
+    syntax: SyntaxType::DateTime,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE,
+    name: Attribute::OAuth2RsTokenExchangeSource.into(),
+    description: "The OAuth2 resource servers that may exchange their tokens for a token to this resource server".to_string(),
+
+    multivalue: true,
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
 pub static ref SCHEMA_ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN: SchemaAttribute = SchemaAttribute {
     uuid: UUID_SCHEMA_ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN,
     name: Attribute::CredentialUpdateIntentToken.into(),
@@ -469,7 +597,7 @@ pub static ref SCHEMA_ATTR_API_TOKEN_SESSION: SchemaAttribute = SchemaAttribute
     name: Attribute::ApiTokenSession.into(),
     description: "A session entry related to an issued API token".to_string(),
 
-    index: vec![IndexType::Equality],
+    index: vec![IndexType::Equality, IndexType::Ordering],
     unique: true,
     multivalue: true,
     syntax: SyntaxType::ApiToken,
@@ -481,7 +609,7 @@ pub static ref SCHEMA_ATTR_USER_AUTH_TOKEN_SESSION: SchemaAttribute = SchemaAttr
     name: Attribute::UserAuthTokenSession.into(),
     description: "A session entry related to an issued user auth token".to_string(),
 
-    index: vec![IndexType::Equality],
+    index: vec![IndexType::Equality, IndexType::Ordering],
     unique: true,
     multivalue: true,
     syntax: SyntaxType::Session,
@@ -493,7 +621,7 @@ pub static ref SCHEMA_ATTR_OAUTH2_SESSION: SchemaAttribute = SchemaAttribute {
     name: Attribute::OAuth2Session.into(),
     description: "A session entry to an active oauth2 session, bound to a parent user auth token".to_string(),
 
-    index: vec![IndexType::Equality],
+    index: vec![IndexType::Equality, IndexType::Ordering],
     multivalue: true,
     syntax: SyntaxType::Oauth2Session,
     ..Default::default()
@@ -519,6 +647,304 @@ pub static ref SCHEMA_ATTR_SYNC_COOKIE: SchemaAttribute = SchemaAttribute {
     ..Default::default()
 };
 
+pub static ref SCHEMA_ATTR_SYNC_WRITEBACK_COOKIE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_SYNC_WRITEBACK_COOKIE,
+    name: Attribute::SyncWritebackCookie.into(),
+    description: "A private cookie of the last change written back to a remote IDM source".to_string(),
+
+    syntax: SyntaxType::PrivateBinary,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_MEMBER_EXPIRING: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_MEMBER_EXPIRING,
+    name: Attribute::MemberExpiring.into(),
+    description: "List of members of the group that are removed once their membership expires".to_string(),
+
+    index: vec![IndexType::Equality],
+    multivalue: true,
+    syntax: SyntaxType::ReferenceUuidExpiring,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REQUEST_APPROVER: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REQUEST_APPROVER,
+    name: Attribute::AccessRequestApprover.into(),
+    description: "Groups whose members may approve requests to join this group".to_string(),
+
+    index: vec![IndexType::Equality],
+    multivalue: true,
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REQUEST_DURATION: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REQUEST_DURATION,
+    name: Attribute::AccessRequestDuration.into(),
+    description: "The number of seconds that an approved request grants membership of this group for".to_string(),
+
+    syntax: SyntaxType::Uint32,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REQUEST_GROUP: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REQUEST_GROUP,
+    name: Attribute::AccessRequestGroup.into(),
+    description: "The group that membership of is requested".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REQUEST_REQUESTER: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REQUEST_REQUESTER,
+    name: Attribute::AccessRequestRequester.into(),
+    description: "The account that requested membership of a group".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REQUEST_JUSTIFICATION: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REQUEST_JUSTIFICATION,
+    name: Attribute::AccessRequestJustification.into(),
+    description: "The reason given for requesting membership of a group".to_string(),
+
+    syntax: SyntaxType::Utf8String,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REQUEST_STATE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REQUEST_STATE,
+    name: Attribute::AccessRequestState.into(),
+    description: "If an access request is pending, approved or denied".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::Utf8StringInsensitive,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REQUEST_DECIDED_BY: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REQUEST_DECIDED_BY,
+    name: Attribute::AccessRequestDecidedBy.into(),
+    description: "The account that approved or denied an access request".to_string(),
+
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ENTRY_MANAGED_BY: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ENTRY_MANAGED_BY,
+    name: Attribute::EntryManagedBy.into(),
+    description: "The account or group that owns and is responsible for this entry".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REVIEW_EXPIRY: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REVIEW_EXPIRY,
+    name: Attribute::AccessReviewExpiry.into(),
+    description: "The time at which an access review closes and undecided memberships are removed".to_string(),
+
+    syntax: SyntaxType::DateTime,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REVIEW_STATE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REVIEW_STATE,
+    name: Attribute::AccessReviewState.into(),
+    description: "If an access review is open or closed".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::Utf8StringInsensitive,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REVIEW_CAMPAIGN: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REVIEW_CAMPAIGN,
+    name: Attribute::AccessReviewCampaign.into(),
+    description: "The access review that this review item is part of".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REVIEW_GROUP: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REVIEW_GROUP,
+    name: Attribute::AccessReviewGroup.into(),
+    description: "The group that membership of is being reviewed".to_string(),
+
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REVIEW_MEMBER: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REVIEW_MEMBER,
+    name: Attribute::AccessReviewMember.into(),
+    description: "The member whose membership of a group is being reviewed".to_string(),
+
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REVIEW_REVIEWER: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REVIEW_REVIEWER,
+    name: Attribute::AccessReviewReviewer.into(),
+    description: "The accounts or groups that are assigned to decide this review item".to_string(),
+
+    index: vec![IndexType::Equality],
+    multivalue: true,
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REVIEW_DECISION: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REVIEW_DECISION,
+    name: Attribute::AccessReviewDecision.into(),
+    description: "If a reviewed membership is pending, kept or removed".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::Utf8StringInsensitive,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_ACCESS_REVIEW_DECIDED_BY: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_ACCESS_REVIEW_DECIDED_BY,
+    name: Attribute::AccessReviewDecidedBy.into(),
+    description: "The account that decided this review item".to_string(),
+
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_SUDO_USER: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_SUDO_USER,
+    name: Attribute::SudoUser.into(),
+    description: "The accounts or groups that a sudo rule applies to".to_string(),
+
+    index: vec![IndexType::Equality],
+    multivalue: true,
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_SUDO_HOST: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_SUDO_HOST,
+    name: Attribute::SudoHost.into(),
+    description: "The hosts or groups of hosts that a sudo rule applies on".to_string(),
+
+    index: vec![IndexType::Equality],
+    multivalue: true,
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_SUDO_COMMAND: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_SUDO_COMMAND,
+    name: Attribute::SudoCommand.into(),
+    description: "The absolute paths of the commands a sudo rule allows, or ALL".to_string(),
+
+    multivalue: true,
+    syntax: SyntaxType::Utf8String,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_SUDO_RUNAS: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_SUDO_RUNAS,
+    name: Attribute::SudoRunas.into(),
+    description: "The users that commands of a sudo rule may be run as, or ALL".to_string(),
+
+    multivalue: true,
+    syntax: SyntaxType::Utf8StringInsensitive,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_SUDO_NOPASSWD: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_SUDO_NOPASSWD,
+    name: Attribute::SudoNopasswd.into(),
+    description: "If a sudo rule allows commands to be run without reauthenticating".to_string(),
+
+    syntax: SyntaxType::Boolean,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_HBAC_USER: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_HBAC_USER,
+    name: Attribute::HbacUser.into(),
+    description: "The accounts or groups that a host access rule applies to".to_string(),
+
+    index: vec![IndexType::Equality],
+    multivalue: true,
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_HBAC_HOST: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_HBAC_HOST,
+    name: Attribute::HbacHost.into(),
+    description: "The hosts or groups of hosts that a host access rule applies on".to_string(),
+
+    index: vec![IndexType::Equality],
+    multivalue: true,
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_HBAC_SERVICE: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_HBAC_SERVICE,
+    name: Attribute::HbacService.into(),
+    description: "The PAM services that a host access rule allows, or ALL".to_string(),
+
+    multivalue: true,
+    syntax: SyntaxType::Utf8StringInsensitive,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_SUBID_RANGE_START: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_SUBID_RANGE_START,
+    name: Attribute::SubidRangeStart.into(),
+    description: "The first id of the subordinate uid and gid range allocated to a posix account".to_string(),
+
+    index: vec![IndexType::Equality],
+    unique: true,
+    syntax: SyntaxType::Uint32,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_AUTOMOUNT_MAP: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_AUTOMOUNT_MAP,
+    name: Attribute::AutomountMap.into(),
+    description: "The automount map that an automount key belongs to".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::ReferenceUuid,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_AUTOMOUNT_KEY: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_AUTOMOUNT_KEY,
+    name: Attribute::AutomountKey.into(),
+    description: "The key of an automount map entry, such as a mount point or *".to_string(),
+
+    index: vec![IndexType::Equality],
+    syntax: SyntaxType::Utf8String,
+    ..Default::default()
+};
+
+pub static ref SCHEMA_ATTR_AUTOMOUNT_INFORMATION: SchemaAttribute = SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_AUTOMOUNT_INFORMATION,
+    name: Attribute::AutomountInformation.into(),
+    description: "The mount options and location of an automount map entry".to_string(),
+
+    syntax: SyntaxType::Utf8String,
+    ..Default::default()
+};
+
 pub static ref SCHEMA_ATTR_GRANT_UI_HINT: SchemaAttribute = SchemaAttribute {
     uuid: UUID_SCHEMA_ATTR_GRANT_UI_HINT,
     name: Attribute::GrantUiHint.into(),
@@ -592,6 +1018,10 @@ pub static ref SCHEMA_CLASS_GROUP: SchemaClass = SchemaClass {
     sync_allowed: true,
     systemmay: vec![
         Attribute::Member.into(),
+        Attribute::MemberExpiring.into(),
+        Attribute::AccessRequestApprover.into(),
+        Attribute::AccessRequestDuration.into(),
+        Attribute::EntryManagedBy.into(),
         Attribute::GrantUiHint.into(),
         Attribute::Description.into()
     ],
@@ -612,6 +1042,130 @@ pub static ref SCHEMA_CLASS_DYNGROUP: SchemaClass = SchemaClass {
     ..Default::default()
 };
 
+pub static ref SCHEMA_CLASS_ACCOUNT_POLICY: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_ACCOUNT_POLICY,
+    name: EntryClass::AccountPolicy.into(),
+    description: "Policy that constrains the credentials and sessions of the members of a group".to_string(),
+
+    systemmay: vec![
+        Attribute::CredentialTypeAllowed.into(),
+        Attribute::AuthPasswordMinimumLength.into(),
+        Attribute::AuthSessionExpiry.into(),
+        Attribute::PrivilegeExpiry.into(),
+    ],
+    systemsupplements: vec![Attribute::Group.into()],
+    ..Default::default()
+};
+
+pub static ref SCHEMA_CLASS_ACCESS_REQUEST: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_ACCESS_REQUEST,
+    name: EntryClass::AccessRequest.into(),
+    description: "A request by an account to be granted membership of a group".to_string(),
+
+    systemmust: vec![Attribute::AccessRequestState.into()],
+    systemmay: vec![
+        Attribute::AccessRequestGroup.into(),
+        Attribute::AccessRequestRequester.into(),
+        Attribute::AccessRequestJustification.into(),
+        Attribute::AccessRequestDecidedBy.into(),
+    ],
+    ..Default::default()
+};
+
+pub static ref SCHEMA_CLASS_ACCESS_REVIEW: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_ACCESS_REVIEW,
+    name: EntryClass::AccessReview.into(),
+    description: "A campaign to review and attest to the memberships of a set of groups".to_string(),
+
+    systemmust: vec![
+        Attribute::Name.into(),
+        Attribute::AccessReviewExpiry.into(),
+        Attribute::AccessReviewState.into(),
+    ],
+    ..Default::default()
+};
+
+pub static ref SCHEMA_CLASS_ACCESS_REVIEW_ITEM: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_ACCESS_REVIEW_ITEM,
+    name: EntryClass::AccessReviewItem.into(),
+    description: "The review of a single membership of a group within an access review".to_string(),
+
+    systemmust: vec![Attribute::AccessReviewDecision.into()],
+    systemmay: vec![
+        Attribute::AccessReviewCampaign.into(),
+        Attribute::AccessReviewGroup.into(),
+        Attribute::AccessReviewMember.into(),
+        Attribute::AccessReviewReviewer.into(),
+        Attribute::AccessReviewDecidedBy.into(),
+    ],
+    ..Default::default()
+};
+
+pub static ref SCHEMA_CLASS_SUDO_RULE: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_SUDO_RULE,
+    name: EntryClass::SudoRule.into(),
+    description: "A rule allowing accounts to run commands as other users on unix hosts".to_string(),
+
+    systemmust: vec![Attribute::Name.into()],
+    systemmay: vec![
+        Attribute::Description.into(),
+        Attribute::SudoUser.into(),
+        Attribute::SudoHost.into(),
+        Attribute::SudoCommand.into(),
+        Attribute::SudoRunas.into(),
+        Attribute::SudoNopasswd.into(),
+    ],
+    ..Default::default()
+};
+
+pub static ref SCHEMA_CLASS_HBAC_RULE: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_HBAC_RULE,
+    name: EntryClass::HbacRule.into(),
+    description: "A rule allowing accounts to access unix hosts through PAM services".to_string(),
+
+    systemmust: vec![Attribute::Name.into()],
+    systemmay: vec![
+        Attribute::Description.into(),
+        Attribute::HbacUser.into(),
+        Attribute::HbacHost.into(),
+        Attribute::HbacService.into(),
+    ],
+    ..Default::default()
+};
+
+pub static ref SCHEMA_CLASS_HOST: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_HOST,
+    name: EntryClass::Host.into(),
+    description: "A unix host that authenticates with its own service account credentials".to_string(),
+
+    systemsupplements: vec![EntryClass::ServiceAccount.into()],
+    ..Default::default()
+};
+
+pub static ref SCHEMA_CLASS_AUTOMOUNT_MAP: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_AUTOMOUNT_MAP,
+    name: EntryClass::AutomountMap.into(),
+    description: "An automount map, such as auto.master or auto.home".to_string(),
+
+    systemmust: vec![Attribute::Name.into()],
+    systemmay: vec![Attribute::Description.into()],
+    ..Default::default()
+};
+
+pub static ref SCHEMA_CLASS_AUTOMOUNT: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_AUTOMOUNT,
+    name: EntryClass::Automount.into(),
+    description: "A key of an automount map and the location it mounts".to_string(),
+
+    systemmust: vec![
+        Attribute::AutomountMap.into(),
+        Attribute::AutomountKey.into(),
+        Attribute::AutomountInformation.into(),
+    ],
+    systemmay: vec![Attribute::Description.into()],
+    ..Default::default()
+};
+
 pub static ref SCHEMA_CLASS_ACCOUNT: SchemaClass = SchemaClass {
     uuid: UUID_SCHEMA_CLASS_ACCOUNT,
     name: EntryClass::Account.into(),
@@ -672,6 +1226,7 @@ pub static ref SCHEMA_CLASS_SYNC_ACCOUNT: SchemaClass = SchemaClass {
         Attribute::SyncCookie.into(),
         Attribute::SyncCredentialPortal.into(),
         Attribute::SyncYieldAuthority.into(),
+        Attribute::SyncWritebackCookie.into(),
     ],
     systemexcludes: vec![EntryClass::Account.into()],
     ..Default::default()
@@ -718,7 +1273,11 @@ pub static ref SCHEMA_CLASS_POSIXACCOUNT: SchemaClass = SchemaClass {
     description: "Object representation of a posix account, requires account".to_string(),
 
     sync_allowed: true,
-    systemmay: vec![Attribute::LoginShell.into(), Attribute::UnixPassword.into()],
+    systemmay: vec![
+        Attribute::LoginShell.into(),
+        Attribute::UnixPassword.into(),
+        Attribute::SubidRangeStart.into(),
+    ],
     systemmust: vec![Attribute::GidNumber.into()],
     systemsupplements: vec![Attribute::Account.into()],
     ..Default::default()
@@ -751,6 +1310,8 @@ pub static ref SCHEMA_CLASS_OAUTH2_RS: SchemaClass = SchemaClass {
         Attribute::OAuth2JwtLegacyCryptoEnable.into(),
         Attribute::OAuth2PreferShortUsername.into(),
         Attribute::OAuth2RsOriginLanding.into(),
+        Attribute::OAuth2DeviceFlowEnable.into(),
+        Attribute::OAuth2RsTokenExchangeSource.into(),
         Attribute::Image.into(),
     ],
     systemmust: vec![
@@ -763,12 +1324,35 @@ pub static ref SCHEMA_CLASS_OAUTH2_RS: SchemaClass = SchemaClass {
     ..Default::default()
 };
 
+pub static ref SCHEMA_CLASS_OAUTH2_DEVICE_AUTHORISATION: SchemaClass = SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_OAUTH2_DEVICE_AUTHORISATION,
+    name: EntryClass::OAuth2DeviceAuthorisation.into(),
+    description: "An OAuth2 device authorisation that is waiting to be collected by the device".to_string(),
+
+    systemmust: vec![
+        Attribute::OAuth2DeviceUserCode.into(),
+        Attribute::OAuth2DeviceExpiry.into(),
+        Attribute::OAuth2DeviceState.into(),
+    ],
+    systemmay: vec![
+        Attribute::OAuth2DeviceClient.into(),
+        Attribute::OAuth2DeviceScope.into(),
+        Attribute::OAuth2DeviceAccount.into(),
+        Attribute::OAuth2DeviceParentSession.into(),
+        Attribute::OAuth2DeviceParentExpiry.into(),
+    ],
+    ..Default::default()
+};
+
 pub static ref SCHEMA_CLASS_OAUTH2_RS_BASIC: SchemaClass = SchemaClass {
     uuid: UUID_SCHEMA_CLASS_OAUTH2_RS_BASIC,
     name: EntryClass::OAuth2ResourceServerBasic.into(),
     description: "The class representing a configured Oauth2 Resource Server authenticated with http basic authentication".to_string(),
 
-    systemmay: vec![ Attribute::OAuth2AllowInsecureClientDisablePkce.into()],
+    systemmay: vec![
+        Attribute::OAuth2AllowInsecureClientDisablePkce.into(),
+        Attribute::OAuth2RsClientCredentialsScopes.into(),
+    ],
     systemmust: vec![ Attribute::OAuth2RsBasicSecret.into()],
     systemexcludes: vec![ EntryClass::OAuth2ResourceServerPublic.into()],
     ..Default::default()
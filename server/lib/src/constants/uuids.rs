@@ -57,6 +57,10 @@ pub const UUID_IDM_HP_SYNC_ACCOUNT_MANAGE_PRIV: Uuid =
 pub const UUID_IDM_UI_ENABLE_EXPERIMENTAL_FEATURES: Uuid =
     uuid!("00000000-0000-0000-0000-000000000038");
 pub const UUID_IDM_ACCOUNT_MAIL_READ_PRIV: Uuid = uuid!("00000000-0000-0000-0000-000000000039");
+pub const UUID_IDM_HP_SUDO_RULE_MANAGE_PRIV: Uuid = uuid!("00000000-0000-0000-0000-000000000040");
+pub const UUID_IDM_HP_HBAC_MANAGE_PRIV: Uuid = uuid!("00000000-0000-0000-0000-000000000041");
+pub const UUID_IDM_HP_AUTOMOUNT_MANAGE_PRIV: Uuid = uuid!("00000000-0000-0000-0000-000000000042");
+pub const UUID_IDM_ALL_HOSTS: Uuid = uuid!("00000000-0000-0000-0000-000000000043");
 
 //
 pub const UUID_IDM_HIGH_PRIVILEGE: Uuid = uuid!("00000000-0000-0000-0000-000000001000");
@@ -239,6 +243,95 @@ pub const UUID_SCHEMA_ATTR_AUTH_PRIVILEGE_EXPIRY: Uuid =
     uuid!("00000000-0000-0000-0000-ffff00000142");
 
 pub const UUID_SCHEMA_ATTR_IMAGE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000143");
+pub const UUID_SCHEMA_ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000144");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_FLOW_ENABLE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000145");
+pub const UUID_SCHEMA_ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000146");
+pub const UUID_SCHEMA_CLASS_ACCOUNT_POLICY: Uuid = uuid!("00000000-0000-0000-0000-ffff00000147");
+pub const UUID_SCHEMA_ATTR_CREDENTIAL_TYPE_ALLOWED: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000148");
+pub const UUID_SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_LENGTH: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000149");
+pub const UUID_SCHEMA_ATTR_SYNC_WRITEBACK_COOKIE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000014a");
+pub const UUID_SCHEMA_ATTR_MEMBER_EXPIRING: Uuid = uuid!("00000000-0000-0000-0000-ffff0000014b");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_APPROVER: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000014c");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_DURATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000014d");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_GROUP: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000014e");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_REQUESTER: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000014f");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_JUSTIFICATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000150");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_STATE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000151");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_DECIDED_BY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000152");
+pub const UUID_SCHEMA_CLASS_ACCESS_REQUEST: Uuid = uuid!("00000000-0000-0000-0000-ffff00000153");
+pub const UUID_SCHEMA_ATTR_ENTRY_MANAGED_BY: Uuid = uuid!("00000000-0000-0000-0000-ffff00000154");
+pub const UUID_SCHEMA_ATTR_ACCESS_REVIEW_EXPIRY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000155");
+pub const UUID_SCHEMA_ATTR_ACCESS_REVIEW_STATE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000156");
+pub const UUID_SCHEMA_ATTR_ACCESS_REVIEW_CAMPAIGN: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000157");
+pub const UUID_SCHEMA_ATTR_ACCESS_REVIEW_GROUP: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000158");
+pub const UUID_SCHEMA_ATTR_ACCESS_REVIEW_MEMBER: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000159");
+pub const UUID_SCHEMA_ATTR_ACCESS_REVIEW_REVIEWER: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000015a");
+pub const UUID_SCHEMA_ATTR_ACCESS_REVIEW_DECISION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000015b");
+pub const UUID_SCHEMA_ATTR_ACCESS_REVIEW_DECIDED_BY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000015c");
+pub const UUID_SCHEMA_CLASS_ACCESS_REVIEW: Uuid = uuid!("00000000-0000-0000-0000-ffff0000015d");
+pub const UUID_SCHEMA_CLASS_ACCESS_REVIEW_ITEM: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000015e");
+pub const UUID_SCHEMA_ATTR_SUDO_USER: Uuid = uuid!("00000000-0000-0000-0000-ffff0000015f");
+pub const UUID_SCHEMA_ATTR_SUDO_HOST: Uuid = uuid!("00000000-0000-0000-0000-ffff00000160");
+pub const UUID_SCHEMA_ATTR_SUDO_COMMAND: Uuid = uuid!("00000000-0000-0000-0000-ffff00000161");
+pub const UUID_SCHEMA_ATTR_SUDO_RUNAS: Uuid = uuid!("00000000-0000-0000-0000-ffff00000162");
+pub const UUID_SCHEMA_ATTR_SUDO_NOPASSWD: Uuid = uuid!("00000000-0000-0000-0000-ffff00000163");
+pub const UUID_SCHEMA_CLASS_SUDO_RULE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000164");
+pub const UUID_SCHEMA_ATTR_HBAC_USER: Uuid = uuid!("00000000-0000-0000-0000-ffff00000165");
+pub const UUID_SCHEMA_ATTR_HBAC_HOST: Uuid = uuid!("00000000-0000-0000-0000-ffff00000166");
+pub const UUID_SCHEMA_ATTR_HBAC_SERVICE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000167");
+pub const UUID_SCHEMA_CLASS_HBAC_RULE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000168");
+pub const UUID_SCHEMA_CLASS_HOST: Uuid = uuid!("00000000-0000-0000-0000-ffff00000169");
+pub const UUID_SCHEMA_ATTR_SUBID_RANGE_START: Uuid = uuid!("00000000-0000-0000-0000-ffff0000016a");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNT_MAP: Uuid = uuid!("00000000-0000-0000-0000-ffff0000016b");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNT_KEY: Uuid = uuid!("00000000-0000-0000-0000-ffff0000016c");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNT_INFORMATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000016d");
+pub const UUID_SCHEMA_CLASS_AUTOMOUNT_MAP: Uuid = uuid!("00000000-0000-0000-0000-ffff0000016e");
+pub const UUID_SCHEMA_CLASS_AUTOMOUNT: Uuid = uuid!("00000000-0000-0000-0000-ffff0000016f");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNTMAPNAME: Uuid = uuid!("00000000-0000-0000-0000-ffff00000170");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNTKEY: Uuid = uuid!("00000000-0000-0000-0000-ffff00000171");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNTINFORMATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000172");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_USER_CODE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000173");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_CLIENT: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000174");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_SCOPE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000175");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_EXPIRY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000176");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_STATE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000177");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_ACCOUNT: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000178");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_PARENT_SESSION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000179");
+pub const UUID_SCHEMA_ATTR_OAUTH2_DEVICE_PARENT_EXPIRY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000017a");
+pub const UUID_SCHEMA_CLASS_OAUTH2_DEVICE_AUTHORISATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000017b");
 
 // System and domain infos
 // I'd like to strongly criticise william of the past for making poor choices about these allocations.
@@ -320,6 +413,22 @@ pub const UUID_IDM_ACP_ACCOUNT_MAIL_READ_PRIV_V1: Uuid =
 pub const UUID_IDM_ACCOUNT_SELF_ACP_WRITE_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000046");
 pub const UUID_IDM_ACP_SYSTEM_CONFIG_SESSION_EXP_PRIV_V1: Uuid =
     uuid!("00000000-0000-0000-0000-ffffff000047");
+pub const UUID_IDM_ACP_GROUP_ACCOUNT_POLICY_MANAGE_PRIV_V1: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff000048");
+pub const UUID_IDM_HP_ACP_SUDO_RULE_MANAGE_PRIV_V1: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff000049");
+pub const UUID_IDM_ACP_SUDO_RULE_READ_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000050");
+pub const UUID_IDM_HP_ACP_HOST_MANAGE_PRIV_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000051");
+pub const UUID_IDM_HP_ACP_HBAC_RULE_MANAGE_PRIV_V1: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff000052");
+pub const UUID_IDM_ACP_HBAC_RULE_READ_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000053");
+pub const UUID_IDM_HP_ACP_AUTOMOUNT_MANAGE_PRIV_V1: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff000054");
+pub const UUID_IDM_ACP_AUTOMOUNT_READ_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000055");
+pub const UUID_IDM_ADMINS_ACP_REPL_CONFLICT_V1: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff000056");
+pub const UUID_IDM_ACP_ACCESS_REVIEW_MANAGE_V1: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff000057");
 
 // End of system ranges
 pub const UUID_DOES_NOT_EXIST: Uuid = uuid!("00000000-0000-0000-0000-fffffffffffe");
@@ -1,7 +1,10 @@
 use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 
 use hashbrown::{HashMap as Map, HashSet};
 use kanidm_proto::v1::{BackupCodesView, CredentialDetail, CredentialDetailType, OperationError};
+use num_enum::TryFromPrimitive;
 use uuid::Uuid;
 use webauthn_rs::prelude::{AuthenticationResult, Passkey, SecurityKey};
 use webauthn_rs_core::proto::{Credential as WebauthnCredential, CredentialV3};
@@ -19,16 +22,42 @@ use kanidm_lib_crypto::CryptoPolicy;
 use crate::credential::softlock::CredSoftLockPolicy;
 use crate::credential::totp::Totp;
 
-// These are in order of "relative" strength.
-/*
-#[derive(Clone, Debug)]
-pub enum Policy {
-    PasswordOnly,
-    WebauthnOnly,
-    GeneratedPassword,
-    PasswordAndWebauthn,
+/// The kind of a credential, without any of its content. This is what account policy uses
+/// to express which credentials an account may authenticate with. Passkeys share the
+/// `Webauthn` kind with webauthn primary credentials.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, TryFromPrimitive)]
+#[repr(u16)]
+pub enum CredentialKind {
+    Password = 0,
+    GeneratedPassword = 1,
+    PasswordMfa = 2,
+    Webauthn = 3,
+}
+
+impl fmt::Display for CredentialKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialKind::Password => write!(f, "password"),
+            CredentialKind::GeneratedPassword => write!(f, "generatedpassword"),
+            CredentialKind::PasswordMfa => write!(f, "passwordmfa"),
+            CredentialKind::Webauthn => write!(f, "webauthn"),
+        }
+    }
+}
+
+impl FromStr for CredentialKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "password" => Ok(CredentialKind::Password),
+            "generatedpassword" => Ok(CredentialKind::GeneratedPassword),
+            "passwordmfa" => Ok(CredentialKind::PasswordMfa),
+            "webauthn" | "passkey" => Ok(CredentialKind::Webauthn),
+            _ => Err(()),
+        }
+    }
 }
-*/
 
 pub use kanidm_lib_crypto::Password;
 
@@ -96,7 +125,6 @@ impl BackupCodes {
 /// In this way, each Credential provides it's own password requirements and policy, and requires
 /// some metadata to support this such as it's source and strength etc.
 pub struct Credential {
-    // policy: Policy,
     pub(crate) type_: CredentialType,
     // Uuid of Credential, used by auth session to lock this specific credential
     // if required.
@@ -953,6 +981,15 @@ impl Credential {
 }
 
 impl CredentialType {
+    pub(crate) fn kind(&self) -> CredentialKind {
+        match self {
+            CredentialType::Password(_) => CredentialKind::Password,
+            CredentialType::GeneratedPassword(_) => CredentialKind::GeneratedPassword,
+            CredentialType::PasswordMfa(..) => CredentialKind::PasswordMfa,
+            CredentialType::Webauthn(_) => CredentialKind::Webauthn,
+        }
+    }
+
     fn is_valid(&self) -> bool {
         match self {
             CredentialType::Password(_) | CredentialType::GeneratedPassword(_) => true,
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use crate::metrics::METRICS;
+
 /// Represents a temporary denial of the credential to authenticate. This is used
 /// to ratelimit and prevent bruteforcing of accounts. At an initial failure the
 /// SoftLock is created and the count set to 1, with a unlock_at set to 1 second
@@ -195,6 +197,9 @@ impl CredSoftLock {
                 // LockState::Locked(count + 1, reset_at, unlock_at)
             }
         };
+        if matches!(next_state, LockState::Locked(..)) {
+            METRICS.softlock_engaged();
+        }
         std::mem::swap(&mut self.state, &mut next_state);
     }
 
@@ -48,7 +48,7 @@ use webauthn_rs::prelude::{AttestedPasskey as DeviceKeyV4, Passkey as PasskeyV4}
 use crate::be::dbentry::{DbEntry, DbEntryVers};
 use crate::be::dbvalue::DbValueSetV2;
 use crate::be::{IdxKey, IdxSlope};
-use crate::credential::Credential;
+use crate::credential::{Credential, CredentialKind};
 use crate::filter::{Filter, FilterInvalid, FilterResolved, FilterValidResolved};
 use crate::idm::ldap::ldap_vattr_map;
 use crate::modify::{Modify, ModifyInvalid, ModifyList, ModifyValid};
@@ -1328,7 +1328,7 @@ impl<STATE> Entry<EntrySealed, STATE> {
 }
 
 impl Entry<EntrySealed, EntryCommitted> {
-    #[cfg(test)]
+    /// The change id of the most recent modification to this entry.
     pub(crate) fn get_last_changed(&self) -> Cid {
         self.valid.ecstate.get_tail_cid()
     }
@@ -1627,6 +1627,11 @@ impl Entry<EntrySealed, EntryCommitted> {
                                         vec![Err((&ikey.attr, ikey.itype, "_".to_string()))]
                                     }
                                     IndexType::SubString => Vec::new(),
+                                    IndexType::Ordering => vs
+                                        .generate_idx_ord_keys()
+                                        .into_iter()
+                                        .map(|idx_key| Err((&ikey.attr, ikey.itype, idx_key)))
+                                        .collect(),
                                 };
                                 changes
                             }
@@ -1663,6 +1668,11 @@ impl Entry<EntrySealed, EntryCommitted> {
                                         vec![Ok((&ikey.attr, ikey.itype, "_".to_string()))]
                                     }
                                     IndexType::SubString => Vec::new(),
+                                    IndexType::Ordering => vs
+                                        .generate_idx_ord_keys()
+                                        .into_iter()
+                                        .map(|idx_key| Ok((&ikey.attr, ikey.itype, idx_key)))
+                                        .collect(),
                                 };
                                 // For each value
                                 //
@@ -1709,6 +1719,11 @@ impl Entry<EntrySealed, EntryCommitted> {
                                         vec![Err((&ikey.attr, ikey.itype, "_".to_string()))]
                                     }
                                     IndexType::SubString => Vec::new(),
+                                    IndexType::Ordering => pre_vs
+                                        .generate_idx_ord_keys()
+                                        .into_iter()
+                                        .map(|idx_key| Err((&ikey.attr, ikey.itype, idx_key)))
+                                        .collect(),
                                 };
                                 changes
                             }
@@ -1728,15 +1743,32 @@ impl Entry<EntrySealed, EntryCommitted> {
                                         vec![Ok((&ikey.attr, ikey.itype, "_".to_string()))]
                                     }
                                     IndexType::SubString => Vec::new(),
+                                    IndexType::Ordering => post_vs
+                                        .generate_idx_ord_keys()
+                                        .into_iter()
+                                        .map(|idx_key| Ok((&ikey.attr, ikey.itype, idx_key)))
+                                        .collect(),
                                 };
                                 changes
                             }
                             (Some(pre_vs), Some(post_vs)) => {
                                 // it exists in both, we need to work out the difference within the attr.
 
-                                let mut pre_idx_keys = pre_vs.generate_idx_eq_keys();
+                                let (mut pre_idx_keys, mut post_idx_keys) = match ikey.itype {
+                                    IndexType::Equality => (
+                                        pre_vs.generate_idx_eq_keys(),
+                                        post_vs.generate_idx_eq_keys(),
+                                    ),
+                                    IndexType::Ordering => (
+                                        pre_vs.generate_idx_ord_keys(),
+                                        post_vs.generate_idx_ord_keys(),
+                                    ),
+                                    // No action - we still are "present", so nothing to do!
+                                    IndexType::Presence | IndexType::SubString => {
+                                        return Vec::new()
+                                    }
+                                };
                                 pre_idx_keys.sort_unstable();
-                                let mut post_idx_keys = post_vs.generate_idx_eq_keys();
                                 post_idx_keys.sort_unstable();
 
                                 let sz = if pre_idx_keys.len() > post_idx_keys.len() {
@@ -1791,22 +1823,14 @@ impl Entry<EntrySealed, EntryCommitted> {
                                 let mut diff =
                                     Vec::with_capacity(removed_vs.len() + added_vs.len());
 
-                                match ikey.itype {
-                                    IndexType::Equality => {
-                                        removed_vs
-                                            .into_iter()
-                                            .map(|idx_key| Err((&ikey.attr, ikey.itype, idx_key)))
-                                            .for_each(|v| diff.push(v));
-                                        added_vs
-                                            .into_iter()
-                                            .map(|idx_key| Ok((&ikey.attr, ikey.itype, idx_key)))
-                                            .for_each(|v| diff.push(v));
-                                    }
-                                    IndexType::Presence => {
-                                        // No action - we still are "present", so nothing to do!
-                                    }
-                                    IndexType::SubString => {}
-                                };
+                                removed_vs
+                                    .into_iter()
+                                    .map(|idx_key| Err((&ikey.attr, ikey.itype, idx_key)))
+                                    .for_each(|v| diff.push(v));
+                                added_vs
+                                    .into_iter()
+                                    .map(|idx_key| Ok((&ikey.attr, ikey.itype, idx_key)))
+                                    .for_each(|v| diff.push(v));
                                 // Return the diff
                                 diff
                             }
@@ -2738,6 +2762,14 @@ impl<VALID, STATE> Entry<VALID, STATE> {
             .and_then(|vs| vs.as_uihint_set())
     }
 
+    #[inline(always)]
+    /// Get the set of credential types in this attribute, if any are present.
+    pub fn get_ava_credtype(&self, attr: Attribute) -> Option<&BTreeSet<CredentialKind>> {
+        self.attrs
+            .get(attr.as_ref())
+            .and_then(|vs| vs.as_credtype_set())
+    }
+
     #[inline(always)]
     /// Return a single secret value, if valid to transform this value.
     pub fn get_ava_single_secret(&self, attr: Attribute) -> Option<&str> {
@@ -2876,6 +2908,31 @@ impl<VALID, STATE> Entry<VALID, STATE> {
             .unwrap_or(false)
     }
 
+    #[inline(always)]
+    /// Assert if an attribute of this name is present, and one of it's values matches
+    /// the following substring pattern.
+    pub fn attribute_substring_pattern(&self, attr: Attribute, pattern: &SubStringPattern) -> bool {
+        self.attrs
+            .get(attr.as_ref())
+            .map(|vset| vset.substring_pattern(pattern))
+            .unwrap_or(false)
+    }
+
+    #[inline(always)]
+    /// Assert if an attribute of this name is present, and one of it's values is equal
+    /// to the following partial value when compared by this matching rule.
+    pub fn attribute_equality_rule(
+        &self,
+        attr: Attribute,
+        rule: MatchingRule,
+        value: &PartialValue,
+    ) -> bool {
+        self.attrs
+            .get(attr.as_ref())
+            .map(|vset| vset.equality_rule(rule, value))
+            .unwrap_or(false)
+    }
+
     #[inline(always)]
     /// Assert if an attribute of this name is present, and one of it's values is less than
     /// the following partial value
@@ -2886,6 +2943,16 @@ impl<VALID, STATE> Entry<VALID, STATE> {
             .unwrap_or(false)
     }
 
+    #[inline(always)]
+    /// Assert if an attribute of this name is present, and one of it's values is greater than
+    /// the following partial value
+    pub fn attribute_greaterthan(&self, attr: Attribute, subvalue: &PartialValue) -> bool {
+        self.attrs
+            .get(attr.as_ref())
+            .map(|vset| vset.greaterthan(subvalue))
+            .unwrap_or(false)
+    }
+
     // Since EntryValid/Invalid is just about class adherenece, not Value correctness, we
     // can now apply filters to invalid entries - why? Because even if they aren't class
     // valid, we still have strict typing checks between the filter -> entry to guarantee
@@ -2918,6 +2985,20 @@ impl<VALID, STATE> Entry<VALID, STATE> {
                     false
                 }
             },
+            FilterResolved::SubPattern(attr, pattern, _) => match attr.try_into() {
+                Ok(a) => self.attribute_substring_pattern(a, pattern),
+                Err(_) => {
+                    admin_error!("Failed to convert {} to attribute!", attr);
+                    false
+                }
+            },
+            FilterResolved::EqRule(attr, rule, value, _) => match attr.try_into() {
+                Ok(a) => self.attribute_equality_rule(a, *rule, value),
+                Err(_) => {
+                    admin_error!("Failed to convert {} to attribute!", attr);
+                    false
+                }
+            },
             FilterResolved::Pres(attr, _) => match attr.try_into() {
                 Ok(a) => self.attribute_pres(a),
                 Err(_) => {
@@ -2932,6 +3013,13 @@ impl<VALID, STATE> Entry<VALID, STATE> {
                     false
                 }
             },
+            FilterResolved::GreaterThan(attr, subvalue, _) => match attr.try_into() {
+                Ok(a) => self.attribute_greaterthan(a, subvalue),
+                Err(_) => {
+                    admin_error!("Failed to convert {} to attribute!", attr);
+                    false
+                }
+            },
             // Check with ftweedal about or filter zero len correctness.
             FilterResolved::Or(l, _) => l.iter().any(|f| self.entry_match_no_index_inner(f)),
             // Check with ftweedal about and filter zero len correctness.
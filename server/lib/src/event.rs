@@ -789,6 +789,48 @@ impl PurgeRecycledEvent {
     }
 }
 
+#[derive(Debug)]
+pub struct PurgeExpiredMembersEvent {
+    pub ident: Identity,
+    pub eventid: Uuid,
+}
+
+impl Default for PurgeExpiredMembersEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PurgeExpiredMembersEvent {
+    pub fn new() -> Self {
+        PurgeExpiredMembersEvent {
+            ident: Identity::from_internal(),
+            eventid: Uuid::new_v4(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CloseExpiredAccessReviewsEvent {
+    pub ident: Identity,
+    pub eventid: Uuid,
+}
+
+impl Default for CloseExpiredAccessReviewsEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CloseExpiredAccessReviewsEvent {
+    pub fn new() -> Self {
+        CloseExpiredAccessReviewsEvent {
+            ident: Identity::from_internal(),
+            eventid: Uuid::new_v4(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct OnlineBackupEvent {
     pub ident: Identity,
@@ -19,8 +19,9 @@ use hashbrown::HashMap;
 #[cfg(test)]
 use hashbrown::HashSet;
 use kanidm_proto::constants::ATTR_UUID;
+use kanidm_proto::scim_v2::{ScimAttrPath, ScimCompareOp, ScimFilter};
 use kanidm_proto::v1::{Filter as ProtoFilter, OperationError, SchemaError};
-use ldap3_proto::proto::{LdapFilter, LdapSubstringFilter};
+use ldap3_proto::proto::{LdapFilter, LdapMatchingRuleAssertion, LdapSubstringFilter};
 // use smartstring::alias::String as AttrString;
 use serde::Deserialize;
 use uuid::Uuid;
@@ -29,10 +30,22 @@ use crate::be::{IdxKey, IdxKeyRef, IdxKeyToRef, IdxMeta, IdxSlope};
 use crate::idm::ldap::ldap_attr_filter_map;
 use crate::prelude::*;
 use crate::schema::SchemaTransaction;
-use crate::value::{IndexType, PartialValue};
+use crate::value::{IndexType, MatchingRule, PartialValue, SubStringPattern};
 
 const FILTER_DEPTH_MAX: usize = 16;
 
+// Ldap extensible matching rules, by name and oid. Names are compared in lower case.
+const LDAP_MATCH_CASE_EXACT: &str = "caseexactmatch";
+const LDAP_MATCH_CASE_EXACT_OID: &str = "2.5.13.5";
+const LDAP_MATCH_CASE_EXACT_IA5: &str = "caseexactia5match";
+const LDAP_MATCH_CASE_EXACT_IA5_OID: &str = "1.3.6.1.4.1.1466.109.114.1";
+const LDAP_MATCH_CASE_IGNORE: &str = "caseignorematch";
+const LDAP_MATCH_CASE_IGNORE_OID: &str = "2.5.13.2";
+const LDAP_MATCH_CASE_IGNORE_IA5: &str = "caseignoreia5match";
+const LDAP_MATCH_CASE_IGNORE_IA5_OID: &str = "1.3.6.1.4.1.1466.109.114.2";
+// LDAP_MATCHING_RULE_IN_CHAIN from active directory.
+const LDAP_MATCH_IN_CHAIN_OID: &str = "1.2.840.113556.1.4.1941";
+
 // Default filter is safe, ignores all hidden types!
 
 // This is &Value so we can lazy const then clone, but perhaps we can reconsider
@@ -45,6 +58,14 @@ pub fn f_sub<'a>(a: Attribute, v: PartialValue) -> FC<'a> {
     FC::Sub(a.into(), v)
 }
 
+pub fn f_sub_pattern<'a>(a: Attribute, p: SubStringPattern) -> FC<'a> {
+    FC::SubPattern(a.into(), p)
+}
+
+pub fn f_eq_rule<'a>(a: Attribute, r: MatchingRule, v: PartialValue) -> FC<'a> {
+    FC::EqRule(a.into(), r, v)
+}
+
 pub fn f_pres<'a>(a: Attribute) -> FC<'a> {
     FC::Pres(a.into())
 }
@@ -53,6 +74,10 @@ pub fn f_lt<'a>(a: Attribute, v: PartialValue) -> FC<'a> {
     FC::LessThan(a.into(), v)
 }
 
+pub fn f_gt<'a>(a: Attribute, v: PartialValue) -> FC<'a> {
+    FC::GreaterThan(a.into(), v)
+}
+
 pub fn f_or(vs: Vec<FC>) -> FC {
     FC::Or(vs)
 }
@@ -100,8 +125,11 @@ pub fn f_spn_name(id: &str) -> FC<'static> {
 pub enum FC<'a> {
     Eq(&'a str, PartialValue),
     Sub(&'a str, PartialValue),
+    SubPattern(&'a str, SubStringPattern),
+    EqRule(&'a str, MatchingRule, PartialValue),
     Pres(&'a str),
     LessThan(&'a str, PartialValue),
+    GreaterThan(&'a str, PartialValue),
     Or(Vec<FC<'a>>),
     And(Vec<FC<'a>>),
     Inclusion(Vec<FC<'a>>),
@@ -116,8 +144,11 @@ enum FilterComp {
     // This is attr - value
     Eq(AttrString, PartialValue),
     Sub(AttrString, PartialValue),
+    SubPattern(AttrString, SubStringPattern),
+    EqRule(AttrString, MatchingRule, PartialValue),
     Pres(AttrString),
     LessThan(AttrString, PartialValue),
+    GreaterThan(AttrString, PartialValue),
     Or(Vec<FilterComp>),
     And(Vec<FilterComp>),
     Inclusion(Vec<FilterComp>),
@@ -142,8 +173,11 @@ pub enum FilterResolved {
     // This is attr - value - indexed slope factor
     Eq(AttrString, PartialValue, Option<NonZeroU8>),
     Sub(AttrString, PartialValue, Option<NonZeroU8>),
+    SubPattern(AttrString, SubStringPattern, Option<NonZeroU8>),
+    EqRule(AttrString, MatchingRule, PartialValue, Option<NonZeroU8>),
     Pres(AttrString, Option<NonZeroU8>),
     LessThan(AttrString, PartialValue, Option<NonZeroU8>),
+    GreaterThan(AttrString, PartialValue, Option<NonZeroU8>),
     Or(Vec<FilterResolved>, Option<NonZeroU8>),
     And(Vec<FilterResolved>, Option<NonZeroU8>),
     // All terms must have 1 or more items, or the inclusion is false!
@@ -175,10 +209,17 @@ pub enum FilterPlan {
     SubIndexed(AttrString, String),
     SubUnindexed(AttrString),
     SubCorrupt(AttrString),
+    SubPatternUnindexed(AttrString),
+    EqRuleUnindexed(AttrString),
     PresIndexed(AttrString),
     PresUnindexed(AttrString),
     PresCorrupt(AttrString),
+    LessThanIndexed(AttrString, String),
     LessThanUnindexed(AttrString),
+    LessThanCorrupt(AttrString),
+    GreaterThanIndexed(AttrString, String),
+    GreaterThanUnindexed(AttrString),
+    GreaterThanCorrupt(AttrString),
     OrUnindexed(Vec<FilterPlan>),
     OrIndexed(Vec<FilterPlan>),
     OrPartial(Vec<FilterPlan>),
@@ -199,6 +240,11 @@ pub enum FilterPlan {
 /// * `Pres`ence. An ava of that attribute's name exists, with any value on the [`Entry`].
 /// * `Eq`uality. An ava of the attribute exists and contains this matching value.
 /// * `Sub`string. An ava of the attribute exists and has a substring containing the requested value.
+/// * `SubPattern`. An ava of the attribute exists and has a value matching the substring pattern.
+/// * `EqRule`. An ava of the attribute exists and is equal to this value when both are compared
+/// by a looser matching rule, such as ignoring case.
+/// * `LessThan` / `GreaterThan`. An ava of the attribute exists with a value ordered before or
+/// after this value.
 /// * `Or`. Contains multiple filters and asserts at least one is true.
 /// * `And`. Contains multiple filters and asserts all of them are true.
 /// * `AndNot`. This is different to a "logical not" operation. This asserts that a condition is not
@@ -430,6 +476,7 @@ impl Filter<FilterInvalid> {
             (Attribute::MemberOf.into(), IndexType::Presence),
             (Attribute::DirectMemberOf.into(), IndexType::Equality),
             (Attribute::DirectMemberOf.into(), IndexType::Presence),
+            (Attribute::GidNumber.into(), IndexType::Ordering),
         ];
 
         let idxmeta_ref = idxmeta.iter().map(|(attr, itype)| (attr, itype)).collect();
@@ -530,6 +577,24 @@ impl Filter<FilterInvalid> {
             },
         })
     }
+
+    /// Convert a SCIM filter, where `resolve_attr` maps the attribute paths of the resource
+    /// type that is being searched to kanidm attributes.
+    #[instrument(name = "filter::from_scim_ro", level = "debug", skip_all)]
+    pub fn from_scim_ro(
+        ev: &Identity,
+        f: &ScimFilter,
+        resolve_attr: &dyn Fn(&ScimAttrPath) -> Result<Attribute, OperationError>,
+        qs: &mut QueryServerReadTransaction,
+    ) -> Result<Self, OperationError> {
+        let depth = FILTER_DEPTH_MAX;
+        let mut elems = ev.limits.filter_max_elements;
+        Ok(Filter {
+            state: FilterInvalid {
+                inner: FilterComp::from_scim_ro(f, resolve_attr, qs, depth, &mut elems)?,
+            },
+        })
+    }
 }
 
 impl FilterComp {
@@ -537,8 +602,11 @@ impl FilterComp {
         match fc {
             FC::Eq(a, v) => FilterComp::Eq(AttrString::from(a), v),
             FC::Sub(a, v) => FilterComp::Sub(AttrString::from(a), v),
+            FC::SubPattern(a, p) => FilterComp::SubPattern(AttrString::from(a), p),
+            FC::EqRule(a, r, v) => FilterComp::EqRule(AttrString::from(a), r, v),
             FC::Pres(a) => FilterComp::Pres(AttrString::from(a)),
             FC::LessThan(a, v) => FilterComp::LessThan(AttrString::from(a), v),
+            FC::GreaterThan(a, v) => FilterComp::GreaterThan(AttrString::from(a), v),
             FC::Or(v) => FilterComp::Or(v.into_iter().map(FilterComp::new).collect()),
             FC::And(v) => FilterComp::And(v.into_iter().map(FilterComp::new).collect()),
             FC::Inclusion(v) => FilterComp::Inclusion(v.into_iter().map(FilterComp::new).collect()),
@@ -572,12 +640,21 @@ impl FilterComp {
             FilterComp::Sub(attr, _) => {
                 r_set.insert(attr.as_str());
             }
+            FilterComp::SubPattern(attr, _) => {
+                r_set.insert(attr.as_str());
+            }
+            FilterComp::EqRule(attr, _, _) => {
+                r_set.insert(attr.as_str());
+            }
             FilterComp::Pres(attr) => {
                 r_set.insert(attr.as_str());
             }
             FilterComp::LessThan(attr, _) => {
                 r_set.insert(attr.as_str());
             }
+            FilterComp::GreaterThan(attr, _) => {
+                r_set.insert(attr.as_str());
+            }
             FilterComp::Or(vs) => vs.iter().for_each(|f| f.get_attr_set(r_set)),
             FilterComp::And(vs) => vs.iter().for_each(|f| f.get_attr_set(r_set)),
             FilterComp::Inclusion(vs) => vs.iter().for_each(|f| f.get_attr_set(r_set)),
@@ -631,6 +708,63 @@ impl FilterComp {
                     None => Err(SchemaError::InvalidAttribute(attr_norm.to_string())),
                 }
             }
+            FilterComp::SubPattern(attr, pattern) => {
+                // Validate/normalise the attr name.
+                let attr_norm = schema.normalise_attr_name(attr);
+                let Some(schema_a) = schema_attributes.get(&attr_norm) else {
+                    return Err(SchemaError::InvalidAttribute(attr_norm.to_string()));
+                };
+                // Patterns only apply to strings, and must have the same case as the
+                // values they are compared to.
+                let (pattern, to_partialvalue): (_, fn(&str) -> PartialValue) = match schema_a
+                    .syntax
+                {
+                    SyntaxType::Utf8String => (pattern.clone(), PartialValue::new_utf8s),
+                    SyntaxType::EmailAddress => {
+                        (pattern.clone(), PartialValue::new_email_address_s)
+                    }
+                    SyntaxType::RestrictedString => {
+                        (pattern.clone(), PartialValue::new_restrictedstring_s)
+                    }
+                    SyntaxType::Utf8StringInsensitive => {
+                        (pattern.to_lowercase(), PartialValue::new_iutf8)
+                    }
+                    SyntaxType::Utf8StringIname => {
+                        (pattern.to_lowercase(), PartialValue::new_iname)
+                    }
+                    _ => return Err(SchemaError::InvalidAttributeSyntax(attr_norm.to_string())),
+                };
+                // A pattern with nothing to match is presence, and a pattern of a single
+                // "any" element is a plain substring.
+                Ok(
+                    match (&pattern.initial, pattern.any.as_slice(), &pattern.final_) {
+                        (None, [], None) => FilterComp::Pres(attr_norm),
+                        (None, [any], None) => FilterComp::Sub(attr_norm, to_partialvalue(any)),
+                        _ => FilterComp::SubPattern(attr_norm, pattern),
+                    },
+                )
+            }
+            FilterComp::EqRule(attr, rule, value) => {
+                // Validate/normalise the attr name.
+                let attr_norm = schema.normalise_attr_name(attr);
+                // Now check it exists
+                match schema_attributes.get(&attr_norm) {
+                    Some(schema_a) => schema_a
+                        .validate_partialvalue(attr_norm.as_str(), value)
+                        .map(|_| match (schema_a.syntax, rule) {
+                            (SyntaxType::Utf8String, _)
+                            | (
+                                SyntaxType::Utf8StringInsensitive | SyntaxType::Utf8StringIname,
+                                MatchingRule::Approximate,
+                            ) => FilterComp::EqRule(attr_norm, *rule, value.clone()),
+                            // Insensitive strings are already stored without case, and other
+                            // syntaxes have no looser form of equality. For these the rule is
+                            // plain equality, which can be indexed.
+                            _ => FilterComp::Eq(attr_norm, value.clone()),
+                        }),
+                    None => Err(SchemaError::InvalidAttribute(attr_norm.to_string())),
+                }
+            }
             FilterComp::Pres(attr) => {
                 let attr_norm = schema.normalise_attr_name(attr);
                 // Now check it exists
@@ -657,6 +791,21 @@ impl FilterComp {
                     None => Err(SchemaError::InvalidAttribute(attr_norm.to_string())),
                 }
             }
+            FilterComp::GreaterThan(attr, value) => {
+                // Validate/normalise the attr name.
+                let attr_norm = schema.normalise_attr_name(attr);
+                // Now check it exists
+                match schema_attributes.get(&attr_norm) {
+                    Some(schema_a) => {
+                        schema_a
+                            .validate_partialvalue(attr_norm.as_str(), value)
+                            // Okay, it worked, transform to a filter component
+                            .map(|_| FilterComp::GreaterThan(attr_norm, value.clone()))
+                        // On error, pass the error back out.
+                    }
+                    None => Err(SchemaError::InvalidAttribute(attr_norm.to_string())),
+                }
+            }
             FilterComp::Or(filters) => {
                 // * If all filters are okay, return Ok(Filter::Or())
                 // * Any filter is invalid, return the error.
@@ -858,32 +1007,211 @@ impl FilterComp {
             }
             LdapFilter::Present(a) => FilterComp::Pres(ldap_attr_filter_map(a)),
             LdapFilter::Substring(
-                _a,
+                a,
                 LdapSubstringFilter {
-                    initial: _,
-                    any: _,
-                    final_: _,
+                    initial,
+                    any,
+                    final_,
                 },
             ) => {
-                // let a = ldap_attr_filter_map(a);
-                admin_error!("Unable to convert ldapsubstringfilter to sub filter");
-                return Err(OperationError::FilterGeneration);
+                let a = ldap_attr_filter_map(a);
+                // The pattern is normalised to the case of the attribute during validation.
+                let p = SubStringPattern::new(initial.clone(), any.clone(), final_.clone());
+                FilterComp::SubPattern(a, p)
             }
-            LdapFilter::GreaterOrEqual(_, _) => {
-                admin_error!("Unsupported filter operation - greater or equal");
-                return Err(OperationError::FilterGeneration);
+            LdapFilter::GreaterOrEqual(a, v) => {
+                *elems = (*elems)
+                    .checked_sub(2)
+                    .ok_or(OperationError::ResourceLimit)?;
+                let a = ldap_attr_filter_map(a);
+                let v = qs.clone_partialvalue(a.as_str(), v)?;
+                FilterComp::Or(vec![
+                    FilterComp::Eq(a.clone(), v.clone()),
+                    FilterComp::GreaterThan(a, v),
+                ])
             }
-            LdapFilter::LessOrEqual(_, _) => {
-                admin_error!("Unsupported filter operation - less or equal");
-                return Err(OperationError::FilterGeneration);
+            LdapFilter::LessOrEqual(a, v) => {
+                *elems = (*elems)
+                    .checked_sub(2)
+                    .ok_or(OperationError::ResourceLimit)?;
+                let a = ldap_attr_filter_map(a);
+                let v = qs.clone_partialvalue(a.as_str(), v)?;
+                FilterComp::Or(vec![
+                    FilterComp::Eq(a.clone(), v.clone()),
+                    FilterComp::LessThan(a, v),
+                ])
             }
-            LdapFilter::Approx(_, _) => {
-                admin_error!("Unsupported filter operation - approximate");
-                return Err(OperationError::FilterGeneration);
+            LdapFilter::Approx(a, v) => {
+                let a = ldap_attr_filter_map(a);
+                let v = qs.clone_partialvalue(a.as_str(), v)?;
+                FilterComp::EqRule(a, MatchingRule::Approximate, v)
             }
-            LdapFilter::Extensible(_) => {
-                admin_error!("Unsupported filter operation - extensible");
-                return Err(OperationError::FilterGeneration);
+            LdapFilter::Extensible(mra) => Self::from_ldap_extensible(mra, qs)?,
+        })
+    }
+
+    fn from_ldap_extensible(
+        mra: &LdapMatchingRuleAssertion,
+        qs: &mut QueryServerReadTransaction,
+    ) -> Result<Self, OperationError> {
+        let Some(a) = mra.type_.as_deref().map(ldap_attr_filter_map) else {
+            admin_error!("Unsupported filter operation - extensible match without an attribute");
+            return Err(OperationError::FilterGeneration);
+        };
+
+        if mra.dn_attributes {
+            admin_error!("Unsupported filter operation - extensible match of dn attributes");
+            return Err(OperationError::FilterGeneration);
+        }
+
+        let v = qs.clone_partialvalue(a.as_str(), &mra.match_value)?;
+
+        let rule = mra.matching_rule.as_deref().map(str::to_lowercase);
+        match rule.as_deref() {
+            // Without a rule, the equality rule of the attribute applies.
+            None
+            | Some(LDAP_MATCH_CASE_EXACT)
+            | Some(LDAP_MATCH_CASE_EXACT_OID)
+            | Some(LDAP_MATCH_CASE_EXACT_IA5)
+            | Some(LDAP_MATCH_CASE_EXACT_IA5_OID) => Ok(FilterComp::Eq(a, v)),
+            Some(LDAP_MATCH_CASE_IGNORE)
+            | Some(LDAP_MATCH_CASE_IGNORE_OID)
+            | Some(LDAP_MATCH_CASE_IGNORE_IA5)
+            | Some(LDAP_MATCH_CASE_IGNORE_IA5_OID) => {
+                Ok(FilterComp::EqRule(a, MatchingRule::CaseIgnore, v))
+            }
+            // Memberof is already the transitive closure of membership, so the chain
+            // rule is equality.
+            Some(LDAP_MATCH_IN_CHAIN_OID) if a.as_str() == Attribute::MemberOf.as_ref() => {
+                Ok(FilterComp::Eq(a, v))
+            }
+            Some(LDAP_MATCH_IN_CHAIN_OID) => {
+                admin_error!(attr = %a, "Unsupported filter operation - transitive match of attribute");
+                Err(OperationError::FilterGeneration)
+            }
+            Some(rule) => {
+                admin_error!(%rule, "Unsupported filter operation - unknown extensible match rule");
+                Err(OperationError::FilterGeneration)
+            }
+        }
+    }
+
+    fn from_scim_ro(
+        f: &ScimFilter,
+        resolve_attr: &dyn Fn(&ScimAttrPath) -> Result<Attribute, OperationError>,
+        qs: &mut QueryServerReadTransaction,
+        depth: usize,
+        elems: &mut usize,
+    ) -> Result<Self, OperationError> {
+        let ndepth = depth.checked_sub(1).ok_or(OperationError::ResourceLimit)?;
+        Ok(match f {
+            ScimFilter::And(l) => {
+                *elems = (*elems)
+                    .checked_sub(l.len())
+                    .ok_or(OperationError::ResourceLimit)?;
+                FilterComp::And(
+                    l.iter()
+                        .map(|f| Self::from_scim_ro(f, resolve_attr, qs, ndepth, elems))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            ScimFilter::Or(l) => {
+                *elems = (*elems)
+                    .checked_sub(l.len())
+                    .ok_or(OperationError::ResourceLimit)?;
+                FilterComp::Or(
+                    l.iter()
+                        .map(|f| Self::from_scim_ro(f, resolve_attr, qs, ndepth, elems))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            ScimFilter::Not(l) => {
+                *elems = (*elems)
+                    .checked_sub(1)
+                    .ok_or(OperationError::ResourceLimit)?;
+                FilterComp::AndNot(Box::new(Self::from_scim_ro(
+                    l,
+                    resolve_attr,
+                    qs,
+                    ndepth,
+                    elems,
+                )?))
+            }
+            ScimFilter::ValuePath(path, l) => {
+                // The inner filter addresses the sub attributes of the values, so
+                // emails[value eq "..."] is the same as emails.value eq "...".
+                let resolve_sub_attr = |sub: &ScimAttrPath| {
+                    if sub.urn.is_some() || sub.sub_attr.is_some() || path.sub_attr.is_some() {
+                        admin_error!(%path, %sub, "Unsupported filter operation - nested value path");
+                        return Err(OperationError::FilterGeneration);
+                    }
+                    resolve_attr(&ScimAttrPath {
+                        urn: path.urn.clone(),
+                        attr: path.attr.clone(),
+                        sub_attr: Some(sub.attr.clone()),
+                    })
+                };
+                Self::from_scim_ro(l, &resolve_sub_attr, qs, ndepth, elems)?
+            }
+            ScimFilter::Present(path) => {
+                FilterComp::Pres(AttrString::from(resolve_attr(path)?.as_ref()))
+            }
+            ScimFilter::Compare(path, op, v) => {
+                let a = AttrString::from(resolve_attr(path)?.as_ref());
+                match op {
+                    ScimCompareOp::Equal => {
+                        let v = qs.clone_partialvalue(a.as_str(), v)?;
+                        FilterComp::Eq(a, v)
+                    }
+                    ScimCompareOp::NotEqual => {
+                        *elems = (*elems)
+                            .checked_sub(1)
+                            .ok_or(OperationError::ResourceLimit)?;
+                        let v = qs.clone_partialvalue(a.as_str(), v)?;
+                        FilterComp::AndNot(Box::new(FilterComp::Eq(a, v)))
+                    }
+                    ScimCompareOp::Contains => {
+                        let v = qs.clone_partialvalue(a.as_str(), v)?;
+                        FilterComp::Sub(a, v)
+                    }
+                    // The pattern is normalised to the case of the attribute during validation.
+                    ScimCompareOp::StartsWith => FilterComp::SubPattern(
+                        a,
+                        SubStringPattern::new(Some(v.clone()), Vec::new(), None),
+                    ),
+                    ScimCompareOp::EndsWith => FilterComp::SubPattern(
+                        a,
+                        SubStringPattern::new(None, Vec::new(), Some(v.clone())),
+                    ),
+                    ScimCompareOp::GreaterThan => {
+                        let v = qs.clone_partialvalue(a.as_str(), v)?;
+                        FilterComp::GreaterThan(a, v)
+                    }
+                    ScimCompareOp::LessThan => {
+                        let v = qs.clone_partialvalue(a.as_str(), v)?;
+                        FilterComp::LessThan(a, v)
+                    }
+                    ScimCompareOp::GreaterOrEqual => {
+                        *elems = (*elems)
+                            .checked_sub(2)
+                            .ok_or(OperationError::ResourceLimit)?;
+                        let v = qs.clone_partialvalue(a.as_str(), v)?;
+                        FilterComp::Or(vec![
+                            FilterComp::Eq(a.clone(), v.clone()),
+                            FilterComp::GreaterThan(a, v),
+                        ])
+                    }
+                    ScimCompareOp::LessOrEqual => {
+                        *elems = (*elems)
+                            .checked_sub(2)
+                            .ok_or(OperationError::ResourceLimit)?;
+                        let v = qs.clone_partialvalue(a.as_str(), v)?;
+                        FilterComp::Or(vec![
+                            FilterComp::Eq(a.clone(), v.clone()),
+                            FilterComp::LessThan(a, v),
+                        ])
+                    }
+                }
             }
         })
     }
@@ -928,10 +1256,19 @@ impl PartialEq for FilterResolved {
             (FilterResolved::Sub(a1, v1, _), FilterResolved::Sub(a2, v2, _)) => {
                 a1 == a2 && v1 == v2
             }
+            (FilterResolved::SubPattern(a1, p1, _), FilterResolved::SubPattern(a2, p2, _)) => {
+                a1 == a2 && p1 == p2
+            }
+            (FilterResolved::EqRule(a1, r1, v1, _), FilterResolved::EqRule(a2, r2, v2, _)) => {
+                a1 == a2 && r1 == r2 && v1 == v2
+            }
             (FilterResolved::Pres(a1, _), FilterResolved::Pres(a2, _)) => a1 == a2,
             (FilterResolved::LessThan(a1, v1, _), FilterResolved::LessThan(a2, v2, _)) => {
                 a1 == a2 && v1 == v2
             }
+            (FilterResolved::GreaterThan(a1, v1, _), FilterResolved::GreaterThan(a2, v2, _)) => {
+                a1 == a2 && v1 == v2
+            }
             (FilterResolved::And(vs1, _), FilterResolved::And(vs2, _)) => vs1 == vs2,
             (FilterResolved::Or(vs1, _), FilterResolved::Or(vs2, _)) => vs1 == vs2,
             (FilterResolved::Inclusion(vs1, _), FilterResolved::Inclusion(vs2, _)) => vs1 == vs2,
@@ -970,11 +1307,19 @@ impl Ord for FilterResolved {
             match (self, rhs) {
                 (FilterResolved::Eq(a1, v1, _), FilterResolved::Eq(a2, v2, _))
                 | (FilterResolved::Sub(a1, v1, _), FilterResolved::Sub(a2, v2, _))
-                | (FilterResolved::LessThan(a1, v1, _), FilterResolved::LessThan(a2, v2, _)) => {
-                    match a1.cmp(a2) {
-                        Ordering::Equal => v1.cmp(v2),
-                        o => o,
-                    }
+                | (FilterResolved::LessThan(a1, v1, _), FilterResolved::LessThan(a2, v2, _))
+                | (
+                    FilterResolved::GreaterThan(a1, v1, _),
+                    FilterResolved::GreaterThan(a2, v2, _),
+                ) => match a1.cmp(a2) {
+                    Ordering::Equal => v1.cmp(v2),
+                    o => o,
+                },
+                (FilterResolved::SubPattern(a1, p1, _), FilterResolved::SubPattern(a2, p2, _)) => {
+                    a1.cmp(a2).then_with(|| p1.cmp(p2))
+                }
+                (FilterResolved::EqRule(a1, r1, v1, _), FilterResolved::EqRule(a2, r2, v2, _)) => {
+                    a1.cmp(a2).then_with(|| r1.cmp(r2)).then_with(|| v1.cmp(v2))
                 }
                 (FilterResolved::Pres(a1, _), FilterResolved::Pres(a2, _)) => a1.cmp(a2),
                 // Now sort these into the generally "best" order.
@@ -984,8 +1329,15 @@ impl Ord for FilterResolved {
                 (_, FilterResolved::Pres(_, _)) => Ordering::Greater,
                 (FilterResolved::LessThan(_, _, _), _) => Ordering::Less,
                 (_, FilterResolved::LessThan(_, _, _)) => Ordering::Greater,
+                (FilterResolved::GreaterThan(_, _, _), _) => Ordering::Less,
+                (_, FilterResolved::GreaterThan(_, _, _)) => Ordering::Greater,
                 (FilterResolved::Sub(_, _, _), _) => Ordering::Less,
                 (_, FilterResolved::Sub(_, _, _)) => Ordering::Greater,
+                // These must compare every value of the attribute, so they are the slowest.
+                (FilterResolved::EqRule(_, _, _, _), _) => Ordering::Less,
+                (_, FilterResolved::EqRule(_, _, _, _)) => Ordering::Greater,
+                (FilterResolved::SubPattern(_, _, _), _) => Ordering::Less,
+                (_, FilterResolved::SubPattern(_, _, _)) => Ordering::Greater,
                 // They can't be re-arranged, they don't move!
                 (_, _) => Ordering::Equal,
             }
@@ -1013,14 +1365,19 @@ impl FilterResolved {
                 // let idx = NonZeroU8::new(idx as u8);
                 FilterResolved::Sub(a, v, None)
             }
+            FilterComp::SubPattern(a, p) => FilterResolved::SubPattern(a, p, None),
+            FilterComp::EqRule(a, r, v) => FilterResolved::EqRule(a, r, v, None),
             FilterComp::Pres(a) => {
                 let idx = idxmeta.contains(&(&a, &IndexType::Presence));
                 FilterResolved::Pres(a, NonZeroU8::new(idx as u8))
             }
             FilterComp::LessThan(a, v) => {
-                // let idx = idxmeta.contains(&(&a, &IndexType::ORDERING));
-                // TODO: For now, don't emit ordering indexes.
-                FilterResolved::LessThan(a, v, None)
+                let idx = idxmeta.contains(&(&a, &IndexType::Ordering));
+                FilterResolved::LessThan(a, v, NonZeroU8::new(idx as u8))
+            }
+            FilterComp::GreaterThan(a, v) => {
+                let idx = idxmeta.contains(&(&a, &IndexType::Ordering));
+                FilterResolved::GreaterThan(a, v, NonZeroU8::new(idx as u8))
             }
             FilterComp::Or(vs) => FilterResolved::Or(
                 vs.into_iter()
@@ -1084,6 +1441,10 @@ impl FilterResolved {
                     .and_then(NonZeroU8::new);
                 Some(FilterResolved::Sub(a, v, idx))
             }
+            // There are no indexes that can answer these, as they compare values in a
+            // different form to how they are stored.
+            FilterComp::SubPattern(a, p) => Some(FilterResolved::SubPattern(a, p, None)),
+            FilterComp::EqRule(a, r, v) => Some(FilterResolved::EqRule(a, r, v, None)),
             FilterComp::Pres(a) => {
                 let idxkref = IdxKeyRef::new(&a, &IndexType::Presence);
                 let idx = idxmeta
@@ -1093,8 +1454,20 @@ impl FilterResolved {
                 Some(FilterResolved::Pres(a, idx))
             }
             FilterComp::LessThan(a, v) => {
-                // let idx = idxmeta.contains(&(&a, &IndexType::SubString));
-                Some(FilterResolved::LessThan(a, v, None))
+                let idxkref = IdxKeyRef::new(&a, &IndexType::Ordering);
+                let idx = idxmeta
+                    .get(&idxkref as &dyn IdxKeyToRef)
+                    .copied()
+                    .and_then(NonZeroU8::new);
+                Some(FilterResolved::LessThan(a, v, idx))
+            }
+            FilterComp::GreaterThan(a, v) => {
+                let idxkref = IdxKeyRef::new(&a, &IndexType::Ordering);
+                let idx = idxmeta
+                    .get(&idxkref as &dyn IdxKeyToRef)
+                    .copied()
+                    .and_then(NonZeroU8::new);
+                Some(FilterResolved::GreaterThan(a, v, idx))
             }
             // We set the compound filters slope factor to "None" here, because when we do
             // optimise we'll actually fill in the correct slope factors after we sort those
@@ -1153,8 +1526,11 @@ impl FilterResolved {
                 )
             }),
             FilterComp::Sub(a, v) => Some(FilterResolved::Sub(a, v, None)),
+            FilterComp::SubPattern(a, p) => Some(FilterResolved::SubPattern(a, p, None)),
+            FilterComp::EqRule(a, r, v) => Some(FilterResolved::EqRule(a, r, v, None)),
             FilterComp::Pres(a) => Some(FilterResolved::Pres(a, None)),
             FilterComp::LessThan(a, v) => Some(FilterResolved::LessThan(a, v, None)),
+            FilterComp::GreaterThan(a, v) => Some(FilterResolved::GreaterThan(a, v, None)),
             FilterComp::Or(vs) => {
                 let fi: Option<Vec<_>> = vs
                     .into_iter()
@@ -1316,8 +1692,11 @@ impl FilterResolved {
         match self {
             FilterResolved::Eq(_, _, sf)
             | FilterResolved::Sub(_, _, sf)
+            | FilterResolved::SubPattern(_, _, sf)
+            | FilterResolved::EqRule(_, _, _, sf)
             | FilterResolved::Pres(_, sf)
             | FilterResolved::LessThan(_, _, sf)
+            | FilterResolved::GreaterThan(_, _, sf)
             | FilterResolved::Or(_, sf)
             | FilterResolved::And(_, sf)
             | FilterResolved::Inclusion(_, sf)
@@ -1576,6 +1955,163 @@ mod tests {
         assert!(e.entry_match_no_index(&f_t1c));
     }
 
+    #[test]
+    fn test_greaterthan_entry_filter() {
+        let e = entry_init!(
+            (Attribute::UserId, Value::new_iutf8("william")),
+            (
+                Attribute::Uuid,
+                Value::Uuid(uuid::uuid!("db237e8a-0079-4b8c-8a56-593b22aa44d1"))
+            ),
+            (Attribute::GidNumber, Value::Uint32(1000))
+        )
+        .into_sealed_new();
+
+        let f_t1a = filter_resolved!(f_gt(Attribute::GidNumber, PartialValue::new_uint32(999)));
+        assert!(e.entry_match_no_index(&f_t1a));
+
+        let f_t1b = filter_resolved!(f_gt(Attribute::GidNumber, PartialValue::new_uint32(1000)));
+        assert!(!e.entry_match_no_index(&f_t1b));
+
+        let f_t1c = filter_resolved!(f_gt(Attribute::GidNumber, PartialValue::new_uint32(1500)));
+        assert!(!e.entry_match_no_index(&f_t1c));
+    }
+
+    #[test]
+    fn test_sub_pattern_entry_filter() {
+        let e = entry_init!(
+            (Attribute::UserId, Value::new_iutf8("william")),
+            (Attribute::DisplayName, Value::new_utf8s("William Brown")),
+            (
+                Attribute::Email,
+                Value::new_email_address_s("william@example.com").unwrap()
+            )
+        )
+        .into_sealed_new();
+
+        let p = |initial: Option<&str>, any: &[&str], final_: Option<&str>| {
+            SubStringPattern::new(
+                initial.map(str::to_string),
+                any.iter().map(|s| s.to_string()).collect(),
+                final_.map(str::to_string),
+            )
+        };
+
+        let f_t1a = filter_resolved!(f_sub_pattern(
+            Attribute::DisplayName,
+            p(Some("Will"), &["am"], Some("Brown"))
+        ));
+        assert!(e.entry_match_no_index(&f_t1a));
+
+        let f_t1b = filter_resolved!(f_sub_pattern(
+            Attribute::DisplayName,
+            p(Some("Brown"), &[], None)
+        ));
+        assert!(!e.entry_match_no_index(&f_t1b));
+
+        let f_t2a = filter_resolved!(f_eq_rule(
+            Attribute::DisplayName,
+            MatchingRule::CaseIgnore,
+            PartialValue::new_utf8s("WILLIAM BROWN")
+        ));
+        assert!(e.entry_match_no_index(&f_t2a));
+
+        let f_t2b = filter_resolved!(f_eq_rule(
+            Attribute::DisplayName,
+            MatchingRule::CaseIgnore,
+            PartialValue::new_utf8s("william.brown")
+        ));
+        assert!(!e.entry_match_no_index(&f_t2b));
+
+        let f_t2c = filter_resolved!(f_eq_rule(
+            Attribute::DisplayName,
+            MatchingRule::Approximate,
+            PartialValue::new_utf8s("william.brown")
+        ));
+        assert!(e.entry_match_no_index(&f_t2c));
+
+        let f_t3a = filter_resolved!(f_sub_pattern(
+            Attribute::Email,
+            p(Some("will"), &[], Some("@example.com"))
+        ));
+        assert!(e.entry_match_no_index(&f_t3a));
+
+        // Strings are ordered.
+        let f_t4a = filter_resolved!(f_gt(
+            Attribute::DisplayName,
+            PartialValue::new_utf8s("William")
+        ));
+        assert!(e.entry_match_no_index(&f_t4a));
+
+        let f_t4b = filter_resolved!(f_lt(
+            Attribute::DisplayName,
+            PartialValue::new_utf8s("William")
+        ));
+        assert!(!e.entry_match_no_index(&f_t4b));
+    }
+
+    #[qs_test]
+    async fn test_filter_validate_rewrite(server: &QueryServer) {
+        let r_txn = server.read().await;
+        let schema = r_txn.get_schema();
+
+        let validate = |fc: FC| Filter::new(fc).validate(schema);
+        let pattern = |initial: Option<&str>, any: &[&str]| {
+            SubStringPattern::new(
+                initial.map(str::to_string),
+                any.iter().map(|s| s.to_string()).collect(),
+                None,
+            )
+        };
+
+        // Patterns are lowered to the case of the attribute.
+        assert_eq!(
+            validate(f_sub_pattern(Attribute::Name, pattern(Some("TeSt"), &[]))),
+            validate(f_sub_pattern(Attribute::Name, pattern(Some("test"), &[])))
+        );
+        // An empty pattern is presence, and a lone any is a plain substring.
+        assert_eq!(
+            validate(f_sub_pattern(Attribute::Name, pattern(None, &[]))),
+            validate(f_pres(Attribute::Name))
+        );
+        assert_eq!(
+            validate(f_sub_pattern(Attribute::Name, pattern(None, &["Test"]))),
+            validate(f_sub(Attribute::Name, PartialValue::new_iname("test")))
+        );
+        // Patterns only apply to strings, including those with a stricter syntax.
+        assert!(validate(f_sub_pattern(Attribute::GidNumber, pattern(Some("1"), &[]))).is_err());
+        assert!(validate(f_sub_pattern(Attribute::Email, pattern(Some("jo"), &[]))).is_ok());
+
+        // Rules that add nothing over the stored form of a value are equality.
+        assert_eq!(
+            validate(f_eq_rule(
+                Attribute::Name,
+                MatchingRule::CaseIgnore,
+                PartialValue::new_iname("test")
+            )),
+            validate(f_eq(Attribute::Name, PartialValue::new_iname("test")))
+        );
+        assert_eq!(
+            validate(f_eq_rule(
+                Attribute::GidNumber,
+                MatchingRule::Approximate,
+                PartialValue::new_uint32(1000)
+            )),
+            validate(f_eq(Attribute::GidNumber, PartialValue::new_uint32(1000)))
+        );
+        assert_ne!(
+            validate(f_eq_rule(
+                Attribute::DisplayName,
+                MatchingRule::CaseIgnore,
+                PartialValue::new_utf8s("Test")
+            )),
+            validate(f_eq(
+                Attribute::DisplayName,
+                PartialValue::new_utf8s("Test")
+            ))
+        );
+    }
+
     #[test]
     fn test_or_entry_filter() {
         let e = entry_init!(
@@ -0,0 +1,700 @@
+//! Just-in-time access requests.
+//!
+//! An account may request to be granted membership of a group that has one or more approver
+//! groups defined in access_request_approver. The members of these approver groups can then
+//! approve or deny the request. An approved request grants the requester an expiring membership
+//! of the group, which is removed once access_request_duration has passed.
+
+use crate::idm::audit::AuditEvent;
+use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
+use crate::prelude::*;
+use kanidm_proto::v1::{AccessRequest as ProtoAccessRequest, AccessRequestState};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+fn access_request_state(
+    entry: &EntrySealedCommitted,
+) -> Result<AccessRequestState, OperationError> {
+    entry
+        .get_ava_single_proto_string(Attribute::AccessRequestState)
+        .and_then(|s| AccessRequestState::from_str(&s).ok())
+        .ok_or_else(|| {
+            admin_error!(uuid = ?entry.get_uuid(), "access request has an invalid state");
+            OperationError::InvalidEntryState
+        })
+}
+
+/// Determine if this identity is a member of one of the approver groups of the group, and so
+/// may approve or deny requests for membership of it. Requesters may never decide their
+/// own requests.
+fn access_request_can_decide(
+    ident: &Identity,
+    group: &EntrySealedCommitted,
+    requester: Uuid,
+) -> bool {
+    if ident.get_uuid() == Some(requester) {
+        return false;
+    }
+
+    group
+        .get_ava_as_refuuid(Attribute::AccessRequestApprover)
+        .map(|mut approvers| approvers.any(|approver| ident.is_memberof(approver)))
+        .unwrap_or(false)
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    /// Request that the identity is granted membership of the group. If a pending request
+    /// already exists for this group, its uuid is returned instead.
+    pub fn access_request_create(
+        &mut self,
+        ident: &Identity,
+        group: Uuid,
+        justification: &str,
+    ) -> Result<Uuid, OperationError> {
+        let requester = match ident.get_user_entry() {
+            Some(entry) if entry.get_uuid() != UUID_ANONYMOUS => entry.get_uuid(),
+            _ => {
+                security_access!("only accounts may request access to groups");
+                return Err(OperationError::AccessDenied);
+            }
+        };
+
+        if ident.access_scope() != AccessScope::ReadWrite {
+            security_access!("identity access scope is not permitted to request access");
+            security_access!("denied ❌");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let justification = justification.trim();
+        if justification.is_empty() {
+            request_error!("access requests must provide a justification");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let group_entry = self.qs_write.internal_search_uuid(group)?;
+
+        if !group_entry.attribute_equality(Attribute::Class, &EntryClass::Group.into())
+            || !group_entry.attribute_pres(Attribute::AccessRequestApprover)
+        {
+            request_error!(?group, "group does not accept access requests");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        // A membership granted by an earlier request counts until it expires, otherwise
+        // approving this request would silently extend it.
+        let now = OffsetDateTime::UNIX_EPOCH + self.qs_write.get_curtime();
+        let is_expiring_member = group_entry
+            .get_ava_set(Attribute::MemberExpiring)
+            .and_then(|vs| vs.as_refer_expiring_map())
+            .and_then(|m| m.get(&requester))
+            .map_or(false, |expiry| *expiry > now);
+
+        if is_expiring_member
+            || group_entry.attribute_equality(Attribute::Member, &PartialValue::Refer(requester))
+        {
+            request_error!(?group, "requester is already a member of this group");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let pending = self.qs_write.internal_search(filter!(f_and!([
+            f_eq(Attribute::Class, EntryClass::AccessRequest.into()),
+            f_eq(Attribute::AccessRequestGroup, PartialValue::Refer(group)),
+            f_eq(
+                Attribute::AccessRequestRequester,
+                PartialValue::Refer(requester)
+            ),
+            f_eq(
+                Attribute::AccessRequestState,
+                PartialValue::new_iutf8(AccessRequestState::Pending.as_str())
+            )
+        ])))?;
+
+        if let Some(existing) = pending.first() {
+            return Ok(existing.get_uuid());
+        }
+
+        let request = Uuid::new_v4();
+
+        let entry = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::AccessRequest.to_value()),
+            (Attribute::Uuid, Value::Uuid(request)),
+            (Attribute::AccessRequestGroup, Value::Refer(group)),
+            (Attribute::AccessRequestRequester, Value::Refer(requester)),
+            (
+                Attribute::AccessRequestJustification,
+                Value::new_utf8s(justification)
+            ),
+            (
+                Attribute::AccessRequestState,
+                Value::new_iutf8(AccessRequestState::Pending.as_str())
+            )
+        );
+
+        self.qs_write.internal_create(vec![entry])?;
+
+        self.qs_write
+            .audit_event_on_commit(AuditEvent::AccessRequestCreated {
+                request,
+                requester,
+                group,
+                time: now,
+            });
+
+        Ok(request)
+    }
+
+    /// Approve or deny a pending access request. On approval the requester is granted
+    /// membership of the group for the duration that the group defines.
+    pub fn access_request_decide(
+        &mut self,
+        ident: &Identity,
+        request: Uuid,
+        approve: bool,
+    ) -> Result<(), OperationError> {
+        let approver = match ident.get_uuid() {
+            Some(approver) if ident.get_user_entry().is_some() => approver,
+            _ => {
+                security_access!("only accounts may decide access requests");
+                return Err(OperationError::AccessDenied);
+            }
+        };
+
+        if ident.access_scope() != AccessScope::ReadWrite {
+            security_access!("identity access scope is not permitted to decide access requests");
+            security_access!("denied ❌");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let request_entry = self.qs_write.internal_search_uuid(request)?;
+
+        if !request_entry.attribute_equality(Attribute::Class, &EntryClass::AccessRequest.into()) {
+            return Err(OperationError::NoMatchingEntries);
+        }
+
+        if access_request_state(&request_entry)? != AccessRequestState::Pending {
+            request_error!(?request, "access request has already been decided");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let (Some(group), Some(requester)) = (
+            request_entry.get_ava_single_refer(Attribute::AccessRequestGroup),
+            request_entry.get_ava_single_refer(Attribute::AccessRequestRequester),
+        ) else {
+            // The group or requester were deleted, so this request can never be approved.
+            request_error!(
+                ?request,
+                "access request group or requester no longer exists"
+            );
+            return Err(OperationError::InvalidRequestState);
+        };
+
+        let group_entry = self.qs_write.internal_search_uuid(group)?;
+
+        if !access_request_can_decide(ident, &group_entry, requester) {
+            security_access!(
+                ?request,
+                "identity is not an approver of this access request"
+            );
+            security_access!("denied ❌");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let now = OffsetDateTime::UNIX_EPOCH + self.qs_write.get_curtime();
+
+        let state = if approve {
+            let duration = group_entry
+                .get_ava_single_uint32(Attribute::AccessRequestDuration)
+                .unwrap_or(DEFAULT_ACCESS_REQUEST_DURATION);
+            let expiry = now + Duration::from_secs(duration as u64);
+
+            let modlist = ModifyList::new_append(
+                Attribute::MemberExpiring,
+                Value::new_refer_expiring(requester, expiry),
+            );
+            self.qs_write.internal_modify_uuid(group, &modlist)?;
+
+            self.qs_write
+                .audit_event_on_commit(AuditEvent::AccessRequestApproved {
+                    request,
+                    requester,
+                    group,
+                    approver,
+                    expiry,
+                    time: now,
+                });
+
+            AccessRequestState::Approved
+        } else {
+            self.qs_write
+                .audit_event_on_commit(AuditEvent::AccessRequestDenied {
+                    request,
+                    requester,
+                    group,
+                    approver,
+                    time: now,
+                });
+
+            AccessRequestState::Denied
+        };
+
+        let modlist = ModifyList::new_list(vec![
+            Modify::Purged(Attribute::AccessRequestState.into()),
+            Modify::Present(
+                Attribute::AccessRequestState.into(),
+                Value::new_iutf8(state.as_str()),
+            ),
+            Modify::Present(
+                Attribute::AccessRequestDecidedBy.into(),
+                Value::Refer(approver),
+            ),
+        ]);
+
+        self.qs_write.internal_modify_uuid(request, &modlist)
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// List the access requests made by this identity, and those that it is able to decide.
+    pub fn access_request_list(
+        &mut self,
+        ident: &Identity,
+    ) -> Result<Vec<ProtoAccessRequest>, OperationError> {
+        let Some(ident_uuid) = ident.get_uuid() else {
+            return Ok(Vec::with_capacity(0));
+        };
+
+        // The groups whose requests this identity may decide, via its approver groups.
+        let approver_of: BTreeSet<Uuid> = match ident.get_memberof() {
+            Some(memberof) if !memberof.is_empty() => {
+                let f_approver = memberof
+                    .iter()
+                    .map(|g| f_eq(Attribute::AccessRequestApprover, PartialValue::Refer(*g)))
+                    .collect();
+                self.qs_read
+                    .internal_search(filter!(f_and!([
+                        f_eq(Attribute::Class, EntryClass::Group.into()),
+                        f_or(f_approver)
+                    ])))?
+                    .iter()
+                    .map(|group| group.get_uuid())
+                    .collect()
+            }
+            _ => BTreeSet::new(),
+        };
+
+        let f_visible = std::iter::once(f_eq(
+            Attribute::AccessRequestRequester,
+            PartialValue::Refer(ident_uuid),
+        ))
+        .chain(
+            approver_of
+                .iter()
+                .map(|g| f_eq(Attribute::AccessRequestGroup, PartialValue::Refer(*g))),
+        )
+        .collect();
+
+        let requests = self.qs_read.internal_search(filter!(f_and!([
+            f_eq(Attribute::Class, EntryClass::AccessRequest.into()),
+            f_or(f_visible)
+        ])))?;
+
+        let mut views = Vec::with_capacity(requests.len());
+
+        for request_entry in requests {
+            let (Some(group), Some(requester)) = (
+                request_entry.get_ava_single_refer(Attribute::AccessRequestGroup),
+                request_entry.get_ava_single_refer(Attribute::AccessRequestRequester),
+            ) else {
+                continue;
+            };
+
+            // Requesters may never decide their own requests.
+            let is_approver = requester != ident_uuid && approver_of.contains(&group);
+
+            let state = access_request_state(&request_entry)?;
+
+            let decided_by =
+                match request_entry.get_ava_single_refer(Attribute::AccessRequestDecidedBy) {
+                    Some(u) => Some(self.access_request_spn(u)?),
+                    None => None,
+                };
+
+            views.push(ProtoAccessRequest {
+                uuid: request_entry.get_uuid(),
+                group: self.access_request_spn(group)?,
+                requester: self.access_request_spn(requester)?,
+                justification: request_entry
+                    .get_ava_single_proto_string(Attribute::AccessRequestJustification)
+                    .unwrap_or_default(),
+                state,
+                decided_by,
+                can_decide: is_approver && state == AccessRequestState::Pending,
+            });
+        }
+
+        Ok(views)
+    }
+
+    fn access_request_spn(&mut self, uuid: Uuid) -> Result<String, OperationError> {
+        self.qs_read.uuid_to_spn(uuid).map(|nv| match nv {
+            Some(v) => v.to_proto_string_clone(),
+            None => uuid.as_hyphenated().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::idm::audit::AuditEvent;
+    use crate::prelude::*;
+    use crate::testkit::{setup_test_ident_readwrite, setup_test_person};
+    use kanidm_proto::v1::AccessRequestState;
+
+    const TEST_CURRENT_TIME: u64 = 6000;
+
+    const UUID_REQUESTER: Uuid = uuid!("cd2b5f56-5c4c-4a07-9fe8-1c2c7e04a8b0");
+    const UUID_APPROVER: Uuid = uuid!("6f4a1c66-94d3-4d10-a1e5-83de4aab0a51");
+    const UUID_APPROVER_GROUP: Uuid = uuid!("5e48cdf4-0f8c-4a3a-a28a-2f0c60bff2fd");
+    const UUID_TARGET_GROUP: Uuid = uuid!("1b8c8d07-61a1-4bd8-bd1c-6a4f4d6b4a6e");
+    const UUID_OTHER: Uuid = uuid!("8e0b7d2a-3f41-4c6e-9a1d-5b2c7e8f9a03");
+
+    async fn setup(idms: &IdmServer, ct: Duration) {
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let approver_group = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("test_approvers")),
+            (Attribute::Uuid, Value::Uuid(UUID_APPROVER_GROUP)),
+            (Attribute::Member, Value::Refer(UUID_APPROVER))
+        );
+
+        let target_group = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("test_target")),
+            (Attribute::Uuid, Value::Uuid(UUID_TARGET_GROUP)),
+            (
+                Attribute::AccessRequestApprover,
+                Value::Refer(UUID_APPROVER_GROUP)
+            ),
+            (Attribute::AccessRequestDuration, Value::Uint32(600))
+        );
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![
+                setup_test_person("test_requester", UUID_REQUESTER),
+                setup_test_person("test_approver", UUID_APPROVER),
+                approver_group,
+                target_group,
+            ])
+            .is_ok());
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_request_approve(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        setup(idms, ct).await;
+
+        let requester = setup_test_ident_readwrite(idms, UUID_REQUESTER).await;
+        let approver = setup_test_ident_readwrite(idms, UUID_APPROVER).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // Read only sessions may not request access.
+        assert_eq!(
+            idms_prox_write.access_request_create(
+                &requester.project_with_scope(AccessScope::ReadOnly),
+                UUID_TARGET_GROUP,
+                "incident 1234"
+            ),
+            Err(OperationError::AccessDenied)
+        );
+
+        // Justifications are required.
+        assert_eq!(
+            idms_prox_write.access_request_create(&requester, UUID_TARGET_GROUP, " "),
+            Err(OperationError::InvalidRequestState)
+        );
+
+        // Groups without approvers don't accept requests.
+        assert_eq!(
+            idms_prox_write.access_request_create(&requester, UUID_APPROVER_GROUP, "please"),
+            Err(OperationError::InvalidRequestState)
+        );
+
+        let request = idms_prox_write
+            .access_request_create(&requester, UUID_TARGET_GROUP, "incident 1234")
+            .expect("Failed to create access request");
+
+        // A second request returns the pending one.
+        assert_eq!(
+            idms_prox_write.access_request_create(&requester, UUID_TARGET_GROUP, "again"),
+            Ok(request)
+        );
+
+        // The requester can't approve their own request.
+        assert_eq!(
+            idms_prox_write.access_request_decide(&requester, request, true),
+            Err(OperationError::AccessDenied)
+        );
+
+        assert!(idms_prox_write
+            .access_request_decide(&approver, request, true)
+            .is_ok());
+
+        // It can't be decided twice.
+        assert_eq!(
+            idms_prox_write.access_request_decide(&approver, request, false),
+            Err(OperationError::InvalidRequestState)
+        );
+
+        let group = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TARGET_GROUP)
+            .expect("Failed to find group");
+        let expiry = group
+            .get_ava_set(Attribute::MemberExpiring)
+            .and_then(|vs| vs.as_refer_expiring_map())
+            .and_then(|m| m.get(&UUID_REQUESTER).copied());
+        assert_eq!(
+            expiry,
+            Some(time::OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(600))
+        );
+
+        let requester_entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_REQUESTER)
+            .expect("Failed to find account");
+        assert!(requester_entry
+            .attribute_equality(Attribute::MemberOf, &PartialValue::Refer(UUID_TARGET_GROUP)));
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AccessRequestCreated {
+                request: event_request,
+                requester,
+                group,
+                ..
+            }) => {
+                assert_eq!(event_request, request);
+                assert_eq!(requester, UUID_REQUESTER);
+                assert_eq!(group, UUID_TARGET_GROUP);
+            }
+            _ => assert!(false),
+        }
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AccessRequestApproved {
+                request: event_request,
+                requester,
+                group,
+                approver,
+                expiry,
+                ..
+            }) => {
+                assert_eq!(event_request, request);
+                assert_eq!(requester, UUID_REQUESTER);
+                assert_eq!(group, UUID_TARGET_GROUP);
+                assert_eq!(approver, UUID_APPROVER);
+                assert_eq!(
+                    expiry,
+                    time::OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(600)
+                );
+            }
+            _ => assert!(false),
+        }
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let requests = idms_prox_read
+            .access_request_list(&requester)
+            .expect("Failed to list access requests");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].uuid, request);
+        assert_eq!(requests[0].state, AccessRequestState::Approved);
+        assert!(!requests[0].can_decide);
+        assert!(requests[0]
+            .decided_by
+            .as_deref()
+            .map_or(false, |spn| spn.starts_with("test_approver@")));
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_request_deny(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        setup(idms, ct).await;
+
+        let requester = setup_test_ident_readwrite(idms, UUID_REQUESTER).await;
+        let approver = setup_test_ident_readwrite(idms, UUID_APPROVER).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let request = idms_prox_write
+            .access_request_create(&requester, UUID_TARGET_GROUP, "incident 1234")
+            .expect("Failed to create access request");
+        assert!(idms_prox_write.commit().is_ok());
+
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessRequestCreated { request: r, .. }) if r == request
+        ));
+
+        // The approver sees the request as one they can decide.
+        let mut idms_prox_read = idms.proxy_read().await;
+        let requests = idms_prox_read
+            .access_request_list(&approver)
+            .expect("Failed to list access requests");
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].can_decide);
+        assert_eq!(requests[0].state, AccessRequestState::Pending);
+        drop(idms_prox_read);
+
+        // Read only sessions may not decide requests.
+        let approver_ro = approver.project_with_scope(AccessScope::ReadOnly);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert_eq!(
+            idms_prox_write.access_request_decide(&approver_ro, request, false),
+            Err(OperationError::AccessDenied)
+        );
+        assert!(idms_prox_write
+            .access_request_decide(&approver, request, false)
+            .is_ok());
+
+        let group = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TARGET_GROUP)
+            .expect("Failed to find group");
+        assert!(!group.attribute_pres(Attribute::MemberExpiring));
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AccessRequestDenied {
+                request: event_request,
+                requester,
+                group,
+                approver,
+                ..
+            }) => {
+                assert_eq!(event_request, request);
+                assert_eq!(requester, UUID_REQUESTER);
+                assert_eq!(group, UUID_TARGET_GROUP);
+                assert_eq!(approver, UUID_APPROVER);
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_request_decide_not_approver(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        setup(idms, ct).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![setup_test_person("test_other", UUID_OTHER)])
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let requester = setup_test_ident_readwrite(idms, UUID_REQUESTER).await;
+        let other = setup_test_ident_readwrite(idms, UUID_OTHER).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let request = idms_prox_write
+            .access_request_create(&requester, UUID_TARGET_GROUP, "incident 1234")
+            .expect("Failed to create access request");
+
+        // An account that isn't a member of an approver group can't decide the request.
+        assert_eq!(
+            idms_prox_write.access_request_decide(&other, request, true),
+            Err(OperationError::AccessDenied)
+        );
+        assert_eq!(
+            idms_prox_write.access_request_decide(&other, request, false),
+            Err(OperationError::AccessDenied)
+        );
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Only the creation is reported.
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessRequestCreated { request: r, .. }) if r == request
+        ));
+
+        // Nor can they see it.
+        let mut idms_prox_read = idms.proxy_read().await;
+        assert!(idms_prox_read
+            .access_request_list(&other)
+            .expect("Failed to list access requests")
+            .is_empty());
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_request_expiring_member(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        setup(idms, ct).await;
+
+        let requester = setup_test_ident_readwrite(idms, UUID_REQUESTER).await;
+        let approver = setup_test_ident_readwrite(idms, UUID_APPROVER).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let request = idms_prox_write
+            .access_request_create(&requester, UUID_TARGET_GROUP, "incident 1234")
+            .expect("Failed to create access request");
+        assert!(idms_prox_write
+            .access_request_decide(&approver, request, true)
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessRequestCreated { .. })
+        ));
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessRequestApproved { .. })
+        ));
+
+        // While the granted membership is live, a new request would only extend it.
+        let mut idms_prox_write = idms.proxy_write(ct + Duration::from_secs(300)).await;
+        assert_eq!(
+            idms_prox_write.access_request_create(&requester, UUID_TARGET_GROUP, "more time"),
+            Err(OperationError::InvalidRequestState)
+        );
+        drop(idms_prox_write);
+
+        // Once it has expired, access may be requested again.
+        let mut idms_prox_write = idms.proxy_write(ct + Duration::from_secs(601)).await;
+        let request = idms_prox_write
+            .access_request_create(&requester, UUID_TARGET_GROUP, "incident 5678")
+            .expect("Failed to create access request");
+        assert!(idms_prox_write.commit().is_ok());
+
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessRequestCreated { request: r, .. }) if r == request
+        ));
+    }
+}
@@ -0,0 +1,1080 @@
+//! Access reviews, also known as attestation campaigns.
+//!
+//! An access review takes a snapshot of the memberships of the groups matched by a filter. Each
+//! membership becomes a review item that is assigned to the owner of the group, as defined by
+//! entry_managed_by. Reviewers then decide to keep or remove each membership. When the review
+//! expires, any memberships that are still undecided are removed, and the review is closed.
+//!
+//! Creating a review, and viewing all of its items, is granted by access controls. Reviewers
+//! remove memberships with their own access to the group.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::idm::audit::AuditEvent;
+use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
+use crate::prelude::*;
+use kanidm_proto::v1::{
+    AccessReview as ProtoAccessReview, AccessReviewCreate, AccessReviewDecide,
+    AccessReviewDecision, AccessReviewItem as ProtoAccessReviewItem, AccessReviewReport,
+    AccessReviewState,
+};
+use time::OffsetDateTime;
+
+/// The members of a group that are reviewed, including expiring memberships.
+fn access_review_group_members(group: &EntrySealedCommitted) -> BTreeSet<Uuid> {
+    group
+        .get_ava_as_refuuid(Attribute::Member)
+        .into_iter()
+        .flatten()
+        .chain(
+            group
+                .get_ava_as_refuuid(Attribute::MemberExpiring)
+                .into_iter()
+                .flatten(),
+        )
+        .collect()
+}
+
+/// The modifications that remove a member from a group, from whichever of member or
+/// member_expiring grants the membership.
+fn access_review_member_removal(group: &EntrySealedCommitted, member: Uuid) -> Vec<Modify> {
+    [Attribute::Member, Attribute::MemberExpiring]
+        .into_iter()
+        .filter(|attr| group.attribute_equality(*attr, &PartialValue::Refer(member)))
+        .map(|attr| Modify::Removed(attr.into(), PartialValue::Refer(member)))
+        .collect()
+}
+
+fn access_review_is_reviewer(ident: &Identity, item: &EntrySealedCommitted) -> bool {
+    item.get_ava_as_refuuid(Attribute::AccessReviewReviewer)
+        .map(|mut reviewers| {
+            reviewers
+                .any(|reviewer| ident.get_uuid() == Some(reviewer) || ident.is_memberof(reviewer))
+        })
+        .unwrap_or(false)
+}
+
+fn access_review_state(entry: &EntrySealedCommitted) -> Result<AccessReviewState, OperationError> {
+    entry
+        .get_ava_single_proto_string(Attribute::AccessReviewState)
+        .and_then(|s| AccessReviewState::from_str(&s).ok())
+        .ok_or_else(|| {
+            admin_error!(uuid = ?entry.get_uuid(), "access review has an invalid state");
+            OperationError::InvalidEntryState
+        })
+}
+
+fn access_review_decision(
+    entry: &EntrySealedCommitted,
+) -> Result<AccessReviewDecision, OperationError> {
+    entry
+        .get_ava_single_proto_string(Attribute::AccessReviewDecision)
+        .and_then(|s| AccessReviewDecision::from_str(&s).ok())
+        .ok_or_else(|| {
+            admin_error!(uuid = ?entry.get_uuid(), "access review item has an invalid decision");
+            OperationError::InvalidEntryState
+        })
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    /// Start an access review of the memberships of the groups matching the filter. The
+    /// identity must be able to create access reviews, and to remove the members of every
+    /// group that is reviewed.
+    pub fn access_review_create(
+        &mut self,
+        ident: &Identity,
+        create: &AccessReviewCreate,
+    ) -> Result<Uuid, OperationError> {
+        let now = OffsetDateTime::UNIX_EPOCH + self.qs_write.get_curtime();
+
+        let expiry = OffsetDateTime::parse(&create.expiry, &Rfc3339)
+            .map(|odt| odt.to_offset(time::UtcOffset::UTC))
+            .map_err(|_| {
+                request_error!(expiry = %create.expiry, "access review expiry is not rfc3339");
+                OperationError::InvalidRequestState
+            })?;
+
+        if expiry <= now {
+            request_error!(expiry = %create.expiry, "access review expiry is in the past");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        // Only groups that this identity can see are able to be reviewed.
+        let scope = Filter::from_rw(ident, &create.filter, &mut self.qs_write)?;
+        let filter = Filter::join_parts_and(
+            filter!(f_eq(Attribute::Class, EntryClass::Group.into())),
+            scope,
+        );
+        let groups = self
+            .qs_write
+            .impersonate_search(filter.clone(), filter, ident)?;
+
+        if groups.is_empty() {
+            request_error!("access review filter did not match any groups");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        // Undecided memberships are removed when the review expires, so this identity must be
+        // able to remove them. This is checked with a "fake" modify in the same manner as revive.
+        let group_filter = filter!(f_or(
+            groups
+                .iter()
+                .map(|group| f_eq(Attribute::Uuid, PartialValue::Uuid(group.get_uuid())))
+                .collect()
+        ))
+        .validate(self.qs_write.get_schema())
+        .map_err(OperationError::SchemaViolation)?;
+
+        let modlist = ModifyList::new_list(vec![
+            Modify::Purged(Attribute::Member.into()),
+            Modify::Purged(Attribute::MemberExpiring.into()),
+        ])
+        .validate(self.qs_write.get_schema())
+        .map_err(OperationError::SchemaViolation)?;
+
+        let me = ModifyEvent::new_impersonate(ident, group_filter.clone(), group_filter, modlist);
+        let op_allow = self
+            .qs_write
+            .get_accesscontrols()
+            .modify_allow_operation(&me, &groups)
+            .map_err(|e| {
+                admin_error!("Unable to check modify access {:?}", e);
+                e
+            })?;
+        if !op_allow {
+            security_access!("identity is not permitted to remove the members of reviewed groups");
+            security_access!("denied ❌");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let review = Uuid::new_v4();
+
+        let mut entries = vec![entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::AccessReview.to_value()),
+            (Attribute::Uuid, Value::Uuid(review)),
+            (Attribute::Name, Value::new_iname(&create.name)),
+            (Attribute::AccessReviewExpiry, Value::new_datetime(expiry)),
+            (
+                Attribute::AccessReviewState,
+                Value::new_iutf8(AccessReviewState::Open.as_str())
+            )
+        )];
+
+        for group in groups.iter() {
+            // Without an owner there is nobody to review the group.
+            let Some(reviewer) = group.get_ava_single_refer(Attribute::EntryManagedBy) else {
+                request_error!(
+                    group = ?group.get_uuid(),
+                    "access review group has no entry manager to review it"
+                );
+                return Err(OperationError::InvalidRequestState);
+            };
+
+            for member in access_review_group_members(group) {
+                entries.push(entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::AccessReviewItem.to_value()),
+                    (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+                    (Attribute::AccessReviewCampaign, Value::Refer(review)),
+                    (Attribute::AccessReviewGroup, Value::Refer(group.get_uuid())),
+                    (Attribute::AccessReviewMember, Value::Refer(member)),
+                    (Attribute::AccessReviewReviewer, Value::Refer(reviewer)),
+                    (
+                        Attribute::AccessReviewDecision,
+                        Value::new_iutf8(AccessReviewDecision::Pending.as_str())
+                    )
+                ));
+            }
+        }
+
+        let ce = CreateEvent {
+            ident: ident.clone(),
+            entries,
+        };
+        self.qs_write.create(&ce)?;
+
+        Ok(review)
+    }
+
+    /// Decide to keep or remove a membership that is under review. Removals take effect
+    /// immediately, and require the reviewer to be able to remove the member from the group.
+    pub fn access_review_decide(
+        &mut self,
+        ident: &Identity,
+        review: Uuid,
+        decide: &AccessReviewDecide,
+    ) -> Result<(), OperationError> {
+        let reviewer = match ident.get_uuid() {
+            Some(reviewer) if ident.get_user_entry().is_some() => reviewer,
+            _ => {
+                security_access!("only accounts may decide access review items");
+                return Err(OperationError::AccessDenied);
+            }
+        };
+
+        if ident.access_scope() != AccessScope::ReadWrite {
+            security_access!("identity access scope is not permitted to decide access reviews");
+            security_access!("denied ❌");
+            return Err(OperationError::AccessDenied);
+        }
+
+        if decide.decision == AccessReviewDecision::Pending {
+            request_error!("access review items must be decided as keep or remove");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let review_entry = self.qs_write.internal_search_uuid(review)?;
+
+        if !review_entry.attribute_equality(Attribute::Class, &EntryClass::AccessReview.into()) {
+            return Err(OperationError::NoMatchingEntries);
+        }
+
+        if access_review_state(&review_entry)? != AccessReviewState::Open {
+            request_error!(?review, "access review is closed");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let item = decide.item;
+        let item_entry = self.qs_write.internal_search_uuid(item)?;
+
+        if !item_entry.attribute_equality(Attribute::Class, &EntryClass::AccessReviewItem.into())
+            || !item_entry.attribute_equality(
+                Attribute::AccessReviewCampaign,
+                &PartialValue::Refer(review),
+            )
+        {
+            return Err(OperationError::NoMatchingEntries);
+        }
+
+        if access_review_decision(&item_entry)? != AccessReviewDecision::Pending {
+            request_error!(?item, "access review item has already been decided");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let (Some(group), Some(member)) = (
+            item_entry.get_ava_single_refer(Attribute::AccessReviewGroup),
+            item_entry.get_ava_single_refer(Attribute::AccessReviewMember),
+        ) else {
+            // The group or member were deleted, so there is nothing left to decide.
+            request_error!(?item, "access review group or member no longer exists");
+            return Err(OperationError::InvalidRequestState);
+        };
+
+        // Reviewers may not attest to their own memberships.
+        if member == reviewer || !access_review_is_reviewer(ident, &item_entry) {
+            security_access!(
+                ?item,
+                "identity is not a reviewer of this access review item"
+            );
+            security_access!("denied ❌");
+            return Err(OperationError::AccessDenied);
+        }
+
+        if decide.decision == AccessReviewDecision::Remove {
+            let group_entry = self.qs_write.internal_search_uuid(group)?;
+            let removals = access_review_member_removal(&group_entry, member);
+
+            // The member may have already left the group.
+            if !removals.is_empty() {
+                let filter = filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(group)));
+                self.qs_write.impersonate_modify(
+                    &filter,
+                    &filter,
+                    &ModifyList::new_list(removals),
+                    ident,
+                )?;
+            }
+        }
+
+        let modlist = ModifyList::new_list(vec![
+            m_purge(Attribute::AccessReviewDecision),
+            Modify::Present(
+                Attribute::AccessReviewDecision.into(),
+                Value::new_iutf8(decide.decision.as_str()),
+            ),
+            Modify::Present(
+                Attribute::AccessReviewDecidedBy.into(),
+                Value::Refer(reviewer),
+            ),
+        ]);
+        self.qs_write.internal_modify_uuid(item, &modlist)?;
+
+        self.qs_write
+            .audit_event_on_commit(AuditEvent::AccessReviewDecided {
+                review,
+                item,
+                group,
+                member,
+                reviewer,
+                decision: decide.decision,
+                time: OffsetDateTime::UNIX_EPOCH + self.qs_write.get_curtime(),
+            });
+
+        Ok(())
+    }
+
+    /// Close access reviews that have passed their expiry, removing any memberships that
+    /// were not decided.
+    pub fn access_review_close_expired(&mut self) -> Result<(), OperationError> {
+        let now = OffsetDateTime::UNIX_EPOCH + self.qs_write.get_curtime();
+
+        let reviews = self.qs_write.internal_search(filter!(f_and!([
+            f_eq(Attribute::Class, EntryClass::AccessReview.into()),
+            f_eq(
+                Attribute::AccessReviewState,
+                PartialValue::new_iutf8(AccessReviewState::Open.as_str())
+            )
+        ])))?;
+
+        let mut modset = Vec::new();
+        let mut group_removals: BTreeMap<Uuid, Vec<Modify>> = BTreeMap::new();
+        let mut audit_events = Vec::new();
+
+        for review_entry in reviews.iter() {
+            let review = review_entry.get_uuid();
+
+            if review_entry
+                .get_ava_single_datetime(Attribute::AccessReviewExpiry)
+                .map(|expiry| expiry > now)
+                .unwrap_or(false)
+            {
+                continue;
+            }
+
+            let items = self.qs_write.internal_search(filter!(f_and!([
+                f_eq(Attribute::Class, EntryClass::AccessReviewItem.into()),
+                f_eq(Attribute::AccessReviewCampaign, PartialValue::Refer(review)),
+                f_eq(
+                    Attribute::AccessReviewDecision,
+                    PartialValue::new_iutf8(AccessReviewDecision::Pending.as_str())
+                )
+            ])))?;
+
+            for item_entry in items.iter() {
+                if let (Some(group), Some(member)) = (
+                    item_entry.get_ava_single_refer(Attribute::AccessReviewGroup),
+                    item_entry.get_ava_single_refer(Attribute::AccessReviewMember),
+                ) {
+                    let group_entry = self.qs_write.internal_search_uuid(group)?;
+                    group_removals
+                        .entry(group)
+                        .or_default()
+                        .extend(access_review_member_removal(&group_entry, member));
+
+                    audit_events.push(AuditEvent::AccessReviewExpired {
+                        review,
+                        item: item_entry.get_uuid(),
+                        group,
+                        member,
+                        time: now,
+                    });
+                }
+
+                modset.push((
+                    item_entry.get_uuid(),
+                    ModifyList::new_purge_and_set(
+                        Attribute::AccessReviewDecision,
+                        Value::new_iutf8(AccessReviewDecision::Remove.as_str()),
+                    ),
+                ));
+            }
+
+            modset.push((
+                review,
+                ModifyList::new_purge_and_set(
+                    Attribute::AccessReviewState,
+                    Value::new_iutf8(AccessReviewState::Closed.as_str()),
+                ),
+            ));
+        }
+
+        if modset.is_empty() {
+            admin_info!("No expired access reviews present");
+            return Ok(());
+        }
+
+        modset.extend(
+            group_removals
+                .into_iter()
+                .filter(|(_, removals)| !removals.is_empty())
+                .map(|(group, removals)| (group, ModifyList::new_list(removals))),
+        );
+
+        self.qs_write
+            .internal_batch_modify(modset.into_iter())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Close expired access reviews operation failed");
+                e
+            })?;
+
+        for event in audit_events {
+            self.qs_write.audit_event_on_commit(event);
+        }
+
+        admin_info!("Close expired access reviews operation success");
+        Ok(())
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// List the access reviews that this identity administers, or has items to review in.
+    pub fn access_review_list(
+        &mut self,
+        ident: &Identity,
+    ) -> Result<Vec<ProtoAccessReview>, OperationError> {
+        let reviews = self.qs_read.internal_search(filter!(f_eq(
+            Attribute::Class,
+            EntryClass::AccessReview.into()
+        )))?;
+
+        let mut views = Vec::with_capacity(reviews.len());
+
+        for review_entry in reviews.iter() {
+            let is_manager = self.access_review_is_manager(ident, review_entry.get_uuid())?;
+            let items =
+                self.access_review_visible_items(ident, review_entry.get_uuid(), is_manager)?;
+
+            if items.is_empty() && !is_manager {
+                continue;
+            }
+
+            let mut pending = 0;
+            for item_entry in items.iter() {
+                if access_review_decision(item_entry)? == AccessReviewDecision::Pending {
+                    pending += 1;
+                }
+            }
+
+            views.push(access_review_to_proto(review_entry, pending)?);
+        }
+
+        Ok(views)
+    }
+
+    /// Report on the decisions made in an access review. Reviewers only see the items that
+    /// they are assigned to.
+    pub fn access_review_report(
+        &mut self,
+        ident: &Identity,
+        review: Uuid,
+    ) -> Result<AccessReviewReport, OperationError> {
+        let review_entry = self.qs_read.internal_search_uuid(review)?;
+
+        if !review_entry.attribute_equality(Attribute::Class, &EntryClass::AccessReview.into()) {
+            return Err(OperationError::NoMatchingEntries);
+        }
+
+        let is_manager = self.access_review_is_manager(ident, review)?;
+        let items = self.access_review_visible_items(ident, review, is_manager)?;
+
+        if items.is_empty() && !is_manager {
+            security_access!(?review, "identity is not a reviewer of this access review");
+            security_access!("denied ❌");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let is_open = access_review_state(&review_entry)? == AccessReviewState::Open;
+
+        let mut views = Vec::with_capacity(items.len());
+        let mut pending = 0;
+
+        for item_entry in items.iter() {
+            let decision = access_review_decision(item_entry)?;
+            let member = item_entry.get_ava_single_refer(Attribute::AccessReviewMember);
+
+            if decision == AccessReviewDecision::Pending {
+                pending += 1;
+            }
+
+            let reviewers = match item_entry.get_ava_as_refuuid(Attribute::AccessReviewReviewer) {
+                Some(reviewers) => reviewers
+                    .map(|u| self.access_review_spn(Some(u)))
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => Vec::with_capacity(0),
+            };
+
+            let decided_by = match item_entry.get_ava_single_refer(Attribute::AccessReviewDecidedBy)
+            {
+                Some(u) => Some(self.access_review_spn(Some(u))?),
+                None => None,
+            };
+
+            views.push(ProtoAccessReviewItem {
+                uuid: item_entry.get_uuid(),
+                group: self.access_review_spn(
+                    item_entry.get_ava_single_refer(Attribute::AccessReviewGroup),
+                )?,
+                member: self.access_review_spn(member)?,
+                reviewers,
+                decision,
+                decided_by,
+                can_decide: is_open
+                    && decision == AccessReviewDecision::Pending
+                    && member.is_some()
+                    && member != ident.get_uuid()
+                    && access_review_is_reviewer(ident, item_entry),
+            });
+        }
+
+        Ok(AccessReviewReport {
+            review: access_review_to_proto(&review_entry, pending)?,
+            items: views,
+        })
+    }
+
+    /// If this identity is granted access to view every item of the access review.
+    fn access_review_is_manager(
+        &mut self,
+        ident: &Identity,
+        review: Uuid,
+    ) -> Result<bool, OperationError> {
+        let filter = filter!(f_and!([
+            f_eq(Attribute::Class, EntryClass::AccessReview.into()),
+            f_eq(Attribute::Uuid, PartialValue::Uuid(review))
+        ]));
+
+        self.qs_read
+            .impersonate_search(filter.clone(), filter, ident)
+            .map(|reviews| !reviews.is_empty())
+    }
+
+    fn access_review_visible_items(
+        &mut self,
+        ident: &Identity,
+        review: Uuid,
+        is_manager: bool,
+    ) -> Result<Vec<Arc<EntrySealedCommitted>>, OperationError> {
+        self.qs_read
+            .internal_search(filter!(f_and!([
+                f_eq(Attribute::Class, EntryClass::AccessReviewItem.into()),
+                f_eq(Attribute::AccessReviewCampaign, PartialValue::Refer(review))
+            ])))
+            .map(|items| {
+                items
+                    .into_iter()
+                    .filter(|item| is_manager || access_review_is_reviewer(ident, item))
+                    .collect()
+            })
+    }
+
+    fn access_review_spn(&mut self, uuid: Option<Uuid>) -> Result<String, OperationError> {
+        // The referenced entry may have been deleted since the review was created.
+        let Some(uuid) = uuid else {
+            return Ok("(deleted)".to_string());
+        };
+
+        self.qs_read.uuid_to_spn(uuid).map(|nv| match nv {
+            Some(v) => v.to_proto_string_clone(),
+            None => uuid.as_hyphenated().to_string(),
+        })
+    }
+}
+
+fn access_review_to_proto(
+    review_entry: &EntrySealedCommitted,
+    pending: usize,
+) -> Result<ProtoAccessReview, OperationError> {
+    let expiry = review_entry
+        .get_ava_single_datetime(Attribute::AccessReviewExpiry)
+        .and_then(|odt| odt.format(&Rfc3339).ok())
+        .ok_or_else(|| {
+            admin_error!(uuid = ?review_entry.get_uuid(), "access review has an invalid expiry");
+            OperationError::InvalidEntryState
+        })?;
+
+    Ok(ProtoAccessReview {
+        uuid: review_entry.get_uuid(),
+        name: review_entry
+            .get_ava_single_iname(Attribute::Name)
+            .map(str::to_string)
+            .unwrap_or_default(),
+        expiry,
+        state: access_review_state(review_entry)?,
+        pending,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::idm::audit::{AuditEvent, AuditOperation};
+    use crate::prelude::*;
+    use crate::testkit::{setup_test_ident_readwrite, setup_test_person};
+    use kanidm_proto::v1::{
+        AccessReviewCreate, AccessReviewDecide, AccessReviewDecision, AccessReviewState,
+        Filter as ProtoFilter,
+    };
+    use std::collections::BTreeSet;
+
+    const TEST_CURRENT_TIME: u64 = 6000;
+
+    const UUID_OWNER: Uuid = uuid!("0ad2e1bb-5f7c-4b6f-8d26-b8f3d1d1c4a1");
+    const UUID_MEMBER_A: Uuid = uuid!("5a3cb30c-6d8c-4a2a-9f6e-0f4f9bd6e2b2");
+    const UUID_MEMBER_B: Uuid = uuid!("9c7d0f3e-2b1a-4a55-8a0c-3e9f1c6b7d83");
+    const UUID_MEMBER_C: Uuid = uuid!("2f8e4d6c-1a3b-4c5d-9e7f-6a5b4c3d2e1f");
+    const UUID_OWNER_GROUP: Uuid = uuid!("4e5f6a7b-8c9d-4e0f-a1b2-c3d4e5f6a7b8");
+    const UUID_REVIEWED_GROUP: Uuid = uuid!("c1d2e3f4-a5b6-4c7d-8e9f-0a1b2c3d4e5f");
+
+    fn review_create(name: &str) -> AccessReviewCreate {
+        AccessReviewCreate {
+            name: "test_review".to_string(),
+            filter: ProtoFilter::Eq(Attribute::Name.to_string(), name.to_string()),
+            expiry: "1970-01-02T00:00:00Z".to_string(),
+        }
+    }
+
+    async fn setup(idms: &IdmServer, ct: Duration) -> Uuid {
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let expiry =
+            time::OffsetDateTime::UNIX_EPOCH + Duration::from_secs(TEST_CURRENT_TIME + 7 * 86400);
+
+        let owner_group = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("test_owners")),
+            (Attribute::Uuid, Value::Uuid(UUID_OWNER_GROUP)),
+            (Attribute::Member, Value::Refer(UUID_OWNER))
+        );
+
+        let reviewed_group = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("test_reviewed")),
+            (Attribute::Uuid, Value::Uuid(UUID_REVIEWED_GROUP)),
+            (Attribute::EntryManagedBy, Value::Refer(UUID_OWNER_GROUP)),
+            (Attribute::Member, Value::Refer(UUID_MEMBER_A)),
+            (Attribute::Member, Value::Refer(UUID_MEMBER_B)),
+            (
+                Attribute::MemberExpiring,
+                Value::new_refer_expiring(UUID_MEMBER_C, expiry)
+            )
+        );
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![
+                setup_test_person("test_owner", UUID_OWNER),
+                setup_test_person("test_member_a", UUID_MEMBER_A),
+                setup_test_person("test_member_b", UUID_MEMBER_B),
+                setup_test_person("test_member_c", UUID_MEMBER_C),
+                owner_group,
+                reviewed_group,
+            ])
+            .is_ok());
+
+        // The owners remove members with their own access to the group.
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_GROUP_WRITE_PRIV,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(UUID_OWNER_GROUP))
+            )
+            .is_ok());
+
+        let idm_admin = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .map(Identity::from_impersonate_entry_readwrite)
+            .expect("Failed to find idm_admin");
+
+        let review = idms_prox_write
+            .access_review_create(&idm_admin, &review_create("test_reviewed"))
+            .expect("Failed to create access review");
+
+        assert!(idms_prox_write.commit().is_ok());
+        review
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_review_decide(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let review = setup(idms, ct).await;
+
+        let owner = setup_test_ident_readwrite(idms, UUID_OWNER).await;
+        let member_a = setup_test_ident_readwrite(idms, UUID_MEMBER_A).await;
+
+        // Members of the group are not reviewers of it.
+        let mut idms_prox_read = idms.proxy_read().await;
+        assert!(idms_prox_read
+            .access_review_list(&member_a)
+            .expect("Failed to list access reviews")
+            .is_empty());
+        assert_eq!(
+            idms_prox_read.access_review_report(&member_a, review),
+            Err(OperationError::AccessDenied)
+        );
+
+        let report = idms_prox_read
+            .access_review_report(&owner, review)
+            .expect("Failed to report access review");
+        drop(idms_prox_read);
+
+        assert_eq!(report.review.state, AccessReviewState::Open);
+        assert_eq!(report.review.pending, 3);
+        assert_eq!(report.items.len(), 3);
+        assert!(report.items.iter().all(|item| item.can_decide));
+
+        let item_a = report
+            .items
+            .iter()
+            .find(|item| item.member.starts_with("test_member_a@"))
+            .map(|item| item.uuid)
+            .expect("Missing review item");
+        let item_b = report
+            .items
+            .iter()
+            .find(|item| item.member.starts_with("test_member_b@"))
+            .map(|item| item.uuid)
+            .expect("Missing review item");
+        let item_c = report
+            .items
+            .iter()
+            .find(|item| item.member.starts_with("test_member_c@"))
+            .map(|item| item.uuid)
+            .expect("Missing review item");
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        assert_eq!(
+            idms_prox_write.access_review_decide(
+                &member_a,
+                review,
+                &AccessReviewDecide {
+                    item: item_a,
+                    decision: AccessReviewDecision::Keep,
+                }
+            ),
+            Err(OperationError::AccessDenied)
+        );
+
+        assert!(idms_prox_write
+            .access_review_decide(
+                &owner,
+                review,
+                &AccessReviewDecide {
+                    item: item_a,
+                    decision: AccessReviewDecision::Keep,
+                }
+            )
+            .is_ok());
+
+        assert!(idms_prox_write
+            .access_review_decide(
+                &owner,
+                review,
+                &AccessReviewDecide {
+                    item: item_b,
+                    decision: AccessReviewDecision::Remove,
+                }
+            )
+            .is_ok());
+
+        // Expiring memberships are reviewed too.
+        assert!(idms_prox_write
+            .access_review_decide(
+                &owner,
+                review,
+                &AccessReviewDecide {
+                    item: item_c,
+                    decision: AccessReviewDecision::Remove,
+                }
+            )
+            .is_ok());
+
+        // Decisions are final.
+        assert_eq!(
+            idms_prox_write.access_review_decide(
+                &owner,
+                review,
+                &AccessReviewDecide {
+                    item: item_b,
+                    decision: AccessReviewDecision::Keep,
+                }
+            ),
+            Err(OperationError::InvalidRequestState)
+        );
+
+        let group = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_REVIEWED_GROUP)
+            .expect("Failed to find group");
+        assert!(group.attribute_equality(Attribute::Member, &PartialValue::Refer(UUID_MEMBER_A)));
+        assert!(!group.attribute_equality(Attribute::Member, &PartialValue::Refer(UUID_MEMBER_B)));
+        assert!(!group.attribute_pres(Attribute::MemberExpiring));
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        for (expect_item, expect_member, expect_decision) in [
+            (item_a, UUID_MEMBER_A, AccessReviewDecision::Keep),
+            (item_b, UUID_MEMBER_B, AccessReviewDecision::Remove),
+            (item_c, UUID_MEMBER_C, AccessReviewDecision::Remove),
+        ] {
+            match idms_audit.audit_rx().try_recv() {
+                Ok(AuditEvent::AccessReviewDecided {
+                    review: event_review,
+                    item,
+                    group,
+                    member,
+                    reviewer,
+                    decision,
+                    ..
+                }) => {
+                    assert_eq!(event_review, review);
+                    assert_eq!(item, expect_item);
+                    assert_eq!(group, UUID_REVIEWED_GROUP);
+                    assert_eq!(member, expect_member);
+                    assert_eq!(reviewer, UUID_OWNER);
+                    assert_eq!(decision, expect_decision);
+                }
+                _ => assert!(false),
+            }
+        }
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let report = idms_prox_read
+            .access_review_report(&owner, review)
+            .expect("Failed to report access review");
+        assert_eq!(report.review.pending, 0);
+        assert!(report.items.iter().all(|item| !item.can_decide
+            && item
+                .decided_by
+                .as_deref()
+                .map_or(false, |spn| spn.starts_with("test_owner@"))));
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_review_expiry(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let review = setup(idms, ct).await;
+
+        // Before the expiry nothing changes.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write.access_review_close_expired().is_ok());
+        let group = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_REVIEWED_GROUP)
+            .expect("Failed to find group");
+        assert!(group.attribute_equality(Attribute::Member, &PartialValue::Refer(UUID_MEMBER_A)));
+        assert!(idms_prox_write.commit().is_ok());
+        assert!(idms_audit.audit_rx().try_recv().is_err());
+
+        // After the expiry, undecided memberships are removed.
+        let ct = Duration::from_secs(TEST_CURRENT_TIME + 86400);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write.access_review_close_expired().is_ok());
+        let group = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_REVIEWED_GROUP)
+            .expect("Failed to find group");
+        assert!(!group.attribute_pres(Attribute::Member));
+        assert!(!group.attribute_pres(Attribute::MemberExpiring));
+
+        let review_entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(review)
+            .expect("Failed to find access review");
+        assert!(review_entry.attribute_equality(
+            Attribute::AccessReviewState,
+            &PartialValue::new_iutf8(AccessReviewState::Closed.as_str())
+        ));
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Each undecided membership that was removed is reported.
+        let mut expired = BTreeSet::new();
+        for _ in 0..3 {
+            match idms_audit.audit_rx().try_recv() {
+                Ok(AuditEvent::AccessReviewExpired {
+                    review: event_review,
+                    group,
+                    member,
+                    ..
+                }) => {
+                    assert_eq!(event_review, review);
+                    assert_eq!(group, UUID_REVIEWED_GROUP);
+                    expired.insert(member);
+                }
+                _ => assert!(false),
+            }
+        }
+        assert_eq!(
+            expired,
+            BTreeSet::from([UUID_MEMBER_A, UUID_MEMBER_B, UUID_MEMBER_C])
+        );
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_review_create_denied(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let review = setup(idms, ct).await;
+
+        let owner = setup_test_ident_readwrite(idms, UUID_OWNER).await;
+        let idm_admin = setup_test_ident_readwrite(idms, UUID_IDM_ADMIN).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let unowned_group = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("test_unowned")),
+            (Attribute::Member, Value::Refer(UUID_MEMBER_A))
+        );
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![unowned_group])
+            .is_ok());
+
+        // The owners may change the group, but are not granted access to create reviews.
+        assert_eq!(
+            idms_prox_write.access_review_create(&owner, &review_create("test_reviewed")),
+            Err(OperationError::AccessDenied)
+        );
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessDenied {
+                identity: Some(identity),
+                operation: AuditOperation::Create,
+                ..
+            }) if identity == UUID_OWNER
+        ));
+
+        // Idm admins may not change the members of high privilege groups.
+        assert_eq!(
+            idms_prox_write.access_review_create(&idm_admin, &review_create("idm_admins")),
+            Err(OperationError::AccessDenied)
+        );
+
+        // A group without an owner has nobody to review it.
+        assert_eq!(
+            idms_prox_write.access_review_create(&idm_admin, &review_create("test_unowned")),
+            Err(OperationError::InvalidRequestState)
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Only the owner of the group sees its items, but idm admins see the whole review.
+        let mut idms_prox_read = idms.proxy_read().await;
+        let report = idms_prox_read
+            .access_review_report(&idm_admin, review)
+            .expect("Failed to report access review");
+        assert_eq!(report.items.len(), 3);
+        assert!(report.items.iter().all(|item| !item.can_decide));
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_review_decide_denied(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let review = setup(idms, ct).await;
+
+        // The owners lose their access to change the group.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_GROUP_WRITE_PRIV,
+                &ModifyList::new_remove(Attribute::Member, PartialValue::Refer(UUID_OWNER_GROUP))
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let owner = setup_test_ident_readwrite(idms, UUID_OWNER).await;
+        let idm_admin = setup_test_ident_readwrite(idms, UUID_IDM_ADMIN).await;
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let report = idms_prox_read
+            .access_review_report(&owner, review)
+            .expect("Failed to report access review");
+        drop(idms_prox_read);
+
+        let item_a = report
+            .items
+            .iter()
+            .find(|item| item.member.starts_with("test_member_a@"))
+            .map(|item| item.uuid)
+            .expect("Missing review item");
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // Idm admins may change the group, but are not the reviewers of it.
+        assert_eq!(
+            idms_prox_write.access_review_decide(
+                &idm_admin,
+                review,
+                &AccessReviewDecide {
+                    item: item_a,
+                    decision: AccessReviewDecision::Remove,
+                }
+            ),
+            Err(OperationError::AccessDenied)
+        );
+
+        // The owner is the reviewer, but can no longer remove the member.
+        assert_eq!(
+            idms_prox_write.access_review_decide(
+                &owner,
+                review,
+                &AccessReviewDecide {
+                    item: item_a,
+                    decision: AccessReviewDecision::Remove,
+                }
+            ),
+            Err(OperationError::AccessDenied)
+        );
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessDenied {
+                identity: Some(identity),
+                operation: AuditOperation::Modify,
+                ..
+            }) if identity == UUID_OWNER
+        ));
+
+        let group = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_REVIEWED_GROUP)
+            .expect("Failed to find group");
+        assert!(group.attribute_equality(Attribute::Member, &PartialValue::Refer(UUID_MEMBER_A)));
+
+        // Keeping the member changes nothing, so is still permitted.
+        assert!(idms_prox_write
+            .access_review_decide(
+                &owner,
+                review,
+                &AccessReviewDecide {
+                    item: item_a,
+                    decision: AccessReviewDecision::Keep,
+                }
+            )
+            .is_ok());
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessReviewDecided {
+                item,
+                reviewer,
+                decision: AccessReviewDecision::Keep,
+                ..
+            }) if item == item_a && reviewer == UUID_OWNER
+        ));
+    }
+}
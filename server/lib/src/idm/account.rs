@@ -17,7 +17,9 @@ use crate::credential::Credential;
 use crate::entry::{Entry, EntryCommitted, EntryReduced, EntrySealed};
 use crate::event::SearchEvent;
 use crate::idm::group::Group;
-use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
+use crate::idm::server::{
+    AccountPolicy, IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction,
+};
 use crate::modify::{ModifyInvalid, ModifyList};
 use crate::prelude::*;
 use crate::schema::SchemaTransaction;
@@ -122,6 +124,12 @@ macro_rules! try_from_entry {
             ui_hints.insert(UiHint::PosixAccount);
         }
 
+        // Policy from groups.
+        let account_policy = groups
+            .iter()
+            .filter_map(|group: &Group| group.account_policy.as_ref())
+            .fold(AccountPolicy::unconstrained(), AccountPolicy::merge);
+
         Ok(Account {
             uuid,
             name,
@@ -139,6 +147,7 @@ macro_rules! try_from_entry {
             mail_primary,
             mail,
             credential_update_intent_tokens,
+            account_policy,
         })
     }};
 }
@@ -171,6 +180,7 @@ pub struct Account {
     pub mail_primary: Option<String>,
     pub mail: Vec<String>,
     pub credential_update_intent_tokens: BTreeMap<String, IntentTokenState>,
+    pub(crate) account_policy: AccountPolicy,
 }
 
 impl Account {
@@ -1,12 +1,16 @@
 use crate::prelude::*;
+use kanidm_proto::v1::AccessReviewDecision;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::net::IpAddr;
 use time::OffsetDateTime;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum AuditSource {
     Internal,
     Https(IpAddr),
+    Ldaps(IpAddr),
+    Replication(IpAddr),
 }
 
 impl From<Source> for AuditSource {
@@ -14,11 +18,24 @@ impl From<Source> for AuditSource {
         match value {
             Source::Internal => AuditSource::Internal,
             Source::Https(ip) => AuditSource::Https(ip),
+            Source::Ldaps(ip) => AuditSource::Ldaps(ip),
+            Source::Replication(ip) => AuditSource::Replication(ip),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+/// The kind of write that an identity was denied from performing.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOperation {
+    Create,
+    Modify,
+    Delete,
+}
+
+/// A security relevant event that occurred in the server. These are submitted to the
+/// audit channel of the [IdmServer](crate::idm::server::IdmServer) where they are
+/// processed by the configured audit sinks.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum AuditEvent {
     AuthenticationDenied {
         source: AuditSource,
@@ -27,4 +44,155 @@ pub enum AuditEvent {
         #[serde(with = "time::serde::timestamp")]
         time: OffsetDateTime,
     },
+    AuthenticationSuccess {
+        source: AuditSource,
+        uuid: Uuid,
+        spn: String,
+        /// If the session was granted privileges as part of the authentication.
+        privileged: bool,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// An existing session re-authenticated to gain privileges.
+    PrivilegeReauthentication {
+        source: AuditSource,
+        uuid: Uuid,
+        spn: String,
+        session_id: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// The credentials of an account were changed by a credential update session.
+    CredentialUpdate {
+        uuid: Uuid,
+        spn: String,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// Access controls denied a write. The uuid is absent for anonymous or internal
+    /// identities.
+    AccessDenied {
+        identity: Option<Uuid>,
+        operation: AuditOperation,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// An account consented to release the listed scopes to an oauth2 resource server.
+    Oauth2ConsentGranted {
+        uuid: Uuid,
+        spn: String,
+        rs_uuid: Uuid,
+        rs_name: String,
+        scopes: BTreeSet<String>,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// A sync account applied changes from an external source.
+    SyncAccountApply {
+        uuid: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// An expiring group membership passed its expiry and was removed from the group.
+    GroupMembershipExpired {
+        group: Uuid,
+        member: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        expiry: OffsetDateTime,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// An account requested to be granted membership of a group.
+    AccessRequestCreated {
+        request: Uuid,
+        requester: Uuid,
+        group: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// An access request was approved, granting the requester membership of the group
+    /// until the expiry.
+    AccessRequestApproved {
+        request: Uuid,
+        requester: Uuid,
+        group: Uuid,
+        approver: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        expiry: OffsetDateTime,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// An access request was denied.
+    AccessRequestDenied {
+        request: Uuid,
+        requester: Uuid,
+        group: Uuid,
+        approver: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// A reviewer decided to keep or remove a membership under review.
+    AccessReviewDecided {
+        review: Uuid,
+        item: Uuid,
+        group: Uuid,
+        member: Uuid,
+        reviewer: Uuid,
+        decision: AccessReviewDecision,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// An access review closed with this membership undecided, so it was removed.
+    AccessReviewExpired {
+        review: Uuid,
+        item: Uuid,
+        group: Uuid,
+        member: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    /// The database content of this server was replaced by a refresh from a replication peer.
+    ReplicationRefresh {
+        source: AuditSource,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditEvent, AuditOperation, AuditSource};
+    use std::net::{IpAddr, Ipv4Addr};
+    use time::OffsetDateTime;
+
+    #[test]
+    fn test_audit_event_serialise() {
+        let events = vec![
+            AuditEvent::AuthenticationSuccess {
+                source: AuditSource::Ldaps(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+                uuid: crate::constants::UUID_ADMIN,
+                spn: "admin@example.com".to_string(),
+                privileged: false,
+                time: OffsetDateTime::UNIX_EPOCH,
+            },
+            AuditEvent::AccessDenied {
+                identity: None,
+                operation: AuditOperation::Modify,
+                time: OffsetDateTime::UNIX_EPOCH,
+            },
+            AuditEvent::GroupMembershipExpired {
+                group: crate::constants::UUID_IDM_ADMINS,
+                member: crate::constants::UUID_ADMIN,
+                expiry: OffsetDateTime::UNIX_EPOCH,
+                time: OffsetDateTime::UNIX_EPOCH,
+            },
+        ];
+
+        for event in events {
+            let json = serde_json::to_string(&event).expect("Failed to serialise audit event");
+            let de: AuditEvent =
+                serde_json::from_str(&json).expect("Failed to deserialise audit event");
+            assert_eq!(de, event);
+        }
+    }
 }
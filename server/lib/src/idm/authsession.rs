@@ -25,13 +25,14 @@ use webauthn_rs::prelude::{
 };
 
 use crate::credential::totp::Totp;
-use crate::credential::{BackupCodes, Credential, CredentialType, Password};
+use crate::credential::{BackupCodes, Credential, CredentialKind, CredentialType, Password};
 use crate::idm::account::Account;
 use crate::idm::audit::AuditEvent;
 use crate::idm::delayed::{
     AuthSessionRecord, BackupCodeRemoval, DelayedAction, PasswordUpgrade, WebauthnCounterIncrement,
 };
 use crate::idm::AuthState;
+use crate::metrics::METRICS;
 use crate::prelude::*;
 use crate::value::{Session, SessionState};
 use time::OffsetDateTime;
@@ -294,6 +295,20 @@ impl TryFrom<(Uuid, &PasskeyV4, &Webauthn)> for CredHandler {
 }
 
 impl CredHandler {
+    /// The type of authentication this handler performs, used when reporting the outcome
+    /// of an attempt.
+    fn auth_type(&self) -> AuthType {
+        match self {
+            CredHandler::Anonymous { .. } => AuthType::Anonymous,
+            CredHandler::Password {
+                generated: true, ..
+            } => AuthType::GeneratedPassword,
+            CredHandler::Password { .. } => AuthType::Password,
+            CredHandler::PasswordMfa { .. } => AuthType::PasswordMfa,
+            CredHandler::Passkey { .. } => AuthType::Passkey,
+        }
+    }
+
     /// Determine if this password factor requires an upgrade of it's cryptographic type. If
     /// so, send an asynchronous event into the queue that will allow the password to have it's
     /// content upgraded later.
@@ -304,6 +319,7 @@ impl CredHandler {
         async_tx: &Sender<DelayedAction>,
     ) {
         if pw.requires_upgrade() {
+            METRICS.delayed_action_queued();
             if let Err(_e) = async_tx.send(DelayedAction::PwUpgrade(PasswordUpgrade {
                 target_uuid: who,
                 existing_password: cleartext.to_string(),
@@ -412,6 +428,7 @@ impl CredHandler {
                                 // async from r.
                                 if auth_result.needs_update() {
                                     // Do async
+                                    METRICS.delayed_action_queued();
                                     if let Err(_e) =
                                         async_tx.send(DelayedAction::WebauthnCounterIncrement(
                                             WebauthnCounterIncrement {
@@ -461,6 +478,7 @@ impl CredHandler {
                     }
                     (AuthCredential::BackupCode(code_chal), _, _, Some(backup_codes)) => {
                         if backup_codes.verify(code_chal) {
+                            METRICS.delayed_action_queued();
                             if let Err(_e) =
                                 async_tx.send(DelayedAction::BackupCodeRemoval(BackupCodeRemoval {
                                     target_uuid: who,
@@ -560,6 +578,7 @@ impl CredHandler {
                             // async from r.
                             if auth_result.needs_update() {
                                 // Do async
+                                METRICS.delayed_action_queued();
                                 if let Err(_e) =
                                     async_tx.send(DelayedAction::WebauthnCounterIncrement(
                                         WebauthnCounterIncrement {
@@ -774,7 +793,15 @@ impl AuthSession {
                 if let Some(cred) = &account.primary {
                     // TODO: Make it possible to have multiple creds.
                     // Probably means new authsession has to be failable
-                    if let Ok(ch) = CredHandler::try_from((cred, webauthn)) {
+                    if !account
+                        .account_policy
+                        .credential_kind_allowed(cred.type_.kind())
+                    {
+                        security_info!(
+                            credential_type = %cred.type_.kind(),
+                            "primary credential is not permitted by account policy"
+                        );
+                    } else if let Ok(ch) = CredHandler::try_from((cred, webauthn)) {
                         handlers.push(ch);
                     } else {
                         security_critical!(
@@ -783,9 +810,16 @@ impl AuthSession {
                     }
                 }
 
-                if let Ok(ch) = CredHandler::try_from((&account.passkeys, webauthn)) {
-                    handlers.push(ch);
-                };
+                if account
+                    .account_policy
+                    .credential_kind_allowed(CredentialKind::Webauthn)
+                {
+                    if let Ok(ch) = CredHandler::try_from((&account.passkeys, webauthn)) {
+                        handlers.push(ch);
+                    };
+                } else {
+                    security_info!("passkeys are not permitted by account policy");
+                }
 
                 if let Some(non_empty_handlers) = NonEmpty::collect(handlers) {
                     AuthSessionState::Init(non_empty_handlers)
@@ -856,8 +890,10 @@ impl AuthSession {
 
             let mut cred_handler = None;
 
+            let policy = &account.account_policy;
+
             if let Some(primary) = account.primary.as_ref() {
-                if primary.uuid == cred_id {
+                if primary.uuid == cred_id && policy.credential_kind_allowed(primary.type_.kind()) {
                     if let Ok(ch) = CredHandler::try_from((primary, webauthn)) {
                         // Update it.
                         debug_assert!(cred_handler.is_none());
@@ -870,7 +906,12 @@ impl AuthSession {
                 }
             }
 
-            if let Some(pk) = account.passkeys.get(&cred_id).map(|(_, pk)| pk) {
+            if let Some(pk) = account
+                .passkeys
+                .get(&cred_id)
+                .filter(|_| policy.credential_kind_allowed(CredentialKind::Webauthn))
+                .map(|(_, pk)| pk)
+            {
                 if let Ok(ch) = CredHandler::try_from((cred_id, pk, webauthn)) {
                     // Update it.
                     debug_assert!(cred_handler.is_none());
@@ -1028,6 +1069,7 @@ impl AuthSession {
                 ));
             }
             AuthSessionState::InProgress(ref mut handler) => {
+                let auth_type = handler.auth_type();
                 match handler.validate(
                     cred,
                     time,
@@ -1037,15 +1079,49 @@ impl AuthSession {
                     Some(account_policy.pw_badlist_cache()),
                 ) {
                     CredState::Success { auth_type, cred_id } => {
-                        // Issue the uat based on a set of factors.
+                        METRICS.auth_attempt(&auth_type.to_string(), true);
+                        // Issue the uat based on a set of factors. The account's policy can only
+                        // shorten the expiry that the server allows.
                         let uat = self.issue_uat(
                             &auth_type,
                             time,
                             async_tx,
                             cred_id,
-                            account_policy.authsession_expiry(),
-                            account_policy.privilege_expiry(),
+                            self.account
+                                .account_policy
+                                .authsession_expiry()
+                                .min(account_policy.authsession_expiry()),
+                            self.account
+                                .account_policy
+                                .privilege_expiry()
+                                .min(account_policy.privilege_expiry()),
                         )?;
+
+                        let event = match self.intent {
+                            AuthIntent::InitialAuth { privileged } => {
+                                AuditEvent::AuthenticationSuccess {
+                                    source: self.source.clone().into(),
+                                    spn: self.account.spn.clone(),
+                                    uuid: self.account.uuid,
+                                    privileged,
+                                    time: OffsetDateTime::UNIX_EPOCH + time,
+                                }
+                            }
+                            AuthIntent::Reauth { session_id, .. } => {
+                                AuditEvent::PrivilegeReauthentication {
+                                    source: self.source.clone().into(),
+                                    spn: self.account.spn.clone(),
+                                    uuid: self.account.uuid,
+                                    session_id,
+                                    time: OffsetDateTime::UNIX_EPOCH + time,
+                                }
+                            }
+                        };
+
+                        if audit_tx.send(event).is_err() {
+                            error!("Unable to submit audit event to queue");
+                        }
+
                         let jwt = Jws::new(uat);
 
                         // Now encrypt and prepare the token for return to the client.
@@ -1070,6 +1146,7 @@ impl AuthSession {
                         (None, Ok(AuthState::Continue(allowed.into_iter().collect())))
                     }
                     CredState::Denied(reason) => {
+                        METRICS.auth_attempt(&auth_type.to_string(), false);
                         if audit_tx
                             .send(AuditEvent::AuthenticationDenied {
                                 source: self.source.clone().into(),
@@ -1165,6 +1242,7 @@ impl AuthSession {
                     | AuthType::PasswordMfa
                     | AuthType::Passkey => {
                         trace!("⚠️   Queued AuthSessionRecord for {}", self.account.uuid);
+                        METRICS.delayed_action_queued();
                         async_tx.send(DelayedAction::AuthSessionRecord(AuthSessionRecord {
                             target_uuid: self.account.uuid,
                             session_id,
@@ -1219,6 +1297,9 @@ impl AuthSession {
 
     /// End the session, defaulting to a denied.
     pub fn end_session(&mut self, reason: &'static str) -> Result<AuthState, OperationError> {
+        if let AuthSessionState::InProgress(handler) = &self.state {
+            METRICS.auth_attempt(&handler.auth_type().to_string(), false);
+        }
         let mut next_state = AuthSessionState::Denied(reason);
         std::mem::swap(&mut self.state, &mut next_state);
         Ok(AuthState::Denied(reason.to_string()))
@@ -1423,6 +1504,11 @@ mod tests {
             _ => panic!(),
         };
 
+        match audit_rx.try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+            _ => assert!(false),
+        }
+
         match async_rx.blocking_recv() {
             Some(DelayedAction::AuthSessionRecord(_)) => {}
             _ => assert!(false),
@@ -1743,6 +1829,11 @@ mod tests {
                 _ => panic!(),
             };
 
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
+
             match async_rx.blocking_recv() {
                 Some(DelayedAction::AuthSessionRecord(_)) => {}
                 _ => assert!(false),
@@ -1994,6 +2085,11 @@ mod tests {
                 _ => panic!(),
             };
 
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
+
             // Check the async counter update was sent.
             match async_rx.blocking_recv() {
                 Some(DelayedAction::WebauthnCounterIncrement(_)) => {}
@@ -2280,6 +2376,11 @@ mod tests {
                 _ => panic!(),
             };
 
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
+
             // Check the async counter update was sent.
             match async_rx.blocking_recv() {
                 Some(DelayedAction::WebauthnCounterIncrement(_)) => {}
@@ -2526,6 +2627,11 @@ mod tests {
                 _ => panic!(),
             };
 
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
+
             match async_rx.blocking_recv() {
                 Some(DelayedAction::AuthSessionRecord(_)) => {}
                 _ => assert!(false),
@@ -2568,6 +2674,11 @@ mod tests {
                 _ => panic!(),
             };
 
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
+
             // Check the async counter update was sent.
             match async_rx.blocking_recv() {
                 Some(DelayedAction::WebauthnCounterIncrement(_)) => {}
@@ -2748,6 +2859,11 @@ mod tests {
                 Ok(AuthState::Success(_, AuthIssueSession::Token)) => {}
                 _ => panic!(),
             };
+
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
         }
         // Can't process BackupCodeRemoval without the server instance
         match async_rx.blocking_recv() {
@@ -2792,6 +2908,11 @@ mod tests {
                 Ok(AuthState::Success(_, AuthIssueSession::Token)) => {}
                 _ => panic!(),
             };
+
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
         }
 
         // There will be a auth session record too
@@ -2876,6 +2997,11 @@ mod tests {
                 _ => panic!(),
             };
 
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
+
             match async_rx.blocking_recv() {
                 Some(DelayedAction::AuthSessionRecord(_)) => {}
                 _ => assert!(false),
@@ -2912,6 +3038,11 @@ mod tests {
                 _ => panic!(),
             };
 
+            match audit_rx.try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+                _ => assert!(false),
+            }
+
             match async_rx.blocking_recv() {
                 Some(DelayedAction::AuthSessionRecord(_)) => {}
                 _ => assert!(false),
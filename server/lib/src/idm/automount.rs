@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use crate::idm::server::IdmServerProxyReadTransaction;
+use crate::idm::unixpolicy::{policy_name, UnixPolicy};
+use crate::prelude::*;
+use kanidm_proto::v1::{UnixAutomountEntry, UnixAutomountMap};
+
+/// A key of an automount map, which is grouped into its map before being sent to hosts.
+struct UnixAutomountKey {
+    map: Uuid,
+    entry: UnixAutomountEntry,
+}
+
+impl UnixPolicy for UnixAutomountKey {
+    fn from_entry(value: &Entry<EntryReduced, EntryCommitted>) -> Result<Self, OperationError> {
+        let map = value
+            .get_ava_single_refer(Attribute::AutomountMap)
+            .ok_or(OperationError::InvalidEntryState)?;
+
+        let key = value
+            .get_ava_single_utf8(Attribute::AutomountKey)
+            .map(str::to_string)
+            .ok_or(OperationError::InvalidEntryState)?;
+
+        let information = value
+            .get_ava_single_utf8(Attribute::AutomountInformation)
+            .map(str::to_string)
+            .ok_or(OperationError::InvalidEntryState)?;
+
+        Ok(UnixAutomountKey {
+            map,
+            entry: UnixAutomountEntry { key, information },
+        })
+    }
+
+    fn sort_key(&self) -> &str {
+        &self.entry.key
+    }
+}
+
+impl UnixPolicy for UnixAutomountMap {
+    fn from_entry(value: &Entry<EntryReduced, EntryCommitted>) -> Result<Self, OperationError> {
+        Ok(UnixAutomountMap {
+            name: policy_name(value)?,
+            uuid: value.get_uuid(),
+            entries: Vec::new(),
+        })
+    }
+
+    fn sort_key(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// List the automount maps and their entries that are visible to this identity. Hosts
+    /// cache these so that mounts work while offline.
+    pub fn list_unix_automount_maps(
+        &mut self,
+        ident: &Identity,
+    ) -> Result<Vec<UnixAutomountMap>, OperationError> {
+        let mut maps: Vec<UnixAutomountMap> = self.list_unix_policy(
+            filter!(f_eq(Attribute::Class, EntryClass::AutomountMap.into())),
+            ident,
+        )?;
+
+        // Keys are already sorted, so they remain sorted within each map.
+        let keys: Vec<UnixAutomountKey> = self.list_unix_policy(
+            filter!(f_eq(Attribute::Class, EntryClass::Automount.into())),
+            ident,
+        )?;
+
+        let mut map_entries: BTreeMap<Uuid, Vec<UnixAutomountEntry>> = BTreeMap::new();
+        for key in keys {
+            map_entries.entry(key.map).or_default().push(key.entry);
+        }
+
+        for map in maps.iter_mut() {
+            map.entries = map_entries.remove(&map.uuid).unwrap_or_default();
+        }
+
+        Ok(maps)
+    }
+}
@@ -17,8 +17,9 @@ use webauthn_rs::prelude::{
 };
 
 use crate::credential::totp::{Totp, TOTP_DEFAULT_STEP};
-use crate::credential::{BackupCodes, Credential};
+use crate::credential::{BackupCodes, Credential, CredentialKind};
 use crate::idm::account::Account;
+use crate::idm::audit::AuditEvent;
 use crate::idm::server::{IdmServerCredUpdateTransaction, IdmServerProxyWriteTransaction};
 use crate::prelude::*;
 use crate::server::access::Access;
@@ -87,7 +88,6 @@ pub(crate) struct CredentialUpdateSession {
     account: Account,
     // What intent was used to initiate this session.
     intent_token_id: Option<String>,
-    // Acc policy
 
     // Is there an extertal credential portal?
     ext_cred_portal: CUExtPortal,
@@ -133,30 +133,32 @@ impl CredentialUpdateSession {
     // In future this should be a Vec of the issues with the current session so that UI's can highlight
     // properly how to proceed.
     fn can_commit(&self) -> bool {
-        // Should be it's own PR and use account policy
+        let policy = &self.account.account_policy;
 
-        /*
-        // We'll check policy here in future.
         let is_primary_valid = match self.primary.as_ref() {
-            Some(Credential {
-                uuid: _,
-                type_: CredentialType::Password(_),
-            }) => {
-                // We refuse password-only auth now.
-                info!("Password only authentication.");
+            Some(cred) if !policy.credential_kind_allowed(cred.type_.kind()) => {
+                info!(
+                    credential_type = %cred.type_.kind(),
+                    "Primary credential type is not permitted by account policy."
+                );
                 false
             }
             // So far valid.
             _ => true,
         };
 
-        info!("can_commit -> {}", is_primary_valid);
+        let are_passkeys_valid = if !self.passkeys.is_empty()
+            && !policy.credential_kind_allowed(CredentialKind::Webauthn)
+        {
+            info!("Passkeys are not permitted by account policy.");
+            false
+        } else {
+            true
+        };
 
-        // For logic later.
-        is_primary_valid
-        */
+        info!("can_commit -> {}", is_primary_valid && are_passkeys_valid);
 
-        true
+        is_primary_valid && are_passkeys_valid
     }
 }
 
@@ -503,7 +505,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         // Stash the issuer for some UI elements
         let issuer = self.qs_write.get_domain_display_name().to_string();
 
-        // - store account policy (if present)
+        // Account policy is carried with the account.
         let session = CredentialUpdateSession {
             account,
             issuer,
@@ -994,7 +996,15 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                 .map_err(|e| {
                     request_error!(error = ?e);
                     e
-                })
+                })?;
+
+            self.qs_write
+                .audit_event_on_commit(AuditEvent::CredentialUpdate {
+                    uuid: session.account.uuid,
+                    spn: session.account.spn.clone(),
+                    time: OffsetDateTime::UNIX_EPOCH + ct,
+                });
+            Ok(())
         }
     }
 
@@ -1139,21 +1149,21 @@ impl<'a> IdmServerCredUpdateTransaction<'a> {
         &self,
         cleartext: &str,
         related_inputs: &[&str],
+        pw_min_length: usize,
     ) -> Result<(), PasswordQuality> {
         // password strength and badlisting is always global, rather than per-pw-policy.
-        // pw-policy as check on the account is about requirements for mfa for example.
-        //
+        // Account policy may only raise the minimum length.
 
-        // is the password at least 10 char?
-        if cleartext.len() < PW_MIN_LENGTH {
-            return Err(PasswordQuality::TooShort(PW_MIN_LENGTH));
+        // is the password at least the minimum length?
+        if cleartext.len() < pw_min_length {
+            return Err(PasswordQuality::TooShort(pw_min_length));
         }
 
         // does the password pass zxcvbn?
 
         let entropy = zxcvbn::zxcvbn(cleartext, related_inputs).map_err(|e| {
             admin_error!("zxcvbn check failure (password empty?) {:?}", e);
-            PasswordQuality::TooShort(PW_MIN_LENGTH)
+            PasswordQuality::TooShort(pw_min_length)
         })?;
 
         // PW's should always be enforced as strong as possible.
@@ -1167,7 +1177,7 @@ impl<'a> IdmServerCredUpdateTransaction<'a> {
                 .map(|v| v.clone())
                 .map_err(|e| {
                     security_info!("zxcvbn returned no feedback when score < 3 -> {:?}", e);
-                    PasswordQuality::TooShort(PW_MIN_LENGTH)
+                    PasswordQuality::TooShort(pw_min_length)
                 })?;
 
             security_info!(?feedback, "pw quality feedback");
@@ -1301,17 +1311,21 @@ impl<'a> IdmServerCredUpdateTransaction<'a> {
             return Err(OperationError::AccessDenied);
         };
 
-        // Check pw quality (future - acc policy applies).
-        self.check_password_quality(pw, session.account.related_inputs().as_slice())
-            .map_err(|e| match e {
-                PasswordQuality::TooShort(sz) => {
-                    OperationError::PasswordQuality(vec![PasswordFeedback::TooShort(sz)])
-                }
-                PasswordQuality::BadListed => {
-                    OperationError::PasswordQuality(vec![PasswordFeedback::BadListed])
-                }
-                PasswordQuality::Feedback(feedback) => OperationError::PasswordQuality(feedback),
-            })?;
+        // Check pw quality, applying the account policy.
+        self.check_password_quality(
+            pw,
+            session.account.related_inputs().as_slice(),
+            session.account.account_policy.password_minimum_length(),
+        )
+        .map_err(|e| match e {
+            PasswordQuality::TooShort(sz) => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::TooShort(sz)])
+            }
+            PasswordQuality::BadListed => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::BadListed])
+            }
+            PasswordQuality::Feedback(feedback) => OperationError::PasswordQuality(feedback),
+        })?;
 
         let ncred = match &session.primary {
             Some(primary) => {
@@ -1736,6 +1750,7 @@ mod tests {
 
     use kanidm_proto::v1::{
         AuthAllowed, AuthIssueSession, AuthMech, CUExtPortal, CredentialDetailType,
+        PasswordFeedback,
     };
     use uuid::uuid;
     use webauthn_authenticator_rs::softpasskey::SoftPasskey;
@@ -1747,7 +1762,9 @@ mod tests {
         MAXIMUM_INTENT_TTL, MINIMUM_INTENT_TTL,
     };
     use crate::credential::totp::Totp;
+    use crate::credential::CredentialKind;
     use crate::event::CreateEvent;
+    use crate::idm::audit::AuditEvent;
     use crate::idm::delayed::DelayedAction;
     use crate::idm::event::{AuthEvent, AuthResult};
     use crate::idm::server::{IdmServer, IdmServerDelayed};
@@ -1757,10 +1774,11 @@ mod tests {
     const TEST_CURRENT_TIME: u64 = 6000;
     const TESTPERSON_UUID: Uuid = uuid!("cf231fea-1a8f-4410-a520-fd9b1a379c86");
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_credential_update_session_init(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -1871,9 +1889,26 @@ mod tests {
         assert!(cur.is_err());
 
         // Success - this was the second use of the token and is valid.
-        let _ = idms_prox_write.commit_credential_update(&cust_b, ct);
+        assert!(idms_prox_write
+            .commit_credential_update(&cust_b, ct)
+            .is_ok());
 
         idms_prox_write.commit().expect("Failed to commit txn");
+        check_credential_update_audit(idms_audit);
+    }
+
+    fn check_credential_update_audit(idms_audit: &mut IdmServerAudit) {
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::CredentialUpdate { uuid, .. }) => assert_eq!(uuid, TESTPERSON_UUID),
+            _ => assert!(false),
+        }
+    }
+
+    fn check_auth_success_audit(idms_audit: &mut IdmServerAudit) {
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, TESTPERSON_UUID),
+            _ => assert!(false),
+        }
     }
 
     async fn setup_test_session(
@@ -2210,10 +2245,11 @@ mod tests {
         assert!(matches!(c_status, OperationError::InvalidState));
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_credential_update_onboarding_create_new_pw(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let test_pw = "fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki";
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
@@ -2243,11 +2279,13 @@ mod tests {
 
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         // Check it works!
         assert!(check_testperson_password(idms, idms_delayed, test_pw, ct)
             .await
             .is_some());
+        check_auth_success_audit(idms_audit);
 
         // Test deleting the pw
         let (cust, _) = renew_test_session(idms, ct).await;
@@ -2267,6 +2305,7 @@ mod tests {
 
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         // Must fail now!
         assert!(check_testperson_password(idms, idms_delayed, test_pw, ct)
@@ -2279,10 +2318,11 @@ mod tests {
     //    - set correctly.
 
     // - setup TOTP
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_credential_update_onboarding_create_new_mfa_totp_basic(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let test_pw = "fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki";
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
@@ -2340,6 +2380,7 @@ mod tests {
 
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         // Check it works!
         assert!(
@@ -2347,6 +2388,7 @@ mod tests {
                 .await
                 .is_some()
         );
+        check_auth_success_audit(idms_audit);
         // No need to test delete of the whole cred, we already did with pw above.
 
         // If we remove TOTP, show it reverts back.
@@ -2365,18 +2407,21 @@ mod tests {
 
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         // Check it works with totp removed.
         assert!(check_testperson_password(idms, idms_delayed, test_pw, ct)
             .await
             .is_some());
+        check_auth_success_audit(idms_audit);
     }
 
     // Check sha1 totp.
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_credential_update_onboarding_create_new_mfa_totp_sha1(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let test_pw = "fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki";
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
@@ -2437,6 +2482,7 @@ mod tests {
 
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         // Check it works!
         assert!(
@@ -2444,13 +2490,15 @@ mod tests {
                 .await
                 .is_some()
         );
+        check_auth_success_audit(idms_audit);
         // No need to test delete, we already did with pw above.
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_credential_update_onboarding_create_new_mfa_totp_backup_codes(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let test_pw = "fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki";
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
@@ -2516,6 +2564,7 @@ mod tests {
         // Should be okay now!
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         let backup_code = codes.iter().next().expect("No codes available");
 
@@ -2529,6 +2578,7 @@ mod tests {
         )
         .await
         .is_some());
+        check_auth_success_audit(idms_audit);
 
         // Renew to start the next steps
         let (cust, _) = renew_test_session(idms, ct).await;
@@ -2582,12 +2632,14 @@ mod tests {
 
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_credential_update_onboarding_cancel_inprogress_totp(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let test_pw = "fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki";
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
@@ -2624,11 +2676,13 @@ mod tests {
 
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         // It's pw only, since we canceled TOTP
         assert!(check_testperson_password(idms, idms_delayed, test_pw, ct)
             .await
             .is_some());
+        check_auth_success_audit(idms_audit);
     }
 
     // Primary cred must be pw or pwmfa
@@ -2637,10 +2691,11 @@ mod tests {
     // - remove webauthn
     // - test multiple webauthn token.
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_credential_update_onboarding_create_new_passkey(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
@@ -2691,6 +2746,7 @@ mod tests {
         // Commit
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         // Do an auth test
         assert!(
@@ -2698,6 +2754,7 @@ mod tests {
                 .await
                 .is_some()
         );
+        check_auth_success_audit(idms_audit);
 
         // Now test removing the token
         let (cust, _) = renew_test_session(idms, ct).await;
@@ -2717,6 +2774,7 @@ mod tests {
 
         drop(cutxn);
         commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
 
         // Must fail now!
         assert!(
@@ -2875,6 +2933,78 @@ mod tests {
         commit_session(idms, ct, cust).await;
     }
 
+    #[idm_test(audit)]
+    async fn test_idm_credential_update_account_policy(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let test_pw = "fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki";
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        // Without any policy a password only credential is fine.
+        let (cust, _) = setup_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await;
+        let c_status = cutxn
+            .credential_primary_set_password(&cust, ct, test_pw)
+            .expect("Failed to update the primary cred password");
+        assert!(c_status.can_commit);
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+        check_credential_update_audit(idms_audit);
+
+        assert!(check_testperson_password(idms, idms_delayed, test_pw, ct)
+            .await
+            .is_some());
+        check_auth_success_audit(idms_audit);
+
+        // Now require mfa and a long password for the account.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let e_policy = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::AccountPolicy.to_value()),
+            (Attribute::Name, Value::new_iname("mfa_required")),
+            (Attribute::Member, Value::Refer(TESTPERSON_UUID)),
+            (
+                Attribute::CredentialTypeAllowed,
+                Value::CredentialType(CredentialKind::PasswordMfa)
+            ),
+            (
+                Attribute::CredentialTypeAllowed,
+                Value::CredentialType(CredentialKind::Webauthn)
+            ),
+            (Attribute::AuthPasswordMinimumLength, Value::Uint32(80))
+        );
+        let ce = CreateEvent::new_internal(vec![e_policy]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+        idms_prox_write.commit().expect("Failed to commit txn");
+
+        // The password alone is no longer allowed to authenticate.
+        assert!(check_testperson_password(idms, idms_delayed, test_pw, ct)
+            .await
+            .is_none());
+
+        // And it can't be committed by a credential update.
+        let (cust, c_status) = renew_test_session(idms, ct).await;
+        assert!(!c_status.can_commit);
+
+        let cutxn = idms.cred_update_transaction().await;
+        let err = cutxn.credential_primary_set_password(&cust, ct, test_pw);
+        assert!(matches!(
+            err,
+            Err(OperationError::PasswordQuality(ref feedback))
+                if matches!(feedback.as_slice(), [PasswordFeedback::TooShort(80)])
+        ));
+        drop(cutxn);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(matches!(
+            idms_prox_write.commit_credential_update(&cust, ct),
+            Err(OperationError::InvalidState)
+        ));
+    }
+
     // W_ policy, assert can't remove MFA if it's enforced.
 
     // enroll trusted device
@@ -1,7 +1,9 @@
 use crate::idm::AuthState;
 use crate::prelude::*;
 use kanidm_proto::v1::OperationError;
-use kanidm_proto::v1::{AuthCredential, AuthIssueSession, AuthMech, AuthRequest, AuthStep};
+use kanidm_proto::v1::{
+    AuthCredential, AuthIssueSession, AuthMech, AuthRequest, AuthStep, UnixUserToken,
+};
 
 #[cfg(test)]
 use std::sync::Arc;
@@ -56,6 +58,22 @@ impl UnixPasswordChangeEvent {
     }
 }
 
+/// An account changing its own unix password. This can only be created from the token that
+/// `auth_unix` returns, so the current password must have been verified first.
+pub struct UnixPasswordSelfChangeEvent {
+    pub target: Uuid,
+    pub cleartext: String,
+}
+
+impl UnixPasswordSelfChangeEvent {
+    pub fn from_unix_auth(token: &UnixUserToken, cleartext: String) -> Self {
+        UnixPasswordSelfChangeEvent {
+            target: token.uuid,
+            cleartext,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GeneratePasswordEvent {
     pub ident: Identity,
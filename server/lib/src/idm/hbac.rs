@@ -0,0 +1,29 @@
+use crate::idm::server::IdmServerProxyReadTransaction;
+use crate::idm::unixpolicy::{policy_iutf8s, policy_name, policy_refers, UnixPolicy};
+use crate::prelude::*;
+use kanidm_proto::v1::UnixHbacRule;
+
+impl UnixPolicy for UnixHbacRule {
+    fn from_entry(value: &Entry<EntryReduced, EntryCommitted>) -> Result<Self, OperationError> {
+        Ok(UnixHbacRule {
+            name: policy_name(value)?,
+            uuid: value.get_uuid(),
+            users: policy_refers(value, Attribute::HbacUser),
+            services: policy_iutf8s(value, Attribute::HbacService),
+        })
+    }
+
+    fn sort_key(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// List the host access rules that apply on the host making this request.
+    pub fn list_unix_hbac_rules(
+        &mut self,
+        ident: &Identity,
+    ) -> Result<Vec<UnixHbacRule>, OperationError> {
+        self.list_unix_host_rules(ident, EntryClass::HbacRule, Attribute::HbacHost)
+    }
+}
@@ -1,19 +1,35 @@
 //! LDAP specific operations handling components. This is where LDAP operations
 //! are sent to for processing.
 
-use std::collections::BTreeSet;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::iter;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use kanidm_proto::constants::*;
-use kanidm_proto::v1::{ApiToken, OperationError, UserAuthToken};
+use kanidm_proto::v1::{
+    ApiToken, CreateRequest, Entry as ProtoEntry, OperationError, UserAuthToken,
+};
+use ldap3_proto::proto::{
+    LdapAddRequest, LdapControl, LdapExtendedResponse, LdapModifyDNRequest, LdapModifyRequest,
+    LdapModifyType, LdapPasswordModifyRequest, LdapResult, LdapServerSideSortKey,
+};
 use ldap3_proto::simple::*;
 use regex::Regex;
+use time::OffsetDateTime;
+use tokio::sync::mpsc::UnboundedSender as Sender;
 use tracing::trace;
 use uuid::Uuid;
 
 use crate::event::SearchEvent;
-use crate::idm::event::{LdapAuthEvent, LdapTokenAuthEvent};
+use crate::idm::audit::AuditEvent;
+use crate::idm::event::{
+    LdapAuthEvent, LdapTokenAuthEvent, UnixPasswordChangeEvent, UnixPasswordSelfChangeEvent,
+    UnixUserAuthEvent,
+};
 use crate::idm::server::{IdmServer, IdmServerTransaction};
+use crate::metrics::METRICS;
 use crate::prelude::*;
 
 // Clippy doesn't like Bind here. But proto needs unboxed ldapmsg,
@@ -49,6 +65,249 @@ pub struct LdapBoundToken {
     // In a way, this is a stepping stone to an "ident" but allows us to check
     // the session is still "valid" depending on it's origin.
     pub effective_session: LdapSession,
+    // Paged searches that are in progress on this connection.
+    pub paged_search: LdapPagedSearchState,
+}
+
+// The maximum number of paged searches a single connection may have in progress.
+const LDAP_PAGED_SEARCH_MAX: usize = 8;
+// How long a paged search remains valid between requests for the next page.
+const LDAP_PAGED_SEARCH_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+struct LdapPagedSearch {
+    // The search this cookie belongs to. RFC 2696 requires the client to repeat the
+    // same request with the cookie to get the next page.
+    base: String,
+    scope: LdapSearchScope,
+    filter: LdapFilter,
+    attrs: Vec<String>,
+    // The sort keys of the search, and the sort result that is returned with each page.
+    sort: Vec<LdapServerSideSortKey>,
+    sort_result: Option<LdapControl>,
+    // The entries yet to be sent, in the order they will be returned.
+    remaining: VecDeque<Uuid>,
+    expiry: Duration,
+}
+
+impl LdapPagedSearch {
+    fn is_request(&self, sr: &SearchRequest, sort: &[LdapServerSideSortKey]) -> bool {
+        self.base == sr.base
+            && self.scope == sr.scope
+            && self.filter == sr.filter
+            && self.attrs == sr.attrs
+            && self.sort.len() == sort.len()
+            && self.sort.iter().zip(sort.iter()).all(|(a, b)| {
+                a.attribute_type.eq_ignore_ascii_case(&b.attribute_type)
+                    && a.ordering_rule == b.ordering_rule
+                    && a.reverse_order == b.reverse_order
+            })
+    }
+}
+
+/// A page of a paged search, and the state needed to continue it.
+struct LdapPage {
+    entries: Vec<Uuid>,
+    sort_result: Option<LdapControl>,
+    // The cookie and count of remaining entries, if the search is not exhausted.
+    next: Option<(Vec<u8>, usize)>,
+}
+
+/// The state of the paged searches in progress for a bound session. This is shared
+/// between clones of the [LdapBoundToken] so that it persists between the operations
+/// of a connection, and is discarded with the connection.
+#[derive(Debug, Clone, Default)]
+pub struct LdapPagedSearchState {
+    inner: Arc<Mutex<BTreeMap<Uuid, LdapPagedSearch>>>,
+}
+
+impl LdapPagedSearchState {
+    fn insert(&self, search: LdapPagedSearch, ct: Duration) -> Result<Vec<u8>, OperationError> {
+        let mut guard = self.inner.lock().map_err(|err| {
+            error!(?err, "Unable to access paged search state");
+            OperationError::InvalidState
+        })?;
+        guard.retain(|_, s| s.expiry > ct);
+        // Uuid v4 is random, so evict the search closest to expiry rather than the
+        // first key when the connection has too many in progress.
+        while guard.len() >= LDAP_PAGED_SEARCH_MAX {
+            let oldest = guard
+                .iter()
+                .min_by_key(|(_, s)| s.expiry)
+                .map(|(cookie, _)| *cookie);
+            match oldest {
+                Some(cookie) => {
+                    guard.remove(&cookie);
+                }
+                None => break,
+            }
+        }
+
+        let cookie = Uuid::new_v4();
+        guard.insert(cookie, search);
+        Ok(cookie.as_bytes().to_vec())
+    }
+
+    /// Take the next page of entry uuids from a paged search. The search is removed once
+    /// it is exhausted, or if the client requested a size of 0 which abandons it.
+    fn next_page(
+        &self,
+        sr: &SearchRequest,
+        sort: &[LdapServerSideSortKey],
+        cookie: &[u8],
+        size: usize,
+        ct: Duration,
+    ) -> Result<LdapPage, OperationError> {
+        let cookie = Uuid::from_slice(cookie).map_err(|_| {
+            request_error!("LDAP Search failure - invalid paged results cookie");
+            OperationError::InvalidRequestState
+        })?;
+
+        let mut guard = self.inner.lock().map_err(|err| {
+            error!(?err, "Unable to access paged search state");
+            OperationError::InvalidState
+        })?;
+
+        let valid = guard
+            .get(&cookie)
+            .map(|search| search.expiry > ct && search.is_request(sr, sort))
+            .unwrap_or(false);
+
+        if !valid {
+            request_error!(
+                "LDAP Search failure - paged results cookie is not valid for this request"
+            );
+            guard.remove(&cookie);
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let search = guard
+            .get_mut(&cookie)
+            .ok_or(OperationError::InvalidRequestState)?;
+
+        let take = size.min(search.remaining.len());
+        let entries: Vec<_> = search.remaining.drain(..take).collect();
+        let sort_result = search.sort_result.clone();
+        search.expiry = ct + LDAP_PAGED_SEARCH_TIMEOUT;
+
+        let next = if size == 0 || search.remaining.is_empty() {
+            guard.remove(&cookie);
+            None
+        } else {
+            Some((cookie.as_bytes().to_vec(), search.remaining.len()))
+        };
+
+        Ok(LdapPage {
+            entries,
+            sort_result,
+            next,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct LdapSearchControls {
+    // Simple paged results, RFC 2696 - the page size and cookie.
+    paged: Option<(usize, Vec<u8>)>,
+    // Server side sorting, RFC 2891.
+    sort: Vec<LdapServerSideSortKey>,
+}
+
+impl From<&[LdapControl]> for LdapSearchControls {
+    fn from(ctrls: &[LdapControl]) -> Self {
+        let mut controls = LdapSearchControls::default();
+        for ctrl in ctrls {
+            match ctrl {
+                LdapControl::SimplePagedResults { size, cookie } => {
+                    let size = usize::try_from(*size).unwrap_or(0);
+                    controls.paged = Some((size, cookie.clone()));
+                }
+                LdapControl::ServerSideSortRequest { keys } => {
+                    controls.sort = keys.clone();
+                }
+                _ => {
+                    trace!(?ctrl, "Ignoring unsupported search control");
+                }
+            }
+        }
+        controls
+    }
+}
+
+// The password modify extended operation, RFC 3062.
+const LDAP_PASSWORD_MODIFY_OID: &str = "1.3.6.1.4.1.4203.1.11.1";
+
+/// Operations that change the content of the directory. These are decoded separately
+/// to [ServerOps] which only contains the read operations.
+#[derive(Debug)]
+pub enum LdapWriteOp {
+    Add(LdapAddRequest),
+    Modify(LdapModifyRequest),
+    Delete(String),
+    ModifyDn(LdapModifyDNRequest),
+    PasswordModify(LdapPasswordModifyRequest),
+}
+
+#[derive(Debug)]
+pub struct LdapWriteRequest {
+    pub msgid: i32,
+    pub op: LdapWriteOp,
+}
+
+impl LdapWriteRequest {
+    fn gen_result(&self, code: LdapResultCode, message: String) -> LdapMsg {
+        let res = LdapResult {
+            code,
+            matcheddn: "".to_string(),
+            message,
+            referral: vec![],
+        };
+        let op = match self.op {
+            LdapWriteOp::Add(_) => LdapOp::AddResponse(res),
+            LdapWriteOp::Modify(_) => LdapOp::ModifyResponse(res),
+            LdapWriteOp::Delete(_) => LdapOp::DelResponse(res),
+            LdapWriteOp::ModifyDn(_) => LdapOp::ModifyDNResponse(res),
+            LdapWriteOp::PasswordModify(_) => LdapOp::ExtendedResponse(LdapExtendedResponse {
+                res,
+                name: None,
+                value: None,
+            }),
+        };
+        LdapMsg {
+            msgid: self.msgid,
+            op,
+            ctrl: vec![],
+        }
+    }
+}
+
+impl TryFrom<LdapMsg> for LdapWriteRequest {
+    // If the message is not a write, it is returned so it can be processed as a read.
+    type Error = LdapMsg;
+
+    fn try_from(msg: LdapMsg) -> Result<Self, Self::Error> {
+        let LdapMsg { msgid, op, ctrl } = msg;
+        let op = match op {
+            LdapOp::AddRequest(ar) => LdapWriteOp::Add(ar),
+            LdapOp::ModifyRequest(mr) => LdapWriteOp::Modify(mr),
+            LdapOp::DelRequest(dn) => LdapWriteOp::Delete(dn),
+            LdapOp::ModifyDNRequest(mdr) => LdapWriteOp::ModifyDn(mdr),
+            LdapOp::ExtendedRequest(ler) if ler.name == LDAP_PASSWORD_MODIFY_OID => {
+                match LdapPasswordModifyRequest::try_from(&ler) {
+                    Ok(pmr) => LdapWriteOp::PasswordModify(pmr),
+                    Err(_) => {
+                        return Err(LdapMsg {
+                            msgid,
+                            op: LdapOp::ExtendedRequest(ler),
+                            ctrl,
+                        })
+                    }
+                }
+            }
+            op => return Err(LdapMsg { msgid, op, ctrl }),
+        };
+        Ok(LdapWriteRequest { msgid, op })
+    }
 }
 
 pub struct LdapServer {
@@ -105,7 +364,21 @@ impl LdapServer {
                 },
                 LdapPartialAttribute {
                     atype: "supportedextension".to_string(),
-                    vals: vec!["1.3.6.1.4.1.4203.1.11.3".as_bytes().to_vec()],
+                    vals: vec![
+                        // Who am I
+                        "1.3.6.1.4.1.4203.1.11.3".as_bytes().to_vec(),
+                        // Password modify
+                        LDAP_PASSWORD_MODIFY_OID.as_bytes().to_vec(),
+                    ],
+                },
+                LdapPartialAttribute {
+                    atype: "supportedcontrol".to_string(),
+                    vals: vec![
+                        // Simple paged results
+                        "1.2.840.113556.1.4.319".as_bytes().to_vec(),
+                        // Server side sort
+                        "1.2.840.113556.1.4.473".as_bytes().to_vec(),
+                    ],
                 },
                 LdapPartialAttribute {
                     atype: "supportedfeatures".to_string(),
@@ -126,12 +399,24 @@ impl LdapServer {
         })
     }
 
-    #[instrument(level = "debug", skip_all)]
+    #[cfg(test)]
     async fn do_search(
         &self,
         idms: &IdmServer,
         sr: &SearchRequest,
         uat: &LdapBoundToken,
+    ) -> Result<Vec<LdapMsg>, OperationError> {
+        self.do_search_ctrl(idms, sr, uat, &LdapSearchControls::default())
+            .await
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn do_search_ctrl(
+        &self,
+        idms: &IdmServer,
+        sr: &SearchRequest,
+        uat: &LdapBoundToken,
+        controls: &LdapSearchControls,
         // eventid: &Uuid,
     ) -> Result<Vec<LdapMsg>, OperationError> {
         admin_info!("Attempt LDAP Search for {}", uat.spn);
@@ -146,6 +431,15 @@ impl LdapServer {
             // We want something else apparently. Need to do some more work ...
             // Parse the operation and make sure it's sane before we start the txn.
 
+            // A size of 0 abandons a paged search, so it's only valid with a cookie.
+            if matches!(&controls.paged, Some((0, cookie)) if cookie.is_empty()) {
+                request_error!("LDAP Search failure - paged results size of 0 without a cookie");
+                return Ok(vec![sr.gen_error(
+                    LdapResultCode::UnwillingToPerform,
+                    "Paged results size must be greater than 0".to_string(),
+                )]);
+            }
+
             // This scoping returns an extra filter component.
 
             let (opt_attr, opt_value) = match self.dnre.captures(sr.base.as_str()) {
@@ -176,6 +470,15 @@ impl LdapServer {
                 // OneLevel and Child searches are veerrrryyy similar for us because child
                 // is a "subtree search excluding base". Because we don't have a tree structure at
                 // all, this is the same as a onelevel (ald children of base excludeing base).
+                //
+                // The only exception is automount keys, which are presented as children of their
+                // map since this is how autofs finds the entries of a map.
+                (LdapSearchScope::Children, Some((a, v)))
+                | (LdapSearchScope::OneLevel, Some((a, v)))
+                    if a.eq_ignore_ascii_case(ATTR_LDAP_AUTOMOUNT_MAP_NAME) =>
+                {
+                    Some(LdapFilter::Equality(Attribute::AutomountMap.to_string(), v))
+                }
                 (LdapSearchScope::Children, Some(_r)) | (LdapSearchScope::OneLevel, Some(_r)) => {
                     return Ok(vec![sr.gen_success()])
                 }
@@ -186,10 +489,12 @@ impl LdapServer {
                         STR_UUID_DOMAIN_INFO.to_string(),
                     ))))
                 }
-                // because we request a specific DN, these are the same since we want the same
-                // entry.
-                (LdapSearchScope::Base, Some((a, v)))
-                | (LdapSearchScope::Subtree, Some((a, v))) => Some(LdapFilter::Equality(a, v)),
+                (LdapSearchScope::Base, Some((a, v))) => Some(LdapFilter::Equality(a, v)),
+                // We want the entry itself, and any automount keys if this is a map.
+                (LdapSearchScope::Subtree, Some((a, v))) => Some(LdapFilter::Or(vec![
+                    LdapFilter::Equality(Attribute::AutomountMap.to_string(), v.clone()),
+                    LdapFilter::Equality(a, v),
+                ])),
                 (LdapSearchScope::Base, None) => {
                     // domain_info
                     Some(LdapFilter::Equality(
@@ -288,32 +593,9 @@ impl LdapServer {
                 Some(ext) => LdapFilter::And(vec![
                     sr.filter.clone(),
                     ext,
-                    LdapFilter::Not(Box::new(LdapFilter::Or(vec![
-                        LdapFilter::Equality(Attribute::Class.to_string(), "classtype".to_string()),
-                        LdapFilter::Equality(
-                            Attribute::Class.to_string(),
-                            "attributetype".to_string(),
-                        ),
-                        LdapFilter::Equality(
-                            Attribute::Class.to_string(),
-                            "access_control_profile".to_string(),
-                        ),
-                    ]))),
-                ]),
-                None => LdapFilter::And(vec![
-                    sr.filter.clone(),
-                    LdapFilter::Not(Box::new(LdapFilter::Or(vec![
-                        LdapFilter::Equality(Attribute::Class.to_string(), "classtype".to_string()),
-                        LdapFilter::Equality(
-                            Attribute::Class.to_string(),
-                            "attributetype".to_string(),
-                        ),
-                        LdapFilter::Equality(
-                            Attribute::Class.to_string(),
-                            "access_control_profile".to_string(),
-                        ),
-                    ]))),
+                    ldap_exclude_internal_classes(),
                 ]),
+                None => LdapFilter::And(vec![sr.filter.clone(), ldap_exclude_internal_classes()]),
             };
 
             admin_info!(filter = ?lfilter, "LDAP Search Filter");
@@ -321,12 +603,114 @@ impl LdapServer {
             // Build the event, with the permissions from effective_session
             //
             // ! Remember, searchEvent wraps to ignore hidden for us.
-            let ident = idm_read
+            let mut ident = idm_read
                 .validate_ldap_session(&uat.effective_session, ct)
                 .map_err(|e| {
                     admin_error!("Invalid identity: {:?}", e);
                     e
                 })?;
+
+            // The search result limit applies to each page rather than the search as a whole,
+            // so that large directories can be enumerated without raising the limits.
+            let page_size = controls
+                .paged
+                .as_ref()
+                .map(|(size, _)| (*size).min(ident.limits.search_max_results));
+
+            if let Some((_, cookie)) = controls.paged.as_ref().filter(|(_, c)| !c.is_empty()) {
+                let size = page_size.unwrap_or_default();
+                let LdapPage {
+                    entries: page,
+                    sort_result,
+                    next,
+                } = uat
+                    .paged_search
+                    .next_page(sr, &controls.sort, cookie, size, ct)?;
+
+                let mut res = if page.is_empty() {
+                    Vec::with_capacity(0)
+                } else {
+                    // Re-apply the filter so that access controls and changes since the
+                    // previous page are respected.
+                    let page_filter = LdapFilter::And(vec![
+                        lfilter,
+                        LdapFilter::Or(
+                            page.iter()
+                                .map(|u| {
+                                    LdapFilter::Equality(
+                                        Attribute::Uuid.to_string(),
+                                        u.as_hyphenated().to_string(),
+                                    )
+                                })
+                                .collect(),
+                        ),
+                    ]);
+
+                    // The page filter adds an element for each entry in the page.
+                    ident.limits.filter_max_elements = ident
+                        .limits
+                        .filter_max_elements
+                        .saturating_add(page.len() + 1);
+
+                    let se = SearchEvent::new_ext_impersonate_uuid(
+                        &mut idm_read.qs_read,
+                        ident,
+                        &page_filter,
+                        k_attrs,
+                    )
+                    .map_err(|e| {
+                        admin_error!("failed to create search event -> {:?}", e);
+                        e
+                    })?;
+
+                    idm_read.qs_read.search_ext(&se).map_err(|e| {
+                        admin_error!("search failure {:?}", e);
+                        e
+                    })?
+                };
+
+                // Return the page in the order established by the first request.
+                let order: BTreeMap<_, _> = page.iter().enumerate().map(|(i, u)| (*u, i)).collect();
+                res.sort_by_key(|e| order.get(&e.get_uuid()).copied());
+
+                let mut done = sr.gen_success();
+                // The entries remain in the order of the original sort, so each page
+                // reports the same result.
+                done.ctrl.extend(sort_result);
+                let (cookie, remaining) = next.unwrap_or_default();
+                done.ctrl.push(LdapControl::SimplePagedResults {
+                    size: i32::try_from(remaining).unwrap_or(i32::MAX),
+                    cookie,
+                });
+
+                return self.to_ldap_result(
+                    &mut idm_read.qs_read,
+                    sr,
+                    res,
+                    all_attrs,
+                    &l_attrs,
+                    done,
+                );
+            }
+
+            if page_size.is_some() {
+                ident.limits.search_max_results = usize::MAX;
+            }
+
+            // Sort keys must be read from the entries, so they are added to the set of
+            // requested attributes. Only the attributes the client asked for are returned
+            // by to_ldap.
+            let sort_keys: Vec<(AttrString, bool)> = controls
+                .sort
+                .iter()
+                .map(|k| (ldap_attr_filter_map(&k.attribute_type), k.reverse_order))
+                .collect();
+
+            let k_attrs = k_attrs.map(|mut k_attrs| {
+                k_attrs.extend(sort_keys.iter().map(|(a, _)| a.clone()));
+                k_attrs
+            });
+
             let se = SearchEvent::new_ext_impersonate_uuid(
                 &mut idm_read.qs_read,
                 ident,
@@ -338,125 +722,720 @@ impl LdapServer {
                 e
             })?;
 
-            let res = idm_read.qs_read.search_ext(&se).map_err(|e| {
+            let mut res = idm_read.qs_read.search_ext(&se).map_err(|e| {
                 admin_error!("search failure {:?}", e);
                 e
             })?;
 
-            // These have already been fully reduced (access controls applied),
-            // so we can just transform the values and open palm slam them into
-            // the result structure.
-            let lres: Result<Vec<_>, _> = res
-                .into_iter()
-                .map(|e| {
-                    e.to_ldap(
-                        &mut idm_read.qs_read,
-                        self.basedn.as_str(),
-                        all_attrs,
-                        &l_attrs,
-                    )
+            let mut done = sr.gen_success();
+            let mut sort_result = None;
+
+            if !controls.sort.is_empty() {
+                let schema_attributes = idm_read.qs_read.get_schema().get_attributes();
+                let result = if let Some(key) = controls.sort.iter().find(|k| {
+                    !schema_attributes
+                        .contains_key(ldap_attr_filter_map(&k.attribute_type).as_str())
+                }) {
+                    // We can't sort on an attribute we don't know about.
+                    LdapControl::ServerSideSortResult {
+                        result: LdapResultCode::NoSuchAttribute,
+                        attribute_type: Some(key.attribute_type.clone()),
+                    }
+                } else if let Some(key) = controls.sort.iter().find(|k| k.ordering_rule.is_some()) {
+                    // Only the default ordering of each syntax is supported.
+                    LdapControl::ServerSideSortResult {
+                        result: LdapResultCode::InappropriateMatching,
+                        attribute_type: Some(key.attribute_type.clone()),
+                    }
+                } else {
+                    ldap_sort_entries(&mut res, &sort_keys);
+                    LdapControl::ServerSideSortResult {
+                        result: LdapResultCode::Success,
+                        attribute_type: None,
+                    }
+                };
+                done.ctrl.push(result.clone());
+                sort_result = Some(result);
+            }
+
+            if let Some(size) = page_size {
+                let remaining: VecDeque<_> = if res.len() > size {
+                    res.split_off(size)
+                        .into_iter()
+                        .map(|e| e.get_uuid())
+                        .collect()
+                } else {
+                    VecDeque::new()
+                };
+
+                let remaining_len = remaining.len();
+                let cookie = if remaining.is_empty() {
+                    Vec::with_capacity(0)
+                } else {
+                    uat.paged_search.insert(
+                        LdapPagedSearch {
+                            base: sr.base.clone(),
+                            scope: sr.scope.clone(),
+                            filter: sr.filter.clone(),
+                            attrs: sr.attrs.clone(),
+                            sort: controls.sort.clone(),
+                            sort_result,
+                            remaining,
+                            expiry: ct + LDAP_PAGED_SEARCH_TIMEOUT,
+                        },
+                        ct,
+                    )?
+                };
+
+                done.ctrl.push(LdapControl::SimplePagedResults {
+                    size: i32::try_from(remaining_len).unwrap_or(i32::MAX),
+                    cookie,
+                });
+            }
+
+            self.to_ldap_result(&mut idm_read.qs_read, sr, res, all_attrs, &l_attrs, done)
+        }
+    }
+
+    fn to_ldap_result(
+        &self,
+        qs: &mut QueryServerReadTransaction,
+        sr: &SearchRequest,
+        res: Vec<EntryReducedCommitted>,
+        all_attrs: bool,
+        l_attrs: &[String],
+        done: LdapMsg,
+    ) -> Result<Vec<LdapMsg>, OperationError> {
+        // These have already been fully reduced (access controls applied),
+        // so we can just transform the values and open palm slam them into
+        // the result structure.
+        let lres: Result<Vec<_>, _> = res
+            .into_iter()
+            .map(|e| {
+                e.to_ldap(qs, self.basedn.as_str(), all_attrs, l_attrs)
                     // if okay, wrap in a ldap msg.
                     .map(|r| sr.gen_result_entry(r))
-                })
-                .chain(iter::once(Ok(sr.gen_success())))
-                .collect();
+            })
+            .chain(iter::once(Ok(done)))
+            .collect();
+
+        let lres = lres.map_err(|e| {
+            admin_error!("entry resolve failure {:?}", e);
+            e
+        })?;
 
-            let lres = lres.map_err(|e| {
-                admin_error!("entry resolve failure {:?}", e);
+        admin_info!(
+            nentries = %lres.len(),
+            "LDAP Search Success -> number of entries"
+        );
+
+        Ok(lres)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn do_compare(
+        &self,
+        idms: &IdmServer,
+        cr: &CompareRequest,
+        uat: &LdapBoundToken,
+    ) -> Result<LdapMsg, OperationError> {
+        admin_info!("Attempt LDAP Compare for {}", uat.spn);
+
+        // Compare always targets exactly one entry, so the dn is resolved the same
+        // way as a base scoped search.
+        let (opt_attr, opt_value) = match self.dnre.captures(cr.dn.as_str()) {
+            Some(caps) => (
+                caps.name("attr").map(|v| v.as_str().to_string()),
+                caps.name("val").map(|v| v.as_str().to_string()),
+            ),
+            None => {
+                request_error!("LDAP Compare failure - invalid dn");
+                return Ok(cr.gen_error(LdapResultCode::NoSuchObject, "".to_string()));
+            }
+        };
+
+        let dn_filter = match (opt_attr, opt_value) {
+            (Some(a), Some(v)) => LdapFilter::Equality(a, v),
+            (None, None) => LdapFilter::Equality(
+                Attribute::Uuid.to_string(),
+                STR_UUID_DOMAIN_INFO.to_string(),
+            ),
+            _ => {
+                request_error!("LDAP Compare failure - invalid rdn");
+                return Err(OperationError::InvalidRequestState);
+            }
+        };
+
+        let l_attr = cr.atype.to_lowercase();
+        let k_attrs: BTreeSet<_> = iter::once(ldap_attr_filter_map(&l_attr)).collect();
+
+        let ct = duration_from_epoch_now();
+        let mut idm_read = idms.proxy_read().await;
+
+        let ident = idm_read
+            .validate_ldap_session(&uat.effective_session, ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
                 e
             })?;
 
-            admin_info!(
-                nentries = %lres.len(),
-                "LDAP Search Success -> number of entries"
-            );
+        // First, find the entry as the bound identity can see it. This applies the same
+        // search access controls and attribute reduction as a search would.
+        let se = SearchEvent::new_ext_impersonate_uuid(
+            &mut idm_read.qs_read,
+            ident.clone(),
+            &LdapFilter::And(vec![dn_filter.clone(), ldap_exclude_internal_classes()]),
+            Some(k_attrs),
+        )
+        .map_err(|e| {
+            admin_error!("failed to create search event -> {:?}", e);
+            e
+        })?;
+
+        let mut res = idm_read.qs_read.search_ext(&se).map_err(|e| {
+            admin_error!("search failure {:?}", e);
+            e
+        })?;
+
+        let entry = match res.pop() {
+            Some(e) if res.is_empty() => e,
+            Some(_) => {
+                admin_error!("LDAP Compare failure - dn matched multiple entries");
+                return Err(OperationError::InvalidRequestState);
+            }
+            None => {
+                admin_info!("LDAP Compare -> NoSuchObject");
+                return Ok(cr.gen_error(LdapResultCode::NoSuchObject, "".to_string()));
+            }
+        };
+
+        // Render the attribute in its ldap form so that virtual attributes such as
+        // memberof or entrydn are resolved exactly as a search would return them.
+        let lentry = entry.to_ldap(
+            &mut idm_read.qs_read,
+            self.basedn.as_str(),
+            false,
+            &[l_attr.clone()],
+        )?;
+
+        let Some(lattr) = lentry
+            .attributes
+            .iter()
+            .find(|a| a.atype == l_attr && !a.vals.is_empty())
+        else {
+            admin_info!("LDAP Compare -> NoSuchAttribute");
+            return Ok(cr.gen_error(LdapResultCode::NoSuchAttribute, "".to_string()));
+        };
+
+        if lattr.vals.iter().any(|v| v.as_slice() == cr.val.as_bytes()) {
+            admin_info!("LDAP Compare -> True");
+            return Ok(cr.gen_compare_true());
+        }
+
+        // The value may still match once normalised by the attribute's syntax, for
+        // example a member dn in a different form, or a case insensitive string. Attributes
+        // that only exist in ldap (such as entrydn) have no syntax, and so can't match.
+        if idm_read
+            .qs_read
+            .get_schema()
+            .get_attributes()
+            .contains_key(ldap_attr_filter_map(&l_attr).as_str())
+        {
+            let se = SearchEvent::new_ext_impersonate_uuid(
+                &mut idm_read.qs_read,
+                ident,
+                &LdapFilter::And(vec![
+                    dn_filter,
+                    LdapFilter::Equality(cr.atype.clone(), cr.val.clone()),
+                    ldap_exclude_internal_classes(),
+                ]),
+                None,
+            )
+            .map_err(|e| {
+                admin_error!("failed to create search event -> {:?}", e);
+                e
+            })?;
 
-            Ok(lres)
+            let res = idm_read.qs_read.search(&se).map_err(|e| {
+                admin_error!("search failure {:?}", e);
+                e
+            })?;
+
+            if !res.is_empty() {
+                admin_info!("LDAP Compare -> True");
+                return Ok(cr.gen_compare_true());
+            }
         }
+
+        admin_info!("LDAP Compare -> False");
+        Ok(cr.gen_compare_false())
     }
 
-    async fn do_bind(
+    /// Resolve the entry that a write targets. The directory is flat, so the value of the
+    /// rdn alone identifies the entry, and the base dn itself is the domain entry.
+    fn resolve_write_target(
         &self,
-        idms: &IdmServer,
+        qs: &mut QueryServerWriteTransaction,
         dn: &str,
-        pw: &str,
-    ) -> Result<Option<LdapBoundToken>, OperationError> {
-        security_info!(
-            "Attempt LDAP Bind for {}",
-            if dn.is_empty() { "anonymous" } else { dn }
-        );
-        let ct = duration_from_epoch_now();
+    ) -> Result<Uuid, OperationError> {
+        let caps = self.dnre.captures(dn).ok_or_else(|| {
+            request_error!(%dn, "LDAP Write failure - invalid dn");
+            OperationError::NoMatchingEntries
+        })?;
+
+        match caps.name("val") {
+            Some(val) => qs.name_to_uuid(val.as_str()).map_err(|e| {
+                request_error!(err = ?e, %dn, "Error resolving dn to target");
+                e
+            }),
+            None => Ok(UUID_DOMAIN_INFO),
+        }
+    }
 
-        let mut idm_auth = idms.auth().await;
+    #[instrument(level = "debug", skip_all)]
+    async fn do_add(
+        &self,
+        idms: &IdmServer,
+        ar: &LdapAddRequest,
+        uat: &LdapBoundToken,
+    ) -> Result<(), OperationError> {
+        admin_info!("Attempt LDAP Add for {}", uat.spn);
+
+        let (rdn_attr, rdn_val) = match self.dnre.captures(ar.dn.as_str()).and_then(|caps| {
+            caps.name("attr")
+                .zip(caps.name("val"))
+                .map(|(a, v)| (a.as_str().to_string(), v.as_str().to_string()))
+        }) {
+            Some(rdn) => rdn,
+            None => {
+                request_error!("LDAP Add failure - invalid dn");
+                return Err(OperationError::InvalidRequestState);
+            }
+        };
 
-        let target_uuid: Uuid = if dn.is_empty() {
-            if pw.is_empty() {
-                security_info!("✅ LDAP Bind success anonymous");
-                UUID_ANONYMOUS
-            } else {
-                // This is the path to access api-token logins.
-                let lae = LdapTokenAuthEvent::from_parts(pw.to_string())?;
-                return idm_auth.token_auth_ldap(&lae, ct).await.and_then(|r| {
-                    idm_auth.commit().map(|_| {
-                        if r.is_some() {
-                            security_info!(%dn, "✅ LDAP Bind success");
-                        } else {
-                            security_info!(%dn, "❌ LDAP Bind failure");
-                        };
-                        r
-                    })
-                });
+        let mut attrs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for attr in ar.attributes.iter() {
+            if attr.atype.eq_ignore_ascii_case(ATTR_USERPASSWORD) {
+                request_error!(
+                    "LDAP Add failure - a password can only be set once the entry exists"
+                );
+                return Err(OperationError::InvalidAttribute(attr.atype.clone()));
             }
-        } else {
-            // Is the passed dn requesting token auth?
-            // We use dn= here since these are attr=value, and dn is a phantom so it will
-            // never be present or match a real value. We also make it an ava so that clients
-            // that over-zealously validate dn syntax are happy.
-            if dn == "dn=token" {
-                let lae = LdapTokenAuthEvent::from_parts(pw.to_string())?;
-                return idm_auth.token_auth_ldap(&lae, ct).await.and_then(|r| {
-                    idm_auth.commit().map(|_| {
-                        if r.is_some() {
-                            security_info!(%dn, "✅ LDAP Bind success");
-                        } else {
-                            security_info!(%dn, "❌ LDAP Bind failure");
-                        };
-                        r
-                    })
-                });
-            };
 
-            let rdn = self
-                .binddnre
-                .captures(dn)
-                .and_then(|caps| caps.name("val"))
-                .map(|v| v.as_str().to_string())
-                .ok_or(OperationError::NoMatchingEntries)?;
+            let k_attr = ldap_write_attr_map(&attr.atype)?;
+            let vals = ldap_write_values(&attr.atype, &attr.vals)?;
+            attrs.entry(k_attr.to_string()).or_default().extend(
+                vals.into_iter()
+                    // top is implied by every entry, and isn't a class in kanidm.
+                    .filter(|v| !(k_attr.as_str() == ATTR_CLASS && v.eq_ignore_ascii_case("top"))),
+            );
+        }
 
-            trace!(?rdn, "relative dn");
+        // The rdn of the new entry is its name, which the client may not have repeated
+        // in the attributes.
+        let name = ldap_rdn_to_name(&rdn_attr, &rdn_val)?;
+        let names = attrs.entry(ATTR_NAME.to_string()).or_default();
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            names.push(name.to_string());
+        }
 
-            if rdn.is_empty() {
-                // That's weird ...
-                return Err(OperationError::NoMatchingEntries);
-            }
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
 
-            idm_auth.qs_read.name_to_uuid(rdn.as_str()).map_err(|e| {
-                request_error!(err = ?e, ?rdn, "Error resolving rdn to target");
+        let ident = idms_prox_write
+            .validate_ldap_session(&uat.effective_session, ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
                 e
-            })?
+            })?;
+
+        let req = CreateRequest {
+            entries: vec![ProtoEntry { attrs }],
         };
 
-        let lae = LdapAuthEvent::from_parts(target_uuid, pw.to_string())?;
-        idm_auth.auth_ldap(&lae, ct).await.and_then(|r| {
-            idm_auth.commit().map(|_| {
-                if r.is_some() {
-                    security_info!(%dn, "✅ LDAP Bind success");
-                } else {
-                    security_info!(%dn, "❌ LDAP Bind failure");
-                };
-                r
-            })
-        })
+        let ce =
+            CreateEvent::from_message(ident, &req, &mut idms_prox_write.qs_write).map_err(|e| {
+                admin_error!("failed to create create event -> {:?}", e);
+                e
+            })?;
+
+        idms_prox_write
+            .qs_write
+            .create(&ce)
+            .and_then(|_| idms_prox_write.commit())
+            .map(|_| admin_info!("LDAP Add success"))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn do_modify(
+        &self,
+        idms: &IdmServer,
+        mr: &LdapModifyRequest,
+        uat: &LdapBoundToken,
+    ) -> Result<(), OperationError> {
+        admin_info!("Attempt LDAP Modify for {}", uat.spn);
+
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let target = self.resolve_write_target(&mut idms_prox_write.qs_write, &mr.dn)?;
+
+        let mut mods = Vec::with_capacity(mr.changes.len());
+        let mut password = None;
+
+        for change in mr.changes.iter() {
+            let atype = &change.modification.atype;
+
+            if atype.eq_ignore_ascii_case(ATTR_USERPASSWORD) {
+                // A password is a credential rather than an attribute, so it must be changed
+                // through the credential path which checks the password quality.
+                password = match (&change.operation, change.modification.vals.as_slice()) {
+                    (LdapModifyType::Add | LdapModifyType::Replace, [pw]) => {
+                        Some(ldap_write_value(atype, pw)?)
+                    }
+                    _ => {
+                        request_error!(
+                            "LDAP Modify failure - userpassword can only be replaced by a single value"
+                        );
+                        return Err(OperationError::InvalidAttribute(atype.clone()));
+                    }
+                };
+                continue;
+            }
+
+            let k_attr = ldap_write_attr_map(atype)?;
+            let vals = ldap_write_values(atype, &change.modification.vals)?;
+
+            match &change.operation {
+                // Deleting without values removes the attribute.
+                LdapModifyType::Delete if vals.is_empty() => mods.push(Modify::Purged(k_attr)),
+                LdapModifyType::Delete => {
+                    for v in vals.iter() {
+                        let pv = idms_prox_write.qs_write.clone_partialvalue(&k_attr, v)?;
+                        mods.push(Modify::Removed(k_attr.clone(), pv));
+                    }
+                }
+                LdapModifyType::Add | LdapModifyType::Replace => {
+                    if matches!(change.operation, LdapModifyType::Replace) {
+                        mods.push(Modify::Purged(k_attr.clone()));
+                    }
+                    for v in vals.iter() {
+                        let v = idms_prox_write.qs_write.clone_value(&k_attr, v)?;
+                        mods.push(Modify::Present(k_attr.clone(), v));
+                    }
+                }
+            }
+        }
+
+        let ident = idms_prox_write
+            .validate_ldap_session(&uat.effective_session, ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        if !mods.is_empty() {
+            let me = ModifyEvent::from_internal_parts(
+                ident.clone(),
+                &ModifyList::new_list(mods),
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(target))),
+                &idms_prox_write.qs_write,
+            )
+            .map_err(|e| {
+                admin_error!("failed to create modify event -> {:?}", e);
+                e
+            })?;
+
+            idms_prox_write.qs_write.modify(&me)?;
+        }
+
+        if let Some(cleartext) = password {
+            ldap_check_write_scope(&ident)?;
+            let pce = UnixPasswordChangeEvent::from_parts(ident, target, cleartext)?;
+            idms_prox_write.set_unix_account_password(&pce)?;
+        }
+
+        idms_prox_write
+            .commit()
+            .map(|_| admin_info!("LDAP Modify success"))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn do_delete(
+        &self,
+        idms: &IdmServer,
+        dn: &str,
+        uat: &LdapBoundToken,
+    ) -> Result<(), OperationError> {
+        admin_info!("Attempt LDAP Delete for {}", uat.spn);
+
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let target = self.resolve_write_target(&mut idms_prox_write.qs_write, dn)?;
+
+        let ident = idms_prox_write
+            .validate_ldap_session(&uat.effective_session, ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let de = DeleteEvent::from_parts(
+            ident,
+            &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(target))),
+            &mut idms_prox_write.qs_write,
+        )
+        .map_err(|e| {
+            admin_error!("failed to create delete event -> {:?}", e);
+            e
+        })?;
+
+        idms_prox_write
+            .qs_write
+            .delete(&de)
+            .and_then(|_| idms_prox_write.commit())
+            .map(|_| admin_info!("LDAP Delete success"))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn do_modify_dn(
+        &self,
+        idms: &IdmServer,
+        mdr: &LdapModifyDNRequest,
+        uat: &LdapBoundToken,
+    ) -> Result<(), OperationError> {
+        admin_info!("Attempt LDAP ModifyDN for {}", uat.spn);
+
+        let Some((rdn_attr, rdn_val)) = mdr.newrdn.split_once('=') else {
+            request_error!("LDAP ModifyDN failure - invalid rdn");
+            return Err(OperationError::InvalidRequestState);
+        };
+
+        // Names are single valued, so the old rdn is always replaced regardless of
+        // deleteoldrdn.
+        let name = ldap_rdn_to_name(rdn_attr, rdn_val)?;
+
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let target = self.resolve_write_target(&mut idms_prox_write.qs_write, &mdr.dn)?;
+        let name = idms_prox_write.qs_write.clone_value(ATTR_NAME, name)?;
+
+        let ident = idms_prox_write
+            .validate_ldap_session(&uat.effective_session, ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let me = ModifyEvent::from_internal_parts(
+            ident,
+            &ModifyList::new_purge_and_set(Attribute::Name, name),
+            &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(target))),
+            &idms_prox_write.qs_write,
+        )
+        .map_err(|e| {
+            admin_error!("failed to create modify event -> {:?}", e);
+            e
+        })?;
+
+        idms_prox_write
+            .qs_write
+            .modify(&me)
+            .and_then(|_| idms_prox_write.commit())
+            .map(|_| admin_info!("LDAP ModifyDN success"))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn do_password_modify(
+        &self,
+        idms: &IdmServer,
+        pmr: &LdapPasswordModifyRequest,
+        uat: &LdapBoundToken,
+    ) -> Result<(), OperationError> {
+        admin_info!("Attempt LDAP Password Modify for {}", uat.spn);
+
+        // We never generate a password on behalf of the client.
+        let Some(cleartext) = pmr.new_password.clone() else {
+            request_error!("LDAP Password Modify failure - a new password is required");
+            return Err(OperationError::InvalidRequestState);
+        };
+
+        let ct = duration_from_epoch_now();
+
+        // Without a user identity the password of the bound account is changed. The identity
+        // may be given as a dn, a name, or an authzid as returned by whoami.
+        let target = match pmr.user_identity.as_deref() {
+            Some(user_identity) => {
+                let user_identity = user_identity.trim_start_matches("u:").trim();
+                let mut idm_read = idms.proxy_read().await;
+                idm_read.qs_read.name_to_uuid(user_identity).map_err(|e| {
+                    request_error!(err = ?e, %user_identity, "Error resolving user identity");
+                    e
+                })?
+            }
+            None => match &uat.effective_session {
+                LdapSession::UnixBind(uuid) => *uuid,
+                LdapSession::UserAuthToken(token) => token.uuid,
+                LdapSession::ApiToken(apit) => apit.account_id,
+            },
+        };
+
+        // Supplying the current password proves the identity of the account, which may then
+        // change its own password. Otherwise the change is made as the bound identity.
+        if let Some(old_password) = pmr.old_password.as_ref() {
+            let mut idm_auth = idms.auth().await;
+            let ident = idm_auth
+                .validate_ldap_session(&uat.effective_session, ct)
+                .map_err(|e| {
+                    admin_error!("Invalid identity: {:?}", e);
+                    e
+                })?;
+            let uae = UnixUserAuthEvent::from_parts(ident, target, old_password.clone())?;
+            let token = idm_auth.auth_unix(&uae, ct).await?;
+            idm_auth.commit()?;
+
+            let Some(token) = token else {
+                security_info!("❌ LDAP Password Modify failure - incorrect password");
+                return Err(OperationError::NotAuthenticated);
+            };
+
+            let pce = UnixPasswordSelfChangeEvent::from_unix_auth(&token, cleartext);
+            let mut idms_prox_write = idms.proxy_write(ct).await;
+            return idms_prox_write
+                .set_unix_account_password_self(&pce)
+                .and_then(|_| idms_prox_write.commit())
+                .map(|_| security_info!("✅ LDAP Password Modify success"));
+        }
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let ident = idms_prox_write
+            .validate_ldap_session(&uat.effective_session, ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        ldap_check_write_scope(&ident)?;
+
+        let pce = UnixPasswordChangeEvent::from_parts(ident, target, cleartext)?;
+
+        idms_prox_write
+            .set_unix_account_password(&pce)
+            .and_then(|_| idms_prox_write.commit())
+            .map(|_| security_info!("✅ LDAP Password Modify success"))
+    }
+
+    async fn do_bind(
+        &self,
+        idms: &IdmServer,
+        dn: &str,
+        pw: &str,
+        source: Source,
+    ) -> Result<Option<LdapBoundToken>, OperationError> {
+        security_info!(
+            "Attempt LDAP Bind for {}",
+            if dn.is_empty() { "anonymous" } else { dn }
+        );
+        let ct = duration_from_epoch_now();
+
+        let mut idm_auth = idms.auth().await;
+        let audit_tx = idm_auth.audit_tx.clone();
+
+        let target_uuid: Uuid = if dn.is_empty() {
+            if pw.is_empty() {
+                security_info!("✅ LDAP Bind success anonymous");
+                UUID_ANONYMOUS
+            } else {
+                // This is the path to access api-token logins.
+                let lae = LdapTokenAuthEvent::from_parts(pw.to_string())?;
+                return idm_auth.token_auth_ldap(&lae, ct).await.and_then(|r| {
+                    idm_auth.commit().map(|_| {
+                        if let Some(lbt) = &r {
+                            security_info!(%dn, "✅ LDAP Bind success");
+                            ldap_bind_audit(&audit_tx, source, lbt, ct);
+                        } else {
+                            security_info!(%dn, "❌ LDAP Bind failure");
+                        };
+                        r
+                    })
+                });
+            }
+        } else {
+            // Is the passed dn requesting token auth?
+            // We use dn= here since these are attr=value, and dn is a phantom so it will
+            // never be present or match a real value. We also make it an ava so that clients
+            // that over-zealously validate dn syntax are happy.
+            if dn == "dn=token" {
+                let lae = LdapTokenAuthEvent::from_parts(pw.to_string())?;
+                return idm_auth.token_auth_ldap(&lae, ct).await.and_then(|r| {
+                    idm_auth.commit().map(|_| {
+                        if let Some(lbt) = &r {
+                            security_info!(%dn, "✅ LDAP Bind success");
+                            ldap_bind_audit(&audit_tx, source, lbt, ct);
+                        } else {
+                            security_info!(%dn, "❌ LDAP Bind failure");
+                        };
+                        r
+                    })
+                });
+            };
+
+            let rdn = self
+                .binddnre
+                .captures(dn)
+                .and_then(|caps| caps.name("val"))
+                .map(|v| v.as_str().to_string())
+                .ok_or(OperationError::NoMatchingEntries)?;
+
+            trace!(?rdn, "relative dn");
+
+            if rdn.is_empty() {
+                // That's weird ...
+                return Err(OperationError::NoMatchingEntries);
+            }
+
+            idm_auth.qs_read.name_to_uuid(rdn.as_str()).map_err(|e| {
+                request_error!(err = ?e, ?rdn, "Error resolving rdn to target");
+                e
+            })?
+        };
+
+        let lae = LdapAuthEvent::from_parts(target_uuid, pw.to_string())?;
+        let r = idm_auth.auth_ldap(&lae, ct).await?;
+
+        // Anonymous binds are too frequent to be worth auditing.
+        if target_uuid != UUID_ANONYMOUS {
+            if let Some(lbt) = &r {
+                ldap_bind_audit(&audit_tx, source, lbt, ct);
+            } else {
+                let spn = idm_auth
+                    .qs_read
+                    .uuid_to_spn(target_uuid)?
+                    .map(|spn| spn.to_proto_string_clone())
+                    .unwrap_or_else(|| dn.to_string());
+                let event = AuditEvent::AuthenticationDenied {
+                    source: source.into(),
+                    uuid: target_uuid,
+                    spn,
+                    time: OffsetDateTime::UNIX_EPOCH + ct,
+                };
+                if audit_tx.send(event).is_err() {
+                    error!("Unable to submit audit event to queue");
+                }
+            }
+        }
+
+        idm_auth.commit().map(|_| {
+            if r.is_some() {
+                security_info!(%dn, "✅ LDAP Bind success");
+            } else {
+                security_info!(%dn, "❌ LDAP Bind failure");
+            };
+            r
+        })
     }
 
     pub async fn do_op(
@@ -464,11 +1443,21 @@ impl LdapServer {
         idms: &IdmServer,
         server_op: ServerOps,
         uat: Option<LdapBoundToken>,
+        ctrl: &[LdapControl],
         eventid: Uuid,
+        source: Source,
     ) -> Result<LdapResponseState, OperationError> {
+        METRICS.ldap_operation(match &server_op {
+            ServerOps::SimpleBind(_) => "bind",
+            ServerOps::Search(_) => "search",
+            ServerOps::Unbind(_) => "unbind",
+            ServerOps::Compare(_) => "compare",
+            ServerOps::Whoami(_) => "whoami",
+        });
+
         match server_op {
             ServerOps::SimpleBind(sbr) => self
-                .do_bind(idms, sbr.dn.as_str(), sbr.pw.as_str())
+                .do_bind(idms, sbr.dn.as_str(), sbr.pw.as_str(), source)
                 .await
                 .map(|r| match r {
                     Some(lbt) => LdapResponseState::Bind(lbt, sbr.gen_success()),
@@ -480,7 +1469,7 @@ impl LdapServer {
                 }),
             ServerOps::Search(sr) => match uat {
                 Some(u) => self
-                    .do_search(idms, &sr, &u)
+                    .do_search_ctrl(idms, &sr, &u, &LdapSearchControls::from(ctrl))
                     .await
                     .map(LdapResponseState::MultiPartResponse)
                     .or_else(|e| {
@@ -489,7 +1478,7 @@ impl LdapServer {
                     }),
                 None => {
                     // Search can occur without a bind, so bind first.
-                    let lbt = match self.do_bind(idms, "", "").await {
+                    let lbt = match self.do_bind(idms, "", "", source).await {
                         Ok(Some(lbt)) => lbt,
                         Ok(None) => {
                             return Ok(LdapResponseState::Respond(
@@ -502,7 +1491,7 @@ impl LdapServer {
                         }
                     };
                     // If okay, do the search.
-                    self.do_search(idms, &sr, &lbt)
+                    self.do_search_ctrl(idms, &sr, &lbt, &LdapSearchControls::from(ctrl))
                         .await
                         .map(|r| LdapResponseState::BindMultiPartResponse(lbt, r))
                         .or_else(|e| {
@@ -515,9 +1504,38 @@ impl LdapServer {
                 // No need to notify on unbind (per rfc4511)
                 Ok(LdapResponseState::Unbind)
             }
-            ServerOps::Compare(cr) => Ok(LdapResponseState::Respond(
-                cr.gen_error(LdapResultCode::Other, "not supported".to_string()),
-            )),
+            ServerOps::Compare(cr) => match uat {
+                Some(u) => self
+                    .do_compare(idms, &cr, &u)
+                    .await
+                    .map(LdapResponseState::Respond)
+                    .or_else(|e| {
+                        let (rc, msg) = operationerr_to_ldapresultcode(e);
+                        Ok(LdapResponseState::Respond(cr.gen_error(rc, msg)))
+                    }),
+                None => {
+                    // Compare can occur without a bind, so bind first.
+                    let lbt = match self.do_bind(idms, "", "", source).await {
+                        Ok(Some(lbt)) => lbt,
+                        Ok(None) => {
+                            return Ok(LdapResponseState::Respond(
+                                cr.gen_error(LdapResultCode::InvalidCredentials, "".to_string()),
+                            ))
+                        }
+                        Err(e) => {
+                            let (rc, msg) = operationerr_to_ldapresultcode(e);
+                            return Ok(LdapResponseState::Respond(cr.gen_error(rc, msg)));
+                        }
+                    };
+                    self.do_compare(idms, &cr, &lbt)
+                        .await
+                        .map(|r| LdapResponseState::Bind(lbt, r))
+                        .or_else(|e| {
+                            let (rc, msg) = operationerr_to_ldapresultcode(e);
+                            Ok(LdapResponseState::Respond(cr.gen_error(rc, msg)))
+                        })
+                }
+            },
             ServerOps::Whoami(wr) => match uat {
                 Some(u) => Ok(LdapResponseState::Respond(
                     wr.gen_success(format!("u: {}", u.spn).as_str()),
@@ -528,6 +1546,77 @@ impl LdapServer {
             },
         } // end match server op
     }
+
+    pub async fn do_write_op(
+        &self,
+        idms: &IdmServer,
+        wr: LdapWriteRequest,
+        uat: Option<LdapBoundToken>,
+        source: Source,
+    ) -> Result<LdapResponseState, OperationError> {
+        METRICS.ldap_operation(match &wr.op {
+            LdapWriteOp::Add(_) => "add",
+            LdapWriteOp::Modify(_) => "modify",
+            LdapWriteOp::Delete(_) => "delete",
+            LdapWriteOp::ModifyDn(_) => "modifydn",
+            LdapWriteOp::PasswordModify(_) => "passwordmodify",
+        });
+
+        // The directory is flat, so an entry can be renamed but never moved.
+        if let LdapWriteOp::ModifyDn(LdapModifyDNRequest {
+            new_superior: Some(new_superior),
+            ..
+        }) = &wr.op
+        {
+            if !new_superior.eq_ignore_ascii_case(&self.basedn) {
+                request_error!(%new_superior, "LDAP ModifyDN failure - entries can not be moved");
+                return Ok(LdapResponseState::Respond(wr.gen_result(
+                    LdapResultCode::UnwillingToPerform,
+                    "Entries can not be moved to a new superior".to_string(),
+                )));
+            }
+        }
+
+        // As with search, an unbound connection acts as anonymous. This allows a password
+        // modify that supplies the current password without a prior bind.
+        let (lbt, bound) = match uat {
+            Some(u) => (u, true),
+            None => match self.do_bind(idms, "", "", source).await {
+                Ok(Some(lbt)) => (lbt, false),
+                Ok(None) => {
+                    return Ok(LdapResponseState::Respond(
+                        wr.gen_result(LdapResultCode::InvalidCredentials, "".to_string()),
+                    ))
+                }
+                Err(e) => {
+                    let (rc, msg) = operationerr_to_ldapresultcode(e);
+                    return Ok(LdapResponseState::Respond(wr.gen_result(rc, msg)));
+                }
+            },
+        };
+
+        let res = match &wr.op {
+            LdapWriteOp::Add(ar) => self.do_add(idms, ar, &lbt).await,
+            LdapWriteOp::Modify(mr) => self.do_modify(idms, mr, &lbt).await,
+            LdapWriteOp::Delete(dn) => self.do_delete(idms, dn, &lbt).await,
+            LdapWriteOp::ModifyDn(mdr) => self.do_modify_dn(idms, mdr, &lbt).await,
+            LdapWriteOp::PasswordModify(pmr) => self.do_password_modify(idms, pmr, &lbt).await,
+        };
+
+        let rmsg = match res {
+            Ok(()) => wr.gen_result(LdapResultCode::Success, "".to_string()),
+            Err(e) => {
+                let (rc, msg) = operationerr_to_ldapresultcode(e);
+                wr.gen_result(rc, msg)
+            }
+        };
+
+        if bound {
+            Ok(LdapResponseState::Respond(rmsg))
+        } else {
+            Ok(LdapResponseState::Bind(lbt, rmsg))
+        }
+    }
 }
 
 fn ldap_domain_to_dc(input: &str) -> String {
@@ -543,23 +1632,162 @@ fn ldap_domain_to_dc(input: &str) -> String {
     output
 }
 
-fn operationerr_to_ldapresultcode(e: OperationError) -> (LdapResultCode, String) {
-    match e {
-        OperationError::InvalidRequestState => {
-            (LdapResultCode::ConstraintViolation, "".to_string())
-        }
-        OperationError::InvalidAttributeName(s) | OperationError::InvalidAttribute(s) => {
-            (LdapResultCode::InvalidAttributeSyntax, s)
-        }
-        OperationError::SchemaViolation(se) => {
-            (LdapResultCode::UnwillingToPerform, format!("{se:?}"))
+/// Sort entries by the requested keys in order. As per RFC 2891, multivalued attributes
+/// sort by their least value (greatest when reversed), and entries without the attribute
+/// sort after all others.
+fn ldap_sort_entries(entries: &mut [EntryReducedCommitted], keys: &[(AttrString, bool)]) {
+    let sort_value = |e: &EntryReducedCommitted, attr: &AttrString, reverse: bool| {
+        e.get_ava().get(attr).and_then(|vs| {
+            if reverse {
+                vs.to_partialvalue_iter().max()
+            } else {
+                vs.to_partialvalue_iter().min()
+            }
+        })
+    };
+
+    entries.sort_by(|a, b| {
+        keys.iter()
+            .map(|(attr, reverse)| {
+                let ord = match (sort_value(a, attr, *reverse), sort_value(b, attr, *reverse)) {
+                    (Some(va), Some(vb)) => va.cmp(&vb),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                };
+                if *reverse {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            })
+            .find(|ord| *ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+/// Schema and access control entries are never exposed over ldap.
+fn ldap_exclude_internal_classes() -> LdapFilter {
+    LdapFilter::Not(Box::new(LdapFilter::Or(vec![
+        LdapFilter::Equality(Attribute::Class.to_string(), "classtype".to_string()),
+        LdapFilter::Equality(Attribute::Class.to_string(), "attributetype".to_string()),
+        LdapFilter::Equality(
+            Attribute::Class.to_string(),
+            "access_control_profile".to_string(),
+        ),
+    ])))
+}
+
+/// Map an ldap attribute to the kanidm attribute it is written to. This is the reverse
+/// of [ldap_attr_filter_map], except for the attributes that are synthesised for ldap
+/// and don't represent a single kanidm attribute.
+fn ldap_write_attr_map(atype: &str) -> Result<AttrString, OperationError> {
+    let a_lower = atype.to_lowercase();
+    match a_lower.as_str() {
+        "dn"
+        | LDAP_ATTR_ENTRYDN
+        | LDAP_ATTR_EMAIL_ALTERNATIVE
+        | LDAP_ATTR_EMAIL_PRIMARY
+        | LDAP_ATTR_MAIL_ALTERNATIVE
+        | LDAP_ATTR_MAIL_PRIMARY => {
+            request_error!(%atype, "LDAP Write failure - attribute is read only");
+            Err(OperationError::InvalidAttributeName(atype.to_string()))
         }
-        e => (LdapResultCode::Other, format!("{e:?}")),
+        _ => Ok(ldap_attr_filter_map(&a_lower)),
+    }
+}
+
+fn ldap_write_value(atype: &str, val: &[u8]) -> Result<String, OperationError> {
+    String::from_utf8(val.to_vec()).map_err(|_| {
+        request_error!(%atype, "LDAP Write failure - value is not valid utf8");
+        OperationError::InvalidAttribute(atype.to_string())
+    })
+}
+
+fn ldap_write_values(atype: &str, vals: &[Vec<u8>]) -> Result<Vec<String>, OperationError> {
+    vals.iter().map(|v| ldap_write_value(atype, v)).collect()
+}
+
+/// The rdn of an entry is derived from its name, so only an rdn of name (or cn), or spn
+/// can be written. The domain of an spn is ignored as the directory has a single domain.
+fn ldap_rdn_to_name<'a>(attr: &str, val: &'a str) -> Result<&'a str, OperationError> {
+    match ldap_write_attr_map(attr)?.as_str() {
+        ATTR_NAME => Ok(val),
+        ATTR_SPN => Ok(val.split_once('@').map(|(name, _)| name).unwrap_or(val)),
+        _ => {
+            request_error!(%attr, "LDAP Write failure - rdn must be name or spn");
+            Err(OperationError::InvalidAttributeName(attr.to_string()))
+        }
+    }
+}
+
+/// A password change made as the bound identity requires a session that may write. Binds
+/// with a posix password only grant read, so they must supply the current password instead.
+fn ldap_check_write_scope(ident: &Identity) -> Result<(), OperationError> {
+    if ident.access_scope() != AccessScope::ReadWrite {
+        security_info!("❌ LDAP Password Modify failure - session is not read-write");
+        return Err(OperationError::AccessDenied);
+    }
+    Ok(())
+}
+
+/// Report a successful bind to the audit channel.
+fn ldap_bind_audit(
+    audit_tx: &Sender<AuditEvent>,
+    source: Source,
+    lbt: &LdapBoundToken,
+    ct: Duration,
+) {
+    let uuid = match &lbt.effective_session {
+        LdapSession::UnixBind(uuid) => *uuid,
+        LdapSession::UserAuthToken(uat) => uat.uuid,
+        LdapSession::ApiToken(apit) => apit.account_id,
+    };
+    let event = AuditEvent::AuthenticationSuccess {
+        source: source.into(),
+        uuid,
+        spn: lbt.spn.clone(),
+        privileged: false,
+        time: OffsetDateTime::UNIX_EPOCH + ct,
+    };
+    if audit_tx.send(event).is_err() {
+        error!("Unable to submit audit event to queue");
+    }
+}
+
+fn operationerr_to_ldapresultcode(e: OperationError) -> (LdapResultCode, String) {
+    match e {
+        OperationError::InvalidRequestState => {
+            (LdapResultCode::ConstraintViolation, "".to_string())
+        }
+        OperationError::InvalidAttributeName(s) | OperationError::InvalidAttribute(s) => {
+            (LdapResultCode::InvalidAttributeSyntax, s)
+        }
+        OperationError::SchemaViolation(se) => {
+            (LdapResultCode::UnwillingToPerform, format!("{se:?}"))
+        }
+        OperationError::NoMatchingEntries => (LdapResultCode::NoSuchObject, "".to_string()),
+        OperationError::NotAuthenticated => (LdapResultCode::InvalidCredentials, "".to_string()),
+        OperationError::AccessDenied
+        | OperationError::SystemProtectedObject
+        | OperationError::SystemProtectedAttribute => {
+            (LdapResultCode::InsufficentAccessRights, "".to_string())
+        }
+        OperationError::PasswordQuality(feedback) => (
+            LdapResultCode::ConstraintViolation,
+            feedback
+                .iter()
+                .map(|f| f.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        e => (LdapResultCode::Other, format!("{e:?}")),
     }
 }
 
 #[inline]
 pub(crate) fn ldap_all_vattrs() -> Vec<String> {
+    // The automount vattrs are not included, as every entry would gain an automountmapname.
     vec![
         ATTR_CN.to_string(),
         ATTR_EMAIL.to_string(),
@@ -586,6 +1814,9 @@ pub(crate) fn ldap_vattr_map(input: &str) -> Option<&str> {
     //
     //   LDAP NAME     KANI ATTR SOURCE NAME
     match input {
+        ATTR_LDAP_AUTOMOUNT_INFORMATION => Some(ATTR_AUTOMOUNT_INFORMATION),
+        ATTR_LDAP_AUTOMOUNT_KEY => Some(ATTR_AUTOMOUNT_KEY),
+        ATTR_LDAP_AUTOMOUNT_MAP_NAME => Some(ATTR_NAME),
         ATTR_CN => Some(ATTR_NAME),
         ATTR_EMAIL => Some(ATTR_MAIL),
         ATTR_LDAP_EMAIL_ADDRESS => Some(ATTR_MAIL),
@@ -616,17 +1847,31 @@ mod tests {
     use compact_jwt::{Jws, JwsUnverified};
     use hashbrown::HashSet;
     use kanidm_proto::v1::ApiToken;
-    use ldap3_proto::proto::{LdapFilter, LdapOp, LdapSearchScope};
+    use ldap3_proto::proto::{
+        LdapAddRequest, LdapAttribute, LdapControl, LdapFilter, LdapMatchingRuleAssertion,
+        LdapModify, LdapModifyDNRequest, LdapModifyRequest, LdapModifyType, LdapOp,
+        LdapPasswordModifyRequest, LdapSearchScope, LdapServerSideSortKey, LdapSubstringFilter,
+    };
     use ldap3_proto::simple::*;
 
-    use super::{LdapServer, LdapSession};
+    use super::{
+        LdapResponseState, LdapSearchControls, LdapServer, LdapSession, LdapWriteOp,
+        LdapWriteRequest,
+    };
+    use crate::idm::audit::{AuditEvent, AuditOperation, AuditSource};
     use crate::idm::event::UnixPasswordChangeEvent;
     use crate::idm::serviceaccount::GenerateApiTokenEvent;
+    use std::net::{IpAddr, Ipv4Addr};
 
     const TEST_PASSWORD: &str = "ntaoeuntnaoeuhraohuercahu😍";
+    const TEST_PASSWORD_NEW: &str = "ntaoeuntnaoeuhraohuercahu😍 rotated";
 
-    #[idm_test]
-    async fn test_ldap_simple_bind(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+    #[idm_test(audit)]
+    async fn test_ldap_simple_bind(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
         let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
 
         let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await;
@@ -645,33 +1890,46 @@ mod tests {
         assert!(idms_prox_write.set_unix_account_password(&pce).is_ok());
         assert!(idms_prox_write.commit().is_ok());
 
-        let anon_t = ldaps.do_bind(idms, "", "").await.unwrap().unwrap();
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
         assert!(anon_t.effective_session == LdapSession::UnixBind(UUID_ANONYMOUS));
         assert!(
-            ldaps.do_bind(idms, "", "test").await.unwrap_err() == OperationError::NotAuthenticated
+            ldaps
+                .do_bind(idms, "", "test", Source::Internal)
+                .await
+                .unwrap_err()
+                == OperationError::NotAuthenticated
         );
 
         // Now test the admin and various DN's
         let admin_t = ldaps
-            .do_bind(idms, "admin", TEST_PASSWORD)
+            .do_bind(idms, "admin", TEST_PASSWORD, Source::Internal)
             .await
             .unwrap()
             .unwrap();
         assert!(admin_t.effective_session == LdapSession::UnixBind(UUID_ADMIN));
         let admin_t = ldaps
-            .do_bind(idms, "admin@example.com", TEST_PASSWORD)
+            .do_bind(idms, "admin@example.com", TEST_PASSWORD, Source::Internal)
             .await
             .unwrap()
             .unwrap();
         assert!(admin_t.effective_session == LdapSession::UnixBind(UUID_ADMIN));
         let admin_t = ldaps
-            .do_bind(idms, STR_UUID_ADMIN, TEST_PASSWORD)
+            .do_bind(idms, STR_UUID_ADMIN, TEST_PASSWORD, Source::Internal)
             .await
             .unwrap()
             .unwrap();
         assert!(admin_t.effective_session == LdapSession::UnixBind(UUID_ADMIN));
         let admin_t = ldaps
-            .do_bind(idms, "name=admin,dc=example,dc=com", TEST_PASSWORD)
+            .do_bind(
+                idms,
+                "name=admin,dc=example,dc=com",
+                TEST_PASSWORD,
+                Source::Internal,
+            )
             .await
             .unwrap()
             .unwrap();
@@ -681,6 +1939,7 @@ mod tests {
                 idms,
                 "spn=admin@example.com,dc=example,dc=com",
                 TEST_PASSWORD,
+                Source::Internal,
             )
             .await
             .unwrap()
@@ -691,6 +1950,7 @@ mod tests {
                 idms,
                 format!("uuid={STR_UUID_ADMIN},dc=example,dc=com").as_str(),
                 TEST_PASSWORD,
+                Source::Internal,
             )
             .await
             .unwrap()
@@ -698,13 +1958,18 @@ mod tests {
         assert!(admin_t.effective_session == LdapSession::UnixBind(UUID_ADMIN));
 
         let admin_t = ldaps
-            .do_bind(idms, "name=admin", TEST_PASSWORD)
+            .do_bind(idms, "name=admin", TEST_PASSWORD, Source::Internal)
             .await
             .unwrap()
             .unwrap();
         assert!(admin_t.effective_session == LdapSession::UnixBind(UUID_ADMIN));
         let admin_t = ldaps
-            .do_bind(idms, "spn=admin@example.com", TEST_PASSWORD)
+            .do_bind(
+                idms,
+                "spn=admin@example.com",
+                TEST_PASSWORD,
+                Source::Internal,
+            )
             .await
             .unwrap()
             .unwrap();
@@ -714,6 +1979,7 @@ mod tests {
                 idms,
                 format!("uuid={STR_UUID_ADMIN}").as_str(),
                 TEST_PASSWORD,
+                Source::Internal,
             )
             .await
             .unwrap()
@@ -721,13 +1987,23 @@ mod tests {
         assert!(admin_t.effective_session == LdapSession::UnixBind(UUID_ADMIN));
 
         let admin_t = ldaps
-            .do_bind(idms, "admin,dc=example,dc=com", TEST_PASSWORD)
+            .do_bind(
+                idms,
+                "admin,dc=example,dc=com",
+                TEST_PASSWORD,
+                Source::Internal,
+            )
             .await
             .unwrap()
             .unwrap();
         assert!(admin_t.effective_session == LdapSession::UnixBind(UUID_ADMIN));
         let admin_t = ldaps
-            .do_bind(idms, "admin@example.com,dc=example,dc=com", TEST_PASSWORD)
+            .do_bind(
+                idms,
+                "admin@example.com,dc=example,dc=com",
+                TEST_PASSWORD,
+                Source::Internal,
+            )
             .await
             .unwrap()
             .unwrap();
@@ -737,25 +2013,39 @@ mod tests {
                 idms,
                 format!("{STR_UUID_ADMIN},dc=example,dc=com").as_str(),
                 TEST_PASSWORD,
+                Source::Internal,
             )
             .await
             .unwrap()
             .unwrap();
         assert!(admin_t.effective_session == LdapSession::UnixBind(UUID_ADMIN));
 
+        // Each of the successful admin binds is reported.
+        for _ in 0..12 {
+            match idms_audit.audit_rx().try_recv() {
+                Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+                _ => assert!(false),
+            }
+        }
+
         // Bad password, check last to prevent softlocking of the admin account.
         assert!(ldaps
-            .do_bind(idms, "admin", "test")
+            .do_bind(idms, "admin", "test", Source::Internal)
             .await
             .unwrap()
             .is_none());
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationDenied { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
 
         // Non-existent and invalid DNs
         assert!(ldaps
             .do_bind(
                 idms,
                 "spn=admin@example.com,dc=clownshoes,dc=example,dc=com",
-                TEST_PASSWORD
+                TEST_PASSWORD,
+                Source::Internal
             )
             .await
             .is_err());
@@ -763,20 +2053,24 @@ mod tests {
             .do_bind(
                 idms,
                 "spn=claire@example.com,dc=example,dc=com",
-                TEST_PASSWORD
+                TEST_PASSWORD,
+                Source::Internal
             )
             .await
             .is_err());
         assert!(ldaps
-            .do_bind(idms, ",dc=example,dc=com", TEST_PASSWORD)
+            .do_bind(idms, ",dc=example,dc=com", TEST_PASSWORD, Source::Internal)
             .await
             .is_err());
         assert!(ldaps
-            .do_bind(idms, "dc=example,dc=com", TEST_PASSWORD)
+            .do_bind(idms, "dc=example,dc=com", TEST_PASSWORD, Source::Internal)
             .await
             .is_err());
 
-        assert!(ldaps.do_bind(idms, "claire", "test").await.is_err());
+        assert!(ldaps
+            .do_bind(idms, "claire", "test", Source::Internal)
+            .await
+            .is_err());
     }
 
     macro_rules! assert_entry_contains {
@@ -804,6 +2098,67 @@ mod tests {
         }};
     }
 
+    #[idm_test(audit)]
+    async fn test_ldap_bind_audit(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
+        let source = Source::Ldaps(IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+        let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await;
+        let me_posix = ModifyEvent::new_internal_invalid(
+            filter!(f_eq(Attribute::Name, PartialValue::new_iname("admin"))),
+            ModifyList::new_list(vec![
+                Modify::Present(Attribute::Class.into(), EntryClass::PosixAccount.into()),
+                Modify::Present(Attribute::GidNumber.into(), Value::new_uint32(2001)),
+            ]),
+        );
+        assert!(idms_prox_write.qs_write.modify(&me_posix).is_ok());
+        let pce = UnixPasswordChangeEvent::new_internal(UUID_ADMIN, TEST_PASSWORD);
+        assert!(idms_prox_write.set_unix_account_password(&pce).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Anonymous binds are not reported.
+        assert!(ldaps
+            .do_bind(idms, "", "", source.clone())
+            .await
+            .unwrap()
+            .is_some());
+        idms_audit.check_is_empty_or_panic();
+
+        assert!(ldaps
+            .do_bind(idms, "admin", TEST_PASSWORD_NEW, source.clone())
+            .await
+            .unwrap()
+            .is_none());
+
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationDenied {
+                source: AuditSource::Ldaps(_),
+                uuid: UUID_ADMIN,
+                ..
+            }) => {}
+            _ => assert!(false),
+        }
+
+        assert!(ldaps
+            .do_bind(idms, "admin", TEST_PASSWORD, source)
+            .await
+            .unwrap()
+            .is_some());
+
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess {
+                source: AuditSource::Ldaps(_),
+                uuid: UUID_ADMIN,
+                ..
+            }) => {}
+            _ => assert!(false),
+        }
+    }
+
     #[idm_test]
     async fn test_ldap_virtual_attribute_generation(
         idms: &IdmServer,
@@ -845,7 +2200,11 @@ mod tests {
         }
 
         // Setup the anonymous login.
-        let anon_t = ldaps.do_bind(idms, "", "").await.unwrap().unwrap();
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
         assert!(anon_t.effective_session == LdapSession::UnixBind(UUID_ANONYMOUS));
 
         // Check that when we request *, we get default list.
@@ -954,10 +2313,11 @@ mod tests {
         };
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_ldap_token_privilege_granting(
         idms: &IdmServer,
         _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // Setup the ldap server
         let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
@@ -1060,7 +2420,11 @@ mod tests {
         // we don't have purpose so this isn't tested.
 
         // Bind with anonymous, search and show mail attr isn't accessible.
-        let anon_lbt = ldaps.do_bind(idms, "", "").await.unwrap().unwrap();
+        let anon_lbt = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
         assert!(anon_lbt.effective_session == LdapSession::UnixBind(UUID_ANONYMOUS));
 
         let r1 = ldaps.do_search(idms, &sr, &anon_lbt).await.unwrap();
@@ -1084,123 +2448,790 @@ mod tests {
             .validate_embeded()
             .expect("Embedded jwk not found");
 
-        let apitoken_inner = apitoken_inner.into_inner();
+        let apitoken_inner = apitoken_inner.into_inner();
+
+        // Bind using the token as a DN
+        let sa_lbt = ldaps
+            .do_bind(idms, "dn=token", &apitoken, Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(sa_lbt.effective_session == LdapSession::ApiToken(apitoken_inner.clone()));
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, sa_uuid),
+            _ => assert!(false),
+        }
+
+        // Bind using the token as a pw
+        let sa_lbt = ldaps
+            .do_bind(idms, "", &apitoken, Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(sa_lbt.effective_session == LdapSession::ApiToken(apitoken_inner));
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, sa_uuid),
+            _ => assert!(false),
+        }
+
+        // Search and retrieve mail that's now accessible.
+        let r1 = ldaps.do_search(idms, &sr, &sa_lbt).await.unwrap();
+        assert!(r1.len() == 2);
+        match &r1[0].op {
+            LdapOp::SearchResultEntry(lsre) => {
+                assert_entry_contains!(
+                    lsre,
+                    "spn=testperson1@example.com,dc=example,dc=com",
+                    (Attribute::Name.as_ref(), "testperson1"),
+                    (Attribute::Mail.as_ref(), "testperson1@example.com"),
+                    (
+                        Attribute::Mail.as_ref(),
+                        "testperson1.alternative@example.com"
+                    ),
+                    (LDAP_ATTR_MAIL_PRIMARY, "testperson1@example.com"),
+                    (
+                        LDAP_ATTR_MAIL_ALTERNATIVE,
+                        "testperson1.alternative@example.com"
+                    ),
+                    (LDAP_ATTR_MAIL_PRIMARY, "testperson1@example.com"),
+                    (
+                        LDAP_ATTR_MAIL_ALTERNATIVE,
+                        "testperson1.alternative@example.com"
+                    )
+                );
+            }
+            _ => assert!(false),
+        };
+    }
+
+    #[idm_test]
+    async fn test_ldap_virtual_attribute_with_all_attr_search(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+    ) {
+        let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
+
+        let acct_uuid = uuid!("cc8e95b4-c24f-4d68-ba54-8bed76f63930");
+
+        // Setup a user we want to check.
+        {
+            let e1 = entry_init!(
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Name, Value::new_iname("testperson1")),
+                (Attribute::Uuid, Value::Uuid(acct_uuid)),
+                (Attribute::Description, Value::new_utf8s("testperson1")),
+                (Attribute::DisplayName, Value::new_utf8s("testperson1"))
+            );
+
+            let mut server_txn = idms.proxy_write(duration_from_epoch_now()).await;
+            assert!(server_txn
+                .qs_write
+                .internal_create(vec![e1])
+                .and_then(|_| server_txn.commit())
+                .is_ok());
+        }
+
+        // Setup the anonymous login.
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(anon_t.effective_session == LdapSession::UnixBind(UUID_ANONYMOUS));
+
+        // Check that when we request a virtual attr by name *and* all_attrs we get all the requested values.
+        let sr = SearchRequest {
+            msgid: 1,
+            base: "dc=example,dc=com".to_string(),
+            scope: LdapSearchScope::Subtree,
+            filter: LdapFilter::Equality(Attribute::Name.to_string(), "testperson1".to_string()),
+            attrs: vec![
+                "*".to_string(),
+                // Already being returned
+                LDAP_ATTR_NAME.to_string(),
+                // This is a virtual attribute
+                Attribute::EntryUuid.to_string(),
+            ],
+        };
+        let r1 = ldaps.do_search(idms, &sr, &anon_t).await.unwrap();
+
+        // The result, and the ldap proto success msg.
+        assert!(r1.len() == 2);
+        match &r1[0].op {
+            LdapOp::SearchResultEntry(lsre) => {
+                assert_entry_contains!(
+                    lsre,
+                    "spn=testperson1@example.com,dc=example,dc=com",
+                    (Attribute::Name, "testperson1"),
+                    (Attribute::DisplayName, "testperson1"),
+                    (Attribute::Uuid, "cc8e95b4-c24f-4d68-ba54-8bed76f63930"),
+                    (
+                        Attribute::EntryUuid.as_ref(),
+                        "cc8e95b4-c24f-4d68-ba54-8bed76f63930"
+                    )
+                );
+            }
+            _ => assert!(false),
+        };
+    }
+
+    #[idm_test]
+    async fn test_ldap_compare_request(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+        let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
+
+        // Setup a user and a group they are a member of.
+        {
+            let e1 = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Name, Value::new_iname("testperson1")),
+                (
+                    Attribute::Uuid,
+                    Value::Uuid(uuid!("cc8e95b4-c24f-4d68-ba54-8bed76f63930"))
+                ),
+                (
+                    Attribute::Mail,
+                    Value::EmailAddress("testperson1@example.com".to_string(), true)
+                ),
+                (Attribute::Description, Value::new_utf8s("testperson1")),
+                (Attribute::DisplayName, Value::new_utf8s("testperson1"))
+            );
+
+            let e2 = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname("testgroup1")),
+                (
+                    Attribute::Member,
+                    Value::Refer(uuid!("cc8e95b4-c24f-4d68-ba54-8bed76f63930"))
+                )
+            );
+
+            let mut server_txn = idms.proxy_write(duration_from_epoch_now()).await;
+            assert!(server_txn
+                .qs_write
+                .internal_create(vec![e1, e2])
+                .and_then(|_| server_txn.commit())
+                .is_ok());
+        }
+
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(anon_t.effective_session == LdapSession::UnixBind(UUID_ANONYMOUS));
+
+        let compare = |atype: &str, val: &str| CompareRequest {
+            msgid: 1,
+            dn: "spn=testperson1@example.com,dc=example,dc=com".to_string(),
+            atype: atype.to_string(),
+            val: val.to_string(),
+        };
+
+        macro_rules! assert_compare_code {
+            ($cr:expr, $code:expr) => {{
+                let r = ldaps.do_compare(idms, &$cr, &anon_t).await.unwrap();
+                match r.op {
+                    LdapOp::CompareResult(lcr) => assert!(lcr.code == $code),
+                    _ => assert!(false),
+                }
+            }};
+        }
+
+        // Group membership in both the returned and the normalised forms.
+        assert_compare_code!(
+            compare(
+                Attribute::MemberOf.as_ref(),
+                "spn=testgroup1@example.com,dc=example,dc=com"
+            ),
+            LdapResultCode::CompareTrue
+        );
+        assert_compare_code!(
+            compare(
+                Attribute::MemberOf.as_ref(),
+                "name=testgroup1,dc=example,dc=com"
+            ),
+            LdapResultCode::CompareTrue
+        );
+        assert_compare_code!(
+            compare(
+                Attribute::MemberOf.as_ref(),
+                "spn=idm_admins@example.com,dc=example,dc=com"
+            ),
+            LdapResultCode::CompareFalse
+        );
+
+        // Virtual attributes map the same as in a search.
+        assert_compare_code!(compare(ATTR_CN, "testperson1"), LdapResultCode::CompareTrue);
+        assert_compare_code!(
+            compare(
+                Attribute::EntryUuid.as_ref(),
+                "cc8e95b4-c24f-4d68-ba54-8bed76f63930"
+            ),
+            LdapResultCode::CompareTrue
+        );
+        assert_compare_code!(
+            compare(Attribute::DisplayName.as_ref(), "not testperson1"),
+            LdapResultCode::CompareFalse
+        );
+
+        // Absent, and access controlled attributes are both not present.
+        assert_compare_code!(
+            compare(Attribute::LoginShell.as_ref(), "/bin/zsh"),
+            LdapResultCode::NoSuchAttribute
+        );
+        assert_compare_code!(
+            compare(Attribute::Mail.as_ref(), "testperson1@example.com"),
+            LdapResultCode::NoSuchAttribute
+        );
+
+        // An entry that doesn't exist.
+        let cr = CompareRequest {
+            msgid: 1,
+            dn: "spn=claire@example.com,dc=example,dc=com".to_string(),
+            atype: Attribute::Name.to_string(),
+            val: "claire".to_string(),
+        };
+        assert_compare_code!(cr, LdapResultCode::NoSuchObject);
+    }
+
+    #[idm_test]
+    async fn test_ldap_paged_sorted_search(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+        let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
+
+        // Setup a number of users, created out of order.
+        {
+            let entries: Vec<_> = [
+                "testperson3",
+                "testperson1",
+                "testperson5",
+                "testperson2",
+                "testperson4",
+            ]
+            .into_iter()
+            .map(|name| {
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Person.to_value()),
+                    (Attribute::Class, EntryClass::Account.to_value()),
+                    (Attribute::Name, Value::new_iname(name)),
+                    (Attribute::Description, Value::new_utf8s(name)),
+                    (Attribute::DisplayName, Value::new_utf8s(name))
+                )
+            })
+            .collect();
+
+            let mut server_txn = idms.proxy_write(duration_from_epoch_now()).await;
+            assert!(server_txn
+                .qs_write
+                .internal_create(entries)
+                .and_then(|_| server_txn.commit())
+                .is_ok());
+        }
+
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let sr = SearchRequest {
+            msgid: 1,
+            base: "dc=example,dc=com".to_string(),
+            scope: LdapSearchScope::Subtree,
+            filter: LdapFilter::Or(
+                (1..=5)
+                    .map(|i| {
+                        LdapFilter::Equality(Attribute::Name.to_string(), format!("testperson{i}"))
+                    })
+                    .collect(),
+            ),
+            attrs: vec![LDAP_ATTR_NAME.to_string()],
+        };
+
+        let sort = LdapControl::ServerSideSortRequest {
+            keys: vec![LdapServerSideSortKey {
+                attribute_type: LDAP_ATTR_NAME.to_string(),
+                ordering_rule: None,
+                reverse_order: true,
+            }],
+        };
+
+        let mut cookie = Vec::with_capacity(0);
+        let mut names = Vec::new();
+        let mut pages = 0;
+
+        loop {
+            let ctrl = vec![
+                sort.clone(),
+                LdapControl::SimplePagedResults {
+                    size: 2,
+                    cookie: cookie.clone(),
+                },
+            ];
+            let r1 = ldaps
+                .do_search_ctrl(
+                    idms,
+                    &sr,
+                    &anon_t,
+                    &LdapSearchControls::from(ctrl.as_slice()),
+                )
+                .await
+                .unwrap();
+            pages += 1;
+
+            let (done, entries) = r1.split_last().unwrap();
+            assert!(entries.len() <= 2);
+            // Every page reports the result of the sort.
+            assert!(done.ctrl.iter().any(|c| matches!(
+                c,
+                LdapControl::ServerSideSortResult {
+                    result: LdapResultCode::Success,
+                    ..
+                }
+            )));
+            for msg in entries {
+                match &msg.op {
+                    LdapOp::SearchResultEntry(lsre) => names.push(lsre.dn.clone()),
+                    _ => assert!(false),
+                }
+            }
+
+            cookie = done
+                .ctrl
+                .iter()
+                .find_map(|c| match c {
+                    LdapControl::SimplePagedResults { cookie, .. } => Some(cookie.clone()),
+                    _ => None,
+                })
+                .expect("No paged results control in response");
+
+            if cookie.is_empty() {
+                break;
+            }
+        }
+
+        assert!(pages == 3);
+        assert!(
+            names
+                == vec![
+                    "spn=testperson5@example.com,dc=example,dc=com",
+                    "spn=testperson4@example.com,dc=example,dc=com",
+                    "spn=testperson3@example.com,dc=example,dc=com",
+                    "spn=testperson2@example.com,dc=example,dc=com",
+                    "spn=testperson1@example.com,dc=example,dc=com",
+                ]
+        );
+
+        // A new paged search must request at least one entry.
+        let ctrl = vec![LdapControl::SimplePagedResults {
+            size: 0,
+            cookie: Vec::with_capacity(0),
+        }];
+        let r1 = ldaps
+            .do_search_ctrl(
+                idms,
+                &sr,
+                &anon_t,
+                &LdapSearchControls::from(ctrl.as_slice()),
+            )
+            .await
+            .unwrap();
+        assert!(r1.len() == 1);
+        assert!(matches!(
+            &r1[0].op,
+            LdapOp::SearchResultDone(res) if res.code == LdapResultCode::UnwillingToPerform
+        ));
+
+        // A cookie can't be used with a different search.
+        let ctrl = vec![LdapControl::SimplePagedResults {
+            size: 2,
+            cookie: Vec::with_capacity(0),
+        }];
+        let r1 = ldaps
+            .do_search_ctrl(
+                idms,
+                &sr,
+                &anon_t,
+                &LdapSearchControls::from(ctrl.as_slice()),
+            )
+            .await
+            .unwrap();
+        let cookie = r1
+            .last()
+            .and_then(|done| {
+                done.ctrl.iter().find_map(|c| match c {
+                    LdapControl::SimplePagedResults { cookie, .. } => Some(cookie.clone()),
+                    _ => None,
+                })
+            })
+            .expect("No paged results control in response");
+        assert!(!cookie.is_empty());
+
+        let sr_other = SearchRequest {
+            filter: LdapFilter::Equality(Attribute::Class.to_string(), "person".to_string()),
+            ..sr.clone()
+        };
+        let ctrl = vec![LdapControl::SimplePagedResults { size: 2, cookie }];
+        assert!(ldaps
+            .do_search_ctrl(
+                idms,
+                &sr_other,
+                &anon_t,
+                &LdapSearchControls::from(ctrl.as_slice())
+            )
+            .await
+            .is_err());
+
+        // Nor with a different sort, as the remaining entries are already ordered.
+        let ctrl = vec![LdapControl::SimplePagedResults {
+            size: 2,
+            cookie: Vec::with_capacity(0),
+        }];
+        let r1 = ldaps
+            .do_search_ctrl(
+                idms,
+                &sr,
+                &anon_t,
+                &LdapSearchControls::from(ctrl.as_slice()),
+            )
+            .await
+            .unwrap();
+        let cookie = r1
+            .last()
+            .and_then(|done| {
+                done.ctrl.iter().find_map(|c| match c {
+                    LdapControl::SimplePagedResults { cookie, .. } => Some(cookie.clone()),
+                    _ => None,
+                })
+            })
+            .expect("No paged results control in response");
+        let ctrl = vec![
+            sort.clone(),
+            LdapControl::SimplePagedResults { size: 2, cookie },
+        ];
+        assert!(ldaps
+            .do_search_ctrl(
+                idms,
+                &sr,
+                &anon_t,
+                &LdapSearchControls::from(ctrl.as_slice())
+            )
+            .await
+            .is_err());
+    }
+
+    #[idm_test]
+    async fn test_ldap_search_filter_types(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+        let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
+
+        let p1_uuid = Uuid::new_v4();
+        let g1_uuid = Uuid::new_v4();
+        {
+            let e1 = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::PosixAccount.to_value()),
+                (Attribute::Name, Value::new_iname("testperson1")),
+                (Attribute::Uuid, Value::Uuid(p1_uuid)),
+                (Attribute::DisplayName, Value::new_utf8s("Test Person One")),
+                (Attribute::LegalName, Value::new_utf8s("Test Person One")),
+                (Attribute::GidNumber, Value::new_uint32(12345))
+            );
+            let e2 = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::PosixAccount.to_value()),
+                (Attribute::Name, Value::new_iname("testperson2")),
+                (Attribute::DisplayName, Value::new_utf8s("Test Person Two")),
+                (Attribute::GidNumber, Value::new_uint32(23456))
+            );
+            let g1 = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname("testgroup_inner")),
+                (Attribute::Uuid, Value::Uuid(g1_uuid)),
+                (Attribute::Member, Value::Refer(p1_uuid))
+            );
+            let g2 = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname("testgroup_outer")),
+                (Attribute::Member, Value::Refer(g1_uuid))
+            );
+
+            let mut server_txn = idms.proxy_write(duration_from_epoch_now()).await;
+            assert!(server_txn
+                .qs_write
+                .internal_create(vec![e1, e2, g1, g2])
+                .and_then(|_| server_txn.commit())
+                .is_ok());
+        }
 
-        // Bind using the token as a DN
-        let sa_lbt = ldaps
-            .do_bind(idms, "dn=token", &apitoken)
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
             .await
             .unwrap()
             .unwrap();
-        assert!(sa_lbt.effective_session == LdapSession::ApiToken(apitoken_inner.clone()));
 
-        // Bind using the token as a pw
-        let sa_lbt = ldaps.do_bind(idms, "", &apitoken).await.unwrap().unwrap();
-        assert!(sa_lbt.effective_session == LdapSession::ApiToken(apitoken_inner));
+        let substring = |a: &str, initial: Option<&str>, any: &[&str], final_: Option<&str>| {
+            LdapFilter::Substring(
+                a.to_string(),
+                LdapSubstringFilter {
+                    initial: initial.map(str::to_string),
+                    any: any.iter().map(|s| s.to_string()).collect(),
+                    final_: final_.map(str::to_string),
+                },
+            )
+        };
+        let extensible = |rule: &str, a: &str, v: &str| {
+            LdapFilter::Extensible(LdapMatchingRuleAssertion {
+                matching_rule: Some(rule.to_string()),
+                type_: Some(a.to_string()),
+                match_value: v.to_string(),
+                dn_attributes: false,
+            })
+        };
+        // Limit each search to the entries of this test.
+        let only_test =
+            |f: LdapFilter| LdapFilter::And(vec![f, substring("name", Some("test"), &[], None)]);
+
+        let p1 = "spn=testperson1@example.com,dc=example,dc=com";
+        let p2 = "spn=testperson2@example.com,dc=example,dc=com";
+        let g1 = "spn=testgroup_inner@example.com,dc=example,dc=com";
+
+        let cases = vec![
+            // Substrings are case insensitive on names.
+            (
+                substring("name", Some("TestPers"), &[], Some("1")),
+                vec![p1],
+            ),
+            (substring("name", None, &["person"], None), vec![p1, p2]),
+            (
+                substring("displayName", Some("Test"), &["Person", "T"], None),
+                vec![p2],
+            ),
+            // ... but sensitive on utf8 strings.
+            (substring("displayName", Some("test"), &[], None), vec![]),
+            // Ordering, where uidnumber is an alias of gidnumber.
+            (
+                only_test(LdapFilter::GreaterOrEqual(
+                    "uidNumber".to_string(),
+                    "23456".to_string(),
+                )),
+                vec![p2],
+            ),
+            (
+                only_test(LdapFilter::LessOrEqual(
+                    "gidNumber".to_string(),
+                    "20000".to_string(),
+                )),
+                vec![p1],
+            ),
+            // Strings are ordered by their bytes.
+            (
+                only_test(LdapFilter::GreaterOrEqual(
+                    "displayName".to_string(),
+                    "Test Person T".to_string(),
+                )),
+                vec![p2],
+            ),
+            (
+                only_test(LdapFilter::LessOrEqual(
+                    "displayName".to_string(),
+                    "Test Person One".to_string(),
+                )),
+                vec![p1],
+            ),
+            (
+                LdapFilter::Approx("displayName".to_string(), "test.person ONE".to_string()),
+                vec![p1],
+            ),
+            (
+                extensible("caseIgnoreMatch", "displayName", "TEST PERSON TWO"),
+                vec![p2],
+            ),
+            (
+                extensible("2.5.13.5", "displayName", "TEST PERSON TWO"),
+                vec![],
+            ),
+            // Membership is transitive through the inner group.
+            (
+                only_test(extensible(
+                    "1.2.840.113556.1.4.1941",
+                    "memberOf",
+                    "spn=testgroup_outer@example.com,dc=example,dc=com",
+                )),
+                vec![p1, g1],
+            ),
+            // Anonymous can't read legal names, so can't search them either.
+            (substring("legalName", Some("Test"), &[], None), vec![]),
+        ];
+
+        for (filter, expect) in cases {
+            let sr = SearchRequest {
+                msgid: 1,
+                base: "dc=example,dc=com".to_string(),
+                scope: LdapSearchScope::Subtree,
+                filter: filter.clone(),
+                attrs: vec![LDAP_ATTR_NAME.to_string()],
+            };
+            let r1 = ldaps.do_search(idms, &sr, &anon_t).await.unwrap();
+            let mut dns: Vec<_> = r1
+                .iter()
+                .filter_map(|msg| match &msg.op {
+                    LdapOp::SearchResultEntry(lsre) => Some(lsre.dn.as_str()),
+                    _ => None,
+                })
+                .collect();
+            dns.sort_unstable();
+            assert_eq!(dns, expect, "{:?}", filter);
+        }
 
-        // Search and retrieve mail that's now accessible.
-        let r1 = ldaps.do_search(idms, &sr, &sa_lbt).await.unwrap();
-        assert!(r1.len() == 2);
-        match &r1[0].op {
-            LdapOp::SearchResultEntry(lsre) => {
-                assert_entry_contains!(
-                    lsre,
-                    "spn=testperson1@example.com,dc=example,dc=com",
-                    (Attribute::Name.as_ref(), "testperson1"),
-                    (Attribute::Mail.as_ref(), "testperson1@example.com"),
-                    (
-                        Attribute::Mail.as_ref(),
-                        "testperson1.alternative@example.com"
-                    ),
-                    (LDAP_ATTR_MAIL_PRIMARY, "testperson1@example.com"),
-                    (
-                        LDAP_ATTR_MAIL_ALTERNATIVE,
-                        "testperson1.alternative@example.com"
-                    ),
-                    (LDAP_ATTR_MAIL_PRIMARY, "testperson1@example.com"),
-                    (
-                        LDAP_ATTR_MAIL_ALTERNATIVE,
-                        "testperson1.alternative@example.com"
-                    )
-                );
-            }
-            _ => assert!(false),
+        // Transitive membership can only be asserted through memberof.
+        let sr = SearchRequest {
+            msgid: 1,
+            base: "dc=example,dc=com".to_string(),
+            scope: LdapSearchScope::Subtree,
+            filter: extensible("1.2.840.113556.1.4.1941", "member", "testperson1"),
+            attrs: vec![LDAP_ATTR_NAME.to_string()],
         };
+        assert!(ldaps.do_search(idms, &sr, &anon_t).await.is_err());
     }
 
     #[idm_test]
-    async fn test_ldap_virtual_attribute_with_all_attr_search(
-        idms: &IdmServer,
-        _idms_delayed: &IdmServerDelayed,
-    ) {
+    async fn test_ldap_automount_search(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
         let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
 
-        let acct_uuid = uuid!("cc8e95b4-c24f-4d68-ba54-8bed76f63930");
-
-        // Setup a user we want to check.
+        let map_uuid = Uuid::new_v4();
+        let key_uuid = Uuid::new_v4();
         {
-            let e1 = entry_init!(
-                (Attribute::Class, EntryClass::Person.to_value()),
-                (Attribute::Class, EntryClass::Account.to_value()),
-                (Attribute::Name, Value::new_iname("testperson1")),
-                (Attribute::Uuid, Value::Uuid(acct_uuid)),
-                (Attribute::Description, Value::new_utf8s("testperson1")),
-                (Attribute::DisplayName, Value::new_utf8s("testperson1"))
+            let e_map = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::AutomountMap.to_value()),
+                (Attribute::Uuid, Value::Uuid(map_uuid)),
+                (Attribute::Name, Value::new_iname("auto.home"))
+            );
+            let e_key = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Automount.to_value()),
+                (Attribute::Uuid, Value::Uuid(key_uuid)),
+                (Attribute::AutomountMap, Value::Refer(map_uuid)),
+                (Attribute::AutomountKey, Value::new_utf8s("*")),
+                (
+                    Attribute::AutomountInformation,
+                    Value::new_utf8s("-rw nfs.example.com:/home/&")
+                )
             );
 
             let mut server_txn = idms.proxy_write(duration_from_epoch_now()).await;
             assert!(server_txn
                 .qs_write
-                .internal_create(vec![e1])
+                .internal_create(vec![e_map, e_key])
                 .and_then(|_| server_txn.commit())
                 .is_ok());
         }
 
-        // Setup the anonymous login.
-        let anon_t = ldaps.do_bind(idms, "", "").await.unwrap().unwrap();
-        assert!(anon_t.effective_session == LdapSession::UnixBind(UUID_ANONYMOUS));
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
 
-        // Check that when we request a virtual attr by name *and* all_attrs we get all the requested values.
+        // autofs first finds the map by name.
         let sr = SearchRequest {
             msgid: 1,
             base: "dc=example,dc=com".to_string(),
             scope: LdapSearchScope::Subtree,
-            filter: LdapFilter::Equality(Attribute::Name.to_string(), "testperson1".to_string()),
-            attrs: vec![
-                "*".to_string(),
-                // Already being returned
-                LDAP_ATTR_NAME.to_string(),
-                // This is a virtual attribute
-                Attribute::EntryUuid.to_string(),
-            ],
+            filter: LdapFilter::And(vec![
+                LdapFilter::Equality("objectClass".to_string(), "automountMap".to_string()),
+                LdapFilter::Equality("automountMapName".to_string(), "auto.home".to_string()),
+            ]),
+            attrs: vec!["automountMapName".to_string()],
         };
         let r1 = ldaps.do_search(idms, &sr, &anon_t).await.unwrap();
-
-        // The result, and the ldap proto success msg.
-        assert!(r1.len() == 2);
+        assert_eq!(r1.len(), 2);
         match &r1[0].op {
             LdapOp::SearchResultEntry(lsre) => {
                 assert_entry_contains!(
                     lsre,
-                    "spn=testperson1@example.com,dc=example,dc=com",
-                    (Attribute::Name, "testperson1"),
-                    (Attribute::DisplayName, "testperson1"),
-                    (Attribute::Uuid, "cc8e95b4-c24f-4d68-ba54-8bed76f63930"),
-                    (
-                        Attribute::EntryUuid.as_ref(),
-                        "cc8e95b4-c24f-4d68-ba54-8bed76f63930"
-                    )
+                    "name=auto.home,dc=example,dc=com",
+                    (ATTR_LDAP_AUTOMOUNT_MAP_NAME, "auto.home")
                 );
             }
-            _ => assert!(false),
+            _ => panic!("Oh no"),
         };
+
+        // Then the keys of the map, which are children of the map entry.
+        for (base, scope) in [
+            ("name=auto.home,dc=example,dc=com", LdapSearchScope::Subtree),
+            (
+                "automountMapName=auto.home,dc=example,dc=com",
+                LdapSearchScope::OneLevel,
+            ),
+            (
+                "automountMapName=auto.home,dc=example,dc=com",
+                LdapSearchScope::Children,
+            ),
+        ] {
+            let sr = SearchRequest {
+                msgid: 1,
+                base: base.to_string(),
+                scope,
+                filter: LdapFilter::Equality("objectClass".to_string(), "automount".to_string()),
+                attrs: vec![
+                    "automountKey".to_string(),
+                    "automountInformation".to_string(),
+                ],
+            };
+            let r1 = ldaps.do_search(idms, &sr, &anon_t).await.unwrap();
+            assert_eq!(r1.len(), 2);
+            match &r1[0].op {
+                LdapOp::SearchResultEntry(lsre) => {
+                    assert_entry_contains!(
+                        lsre,
+                        format!("uuid={key_uuid},dc=example,dc=com"),
+                        (ATTR_LDAP_AUTOMOUNT_KEY, "*"),
+                        (
+                            ATTR_LDAP_AUTOMOUNT_INFORMATION,
+                            "-rw nfs.example.com:/home/&"
+                        )
+                    );
+                }
+                _ => panic!("Oh no"),
+            };
+        }
+
+        // Searches below any other rdn are unchanged, and find nothing as only automount
+        // maps have children.
+        for scope in [LdapSearchScope::OneLevel, LdapSearchScope::Children] {
+            let sr = SearchRequest {
+                msgid: 1,
+                base: "name=auto.home,dc=example,dc=com".to_string(),
+                scope,
+                filter: LdapFilter::Present(Attribute::ObjectClass.to_string()),
+                attrs: vec![],
+            };
+            let r1 = ldaps.do_search(idms, &sr, &anon_t).await.unwrap();
+            assert_eq!(r1.len(), 1);
+            assert!(matches!(r1[0].op, LdapOp::SearchResultDone(_)));
+        }
     }
 
     #[idm_test]
     async fn test_ldap_rootdse_basedn_change(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
         let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
 
-        let anon_t = ldaps.do_bind(idms, "", "").await.unwrap().unwrap();
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
         assert!(anon_t.effective_session == LdapSession::UnixBind(UUID_ANONYMOUS));
 
         let sr = SearchRequest {
@@ -1250,7 +3281,11 @@ mod tests {
         // Now re-test
         let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
 
-        let anon_t = ldaps.do_bind(idms, "", "").await.unwrap().unwrap();
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
         assert!(anon_t.effective_session == LdapSession::UnixBind(UUID_ANONYMOUS));
 
         let sr = SearchRequest {
@@ -1280,4 +3315,309 @@ mod tests {
             _ => assert!(false),
         };
     }
+
+    #[idm_test(audit)]
+    async fn test_ldap_write_operations(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
+
+        let sa_uuid = uuid::uuid!("8a2ad8b4-6a56-4a37-9e0c-0e7f3c0b8f21");
+        let person_uuid = uuid::uuid!("cc8e95b4-c24f-4d68-ba54-8bed76f63930");
+
+        let apitoken = {
+            let e1 = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Uuid, Value::Uuid(sa_uuid)),
+                (Attribute::Name, Value::new_iname("service_write_test")),
+                (
+                    Attribute::DisplayName,
+                    Value::new_utf8s("service_write_test")
+                )
+            );
+
+            let e2 = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::PosixAccount.to_value()),
+                (Attribute::Uuid, Value::Uuid(person_uuid)),
+                (Attribute::Name, Value::new_iname("testperson1")),
+                (Attribute::DisplayName, Value::new_utf8s("testperson1")),
+                (Attribute::GidNumber, Value::new_uint32(12345678))
+            );
+
+            let ct = duration_from_epoch_now();
+            let mut server_txn = idms.proxy_write(ct).await;
+            let ce = CreateEvent::new_internal(vec![e1, e2]);
+            assert!(server_txn.qs_write.create(&ce).is_ok());
+
+            // Allow the service account to create, modify and delete groups.
+            let me = ModifyEvent::new_internal_invalid(
+                filter!(f_eq(
+                    Attribute::Uuid,
+                    PartialValue::Uuid(UUID_IDM_GROUP_MANAGE_PRIV)
+                )),
+                ModifyList::new_list(vec![Modify::Present(
+                    Attribute::Member.into(),
+                    Value::Refer(sa_uuid),
+                )]),
+            );
+            assert!(server_txn.qs_write.modify(&me).is_ok());
+
+            let pce = UnixPasswordChangeEvent::new_internal(person_uuid, TEST_PASSWORD);
+            assert!(server_txn.set_unix_account_password(&pce).is_ok());
+
+            let gte = GenerateApiTokenEvent {
+                read_write: true,
+                ..GenerateApiTokenEvent::new_internal(sa_uuid, "TestToken", None)
+            };
+
+            let apitoken = server_txn
+                .service_account_generate_api_token(&gte, ct)
+                .expect("Failed to create new apitoken");
+
+            assert!(server_txn.commit().is_ok());
+
+            apitoken
+        };
+
+        let anon_t = ldaps
+            .do_bind(idms, "", "", Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
+        let sa_t = ldaps
+            .do_bind(idms, "", &apitoken, Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
+        let person_t = ldaps
+            .do_bind(idms, "testperson1", TEST_PASSWORD, Source::Internal)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(person_t.effective_session == LdapSession::UnixBind(person_uuid));
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, sa_uuid),
+            _ => assert!(false),
+        }
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, person_uuid),
+            _ => assert!(false),
+        }
+
+        macro_rules! assert_write_code {
+            ($lbt:expr, $op:expr, $code:expr) => {{
+                let wr = LdapWriteRequest { msgid: 1, op: $op };
+                match ldaps
+                    .do_write_op(idms, wr, Some($lbt.clone()), Source::Internal)
+                    .await
+                    .unwrap()
+                {
+                    LdapResponseState::Respond(LdapMsg {
+                        op:
+                            LdapOp::AddResponse(res)
+                            | LdapOp::ModifyResponse(res)
+                            | LdapOp::DelResponse(res)
+                            | LdapOp::ModifyDNResponse(res),
+                        ..
+                    }) => assert!(res.code == $code),
+                    LdapResponseState::Respond(LdapMsg {
+                        op: LdapOp::ExtendedResponse(ler),
+                        ..
+                    }) => assert!(ler.res.code == $code),
+                    _ => assert!(false),
+                }
+            }};
+        }
+
+        let add_group = || {
+            LdapWriteOp::Add(LdapAddRequest {
+                dn: "cn=testgroup1,dc=example,dc=com".to_string(),
+                attributes: vec![
+                    LdapAttribute {
+                        atype: "objectClass".to_string(),
+                        vals: vec!["top".as_bytes().to_vec(), "group".as_bytes().to_vec()],
+                    },
+                    LdapAttribute {
+                        atype: Attribute::Description.to_string(),
+                        vals: vec!["testgroup1".as_bytes().to_vec()],
+                    },
+                ],
+            })
+        };
+
+        // Anonymous and unix binds are read only.
+        assert_write_code!(anon_t, add_group(), LdapResultCode::InsufficentAccessRights);
+        assert_write_code!(
+            person_t,
+            add_group(),
+            LdapResultCode::InsufficentAccessRights
+        );
+        for expected in [UUID_ANONYMOUS, person_uuid] {
+            match idms_audit.audit_rx().try_recv() {
+                Ok(AuditEvent::AccessDenied {
+                    identity: Some(identity),
+                    operation: AuditOperation::Create,
+                    ..
+                }) => assert_eq!(identity, expected),
+                _ => assert!(false),
+            }
+        }
+        assert_write_code!(sa_t, add_group(), LdapResultCode::Success);
+
+        // Add a member by the dn returned in searches.
+        assert_write_code!(
+            sa_t,
+            LdapWriteOp::Modify(LdapModifyRequest {
+                dn: "spn=testgroup1@example.com,dc=example,dc=com".to_string(),
+                changes: vec![LdapModify {
+                    operation: LdapModifyType::Add,
+                    modification: LdapPartialAttribute {
+                        atype: Attribute::Member.to_string(),
+                        vals: vec!["spn=testperson1@example.com,dc=example,dc=com"
+                            .as_bytes()
+                            .to_vec()],
+                    },
+                }],
+            }),
+            LdapResultCode::Success
+        );
+
+        let cr = CompareRequest {
+            msgid: 1,
+            dn: "spn=testgroup1@example.com,dc=example,dc=com".to_string(),
+            atype: Attribute::Member.to_string(),
+            val: "spn=testperson1@example.com,dc=example,dc=com".to_string(),
+        };
+        let r = ldaps.do_compare(idms, &cr, &anon_t).await.unwrap();
+        assert!(
+            matches!(r.op, LdapOp::CompareResult(lcr) if lcr.code == LdapResultCode::CompareTrue)
+        );
+
+        // Attributes synthesised for ldap can't be written.
+        assert_write_code!(
+            sa_t,
+            LdapWriteOp::Modify(LdapModifyRequest {
+                dn: "cn=testgroup1,dc=example,dc=com".to_string(),
+                changes: vec![LdapModify {
+                    operation: LdapModifyType::Replace,
+                    modification: LdapPartialAttribute {
+                        atype: "entrydn".to_string(),
+                        vals: vec!["cn=other,dc=example,dc=com".as_bytes().to_vec()],
+                    },
+                }],
+            }),
+            LdapResultCode::InvalidAttributeSyntax
+        );
+
+        // Rename, but the directory is flat so entries can't be moved.
+        assert_write_code!(
+            sa_t,
+            LdapWriteOp::ModifyDn(LdapModifyDNRequest {
+                dn: "cn=testgroup1,dc=example,dc=com".to_string(),
+                newrdn: "cn=testgroup2".to_string(),
+                deleteoldrdn: true,
+                new_superior: Some("ou=groups,dc=example,dc=com".to_string()),
+            }),
+            LdapResultCode::UnwillingToPerform
+        );
+        assert_write_code!(
+            sa_t,
+            LdapWriteOp::ModifyDn(LdapModifyDNRequest {
+                dn: "cn=testgroup1,dc=example,dc=com".to_string(),
+                newrdn: "cn=testgroup2".to_string(),
+                deleteoldrdn: true,
+                new_superior: None,
+            }),
+            LdapResultCode::Success
+        );
+
+        assert_write_code!(
+            sa_t,
+            LdapWriteOp::Delete("cn=testgroup1,dc=example,dc=com".to_string()),
+            LdapResultCode::NoSuchObject
+        );
+        assert_write_code!(
+            sa_t,
+            LdapWriteOp::Delete("cn=testgroup2,dc=example,dc=com".to_string()),
+            LdapResultCode::Success
+        );
+
+        // Password modify checks the current password and the quality of the new one.
+        let password_modify = |old: &str, new: &str| {
+            LdapWriteOp::PasswordModify(LdapPasswordModifyRequest {
+                user_identity: Some("spn=testperson1@example.com,dc=example,dc=com".to_string()),
+                old_password: Some(old.to_string()),
+                new_password: Some(new.to_string()),
+            })
+        };
+
+        assert_write_code!(
+            anon_t,
+            password_modify(TEST_PASSWORD, "password"),
+            LdapResultCode::ConstraintViolation
+        );
+        assert_write_code!(
+            anon_t,
+            password_modify(TEST_PASSWORD, TEST_PASSWORD_NEW),
+            LdapResultCode::Success
+        );
+        assert!(ldaps
+            .do_bind(idms, "testperson1", TEST_PASSWORD_NEW, Source::Internal)
+            .await
+            .unwrap()
+            .is_some());
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, person_uuid),
+            _ => assert!(false),
+        }
+
+        // A unix bind only grants read, so it can't change a password without supplying the
+        // current one.
+        assert_write_code!(
+            person_t,
+            LdapWriteOp::Modify(LdapModifyRequest {
+                dn: "spn=testperson1@example.com,dc=example,dc=com".to_string(),
+                changes: vec![LdapModify {
+                    operation: LdapModifyType::Replace,
+                    modification: LdapPartialAttribute {
+                        atype: "userPassword".to_string(),
+                        vals: vec![TEST_PASSWORD.as_bytes().to_vec()],
+                    },
+                }],
+            }),
+            LdapResultCode::InsufficentAccessRights
+        );
+        assert_write_code!(
+            person_t,
+            LdapWriteOp::PasswordModify(LdapPasswordModifyRequest {
+                user_identity: None,
+                old_password: None,
+                new_password: Some(TEST_PASSWORD.to_string()),
+            }),
+            LdapResultCode::InsufficentAccessRights
+        );
+        assert!(ldaps
+            .do_bind(idms, "testperson1", TEST_PASSWORD, Source::Internal)
+            .await
+            .unwrap()
+            .is_none());
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationDenied { uuid, .. }) => assert_eq!(uuid, person_uuid),
+            _ => assert!(false),
+        }
+
+        // This is last, as the failure softlocks the account.
+        assert_write_code!(
+            anon_t,
+            password_modify("wrong password", TEST_PASSWORD),
+            LdapResultCode::InvalidCredentials
+        );
+    }
 }
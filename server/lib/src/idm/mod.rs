@@ -3,23 +3,30 @@
 //! actions in the [QueryServer](crate::server::QueryServer). Generally this is where "Identity Management" policy and code
 //! is implemented.
 
+pub mod accessrequest;
+pub mod accessreview;
 pub mod account;
 pub(crate) mod applinks;
 pub mod audit;
 pub(crate) mod authsession;
+pub(crate) mod automount;
 pub mod credupdatesession;
 pub mod delayed;
 pub mod event;
 pub mod group;
+pub(crate) mod hbac;
 pub mod identityverification;
 pub mod ldap;
 pub mod oauth2;
 pub(crate) mod radius;
 pub(crate) mod reauth;
 pub mod scim;
+pub mod scim_v2;
 pub mod server;
 pub mod serviceaccount;
+pub(crate) mod sudo;
 pub(crate) mod unix;
+pub(crate) mod unixpolicy;
 
 use std::fmt;
 
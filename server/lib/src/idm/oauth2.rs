@@ -22,7 +22,8 @@ use kanidm_proto::constants::*;
 
 pub use kanidm_proto::oauth2::{
     AccessTokenIntrospectRequest, AccessTokenIntrospectResponse, AccessTokenRequest,
-    AccessTokenResponse, AuthorisationRequest, CodeChallengeMethod, ErrorResponse, GrantTypeReq,
+    AccessTokenResponse, ActorClaim, AuthorisationRequest, CodeChallengeMethod,
+    DeviceAuthorizationRequest, DeviceAuthorizationResponse, ErrorResponse, GrantTypeReq,
     OidcDiscoveryResponse, TokenRevokeRequest,
 };
 use kanidm_proto::oauth2::{
@@ -31,15 +32,18 @@ use kanidm_proto::oauth2::{
 };
 use kanidm_proto::v1::UserAuthToken;
 use openssl::sha;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tracing::trace;
 use url::{Origin, Url};
 
 use crate::idm::account::Account;
+use crate::idm::audit::AuditEvent;
 use crate::idm::server::{
     IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction, IdmServerTransaction,
 };
+use crate::metrics::METRICS;
 use crate::prelude::*;
 use crate::value::{Oauth2Session, SessionState, OAUTHSCOPE_RE};
 
@@ -64,6 +68,12 @@ pub enum Oauth2Error {
     InsufficientScope,
     // from https://datatracker.ietf.org/doc/html/rfc7009#section-2.2.1
     UnsupportedTokenType,
+    // from https://datatracker.ietf.org/doc/html/rfc8628#section-3.5
+    AuthorizationPending,
+    SlowDown,
+    ExpiredToken,
+    // from https://datatracker.ietf.org/doc/html/rfc8693#section-2.2.2
+    InvalidTarget,
 }
 
 impl std::fmt::Display for Oauth2Error {
@@ -83,6 +93,10 @@ impl std::fmt::Display for Oauth2Error {
             Oauth2Error::InvalidToken => "invalid_token",
             Oauth2Error::InsufficientScope => "insufficient_scope",
             Oauth2Error::UnsupportedTokenType => "unsupported_token_type",
+            Oauth2Error::AuthorizationPending => "authorization_pending",
+            Oauth2Error::SlowDown => "slow_down",
+            Oauth2Error::ExpiredToken => "expired_token",
+            Oauth2Error::InvalidTarget => "invalid_target",
         })
     }
 }
@@ -108,6 +122,116 @@ struct ConsentToken {
     pub nonce: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct DeviceConsentToken {
+    pub client_id: String,
+    // Must match the session id of the Uat,
+    pub session_id: Uuid,
+    // So we can ensure that we really match the same uat to prevent confusions.
+    pub ident_id: IdentityId,
+    // The device authorisation being consented to.
+    pub user_code: String,
+    // The scopes being granted
+    pub scopes: BTreeSet<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct DeviceCode {
+    // We don't need the client_id here, because it's encrypted with an RS specific
+    // key which gives us the assurance that it's the correct combination.
+    pub user_code: String,
+}
+
+const OAUTH2_DEVICE_STATE_PENDING: &str = "pending";
+const OAUTH2_DEVICE_STATE_APPROVED: &str = "approved";
+const OAUTH2_DEVICE_STATE_DENIED: &str = "denied";
+
+#[derive(Debug)]
+enum Oauth2DeviceState {
+    // Waiting for the user to enter the user code and consent.
+    Pending,
+    Approved {
+        account_uuid: Uuid,
+        parent_session_id: Uuid,
+        parent_expiry: Option<OffsetDateTime>,
+    },
+    Denied,
+}
+
+/// An in progress device authorisation. These are stored as entries so that they survive a
+/// restart, and so that the user and the device may reach different servers.
+#[derive(Debug)]
+struct Oauth2DeviceSession {
+    uuid: Uuid,
+    client_uuid: Option<Uuid>,
+    // Once approved, these are the scopes that were granted.
+    scopes: BTreeSet<String>,
+    expiry: OffsetDateTime,
+    state: Oauth2DeviceState,
+}
+
+impl Oauth2DeviceSession {
+    fn try_from_entry(entry: &EntrySealedCommitted) -> Result<Self, OperationError> {
+        let invalid = || {
+            admin_error!(uuid = ?entry.get_uuid(), "oauth2 device authorisation is invalid");
+            OperationError::InvalidEntryState
+        };
+
+        let state = match entry
+            .get_ava_single_proto_string(Attribute::OAuth2DeviceState)
+            .as_deref()
+        {
+            Some(OAUTH2_DEVICE_STATE_PENDING) => Oauth2DeviceState::Pending,
+            Some(OAUTH2_DEVICE_STATE_APPROVED) => Oauth2DeviceState::Approved {
+                account_uuid: entry
+                    .get_ava_single_refer(Attribute::OAuth2DeviceAccount)
+                    .ok_or_else(invalid)?,
+                parent_session_id: entry
+                    .get_ava_single_uuid(Attribute::OAuth2DeviceParentSession)
+                    .ok_or_else(invalid)?,
+                parent_expiry: entry.get_ava_single_datetime(Attribute::OAuth2DeviceParentExpiry),
+            },
+            Some(OAUTH2_DEVICE_STATE_DENIED) => Oauth2DeviceState::Denied,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Oauth2DeviceSession {
+            uuid: entry.get_uuid(),
+            client_uuid: entry.get_ava_single_refer(Attribute::OAuth2DeviceClient),
+            scopes: entry
+                .get_ava_as_oauthscopes(Attribute::OAuth2DeviceScope)
+                .map(|scopes| scopes.map(str::to_string).collect())
+                .unwrap_or_default(),
+            expiry: entry
+                .get_ava_single_datetime(Attribute::OAuth2DeviceExpiry)
+                .ok_or_else(invalid)?,
+            state,
+        })
+    }
+}
+
+fn oauth2_device_session_get<'a, TXN>(
+    qs: &mut TXN,
+    user_code: &str,
+) -> Result<Option<Oauth2DeviceSession>, OperationError>
+where
+    TXN: QueryServerTransaction<'a>,
+{
+    qs.internal_search(filter!(f_and!([
+        f_eq(
+            Attribute::Class,
+            EntryClass::OAuth2DeviceAuthorisation.into()
+        ),
+        f_eq(
+            Attribute::OAuth2DeviceUserCode,
+            PartialValue::new_iutf8(user_code)
+        )
+    ])))?
+    .first()
+    .map(|entry| Oauth2DeviceSession::try_from_entry(entry))
+    .transpose()
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct TokenExchangeCode {
     // We don't need the client_id here, because it's signed with an RS specific
@@ -137,6 +261,9 @@ pub(crate) enum Oauth2TokenType {
         auth_time: Option<i64>,
         // We stash some details here for oidc.
         nonce: Option<String>,
+        // The delegation chain if this token was issued by a token exchange.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        act: Option<ActorClaim>,
     },
     Refresh {
         scopes: BTreeSet<String>,
@@ -151,6 +278,17 @@ pub(crate) enum Oauth2TokenType {
         // We stash some details here for oidc.
         nonce: Option<String>,
     },
+    // An access token issued by the client credentials grant. The subject is
+    // the resource server itself, so there is no account or session to check.
+    ClientAccess {
+        scopes: BTreeSet<String>,
+        session_id: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        expiry: time::OffsetDateTime,
+        uuid: Uuid,
+        iat: i64,
+        nbf: i64,
+    },
 }
 
 impl fmt::Display for Oauth2TokenType {
@@ -162,6 +300,9 @@ impl fmt::Display for Oauth2TokenType {
             Oauth2TokenType::Refresh { session_id, .. } => {
                 write!(f, "refresh_token ({session_id}) ")
             }
+            Oauth2TokenType::ClientAccess { session_id, .. } => {
+                write!(f, "client_access_token ({session_id}) ")
+            }
         }
     }
 }
@@ -225,6 +366,12 @@ pub struct Oauth2RS {
     origin_https: bool,
     scope_maps: BTreeMap<Uuid, BTreeSet<String>>,
     sup_scope_maps: BTreeMap<Uuid, BTreeSet<String>>,
+    // The scopes this rs may be granted for itself via client credentials.
+    client_credentials_scopes: BTreeSet<String>,
+    // Can this rs use the device authorisation grant?
+    device_flow_enable: bool,
+    // The resource servers that may exchange their tokens for a token to this rs.
+    token_exchange_sources: BTreeSet<Uuid>,
     // Our internal exchange encryption material for this rs.
     token_fernet: Fernet,
     jws_signer: JwsSigner,
@@ -234,6 +381,7 @@ pub struct Oauth2RS {
     // For discovery we need to build and keep a number of values.
     authorization_endpoint: Url,
     token_endpoint: Url,
+    device_authorization_endpoint: Url,
     userinfo_endpoint: Url,
     jwks_uri: Url,
     scopes_supported: BTreeSet<String>,
@@ -243,6 +391,30 @@ pub struct Oauth2RS {
     has_custom_image: bool,
 }
 
+impl Oauth2RS {
+    /// Check the secret presented by a client. Public clients have no secret, and rely
+    /// on the validity of the token or code they present instead.
+    fn authenticate_client(&self, secret: Option<&str>) -> Result<(), Oauth2Error> {
+        match &self.type_ {
+            OauthRSType::Basic { authz_secret, .. } => match secret {
+                Some(secret) => {
+                    if authz_secret != secret {
+                        security_info!("Invalid oauth2 client_id secret");
+                        return Err(Oauth2Error::AuthenticationRequired);
+                    }
+                    Ok(())
+                }
+                None => {
+                    // We can only get here if we relied on the request for the client_id and secret
+                    security_info!("Invalid oauth2 authentication - no secret in request");
+                    Err(Oauth2Error::AuthenticationRequired)
+                }
+            },
+            OauthRSType::Public => Ok(()),
+        }
+    }
+}
+
 impl std::fmt::Debug for Oauth2RS {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_struct("Oauth2RS")
@@ -253,6 +425,9 @@ impl std::fmt::Debug for Oauth2RS {
             .field("origin", &self.origin)
             .field("scope_maps", &self.scope_maps)
             .field("sup_scope_maps", &self.sup_scope_maps)
+            .field("client_credentials_scopes", &self.client_credentials_scopes)
+            .field("device_flow_enable", &self.device_flow_enable)
+            .field("token_exchange_sources", &self.token_exchange_sources)
             .field("has_custom_image", &self.has_custom_image)
             .finish()
     }
@@ -391,6 +566,16 @@ impl<'a> Oauth2ResourceServersWriteTransaction<'a> {
                     .cloned()
                     .unwrap_or_default();
 
+                let client_credentials_scopes: BTreeSet<String> = ent
+                    .get_ava_as_oauthscopes(Attribute::OAuth2RsClientCredentialsScopes)
+                    .map(|scopes| scopes.map(str::to_string).collect())
+                    .unwrap_or_default();
+
+                let token_exchange_sources = ent
+                    .get_ava_refer(Attribute::OAuth2RsTokenExchangeSource)
+                    .cloned()
+                    .unwrap_or_default();
+
                 trace!("{}", Attribute::OAuth2JwtLegacyCryptoEnable.as_ref());
                 let jws_signer = if ent.get_ava_single_bool(Attribute::OAuth2JwtLegacyCryptoEnable).unwrap_or(false) {
                     trace!("{}", Attribute::Rs256PrivateKeyDer);
@@ -420,6 +605,10 @@ impl<'a> Oauth2ResourceServersWriteTransaction<'a> {
                     .get_ava_single_bool(Attribute::OAuth2PreferShortUsername)
                     .unwrap_or(false);
 
+                let device_flow_enable = ent
+                    .get_ava_single_bool(Attribute::OAuth2DeviceFlowEnable)
+                    .unwrap_or(false);
+
                 let has_custom_image = ent.get_ava_single_image(Attribute::Image).is_some();
 
                 let mut authorization_endpoint = self.inner.origin.clone();
@@ -428,6 +617,9 @@ impl<'a> Oauth2ResourceServersWriteTransaction<'a> {
                 let mut token_endpoint = self.inner.origin.clone();
                 token_endpoint.set_path("/oauth2/token");
 
+                let mut device_authorization_endpoint = self.inner.origin.clone();
+                device_authorization_endpoint.set_path("/oauth2/device");
+
                 let mut userinfo_endpoint = self.inner.origin.clone();
                 userinfo_endpoint.set_path(&format!("/oauth2/openid/{name}/userinfo"));
 
@@ -460,11 +652,15 @@ impl<'a> Oauth2ResourceServersWriteTransaction<'a> {
                     origin_https,
                     scope_maps,
                     sup_scope_maps,
+                    client_credentials_scopes,
+                    device_flow_enable,
+                    token_exchange_sources,
                     token_fernet,
                     jws_signer,
                     iss,
                     authorization_endpoint,
                     token_endpoint,
+                    device_authorization_endpoint,
                     userinfo_endpoint,
                     jwks_uri,
                     scopes_supported,
@@ -580,6 +776,12 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                         Oauth2Error::ServerError(e)
                     })
             }
+            // Client credential tokens have no session to remove. They are short lived
+            // and are invalidated by removing the scopes from the resource server.
+            Oauth2TokenType::ClientAccess { .. } => {
+                security_info!("unable to revoke a client credentials access token");
+                Err(Oauth2Error::UnsupportedTokenType)
+            }
         }
     }
 
@@ -591,21 +793,11 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         ct: Duration,
     ) -> Result<AccessTokenResponse, Oauth2Error> {
         // Public clients will send the client_id via the ATR, so we need to handle this case.
-        let (client_id, secret) = if let Some(client_authz) = client_authz {
-            let (client_id, secret) = parse_basic_authz(client_authz)?;
-            (client_id, Some(secret))
-        } else {
-            match (&token_req.client_id, &token_req.client_secret) {
-                (Some(a), b) => (a.clone(), b.clone()),
-                _ => {
-                    // We at least need the client_id, else we can't proceed!
-                    security_info!(
-                        "Invalid oauth2 authentication - no basic auth or missing client_id in access token request"
-                    );
-                    return Err(Oauth2Error::AuthenticationRequired);
-                }
-            }
-        };
+        let (client_id, secret) = parse_client_authz(
+            client_authz,
+            token_req.client_id.as_ref(),
+            token_req.client_secret.as_ref(),
+        )?;
 
         // DANGER: Why do we have to do this? During the use of qs for internal search
         // and other operations we need qs to be mut. But when we borrow oauth2rs here we
@@ -622,34 +814,22 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         };
 
         // check the secret.
-        match &o2rs.type_ {
-            OauthRSType::Basic { authz_secret, .. } => {
-                match secret {
-                    Some(secret) => {
-                        if authz_secret != &secret {
-                            security_info!("Invalid oauth2 client_id secret");
-                            return Err(Oauth2Error::AuthenticationRequired);
-                        }
-                    }
-                    None => {
-                        // We can only get here if we relied on the atr for the client_id and secret
-                        security_info!(
-                            "Invalid oauth2 authentication - no secret in access token request"
-                        );
-                        return Err(Oauth2Error::AuthenticationRequired);
-                    }
-                }
-            }
-            // Relies on the token to be valid - no further action needed.
-            OauthRSType::Public => {}
-        };
+        o2rs.authenticate_client(secret.as_deref())?;
 
         // We are authenticated! Yay! Now we can actually check things ...
 
         // TODO: add refresh token grant type.
         //  If it's a refresh token grant, are the consent permissions the same?
 
-        match &token_req.grant_type {
+        let grant_type = match &token_req.grant_type {
+            GrantTypeReq::AuthorizationCode { .. } => "authorization_code",
+            GrantTypeReq::RefreshToken { .. } => "refresh_token",
+            GrantTypeReq::ClientCredentials { .. } => "client_credentials",
+            GrantTypeReq::DeviceCode { .. } => "device_code",
+            GrantTypeReq::TokenExchange { .. } => "token_exchange",
+        };
+
+        let res = match &token_req.grant_type {
             GrantTypeReq::AuthorizationCode {
                 code,
                 redirect_uri,
@@ -665,7 +845,33 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                 refresh_token,
                 scope,
             } => self.check_oauth2_token_refresh(o2rs, refresh_token, scope.as_ref(), ct),
+            GrantTypeReq::ClientCredentials { scope } => {
+                self.check_oauth2_token_client_credentials(o2rs, scope.as_ref(), ct)
+            }
+            GrantTypeReq::DeviceCode { device_code } => {
+                self.check_oauth2_token_device_code(o2rs, device_code, ct)
+            }
+            GrantTypeReq::TokenExchange {
+                subject_token,
+                subject_token_type,
+                requested_token_type,
+                audience,
+                scope,
+            } => self.check_oauth2_token_exchange_subject_token(
+                o2rs,
+                subject_token,
+                subject_token_type,
+                requested_token_type.as_deref(),
+                audience.as_deref(),
+                scope.as_ref(),
+                ct,
+            ),
+        };
+
+        if res.is_ok() {
+            METRICS.oauth2_token_issued(&o2rs.name, grant_type);
         }
+        res
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -732,19 +938,42 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         })?;
 
         let code = o2rs.token_fernet.encrypt_at_time(&code_data, ct.as_secs());
+        let rs_uuid = o2rs.uuid;
 
         // Everything is DONE! Now submit that it's all happy and the user consented correctly.
         // this will let them bypass consent steps in the future.
-        // Submit that we consented to the delayed action queue
+        self.record_oauth2_consent(
+            uat,
+            rs_uuid,
+            &consent_req.client_id,
+            &consent_req.scopes,
+            ct,
+        )?;
 
+        Ok(AuthorisePermitSuccess {
+            redirect_uri: consent_req.redirect_uri,
+            state: consent_req.state,
+            code,
+        })
+    }
+
+    /// Record the scopes that the account consented to for this resource server.
+    fn record_oauth2_consent(
+        &mut self,
+        uat: &UserAuthToken,
+        rs_uuid: Uuid,
+        rs_name: &str,
+        scopes: &BTreeSet<String>,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
         let modlist = ModifyList::new_list(vec![
             Modify::Removed(
                 Attribute::OAuth2ConsentScopeMap.into(),
-                PartialValue::Refer(o2rs.uuid),
+                PartialValue::Refer(rs_uuid),
             ),
             Modify::Present(
                 Attribute::OAuth2ConsentScopeMap.into(),
-                Value::OauthScopeMap(o2rs.uuid, consent_req.scopes.iter().cloned().collect()),
+                Value::OauthScopeMap(rs_uuid, scopes.iter().cloned().collect()),
             ),
         ]);
 
@@ -753,127 +982,422 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             &modlist,
         )?;
 
-        Ok(AuthorisePermitSuccess {
-            redirect_uri: consent_req.redirect_uri,
-            state: consent_req.state,
-            code,
-        })
+        self.qs_write
+            .audit_event_on_commit(AuditEvent::Oauth2ConsentGranted {
+                uuid: uat.uuid,
+                spn: uat.spn.clone(),
+                rs_uuid,
+                rs_name: rs_name.to_string(),
+                scopes: scopes.clone(),
+                time: OffsetDateTime::UNIX_EPOCH + ct,
+            });
+        Ok(())
     }
 
     #[instrument(level = "debug", skip_all)]
-    fn check_oauth2_token_exchange_authorization_code(
+    pub fn check_oauth2_device_authorisation(
         &mut self,
-        o2rs: &Oauth2RS,
-        token_req_code: &str,
-        token_req_redirect_uri: &Url,
-        token_req_code_verifier: Option<&str>,
+        client_authz: Option<&str>,
+        device_req: &DeviceAuthorizationRequest,
         ct: Duration,
-    ) -> Result<AccessTokenResponse, Oauth2Error> {
-        // Check the token_req is within the valid time, and correctly signed for
-        // this client.
+    ) -> Result<DeviceAuthorizationResponse, Oauth2Error> {
+        let (client_id, secret) = parse_client_authz(
+            client_authz,
+            device_req.client_id.as_ref(),
+            device_req.client_secret.as_ref(),
+        )?;
 
-        let code_xchg: TokenExchangeCode = o2rs
-            .token_fernet
-            .decrypt_at_time(token_req_code, Some(60), ct.as_secs())
-            .map_err(|_| {
-                admin_error!("Failed to decrypt token exchange request");
-                Oauth2Error::InvalidRequest
-            })
-            .and_then(|data| {
-                serde_json::from_slice(&data).map_err(|e| {
-                    admin_error!("Failed to deserialise token exchange code - {:?}", e);
-                    Oauth2Error::InvalidRequest
-                })
-            })?;
+        let o2rs = self.oauth2rs.inner.rs_set.get(&client_id).ok_or_else(|| {
+            admin_warn!("Invalid oauth2 client_id");
+            Oauth2Error::AuthenticationRequired
+        })?;
 
-        let require_pkce = match &o2rs.type_ {
-            OauthRSType::Basic { enable_pkce, .. } => *enable_pkce,
-            OauthRSType::Public => true,
-        };
+        o2rs.authenticate_client(secret.as_deref())?;
 
-        // If we have a verifier present, we MUST assert that a code challenge is present!
-        // It is worth noting here that code_xchg is *server issued* and encrypted, with
-        // a short validity period. The client controlled value is in token_req.code_verifier
-        if let Some(code_challenge) = code_xchg.code_challenge {
-            // Validate the code_verifier
-            let code_verifier = token_req_code_verifier
-                    .ok_or_else(|| {
-                        security_info!("PKCE code verification failed - code challenge is present, but no verifier was provided");
-                        Oauth2Error::InvalidRequest
-                    })?;
-            let mut hasher = sha::Sha256::new();
-            hasher.update(code_verifier.as_bytes());
-            let code_verifier_hash: Vec<u8> = hasher.finish().to_vec();
+        if !o2rs.device_flow_enable {
+            security_info!(?o2rs.name, "device authorisation grant is not enabled for this client");
+            return Err(Oauth2Error::UnauthorizedClient);
+        }
 
-            if code_challenge.0 != code_verifier_hash {
-                security_info!(
-                    "PKCE code verification failed - this may indicate malicious activity"
-                );
-                return Err(Oauth2Error::InvalidRequest);
-            }
-        } else if require_pkce {
-            security_info!(
-                "PKCE code verification failed - no code challenge present in PKCE enforced mode"
-            );
-            return Err(Oauth2Error::InvalidRequest);
-        } else if token_req_code_verifier.is_some() {
-            security_info!(
-                "PKCE code verification failed - a code verifier is present, but no code challenge in exchange"
-            );
+        let req_scopes = device_req.scope.clone().unwrap_or_default();
+        if req_scopes.is_empty() {
+            admin_error!("Invalid oauth2 request - must contain at least one requested scope");
             return Err(Oauth2Error::InvalidRequest);
         }
 
-        // Validate the redirect_uri is the same as the original.
-        if token_req_redirect_uri != &code_xchg.redirect_uri {
-            security_info!("Invalid oauth2 redirect_uri (differs from original request uri)");
-            return Err(Oauth2Error::InvalidOrigin);
+        if !req_scopes.iter().all(|s| OAUTHSCOPE_RE.is_match(s)) {
+            admin_error!(
+                "Invalid oauth2 request - requested scopes failed to pass validation rules"
+            );
+            return Err(Oauth2Error::InvalidScope);
         }
 
-        // Check that the UAT we are issuing for still is valid.
+        // Clean up any device authorisations that were never completed.
         let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
-        if let Some(expiry) = code_xchg.uat.expiry {
-            if expiry <= odt_ct {
-                security_info!(
-                    "User Auth Token has expired before we could publish the oauth2 response"
-                );
-                return Err(Oauth2Error::AccessDenied);
-            }
+        let expired: Vec<_> = self
+            .qs_write
+            .internal_search(filter!(f_eq(
+                Attribute::Class,
+                EntryClass::OAuth2DeviceAuthorisation.into()
+            )))
+            .map_err(Oauth2Error::ServerError)?
+            .iter()
+            .filter(|entry| {
+                entry
+                    .get_ava_single_datetime(Attribute::OAuth2DeviceExpiry)
+                    .map_or(true, |expiry| expiry <= odt_ct)
+            })
+            .map(|entry| f_eq(Attribute::Uuid, PartialValue::Uuid(entry.get_uuid())))
+            .collect();
+
+        if !expired.is_empty() {
+            self.qs_write
+                .internal_delete(&filter!(f_or(expired)))
+                .map_err(Oauth2Error::ServerError)?;
         }
 
-        // ==== We are now GOOD TO GO! ====
-        // Grant the access token response.
-        let parent_session_id = code_xchg.uat.session_id;
-        let session_id = Uuid::new_v4();
+        let user_code = loop {
+            let user_code = device_user_code_from_random();
+            if oauth2_device_session_get(&mut self.qs_write, &user_code)
+                .map_err(Oauth2Error::ServerError)?
+                .is_none()
+            {
+                break user_code;
+            }
+        };
 
-        let scopes = code_xchg.scopes;
-        let account_uuid = code_xchg.uat.uuid;
-        let nonce = code_xchg.nonce;
+        // The device code is only ever given to the device, and is encrypted with the key
+        // of the client so it can only be used by that client.
+        let device_code_data = serde_json::to_vec(&DeviceCode {
+            user_code: user_code.clone(),
+        })
+        .map_err(|e| {
+            admin_error!(err = ?e, "Unable to encode device code data");
+            Oauth2Error::ServerError(OperationError::SerdeJsonError)
+        })?;
 
-        self.generate_access_token_response(
-            o2rs,
-            ct,
-            scopes,
-            account_uuid,
-            parent_session_id,
-            session_id,
-            nonce,
-        )
+        let device_code = o2rs
+            .token_fernet
+            .encrypt_at_time(&device_code_data, ct.as_secs());
+
+        let display_user_code = format_device_user_code(&user_code);
+
+        let mut verification_uri = self.oauth2rs.inner.origin.clone();
+        verification_uri.set_path("/ui/oauth2/device");
+
+        let mut verification_uri_complete = verification_uri.clone();
+        verification_uri_complete
+            .query_pairs_mut()
+            .append_pair("user_code", &display_user_code);
+
+        let mut entry = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (
+                Attribute::Class,
+                EntryClass::OAuth2DeviceAuthorisation.to_value()
+            ),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+            (
+                Attribute::OAuth2DeviceUserCode,
+                Value::new_iutf8(&user_code)
+            ),
+            (Attribute::OAuth2DeviceClient, Value::Refer(o2rs.uuid)),
+            (
+                Attribute::OAuth2DeviceExpiry,
+                Value::new_datetime(odt_ct + Duration::from_secs(OAUTH2_DEVICE_CODE_EXPIRY as u64))
+            ),
+            (
+                Attribute::OAuth2DeviceState,
+                Value::new_iutf8(OAUTH2_DEVICE_STATE_PENDING)
+            )
+        );
+        for scope in req_scopes.iter() {
+            let scope = Value::new_oauthscope(scope).ok_or(Oauth2Error::InvalidScope)?;
+            entry.add_ava(Attribute::OAuth2DeviceScope, scope);
+        }
+
+        self.qs_write
+            .internal_create(vec![entry])
+            .map_err(Oauth2Error::ServerError)?;
+
+        Ok(DeviceAuthorizationResponse {
+            device_code,
+            user_code: display_user_code,
+            verification_uri,
+            verification_uri_complete: Some(verification_uri_complete),
+            expires_in: OAUTH2_DEVICE_CODE_EXPIRY,
+            interval: Some(OAUTH2_DEVICE_CODE_INTERVAL),
+        })
     }
 
-    #[instrument(level = "debug", skip_all)]
-    fn check_oauth2_token_refresh(
-        &mut self,
-        o2rs: &Oauth2RS,
-        refresh_token: &str,
-        req_scopes: Option<&BTreeSet<String>>,
+    fn decode_oauth2_device_consent_token(
+        &self,
+        ident: &Identity,
+        uat: &UserAuthToken,
+        consent_token: &str,
         ct: Duration,
-    ) -> Result<AccessTokenResponse, Oauth2Error> {
-        // Validate the refresh token decrypts and it's expiry is within the valid window.
-        let token: Oauth2TokenType = o2rs
-            .token_fernet
-            .decrypt(refresh_token)
-            .map_err(|_| {
-                admin_error!("Failed to decrypt refresh token request");
+    ) -> Result<DeviceConsentToken, OperationError> {
+        // Decode the consent req with our system fernet key. Use a ttl of 5 minutes.
+        let consent_req: DeviceConsentToken = self
+            .oauth2rs
+            .inner
+            .fernet
+            .decrypt_at_time(consent_token, Some(300), ct.as_secs())
+            .map_err(|_| {
+                admin_error!("Failed to decrypt device consent request");
+                OperationError::CryptographyError
+            })
+            .and_then(|data| {
+                serde_json::from_slice(&data).map_err(|e| {
+                    admin_error!(err = ?e, "Failed to deserialise device consent request");
+                    OperationError::SerdeJsonError
+                })
+            })?;
+
+        // Validate that the ident_id matches our current ident.
+        if consent_req.ident_id != ident.get_event_origin_id() {
+            security_info!("consent request ident id does not match the identity of our UAT.");
+            return Err(OperationError::InvalidSessionState);
+        }
+
+        // Validate that the session id matches our uat.
+        if consent_req.session_id != uat.session_id {
+            security_info!("consent request session id does not match the session id of our UAT.");
+            return Err(OperationError::InvalidSessionState);
+        }
+
+        Ok(consent_req)
+    }
+
+    /// Find the pending device authorisation that this consent token refers to.
+    fn pending_oauth2_device_session(
+        &mut self,
+        consent_req: &DeviceConsentToken,
+        ct: Duration,
+    ) -> Result<Oauth2DeviceSession, OperationError> {
+        let session = oauth2_device_session_get(&mut self.qs_write, &consent_req.user_code)?
+            .ok_or_else(|| {
+                security_info!("device authorisation not found, it may have expired");
+                OperationError::InvalidRequestState
+            })?;
+
+        let client_uuid = self
+            .oauth2rs
+            .inner
+            .rs_set
+            .get(&consent_req.client_id)
+            .map(|o2rs| o2rs.uuid);
+
+        if session.expiry <= OffsetDateTime::UNIX_EPOCH + ct
+            || client_uuid.is_none()
+            || session.client_uuid != client_uuid
+            || !matches!(session.state, Oauth2DeviceState::Pending)
+        {
+            security_info!("device authorisation is no longer valid for this consent request");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        Ok(session)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub fn check_oauth2_device_authorise_permit(
+        &mut self,
+        ident: &Identity,
+        uat: &UserAuthToken,
+        consent_token: &str,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        let consent_req = self.decode_oauth2_device_consent_token(ident, uat, consent_token, ct)?;
+        let session = self.pending_oauth2_device_session(&consent_req, ct)?;
+
+        let rs_uuid = self
+            .oauth2rs
+            .inner
+            .rs_set
+            .get(&consent_req.client_id)
+            .map(|o2rs| o2rs.uuid)
+            .ok_or_else(|| {
+                admin_error!("Invalid consent request oauth2 client_id");
+                OperationError::InvalidRequestState
+            })?;
+
+        // The device will collect its tokens on the next poll.
+        let mut mods = vec![
+            Modify::Purged(Attribute::OAuth2DeviceState.into()),
+            Modify::Present(
+                Attribute::OAuth2DeviceState.into(),
+                Value::new_iutf8(OAUTH2_DEVICE_STATE_APPROVED),
+            ),
+            Modify::Present(
+                Attribute::OAuth2DeviceAccount.into(),
+                Value::Refer(uat.uuid),
+            ),
+            Modify::Present(
+                Attribute::OAuth2DeviceParentSession.into(),
+                Value::Uuid(uat.session_id),
+            ),
+            Modify::Purged(Attribute::OAuth2DeviceScope.into()),
+        ];
+        if let Some(expiry) = uat.expiry {
+            mods.push(Modify::Present(
+                Attribute::OAuth2DeviceParentExpiry.into(),
+                Value::new_datetime(expiry),
+            ));
+        }
+        for scope in consent_req.scopes.iter() {
+            let scope = Value::new_oauthscope(scope).ok_or(OperationError::InvalidAttribute(
+                Attribute::OAuth2DeviceScope.to_string(),
+            ))?;
+            mods.push(Modify::Present(Attribute::OAuth2DeviceScope.into(), scope));
+        }
+
+        self.qs_write
+            .internal_modify_uuid(session.uuid, &ModifyList::new_list(mods))?;
+
+        self.record_oauth2_consent(
+            uat,
+            rs_uuid,
+            &consent_req.client_id,
+            &consent_req.scopes,
+            ct,
+        )
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub fn check_oauth2_device_authorise_reject(
+        &mut self,
+        ident: &Identity,
+        uat: &UserAuthToken,
+        consent_token: &str,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        let consent_req = self.decode_oauth2_device_consent_token(ident, uat, consent_token, ct)?;
+        let session = self.pending_oauth2_device_session(&consent_req, ct)?;
+
+        self.qs_write.internal_modify_uuid(
+            session.uuid,
+            &ModifyList::new_purge_and_set(
+                Attribute::OAuth2DeviceState,
+                Value::new_iutf8(OAUTH2_DEVICE_STATE_DENIED),
+            ),
+        )
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    fn check_oauth2_token_exchange_authorization_code(
+        &mut self,
+        o2rs: &Oauth2RS,
+        token_req_code: &str,
+        token_req_redirect_uri: &Url,
+        token_req_code_verifier: Option<&str>,
+        ct: Duration,
+    ) -> Result<AccessTokenResponse, Oauth2Error> {
+        // Check the token_req is within the valid time, and correctly signed for
+        // this client.
+
+        let code_xchg: TokenExchangeCode = o2rs
+            .token_fernet
+            .decrypt_at_time(token_req_code, Some(60), ct.as_secs())
+            .map_err(|_| {
+                admin_error!("Failed to decrypt token exchange request");
+                Oauth2Error::InvalidRequest
+            })
+            .and_then(|data| {
+                serde_json::from_slice(&data).map_err(|e| {
+                    admin_error!("Failed to deserialise token exchange code - {:?}", e);
+                    Oauth2Error::InvalidRequest
+                })
+            })?;
+
+        let require_pkce = match &o2rs.type_ {
+            OauthRSType::Basic { enable_pkce, .. } => *enable_pkce,
+            OauthRSType::Public => true,
+        };
+
+        // If we have a verifier present, we MUST assert that a code challenge is present!
+        // It is worth noting here that code_xchg is *server issued* and encrypted, with
+        // a short validity period. The client controlled value is in token_req.code_verifier
+        if let Some(code_challenge) = code_xchg.code_challenge {
+            // Validate the code_verifier
+            let code_verifier = token_req_code_verifier
+                    .ok_or_else(|| {
+                        security_info!("PKCE code verification failed - code challenge is present, but no verifier was provided");
+                        Oauth2Error::InvalidRequest
+                    })?;
+            let mut hasher = sha::Sha256::new();
+            hasher.update(code_verifier.as_bytes());
+            let code_verifier_hash: Vec<u8> = hasher.finish().to_vec();
+
+            if code_challenge.0 != code_verifier_hash {
+                security_info!(
+                    "PKCE code verification failed - this may indicate malicious activity"
+                );
+                return Err(Oauth2Error::InvalidRequest);
+            }
+        } else if require_pkce {
+            security_info!(
+                "PKCE code verification failed - no code challenge present in PKCE enforced mode"
+            );
+            return Err(Oauth2Error::InvalidRequest);
+        } else if token_req_code_verifier.is_some() {
+            security_info!(
+                "PKCE code verification failed - a code verifier is present, but no code challenge in exchange"
+            );
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        // Validate the redirect_uri is the same as the original.
+        if token_req_redirect_uri != &code_xchg.redirect_uri {
+            security_info!("Invalid oauth2 redirect_uri (differs from original request uri)");
+            return Err(Oauth2Error::InvalidOrigin);
+        }
+
+        // Check that the UAT we are issuing for still is valid.
+        let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
+        if let Some(expiry) = code_xchg.uat.expiry {
+            if expiry <= odt_ct {
+                security_info!(
+                    "User Auth Token has expired before we could publish the oauth2 response"
+                );
+                return Err(Oauth2Error::AccessDenied);
+            }
+        }
+
+        // ==== We are now GOOD TO GO! ====
+        // Grant the access token response.
+        let parent_session_id = code_xchg.uat.session_id;
+        let session_id = Uuid::new_v4();
+
+        let scopes = code_xchg.scopes;
+        let account_uuid = code_xchg.uat.uuid;
+        let nonce = code_xchg.nonce;
+
+        self.generate_access_token_response(
+            o2rs,
+            ct,
+            scopes,
+            account_uuid,
+            parent_session_id,
+            session_id,
+            nonce,
+        )
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    fn check_oauth2_token_refresh(
+        &mut self,
+        o2rs: &Oauth2RS,
+        refresh_token: &str,
+        req_scopes: Option<&BTreeSet<String>>,
+        ct: Duration,
+    ) -> Result<AccessTokenResponse, Oauth2Error> {
+        // Validate the refresh token decrypts and it's expiry is within the valid window.
+        let token: Oauth2TokenType = o2rs
+            .token_fernet
+            .decrypt(refresh_token)
+            .map_err(|_| {
+                admin_error!("Failed to decrypt refresh token request");
                 Oauth2Error::InvalidRequest
             })
             .and_then(|data| {
@@ -884,7 +1408,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             })?;
 
         match token {
-            Oauth2TokenType::Access { .. } => {
+            Oauth2TokenType::Access { .. } | Oauth2TokenType::ClientAccess { .. } => {
                 admin_error!("attempt to refresh with access token");
                 Err(Oauth2Error::InvalidToken)
             }
@@ -990,51 +1514,426 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         }
     }
 
-    fn generate_access_token_response(
+    #[instrument(level = "debug", skip_all)]
+    fn check_oauth2_token_client_credentials(
         &mut self,
         o2rs: &Oauth2RS,
+        req_scopes: Option<&BTreeSet<String>>,
         ct: Duration,
-        //
-        scopes: BTreeSet<String>,
-        account_uuid: Uuid,
-        parent_session_id: Uuid,
-        session_id: Uuid,
-        nonce: Option<String>,
     ) -> Result<AccessTokenResponse, Oauth2Error> {
-        let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
-        let iat = ct.as_secs() as i64;
+        // rfc6749 4.4 - the client credentials grant must only be used by confidential
+        // clients, since the client is acting on its own behalf.
+        if !matches!(o2rs.type_, OauthRSType::Basic { .. }) {
+            security_info!("public clients may not use the client credentials grant");
+            return Err(Oauth2Error::UnauthorizedClient);
+        }
 
-        // TODO: Configurable from the oauth2rs configuration?
+        if o2rs.client_credentials_scopes.is_empty() {
+            security_info!(
+                "resource server has no client credentials scopes, denying client credentials grant"
+            );
+            return Err(Oauth2Error::UnauthorizedClient);
+        }
 
-        // Disclaimer: It may seem odd here that we are ignoring our session when it comes to expiry
-        // times. However, this actually is valid because when we take the initial code exchange
-        // path we have already validate the expiry of the account/session in that process. For the
-        // refresh path, we validate the session expiry and validity before we call this. So these
-        // expiries are *purely* for the tokens we issue and are *not related* to the expiries of the
-        // the session - these are enforced as above!
+        // If no scopes are requested, we grant everything the rs is permitted.
+        let scopes = match req_scopes {
+            Some(req_scopes) => {
+                if !req_scopes.is_subset(&o2rs.client_credentials_scopes) {
+                    security_info!(
+                        ?req_scopes,
+                        "requested scopes are not granted to this resource server"
+                    );
+                    return Err(Oauth2Error::InvalidScope);
+                }
+                req_scopes.clone()
+            }
+            None => o2rs.client_credentials_scopes.clone(),
+        };
 
+        let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
+        let iat = ct.as_secs() as i64;
         let expiry = odt_ct + Duration::from_secs(OAUTH2_ACCESS_TOKEN_EXPIRY as u64);
-        let expires_in = OAUTH2_ACCESS_TOKEN_EXPIRY;
-        let refresh_expiry = odt_ct + Duration::from_secs(OAUTH_REFRESH_TOKEN_EXPIRY);
 
-        let scope = if scopes.is_empty() {
-            None
-        } else {
-            Some(str_join(&scopes))
-        };
+        let scope = Some(str_join(&scopes));
 
-        let id_token = if scopes.contains(OAUTH2_SCOPE_OPENID) {
-            // TODO: Scopes map to claims:
-            //
-            // * profile - (name, family\_name, given\_name, middle\_name, nickname, preferred\_username, profile, picture, website, gender, birthdate, zoneinfo, locale, and updated\_at)
-            // * email - (email, email\_verified)
-            // * address - (address)
-            // * phone - (phone\_number, phone\_number\_verified)
-            //
-            // https://openid.net/specs/openid-connect-basic-1_0.html#StandardClaims
+        // The subject of this token is the resource server itself. There is no refresh
+        // token, the client can always repeat this grant to get a new access token.
+        let access_token_raw = Oauth2TokenType::ClientAccess {
+            scopes,
+            session_id: Uuid::new_v4(),
+            expiry,
+            uuid: o2rs.uuid,
+            iat,
+            nbf: iat,
+        };
 
-            // TODO: Can the user consent to which claims are released? Today as we don't support most
-            // of them anyway, no, but in the future, we can stash these to the consent req.
+        let access_token_data = serde_json::to_vec(&access_token_raw).map_err(|e| {
+            admin_error!(err = ?e, "Unable to encode token data");
+            Oauth2Error::ServerError(OperationError::SerdeJsonError)
+        })?;
+
+        let access_token = o2rs
+            .token_fernet
+            .encrypt_at_time(&access_token_data, ct.as_secs());
+
+        Ok(AccessTokenResponse {
+            access_token,
+            token_type: "bearer".to_string(),
+            expires_in: OAUTH2_ACCESS_TOKEN_EXPIRY,
+            refresh_token: None,
+            scope,
+            id_token: None,
+            issued_token_type: None,
+        })
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    fn check_oauth2_token_device_code(
+        &mut self,
+        o2rs: &Oauth2RS,
+        device_code: &str,
+        ct: Duration,
+    ) -> Result<AccessTokenResponse, Oauth2Error> {
+        if !o2rs.device_flow_enable {
+            security_info!(?o2rs.name, "device authorisation grant is not enabled for this client");
+            return Err(Oauth2Error::UnauthorizedClient);
+        }
+
+        let code: DeviceCode = o2rs
+            .token_fernet
+            .decrypt(device_code)
+            .map_err(|_| {
+                admin_error!("Failed to decrypt device code");
+                Oauth2Error::InvalidGrant
+            })
+            .and_then(|data| {
+                serde_json::from_slice(&data).map_err(|e| {
+                    admin_error!("Failed to deserialise device code - {:?}", e);
+                    Oauth2Error::InvalidGrant
+                })
+            })?;
+
+        // If the session is missing, it was either already used, or it expired and was
+        // cleaned up.
+        let Some(session) = oauth2_device_session_get(&mut self.qs_write, &code.user_code)
+            .map_err(Oauth2Error::ServerError)?
+        else {
+            security_info!("device authorisation not found, it may have expired");
+            return Err(Oauth2Error::ExpiredToken);
+        };
+
+        if session.client_uuid != Some(o2rs.uuid) {
+            security_info!("device code was not issued to this client");
+            return Err(Oauth2Error::InvalidGrant);
+        }
+
+        let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
+
+        // Polling too quickly is handled before the write transaction, so a pending device
+        // authorisation is never changed here.
+        if matches!(session.state, Oauth2DeviceState::Pending) && session.expiry > odt_ct {
+            return Err(Oauth2Error::AuthorizationPending);
+        }
+
+        // Otherwise this device code can't be used again.
+        self.qs_write
+            .internal_delete_uuid(session.uuid)
+            .map_err(Oauth2Error::ServerError)?;
+
+        if session.expiry <= odt_ct {
+            security_info!("device authorisation has expired");
+            return Err(Oauth2Error::ExpiredToken);
+        }
+
+        match session.state {
+            Oauth2DeviceState::Pending => Err(Oauth2Error::AuthorizationPending),
+            Oauth2DeviceState::Denied => {
+                security_info!("device authorisation was denied by the user");
+                Err(Oauth2Error::AccessDenied)
+            }
+            Oauth2DeviceState::Approved {
+                account_uuid,
+                parent_session_id,
+                parent_expiry,
+            } => {
+                // Check that the session that approved this is still valid.
+                if parent_expiry
+                    .map(|expiry| expiry <= odt_ct)
+                    .unwrap_or(false)
+                {
+                    security_info!(
+                        "User Auth Token has expired before we could publish the oauth2 response"
+                    );
+                    return Err(Oauth2Error::AccessDenied);
+                }
+
+                let session_id = Uuid::new_v4();
+
+                self.generate_access_token_response(
+                    o2rs,
+                    ct,
+                    session.scopes,
+                    account_uuid,
+                    parent_session_id,
+                    session_id,
+                    None,
+                )
+            }
+        }
+    }
+
+    /// rfc8693 - exchange an access token that was issued to this client for an access token
+    /// to another resource server, so that the client can act on behalf of the subject.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(level = "debug", skip_all)]
+    fn check_oauth2_token_exchange_subject_token(
+        &mut self,
+        o2rs: &Oauth2RS,
+        subject_token: &str,
+        subject_token_type: &str,
+        requested_token_type: Option<&str>,
+        audience: Option<&str>,
+        req_scopes: Option<&BTreeSet<String>>,
+        ct: Duration,
+    ) -> Result<AccessTokenResponse, Oauth2Error> {
+        // Only clients that can authenticate may act on behalf of a user.
+        if matches!(o2rs.type_, OauthRSType::Public) {
+            security_info!(?o2rs.name, "public clients may not exchange tokens");
+            return Err(Oauth2Error::UnauthorizedClient);
+        }
+
+        if subject_token_type != OAUTH2_TOKEN_TYPE_ACCESS_TOKEN
+            || requested_token_type
+                .map(|t| t != OAUTH2_TOKEN_TYPE_ACCESS_TOKEN)
+                .unwrap_or(false)
+        {
+            admin_error!("Invalid oauth2 request - only access tokens may be exchanged");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        let target_name = match audience.and_then(|aud| self.oauth2rs.inner.rs_set.get(aud)) {
+            Some(target_rs) if target_rs.token_exchange_sources.contains(&o2rs.uuid) => {
+                target_rs.name.clone()
+            }
+            Some(target_rs) => {
+                security_info!(
+                    source = ?o2rs.name,
+                    target = ?target_rs.name,
+                    "client is not permitted to exchange tokens for this resource server"
+                );
+                return Err(Oauth2Error::UnauthorizedClient);
+            }
+            None => {
+                admin_warn!(?audience, "Invalid token exchange audience");
+                return Err(Oauth2Error::InvalidTarget);
+            }
+        };
+
+        // The subject token must have been issued to the client presenting it.
+        let token: Oauth2TokenType = o2rs
+            .token_fernet
+            .decrypt(subject_token)
+            .map_err(|_| {
+                admin_error!("Failed to decrypt subject token");
+                Oauth2Error::InvalidRequest
+            })
+            .and_then(|data| {
+                serde_json::from_slice(&data).map_err(|e| {
+                    admin_error!("Failed to deserialise subject token - {:?}", e);
+                    Oauth2Error::InvalidRequest
+                })
+            })?;
+
+        let Oauth2TokenType::Access {
+            parent_session_id,
+            session_id,
+            expiry,
+            uuid,
+            iat,
+            act,
+            ..
+        } = token
+        else {
+            admin_error!("Invalid oauth2 request - subject token is not a user access token");
+            return Err(Oauth2Error::InvalidRequest);
+        };
+
+        let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
+        if expiry <= odt_ct {
+            security_info!(?uuid, "subject token has expired");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        // An account that has since been deleted is as invalid as an expired one, but any
+        // other failure is an error of the server rather than of the request.
+        let entry = match self.check_oauth2_account_uuid_valid(
+            uuid,
+            session_id,
+            parent_session_id,
+            iat,
+            ct,
+        ) {
+            Ok(Some(entry)) => entry,
+            Ok(None) | Err(OperationError::NoMatchingEntries) => {
+                security_info!(?uuid, "subject token account or session is not valid");
+                return Err(Oauth2Error::InvalidRequest);
+            }
+            Err(e) => {
+                admin_error!(?e, "Unable to validate the subject token account");
+                return Err(Oauth2Error::ServerError(e));
+            }
+        };
+
+        let target_rs = self
+            .oauth2rs
+            .inner
+            .rs_set
+            .get(&target_name)
+            .ok_or(Oauth2Error::InvalidTarget)?;
+
+        // The scopes are determined by what the subject is granted by the target rs, never
+        // by the scopes of the subject token.
+        let ident = Identity {
+            origin: IdentType::User(IdentUser { entry }),
+            session_id,
+            scope: AccessScope::ReadOnly,
+            limits: Limits::default(),
+        };
+        let req_scopes = match req_scopes {
+            Some(req_scopes) => req_scopes.clone(),
+            None => oauth2_scope_map_scopes(target_rs, &ident),
+        };
+
+        if req_scopes.is_empty() {
+            admin_error!("Invalid oauth2 request - no scopes are available for the audience");
+            return Err(Oauth2Error::InvalidScope);
+        }
+
+        let scopes = oauth2_granted_scopes(target_rs, &ident, req_scopes).map_err(|e| {
+            if e == Oauth2Error::AccessDenied {
+                Oauth2Error::InvalidScope
+            } else {
+                e
+            }
+        })?;
+
+        // Record who is acting on behalf of the subject, including anyone who acted
+        // before them.
+        let act = ActorClaim {
+            sub: o2rs.name.clone(),
+            act: act.map(Box::new),
+        };
+
+        // The exchanged token is bound to the same parent session as the subject token, so
+        // that if the user logs out, every token in the chain is invalidated.
+        let session_id = Uuid::new_v4();
+        let iat = ct.as_secs() as i64;
+        let expiry = odt_ct + Duration::from_secs(OAUTH2_ACCESS_TOKEN_EXPIRY as u64);
+
+        let access_token_raw = Oauth2TokenType::Access {
+            scopes: scopes.clone(),
+            parent_session_id,
+            session_id,
+            expiry,
+            uuid,
+            iat,
+            nbf: iat,
+            auth_time: None,
+            nonce: None,
+            act: Some(act),
+        };
+
+        let access_token_data = serde_json::to_vec(&access_token_raw).map_err(|e| {
+            admin_error!(err = ?e, "Unable to encode token data");
+            Oauth2Error::ServerError(OperationError::SerdeJsonError)
+        })?;
+
+        let access_token = target_rs
+            .token_fernet
+            .encrypt_at_time(&access_token_data, ct.as_secs());
+
+        let session = Value::Oauth2Session(
+            session_id,
+            Oauth2Session {
+                parent: parent_session_id,
+                state: SessionState::ExpiresAt(expiry),
+                issued_at: odt_ct,
+                rs_uuid: target_rs.uuid,
+            },
+        );
+
+        let modlist = ModifyList::new_list(vec![Modify::Present(
+            Attribute::OAuth2Session.into(),
+            session,
+        )]);
+
+        self.qs_write
+            .internal_modify(
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(uuid))),
+                &modlist,
+            )
+            .map_err(|e| {
+                admin_error!("Failed to persist oauth2 session record {:?}", e);
+                Oauth2Error::ServerError(e)
+            })?;
+
+        // No refresh token is issued, the client can exchange the subject token again.
+        Ok(AccessTokenResponse {
+            access_token,
+            token_type: "bearer".to_string(),
+            expires_in: OAUTH2_ACCESS_TOKEN_EXPIRY,
+            refresh_token: None,
+            scope: Some(str_join(&scopes)),
+            id_token: None,
+            issued_token_type: Some(OAUTH2_TOKEN_TYPE_ACCESS_TOKEN.to_string()),
+        })
+    }
+
+    fn generate_access_token_response(
+        &mut self,
+        o2rs: &Oauth2RS,
+        ct: Duration,
+        //
+        scopes: BTreeSet<String>,
+        account_uuid: Uuid,
+        parent_session_id: Uuid,
+        session_id: Uuid,
+        nonce: Option<String>,
+    ) -> Result<AccessTokenResponse, Oauth2Error> {
+        let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
+        let iat = ct.as_secs() as i64;
+
+        // TODO: Configurable from the oauth2rs configuration?
+
+        // Disclaimer: It may seem odd here that we are ignoring our session when it comes to expiry
+        // times. However, this actually is valid because when we take the initial code exchange
+        // path we have already validate the expiry of the account/session in that process. For the
+        // refresh path, we validate the session expiry and validity before we call this. So these
+        // expiries are *purely* for the tokens we issue and are *not related* to the expiries of the
+        // the session - these are enforced as above!
+
+        let expiry = odt_ct + Duration::from_secs(OAUTH2_ACCESS_TOKEN_EXPIRY as u64);
+        let expires_in = OAUTH2_ACCESS_TOKEN_EXPIRY;
+        let refresh_expiry = odt_ct + Duration::from_secs(OAUTH_REFRESH_TOKEN_EXPIRY);
+
+        let scope = if scopes.is_empty() {
+            None
+        } else {
+            Some(str_join(&scopes))
+        };
+
+        let id_token = if scopes.contains(OAUTH2_SCOPE_OPENID) {
+            // TODO: Scopes map to claims:
+            //
+            // * profile - (name, family\_name, given\_name, middle\_name, nickname, preferred\_username, profile, picture, website, gender, birthdate, zoneinfo, locale, and updated\_at)
+            // * email - (email, email\_verified)
+            // * address - (address)
+            // * phone - (phone\_number, phone\_number\_verified)
+            //
+            // https://openid.net/specs/openid-connect-basic-1_0.html#StandardClaims
+
+            // TODO: Can the user consent to which claims are released? Today as we don't support most
+            // of them anyway, no, but in the future, we can stash these to the consent req.
 
             // TODO: If max_age was requested in the request, we MUST provide auth_time.
 
@@ -1106,6 +2005,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             nbf: iat,
             auth_time: None,
             nonce: nonce.clone(),
+            act: None,
         };
 
         let access_token_data = serde_json::to_vec(&access_token_raw).map_err(|e| {
@@ -1174,6 +2074,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             refresh_token: Some(refresh_token),
             scope,
             id_token,
+            issued_token_type: None,
         })
     }
 
@@ -1354,70 +2255,11 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
             return Err(Oauth2Error::InvalidScope);
         }
 
-        let uat_scopes: BTreeSet<String> = o2rs
-            .scope_maps
-            .iter()
-            .filter_map(|(u, m)| {
-                if ident.is_memberof(*u) {
-                    Some(m.iter())
-                } else {
-                    None
-                }
-            })
-            .flatten()
-            .cloned()
-            .collect();
-
-        // Needs to use s.to_string due to &&str which can't use the str::to_string
-        let avail_scopes: Vec<String> = req_scopes
-            .intersection(&uat_scopes)
-            .map(|s| s.to_string())
-            .collect();
-
-        debug!(?o2rs.scope_maps);
-
-        // Due to the intersection above, this is correct because the equal len can only
-        // occur if all terms were satisfied - effectively this check is that avail_scopes
-        // and req_scopes are identical after intersection with the scopes defined by uat_scopes
-        if avail_scopes.len() != req_scopes.len() {
-            admin_warn!(
-                %ident,
-                requested_scopes = ?req_scopes,
-                available_scopes = ?uat_scopes,
-                "Identity does not have access to the requested scopes"
-            );
-            return Err(Oauth2Error::AccessDenied);
-        }
-
-        drop(avail_scopes);
-
-        // ⚠️  At this point, per scopes we are *authorised*
-
-        // We now access the supplemental scopes that will be granted to this session. It is important
-        // we DO NOT do this prior to the requested scope check, just in case we accidentally
-        // confuse the two!
-
-        // The set of scopes that are being granted during this auth_request. This is a combination
-        // of the scopes that were requested, and the scopes we supplement.
-
         // MICRO OPTIMISATION = flag if we have openid first, so we can into_iter here rather than
         // cloning.
         let openid_requested = req_scopes.contains(OAUTH2_SCOPE_OPENID);
 
-        let granted_scopes: BTreeSet<String> = o2rs
-            .sup_scope_maps
-            .iter()
-            .filter_map(|(u, m)| {
-                if ident.is_memberof(*u) {
-                    Some(m.iter())
-                } else {
-                    None
-                }
-            })
-            .flatten()
-            .cloned()
-            .chain(req_scopes)
-            .collect();
+        let granted_scopes = oauth2_granted_scopes(o2rs, ident, req_scopes)?;
 
         let consent_previously_granted =
             if let Some(consent_scopes) = ident.get_oauth2_consent_scopes(o2rs.uuid) {
@@ -1469,14 +2311,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
 
             // IMPORTANT DISTINCTION - Here req scopes must contain openid, but the PII can be supplemented
             // be the servers scopes!
-            let mut pii_scopes = BTreeSet::default();
-            if openid_requested {
-                // Only mutate if things were requested under openid
-                if granted_scopes.contains(OAUTH2_SCOPE_EMAIL) {
-                    pii_scopes.insert(OAUTH2_SCOPE_EMAIL.to_string());
-                    pii_scopes.insert("email_verified".to_string());
-                }
-            };
+            let pii_scopes = oauth2_pii_scopes(openid_requested, &granted_scopes);
 
             // Subsequent we then return an encrypted session handle which allows
             // the user to indicate their consent to this authorisation.
@@ -1515,18 +2350,98 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
     }
 
     #[instrument(level = "debug", skip_all)]
-    pub fn check_oauth2_authorise_reject(
-        &self,
+    pub fn check_oauth2_device_user_code(
+        &mut self,
         ident: &Identity,
         uat: &UserAuthToken,
-        consent_token: &str,
+        user_code: &str,
         ct: Duration,
-    ) -> Result<Url, OperationError> {
-        // Decode the consent req with our system fernet key. Use a ttl of 5 minutes.
-        let consent_req: ConsentToken = self
-            .oauth2rs
-            .inner
-            .fernet
+    ) -> Result<AuthoriseResponse, Oauth2Error> {
+        let user_code = normalise_device_user_code(user_code);
+        let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
+
+        let session = oauth2_device_session_get(&mut self.qs_read, &user_code)
+            .map_err(Oauth2Error::ServerError)?
+            .filter(|session| {
+                session.expiry > odt_ct && matches!(session.state, Oauth2DeviceState::Pending)
+            })
+            .ok_or_else(|| {
+                security_info!("device user code not found, it may have expired");
+                Oauth2Error::InvalidGrant
+            })?;
+
+        let o2rs = session
+            .client_uuid
+            .and_then(|client_uuid| {
+                self.oauth2rs
+                    .inner
+                    .rs_set
+                    .values()
+                    .find(|o2rs| o2rs.uuid == client_uuid)
+            })
+            .ok_or_else(|| {
+                admin_warn!(
+                    "Invalid oauth2 client ({:?}) Have you configured the oauth2 resource server?",
+                    session.client_uuid
+                );
+                Oauth2Error::InvalidClientId
+            })?;
+
+        // Deny anonymous access to oauth2
+        if uat.uuid == UUID_ANONYMOUS {
+            admin_error!(
+                "Invalid oauth2 request - refusing to allow user that authenticated with anonymous"
+            );
+            return Err(Oauth2Error::AccessDenied);
+        }
+
+        let openid_requested = session.scopes.contains(OAUTH2_SCOPE_OPENID);
+        let granted_scopes = oauth2_granted_scopes(o2rs, ident, session.scopes)?;
+        let pii_scopes = oauth2_pii_scopes(openid_requested, &granted_scopes);
+
+        // rfc8628 section 5.4 - consent is always requested, even if the user has consented
+        // to this client before. The user code may have been sent to them by an attacker, so
+        // the user must confirm that this is the device they expect.
+        let consent_req = DeviceConsentToken {
+            client_id: o2rs.name.clone(),
+            ident_id: ident.get_event_origin_id(),
+            session_id: uat.session_id,
+            user_code,
+            scopes: granted_scopes.clone(),
+        };
+
+        let consent_data = serde_json::to_vec(&consent_req).map_err(|e| {
+            admin_error!(err = ?e, "Unable to encode device consent data");
+            Oauth2Error::ServerError(OperationError::SerdeJsonError)
+        })?;
+
+        let consent_token = self
+            .oauth2rs
+            .inner
+            .fernet
+            .encrypt_at_time(&consent_data, ct.as_secs());
+
+        Ok(AuthoriseResponse::ConsentRequested {
+            client_name: o2rs.displayname.clone(),
+            scopes: granted_scopes,
+            pii_scopes,
+            consent_token,
+        })
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub fn check_oauth2_authorise_reject(
+        &self,
+        ident: &Identity,
+        uat: &UserAuthToken,
+        consent_token: &str,
+        ct: Duration,
+    ) -> Result<Url, OperationError> {
+        // Decode the consent req with our system fernet key. Use a ttl of 5 minutes.
+        let consent_req: ConsentToken = self
+            .oauth2rs
+            .inner
+            .fernet
             .decrypt_at_time(consent_token, Some(300), ct.as_secs())
             .map_err(|_| {
                 admin_error!("Failed to decrypt consent request");
@@ -1620,6 +2535,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                 nbf,
                 auth_time: _,
                 nonce: _,
+                act,
             } => {
                 // Has this token expired?
                 let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
@@ -1670,6 +2586,52 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                     aud: Some(client_id),
                     iss: None,
                     jti: None,
+                    act,
+                })
+            }
+            Oauth2TokenType::ClientAccess {
+                scopes,
+                session_id,
+                expiry,
+                uuid,
+                iat,
+                nbf,
+            } => {
+                let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
+                if expiry <= odt_ct {
+                    security_info!(?uuid, "access token has expired, returning inactive");
+                    return Ok(AccessTokenIntrospectResponse::inactive());
+                }
+
+                // The token must belong to this rs, and the rs must still be granted
+                // every scope in the token. This allows an admin to invalidate these
+                // tokens by removing the client credentials scopes.
+                if uuid != o2rs.uuid || !scopes.is_subset(&o2rs.client_credentials_scopes) {
+                    security_info!(
+                        ?uuid,
+                        "client credentials are no longer valid, returning inactive"
+                    );
+                    return Ok(AccessTokenIntrospectResponse::inactive());
+                }
+
+                let scope = Some(str_join(&scopes));
+                let exp = expiry.unix_timestamp();
+
+                let token_type = Some("access_token".to_string());
+                Ok(AccessTokenIntrospectResponse {
+                    active: true,
+                    scope,
+                    client_id: Some(client_id.clone()),
+                    username: Some(client_id.clone()),
+                    token_type,
+                    iat: Some(iat),
+                    exp: Some(exp),
+                    nbf: Some(nbf),
+                    sub: Some(uuid.to_string()),
+                    aud: Some(client_id),
+                    iss: None,
+                    jti: Some(session_id.to_string()),
+                    act: None,
                 })
             }
             Oauth2TokenType::Refresh { .. } => Ok(AccessTokenIntrospectResponse::inactive()),
@@ -1724,6 +2686,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                 nbf,
                 auth_time: _,
                 nonce,
+                act: _,
             } => {
                 // Has this token expired?
                 let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
@@ -1779,7 +2742,10 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                 })
             }
             // https://openid.net/specs/openid-connect-basic-1_0.html#UserInfoErrorResponse
-            Oauth2TokenType::Refresh { .. } => Err(Oauth2Error::InvalidToken),
+            // There is no user to provide information about for client credentials.
+            Oauth2TokenType::Refresh { .. } | Oauth2TokenType::ClientAccess { .. } => {
+                Err(Oauth2Error::InvalidToken)
+            }
         }
     }
 
@@ -1804,7 +2770,29 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
         let scopes_supported = Some(o2rs.scopes_supported.iter().cloned().collect());
         let response_types_supported = vec![ResponseType::Code];
         let response_modes_supported = vec![ResponseMode::Query];
-        let grant_types_supported = vec![GrantType::AuthorisationCode];
+        let mut grant_types_supported = vec![GrantType::AuthorisationCode];
+        if matches!(o2rs.type_, OauthRSType::Basic { .. })
+            && !o2rs.client_credentials_scopes.is_empty()
+        {
+            grant_types_supported.push(GrantType::ClientCredentials);
+        }
+
+        if self
+            .oauth2rs
+            .inner
+            .rs_set
+            .values()
+            .any(|rs| rs.token_exchange_sources.contains(&o2rs.uuid))
+        {
+            grant_types_supported.push(GrantType::TokenExchange);
+        }
+
+        let device_authorization_endpoint = if o2rs.device_flow_enable {
+            grant_types_supported.push(GrantType::DeviceCode);
+            Some(o2rs.device_authorization_endpoint.clone())
+        } else {
+            None
+        };
         let subject_types_supported = vec![SubjectType::Public];
 
         let id_token_signing_alg_values_supported = match &o2rs.jws_signer {
@@ -1834,6 +2822,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
             userinfo_endpoint,
             jwks_uri,
             registration_endpoint: None,
+            device_authorization_endpoint,
             scopes_supported,
             response_types_supported,
             response_modes_supported,
@@ -1918,6 +2907,57 @@ fn parse_basic_authz(client_authz: &str) -> Result<(String, String), Oauth2Error
     Ok((client_id.to_string(), secret.to_string()))
 }
 
+// rfc8628 section 6.1 - user codes avoid vowels and ambiguous characters so that they
+// are easy to type, and can't spell words.
+const DEVICE_USER_CODE_CHARSET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const DEVICE_USER_CODE_LEN: usize = 8;
+
+fn device_user_code_from_random() -> String {
+    let mut rng = rand::thread_rng();
+    (0..DEVICE_USER_CODE_LEN)
+        .map(|_| DEVICE_USER_CODE_CHARSET[rng.gen_range(0..DEVICE_USER_CODE_CHARSET.len())] as char)
+        .collect()
+}
+
+/// Display the user code in two halves, such as `BCDF-GHJK`.
+fn format_device_user_code(user_code: &str) -> String {
+    let (a, b) = user_code.split_at(user_code.len() / 2);
+    format!("{a}-{b}")
+}
+
+/// Users may enter the code with any case, and with or without the separator.
+fn normalise_device_user_code(user_code: &str) -> String {
+    user_code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Public clients send their client_id in the request rather than with basic auth, so
+/// we need to handle both cases.
+fn parse_client_authz(
+    client_authz: Option<&str>,
+    client_id: Option<&String>,
+    client_secret: Option<&String>,
+) -> Result<(String, Option<String>), Oauth2Error> {
+    if let Some(client_authz) = client_authz {
+        let (client_id, secret) = parse_basic_authz(client_authz)?;
+        Ok((client_id, Some(secret)))
+    } else {
+        match (client_id, client_secret) {
+            (Some(a), b) => Ok((a.clone(), b.cloned())),
+            _ => {
+                // We at least need the client_id, else we can't proceed!
+                security_info!(
+                    "Invalid oauth2 authentication - no basic auth or missing client_id in request"
+                );
+                Err(Oauth2Error::AuthenticationRequired)
+            }
+        }
+    }
+}
+
 fn s_claims_for_account(
     o2rs: &Oauth2RS,
     account: &Account,
@@ -1963,6 +3003,97 @@ fn extra_claims_for_account(
     extra_claims
 }
 
+/// The personal information that will be released to the resource server by these scopes.
+fn oauth2_pii_scopes(
+    openid_requested: bool,
+    granted_scopes: &BTreeSet<String>,
+) -> BTreeSet<String> {
+    let mut pii_scopes = BTreeSet::default();
+    if openid_requested {
+        // Only mutate if things were requested under openid
+        if granted_scopes.contains(OAUTH2_SCOPE_EMAIL) {
+            pii_scopes.insert(OAUTH2_SCOPE_EMAIL.to_string());
+            pii_scopes.insert("email_verified".to_string());
+        }
+    };
+    pii_scopes
+}
+
+/// Determine the scopes granted to an identity that requested `req_scopes`. Every requested
+/// scope must be provided to the identity by the scope maps, else access is denied. The
+/// supplementary scopes of the identity are then added.
+/// The scopes that the scope maps of this rs make available to the identity.
+fn oauth2_scope_map_scopes(o2rs: &Oauth2RS, ident: &Identity) -> BTreeSet<String> {
+    o2rs.scope_maps
+        .iter()
+        .filter_map(|(u, m)| {
+            if ident.is_memberof(*u) {
+                Some(m.iter())
+            } else {
+                None
+            }
+        })
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+fn oauth2_granted_scopes(
+    o2rs: &Oauth2RS,
+    ident: &Identity,
+    req_scopes: BTreeSet<String>,
+) -> Result<BTreeSet<String>, Oauth2Error> {
+    let uat_scopes = oauth2_scope_map_scopes(o2rs, ident);
+
+    // Needs to use s.to_string due to &&str which can't use the str::to_string
+    let avail_scopes: Vec<String> = req_scopes
+        .intersection(&uat_scopes)
+        .map(|s| s.to_string())
+        .collect();
+
+    debug!(?o2rs.scope_maps);
+
+    // Due to the intersection above, this is correct because the equal len can only
+    // occur if all terms were satisfied - effectively this check is that avail_scopes
+    // and req_scopes are identical after intersection with the scopes defined by uat_scopes
+    if avail_scopes.len() != req_scopes.len() {
+        admin_warn!(
+            %ident,
+            requested_scopes = ?req_scopes,
+            available_scopes = ?uat_scopes,
+            "Identity does not have access to the requested scopes"
+        );
+        return Err(Oauth2Error::AccessDenied);
+    }
+
+    drop(avail_scopes);
+
+    // ⚠️  At this point, per scopes we are *authorised*
+
+    // We now access the supplemental scopes that will be granted to this session. It is important
+    // we DO NOT do this prior to the requested scope check, just in case we accidentally
+    // confuse the two!
+
+    // The set of scopes that are being granted during this auth_request. This is a combination
+    // of the scopes that were requested, and the scopes we supplement.
+    let granted_scopes: BTreeSet<String> = o2rs
+        .sup_scope_maps
+        .iter()
+        .filter_map(|(u, m)| {
+            if ident.is_memberof(*u) {
+                Some(m.iter())
+            } else {
+                None
+            }
+        })
+        .flatten()
+        .cloned()
+        .chain(req_scopes)
+        .collect();
+
+    Ok(granted_scopes)
+}
+
 fn str_join(set: &BTreeSet<String>) -> String {
     let alloc_len = set.iter().fold(0, |acc, s| acc + s.len() + 1);
     let mut buf = String::with_capacity(alloc_len);
@@ -1991,6 +3122,7 @@ mod tests {
     use kanidm_proto::v1::UserAuthToken;
     use openssl::sha;
 
+    use crate::idm::audit::AuditEvent;
     use crate::idm::oauth2::{AuthoriseResponse, Oauth2Error};
     use crate::idm::server::{IdmServer, IdmServerTransaction};
     use crate::prelude::*;
@@ -2340,10 +3472,11 @@ mod tests {
         (uat, ident)
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_basic_function(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (secret, uat, ident, _) =
@@ -2405,12 +3538,28 @@ mod tests {
         assert!(token_response.token_type == "bearer");
 
         assert!(idms_prox_write.commit().is_ok());
+
+        // The consent is recorded once the transaction commits.
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::Oauth2ConsentGranted {
+                uuid,
+                rs_name,
+                scopes,
+                ..
+            }) => {
+                assert_eq!(uuid, UUID_ADMIN);
+                assert_eq!(rs_name, "test_resource_server");
+                assert!(scopes.contains(OAUTH2_SCOPE_OPENID));
+            }
+            _ => assert!(false),
+        }
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_public_function(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (uat, ident, _) = setup_oauth2_resource_server_public(idms, ct).await;
@@ -2471,6 +3620,10 @@ mod tests {
         assert!(token_response.token_type == "bearer");
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
     }
 
     #[idm_test]
@@ -2726,10 +3879,11 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_invalid_token_exchange_requests(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (secret, mut uat, ident, _) =
@@ -2839,83 +3993,533 @@ mod tests {
                 == Oauth2Error::InvalidRequest
         );
 
-        //  * Uat has expired!
-        // NOTE: This is setup EARLY in the test, by manipulation of the UAT expiry.
+        //  * Uat has expired!
+        // NOTE: This is setup EARLY in the test, by manipulation of the UAT expiry.
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(
+                    client_authz.as_deref(),
+                    &token_req,
+                    ct + Duration::from_secs(UAT_EXPIRE)
+                )
+                .unwrap_err()
+                == Oauth2Error::AccessDenied
+        );
+
+        /*
+        //  * incorrect grant_type
+        // No longer possible due to changes in json api
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            grant_type: "INCORRECT GRANT TYPE".to_string(),
+            code: permit_success.code.clone(),
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            client_id: None,
+            client_secret: None,
+            code_verifier: code_verifier.clone(),
+        };
+        assert!(
+            idms_prox_read
+                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .unwrap_err()
+                == Oauth2Error::InvalidRequest
+        );
+        */
+
+        //  * Incorrect redirect uri
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code.clone(),
+            redirect_uri: Url::parse("https://totes.not.sus.org/oauth2/result").unwrap(),
+            code_verifier,
+        }
+        .into();
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .unwrap_err()
+                == Oauth2Error::InvalidOrigin
+        );
+
+        //  * code verifier incorrect
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code,
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            code_verifier: Some("12345".to_string()),
+        }
+        .into();
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .unwrap_err()
+                == Oauth2Error::InvalidRequest
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_oauth2_token_introspect(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, uat, ident, _) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz =
+            Some(general_purpose::STANDARD.encode(format!("test_resource_server:{secret}")));
+
+        let idms_prox_read = idms.proxy_read().await;
+
+        // == Setup the authorisation request
+        let (code_verifier, code_challenge) = create_code_verifier!("Whar Garble");
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            &uat,
+            ct,
+            code_challenge,
+            OAUTH2_SCOPE_OPENID.to_string()
+        );
+
+        let consent_token =
+            if let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request {
+                consent_token
+            } else {
+                unreachable!();
+            };
+
+        // == Manually submit the consent token to the permit for the permit_success
+        drop(idms_prox_read);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let permit_success = idms_prox_write
+            .check_oauth2_authorise_permit(&ident, &uat, &consent_token, ct)
+            .expect("Failed to perform oauth2 permit");
+
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code,
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            code_verifier,
+        }
+        .into();
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .expect("Unable to exchange for oauth2 token");
+
+        assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
+
+        // Okay, now we have the token, we can check it works with introspect.
+        let mut idms_prox_read = idms.proxy_read().await;
+
+        let intr_request = AccessTokenIntrospectRequest {
+            token: oauth2_token.access_token,
+            token_type_hint: None,
+        };
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .expect("Failed to inspect token");
+
+        eprintln!("👉  {intr_response:?}");
+        assert!(intr_response.active);
+        assert!(intr_response.scope.as_deref() == Some("openid supplement"));
+        assert!(intr_response.client_id.as_deref() == Some("test_resource_server"));
+        assert!(intr_response.username.as_deref() == Some("admin@example.com"));
+        assert!(intr_response.token_type.as_deref() == Some("access_token"));
+        assert!(intr_response.iat == Some(ct.as_secs() as i64));
+        assert!(intr_response.nbf == Some(ct.as_secs() as i64));
+
+        drop(idms_prox_read);
+        // start a write,
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        // Expire the account, should cause introspect to return inactive.
+        let v_expire = Value::new_datetime_epoch(Duration::from_secs(TEST_CURRENT_TIME - 1));
+        let me_inv_m = ModifyEvent::new_internal_invalid(
+            filter!(f_eq(Attribute::Name, PartialValue::new_iname("admin"))),
+            ModifyList::new_list(vec![Modify::Present(
+                Attribute::AccountExpire.into(),
+                v_expire,
+            )]),
+        );
+        // go!
+        assert!(idms_prox_write.qs_write.modify(&me_inv_m).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // start a new read
+        // check again.
+        let mut idms_prox_read = idms.proxy_read().await;
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_authz.unwrap(), &intr_request, ct)
+            .expect("Failed to inspect token");
+
+        assert!(!intr_response.active);
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_token_client_credentials(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, _ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz =
+            Some(general_purpose::STANDARD.encode(format!("test_resource_server:{secret}")));
+
+        let token_req: AccessTokenRequest = GrantTypeReq::ClientCredentials { scope: None }.into();
+
+        // Without any client credential scopes the grant is denied.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .unwrap_err()
+                == Oauth2Error::UnauthorizedClient
+        );
+
+        let modlist = ModifyList::new_list(vec![
+            Modify::Present(
+                Attribute::OAuth2RsClientCredentialsScopes.into(),
+                Value::new_oauthscope(OAUTH2_SCOPE_READ).expect("invalid oauthscope"),
+            ),
+            Modify::Present(
+                Attribute::OAuth2RsClientCredentialsScopes.into(),
+                Value::new_oauthscope("write").expect("invalid oauthscope"),
+            ),
+        ]);
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify(
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(rs_uuid))),
+                &modlist,
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // Can't request scopes beyond those granted to the rs.
+        let bad_token_req: AccessTokenRequest = GrantTypeReq::ClientCredentials {
+            scope: Some(btreeset!["write".to_string(), "admin".to_string()]),
+        }
+        .into();
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(client_authz.as_deref(), &bad_token_req, ct)
+                .unwrap_err()
+                == Oauth2Error::InvalidScope
+        );
+
+        // A subset of the scopes is fine.
+        let subset_token_req: AccessTokenRequest = GrantTypeReq::ClientCredentials {
+            scope: Some(btreeset![OAUTH2_SCOPE_READ.to_string()]),
+        }
+        .into();
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(client_authz.as_deref(), &subset_token_req, ct)
+            .expect("Unable to exchange for oauth2 token");
+        assert!(oauth2_token.scope.as_deref() == Some(OAUTH2_SCOPE_READ));
+
+        // Requesting no scopes grants everything.
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .expect("Unable to exchange for oauth2 token");
+
+        assert!(oauth2_token.scope.as_deref() == Some("read write"));
+        assert!(oauth2_token.refresh_token.is_none());
+        assert!(oauth2_token.id_token.is_none());
+
+        // The token can't be revoked, there is no session.
+        let revoke_request = TokenRevokeRequest {
+            token: oauth2_token.access_token.clone(),
+            token_type_hint: None,
+        };
+        assert!(
+            idms_prox_write
+                .oauth2_token_revoke(client_authz.as_deref().unwrap(), &revoke_request, ct)
+                .unwrap_err()
+                == Oauth2Error::UnsupportedTokenType
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+
+        // The subject of the token is the resource server.
+        let intr_request = AccessTokenIntrospectRequest {
+            token: oauth2_token.access_token.clone(),
+            token_type_hint: None,
+        };
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .expect("Failed to inspect token");
+
+        assert!(intr_response.active);
+        assert!(intr_response.scope.as_deref() == Some("read write"));
+        assert!(intr_response.client_id.as_deref() == Some("test_resource_server"));
+        assert!(intr_response.username.as_deref() == Some("test_resource_server"));
+        assert!(intr_response.sub == Some(rs_uuid.to_string()));
+
+        // There is no user, so there is no userinfo.
+        assert!(
+            idms_prox_read
+                .oauth2_openid_userinfo("test_resource_server", &oauth2_token.access_token, ct)
+                .unwrap_err()
+                == Oauth2Error::InvalidToken
+        );
+
+        let discovery = idms_prox_read
+            .oauth2_openid_discovery("test_resource_server")
+            .expect("Failed to get discovery");
+        assert!(discovery
+            .grant_types_supported
+            .contains(&GrantType::ClientCredentials));
+
+        drop(idms_prox_read);
+
+        // Removing the scopes from the rs invalidates the token.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let modlist = ModifyList::new_list(vec![Modify::Purged(
+            Attribute::OAuth2RsClientCredentialsScopes.into(),
+        )]);
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify(
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(rs_uuid))),
+                &modlist,
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(!intr_response.active);
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_oauth2_token_device_code(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, uat, ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz =
+            Some(general_purpose::STANDARD.encode(format!("test_resource_server:{secret}")));
+
+        let device_req = DeviceAuthorizationRequest {
+            client_id: None,
+            client_secret: None,
+            scope: Some(btreeset![
+                OAUTH2_SCOPE_OPENID.to_string(),
+                OAUTH2_SCOPE_GROUPS.to_string()
+            ]),
+        };
+
+        // The device flow must be enabled on the rs.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(
+            idms_prox_write
+                .check_oauth2_device_authorisation(client_authz.as_deref(), &device_req, ct)
+                .unwrap_err()
+                == Oauth2Error::UnauthorizedClient
+        );
+
+        let modlist = ModifyList::new_list(vec![Modify::Present(
+            Attribute::OAuth2DeviceFlowEnable.into(),
+            Value::new_bool(true),
+        )]);
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify(
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(rs_uuid))),
+                &modlist,
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let device_resp = idms_prox_write
+            .check_oauth2_device_authorisation(client_authz.as_deref(), &device_req, ct)
+            .expect("Failed to begin device authorisation");
+
+        assert!(device_resp.verification_uri.path() == "/ui/oauth2/device");
+        assert!(device_resp.expires_in == OAUTH2_DEVICE_CODE_EXPIRY);
+
+        // The device polls, but the user hasn't approved yet.
+        let token_req: AccessTokenRequest = GrantTypeReq::DeviceCode {
+            device_code: device_resp.device_code.clone(),
+        }
+        .into();
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .unwrap_err()
+                == Oauth2Error::AuthorizationPending
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Polling too quickly is rejected before a transaction is started.
+        assert!(
+            idms.oauth2_device_poll_allowed(&device_resp.device_code, ct)
+                .await
+        );
+        assert!(
+            !idms
+                .oauth2_device_poll_allowed(&device_resp.device_code, ct)
+                .await
+        );
+        assert!(
+            idms.oauth2_device_poll_allowed(
+                &device_resp.device_code,
+                ct + Duration::from_secs(OAUTH2_DEVICE_CODE_INTERVAL as u64)
+            )
+            .await
+        );
+
+        // The device authorisation is stored in the database, so any server can continue it.
+        let mut idms_prox_read = idms.proxy_read().await;
+        let device_sessions = idms_prox_read
+            .qs_read
+            .internal_search(filter!(f_eq(
+                Attribute::Class,
+                EntryClass::OAuth2DeviceAuthorisation.into()
+            )))
+            .expect("Failed to search device authorisations");
+        assert!(device_sessions.len() == 1);
+        drop(idms_prox_read);
+
+        // The user enters the code, without the separator and in lower case.
+        let user_code = device_resp.user_code.replace('-', "").to_lowercase();
+        let mut idms_prox_read = idms.proxy_read().await;
+        let consent_request = idms_prox_read
+            .check_oauth2_device_user_code(&ident, &uat, &user_code, ct)
+            .expect("Failed to find device authorisation");
+
+        // Consent is always requested for a device.
+        let AuthoriseResponse::ConsentRequested {
+            scopes,
+            consent_token,
+            ..
+        } = consent_request
+        else {
+            unreachable!();
+        };
+        assert!(scopes.contains(OAUTH2_SCOPE_OPENID));
+        assert!(scopes.contains("supplement"));
+
+        assert!(idms_prox_read
+            .check_oauth2_device_user_code(&ident, &uat, "BCDF-GHJK", ct)
+            .is_err());
+        drop(idms_prox_read);
+
+        let ct = ct + Duration::from_secs(OAUTH2_DEVICE_CODE_INTERVAL as u64);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write
+            .check_oauth2_device_authorise_permit(&ident, &uat, &consent_token, ct)
+            .expect("Failed to permit device");
+
+        // The consent token can't be reused.
+        assert!(idms_prox_write
+            .check_oauth2_device_authorise_reject(&ident, &uat, &consent_token, ct)
+            .is_err());
+
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .expect("Unable to exchange for oauth2 token");
+        assert!(oauth2_token.refresh_token.is_some());
+        assert!(oauth2_token.id_token.is_some());
+
+        // The device code can only be used once.
         assert!(
             idms_prox_write
-                .check_oauth2_token_exchange(
-                    client_authz.as_deref(),
-                    &token_req,
-                    ct + Duration::from_secs(UAT_EXPIRE)
-                )
+                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
                 .unwrap_err()
-                == Oauth2Error::AccessDenied
+                == Oauth2Error::ExpiredToken
         );
 
-        /*
-        //  * incorrect grant_type
-        // No longer possible due to changes in json api
-        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
-            grant_type: "INCORRECT GRANT TYPE".to_string(),
-            code: permit_success.code.clone(),
-            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
-            client_id: None,
-            client_secret: None,
-            code_verifier: code_verifier.clone(),
+        // A denied device receives access denied.
+        let device_resp = idms_prox_write
+            .check_oauth2_device_authorisation(client_authz.as_deref(), &device_req, ct)
+            .expect("Failed to begin device authorisation");
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Approving the device records the consent of the user.
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let consent_request = idms_prox_read
+            .check_oauth2_device_user_code(&ident, &uat, &device_resp.user_code, ct)
+            .expect("Failed to find device authorisation");
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
         };
-        assert!(
-            idms_prox_read
-                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
-                .unwrap_err()
-                == Oauth2Error::InvalidRequest
-        );
-        */
+        drop(idms_prox_read);
 
-        //  * Incorrect redirect uri
-        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
-            code: permit_success.code.clone(),
-            redirect_uri: Url::parse("https://totes.not.sus.org/oauth2/result").unwrap(),
-            code_verifier,
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write
+            .check_oauth2_device_authorise_reject(&ident, &uat, &consent_token, ct)
+            .expect("Failed to reject device");
+
+        let token_req: AccessTokenRequest = GrantTypeReq::DeviceCode {
+            device_code: device_resp.device_code.clone(),
         }
         .into();
         assert!(
             idms_prox_write
                 .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
                 .unwrap_err()
-                == Oauth2Error::InvalidOrigin
+                == Oauth2Error::AccessDenied
         );
 
-        //  * code verifier incorrect
-        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
-            code: permit_success.code,
-            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
-            code_verifier: Some("12345".to_string()),
+        // An abandoned device authorisation expires.
+        let device_resp = idms_prox_write
+            .check_oauth2_device_authorisation(client_authz.as_deref(), &device_req, ct)
+            .expect("Failed to begin device authorisation");
+        let token_req: AccessTokenRequest = GrantTypeReq::DeviceCode {
+            device_code: device_resp.device_code.clone(),
         }
         .into();
+        let ct = ct + Duration::from_secs(OAUTH2_DEVICE_CODE_EXPIRY as u64);
         assert!(
             idms_prox_write
                 .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
                 .unwrap_err()
-                == Oauth2Error::InvalidRequest
+                == Oauth2Error::ExpiredToken
         );
 
         assert!(idms_prox_write.commit().is_ok());
     }
 
-    #[idm_test]
-    async fn test_idm_oauth2_token_introspect(
+    #[idm_test(audit)]
+    async fn test_idm_oauth2_token_exchange_subject_token(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
-        let (secret, uat, ident, _) =
+        let (secret, uat, ident, rs_uuid) =
             setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
         let client_authz =
             Some(general_purpose::STANDARD.encode(format!("test_resource_server:{secret}")));
 
+        // == Get an access token for the upstream rs.
         let idms_prox_read = idms.proxy_read().await;
-
-        // == Setup the authorisation request
         let (code_verifier, code_challenge) = create_code_verifier!("Whar Garble");
         let consent_request = good_authorisation_request!(
             idms_prox_read,
@@ -2926,17 +4530,12 @@ mod tests {
             OAUTH2_SCOPE_OPENID.to_string()
         );
 
-        let consent_token =
-            if let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request {
-                consent_token
-            } else {
-                unreachable!();
-            };
-
-        // == Manually submit the consent token to the permit for the permit_success
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
+        };
         drop(idms_prox_read);
-        let mut idms_prox_write = idms.proxy_write(ct).await;
 
+        let mut idms_prox_write = idms.proxy_write(ct).await;
         let permit_success = idms_prox_write
             .check_oauth2_authorise_permit(&ident, &uat, &consent_token, ct)
             .expect("Failed to perform oauth2 permit");
@@ -2947,61 +4546,234 @@ mod tests {
             code_verifier,
         }
         .into();
-        let oauth2_token = idms_prox_write
+        let upstream_token = idms_prox_write
             .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
+        // == Setup the downstream rs.
+        let downstream_uuid = Uuid::new_v4();
+        let e: Entry<EntryInit, EntryNew> = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (
+                Attribute::Class,
+                EntryClass::OAuth2ResourceServer.to_value()
+            ),
+            (
+                Attribute::Class,
+                EntryClass::OAuth2ResourceServerBasic.to_value()
+            ),
+            (Attribute::Uuid, Value::Uuid(downstream_uuid)),
+            (Attribute::OAuth2RsName, Value::new_iname("downstream")),
+            (Attribute::DisplayName, Value::new_utf8s("downstream")),
+            (
+                Attribute::OAuth2RsOrigin,
+                Value::new_url_s("https://downstream.example.com").unwrap()
+            ),
+            (
+                Attribute::OAuth2RsScopeMap,
+                Value::new_oauthscopemap(
+                    UUID_IDM_ALL_ACCOUNTS,
+                    btreeset![OAUTH2_SCOPE_READ.to_string(), "write".to_string()]
+                )
+                .expect("invalid oauthscope")
+            )
+        );
+        let ce = CreateEvent::new_internal(vec![e]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+
+        let downstream_secret = idms_prox_write
+            .qs_write
+            .internal_search_uuid(downstream_uuid)
+            .expect("Failed to retrieve oauth2 resource entry")
+            .get_ava_single_secret(Attribute::OAuth2RsBasicSecret)
+            .map(str::to_string)
+            .expect("No oauth2_rs_basic_secret found");
+        let downstream_authz =
+            Some(general_purpose::STANDARD.encode(format!("downstream:{downstream_secret}")));
+
+        assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
+
+        let exchange_req = |audience: &str, scope: Option<BTreeSet<String>>, token: &str| {
+            AccessTokenRequest::from(GrantTypeReq::TokenExchange {
+                subject_token: token.to_string(),
+                subject_token_type: OAUTH2_TOKEN_TYPE_ACCESS_TOKEN.to_string(),
+                requested_token_type: None,
+                audience: Some(audience.to_string()),
+                scope,
+            })
+        };
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // The downstream rs has not allowed the exchange.
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(
+                    client_authz.as_deref(),
+                    &exchange_req("downstream", None, &upstream_token.access_token),
+                    ct
+                )
+                .unwrap_err()
+                == Oauth2Error::UnauthorizedClient
+        );
+
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(
+                    client_authz.as_deref(),
+                    &exchange_req("nonexistent", None, &upstream_token.access_token),
+                    ct
+                )
+                .unwrap_err()
+                == Oauth2Error::InvalidTarget
+        );
+
+        let modlist = ModifyList::new_list(vec![Modify::Present(
+            Attribute::OAuth2RsTokenExchangeSource.into(),
+            Value::Refer(rs_uuid),
+        )]);
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify(
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(downstream_uuid))),
+                &modlist,
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // Can't request scopes the user doesn't have on the downstream rs.
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(
+                    client_authz.as_deref(),
+                    &exchange_req(
+                        "downstream",
+                        Some(btreeset!["admin".to_string()]),
+                        &upstream_token.access_token
+                    ),
+                    ct
+                )
+                .unwrap_err()
+                == Oauth2Error::InvalidScope
+        );
+
+        // A narrower token can be requested.
+        let downstream_token = idms_prox_write
+            .check_oauth2_token_exchange(
+                client_authz.as_deref(),
+                &exchange_req(
+                    "downstream",
+                    Some(btreeset![OAUTH2_SCOPE_READ.to_string()]),
+                    &upstream_token.access_token,
+                ),
+                ct,
+            )
+            .expect("Unable to exchange token");
+
+        assert!(downstream_token.scope.as_deref() == Some(OAUTH2_SCOPE_READ));
+        assert!(downstream_token.refresh_token.is_none());
+        assert!(
+            downstream_token.issued_token_type.as_deref() == Some(OAUTH2_TOKEN_TYPE_ACCESS_TOKEN)
+        );
+
+        // The client can only exchange tokens that were issued to it.
+        assert!(
+            idms_prox_write
+                .check_oauth2_token_exchange(
+                    client_authz.as_deref(),
+                    &exchange_req("downstream", None, &downstream_token.access_token),
+                    ct
+                )
+                .unwrap_err()
+                == Oauth2Error::InvalidRequest
+        );
+
+        // Allow the downstream to exchange back to the upstream, to check the delegation chain.
+        let modlist = ModifyList::new_list(vec![Modify::Present(
+            Attribute::OAuth2RsTokenExchangeSource.into(),
+            Value::Refer(downstream_uuid),
+        )]);
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify(
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(rs_uuid))),
+                &modlist,
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let chained_token = idms_prox_write
+            .check_oauth2_token_exchange(
+                downstream_authz.as_deref(),
+                &exchange_req("test_resource_server", None, &downstream_token.access_token),
+                ct,
+            )
+            .expect("Unable to exchange token");
         assert!(idms_prox_write.commit().is_ok());
 
-        // Okay, now we have the token, we can check it works with introspect.
         let mut idms_prox_read = idms.proxy_read().await;
 
+        // The downstream token records who acted on behalf of the user.
         let intr_request = AccessTokenIntrospectRequest {
-            token: oauth2_token.access_token,
+            token: downstream_token.access_token,
             token_type_hint: None,
         };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .check_oauth2_token_introspect(downstream_authz.as_deref().unwrap(), &intr_request, ct)
             .expect("Failed to inspect token");
 
-        eprintln!("👉  {intr_response:?}");
         assert!(intr_response.active);
-        assert!(intr_response.scope.as_deref() == Some("openid supplement"));
-        assert!(intr_response.client_id.as_deref() == Some("test_resource_server"));
         assert!(intr_response.username.as_deref() == Some("admin@example.com"));
-        assert!(intr_response.token_type.as_deref() == Some("access_token"));
-        assert!(intr_response.iat == Some(ct.as_secs() as i64));
-        assert!(intr_response.nbf == Some(ct.as_secs() as i64));
-
-        drop(idms_prox_read);
-        // start a write,
-
-        let mut idms_prox_write = idms.proxy_write(ct).await;
-        // Expire the account, should cause introspect to return inactive.
-        let v_expire = Value::new_datetime_epoch(Duration::from_secs(TEST_CURRENT_TIME - 1));
-        let me_inv_m = ModifyEvent::new_internal_invalid(
-            filter!(f_eq(Attribute::Name, PartialValue::new_iname("admin"))),
-            ModifyList::new_list(vec![Modify::Present(
-                Attribute::AccountExpire.into(),
-                v_expire,
-            )]),
+        assert!(intr_response.sub == Some(uat.uuid.to_string()));
+        assert!(
+            intr_response.act
+                == Some(ActorClaim {
+                    sub: "test_resource_server".to_string(),
+                    act: None,
+                })
         );
-        // go!
-        assert!(idms_prox_write.qs_write.modify(&me_inv_m).is_ok());
-        assert!(idms_prox_write.commit().is_ok());
 
-        // start a new read
-        // check again.
-        let mut idms_prox_read = idms.proxy_read().await;
+        let intr_request = AccessTokenIntrospectRequest {
+            token: chained_token.access_token,
+            token_type_hint: None,
+        };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&client_authz.unwrap(), &intr_request, ct)
+            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
             .expect("Failed to inspect token");
 
-        assert!(!intr_response.active);
+        assert!(intr_response.active);
+        assert!(
+            intr_response.act
+                == Some(ActorClaim {
+                    sub: "downstream".to_string(),
+                    act: Some(Box::new(ActorClaim {
+                        sub: "test_resource_server".to_string(),
+                        act: None,
+                    })),
+                })
+        );
+
+        let discovery = idms_prox_read
+            .oauth2_openid_discovery("test_resource_server")
+            .expect("Failed to get discovery");
+        assert!(discovery
+            .grant_types_supported
+            .contains(&GrantType::TokenExchange));
     }
 
-    #[idm_test]
-    async fn test_idm_oauth2_token_revoke(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
+    #[idm_test(audit)]
+    async fn test_idm_oauth2_token_revoke(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
         // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (secret, uat, ident, _) =
@@ -3049,6 +4821,10 @@ mod tests {
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
 
         // Okay, now we have the token, we can check behaviours with the revoke interface.
 
@@ -3164,10 +4940,11 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_session_cleanup_post_rs_delete(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
@@ -3222,9 +4999,14 @@ mod tests {
         let session_id = match reflected_token {
             Oauth2TokenType::Refresh { session_id, .. } => session_id,
             Oauth2TokenType::Access { session_id, .. } => session_id,
+            Oauth2TokenType::ClientAccess { .. } => unreachable!(),
         };
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
 
         // Process it to ensure the record exists.
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -3495,10 +5277,11 @@ mod tests {
         assert!(discovery.request_parameter_supported);
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_openid_extensions(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (secret, uat, ident, _) =
@@ -3560,6 +5343,10 @@ mod tests {
 
         // Get the read txn for inspecting the tokens
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
 
         let mut idms_prox_read = idms.proxy_read().await;
 
@@ -3673,10 +5460,11 @@ mod tests {
         assert!(userinfo.claims.is_empty());
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_openid_short_username(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // we run the same test as test_idm_oauth2_openid_extensions()
         // but change the preferred_username setting on the RS
@@ -3731,6 +5519,10 @@ mod tests {
         let access_token = token_response.access_token;
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
         let mut idms_prox_read = idms.proxy_read().await;
 
         let mut jwkset = idms_prox_read
@@ -3759,10 +5551,11 @@ mod tests {
         assert!(oidc.s_claims == userinfo.s_claims);
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_openid_group_claims(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // we run the same test as test_idm_oauth2_openid_extensions()
         // but change the preferred_username setting on the RS
@@ -3817,6 +5610,10 @@ mod tests {
         let access_token = token_response.access_token;
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
         let mut idms_prox_read = idms.proxy_read().await;
 
         let mut jwkset = idms_prox_read
@@ -3897,10 +5694,11 @@ mod tests {
             .expect("Oauth2 authorisation failed");
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_openid_legacy_crypto(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (secret, uat, ident, _) =
@@ -3995,12 +5793,17 @@ mod tests {
         assert!(oidc.sub == OidcSubject::U(UUID_ADMIN));
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_consent_granted_and_changed_workflow(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (_secret, uat, ident, _) =
@@ -4035,6 +5838,10 @@ mod tests {
             .expect("Failed to perform oauth2 permit");
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
 
         // == Now try the authorise again, should be in the permitted state.
         let mut idms_prox_read = idms.proxy_read().await;
@@ -4193,10 +6000,11 @@ mod tests {
         };
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_consent_granted_refint_cleanup_on_delete(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (_secret, uat, ident, o2rs_uuid) =
@@ -4264,9 +6072,13 @@ mod tests {
         assert!(consent_scopes.is_none());
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     // https://datatracker.ietf.org/doc/html/draft-ietf-oauth-security-topics#section-4.8
     //
     // It was reported we were vulnerable to this attack, but that isn't the case. First
@@ -4289,6 +6101,7 @@ mod tests {
     async fn test_idm_oauth2_1076_pkce_downgrade(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         // Enable pkce is set to FALSE
@@ -4357,15 +6170,20 @@ mod tests {
         ));
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     // https://datatracker.ietf.org/doc/html/draft-ietf-oauth-security-topics#section-2.1
     //
     // If the origin configured is https, do not allow downgrading to http on redirect
     async fn test_idm_oauth2_redir_http_downgrade(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         // Enable pkce is set to FALSE
@@ -4449,11 +6267,16 @@ mod tests {
         ));
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
     }
 
     async fn setup_refresh_token(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
         ct: Duration,
     ) -> (AccessTokenResponse, Option<String>) {
         // First, setup to get a token.
@@ -4501,22 +6324,27 @@ mod tests {
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::Oauth2ConsentGranted { .. })
+        ));
 
         trace!(?access_token_response_1);
 
         (access_token_response_1, client_authz)
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_refresh_token_basic(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
         let (access_token_response_1, client_authz) =
-            setup_refresh_token(idms, idms_delayed, ct).await;
+            setup_refresh_token(idms, idms_delayed, idms_audit, ct).await;
 
         // ============================================
         // test basic refresh while access still valid.
@@ -4568,7 +6396,9 @@ mod tests {
 
         let refresh_exp = match reflected_token {
             Oauth2TokenType::Refresh { expiry, .. } => expiry.unix_timestamp(),
-            Oauth2TokenType::Access { .. } => unreachable!(),
+            Oauth2TokenType::Access { .. } | Oauth2TokenType::ClientAccess { .. } => {
+                unreachable!()
+            }
         };
 
         let token_req: AccessTokenRequest = GrantTypeReq::RefreshToken {
@@ -4621,16 +6451,17 @@ mod tests {
     }
 
     // refresh when oauth2 parent session exp / missing.
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_refresh_token_oauth2_session_expired(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
         let (access_token_response_1, client_authz) =
-            setup_refresh_token(idms, idms_delayed, ct).await;
+            setup_refresh_token(idms, idms_delayed, idms_audit, ct).await;
 
         // ============================================
         // Revoke the oauth2 session
@@ -4671,16 +6502,17 @@ mod tests {
     }
 
     // refresh with wrong client id/authz
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_refresh_token_invalid_client_authz(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
         let (access_token_response_1, mut client_authz) =
-            setup_refresh_token(idms, idms_delayed, ct).await;
+            setup_refresh_token(idms, idms_delayed, idms_audit, ct).await;
 
         if let Some(s) = client_authz.as_mut() {
             s.push_str("invalid")
@@ -4712,16 +6544,17 @@ mod tests {
     }
 
     // Incorrect scopes re-requested
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_refresh_token_inconsistent_scopes(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
         let (access_token_response_1, client_authz) =
-            setup_refresh_token(idms, idms_delayed, ct).await;
+            setup_refresh_token(idms, idms_delayed, idms_audit, ct).await;
 
         // ============================================
         // Refresh with different scopes
@@ -4751,16 +6584,17 @@ mod tests {
     // Test that reuse of a refresh token is denied + terminates the session.
     //
     // https://www.ietf.org/archive/id/draft-ietf-oauth-security-topics-18.html#refresh_token_protection
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_refresh_token_reuse_invalidates_session(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
         let (access_token_response_1, client_authz) =
-            setup_refresh_token(idms, idms_delayed, ct).await;
+            setup_refresh_token(idms, idms_delayed, idms_audit, ct).await;
 
         // ============================================
         // Use the refresh token once
@@ -4829,16 +6663,17 @@ mod tests {
     // use refresh 2 -> access + refresh 3
     //    check the session state.
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_oauth2_refresh_token_divergence(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
         let (access_token_response_1, client_authz) =
-            setup_refresh_token(idms, idms_delayed, ct).await;
+            setup_refresh_token(idms, idms_delayed, idms_audit, ct).await;
 
         // ============================================
         // Use the refresh token once
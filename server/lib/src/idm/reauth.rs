@@ -588,18 +588,30 @@ mod tests {
         }
     }
 
-    #[idm_test]
-    async fn test_idm_reauth_passkey(idms: &IdmServer, idms_delayed: &mut IdmServerDelayed) {
+    #[idm_test(audit)]
+    async fn test_idm_reauth_passkey(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
         let ct = duration_from_epoch_now();
 
         // Setup the test account
         setup_testaccount(idms, ct).await;
         let mut passkey = setup_testaccount_passkey(idms, ct).await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::CredentialUpdate { uuid, .. }) => assert_eq!(uuid, TESTPERSON_UUID),
+            _ => assert!(false),
+        }
 
         // Do an initial auth.
         let token = auth_passkey(idms, ct, &mut passkey, idms_delayed)
             .await
             .expect("failed to authenticate with passkey");
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, TESTPERSON_UUID),
+            _ => assert!(false),
+        }
 
         // Token_str to uat
         let ident = token_to_ident(idms, ct, Some(token.as_str())).await;
@@ -619,6 +631,12 @@ mod tests {
         let token = reauth_passkey(idms, ct, &ident, &mut passkey, idms_delayed)
             .await
             .expect("Failed to get new session token");
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::PrivilegeReauthentication { uuid, .. }) => {
+                assert_eq!(uuid, TESTPERSON_UUID)
+            }
+            _ => assert!(false),
+        }
 
         // Token_str to uat
         let ident = token_to_ident(idms, ct, Some(token.as_str())).await;
@@ -641,11 +659,19 @@ mod tests {
         // Setup the test account
         setup_testaccount(idms, ct).await;
         let (pw, totp) = setup_testaccount_password_totp(idms, ct).await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::CredentialUpdate { uuid, .. }) => assert_eq!(uuid, TESTPERSON_UUID),
+            _ => assert!(false),
+        }
 
         // Do an initial auth.
         let token = auth_password_totp(idms, ct, &pw, &totp, idms_delayed)
             .await
             .expect("failed to authenticate with passkey");
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, TESTPERSON_UUID),
+            _ => assert!(false),
+        }
 
         // Token_str to uat
         let ident = token_to_ident(idms, ct, Some(token.as_str())).await;
@@ -8,9 +8,13 @@ use kanidm_proto::scim_v1::*;
 use kanidm_proto::v1::ApiTokenPurpose;
 use std::collections::{BTreeMap, BTreeSet};
 
+use crate::be::BackendTransaction;
 use crate::credential::totp::{Totp, TotpAlgo, TotpDigits};
+use crate::idm::audit::AuditEvent;
 use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
 use crate::prelude::*;
+use crate::repl::entry::State;
+use crate::repl::ruv::ReplicationUpdateVectorTransaction;
 use crate::value::ApiToken;
 
 use crate::schema::{SchemaClass, SchemaTransaction};
@@ -494,7 +498,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         &mut self,
         sse: &ScimSyncUpdateEvent,
         changes: &ScimSyncRequest,
-        _ct: Duration,
+        ct: Duration,
     ) -> Result<(), OperationError> {
         let (sync_uuid, sync_authority_set, change_entries, sync_refresh) =
             self.scim_sync_apply_phase_1(sse, changes)?;
@@ -523,7 +527,14 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         self.scim_sync_apply_phase_4(&changes.retain, sync_uuid)?;
 
         // Final house keeping. Commit the new sync state.
-        self.scim_sync_apply_phase_5(sync_uuid, &changes.to_state)
+        self.scim_sync_apply_phase_5(sync_uuid, &changes.to_state)?;
+
+        self.qs_write
+            .audit_event_on_commit(AuditEvent::SyncAccountApply {
+                uuid: sync_uuid,
+                time: time::OffsetDateTime::UNIX_EPOCH + ct,
+            });
+        Ok(())
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -841,6 +852,19 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                 })
                 .map(|value| vec![Value::Uint32(value)]),
 
+            (
+                SyntaxType::DateTime,
+                false,
+                ScimAttr::SingleSimple(ScimSimpleAttr::String(value)),
+            ) => Value::new_datetime_s(value)
+                .map(|value| vec![value])
+                .ok_or_else(|| {
+                    error!("Invalid value - not a valid rfc3339 datetime");
+                    OperationError::InvalidAttribute(format!(
+                        "Invalid rfc3339 datetime - {scim_attr_name}"
+                    ))
+                }),
+
             (SyntaxType::ReferenceUuid, true, ScimAttr::MultiComplex(values)) => {
                 // In this case, because it's a reference uuid only, despite the multicomplex structure, it's a list of
                 // "external_id" to external_ids. These *might* also be uuids. So we need to use sync_external_id_to_uuid
@@ -1507,20 +1531,250 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
     }
 }
 
+/// Determine the sync agreement of a synchronise session.
+fn scim_sync_ident_uuid(ident: &Identity) -> Result<Uuid, OperationError> {
+    let sync_uuid = match &ident.origin {
+        IdentType::User(_) | IdentType::Internal => {
+            warn!("Ident type is not synchronise");
+            return Err(OperationError::AccessDenied);
+        }
+        IdentType::Synch(u) => *u,
+    };
+
+    match ident.access_scope() {
+        AccessScope::ReadOnly | AccessScope::ReadWrite => {
+            warn!("Ident access scope is not synchronise");
+            Err(OperationError::AccessDenied)
+        }
+        AccessScope::Synchronise => Ok(sync_uuid),
+    }
+}
+
+/// The writeback cookie records, for each server, the newest change from that server that
+/// had been received when the writeback was generated. Replication delivers the changes of
+/// each server in order, so like the replication update vector, a change that arrives late
+/// from another server is still newer than the cookie entry of the server that made it.
+type ScimSyncWritebackCookie = BTreeMap<Uuid, Duration>;
+
+// Each server in the cookie is its uuid followed by the nanoseconds of its newest change.
+const SCIM_SYNC_WRITEBACK_COOKIE_ENTRY_LEN: usize = 24;
+
+fn scim_sync_writeback_cookie_decode(
+    cookie: &[u8],
+) -> Result<ScimSyncWritebackCookie, OperationError> {
+    if cookie.len() % SCIM_SYNC_WRITEBACK_COOKIE_ENTRY_LEN != 0 {
+        error!("Invalid Sync Writeback Cookie - incorrect length");
+        return Err(OperationError::InvalidSyncState);
+    }
+
+    cookie
+        .chunks_exact(SCIM_SYNC_WRITEBACK_COOKIE_ENTRY_LEN)
+        .map(|server| {
+            let (s_uuid, nanos) = server.split_at(16);
+            let s_uuid = Uuid::from_slice(s_uuid).map_err(|_| {
+                error!("Invalid Sync Writeback Cookie - invalid server uuid");
+                OperationError::InvalidSyncState
+            })?;
+            let nanos: [u8; 8] = nanos.try_into().map_err(|_| {
+                error!("Invalid Sync Writeback Cookie - invalid change time");
+                OperationError::InvalidSyncState
+            })?;
+            Ok((s_uuid, Duration::from_nanos(u64::from_be_bytes(nanos))))
+        })
+        .collect()
+}
+
+fn scim_sync_writeback_cookie_encode(cookie: &ScimSyncWritebackCookie) -> Base64UrlSafeData {
+    Base64UrlSafeData(
+        cookie
+            .iter()
+            .flat_map(|(s_uuid, ts)| {
+                s_uuid
+                    .as_bytes()
+                    .iter()
+                    .copied()
+                    .chain((ts.as_nanos() as u64).to_be_bytes())
+            })
+            .collect(),
+    )
+}
+
+/// If a change was made after the writeback that generated the cookie.
+fn scim_sync_writeback_is_new(cookie: &ScimSyncWritebackCookie, cid: &Cid) -> bool {
+    cookie.get(&cid.s_uuid).map_or(true, |ts| cid.ts > *ts)
+}
+
+/// Render the values of an attribute in the form they would be written to an external
+/// directory. Credentials are sent as their password hash, if it can be represented.
+fn scim_sync_writeback_values(vs: &ValueSet) -> Vec<String> {
+    match vs.syntax() {
+        SyntaxType::Credential => vs
+            .as_credential_map()
+            .map(|cred_map| {
+                cred_map
+                    .values()
+                    .filter_map(|cred| cred.password_ref().ok())
+                    .filter_map(|pw| pw.to_ldap_userpassword())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        SyntaxType::SshKey => vs
+            .as_sshkey_map()
+            .map(|ssh_map| ssh_map.values().cloned().collect())
+            .unwrap_or_default(),
+        _ => vs.to_proto_string_clone_iter().collect(),
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// Retrieve the changes to synchronised entries that have been made in Kanidm since the
+    /// last acknowledged writeback, for attributes that the sync agreement has yielded
+    /// authority over.
+    #[instrument(level = "debug", skip_all)]
+    pub fn scim_sync_get_writeback(
+        &mut self,
+        ident: &Identity,
+    ) -> Result<ScimSyncWriteback, OperationError> {
+        // As with the sync state, we do internal searches here that bypass access controls.
+        let sync_uuid = scim_sync_ident_uuid(ident)?;
+
+        let sync_entry = self.qs_read.internal_search_uuid(sync_uuid)?;
+
+        let from_cookie = sync_entry
+            .get_ava_single_private_binary(Attribute::SyncWritebackCookie)
+            .map(|b| Base64UrlSafeData(b.to_vec()));
+
+        let from_ruv = from_cookie
+            .as_ref()
+            .map(|cookie| scim_sync_writeback_cookie_decode(&cookie.0))
+            .transpose()?
+            .unwrap_or_default();
+
+        // Everything this server has received up to now is considered by this writeback.
+        // Servers that have since been trimmed from the RUV keep their last position.
+        let mut to_ruv = from_ruv.clone();
+        for (s_uuid, range) in self.qs_read.get_be_txn().get_ruv().current_ruv_range()? {
+            let ts = to_ruv.entry(s_uuid).or_default();
+            *ts = range.ts_max.max(*ts);
+        }
+
+        let sync_authority_set = sync_entry
+            .get_ava_as_iutf8(Attribute::SyncYieldAuthority)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut entries = Vec::new();
+
+        if !sync_authority_set.is_empty() {
+            let synced_entries = self.qs_read.internal_search(filter!(f_eq(
+                Attribute::SyncParentUuid,
+                PartialValue::Refer(sync_uuid)
+            )))?;
+
+            for entry in synced_entries.iter() {
+                let State::Live { changes, .. } = entry.get_changestate().current() else {
+                    continue;
+                };
+
+                let attrs: BTreeMap<_, _> = sync_authority_set
+                    .iter()
+                    .filter_map(|attr| {
+                        let cid = changes.get(attr.as_str())?;
+                        if !scim_sync_writeback_is_new(&from_ruv, cid) {
+                            return None;
+                        }
+
+                        let values = entry
+                            .get_ava()
+                            .get(attr.as_str())
+                            .map(scim_sync_writeback_values)
+                            .unwrap_or_default();
+
+                        Some((
+                            attr.clone(),
+                            ScimSyncWritebackAttr {
+                                values,
+                                changed: time::OffsetDateTime::UNIX_EPOCH + cid.ts,
+                            },
+                        ))
+                    })
+                    .collect();
+
+                if !attrs.is_empty() {
+                    entries.push(ScimSyncWritebackEntry {
+                        id: entry.get_uuid(),
+                        external_id: entry
+                            .get_ava_single_iutf8(Attribute::SyncExternalId)
+                            .map(str::to_string),
+                        attrs,
+                    });
+                }
+            }
+        }
+
+        Ok(ScimSyncWriteback {
+            from_cookie,
+            to_cookie: scim_sync_writeback_cookie_encode(&to_ruv),
+            entries,
+        })
+    }
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    /// Record that changes up to this cookie have been written to the external source.
+    #[instrument(level = "info", skip_all)]
+    pub fn scim_sync_writeback_ack(
+        &mut self,
+        sse: &ScimSyncUpdateEvent,
+        ack: &ScimSyncWritebackAck,
+    ) -> Result<(), OperationError> {
+        let sync_uuid = scim_sync_ident_uuid(&sse.ident)?;
+
+        let sync_entry = self.qs_write.internal_search_uuid(sync_uuid)?;
+
+        // Like the sync state, the writeback must follow from what we last recorded, else
+        // another writeback has occurred and changes may be lost or duplicated.
+        let current = sync_entry.get_ava_single_private_binary(Attribute::SyncWritebackCookie);
+        if current != ack.from_cookie.as_ref().map(|c| c.0.as_slice()) {
+            error!("Invalid Sync Writeback State - agreement has a divergent writeback cookie.");
+            return Err(OperationError::InvalidSyncState);
+        }
+
+        // Validate the new cookie before we store it.
+        scim_sync_writeback_cookie_decode(&ack.to_cookie.0)?;
+
+        self.qs_write
+            .internal_modify_uuid(
+                sync_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::SyncWritebackCookie,
+                    Value::PrivateBinary(ack.to_cookie.0.clone()),
+                ),
+            )
+            .map_err(|e| {
+                error!("Failed to update sync entry writeback state");
+                e
+            })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::idm::audit::AuditEvent;
     use crate::idm::server::{IdmServerProxyWriteTransaction, IdmServerTransaction};
     use crate::prelude::*;
     use base64urlsafedata::Base64UrlSafeData;
     use compact_jwt::Jws;
     use kanidm_proto::scim_v1::*;
     use kanidm_proto::v1::ApiTokenPurpose;
+    use std::collections::BTreeMap;
     use std::sync::Arc;
     use std::time::Duration;
 
     use super::{
-        GenerateScimSyncTokenEvent, ScimSyncFinaliseEvent, ScimSyncTerminateEvent, ScimSyncToken,
-        ScimSyncUpdateEvent,
+        scim_sync_writeback_cookie_decode, scim_sync_writeback_cookie_encode,
+        scim_sync_writeback_is_new, GenerateScimSyncTokenEvent, ScimSyncFinaliseEvent,
+        ScimSyncTerminateEvent, ScimSyncToken, ScimSyncUpdateEvent, ScimSyncWritebackCookie,
     };
 
     const TEST_CURRENT_TIME: u64 = 6000;
@@ -1717,6 +1971,13 @@ mod tests {
         assert!(matches!(fail, Err(OperationError::NotAuthenticated)));
     }
 
+    fn check_sync_apply_audit(idms_audit: &mut IdmServerAudit, sync_uuid: Uuid) {
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::SyncAccountApply { uuid, .. }) => assert_eq!(uuid, sync_uuid),
+            _ => assert!(false),
+        }
+    }
+
     fn test_scim_sync_apply_setup_ident(
         idms_prox_write: &mut IdmServerProxyWriteTransaction,
         ct: Duration,
@@ -1959,6 +2220,57 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
+    #[idm_test]
+    async fn test_idm_scim_sync_phase_3_account_expire(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let user_sync_uuid = Uuid::new_v4();
+
+        let person = |account_expire: &str| ScimSyncPerson {
+            id: user_sync_uuid,
+            external_id: Some("cn=testuser,ou=people,dc=test".to_string()),
+            user_name: "testuser".to_string(),
+            display_name: "Test User".to_string(),
+            gidnumber: None,
+            password_import: None,
+            totp_import: Vec::default(),
+            login_shell: None,
+            mail: Vec::default(),
+            ssh_publickey: Vec::default(),
+            account_expire: Some(account_expire.to_string()),
+            account_valid_from: None,
+        };
+
+        // Not a datetime
+        assert!(apply_phase_3_test(idms, vec![person("tomorrow").into()])
+            .await
+            .is_err());
+
+        assert!(
+            apply_phase_3_test(idms, vec![person("2023-06-01T00:00:00Z").into()])
+                .await
+                .is_ok()
+        );
+
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let ent = idms_prox_write
+            .qs_write
+            .internal_search_uuid(user_sync_uuid)
+            .expect("Unable to access entry");
+
+        assert!(
+            ent.get_ava_single_datetime(Attribute::AccountExpire)
+                .map(|odt| odt.unix_timestamp())
+                == Some(1685577600)
+        );
+        assert!(!ent.attribute_pres(Attribute::AccountValidFrom));
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
     // -- try to set uuid
     #[idm_test]
     async fn test_idm_scim_sync_phase_3_uuid_manipulation(
@@ -2085,17 +2397,18 @@ mod tests {
     // Phase 4
 
     // Good delete - requires phase 5 due to need to do two syncs
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_phase_4_correct_delete(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let user_sync_uuid = Uuid::new_v4();
         // Create an entry via sync
 
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent {
             ident: ident.clone(),
         };
@@ -2120,6 +2433,7 @@ mod tests {
 
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Now we can attempt the delete.
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2154,17 +2468,19 @@ mod tests {
             .unwrap_or(false));
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
     // Delete that doesn't exist.
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_phase_4_nonexisting_delete(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent { ident };
 
         let changes = ScimSyncRequest {
@@ -2183,6 +2499,7 @@ mod tests {
         // nothing.
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
     // Delete of something outside of agreement control - must fail.
@@ -2223,10 +2540,11 @@ mod tests {
     }
 
     // Delete already deleted entry.
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_phase_4_delete_already_deleted(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2245,7 +2563,7 @@ mod tests {
             .internal_delete_uuid(user_sync_uuid)
             .is_ok());
 
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent { ident };
 
         let changes = ScimSyncRequest {
@@ -2264,12 +2582,14 @@ mod tests {
         // the delete req applies to a live entry.
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_phase_4_correct_retain(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // Setup two entries.
         let sync_uuid_a = Uuid::new_v4();
@@ -2278,7 +2598,7 @@ mod tests {
 
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent {
             ident: ident.clone(),
         };
@@ -2315,6 +2635,7 @@ mod tests {
 
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Now retain only a single entry
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2349,12 +2670,14 @@ mod tests {
             .unwrap_or(false));
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_phase_4_retain_none(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // Setup two entries.
         let sync_uuid_a = Uuid::new_v4();
@@ -2362,7 +2685,7 @@ mod tests {
 
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent {
             ident: ident.clone(),
         };
@@ -2399,6 +2722,7 @@ mod tests {
 
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Now retain no entries at all
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2448,19 +2772,21 @@ mod tests {
             .unwrap_or(false));
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_phase_4_retain_no_deletes(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // Setup two entries.
         let sync_uuid_a = Uuid::new_v4();
 
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent {
             ident: ident.clone(),
         };
@@ -2485,6 +2811,7 @@ mod tests {
 
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Now retain no entries at all
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2512,17 +2839,19 @@ mod tests {
         assert!(ent.get_ava_single_iname(Attribute::Name) == Some("testgroup"));
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
     // Phase 5
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_phase_5_from_refresh_to_active(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent {
             ident: ident.clone(),
         };
@@ -2538,6 +2867,7 @@ mod tests {
 
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Advance the from -> to state.
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2556,6 +2886,7 @@ mod tests {
 
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
     // Test the client doing a sync refresh request (active -> refresh).
@@ -2584,14 +2915,15 @@ mod tests {
             .expect("Failed to access entry.")
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_refresh_ipa_example_1(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent { ident };
 
         let changes =
@@ -2600,6 +2932,7 @@ mod tests {
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Test properties of the imported entries.
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2670,6 +3003,7 @@ mod tests {
 
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Test properties of the updated entries.
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2720,14 +3054,15 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_refresh_ipa_example_2(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
-        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
         let sse = ScimSyncUpdateEvent { ident };
 
         let changes =
@@ -2850,12 +3185,16 @@ mod tests {
         assert!(testuser_mo.contains(&testgroup.get_uuid()));
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
+        check_sync_apply_audit(idms_audit, sync_uuid);
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_yield_authority(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2910,10 +3249,162 @@ mod tests {
         );
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
+        check_sync_apply_audit(idms_audit, sync_uuid);
     }
 
-    #[idm_test]
-    async fn test_idm_scim_sync_finalise_1(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
+    #[idm_test(audit)]
+    async fn test_idm_scim_sync_writeback(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let sse = ScimSyncUpdateEvent { ident };
+
+        let changes =
+            serde_json::from_str(TEST_SYNC_SCIM_IPA_1).expect("failed to parse scim sync");
+
+        assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
+
+        // With no authority yielded, there is nothing to write back.
+        let mut idms_prox_read = idms.proxy_read().await;
+        let writeback = idms_prox_read
+            .scim_sync_get_writeback(&sse.ident)
+            .expect("Failed to get writeback");
+        assert!(writeback.from_cookie.is_none());
+        assert!(writeback.entries.is_empty());
+        drop(idms_prox_read);
+
+        let ct = ct + Duration::from_secs(1);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                sync_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::SyncYieldAuthority,
+                    Value::new_iutf8(Attribute::LegalName.as_ref())
+                )
+            )
+            .is_ok());
+
+        let testuser_filter = filter!(f_eq(Attribute::Name, PartialValue::new_iname("testuser")));
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify(
+                &testuser_filter,
+                &ModifyList::new_purge_and_set(
+                    Attribute::LegalName,
+                    Value::Utf8("Test Userington the First".to_string())
+                )
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let writeback = idms_prox_read
+            .scim_sync_get_writeback(&sse.ident)
+            .expect("Failed to get writeback");
+        drop(idms_prox_read);
+
+        assert_eq!(writeback.entries.len(), 1);
+        let entry = &writeback.entries[0];
+        assert_eq!(
+            entry.external_id.as_deref(),
+            Some("uid=testuser,cn=users,cn=accounts,dc=dev,dc=blackhats,dc=net,dc=au")
+        );
+        assert_eq!(entry.attrs.len(), 1);
+        assert_eq!(
+            entry
+                .attrs
+                .get(Attribute::LegalName.as_ref())
+                .map(|attr| attr.values.clone()),
+            Some(vec!["Test Userington the First".to_string()])
+        );
+
+        // An ack that doesn't follow the current state is rejected.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let bad_ack = ScimSyncWritebackAck {
+            from_cookie: Some(writeback.to_cookie.clone()),
+            to_cookie: writeback.to_cookie.clone(),
+        };
+        assert_eq!(
+            idms_prox_write.scim_sync_writeback_ack(&sse, &bad_ack),
+            Err(OperationError::InvalidSyncState)
+        );
+
+        let ack = ScimSyncWritebackAck {
+            from_cookie: writeback.from_cookie.clone(),
+            to_cookie: writeback.to_cookie.clone(),
+        };
+        assert!(idms_prox_write.scim_sync_writeback_ack(&sse, &ack).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Once acknowledged, the change is not sent again.
+        let mut idms_prox_read = idms.proxy_read().await;
+        let writeback = idms_prox_read
+            .scim_sync_get_writeback(&sse.ident)
+            .expect("Failed to get writeback");
+        assert_eq!(writeback.from_cookie, Some(ack.to_cookie));
+        assert!(writeback.entries.is_empty());
+    }
+
+    #[test]
+    fn test_scim_sync_writeback_cookie() {
+        let s_uuid_a = Uuid::new_v4();
+        let s_uuid_b = Uuid::new_v4();
+
+        let cookie: ScimSyncWritebackCookie = BTreeMap::from([
+            (s_uuid_a, Duration::from_secs(10)),
+            (s_uuid_b, Duration::from_secs(5)),
+        ]);
+        let encoded = scim_sync_writeback_cookie_encode(&cookie);
+        assert_eq!(
+            scim_sync_writeback_cookie_decode(&encoded.0),
+            Ok(cookie.clone())
+        );
+        assert_eq!(
+            scim_sync_writeback_cookie_decode(&encoded.0[1..]),
+            Err(OperationError::InvalidSyncState)
+        );
+        assert_eq!(scim_sync_writeback_cookie_decode(&[]), Ok(BTreeMap::new()));
+
+        // Changes are compared to the newest change of the server that made them, so a
+        // change from b that is older than a's newest, but arrives later, is still sent.
+        assert!(!scim_sync_writeback_is_new(
+            &cookie,
+            &Cid::new(s_uuid_a, Duration::from_secs(10))
+        ));
+        assert!(scim_sync_writeback_is_new(
+            &cookie,
+            &Cid::new(s_uuid_a, Duration::from_secs(11))
+        ));
+        assert!(!scim_sync_writeback_is_new(
+            &cookie,
+            &Cid::new(s_uuid_b, Duration::from_secs(4))
+        ));
+        assert!(scim_sync_writeback_is_new(
+            &cookie,
+            &Cid::new(s_uuid_b, Duration::from_secs(6))
+        ));
+        // A server that was unknown when the cookie was made.
+        assert!(scim_sync_writeback_is_new(
+            &cookie,
+            &Cid::new(Uuid::new_v4(), Duration::from_secs(1))
+        ));
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_scim_sync_finalise_1(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
         let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
@@ -2925,6 +3416,7 @@ mod tests {
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Finalise the sync account.
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -2960,8 +3452,12 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
-    #[idm_test]
-    async fn test_idm_scim_sync_finalise_2(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
+    #[idm_test(audit)]
+    async fn test_idm_scim_sync_finalise_2(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
         let (sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
@@ -2980,6 +3476,8 @@ mod tests {
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Finalise the sync account.
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -3021,10 +3519,11 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_terminate_1(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -3037,6 +3536,7 @@ mod tests {
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Terminate the sync account
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -3072,10 +3572,11 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_scim_sync_terminate_2(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -3095,6 +3596,8 @@ mod tests {
         assert!(idms_prox_write.scim_sync_apply(&sse, &changes, ct).is_ok());
 
         assert!(idms_prox_write.commit().is_ok());
+        check_sync_apply_audit(idms_audit, sync_uuid);
+        check_sync_apply_audit(idms_audit, sync_uuid);
 
         // Terminate the sync account
         let mut idms_prox_write = idms.proxy_write(ct).await;
@@ -0,0 +1,1492 @@
+//! A SCIM 2.0 service provider for users and groups, as described by RFC 7643 and RFC 7644.
+//!
+//! SCIM resources are a projection of kanidm entries. Every read and write is performed as
+//! the requesting identity, so SCIM clients are subject to the same access controls as any
+//! other client. The version of a resource is derived from the change id of the last
+//! modification to its entry, and is used to provide ETags and conditional writes.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use kanidm_proto::scim_v2::*;
+use serde_json::Value as JsonValue;
+
+use crate::idm::account::Account;
+use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScimResourceType {
+    User,
+    Group,
+}
+
+/// How an attribute of a SCIM resource maps to a kanidm attribute.
+#[derive(Debug)]
+struct ScimAttribute {
+    name: &'static str,
+    /// For complex attributes, the sub attribute that holds the value, such as `emails.value`.
+    sub_attr: Option<&'static str>,
+    /// The attribute the value is stored in. `active` is derived from the account validity
+    /// so has no single attribute.
+    attr: Option<Attribute>,
+    multi_valued: bool,
+    mutable: bool,
+    required: bool,
+    case_exact: bool,
+    unique: bool,
+}
+
+const fn scim_attr(name: &'static str, attr: Attribute) -> ScimAttribute {
+    ScimAttribute {
+        name,
+        sub_attr: None,
+        attr: Some(attr),
+        multi_valued: false,
+        mutable: true,
+        required: false,
+        case_exact: false,
+        unique: false,
+    }
+}
+
+const SCIM_ATTR_ID: ScimAttribute = ScimAttribute {
+    mutable: false,
+    case_exact: true,
+    unique: true,
+    ..scim_attr("id", Attribute::Uuid)
+};
+
+const SCIM_ATTR_EXTERNAL_ID: ScimAttribute = ScimAttribute {
+    mutable: false,
+    ..scim_attr("externalId", Attribute::SyncExternalId)
+};
+
+const SCIM_USER_ATTRIBUTES: &[ScimAttribute] = &[
+    SCIM_ATTR_ID,
+    SCIM_ATTR_EXTERNAL_ID,
+    ScimAttribute {
+        required: true,
+        unique: true,
+        ..scim_attr("userName", Attribute::Name)
+    },
+    ScimAttribute {
+        case_exact: true,
+        ..scim_attr("displayName", Attribute::DisplayName)
+    },
+    ScimAttribute {
+        sub_attr: Some("formatted"),
+        case_exact: true,
+        ..scim_attr("name", Attribute::LegalName)
+    },
+    ScimAttribute {
+        sub_attr: Some("value"),
+        multi_valued: true,
+        unique: true,
+        ..scim_attr("emails", Attribute::Mail)
+    },
+    ScimAttribute {
+        sub_attr: Some("value"),
+        multi_valued: true,
+        mutable: false,
+        ..scim_attr("groups", Attribute::MemberOf)
+    },
+    ScimAttribute {
+        name: "active",
+        sub_attr: None,
+        attr: None,
+        multi_valued: false,
+        mutable: true,
+        required: false,
+        case_exact: false,
+        unique: false,
+    },
+];
+
+const SCIM_GROUP_ATTRIBUTES: &[ScimAttribute] = &[
+    SCIM_ATTR_ID,
+    SCIM_ATTR_EXTERNAL_ID,
+    ScimAttribute {
+        required: true,
+        unique: true,
+        ..scim_attr("displayName", Attribute::Name)
+    },
+    ScimAttribute {
+        sub_attr: Some("value"),
+        multi_valued: true,
+        ..scim_attr("members", Attribute::Member)
+    },
+];
+
+impl ScimResourceType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScimResourceType::User => "User",
+            ScimResourceType::Group => "Group",
+        }
+    }
+
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            ScimResourceType::User => "Users",
+            ScimResourceType::Group => "Groups",
+        }
+    }
+
+    pub fn schema(&self) -> &'static str {
+        match self {
+            ScimResourceType::User => SCIM_SCHEMA_USER,
+            ScimResourceType::Group => SCIM_SCHEMA_GROUP,
+        }
+    }
+
+    fn classes(&self) -> &'static [EntryClass] {
+        match self {
+            ScimResourceType::User => {
+                &[EntryClass::Object, EntryClass::Account, EntryClass::Person]
+            }
+            ScimResourceType::Group => &[EntryClass::Object, EntryClass::Group],
+        }
+    }
+
+    fn attributes(&self) -> &'static [ScimAttribute] {
+        match self {
+            ScimResourceType::User => SCIM_USER_ATTRIBUTES,
+            ScimResourceType::Group => SCIM_GROUP_ATTRIBUTES,
+        }
+    }
+
+    fn filter(&self) -> Filter<FilterInvalid> {
+        match self {
+            ScimResourceType::User => {
+                filter!(f_eq(Attribute::Class, EntryClass::Person.into()))
+            }
+            ScimResourceType::Group => {
+                filter!(f_eq(Attribute::Class, EntryClass::Group.into()))
+            }
+        }
+    }
+
+    fn filter_uuid(&self, uuid: Uuid) -> Filter<FilterInvalid> {
+        Filter::join_parts_and(
+            self.filter(),
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(uuid))),
+        )
+    }
+
+    fn location(&self, origin: &Url, uuid: Uuid) -> String {
+        let mut location = origin.clone();
+        location.set_path(&format!("/scim/v2/{}/{}", self.endpoint(), uuid));
+        location.to_string()
+    }
+
+    fn resolve_attr(&self, path: &ScimAttrPath) -> Result<&'static ScimAttribute, OperationError> {
+        let urn_valid = path
+            .urn
+            .as_deref()
+            .map(|urn| urn.eq_ignore_ascii_case(self.schema()))
+            .unwrap_or(true);
+
+        self.attributes()
+            .iter()
+            .filter(|_| urn_valid)
+            .find(|sattr| {
+                sattr.name.eq_ignore_ascii_case(&path.attr)
+                    && match (sattr.sub_attr, path.sub_attr.as_deref()) {
+                        (_, None) => true,
+                        (Some(sub_attr), Some(path_sub_attr)) => {
+                            sub_attr.eq_ignore_ascii_case(path_sub_attr)
+                        }
+                        (None, Some(_)) => false,
+                    }
+            })
+            .ok_or_else(|| {
+                request_error!(%path, "Unsupported SCIM attribute");
+                OperationError::InvalidAttributeName(path.to_string())
+            })
+    }
+
+    fn resolve_filter_attr(&self, path: &ScimAttrPath) -> Result<Attribute, OperationError> {
+        self.resolve_attr(path)?.attr.ok_or_else(|| {
+            request_error!(%path, "Unable to filter on SCIM attribute");
+            OperationError::InvalidAttributeName(path.to_string())
+        })
+    }
+
+    fn meta(&self, origin: &Url, uuid: Uuid, version: &Cid) -> ScimMeta {
+        ScimMeta {
+            resource_type: self.name().to_string(),
+            location: Some(self.location(origin, uuid)),
+            version: Some(scim_version(version)),
+        }
+    }
+
+    fn resource_from_entry(
+        &self,
+        entry: &Entry<EntryReduced, EntryCommitted>,
+        version: &Cid,
+        origin: &Url,
+        ct: Duration,
+    ) -> ScimResource {
+        let uuid = entry.get_uuid();
+        let meta = Some(self.meta(origin, uuid, version));
+
+        match self {
+            ScimResourceType::User => {
+                let primary = entry.get_ava_mail_primary(Attribute::Mail);
+                let emails = entry
+                    .get_ava_iter_mail(Attribute::Mail)
+                    .map(|iter| {
+                        iter.map(|mail| ScimMultiValue {
+                            value: mail.to_string(),
+                            primary: Some(Some(mail) == primary),
+                            ..Default::default()
+                        })
+                        .collect()
+                    })
+                    .unwrap_or_default();
+
+                let groups = entry
+                    .get_ava_refer(Attribute::MemberOf)
+                    .map(|groups| {
+                        groups
+                            .iter()
+                            .map(|group| ScimMultiValue {
+                                value: group.to_string(),
+                                ref_: Some(ScimResourceType::Group.location(origin, *group)),
+                                ..Default::default()
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let valid_from = entry.get_ava_single_datetime(Attribute::AccountValidFrom);
+                let expire = entry.get_ava_single_datetime(Attribute::AccountExpire);
+
+                ScimResource::User(ScimUser {
+                    schemas: vec![SCIM_SCHEMA_USER.to_string()],
+                    id: Some(uuid),
+                    external_id: entry.get_ava_single_proto_string(Attribute::SyncExternalId),
+                    user_name: entry.get_ava_single_proto_string(Attribute::Name),
+                    display_name: entry.get_ava_single_proto_string(Attribute::DisplayName),
+                    name: entry.get_ava_single_proto_string(Attribute::LegalName).map(
+                        |formatted| ScimName {
+                            formatted: Some(formatted),
+                        },
+                    ),
+                    emails,
+                    groups,
+                    active: Some(Account::check_within_valid_time(
+                        ct,
+                        valid_from.as_ref(),
+                        expire.as_ref(),
+                    )),
+                    meta,
+                })
+            }
+            ScimResourceType::Group => {
+                let members = entry
+                    .get_ava_refer(Attribute::Member)
+                    .map(|members| {
+                        members
+                            .iter()
+                            .map(|member| ScimMultiValue {
+                                value: member.to_string(),
+                                ..Default::default()
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                ScimResource::Group(ScimGroup {
+                    schemas: vec![SCIM_SCHEMA_GROUP.to_string()],
+                    id: Some(uuid),
+                    external_id: entry.get_ava_single_proto_string(Attribute::SyncExternalId),
+                    display_name: entry.get_ava_single_proto_string(Attribute::Name),
+                    members,
+                    meta,
+                })
+            }
+        }
+    }
+
+    /// The values of the mutable attributes of a resource that is being created or replaced.
+    /// The display name of a user defaults to the user name, as kanidm requires it.
+    fn resource_values(
+        &self,
+        resource: &ScimResource,
+    ) -> Result<Vec<(Attribute, Vec<ScimValue>)>, OperationError> {
+        match (self, resource) {
+            (ScimResourceType::User, ScimResource::User(user)) => Ok(vec![
+                (Attribute::Name, ScimValue::from_iter(&user.user_name)),
+                (
+                    Attribute::DisplayName,
+                    ScimValue::from_iter(user.display_name.as_ref().or(user.user_name.as_ref())),
+                ),
+                (
+                    Attribute::LegalName,
+                    ScimValue::from_iter(user.name.as_ref().and_then(|n| n.formatted.as_ref())),
+                ),
+                (
+                    Attribute::Mail,
+                    user.emails.iter().map(ScimValue::from).collect(),
+                ),
+            ]),
+            (ScimResourceType::Group, ScimResource::Group(group)) => Ok(vec![
+                (Attribute::Name, ScimValue::from_iter(&group.display_name)),
+                (
+                    Attribute::Member,
+                    group.members.iter().map(ScimValue::from).collect(),
+                ),
+            ]),
+            _ => {
+                request_error!(resource_type = %self.name(), "SCIM resource does not match the endpoint");
+                Err(OperationError::InvalidRequestState)
+            }
+        }
+    }
+
+    fn resource_active(&self, resource: &ScimResource) -> Option<bool> {
+        match resource {
+            ScimResource::User(user) => user.active,
+            ScimResource::Group(_) => None,
+        }
+    }
+
+    fn schema_definition(&self, origin: &Url) -> ScimSchemaDefinition {
+        let attributes = self
+            .attributes()
+            .iter()
+            .filter(|sattr| sattr.name != "id")
+            .map(|sattr| {
+                let attr_type = if sattr.attr.is_none() {
+                    "boolean"
+                } else {
+                    "string"
+                };
+                let uniqueness = if sattr.unique { "server" } else { "none" };
+                let mutability = if sattr.mutable {
+                    "readWrite"
+                } else {
+                    "readOnly"
+                };
+
+                let sub_attributes = sattr
+                    .sub_attr
+                    .map(|sub_attr| ScimSchemaAttribute {
+                        name: sub_attr.to_string(),
+                        type_: attr_type.to_string(),
+                        multi_valued: false,
+                        description: None,
+                        required: false,
+                        case_exact: sattr.case_exact,
+                        mutability: mutability.to_string(),
+                        returned: "default".to_string(),
+                        uniqueness: uniqueness.to_string(),
+                        sub_attributes: Vec::new(),
+                    })
+                    .into_iter()
+                    .collect::<Vec<_>>();
+
+                ScimSchemaAttribute {
+                    name: sattr.name.to_string(),
+                    type_: if sub_attributes.is_empty() {
+                        attr_type.to_string()
+                    } else {
+                        "complex".to_string()
+                    },
+                    multi_valued: sattr.multi_valued,
+                    description: sattr.attr.map(|attr| format!("kanidm attribute {}", attr)),
+                    required: sattr.required,
+                    case_exact: sattr.case_exact,
+                    mutability: mutability.to_string(),
+                    returned: "default".to_string(),
+                    uniqueness: uniqueness.to_string(),
+                    sub_attributes,
+                }
+            })
+            .collect();
+
+        let mut location = origin.clone();
+        location.set_path(&format!("/scim/v2/Schemas/{}", self.schema()));
+
+        ScimSchemaDefinition {
+            schemas: vec![SCIM_SCHEMA_SCHEMA.to_string()],
+            id: self.schema().to_string(),
+            name: self.name().to_string(),
+            description: None,
+            attributes,
+            meta: Some(ScimMeta {
+                resource_type: "Schema".to_string(),
+                location: Some(location.to_string()),
+                version: None,
+            }),
+        }
+    }
+
+    fn resource_type_definition(&self, origin: &Url) -> ScimResourceTypeDefinition {
+        let mut location = origin.clone();
+        location.set_path(&format!("/scim/v2/ResourceTypes/{}", self.name()));
+
+        ScimResourceTypeDefinition {
+            schemas: vec![SCIM_SCHEMA_RESOURCE_TYPE.to_string()],
+            id: self.name().to_string(),
+            name: self.name().to_string(),
+            endpoint: format!("/{}", self.endpoint()),
+            description: None,
+            schema: self.schema().to_string(),
+            meta: Some(ScimMeta {
+                resource_type: "ResourceType".to_string(),
+                location: Some(location.to_string()),
+                version: None,
+            }),
+        }
+    }
+}
+
+/// A single value supplied by a SCIM client, before it is converted to the syntax of the
+/// attribute it is stored in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScimValue {
+    value: String,
+    primary: bool,
+}
+
+impl ScimValue {
+    fn from_iter<'b>(values: impl IntoIterator<Item = &'b String>) -> Vec<Self> {
+        values
+            .into_iter()
+            .map(|value| ScimValue {
+                value: value.clone(),
+                primary: false,
+            })
+            .collect()
+    }
+
+    fn from_json(sattr: &ScimAttribute, value: &JsonValue) -> Result<Vec<Self>, OperationError> {
+        match value {
+            JsonValue::Null => Ok(Vec::new()),
+            JsonValue::Array(values) => values.iter().try_fold(Vec::new(), |mut acc, value| {
+                acc.extend(Self::from_json(sattr, value)?);
+                Ok(acc)
+            }),
+            JsonValue::String(value) => Ok(vec![ScimValue {
+                value: value.clone(),
+                primary: false,
+            }]),
+            JsonValue::Number(value) => Ok(vec![ScimValue {
+                value: value.to_string(),
+                primary: false,
+            }]),
+            JsonValue::Bool(value) => Ok(vec![ScimValue {
+                value: value.to_string(),
+                primary: false,
+            }]),
+            JsonValue::Object(object) => {
+                // A value of a complex attribute, such as {"value": "...", "primary": true}.
+                let Some(sub_attr) = sattr.sub_attr else {
+                    request_error!(attr = %sattr.name, "SCIM attribute is not complex");
+                    return Err(OperationError::InvalidAttribute(sattr.name.to_string()));
+                };
+                let get = |key: &str| {
+                    object
+                        .iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                        .map(|(_, v)| v)
+                };
+                let primary = get("primary").and_then(JsonValue::as_bool).unwrap_or(false);
+                match get(sub_attr) {
+                    Some(JsonValue::String(value)) => Ok(vec![ScimValue {
+                        value: value.clone(),
+                        primary,
+                    }]),
+                    Some(JsonValue::Null) | None => Ok(Vec::new()),
+                    Some(_) => {
+                        request_error!(attr = %sattr.name, "SCIM sub attribute is not a string");
+                        Err(OperationError::InvalidAttribute(sattr.name.to_string()))
+                    }
+                }
+            }
+        }
+    }
+
+    fn to_value(
+        &self,
+        attr: Attribute,
+        qs: &mut QueryServerWriteTransaction,
+    ) -> Result<Value, OperationError> {
+        if attr == Attribute::Mail && self.primary {
+            Value::new_email_address_primary_s(&self.value).ok_or_else(|| {
+                OperationError::InvalidAttribute("Invalid Email Address syntax".to_string())
+            })
+        } else {
+            qs.clone_value(attr.as_ref(), &self.value)
+        }
+    }
+}
+
+impl From<&ScimMultiValue> for ScimValue {
+    fn from(value: &ScimMultiValue) -> Self {
+        ScimValue {
+            value: value.value.clone(),
+            primary: value.primary.unwrap_or(false),
+        }
+    }
+}
+
+/// Render a change id as a weak entity tag.
+fn scim_version(cid: &Cid) -> String {
+    format!("W/\"{}\"", cid)
+}
+
+/// Check a version against the value of an If-Match header, which may list many tags.
+fn scim_version_matches(if_match: &str, version: &str) -> bool {
+    // Versions are weak, so are compared without the weak indicator.
+    let strip = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || strip(tag) == strip(version))
+}
+
+/// Modifications that set whether a user is active. Accounts are deactivated by expiring
+/// them, and activated by removing their validity window.
+fn scim_active_mods(active: bool, ct: Duration) -> Vec<Modify> {
+    if active {
+        vec![
+            m_purge(Attribute::AccountExpire),
+            m_purge(Attribute::AccountValidFrom),
+        ]
+    } else {
+        // The expiry is inclusive, so it is set in the past to take effect immediately.
+        let expire = Value::new_datetime_epoch(ct.saturating_sub(Duration::from_secs(1)));
+        vec![
+            m_purge(Attribute::AccountExpire),
+            m_pres(Attribute::AccountExpire, &expire),
+        ]
+    }
+}
+
+fn scim_active_from_json(value: &JsonValue) -> Result<bool, OperationError> {
+    match value {
+        JsonValue::Bool(active) => Ok(*active),
+        // Some clients send booleans as strings, such as "False".
+        JsonValue::String(active) => bool::from_str(&active.to_lowercase())
+            .map_err(|_| OperationError::InvalidAttribute("active".to_string())),
+        _ => Err(OperationError::InvalidAttribute("active".to_string())),
+    }
+}
+
+/// Search as the identity, returning the matching entries before their attributes are
+/// reduced, ordered by uuid so that pages are consistent between requests.
+fn scim_search<'a, T: QueryServerTransaction<'a>>(
+    qs: &mut T,
+    ident: &Identity,
+    filter: Filter<FilterInvalid>,
+) -> Result<(SearchEvent, Vec<Arc<EntrySealedCommitted>>), OperationError> {
+    let f_valid = filter.validate(qs.get_schema()).map_err(|e| {
+        request_error!(?e, "SCIM filter schema violation");
+        OperationError::SchemaViolation(e)
+    })?;
+    let se = SearchEvent::new_impersonate(ident, f_valid.clone().into_ignore_hidden(), f_valid);
+
+    let mut entries = qs.search(&se)?;
+    entries.sort_unstable_by_key(|e| e.get_uuid());
+    Ok((se, entries))
+}
+
+/// Reduce the entries of a search to the attributes the identity can read, with the change
+/// id of the last modification to each entry.
+fn scim_reduce_entries<'a, T: QueryServerTransaction<'a>>(
+    qs: &mut T,
+    se: &SearchEvent,
+    entries: Vec<Arc<EntrySealedCommitted>>,
+) -> Result<Vec<(Entry<EntryReduced, EntryCommitted>, Cid)>, OperationError> {
+    // The versions are taken before the attributes are reduced, as the reduced entry has no
+    // change state.
+    let mut versions: BTreeMap<Uuid, Cid> = entries
+        .iter()
+        .map(|e| (e.get_uuid(), e.get_last_changed()))
+        .collect();
+
+    let entries = qs
+        .get_accesscontrols()
+        .search_filter_entry_attributes(se, entries)?;
+
+    entries
+        .into_iter()
+        .map(|e| {
+            versions
+                .remove(&e.get_uuid())
+                .map(|version| (e, version))
+                .ok_or(OperationError::InvalidEntryState)
+        })
+        .collect()
+}
+
+fn scim_get_resource<'a, T: QueryServerTransaction<'a>>(
+    qs: &mut T,
+    ident: &Identity,
+    rtype: ScimResourceType,
+    uuid: Uuid,
+    origin: &Url,
+    ct: Duration,
+) -> Result<ScimResource, OperationError> {
+    let (se, entries) = scim_search(qs, ident, rtype.filter_uuid(uuid))?;
+    scim_reduce_entries(qs, &se, entries)?
+        .first()
+        .map(|(entry, version)| rtype.resource_from_entry(entry, version, origin, ct))
+        .ok_or(OperationError::NoMatchingEntries)
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    pub fn scim_v2_search(
+        &mut self,
+        ident: &Identity,
+        rtype: ScimResourceType,
+        req: &ScimListRequest,
+        ct: Duration,
+    ) -> Result<ScimListResponse<ScimResource>, OperationError> {
+        let filter = match req.filter.as_deref() {
+            Some(f) => {
+                let f = ScimFilter::from_str(f).map_err(|e| {
+                    request_error!(%e, "Invalid SCIM filter");
+                    OperationError::FilterGeneration
+                })?;
+                let resolve_attr = |path: &ScimAttrPath| rtype.resolve_filter_attr(path);
+                let f = Filter::from_scim_ro(ident, &f, &resolve_attr, &mut self.qs_read)?;
+                Filter::join_parts_and(rtype.filter(), f)
+            }
+            None => rtype.filter(),
+        };
+
+        let (se, entries) = scim_search(&mut self.qs_read, ident, filter)?;
+
+        let total_results = entries.len();
+        // The start index is 1 based, and values less than 1 are interpreted as 1.
+        let start_index = req.start_index.unwrap_or(1).max(1);
+        let origin = self.get_origin().clone();
+
+        // Only the entries of the requested page are reduced and converted to resources.
+        let page: Vec<_> = entries
+            .into_iter()
+            .skip(start_index - 1)
+            .take(req.count.unwrap_or(usize::MAX))
+            .collect();
+
+        let resources: Vec<_> = scim_reduce_entries(&mut self.qs_read, &se, page)?
+            .iter()
+            .map(|(e, version)| rtype.resource_from_entry(e, version, &origin, ct))
+            .collect();
+
+        Ok(ScimListResponse {
+            schemas: vec![SCIM_SCHEMA_LIST_RESPONSE.to_string()],
+            total_results,
+            start_index,
+            items_per_page: resources.len(),
+            resources,
+        })
+    }
+
+    pub fn scim_v2_get(
+        &mut self,
+        ident: &Identity,
+        rtype: ScimResourceType,
+        uuid: Uuid,
+        ct: Duration,
+    ) -> Result<ScimResource, OperationError> {
+        let origin = self.get_origin().clone();
+        scim_get_resource(&mut self.qs_read, ident, rtype, uuid, &origin, ct)
+    }
+
+    pub fn scim_v2_service_provider_config(&self) -> ScimServiceProviderConfig {
+        let mut location = self.get_origin().clone();
+        location.set_path("/scim/v2/ServiceProviderConfig");
+
+        ScimServiceProviderConfig {
+            schemas: vec![SCIM_SCHEMA_SERVICE_PROVIDER_CONFIG.to_string()],
+            documentation_uri: Some(
+                "https://kanidm.github.io/kanidm/stable/integrations/scim.html".to_string(),
+            ),
+            patch: ScimSupported { supported: true },
+            bulk: ScimBulkSupported {
+                supported: false,
+                max_operations: 0,
+                max_payload_size: 0,
+            },
+            filter: ScimFilterSupported {
+                supported: true,
+                max_results: Limits::default().search_max_results,
+            },
+            change_password: ScimSupported { supported: false },
+            sort: ScimSupported { supported: false },
+            etag: ScimSupported { supported: true },
+            authentication_schemes: vec![ScimAuthenticationScheme {
+                type_: "oauthbearertoken".to_string(),
+                name: "Bearer Token".to_string(),
+                description: "Authentication with a kanidm session or api token".to_string(),
+                spec_uri: Some("https://www.rfc-editor.org/rfc/rfc6750".to_string()),
+                documentation_uri: None,
+                primary: Some(true),
+            }],
+            meta: Some(ScimMeta {
+                resource_type: "ServiceProviderConfig".to_string(),
+                location: Some(location.to_string()),
+                version: None,
+            }),
+        }
+    }
+
+    pub fn scim_v2_resource_types(&self) -> ScimListResponse<ScimResourceTypeDefinition> {
+        let origin = self.get_origin();
+        let resources: Vec<_> = [ScimResourceType::User, ScimResourceType::Group]
+            .iter()
+            .map(|rtype| rtype.resource_type_definition(origin))
+            .collect();
+
+        ScimListResponse {
+            schemas: vec![SCIM_SCHEMA_LIST_RESPONSE.to_string()],
+            total_results: resources.len(),
+            start_index: 1,
+            items_per_page: resources.len(),
+            resources,
+        }
+    }
+
+    pub fn scim_v2_schemas(&self) -> ScimListResponse<ScimSchemaDefinition> {
+        let origin = self.get_origin();
+        let resources: Vec<_> = [ScimResourceType::User, ScimResourceType::Group]
+            .iter()
+            .map(|rtype| rtype.schema_definition(origin))
+            .collect();
+
+        ScimListResponse {
+            schemas: vec![SCIM_SCHEMA_LIST_RESPONSE.to_string()],
+            total_results: resources.len(),
+            start_index: 1,
+            items_per_page: resources.len(),
+            resources,
+        }
+    }
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    /// Check that the resource exists and is visible to the identity, and that it has not
+    /// changed since the version in the If-Match header.
+    fn scim_v2_assert_version(
+        &mut self,
+        ident: &Identity,
+        rtype: ScimResourceType,
+        uuid: Uuid,
+        if_match: Option<&str>,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        let origin = self.get_origin().clone();
+        let current = scim_get_resource(&mut self.qs_write, ident, rtype, uuid, &origin, ct)?;
+
+        let version = current.meta().and_then(|meta| meta.version.as_deref());
+        match (if_match, version) {
+            (Some(if_match), Some(version)) if !scim_version_matches(if_match, version) => {
+                request_error!(%if_match, %version, "SCIM resource version does not match");
+                Err(OperationError::ModifyAssertionFailed)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn scim_v2_modify(
+        &mut self,
+        ident: &Identity,
+        rtype: ScimResourceType,
+        uuid: Uuid,
+        mods: Vec<Modify>,
+    ) -> Result<(), OperationError> {
+        if mods.is_empty() {
+            return Ok(());
+        }
+        let filter = rtype.filter_uuid(uuid);
+        let modlist = ModifyList::new_list(mods);
+        self.qs_write
+            .impersonate_modify(&filter, &filter, &modlist, ident)
+    }
+
+    fn scim_v2_values(
+        &mut self,
+        attr: Attribute,
+        values: &[ScimValue],
+    ) -> Result<Vec<Value>, OperationError> {
+        values
+            .iter()
+            .map(|value| value.to_value(attr, &mut self.qs_write))
+            .collect()
+    }
+
+    pub fn scim_v2_create(
+        &mut self,
+        ident: &Identity,
+        rtype: ScimResourceType,
+        resource: &ScimResource,
+        ct: Duration,
+    ) -> Result<ScimResource, OperationError> {
+        let uuid = Uuid::new_v4();
+
+        let mut entry = Entry::new();
+        entry.set_ava(
+            Attribute::Class,
+            rtype.classes().iter().map(|class| class.to_value()),
+        );
+        entry.add_ava(Attribute::Uuid, Value::Uuid(uuid));
+
+        for (attr, values) in rtype.resource_values(resource)? {
+            let values = self.scim_v2_values(attr, &values)?;
+            if !values.is_empty() {
+                entry.set_ava(attr, values);
+            }
+        }
+
+        if rtype.resource_active(resource) == Some(false) {
+            let expire = Value::new_datetime_epoch(ct.saturating_sub(Duration::from_secs(1)));
+            entry.add_ava(Attribute::AccountExpire, expire);
+        }
+
+        let ce = CreateEvent {
+            ident: ident.clone(),
+            entries: vec![entry],
+        };
+        self.qs_write.create(&ce)?;
+
+        let origin = self.get_origin().clone();
+        scim_get_resource(&mut self.qs_write, ident, rtype, uuid, &origin, ct)
+    }
+
+    /// Replace the mutable attributes of a resource. Attributes that are absent from the
+    /// resource are removed, except for `active` which is unchanged if absent.
+    pub fn scim_v2_replace(
+        &mut self,
+        ident: &Identity,
+        rtype: ScimResourceType,
+        uuid: Uuid,
+        resource: &ScimResource,
+        if_match: Option<&str>,
+        ct: Duration,
+    ) -> Result<ScimResource, OperationError> {
+        self.scim_v2_assert_version(ident, rtype, uuid, if_match, ct)?;
+
+        let mut mods = Vec::new();
+        for (attr, values) in rtype.resource_values(resource)? {
+            mods.push(m_purge(attr));
+            for value in self.scim_v2_values(attr, &values)? {
+                mods.push(Modify::Present(attr.into(), value));
+            }
+        }
+
+        if let Some(active) = rtype.resource_active(resource) {
+            mods.extend(scim_active_mods(active, ct));
+        }
+
+        self.scim_v2_modify(ident, rtype, uuid, mods)?;
+
+        let origin = self.get_origin().clone();
+        scim_get_resource(&mut self.qs_write, ident, rtype, uuid, &origin, ct)
+    }
+
+    pub fn scim_v2_patch(
+        &mut self,
+        ident: &Identity,
+        rtype: ScimResourceType,
+        uuid: Uuid,
+        patch: &ScimPatchRequest,
+        if_match: Option<&str>,
+        ct: Duration,
+    ) -> Result<ScimResource, OperationError> {
+        self.scim_v2_assert_version(ident, rtype, uuid, if_match, ct)?;
+
+        let mut mods = Vec::new();
+        for operation in patch.operations.iter() {
+            mods.extend(self.scim_v2_patch_mods(rtype, operation, ct)?);
+        }
+
+        self.scim_v2_modify(ident, rtype, uuid, mods)?;
+
+        let origin = self.get_origin().clone();
+        scim_get_resource(&mut self.qs_write, ident, rtype, uuid, &origin, ct)
+    }
+
+    /// Map a patch operation to the equivalent modifications.
+    fn scim_v2_patch_mods(
+        &mut self,
+        rtype: ScimResourceType,
+        operation: &ScimPatchOperation,
+        ct: Duration,
+    ) -> Result<Vec<Modify>, OperationError> {
+        let Some(path) = operation.path.as_deref() else {
+            // Without a path the value is an object of the attributes to add or replace.
+            let JsonValue::Object(attrs) = &operation.value else {
+                request_error!("SCIM patch operation without a path requires an object value");
+                return Err(OperationError::InvalidAttribute("value".to_string()));
+            };
+            if operation.op == ScimPatchOp::Remove {
+                request_error!("SCIM patch remove operation requires a path");
+                return Err(OperationError::InvalidAttributeName("path".to_string()));
+            }
+
+            let mut mods = Vec::new();
+            for (name, value) in attrs.iter() {
+                let path = ScimPatchPath {
+                    attr: ScimAttrPath::from_str(name)
+                        .map_err(|_| OperationError::InvalidAttributeName(name.clone()))?,
+                    value_filter: None,
+                };
+                mods.extend(self.scim_v2_patch_path_mods(rtype, operation.op, &path, value, ct)?);
+            }
+            return Ok(mods);
+        };
+
+        let path = ScimPatchPath::from_str(path).map_err(|e| {
+            request_error!(%e, %path, "Invalid SCIM patch path");
+            OperationError::InvalidAttributeName(path.to_string())
+        })?;
+        self.scim_v2_patch_path_mods(rtype, operation.op, &path, &operation.value, ct)
+    }
+
+    fn scim_v2_patch_path_mods(
+        &mut self,
+        rtype: ScimResourceType,
+        op: ScimPatchOp,
+        path: &ScimPatchPath,
+        value: &JsonValue,
+        ct: Duration,
+    ) -> Result<Vec<Modify>, OperationError> {
+        let sattr = rtype.resolve_attr(&path.attr)?;
+        if !sattr.mutable {
+            request_error!(attr = %sattr.name, "SCIM attribute is read only");
+            return Err(OperationError::SystemProtectedAttribute);
+        }
+
+        let Some(attr) = sattr.attr else {
+            // Removing active clears the validity of the account, which makes it active.
+            let active = match op {
+                ScimPatchOp::Remove => true,
+                ScimPatchOp::Add | ScimPatchOp::Replace => scim_active_from_json(value)?,
+            };
+            return Ok(scim_active_mods(active, ct));
+        };
+
+        match (op, &path.value_filter) {
+            // Only the removal of specific values is supported with a value filter, such as
+            // members[value eq "..."].
+            (ScimPatchOp::Remove, Some(f)) if path.attr.sub_attr.is_none() => {
+                let mut values = Vec::new();
+                scim_filter_values(sattr, f, &mut values)?;
+                values
+                    .iter()
+                    .map(|value| {
+                        self.qs_write
+                            .clone_partialvalue(attr.as_ref(), value)
+                            .map(|pv| Modify::Removed(attr.into(), pv))
+                    })
+                    .collect()
+            }
+            (_, Some(_)) => {
+                request_error!(attr = %path.attr, "Unsupported SCIM patch value filter");
+                Err(OperationError::InvalidAttributeName(path.attr.to_string()))
+            }
+            (ScimPatchOp::Remove, None) => {
+                let values = ScimValue::from_json(sattr, value)?;
+                if values.is_empty() {
+                    Ok(vec![m_purge(attr)])
+                } else {
+                    values
+                        .iter()
+                        .map(|value| {
+                            self.qs_write
+                                .clone_partialvalue(attr.as_ref(), &value.value)
+                                .map(|pv| Modify::Removed(attr.into(), pv))
+                        })
+                        .collect()
+                }
+            }
+            (ScimPatchOp::Add, None) if sattr.multi_valued => {
+                let values = ScimValue::from_json(sattr, value)?;
+                Ok(self
+                    .scim_v2_values(attr, &values)?
+                    .into_iter()
+                    .map(|value| Modify::Present(attr.into(), value))
+                    .collect())
+            }
+            // Adding to a single valued attribute replaces the value.
+            (ScimPatchOp::Add, None) | (ScimPatchOp::Replace, None) => {
+                let values = ScimValue::from_json(sattr, value)?;
+                Ok(std::iter::once(m_purge(attr))
+                    .chain(
+                        self.scim_v2_values(attr, &values)?
+                            .into_iter()
+                            .map(|value| Modify::Present(attr.into(), value)),
+                    )
+                    .collect())
+            }
+        }
+    }
+
+    pub fn scim_v2_delete(
+        &mut self,
+        ident: &Identity,
+        rtype: ScimResourceType,
+        uuid: Uuid,
+        if_match: Option<&str>,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        self.scim_v2_assert_version(ident, rtype, uuid, if_match, ct)?;
+
+        let de =
+            DeleteEvent::from_parts(ident.clone(), &rtype.filter_uuid(uuid), &mut self.qs_write)?;
+        self.qs_write.delete(&de)
+    }
+}
+
+/// Collect the values selected by a value filter of a patch path, which must be equality
+/// assertions of the value sub attribute, optionally joined by or.
+fn scim_filter_values(
+    sattr: &ScimAttribute,
+    f: &ScimFilter,
+    values: &mut Vec<String>,
+) -> Result<(), OperationError> {
+    match f {
+        ScimFilter::Compare(path, ScimCompareOp::Equal, value)
+            if path.urn.is_none()
+                && path.sub_attr.is_none()
+                && sattr
+                    .sub_attr
+                    .map(|sub_attr| sub_attr.eq_ignore_ascii_case(&path.attr))
+                    .unwrap_or(false) =>
+        {
+            values.push(value.clone());
+            Ok(())
+        }
+        ScimFilter::Or(l) => l
+            .iter()
+            .try_for_each(|f| scim_filter_values(sattr, f, values)),
+        _ => {
+            request_error!(attr = %sattr.name, "Unsupported SCIM patch value filter");
+            Err(OperationError::FilterGeneration)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idm::audit::{AuditEvent, AuditOperation};
+
+    #[test]
+    fn test_scim_v2_version_matches() {
+        let version = "W/\"00000000000000000000000000000001-00000000-0000-0000-0000-000000000000\"";
+        assert!(scim_version_matches(version, version));
+        assert!(scim_version_matches("*", version));
+        assert!(scim_version_matches(
+            "\"abc\", \"00000000000000000000000000000001-00000000-0000-0000-0000-000000000000\"",
+            version
+        ));
+        assert!(!scim_version_matches("W/\"abc\"", version));
+    }
+
+    #[test]
+    fn test_scim_v2_resolve_attr() {
+        let path = |s: &str| ScimAttrPath::from_str(s).expect("Invalid attribute path");
+
+        assert_eq!(
+            ScimResourceType::User.resolve_filter_attr(&path("USERNAME")),
+            Ok(Attribute::Name)
+        );
+        assert_eq!(
+            ScimResourceType::User.resolve_filter_attr(&path(
+                "urn:ietf:params:scim:schemas:core:2.0:User:emails.value"
+            )),
+            Ok(Attribute::Mail)
+        );
+        assert_eq!(
+            ScimResourceType::Group.resolve_filter_attr(&path("displayName")),
+            Ok(Attribute::Name)
+        );
+
+        // The urn must match the resource, and the sub attribute must exist.
+        assert!(ScimResourceType::Group
+            .resolve_filter_attr(&path("urn:ietf:params:scim:schemas:core:2.0:User:userName"))
+            .is_err());
+        assert!(ScimResourceType::User
+            .resolve_filter_attr(&path("emails.type"))
+            .is_err());
+        assert!(ScimResourceType::User
+            .resolve_filter_attr(&path("active"))
+            .is_err());
+    }
+
+    fn user_filter(
+        idms_prox_read: &mut IdmServerProxyReadTransaction,
+        filter: &str,
+    ) -> Result<Vec<String>, OperationError> {
+        let req = ScimListRequest {
+            filter: Some(filter.to_string()),
+            ..Default::default()
+        };
+        let ident = Identity::from_internal();
+        let res = idms_prox_read.scim_v2_search(
+            &ident,
+            ScimResourceType::User,
+            &req,
+            duration_from_epoch_now(),
+        )?;
+        Ok(res
+            .resources
+            .into_iter()
+            .filter_map(|r| match r {
+                ScimResource::User(u) => u.user_name,
+                ScimResource::Group(_) => None,
+            })
+            .collect())
+    }
+
+    #[idm_test]
+    async fn test_scim_v2_search(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("scim_alice")),
+            (Attribute::DisplayName, Value::new_utf8s("Alice Smith")),
+            (
+                Attribute::Mail,
+                Value::new_email_address_s("alice@example.com").expect("Invalid mail")
+            )
+        );
+        let e2 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("scim_bob")),
+            (Attribute::DisplayName, Value::new_utf8s("Bob Jones"))
+        );
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![e1, e2])
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+
+        assert_eq!(
+            user_filter(&mut idms_prox_read, r#"userName eq "scim_alice""#),
+            Ok(vec!["scim_alice".to_string()])
+        );
+        assert_eq!(
+            user_filter(
+                &mut idms_prox_read,
+                r#"userName sw "scim_" and not (emails pr)"#
+            ),
+            Ok(vec!["scim_bob".to_string()])
+        );
+        assert_eq!(
+            user_filter(
+                &mut idms_prox_read,
+                r#"emails[value eq "alice@example.com"] or displayName co "Jones""#
+            )
+            .map(|mut names| {
+                names.sort();
+                names
+            }),
+            Ok(vec!["scim_alice".to_string(), "scim_bob".to_string()])
+        );
+        assert_eq!(
+            user_filter(&mut idms_prox_read, r#"active eq true"#),
+            Err(OperationError::InvalidAttributeName("active".to_string()))
+        );
+        assert_eq!(
+            user_filter(&mut idms_prox_read, r#"userName eq"#),
+            Err(OperationError::FilterGeneration)
+        );
+
+        // Pagination
+        let req = ScimListRequest {
+            filter: Some(r#"userName sw "scim_""#.to_string()),
+            start_index: Some(2),
+            count: Some(5),
+        };
+        let res = idms_prox_read
+            .scim_v2_search(&Identity::from_internal(), ScimResourceType::User, &req, ct)
+            .expect("Failed to search");
+        assert_eq!(res.total_results, 2);
+        assert_eq!(res.start_index, 2);
+        assert_eq!(res.items_per_page, 1);
+    }
+
+    #[idm_test(audit)]
+    async fn test_scim_v2_unprivileged(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let alice_uuid = Uuid::new_v4();
+        let bob_uuid = Uuid::new_v4();
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("scim_alice")),
+            (Attribute::Uuid, Value::Uuid(alice_uuid)),
+            (Attribute::DisplayName, Value::new_utf8s("Alice Smith")),
+            (
+                Attribute::Mail,
+                Value::new_email_address_s("alice@example.com").expect("Invalid mail")
+            )
+        );
+        let e2 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("scim_bob")),
+            (Attribute::Uuid, Value::Uuid(bob_uuid)),
+            (Attribute::DisplayName, Value::new_utf8s("Bob Jones"))
+        );
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![e1, e2])
+            .is_ok());
+
+        let bob = idms_prox_write
+            .qs_write
+            .internal_search_uuid(bob_uuid)
+            .map(Identity::from_impersonate_entry_readwrite)
+            .expect("Failed to find bob");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let search = |idms_prox_read: &mut IdmServerProxyReadTransaction, filter: &str| {
+            let req = ScimListRequest {
+                filter: Some(filter.to_string()),
+                ..Default::default()
+            };
+            idms_prox_read
+                .scim_v2_search(&bob, ScimResourceType::User, &req, ct)
+                .expect("Failed to search")
+                .resources
+        };
+
+        // Bob can see alice, but not her mail.
+        let resources = search(&mut idms_prox_read, r#"userName eq "scim_alice""#);
+        assert!(matches!(
+            resources.as_slice(),
+            [ScimResource::User(ScimUser { user_name: Some(name), emails, .. })]
+                if name == "scim_alice" && emails.is_empty()
+        ));
+
+        // Nor can bob find alice by her mail.
+        assert!(search(&mut idms_prox_read, r#"emails pr"#).is_empty());
+        assert!(search(
+            &mut idms_prox_read,
+            r#"emails[value eq "alice@example.com"]"#
+        )
+        .is_empty());
+        drop(idms_prox_read);
+
+        // Bob can not create, change or delete resources.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let user = ScimResource::User(ScimUser {
+            user_name: Some("scim_mallory".to_string()),
+            display_name: Some("Mallory".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            idms_prox_write.scim_v2_create(&bob, ScimResourceType::User, &user, ct),
+            Err(OperationError::AccessDenied)
+        );
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessDenied {
+                identity: Some(identity),
+                operation: AuditOperation::Create,
+                ..
+            }) if identity == bob_uuid
+        ));
+
+        let patch: ScimPatchRequest = serde_json::from_str(
+            r#"{"Operations": [{"op": "replace", "path": "displayName", "value": "Mallory"}]}"#,
+        )
+        .expect("Invalid patch request");
+        assert_eq!(
+            idms_prox_write.scim_v2_patch(
+                &bob,
+                ScimResourceType::User,
+                alice_uuid,
+                &patch,
+                None,
+                ct
+            ),
+            Err(OperationError::AccessDenied)
+        );
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessDenied {
+                identity: Some(identity),
+                operation: AuditOperation::Modify,
+                ..
+            }) if identity == bob_uuid
+        ));
+
+        assert_eq!(
+            idms_prox_write.scim_v2_delete(&bob, ScimResourceType::User, alice_uuid, None, ct),
+            Err(OperationError::AccessDenied)
+        );
+        assert!(matches!(
+            idms_audit.audit_rx().try_recv(),
+            Ok(AuditEvent::AccessDenied {
+                identity: Some(identity),
+                operation: AuditOperation::Delete,
+                ..
+            }) if identity == bob_uuid
+        ));
+    }
+
+    #[idm_test]
+    async fn test_scim_v2_write(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
+        let ct = duration_from_epoch_now();
+        let ident = Identity::from_internal();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let user = ScimResource::User(ScimUser {
+            user_name: Some("scim_carol".to_string()),
+            display_name: Some("Carol".to_string()),
+            emails: vec![ScimMultiValue {
+                value: "carol@example.com".to_string(),
+                primary: Some(true),
+                ..Default::default()
+            }],
+            active: Some(false),
+            ..Default::default()
+        });
+        let user = idms_prox_write
+            .scim_v2_create(&ident, ScimResourceType::User, &user, ct)
+            .expect("Failed to create user");
+        let ScimResource::User(user) = user else {
+            panic!("Created resource is not a user");
+        };
+        let user_uuid = user.id.expect("Created user has no id");
+        assert_eq!(user.active, Some(false));
+        assert_eq!(user.emails.len(), 1);
+        assert_eq!(user.emails[0].primary, Some(true));
+
+        let group = ScimResource::Group(ScimGroup {
+            display_name: Some("scim_group".to_string()),
+            members: vec![ScimMultiValue {
+                value: user_uuid.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let group = idms_prox_write
+            .scim_v2_create(&ident, ScimResourceType::Group, &group, ct)
+            .expect("Failed to create group");
+        let group_uuid = group.id().expect("Created group has no id");
+        let version = group
+            .meta()
+            .and_then(|meta| meta.version.clone())
+            .expect("Created group has no version");
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Patching with a stale version fails.
+        let ct = ct + Duration::from_secs(1);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let patch: ScimPatchRequest = serde_json::from_str(&format!(
+            r#"{{
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [
+                    {{"op": "remove", "path": "members[value eq \"{}\"]"}},
+                    {{"op": "Replace", "value": {{"displayName": "scim_group_renamed"}}}}
+                ]
+            }}"#,
+            user_uuid
+        ))
+        .expect("Invalid patch request");
+
+        assert_eq!(
+            idms_prox_write.scim_v2_patch(
+                &ident,
+                ScimResourceType::Group,
+                group_uuid,
+                &patch,
+                Some("W/\"stale\""),
+                ct
+            ),
+            Err(OperationError::ModifyAssertionFailed)
+        );
+
+        let group = idms_prox_write
+            .scim_v2_patch(
+                &ident,
+                ScimResourceType::Group,
+                group_uuid,
+                &patch,
+                Some(&version),
+                ct,
+            )
+            .expect("Failed to patch group");
+        let ScimResource::Group(group) = group else {
+            panic!("Patched resource is not a group");
+        };
+        assert_eq!(group.display_name.as_deref(), Some("scim_group_renamed"));
+        assert!(group.members.is_empty());
+        assert_ne!(
+            group.meta.and_then(|meta| meta.version),
+            Some(version.clone())
+        );
+
+        // Reactivate the user.
+        let patch: ScimPatchRequest = serde_json::from_str(
+            r#"{"Operations": [{"op": "replace", "path": "active", "value": "True"}]}"#,
+        )
+        .expect("Invalid patch request");
+        let user = idms_prox_write
+            .scim_v2_patch(&ident, ScimResourceType::User, user_uuid, &patch, None, ct)
+            .expect("Failed to patch user");
+        assert!(matches!(
+            user,
+            ScimResource::User(ScimUser {
+                active: Some(true),
+                ..
+            })
+        ));
+
+        // Read only attributes can not be changed.
+        let patch: ScimPatchRequest = serde_json::from_str(
+            r#"{"Operations": [{"op": "add", "path": "groups", "value": [{"value": "admins"}]}]}"#,
+        )
+        .expect("Invalid patch request");
+        assert_eq!(
+            idms_prox_write.scim_v2_patch(
+                &ident,
+                ScimResourceType::User,
+                user_uuid,
+                &patch,
+                None,
+                ct
+            ),
+            Err(OperationError::SystemProtectedAttribute)
+        );
+
+        // A group is not a user.
+        assert_eq!(
+            idms_prox_write.scim_v2_delete(&ident, ScimResourceType::User, group_uuid, None, ct),
+            Err(OperationError::NoMatchingEntries)
+        );
+        assert!(idms_prox_write
+            .scim_v2_delete(&ident, ScimResourceType::Group, group_uuid, Some("*"), ct)
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+    }
+}
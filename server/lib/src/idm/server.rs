@@ -1,3 +1,4 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -27,8 +28,8 @@ use url::Url;
 use webauthn_rs::prelude::{Webauthn, WebauthnBuilder};
 
 use super::event::ReadBackupCodeEvent;
-use super::ldap::{LdapBoundToken, LdapSession};
-use crate::credential::{softlock::CredSoftLock, Credential};
+use super::ldap::{LdapBoundToken, LdapPagedSearchState, LdapSession};
+use crate::credential::{softlock::CredSoftLock, Credential, CredentialKind};
 use crate::idm::account::Account;
 use crate::idm::audit::AuditEvent;
 use crate::idm::authsession::AuthSession;
@@ -42,8 +43,8 @@ use crate::idm::event::PasswordChangeEvent;
 use crate::idm::event::{AuthEvent, AuthEventStep, AuthResult};
 use crate::idm::event::{
     CredentialStatusEvent, LdapAuthEvent, LdapTokenAuthEvent, RadiusAuthTokenEvent,
-    RegenerateRadiusSecretEvent, UnixGroupTokenEvent, UnixPasswordChangeEvent, UnixUserAuthEvent,
-    UnixUserTokenEvent,
+    RegenerateRadiusSecretEvent, UnixGroupTokenEvent, UnixPasswordChangeEvent,
+    UnixPasswordSelfChangeEvent, UnixUserAuthEvent, UnixUserTokenEvent,
 };
 use crate::idm::oauth2::{
     Oauth2ResourceServers, Oauth2ResourceServersReadTransaction,
@@ -54,6 +55,7 @@ use crate::idm::scim::SyncAccount;
 use crate::idm::serviceaccount::ServiceAccount;
 use crate::idm::unix::{UnixGroup, UnixUserAccount};
 use crate::idm::AuthState;
+use crate::metrics::METRICS;
 use crate::prelude::*;
 use crate::utils::{password_from_random, readable_password_from_random, uuid_from_duration, Sid};
 use crate::value::{Session, SessionState};
@@ -69,11 +71,17 @@ pub struct DomainKeys {
     pub(crate) cookie_key: [u8; 64],
 }
 
-#[derive(Clone)]
+/// The policy that constrains the credentials and sessions of accounts. The server holds the
+/// system wide policy, and each account holds the policy combined from the account policy
+/// groups that it is a member of. Where more than one policy applies, the most restrictive
+/// setting of each of them wins.
+#[derive(Clone, Debug)]
 pub(crate) struct AccountPolicy {
     privilege_expiry: u32,
     authsession_expiry: u32,
     pw_badlist_cache: HashSet<String>,
+    pw_min_length: usize,
+    credential_type_allowed: Option<BTreeSet<CredentialKind>>,
 }
 
 impl AccountPolicy {
@@ -86,7 +94,64 @@ impl AccountPolicy {
             privilege_expiry,
             authsession_expiry,
             pw_badlist_cache,
+            pw_min_length: PW_MIN_LENGTH,
+            credential_type_allowed: None,
+        }
+    }
+
+    /// A policy that places no constraints beyond the server minimums. Merging policies into
+    /// this one results in their combination.
+    pub(crate) fn unconstrained() -> Self {
+        Self {
+            privilege_expiry: u32::MAX,
+            authsession_expiry: u32::MAX,
+            pw_badlist_cache: Default::default(),
+            pw_min_length: PW_MIN_LENGTH,
+            credential_type_allowed: None,
+        }
+    }
+
+    /// Extract the account policy that a group defines, if it has the `account_policy` class.
+    /// Settings that the group doesn't define are left unconstrained.
+    pub(crate) fn try_from_group_entry(value: &Entry<EntrySealed, EntryCommitted>) -> Option<Self> {
+        if !value.attribute_equality(Attribute::Class, &EntryClass::AccountPolicy.into()) {
+            return None;
+        }
+
+        let mut policy = Self::unconstrained();
+        if let Some(expiry) = value.get_ava_single_uint32(Attribute::PrivilegeExpiry) {
+            policy.privilege_expiry = expiry;
+        }
+        if let Some(expiry) = value.get_ava_single_uint32(Attribute::AuthSessionExpiry) {
+            policy.authsession_expiry = expiry;
         }
+        if let Some(len) = value.get_ava_single_uint32(Attribute::AuthPasswordMinimumLength) {
+            policy.pw_min_length = policy.pw_min_length.max(len as usize);
+        }
+        policy.credential_type_allowed = value
+            .get_ava_credtype(Attribute::CredentialTypeAllowed)
+            .cloned();
+
+        Some(policy)
+    }
+
+    /// Combine this policy with another, keeping the most restrictive setting of each.
+    pub(crate) fn merge(mut self, other: &AccountPolicy) -> Self {
+        self.privilege_expiry = self.privilege_expiry.min(other.privilege_expiry);
+        self.authsession_expiry = self.authsession_expiry.min(other.authsession_expiry);
+        self.pw_min_length = self.pw_min_length.max(other.pw_min_length);
+        self.pw_badlist_cache
+            .extend(other.pw_badlist_cache.iter().cloned());
+        // The allowed credential types are the intersection of both policies' allowed types.
+        self.credential_type_allowed =
+            match (self.credential_type_allowed, &other.credential_type_allowed) {
+                (Some(allowed), Some(other_allowed)) => {
+                    Some(allowed.intersection(other_allowed).copied().collect())
+                }
+                (allowed, None) => allowed,
+                (None, other_allowed) => other_allowed.clone(),
+            };
+        self
     }
 
     pub(crate) fn privilege_expiry(&self) -> u32 {
@@ -101,6 +166,18 @@ impl AccountPolicy {
         &self.pw_badlist_cache
     }
 
+    /// The minimum length of a password, which is never less than the server minimum.
+    pub(crate) fn password_minimum_length(&self) -> usize {
+        self.pw_min_length
+    }
+
+    /// Is this kind of credential allowed to be used for authentication?
+    pub(crate) fn credential_kind_allowed(&self, kind: CredentialKind) -> bool {
+        self.credential_type_allowed
+            .as_ref()
+            .map_or(true, |allowed| allowed.contains(&kind))
+    }
+
     #[cfg(test)]
     pub(crate) fn from_pw_badlist_cache(pw_badlist_cache: HashSet<String>) -> Self {
         Self {
@@ -116,6 +193,8 @@ impl Default for AccountPolicy {
             privilege_expiry: DEFAULT_AUTH_PRIVILEGE_EXPIRY,
             authsession_expiry: DEFAULT_AUTH_SESSION_EXPIRY,
             pw_badlist_cache: Default::default(), // TODO: more thoughts on this
+            pw_min_length: PW_MIN_LENGTH,
+            credential_type_allowed: None,
         }
     }
 }
@@ -130,6 +209,9 @@ pub struct IdmServer {
     softlocks: HashMap<Uuid, CredSoftLockMutex>,
     /// A set of in progress credential registrations
     cred_update_sessions: BptreeMap<Uuid, CredentialUpdateSessionMutex>,
+    /// The time after which each oauth2 device may next poll for its tokens, keyed by the
+    /// device code.
+    oauth2_device_polls: Mutex<BTreeMap<String, Duration>>,
     /// Reference to the query server.
     qs: QueryServer,
     /// The configured crypto policy for the IDM server. Later this could be transactional and loaded from the db similar to access. But today it's just to allow dynamic pbkdf2rounds
@@ -175,6 +257,7 @@ pub struct IdmServerProxyReadTransaction<'a> {
     pub qs_read: QueryServerReadTransaction<'a>,
     pub(crate) domain_keys: CowCellReadTxn<DomainKeys>,
     pub(crate) oauth2rs: Oauth2ResourceServersReadTransaction,
+    webauthn: &'a Webauthn,
 }
 
 pub struct IdmServerProxyWriteTransaction<'a> {
@@ -199,6 +282,12 @@ pub struct IdmServerAudit {
     pub(crate) audit_rx: Receiver<AuditEvent>,
 }
 
+/// The origin of this server. Webauthn is always built with it as the first allowed origin.
+fn webauthn_origin(webauthn: &Webauthn) -> &Url {
+    #[allow(clippy::unwrap_used)]
+    webauthn.get_allowed_origins().get(0).unwrap()
+}
+
 impl IdmServer {
     pub async fn new(
         qs: QueryServer,
@@ -209,6 +298,8 @@ impl IdmServer {
         let crypto_policy = CryptoPolicy::time_target(Duration::from_millis(10));
         let (async_tx, async_rx) = unbounded();
         let (audit_tx, audit_rx) = unbounded();
+        // Write transactions report events such as denied access to the same channel.
+        qs.set_audit_tx(audit_tx.clone());
 
         // Get the domain name, as the relying party id.
         let (
@@ -303,6 +394,7 @@ impl IdmServer {
                 sessions: BptreeMap::new(),
                 softlocks: HashMap::new(),
                 cred_update_sessions: BptreeMap::new(),
+                oauth2_device_polls: Mutex::new(BTreeMap::new()),
                 qs,
                 crypto_policy,
                 async_tx,
@@ -325,6 +417,20 @@ impl IdmServer {
         self.domain_keys.read().cookie_key
     }
 
+    /// rfc8628 section 3.5 - a device that polls faster than the interval must slow down. This
+    /// is checked before the poll starts a write transaction.
+    pub async fn oauth2_device_poll_allowed(&self, device_code: &str, ct: Duration) -> bool {
+        let mut polls = self.oauth2_device_polls.lock().await;
+        // Forget the devices that are able to poll again.
+        polls.retain(|_, next_poll| *next_poll > ct);
+        let allowed = !polls.contains_key(device_code);
+        polls.insert(
+            device_code.to_string(),
+            ct + Duration::from_secs(OAUTH2_DEVICE_CODE_INTERVAL as u64),
+        );
+        allowed
+    }
+
     /// Start an auth txn
     pub async fn auth(&self) -> IdmServerAuthTransaction<'_> {
         let qs_read = self.qs.read().await;
@@ -354,6 +460,7 @@ impl IdmServer {
             qs_read: self.qs.read().await,
             domain_keys: self.domain_keys.read(),
             oauth2rs: self.oauth2rs.read(),
+            webauthn: &self.webauthn,
             // async_tx: self.async_tx.clone(),
         }
     }
@@ -420,6 +527,13 @@ impl IdmServerAudit {
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn discard(&mut self) {
+        while let Ok(m) = self.audit_rx.try_recv() {
+            trace!(?m, "discarding audit event");
+        }
+    }
+
     pub fn audit_rx(&mut self) -> &mut Receiver<AuditEvent> {
         &mut self.audit_rx
     }
@@ -457,7 +571,11 @@ impl IdmServerDelayed {
     }
 
     pub async fn next(&mut self) -> Option<DelayedAction> {
-        self.async_rx.recv().await
+        let da = self.async_rx.recv().await;
+        if da.is_some() {
+            METRICS.delayed_action_dequeued();
+        }
+        da
     }
 }
 
@@ -1002,8 +1120,7 @@ impl<'a> IdmServerAuthTransaction<'a> {
     }
 
     pub fn get_origin(&self) -> &Url {
-        #[allow(clippy::unwrap_used)]
-        self.webauthn.get_allowed_origins().get(0).unwrap()
+        webauthn_origin(self.webauthn)
     }
 
     #[instrument(level = "trace", skip(self))]
@@ -1347,6 +1464,7 @@ impl<'a> IdmServerAuthTransaction<'a> {
                     session_id: uat.session_id,
                     spn,
                     effective_session: LdapSession::UserAuthToken(uat),
+                    paged_search: LdapPagedSearchState::default(),
                 }))
             }
             Token::ApiToken(apit, entry) => {
@@ -1360,6 +1478,7 @@ impl<'a> IdmServerAuthTransaction<'a> {
                     session_id: apit.token_id,
                     spn,
                     effective_session: LdapSession::ApiToken(apit),
+                    paged_search: LdapPagedSearchState::default(),
                 }))
             }
         }
@@ -1397,6 +1516,7 @@ impl<'a> IdmServerAuthTransaction<'a> {
                 session_id,
                 spn: account.spn,
                 effective_session: LdapSession::UnixBind(UUID_ANONYMOUS),
+                paged_search: LdapPagedSearchState::default(),
             }))
         } else {
             let account =
@@ -1463,6 +1583,7 @@ impl<'a> IdmServerAuthTransaction<'a> {
                             spn: account.spn,
                             session_id,
                             effective_session: LdapSession::UnixBind(account.uuid),
+                            paged_search: LdapPagedSearchState::default(),
                         }))
                     } else {
                         // PW failure, update softlock.
@@ -1505,6 +1626,10 @@ impl<'a> IdmServerTransaction<'a> for IdmServerProxyReadTransaction<'a> {
 }
 
 impl<'a> IdmServerProxyReadTransaction<'a> {
+    pub fn get_origin(&self) -> &Url {
+        webauthn_origin(self.webauthn)
+    }
+
     pub fn get_radiusauthtoken(
         &mut self,
         rate: &RadiusAuthTokenEvent,
@@ -1613,8 +1738,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
     }
 
     pub fn get_origin(&self) -> &Url {
-        #[allow(clippy::unwrap_used)]
-        self.webauthn.get_allowed_origins().get(0).unwrap()
+        webauthn_origin(self.webauthn)
     }
 
     fn check_password_quality(
@@ -1837,6 +1961,54 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         Ok(())
     }
 
+    /// Change the unix password of an account that has proven its current password. As with
+    /// the self write access control, only persons may change their own password.
+    pub fn set_unix_account_password_self(
+        &mut self,
+        pce: &UnixPasswordSelfChangeEvent,
+    ) -> Result<(), OperationError> {
+        let account_entry = self
+            .qs_write
+            .internal_search_uuid(pce.target)
+            .map_err(|e| {
+                admin_error!("Failed to start set unix account password {:?}", e);
+                e
+            })?;
+
+        if !account_entry
+            .attribute_equality(Attribute::Class, &EntryClass::Person.to_partialvalue())
+        {
+            security_info!("Only persons may change their own unix password");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let account = UnixUserAccount::try_from_entry_rw(&account_entry, &mut self.qs_write)?;
+
+        if account.is_anonymous() {
+            return Err(OperationError::SystemProtectedObject);
+        }
+
+        self.check_password_quality(pce.cleartext.as_str(), account.related_inputs().as_slice())
+            .map_err(|e| {
+                admin_error!(?e, "Failed to checked password quality");
+                e
+            })?;
+
+        let modlist = account
+            .gen_password_mod(pce.cleartext.as_str(), self.crypto_policy)
+            .map_err(|e| {
+                admin_error!(?e, "Unable to generate password change modlist");
+                e
+            })?;
+
+        self.qs_write
+            .internal_modify_uuid(pce.target, &modlist)
+            .map_err(|e| {
+                request_error!(error = ?e);
+                e
+            })
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub fn recover_account(
         &mut self,
@@ -2178,16 +2350,16 @@ mod tests {
     use time::OffsetDateTime;
     use uuid::Uuid;
 
-    use crate::credential::{Credential, Password};
+    use crate::credential::{Credential, CredentialKind, Password};
     use crate::idm::account::DestroySessionTokenEvent;
-    use crate::idm::audit::AuditEvent;
+    use crate::idm::audit::{AuditEvent, AuditOperation};
     use crate::idm::delayed::{AuthSessionRecord, DelayedAction};
     use crate::idm::event::{AuthEvent, AuthResult};
     use crate::idm::event::{
         PasswordChangeEvent, RadiusAuthTokenEvent, RegenerateRadiusSecretEvent,
         UnixGroupTokenEvent, UnixPasswordChangeEvent, UnixUserAuthEvent, UnixUserTokenEvent,
     };
-    use crate::idm::server::{IdmServer, IdmServerTransaction, Token};
+    use crate::idm::server::{AccountPolicy, IdmServer, IdmServerTransaction, Token};
     use crate::idm::AuthState;
     use crate::modify::{Modify, ModifyList};
     use crate::prelude::*;
@@ -2198,8 +2370,12 @@ mod tests {
     const TEST_PASSWORD_INC: &str = "ntaoentu nkrcgaeunhibwmwmqj;k wqjbkx ";
     const TEST_CURRENT_TIME: u64 = 6000;
 
-    #[idm_test]
-    async fn test_idm_anonymous_auth(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+    #[idm_test(audit)]
+    async fn test_idm_anonymous_auth(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
         // Start and test anonymous auth.
         let mut idms_auth = idms.auth().await;
         // Send the initial auth event for initialising the session
@@ -2322,6 +2498,11 @@ mod tests {
             }
         };
 
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ANONYMOUS),
+            _ => assert!(false),
+        }
+
         idms_auth.commit().expect("Must not fail");
     }
 
@@ -2457,12 +2638,20 @@ mod tests {
         token
     }
 
-    #[idm_test]
-    async fn test_idm_simple_password_auth(idms: &IdmServer, idms_delayed: &mut IdmServerDelayed) {
+    #[idm_test(audit)]
+    async fn test_idm_simple_password_auth(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
         init_admin_w_password(idms, TEST_PASSWORD)
             .await
             .expect("Failed to setup admin account");
         check_admin_password(idms, TEST_PASSWORD).await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
 
         // Clear our the session record
         let da = idms_delayed.try_recv().expect("invalid");
@@ -2470,10 +2659,11 @@ mod tests {
         idms_delayed.check_is_empty_or_panic();
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_simple_password_spn_auth(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         init_admin_w_password(idms, TEST_PASSWORD)
             .await
@@ -2522,6 +2712,11 @@ mod tests {
             }
         };
 
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
+
         // Clear our the session record
         let da = idms_delayed.try_recv().expect("invalid");
         assert!(matches!(da, DelayedAction::AuthSessionRecord(_)));
@@ -2586,6 +2781,44 @@ mod tests {
         idms_auth.commit().expect("Must not fail");
     }
 
+    #[idm_test(audit)]
+    async fn test_idm_audit_access_denied(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let anon_entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_ANONYMOUS)
+            .expect("Failed to find anonymous");
+
+        // Anonymous may not create entries.
+        let ce = CreateEvent::new_impersonate_identity(
+            Identity::from_impersonate_entry_readwrite(anon_entry),
+            vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname("testgroup"))
+            )],
+        );
+
+        assert!(idms_prox_write.qs_write.create(&ce) == Err(OperationError::AccessDenied));
+        drop(idms_prox_write);
+
+        // The denial is reported even though the transaction was never committed.
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AccessDenied {
+                identity: Some(UUID_ANONYMOUS),
+                operation: AuditOperation::Create,
+                ..
+            }) => {}
+            _ => assert!(false),
+        }
+    }
+
     #[idm_test]
     async fn test_idm_simple_password_reset(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
         let pce = PasswordChangeEvent::new_internal(UUID_ADMIN, TEST_PASSWORD);
@@ -2785,6 +3018,8 @@ mod tests {
         assert!(tok_r.groups[0].name == "admin");
         assert!(tok_r.groups[1].name == "testgroup");
         assert!(tok_r.valid);
+        // A subordinate id range was allocated when the account became posix.
+        assert!(tok_r.subid_range.map(|r| r.count) == Some(SUBID_RANGE_SIZE));
 
         // Show we can get the admin as a unix group token too
         let ugte = UnixGroupTokenEvent::new_impersonate(
@@ -2865,9 +3100,88 @@ mod tests {
     }
 
     #[idm_test]
+    async fn test_idm_unix_password_self_change(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let person_uuid = Uuid::new_v4();
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::PosixAccount.to_value()),
+            (Attribute::Uuid, Value::Uuid(person_uuid)),
+            (Attribute::Name, Value::new_iname("testperson1")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson1")),
+            (Attribute::GidNumber, Value::new_uint32(12345678))
+        );
+        let ce = CreateEvent::new_internal(vec![e1]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+
+        // Admin is not a person, so it can't change its own password this way.
+        let me_posix = ModifyEvent::new_internal_invalid(
+            filter!(f_eq(Attribute::Name, PartialValue::new_iname("admin"))),
+            ModifyList::new_list(vec![
+                Modify::Present(Attribute::Class.into(), EntryClass::PosixAccount.into()),
+                Modify::Present(Attribute::GidNumber.into(), Value::new_uint32(2001)),
+            ]),
+        );
+        assert!(idms_prox_write.qs_write.modify(&me_posix).is_ok());
+
+        for target in [person_uuid, UUID_ADMIN] {
+            let pce = UnixPasswordChangeEvent::new_internal(target, TEST_PASSWORD);
+            assert!(idms_prox_write.set_unix_account_password(&pce).is_ok());
+        }
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_auth = idms.auth().await;
+        let uuae = UnixUserAuthEvent::new_internal(person_uuid, TEST_PASSWORD);
+        let person_tok = idms_auth
+            .auth_unix(&uuae, ct)
+            .await
+            .expect("failed to auth")
+            .expect("incorrect password");
+        let uuae = UnixUserAuthEvent::new_internal(UUID_ADMIN, TEST_PASSWORD);
+        let admin_tok = idms_auth
+            .auth_unix(&uuae, ct)
+            .await
+            .expect("failed to auth")
+            .expect("incorrect password");
+        assert!(idms_auth.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let pce = UnixPasswordSelfChangeEvent::from_unix_auth(&admin_tok, TEST_PASSWORD_INC.into());
+        assert_eq!(
+            idms_prox_write.set_unix_account_password_self(&pce),
+            Err(OperationError::AccessDenied)
+        );
+
+        // The new password is still subject to the quality checks.
+        let pce = UnixPasswordSelfChangeEvent::from_unix_auth(&person_tok, "password".into());
+        assert!(matches!(
+            idms_prox_write.set_unix_account_password_self(&pce),
+            Err(OperationError::PasswordQuality(_))
+        ));
+
+        let pce =
+            UnixPasswordSelfChangeEvent::from_unix_auth(&person_tok, TEST_PASSWORD_INC.into());
+        assert!(idms_prox_write.set_unix_account_password_self(&pce).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_auth = idms.auth().await;
+        let uuae = UnixUserAuthEvent::new_internal(person_uuid, TEST_PASSWORD_INC);
+        assert!(matches!(idms_auth.auth_unix(&uuae, ct).await, Ok(Some(_))));
+        assert!(idms_auth.commit().is_ok());
+    }
+
+    #[idm_test(audit)]
     async fn test_idm_simple_password_upgrade(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         // Assert the delayed action queue is empty
         idms_delayed.check_is_empty_or_panic();
@@ -2903,6 +3217,10 @@ mod tests {
 
         // Do an auth, this will trigger the action to send.
         check_admin_password(idms, "password").await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
 
         // ⚠️  We have to be careful here. Between these two actions, it's possible
         // that on the pw upgrade that the credential uuid changes. This immediately
@@ -2936,6 +3254,10 @@ mod tests {
 
         // Check the admin pw still matches
         check_admin_password(idms, "password").await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
         // Clear the next auth session record
         let da = idms_delayed.try_recv().expect("invalid");
         assert!(matches!(da, DelayedAction::AuthSessionRecord(_)));
@@ -3345,6 +3667,11 @@ mod tests {
 
         idms_auth.commit().expect("Must not fail");
 
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { .. }) => {}
+            _ => assert!(false),
+        }
+
         // Clear the auth session record
         let da = idms_delayed.try_recv().expect("invalid");
         assert!(matches!(da, DelayedAction::AuthSessionRecord(_)));
@@ -3510,8 +3837,12 @@ mod tests {
         assert!(idms_auth.commit().is_ok());
     }
 
-    #[idm_test]
-    async fn test_idm_jwt_uat_expiry(idms: &IdmServer, idms_delayed: &mut IdmServerDelayed) {
+    #[idm_test(audit)]
+    async fn test_idm_jwt_uat_expiry(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let expiry = ct + Duration::from_secs((DEFAULT_AUTH_SESSION_EXPIRY + 1).into());
         // Do an authenticate
@@ -3519,6 +3850,10 @@ mod tests {
             .await
             .expect("Failed to setup admin account");
         let token = check_admin_password(idms, TEST_PASSWORD).await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
 
         // Clear out the queued session record
         let da = idms_delayed.try_recv().expect("invalid");
@@ -3633,10 +3968,11 @@ mod tests {
         // Now show that sessions trim!
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_account_session_validation(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         use compact_jwt::{Jws, JwsUnverified};
         use kanidm_proto::v1::UserAuthToken;
@@ -3656,6 +3992,10 @@ mod tests {
             .await
             .expect("Failed to setup admin account");
         let token = check_admin_password(idms, TEST_PASSWORD).await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
 
         // Process the session info.
         let da = idms_delayed.try_recv().expect("invalid");
@@ -3729,10 +4069,11 @@ mod tests {
         }
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_account_session_expiry(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
@@ -3841,6 +4182,11 @@ mod tests {
             }
         };
 
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ANONYMOUS),
+            _ => assert!(false),
+        }
+
         idms_auth.commit().expect("Must not fail");
 
         // Token_str to uat
@@ -3991,10 +4337,11 @@ mod tests {
         assert!(!ident.has_claim("authclass_single"));
     }
 
-    #[idm_test]
+    #[idm_test(audit)]
     async fn test_idm_jwt_uat_token_key_reload(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
@@ -4002,6 +4349,10 @@ mod tests {
             .await
             .expect("Failed to setup admin account");
         let token = check_admin_password(idms, TEST_PASSWORD).await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
 
         // Clear the session record
         let da = idms_delayed.try_recv().expect("invalid");
@@ -4035,6 +4386,10 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
         // Check the old token is invalid, due to reload.
         let new_token = check_admin_password(idms, TEST_PASSWORD).await;
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AuthenticationSuccess { uuid, .. }) => assert_eq!(uuid, UUID_ADMIN),
+            _ => assert!(false),
+        }
 
         // Clear the session record
         let da = idms_delayed.try_recv().expect("invalid");
@@ -4084,4 +4439,76 @@ mod tests {
 
         // Any checks?
     }
+
+    #[test]
+    fn test_idm_account_policy_merge() {
+        let admins = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::AccountPolicy.to_value()),
+            (Attribute::Name, Value::new_iname("admins")),
+            (
+                Attribute::CredentialTypeAllowed,
+                Value::CredentialType(CredentialKind::PasswordMfa)
+            ),
+            (
+                Attribute::CredentialTypeAllowed,
+                Value::CredentialType(CredentialKind::Webauthn)
+            ),
+            (Attribute::AuthPasswordMinimumLength, Value::Uint32(16)),
+            (Attribute::AuthSessionExpiry, Value::Uint32(3600))
+        )
+        .into_sealed_committed();
+
+        let contractors = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::AccountPolicy.to_value()),
+            (Attribute::Name, Value::new_iname("contractors")),
+            (
+                Attribute::CredentialTypeAllowed,
+                Value::CredentialType(CredentialKind::Password)
+            ),
+            (
+                Attribute::CredentialTypeAllowed,
+                Value::CredentialType(CredentialKind::PasswordMfa)
+            ),
+            (Attribute::AuthPasswordMinimumLength, Value::Uint32(4)),
+            (Attribute::PrivilegeExpiry, Value::Uint32(60))
+        )
+        .into_sealed_committed();
+
+        let plain = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("plain"))
+        )
+        .into_sealed_committed();
+
+        assert!(AccountPolicy::try_from_group_entry(&plain).is_none());
+
+        let admins = AccountPolicy::try_from_group_entry(&admins).expect("No policy");
+        let contractors = AccountPolicy::try_from_group_entry(&contractors).expect("No policy");
+
+        // A group can not weaken the server minimum password length.
+        assert_eq!(contractors.password_minimum_length(), PW_MIN_LENGTH);
+
+        // No policy is unconstrained.
+        let policy = AccountPolicy::unconstrained();
+        assert!(policy.credential_kind_allowed(CredentialKind::Password));
+        assert!(policy.credential_kind_allowed(CredentialKind::GeneratedPassword));
+        assert_eq!(policy.password_minimum_length(), PW_MIN_LENGTH);
+
+        // The most restrictive settings win.
+        let policy = [&admins, &contractors]
+            .into_iter()
+            .fold(AccountPolicy::unconstrained(), AccountPolicy::merge);
+        assert!(!policy.credential_kind_allowed(CredentialKind::Password));
+        assert!(!policy.credential_kind_allowed(CredentialKind::GeneratedPassword));
+        assert!(!policy.credential_kind_allowed(CredentialKind::Webauthn));
+        assert!(policy.credential_kind_allowed(CredentialKind::PasswordMfa));
+        assert_eq!(policy.password_minimum_length(), 16);
+        assert_eq!(policy.authsession_expiry(), 3600);
+        assert_eq!(policy.privilege_expiry(), 60);
+    }
 }
@@ -0,0 +1,42 @@
+use crate::idm::server::IdmServerProxyReadTransaction;
+use crate::idm::unixpolicy::{policy_iutf8s, policy_name, policy_refers, UnixPolicy};
+use crate::prelude::*;
+use kanidm_proto::v1::UnixSudoRule;
+
+impl UnixPolicy for UnixSudoRule {
+    fn from_entry(value: &Entry<EntryReduced, EntryCommitted>) -> Result<Self, OperationError> {
+        let commands = value
+            .get_ava_set(Attribute::SudoCommand)
+            .and_then(|vs| vs.as_utf8_iter())
+            .map(|i| i.map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let nopasswd = value
+            .get_ava_single_bool(Attribute::SudoNopasswd)
+            .unwrap_or(false);
+
+        Ok(UnixSudoRule {
+            name: policy_name(value)?,
+            uuid: value.get_uuid(),
+            users: policy_refers(value, Attribute::SudoUser),
+            commands,
+            runas: policy_iutf8s(value, Attribute::SudoRunas),
+            nopasswd,
+        })
+    }
+
+    fn sort_key(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// List the sudo rules that apply on the host making this request. Hosts are
+    /// expected to cache these and evaluate them locally.
+    pub fn list_unix_sudo_rules(
+        &mut self,
+        ident: &Identity,
+    ) -> Result<Vec<UnixSudoRule>, OperationError> {
+        self.list_unix_host_rules(ident, EntryClass::SudoRule, Attribute::SudoHost)
+    }
+}
@@ -2,7 +2,7 @@ use std::iter;
 // use crossbeam::channel::Sender;
 use std::time::Duration;
 
-use kanidm_proto::v1::{OperationError, UnixGroupToken, UnixUserToken};
+use kanidm_proto::v1::{OperationError, UnixGroupToken, UnixSubidRange, UnixUserToken};
 use time::OffsetDateTime;
 use tokio::sync::mpsc::UnboundedSender as Sender;
 use uuid::Uuid;
@@ -12,6 +12,7 @@ use kanidm_lib_crypto::CryptoPolicy;
 use crate::credential::softlock::CredSoftLockPolicy;
 use crate::credential::Credential;
 use crate::idm::delayed::{DelayedAction, UnixPasswordUpgrade};
+use crate::metrics::METRICS;
 use crate::modify::{ModifyInvalid, ModifyList};
 use crate::prelude::*;
 
@@ -24,6 +25,7 @@ pub(crate) struct UnixUserAccount {
     pub uuid: Uuid,
     pub shell: Option<String>,
     pub sshkeys: Vec<String>,
+    pub subid_range_start: Option<u32>,
     pub groups: Vec<UnixGroup>,
     cred: Option<Credential>,
     pub valid_from: Option<OffsetDateTime>,
@@ -98,6 +100,8 @@ macro_rules! try_from_entry {
             .map(|i| i.map(|s| s.to_string()).collect())
             .unwrap_or_default();
 
+        let subid_range_start = $value.get_ava_single_uint32(Attribute::SubidRangeStart);
+
         let cred = $value
             .get_ava_single_credential(Attribute::UnixPassword)
             .map(|v| v.clone());
@@ -123,6 +127,7 @@ macro_rules! try_from_entry {
             gidnumber,
             shell,
             sshkeys,
+            subid_range_start,
             groups: $groups,
             cred,
             valid_from,
@@ -174,6 +179,10 @@ impl UnixUserAccount {
             groups,
             sshkeys: self.sshkeys.clone(),
             valid: self.is_within_valid_time(ct),
+            subid_range: self.subid_range_start.map(|start| UnixSubidRange {
+                start,
+                count: SUBID_RANGE_SIZE,
+            }),
         })
     }
 
@@ -284,9 +293,11 @@ impl UnixUserAccount {
                         error!(crypto_err = ?e);
                         e.into()
                     })?;
+                    METRICS.auth_attempt("unixpassword", valid);
                     if valid {
                         security_info!("Successful unix cred handling");
                         if pw.requires_upgrade() {
+                            METRICS.delayed_action_queued();
                             async_tx
                                 .send(DelayedAction::UnixPwUpgrade(UnixPasswordUpgrade {
                                     target_uuid: self.uuid,
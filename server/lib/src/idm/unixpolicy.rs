@@ -0,0 +1,387 @@
+//! The policy that unix hosts retrieve from the server and cache, such as the sudo and
+//! host access rules that apply to them.
+
+use std::collections::BTreeSet;
+
+use crate::idm::server::IdmServerProxyReadTransaction;
+use crate::prelude::*;
+
+/// An entry in the form that unix hosts consume.
+pub(crate) trait UnixPolicy: Sized {
+    fn from_entry(value: &Entry<EntryReduced, EntryCommitted>) -> Result<Self, OperationError>;
+
+    /// Hosts are given a stable ordering by this key so that cache updates are predictable.
+    fn sort_key(&self) -> &str;
+}
+
+pub(crate) fn policy_name(
+    value: &Entry<EntryReduced, EntryCommitted>,
+) -> Result<String, OperationError> {
+    value
+        .get_ava_single_iname(Attribute::Name)
+        .map(str::to_string)
+        .ok_or(OperationError::InvalidEntryState)
+}
+
+pub(crate) fn policy_refers(
+    value: &Entry<EntryReduced, EntryCommitted>,
+    attr: Attribute,
+) -> Vec<Uuid> {
+    value
+        .get_ava_refer(attr)
+        .map(|s| s.iter().copied().collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn policy_iutf8s(
+    value: &Entry<EntryReduced, EntryCommitted>,
+    attr: Attribute,
+) -> Vec<String> {
+    value
+        .get_ava_iter_iutf8(attr)
+        .map(|i| i.map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// Search for the policy that is visible to this identity, in the form that unix
+    /// hosts consume.
+    pub(crate) fn list_unix_policy<T: UnixPolicy>(
+        &mut self,
+        filter: Filter<FilterInvalid>,
+        ident: &Identity,
+    ) -> Result<Vec<T>, OperationError> {
+        let entries = self
+            .qs_read
+            .impersonate_search_ext(filter.clone(), filter, ident)?;
+
+        entries
+            .iter()
+            .map(T::from_entry)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|mut policy| {
+                policy.sort_unstable_by(|a, b| a.sort_key().cmp(b.sort_key()));
+                policy
+            })
+    }
+
+    /// List the rules of `class` that apply on the host making this request, being those
+    /// that name the host, or a group it is a member of, in `host_attr`. The host is
+    /// determined from the identity, so this must be called with a host's credential.
+    pub(crate) fn list_unix_host_rules<T: UnixPolicy>(
+        &mut self,
+        ident: &Identity,
+        class: EntryClass,
+        host_attr: Attribute,
+    ) -> Result<Vec<T>, OperationError> {
+        let host_entry = match &ident.origin {
+            IdentType::User(u)
+                if u.entry
+                    .attribute_equality(Attribute::Class, &EntryClass::Host.into()) =>
+            {
+                &u.entry
+            }
+            _ => {
+                security_info!(
+                    ?class,
+                    "Only hosts may request the rules that apply to them"
+                );
+                return Err(OperationError::NotAuthorised);
+            }
+        };
+
+        let mut host_set: BTreeSet<Uuid> = host_entry
+            .get_ava_refer(Attribute::MemberOf)
+            .cloned()
+            .unwrap_or_default();
+        host_set.insert(host_entry.get_uuid());
+
+        let filter = filter!(f_and!([
+            f_eq(Attribute::Class, class.into()),
+            f_or(
+                host_set
+                    .iter()
+                    .map(|u| f_eq(host_attr, PartialValue::Refer(*u)))
+                    .collect()
+            )
+        ]));
+
+        self.list_unix_policy(filter, ident)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use kanidm_proto::v1::UnixAutomountEntry;
+
+    #[idm_test]
+    async fn test_idm_unix_policy_list(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let host_uuid = Uuid::new_v4();
+        let host_other_uuid = Uuid::new_v4();
+        let host_grp_uuid = Uuid::new_v4();
+        let user_grp_uuid = Uuid::new_v4();
+        let person_uuid = Uuid::new_v4();
+        let sudo_rule_uuid = Uuid::new_v4();
+        let map_uuid = Uuid::new_v4();
+
+        let e_host = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+            (Attribute::Class, EntryClass::Host.to_value()),
+            (Attribute::Uuid, Value::Uuid(host_uuid)),
+            (Attribute::Name, Value::new_iname("test_host")),
+            (Attribute::DisplayName, Value::new_utf8s("test_host"))
+        );
+
+        let e_host_other = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+            (Attribute::Class, EntryClass::Host.to_value()),
+            (Attribute::Uuid, Value::Uuid(host_other_uuid)),
+            (Attribute::Name, Value::new_iname("test_host_other")),
+            (Attribute::DisplayName, Value::new_utf8s("test_host_other"))
+        );
+
+        let e_host_grp = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Uuid, Value::Uuid(host_grp_uuid)),
+            (Attribute::Name, Value::new_iname("test_host_group")),
+            (Attribute::Member, Value::Refer(host_uuid))
+        );
+
+        let e_user_grp = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Uuid, Value::Uuid(user_grp_uuid)),
+            (Attribute::Name, Value::new_iname("test_user_group"))
+        );
+
+        let e_person = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Uuid, Value::Uuid(person_uuid)),
+            (Attribute::Name, Value::new_iname("test_person")),
+            (Attribute::DisplayName, Value::new_utf8s("test_person"))
+        );
+
+        // Applies through the host group. Created first to check the ordering.
+        let e_hbac_b = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::HbacRule.to_value()),
+            (Attribute::Name, Value::new_iname("test_hbac_rule_b")),
+            (Attribute::HbacUser, Value::Refer(user_grp_uuid)),
+            (Attribute::HbacHost, Value::Refer(host_grp_uuid)),
+            (Attribute::HbacService, Value::new_iutf8("sshd"))
+        );
+
+        // Names the host directly.
+        let e_hbac_a = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::HbacRule.to_value()),
+            (Attribute::Name, Value::new_iname("test_hbac_rule_a")),
+            (Attribute::HbacUser, Value::Refer(user_grp_uuid)),
+            (Attribute::HbacHost, Value::Refer(host_uuid)),
+            (Attribute::HbacService, Value::new_iutf8("login"))
+        );
+
+        let e_hbac_other = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::HbacRule.to_value()),
+            (Attribute::Name, Value::new_iname("test_hbac_rule_other")),
+            (Attribute::HbacUser, Value::Refer(user_grp_uuid)),
+            (Attribute::HbacHost, Value::Refer(host_other_uuid)),
+            (Attribute::HbacService, Value::new_iutf8("ALL"))
+        );
+
+        let e_sudo = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::SudoRule.to_value()),
+            (Attribute::Uuid, Value::Uuid(sudo_rule_uuid)),
+            (Attribute::Name, Value::new_iname("test_sudo_rule")),
+            (Attribute::SudoUser, Value::Refer(user_grp_uuid)),
+            (Attribute::SudoHost, Value::Refer(host_grp_uuid)),
+            (
+                Attribute::SudoCommand,
+                Value::new_utf8s("/usr/bin/systemctl")
+            ),
+            (Attribute::SudoRunas, Value::new_iutf8("root")),
+            (Attribute::SudoNopasswd, Value::new_bool(true))
+        );
+
+        let e_sudo_other = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::SudoRule.to_value()),
+            (Attribute::Name, Value::new_iname("test_sudo_rule_other")),
+            (Attribute::SudoUser, Value::Refer(user_grp_uuid)),
+            (Attribute::SudoHost, Value::Refer(host_other_uuid)),
+            (Attribute::SudoCommand, Value::new_utf8s("ALL"))
+        );
+
+        let e_map = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::AutomountMap.to_value()),
+            (Attribute::Uuid, Value::Uuid(map_uuid)),
+            (Attribute::Name, Value::new_iname("auto.home"))
+        );
+
+        let e_key_a = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Automount.to_value()),
+            (Attribute::AutomountMap, Value::Refer(map_uuid)),
+            (Attribute::AutomountKey, Value::new_utf8s("*")),
+            (
+                Attribute::AutomountInformation,
+                Value::new_utf8s("-rw nfs.example.com:/home/&")
+            )
+        );
+
+        let e_key_b = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Automount.to_value()),
+            (Attribute::AutomountMap, Value::Refer(map_uuid)),
+            (Attribute::AutomountKey, Value::new_utf8s("alice")),
+            (
+                Attribute::AutomountInformation,
+                Value::new_utf8s("-rw nfs2.example.com:/home/alice")
+            )
+        );
+
+        // A map without any keys is still listed.
+        let e_map_empty = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::AutomountMap.to_value()),
+            (Attribute::Name, Value::new_iname("auto.master"))
+        );
+
+        let ce = CreateEvent::new_internal(vec![
+            e_host,
+            e_host_other,
+            e_host_grp,
+            e_user_grp,
+            e_person,
+            e_hbac_b,
+            e_hbac_a,
+            e_hbac_other,
+            e_sudo,
+            e_sudo_other,
+            e_map,
+            e_key_b,
+            e_key_a,
+            e_map_empty,
+        ]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+
+        let ident = idms_prox_read
+            .qs_read
+            .internal_search_uuid(host_uuid)
+            .map(Identity::from_impersonate_entry_readonly)
+            .expect("Failed to impersonate identity");
+
+        let rules = idms_prox_read
+            .list_unix_hbac_rules(&ident)
+            .expect("Failed to list hbac rules");
+
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].name, "test_hbac_rule_a");
+        assert_eq!(rules[0].services, vec!["login".to_string()]);
+        assert_eq!(rules[1].name, "test_hbac_rule_b");
+        assert_eq!(rules[1].users, vec![user_grp_uuid]);
+        assert_eq!(rules[1].services, vec!["sshd".to_string()]);
+
+        let rules = idms_prox_read
+            .list_unix_sudo_rules(&ident)
+            .expect("Failed to list sudo rules");
+
+        assert_eq!(rules.len(), 1);
+        let rule = &rules[0];
+        assert_eq!(rule.name, "test_sudo_rule");
+        assert_eq!(rule.uuid, sudo_rule_uuid);
+        assert_eq!(rule.users, vec![user_grp_uuid]);
+        assert_eq!(rule.commands, vec!["/usr/bin/systemctl".to_string()]);
+        assert_eq!(rule.runas, vec!["root".to_string()]);
+        assert!(rule.nopasswd);
+
+        // The other host only receives the rules that name it.
+        let ident = idms_prox_read
+            .qs_read
+            .internal_search_uuid(host_other_uuid)
+            .map(Identity::from_impersonate_entry_readonly)
+            .expect("Failed to impersonate identity");
+
+        let rules = idms_prox_read
+            .list_unix_hbac_rules(&ident)
+            .expect("Failed to list hbac rules");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].name, "test_hbac_rule_other");
+
+        let rules = idms_prox_read
+            .list_unix_sudo_rules(&ident)
+            .expect("Failed to list sudo rules");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].name, "test_sudo_rule_other");
+
+        // Accounts that are not hosts can not request rules.
+        for uuid in [UUID_ANONYMOUS, person_uuid] {
+            let ident = idms_prox_read
+                .qs_read
+                .internal_search_uuid(uuid)
+                .map(Identity::from_impersonate_entry_readonly)
+                .expect("Failed to impersonate identity");
+
+            // Automount maps are not host specific, so any account may read them.
+            let maps = idms_prox_read
+                .list_unix_automount_maps(&ident)
+                .expect("Failed to list automount maps");
+
+            assert_eq!(maps.len(), 2);
+            assert_eq!(maps[0].name, "auto.home");
+            assert_eq!(maps[0].uuid, map_uuid);
+            assert_eq!(
+                maps[0].entries,
+                vec![
+                    UnixAutomountEntry {
+                        key: "*".to_string(),
+                        information: "-rw nfs.example.com:/home/&".to_string(),
+                    },
+                    UnixAutomountEntry {
+                        key: "alice".to_string(),
+                        information: "-rw nfs2.example.com:/home/alice".to_string(),
+                    },
+                ]
+            );
+            assert_eq!(maps[1].name, "auto.master");
+            assert!(maps[1].entries.is_empty());
+
+            assert_eq!(
+                idms_prox_read.list_unix_hbac_rules(&ident),
+                Err(OperationError::NotAuthorised)
+            );
+            assert_eq!(
+                idms_prox_read.list_unix_sudo_rules(&ident),
+                Err(OperationError::NotAuthorised)
+            );
+
+            // Nor can they read the rules directly, as only hosts may search them.
+            let filter = filter!(f_or!([
+                f_eq(Attribute::Class, EntryClass::HbacRule.into()),
+                f_eq(Attribute::Class, EntryClass::SudoRule.into())
+            ]));
+            let entries = idms_prox_read
+                .qs_read
+                .impersonate_search_ext(filter.clone(), filter, &ident)
+                .expect("Failed to search");
+            assert!(entries.is_empty());
+        }
+    }
+}
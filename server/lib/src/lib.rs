@@ -53,6 +53,7 @@ pub mod valueset;
 #[macro_use]
 mod plugins;
 pub mod idm;
+pub mod metrics;
 pub mod repl;
 pub mod schema;
 pub mod server;
@@ -85,8 +86,8 @@ pub mod prelude {
     };
     pub use crate::event::{CreateEvent, DeleteEvent, ExistsEvent, ModifyEvent, SearchEvent};
     pub use crate::filter::{
-        f_and, f_andnot, f_eq, f_id, f_inc, f_lt, f_or, f_pres, f_self, f_spn_name, f_sub, Filter,
-        FilterInvalid, FilterValid, FC,
+        f_and, f_andnot, f_eq, f_eq_rule, f_gt, f_id, f_inc, f_lt, f_or, f_pres, f_self,
+        f_spn_name, f_sub, f_sub_pattern, Filter, FilterInvalid, FilterValid, FC,
     };
     pub use crate::idm::server::{IdmServer, IdmServerAudit, IdmServerDelayed};
     pub use crate::modify::{
@@ -104,7 +105,8 @@ pub mod prelude {
     };
     pub use crate::time::duration_from_epoch_now;
     pub use crate::value::{
-        ApiTokenScope, IndexType, PartialValue, SessionScope, SyntaxType, Value,
+        ApiTokenScope, IndexType, MatchingRule, PartialValue, SessionScope, SubStringPattern,
+        SyntaxType, Value,
     };
     pub use crate::valueset::{
         ValueSet, ValueSetBool, ValueSetCid, ValueSetIndex, ValueSetIutf8, ValueSetRefer,
@@ -0,0 +1,380 @@
+//! Counters and gauges describing the internal state of the server. These are collected
+//! at all times as they are cheap to update, and are rendered in the OpenMetrics text
+//! format when the server has a metrics endpoint configured.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// The metrics of this server process.
+pub static METRICS: Metrics = Metrics::new();
+
+/// The upper bounds in seconds of the write transaction latency histogram buckets.
+const WRITE_TXN_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The in memory caches of the backend that report hits and misses.
+#[derive(Debug, Clone, Copy)]
+pub enum CacheKind {
+    Entry,
+    Idl,
+    Name,
+}
+
+/// A counter that is partitioned by a set of label values.
+struct LabelledCounter {
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl LabelledCounter {
+    const fn new() -> Self {
+        LabelledCounter {
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn inc(&self, labels: &[&str]) {
+        let mut guard = self.values.lock().unwrap_or_else(PoisonError::into_inner);
+        let key: Vec<String> = labels.iter().map(|l| l.to_string()).collect();
+        *guard.entry(key).or_insert(0) += 1;
+    }
+
+    fn snapshot(&self) -> BTreeMap<Vec<String>, u64> {
+        self.values
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+struct CacheCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounter {
+    const fn new() -> Self {
+        CacheCounter {
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+struct Histogram {
+    // Each bucket counts only the observations that fell within it, the buckets are made
+    // cumulative as they are rendered. The final bucket is +Inf.
+    buckets: [AtomicU64; WRITE_TXN_BUCKETS.len() + 1],
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicU64 = AtomicU64::new(0);
+        Histogram {
+            buckets: [ZERO; WRITE_TXN_BUCKETS.len() + 1],
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, d: Duration) {
+        let secs = d.as_secs_f64();
+        let idx = WRITE_TXN_BUCKETS
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or(WRITE_TXN_BUCKETS.len());
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(d.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+pub struct Metrics {
+    auth_attempts: LabelledCounter,
+    softlock_engagements: AtomicU64,
+    entry_cache: CacheCounter,
+    idl_cache: CacheCounter,
+    name_cache: CacheCounter,
+    write_txn_duration: Histogram,
+    ldap_operations: LabelledCounter,
+    oauth2_tokens_issued: LabelledCounter,
+    delayed_action_queue: AtomicI64,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Metrics {
+            auth_attempts: LabelledCounter::new(),
+            softlock_engagements: AtomicU64::new(0),
+            entry_cache: CacheCounter::new(),
+            idl_cache: CacheCounter::new(),
+            name_cache: CacheCounter::new(),
+            write_txn_duration: Histogram::new(),
+            ldap_operations: LabelledCounter::new(),
+            oauth2_tokens_issued: LabelledCounter::new(),
+            delayed_action_queue: AtomicI64::new(0),
+        }
+    }
+
+    /// An authentication session reached a final outcome.
+    pub fn auth_attempt(&self, auth_type: &str, success: bool) {
+        let result = if success { "success" } else { "denied" };
+        self.auth_attempts.inc(&[auth_type, result]);
+    }
+
+    /// A credential failure caused a softlock to be applied.
+    pub fn softlock_engaged(&self) {
+        self.softlock_engagements.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_lookup(&self, cache: CacheKind, hits: u64, misses: u64) {
+        let counter = match cache {
+            CacheKind::Entry => &self.entry_cache,
+            CacheKind::Idl => &self.idl_cache,
+            CacheKind::Name => &self.name_cache,
+        };
+        if hits > 0 {
+            counter.hits.fetch_add(hits, Ordering::Relaxed);
+        }
+        if misses > 0 {
+            counter.misses.fetch_add(misses, Ordering::Relaxed);
+        }
+    }
+
+    /// Record the time from the start of a write transaction to its commit.
+    pub fn write_txn_committed(&self, d: Duration) {
+        self.write_txn_duration.observe(d);
+    }
+
+    pub fn ldap_operation(&self, op: &str) {
+        self.ldap_operations.inc(&[op]);
+    }
+
+    pub fn oauth2_token_issued(&self, rs_name: &str, grant_type: &str) {
+        self.oauth2_tokens_issued.inc(&[rs_name, grant_type]);
+    }
+
+    pub fn delayed_action_queued(&self) {
+        self.delayed_action_queue.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn delayed_action_dequeued(&self) {
+        self.delayed_action_queue.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Write all metrics of this process as OpenMetrics families.
+    pub fn render(&self, w: &mut OpenMetricsWriter) {
+        w.family(
+            "kanidm_auth_attempts",
+            "counter",
+            "Authentication attempts by credential type and outcome.",
+        );
+        for (labels, v) in self.auth_attempts.snapshot() {
+            if let [auth_type, result] = labels.as_slice() {
+                w.sample(
+                    "kanidm_auth_attempts_total",
+                    &[("type", auth_type), ("result", result)],
+                    v,
+                );
+            }
+        }
+
+        w.family(
+            "kanidm_softlock_engagements",
+            "counter",
+            "Credential failures that caused a softlock to be applied.",
+        );
+        w.sample(
+            "kanidm_softlock_engagements_total",
+            &[],
+            self.softlock_engagements.load(Ordering::Relaxed),
+        );
+
+        w.family(
+            "kanidm_cache_lookups",
+            "counter",
+            "Backend cache lookups by cache and result.",
+        );
+        for (name, counter) in [
+            ("entry", &self.entry_cache),
+            ("idl", &self.idl_cache),
+            ("name", &self.name_cache),
+        ] {
+            w.sample(
+                "kanidm_cache_lookups_total",
+                &[("cache", name), ("result", "hit")],
+                counter.hits.load(Ordering::Relaxed),
+            );
+            w.sample(
+                "kanidm_cache_lookups_total",
+                &[("cache", name), ("result", "miss")],
+                counter.misses.load(Ordering::Relaxed),
+            );
+        }
+
+        w.family(
+            "kanidm_write_transaction_seconds",
+            "histogram",
+            "Time from the start of a write transaction to its commit.",
+        );
+        let mut cumulative = 0;
+        for (idx, bucket) in self.write_txn_duration.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let le = WRITE_TXN_BUCKETS
+                .get(idx)
+                .map(|bound| bound.to_string())
+                .unwrap_or_else(|| "+Inf".to_string());
+            w.sample(
+                "kanidm_write_transaction_seconds_bucket",
+                &[("le", &le)],
+                cumulative,
+            );
+        }
+        w.sample(
+            "kanidm_write_transaction_seconds_sum",
+            &[],
+            self.write_txn_duration.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+        );
+        w.sample("kanidm_write_transaction_seconds_count", &[], cumulative);
+
+        w.family(
+            "kanidm_ldap_operations",
+            "counter",
+            "LDAP operations by type.",
+        );
+        for (labels, v) in self.ldap_operations.snapshot() {
+            if let [op] = labels.as_slice() {
+                w.sample("kanidm_ldap_operations_total", &[("op", op)], v);
+            }
+        }
+
+        w.family(
+            "kanidm_oauth2_tokens_issued",
+            "counter",
+            "OAuth2 access tokens issued by resource server and grant type.",
+        );
+        for (labels, v) in self.oauth2_tokens_issued.snapshot() {
+            if let [rs, grant_type] = labels.as_slice() {
+                w.sample(
+                    "kanidm_oauth2_tokens_issued_total",
+                    &[("rs", rs), ("grant_type", grant_type)],
+                    v,
+                );
+            }
+        }
+
+        w.family(
+            "kanidm_delayed_action_queue_depth",
+            "gauge",
+            "Delayed actions waiting to be processed.",
+        );
+        w.sample(
+            "kanidm_delayed_action_queue_depth",
+            &[],
+            self.delayed_action_queue.load(Ordering::Relaxed).max(0),
+        );
+    }
+}
+
+/// Builds a document in the OpenMetrics text format.
+#[derive(Default)]
+pub struct OpenMetricsWriter {
+    buf: String,
+}
+
+impl OpenMetricsWriter {
+    pub const CONTENT_TYPE: &'static str =
+        "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new metric family. All samples that follow belong to this family.
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.buf, "# TYPE {} {}", name, kind);
+        let _ = writeln!(self.buf, "# HELP {} {}", name, help);
+    }
+
+    pub fn sample<V: Display>(&mut self, name: &str, labels: &[(&str, &str)], value: V) {
+        self.buf.push_str(name);
+        if !labels.is_empty() {
+            self.buf.push('{');
+            for (idx, (label, lvalue)) in labels.iter().enumerate() {
+                if idx > 0 {
+                    self.buf.push(',');
+                }
+                self.buf.push_str(label);
+                self.buf.push_str("=\"");
+                for c in lvalue.chars() {
+                    match c {
+                        '\\' => self.buf.push_str("\\\\"),
+                        '"' => self.buf.push_str("\\\""),
+                        '\n' => self.buf.push_str("\\n"),
+                        c => self.buf.push(c),
+                    }
+                }
+                self.buf.push('"');
+            }
+            self.buf.push('}');
+        }
+        let _ = writeln!(self.buf, " {}", value);
+    }
+
+    pub fn finish(mut self) -> String {
+        self.buf.push_str("# EOF\n");
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheKind, Metrics, OpenMetricsWriter};
+    use std::time::Duration;
+
+    #[test]
+    fn test_metrics_render() {
+        let metrics = Metrics::new();
+        metrics.auth_attempt("password", true);
+        metrics.auth_attempt("password", false);
+        metrics.auth_attempt("password", false);
+        metrics.softlock_engaged();
+        metrics.cache_lookup(CacheKind::Entry, 3, 1);
+        metrics.write_txn_committed(Duration::from_millis(3));
+        metrics.write_txn_committed(Duration::from_secs(20));
+        metrics.ldap_operation("search");
+        metrics.oauth2_token_issued("test \"rs\"", "authorization_code");
+        metrics.delayed_action_queued();
+        metrics.delayed_action_queued();
+        metrics.delayed_action_dequeued();
+
+        let mut w = OpenMetricsWriter::new();
+        metrics.render(&mut w);
+        let doc = w.finish();
+
+        for line in [
+            "# TYPE kanidm_auth_attempts counter",
+            "kanidm_auth_attempts_total{type=\"password\",result=\"denied\"} 2",
+            "kanidm_auth_attempts_total{type=\"password\",result=\"success\"} 1",
+            "kanidm_softlock_engagements_total 1",
+            "kanidm_cache_lookups_total{cache=\"entry\",result=\"hit\"} 3",
+            "kanidm_cache_lookups_total{cache=\"entry\",result=\"miss\"} 1",
+            "kanidm_write_transaction_seconds_bucket{le=\"0.001\"} 0",
+            "kanidm_write_transaction_seconds_bucket{le=\"0.005\"} 1",
+            "kanidm_write_transaction_seconds_bucket{le=\"10\"} 1",
+            "kanidm_write_transaction_seconds_bucket{le=\"+Inf\"} 2",
+            "kanidm_write_transaction_seconds_sum 20.003",
+            "kanidm_write_transaction_seconds_count 2",
+            "kanidm_ldap_operations_total{op=\"search\"} 1",
+            "kanidm_oauth2_tokens_issued_total{rs=\"test \\\"rs\\\"\",grant_type=\"authorization_code\"} 1",
+            "kanidm_delayed_action_queue_depth 1",
+        ] {
+            assert!(doc.lines().any(|l| l == line), "missing {}", line);
+        }
+        assert!(doc.ends_with("# EOF\n"));
+    }
+}
@@ -1,6 +1,7 @@
 // A plugin that generates gid numbers on types that require them for posix
-// support.
+// support, and allocates subordinate id ranges to posix accounts.
 
+use std::collections::BTreeSet;
 use std::iter::once;
 use std::sync::Arc;
 
@@ -17,6 +18,12 @@ const GID_SYSTEM_NUMBER_MIN: u32 = 65536;
 /// This is the normal system range, we MUST NOT allow it to be allocated.
 const GID_SAFETY_NUMBER_MIN: u32 = 1000;
 
+/// Subordinate id ranges are allocated in blocks of SUBID_RANGE_SIZE between these
+/// bounds. Ids above 2^31 are avoided as some software mishandles them as negative.
+const SUBID_RANGE_MIN: u32 = 0x1000_0000;
+const SUBID_RANGE_MAX: u32 = 0x8000_0000;
+const SUBID_RANGE_BLOCKS: u32 = (SUBID_RANGE_MAX - SUBID_RANGE_MIN) / SUBID_RANGE_SIZE;
+
 pub struct GidNumber {}
 
 fn apply_gidnumber<T: Clone>(e: &mut Entry<EntryInvalid, T>) -> Result<(), OperationError> {
@@ -64,6 +71,217 @@ fn apply_gidnumber<T: Clone>(e: &mut Entry<EntryInvalid, T>) -> Result<(), Opera
     }
 }
 
+/// The start of the subordinate id block that contains this id, if any.
+fn subid_block_of(id: u32) -> Option<u32> {
+    if (SUBID_RANGE_MIN..SUBID_RANGE_MAX).contains(&id) {
+        Some(id - ((id - SUBID_RANGE_MIN) % SUBID_RANGE_SIZE))
+    } else {
+        None
+    }
+}
+
+/// A gid must never lie within another account's subordinate id range, else that
+/// account could act as the owner of the gid from within a user namespace.
+fn check_gid_subid_overlap<T: Clone>(
+    qs: &mut QueryServerWriteTransaction,
+    e: &Entry<EntryInvalid, T>,
+) -> Result<(), OperationError> {
+    let Some(gid) = e.get_ava_single_uint32(Attribute::GidNumber) else {
+        return Ok(());
+    };
+    let Some(block) = subid_block_of(gid) else {
+        return Ok(());
+    };
+    let u_ref = e.get_uuid().ok_or(OperationError::InvalidEntryState)?;
+
+    let overlaps = qs.internal_exists(filter_all!(f_and!([
+        f_eq(Attribute::SubidRangeStart, PartialValue::new_uint32(block)),
+        f_andnot(f_eq(Attribute::Uuid, PartialValue::Uuid(u_ref)))
+    ])))?;
+
+    if overlaps {
+        Err(OperationError::InvalidAttribute(format!(
+            "{} {} overlaps with the subordinate id range {}",
+            Attribute::GidNumber,
+            gid,
+            block
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn apply_subid_range<T: Clone>(
+    qs: &mut QueryServerWriteTransaction,
+    pre_cand: Option<&[Arc<EntrySealedCommitted>]>,
+    cand: &mut [Entry<EntryInvalid, T>],
+) -> Result<(), OperationError> {
+    // Only ranges that are being set or removed are checked, and only posix accounts without
+    // a range are allocated one. This is the case for new posix accounts, and for accounts
+    // that existed before ranges were allocated once they are touched by the migration.
+    let changed: Vec<usize> = cand
+        .iter()
+        .enumerate()
+        .filter(|(i, e)| {
+            let start = e.get_ava_single_uint32(Attribute::SubidRangeStart);
+            let needs_range = start.is_none()
+                && e.attribute_equality(Attribute::Class, &EntryClass::PosixAccount.into());
+            match pre_cand.and_then(|pre_cand| pre_cand.get(*i)) {
+                Some(pre) => {
+                    needs_range || start != pre.get_ava_single_uint32(Attribute::SubidRangeStart)
+                }
+                None => needs_range || start.is_some(),
+            }
+        })
+        .map(|(i, _)| i)
+        .collect();
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let cand_uuids = cand
+        .iter()
+        .map(|e| e.get_uuid().ok_or(OperationError::InvalidEntryState))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+
+    // A range that is set manually must be aligned to a block, and must not contain the
+    // gid of another account or group.
+    for e in changed.iter().map(|i| &cand[*i]) {
+        let Some(start) = e.get_ava_single_uint32(Attribute::SubidRangeStart) else {
+            continue;
+        };
+
+        if subid_block_of(start) != Some(start) {
+            return Err(OperationError::InvalidAttribute(format!(
+                "{} {} is not the start of a subordinate id range",
+                Attribute::SubidRangeStart,
+                start
+            )));
+        }
+
+        let u_ref = e.get_uuid().ok_or(OperationError::InvalidEntryState)?;
+
+        // Candidates are taken as they will be after this change, rather than as committed.
+        let cand_contains_gid = cand.iter().any(|c| {
+            c.get_uuid() != Some(u_ref)
+                && c.get_ava_single_uint32(Attribute::GidNumber)
+                    .and_then(subid_block_of)
+                    == Some(start)
+        });
+
+        let contains_gid = cand_contains_gid
+            || qs.internal_exists(filter_all!(f_and!([
+                f_gt(Attribute::GidNumber, PartialValue::new_uint32(start - 1)),
+                f_lt(
+                    Attribute::GidNumber,
+                    PartialValue::new_uint32(start + SUBID_RANGE_SIZE)
+                ),
+                f_andnot(f_or(
+                    cand_uuids
+                        .iter()
+                        .map(|u| f_eq(Attribute::Uuid, PartialValue::Uuid(*u)))
+                        .collect()
+                ))
+            ])))?;
+
+        if contains_gid {
+            return Err(OperationError::InvalidAttribute(format!(
+                "{} {} contains the {} of another entry",
+                Attribute::SubidRangeStart,
+                start,
+                Attribute::GidNumber
+            )));
+        }
+    }
+
+    let allocate: Vec<usize> = changed
+        .into_iter()
+        .filter(|i| {
+            let e = &cand[*i];
+            e.attribute_equality(Attribute::Class, &EntryClass::PosixAccount.into())
+                && !e.attribute_pres(Attribute::SubidRangeStart)
+        })
+        .collect();
+
+    if allocate.is_empty() {
+        return Ok(());
+    }
+
+    // A block is in use if it is allocated to any entry, including recycled entries that
+    // may be revived, or if the gid of any account or group lies within it.
+    let committed = qs.internal_search(filter_all!(f_or!([
+        f_pres(Attribute::SubidRangeStart),
+        f_and!([
+            f_gt(
+                Attribute::GidNumber,
+                PartialValue::new_uint32(SUBID_RANGE_MIN - 1)
+            ),
+            f_lt(
+                Attribute::GidNumber,
+                PartialValue::new_uint32(SUBID_RANGE_MAX)
+            )
+        ])
+    ])))?;
+
+    // Candidates are taken as they will be after this change, rather than as committed.
+    let mut claimed: BTreeSet<u32> = committed
+        .iter()
+        .filter(|e| !cand_uuids.contains(&e.get_uuid()))
+        .flat_map(|e| {
+            [
+                e.get_ava_single_uint32(Attribute::SubidRangeStart),
+                e.get_ava_single_uint32(Attribute::GidNumber)
+                    .and_then(subid_block_of),
+            ]
+        })
+        .chain(cand.iter().flat_map(|e| {
+            [
+                e.get_ava_single_uint32(Attribute::SubidRangeStart),
+                e.get_ava_single_uint32(Attribute::GidNumber)
+                    .and_then(subid_block_of),
+            ]
+        }))
+        .flatten()
+        .collect();
+
+    for e in allocate.into_iter().map(|i| &mut cand[i]) {
+        let u_ref = e
+            .get_uuid()
+            .ok_or(OperationError::InvalidEntryState)
+            .map_err(|e| {
+                admin_error!("Invalid Entry State - Missing UUID");
+                e
+            })?;
+
+        // Like gids, start from a block derived from the uuid so that replicas are unlikely
+        // to choose the same block concurrently. Probe forward from there for a free block.
+        let first = uuid_to_gid_u32(u_ref) % SUBID_RANGE_BLOCKS;
+        let allocated = (0..SUBID_RANGE_BLOCKS)
+            .map(|i| SUBID_RANGE_MIN + ((first + i) % SUBID_RANGE_BLOCKS) * SUBID_RANGE_SIZE)
+            .find(|start| !claimed.contains(start));
+
+        let Some(start) = allocated else {
+            admin_error!("No free subordinate id ranges remain for {:?}", u_ref);
+            return Err(OperationError::InvalidAttribute(format!(
+                "{} no free ranges remain",
+                Attribute::SubidRangeStart
+            )));
+        };
+
+        admin_info!(
+            "Generated {} {} for {:?}",
+            Attribute::SubidRangeStart,
+            start,
+            u_ref
+        );
+        claimed.insert(start);
+        e.set_ava(Attribute::SubidRangeStart, once(Value::new_uint32(start)));
+    }
+
+    Ok(())
+}
+
 impl Plugin for GidNumber {
     fn id() -> &'static str {
         "plugin_gidnumber"
@@ -71,31 +289,40 @@ impl Plugin for GidNumber {
 
     #[instrument(level = "debug", name = "gidnumber_pre_create_transform", skip_all)]
     fn pre_create_transform(
-        _qs: &mut QueryServerWriteTransaction,
+        qs: &mut QueryServerWriteTransaction,
         cand: &mut Vec<Entry<EntryInvalid, EntryNew>>,
         _ce: &CreateEvent,
     ) -> Result<(), OperationError> {
-        cand.iter_mut().try_for_each(apply_gidnumber)
+        cand.iter_mut().try_for_each(apply_gidnumber)?;
+        cand.iter()
+            .try_for_each(|e| check_gid_subid_overlap(qs, e))?;
+        apply_subid_range(qs, None, cand)
     }
 
     #[instrument(level = "debug", name = "gidnumber_pre_modify", skip_all)]
     fn pre_modify(
-        _qs: &mut QueryServerWriteTransaction,
-        _pre_cand: &[Arc<EntrySealedCommitted>],
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
         cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
         _me: &ModifyEvent,
     ) -> Result<(), OperationError> {
-        cand.iter_mut().try_for_each(apply_gidnumber)
+        cand.iter_mut().try_for_each(apply_gidnumber)?;
+        cand.iter()
+            .try_for_each(|e| check_gid_subid_overlap(qs, e))?;
+        apply_subid_range(qs, Some(pre_cand), cand)
     }
 
     #[instrument(level = "debug", name = "gidnumber_pre_batch_modify", skip_all)]
     fn pre_batch_modify(
-        _qs: &mut QueryServerWriteTransaction,
-        _pre_cand: &[Arc<EntrySealedCommitted>],
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
         cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
         _me: &BatchModifyEvent,
     ) -> Result<(), OperationError> {
-        cand.iter_mut().try_for_each(apply_gidnumber)
+        cand.iter_mut().try_for_each(apply_gidnumber)?;
+        cand.iter()
+            .try_for_each(|e| check_gid_subid_overlap(qs, e))?;
+        apply_subid_range(qs, Some(pre_cand), cand)
     }
 }
 
@@ -111,6 +338,15 @@ mod tests {
         assert!(ex_gid == gidnumber);
     }
 
+    fn check_subid_range(qs_write: &mut QueryServerWriteTransaction, uuid: &str, start: u32) {
+        let u = Uuid::parse_str(uuid).unwrap();
+        let e = qs_write.internal_search_uuid(u).unwrap();
+        assert_eq!(
+            e.get_ava_single_uint32(Attribute::SubidRangeStart),
+            Some(start)
+        );
+    }
+
     #[test]
     fn test_gidnumber_create_generate() {
         let e = entry_init!(
@@ -349,4 +585,220 @@ mod tests {
             |_| {}
         );
     }
+
+    #[test]
+    fn test_subid_range_create_generate() {
+        let e = entry_init!(
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::PosixAccount.to_value()),
+            (Attribute::Name, Value::new_iname("testperson")),
+            (
+                Attribute::Uuid,
+                Value::Uuid(uuid!("83a0927f-3de1-45ec-bea0-2f7b997ef244"))
+            ),
+            (Attribute::Description, Value::new_utf8s("testperson")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson"))
+        );
+
+        let create = vec![e];
+        let preload = Vec::new();
+
+        run_create_test!(
+            Ok(()),
+            preload,
+            create,
+            None,
+            |qs_write: &mut QueryServerWriteTransaction| check_subid_range(
+                qs_write,
+                "83a0927f-3de1-45ec-bea0-2f7b997ef244",
+                0x1244_0000
+            )
+        );
+    }
+
+    // Test that ranges in use, or that contain a gid, are skipped.
+    #[test]
+    fn test_subid_range_create_skip_used() {
+        let e_group = entry_init!(
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::PosixGroup.to_value()),
+            (Attribute::Name, Value::new_iname("testgroup")),
+            (Attribute::GidNumber, Value::Uint32(0x1244_0001)),
+            (Attribute::Description, Value::new_utf8s("testgroup"))
+        );
+
+        let e_a = entry_init!(
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::PosixAccount.to_value()),
+            (Attribute::Name, Value::new_iname("testperson_a")),
+            (Attribute::GidNumber, Value::Uint32(10001)),
+            (
+                Attribute::Uuid,
+                Value::Uuid(uuid!("83a0927f-3de1-45ec-bea0-3f7b997ef244"))
+            ),
+            (Attribute::Description, Value::new_utf8s("testperson_a")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson_a"))
+        );
+
+        let e_b = entry_init!(
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::PosixAccount.to_value()),
+            (Attribute::Name, Value::new_iname("testperson_b")),
+            (
+                Attribute::Uuid,
+                Value::Uuid(uuid!("83a0927f-3de1-45ec-bea0-2f7b997ef244"))
+            ),
+            (Attribute::Description, Value::new_utf8s("testperson_b")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson_b"))
+        );
+
+        let preload = vec![e_group, e_a];
+        let create = vec![e_b];
+
+        run_create_test!(
+            Ok(()),
+            preload,
+            create,
+            None,
+            |qs_write: &mut QueryServerWriteTransaction| {
+                check_subid_range(
+                    qs_write,
+                    "83a0927f-3de1-45ec-bea0-3f7b997ef244",
+                    0x1245_0000,
+                );
+                check_subid_range(
+                    qs_write,
+                    "83a0927f-3de1-45ec-bea0-2f7b997ef244",
+                    0x1246_0000,
+                );
+            }
+        );
+    }
+
+    // Test a range is allocated on mod, which is how existing accounts are upgraded.
+    #[test]
+    fn test_subid_range_modify_regenerate() {
+        let e = entry_init!(
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::PosixAccount.to_value()),
+            (Attribute::Name, Value::new_iname("testperson")),
+            (
+                Attribute::Uuid,
+                Value::Uuid(uuid!("83a0927f-3de1-45ec-bea0-2f7b997ef244"))
+            ),
+            (Attribute::Description, Value::new_utf8s("testperson")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson"))
+        );
+
+        let preload = vec![e];
+
+        run_modify_test!(
+            Ok(()),
+            preload,
+            filter!(f_eq(Attribute::Name, PartialValue::new_iname("testperson"))),
+            modlist!([m_purge(Attribute::SubidRangeStart)]),
+            None,
+            |_| {},
+            |qs_write: &mut QueryServerWriteTransaction| check_subid_range(
+                qs_write,
+                "83a0927f-3de1-45ec-bea0-2f7b997ef244",
+                0x1244_0000
+            )
+        );
+    }
+
+    #[test]
+    fn test_subid_range_create_unaligned_reject() {
+        let e = entry_init!(
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::PosixAccount.to_value()),
+            (Attribute::Name, Value::new_iname("testperson")),
+            (Attribute::SubidRangeStart, Value::Uint32(0x1244_0001)),
+            (Attribute::Description, Value::new_utf8s("testperson")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson"))
+        );
+
+        let create = vec![e];
+        let preload = Vec::new();
+
+        run_create_test!(
+            Err(OperationError::InvalidAttribute(
+                "subid_range_start 306446337 is not the start of a subordinate id range"
+                    .to_string()
+            )),
+            preload,
+            create,
+            None,
+            |_| {}
+        );
+    }
+
+    #[test]
+    fn test_subid_range_create_gid_contained_reject() {
+        let e_group = entry_init!(
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::PosixGroup.to_value()),
+            (Attribute::Name, Value::new_iname("testgroup")),
+            (Attribute::GidNumber, Value::Uint32(0x1244_0010)),
+            (Attribute::Description, Value::new_utf8s("testgroup"))
+        );
+
+        let e_account = entry_init!(
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::PosixAccount.to_value()),
+            (Attribute::Name, Value::new_iname("testperson")),
+            (Attribute::SubidRangeStart, Value::Uint32(0x1244_0000)),
+            (Attribute::Description, Value::new_utf8s("testperson")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson"))
+        );
+
+        let preload = vec![e_group];
+        let create = vec![e_account];
+
+        run_create_test!(
+            Err(OperationError::InvalidAttribute(
+                "subid_range_start 306446336 contains the gidnumber of another entry".to_string()
+            )),
+            preload,
+            create,
+            None,
+            |_| {}
+        );
+    }
+
+    #[test]
+    fn test_subid_range_gid_overlap_reject() {
+        let e_account = entry_init!(
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::PosixAccount.to_value()),
+            (Attribute::Name, Value::new_iname("testperson")),
+            (
+                Attribute::Uuid,
+                Value::Uuid(uuid!("83a0927f-3de1-45ec-bea0-2f7b997ef244"))
+            ),
+            (Attribute::Description, Value::new_utf8s("testperson")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson"))
+        );
+
+        let e_group = entry_init!(
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::PosixGroup.to_value()),
+            (Attribute::Name, Value::new_iname("testgroup")),
+            (Attribute::GidNumber, Value::Uint32(0x1244_0010)),
+            (Attribute::Description, Value::new_utf8s("testgroup"))
+        );
+
+        let preload = vec![e_account];
+        let create = vec![e_group];
+
+        run_create_test!(
+            Err(OperationError::InvalidAttribute(
+                "gidnumber 306446352 overlaps with the subordinate id range 306446336".to_string()
+            )),
+            preload,
+            create,
+            None,
+            |_| {}
+        );
+    }
 }
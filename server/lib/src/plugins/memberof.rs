@@ -21,9 +21,35 @@ use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
 use crate::plugins::Plugin;
 use crate::prelude::*;
 use crate::value::PartialValue;
+use time::OffsetDateTime;
 
 pub struct MemberOf;
 
+/// The direct members of a group, including members with an expiring membership. This is
+/// used to find the entries that are affected by a change to the group, so it includes
+/// memberships that have expired but are not yet purged.
+fn group_members<VALID, STATE>(e: &Entry<VALID, STATE>) -> impl Iterator<Item = Uuid> + '_ {
+    e.get_ava_as_refuuid(Attribute::Member)
+        .into_iter()
+        .flatten()
+        .chain(
+            e.get_ava_as_refuuid(Attribute::MemberExpiring)
+                .into_iter()
+                .flatten(),
+        )
+}
+
+/// If `uuid` is a direct member of the group `e` at `now`. An expiring membership no longer
+/// grants membership once it has expired, even before it is purged.
+fn group_has_member(e: &EntrySealedCommitted, uuid: Uuid, now: OffsetDateTime) -> bool {
+    e.attribute_equality(Attribute::Member, &PartialValue::Refer(uuid))
+        || e.attribute_equality(Attribute::DynMember, &PartialValue::Refer(uuid))
+        || e.get_ava_set(Attribute::MemberExpiring)
+            .and_then(|vs| vs.as_refer_expiring_map())
+            .and_then(|member_map| member_map.get(&uuid))
+            .map_or(false, |expiry| *expiry > now)
+}
+
 fn do_memberof(
     qs: &mut QueryServerWriteTransaction,
     uuid: Uuid,
@@ -35,6 +61,7 @@ fn do_memberof(
             f_eq(Attribute::Class, EntryClass::Group.into()),
             f_or!([
                 f_eq(Attribute::Member, PartialValue::Refer(uuid)),
+                f_eq(Attribute::MemberExpiring, PartialValue::Refer(uuid)),
                 f_eq(Attribute::DynMember, PartialValue::Refer(uuid))
             ])
         ])))
@@ -43,6 +70,12 @@ fn do_memberof(
             e
         })?;
 
+    let now = OffsetDateTime::UNIX_EPOCH + qs.get_curtime();
+    let groups: Vec<_> = groups
+        .into_iter()
+        .filter(|g| group_has_member(g, uuid, now))
+        .collect();
+
     // Ensure we are MO capable. We only add this if it's not already present.
     tgte.add_ava_if_not_exist(Attribute::Class, EntryClass::MemberOf.into());
     // Clear the dmo + mos, we will recreate them now.
@@ -158,9 +191,7 @@ fn apply_memberof(
                     "{:?} changed, flagging members as groups to change. ",
                     guuid
                 );
-                if let Some(miter) = tgte.get_ava_as_refuuid(Attribute::Member) {
-                    group_affect.extend(miter.filter(|m| !other_cache.contains_key(m)));
-                };
+                group_affect.extend(group_members(&tgte).filter(|m| !other_cache.contains_key(m)));
                 if let Some(miter) = tgte.get_ava_as_refuuid(Attribute::DynMember) {
                     group_affect.extend(miter.filter(|m| !other_cache.contains_key(m)));
                 };
@@ -276,7 +307,7 @@ impl Plugin for MemberOf {
             .filter_map(|e| {
                 // Is it a group?
                 if e.attribute_equality(Attribute::Class, &EntryClass::Group.into()) {
-                    e.get_ava_as_refuuid(Attribute::Member)
+                    Some(group_members(e))
                 } else {
                     None
                 }
@@ -313,6 +344,8 @@ impl Plugin for MemberOf {
             Err(e) => return vec![e],
         };
 
+        let now = OffsetDateTime::UNIX_EPOCH + duration_from_epoch_now();
+
         // for each entry in the DB (live).
         for e in all_cand {
             let uuid = e.get_uuid();
@@ -320,6 +353,7 @@ impl Plugin for MemberOf {
                 f_eq(Attribute::Class, EntryClass::Group.into()),
                 f_or!([
                     f_eq(Attribute::Member, PartialValue::Refer(uuid)),
+                    f_eq(Attribute::MemberExpiring, PartialValue::Refer(uuid)),
                     f_eq(Attribute::DynMember, PartialValue::Refer(uuid))
                 ])
             ]));
@@ -333,8 +367,17 @@ impl Plugin for MemberOf {
             };
             // for all direct -> add uuid to map
 
-            let d_groups_set: BTreeSet<Uuid> =
-                direct_memberof.iter().map(|e| e.get_uuid()).collect();
+            // An expiring membership that has expired is only removed from the member when
+            // it is next updated, so these groups may or may not be present.
+            let (d_groups_set, expired_groups): (BTreeSet<Uuid>, BTreeSet<Uuid>) = {
+                let (current, expired): (Vec<_>, Vec<_>) = direct_memberof
+                    .iter()
+                    .partition(|g| group_has_member(g, uuid, now));
+                (
+                    current.iter().map(|g| g.get_uuid()).collect(),
+                    expired.iter().map(|g| g.get_uuid()).collect(),
+                )
+            };
 
             let d_groups_set = if d_groups_set.is_empty() {
                 None
@@ -349,7 +392,10 @@ impl Plugin for MemberOf {
                     // Can they both be reference sets?
                     match edmos.as_refer_set() {
                         Some(a) => {
-                            let diff: Vec<_> = a.symmetric_difference(&b).collect();
+                            let diff: Vec<_> = a
+                                .symmetric_difference(&b)
+                                .filter(|u| !expired_groups.contains(u))
+                                .collect();
                             if !diff.is_empty() {
                                 admin_error!(
                                     "MemberOfInvalid: Entry {}, DMO has inconsistencies -> {:?}",
@@ -368,6 +414,13 @@ impl Plugin for MemberOf {
                 (None, None) => {
                     // Ok
                 }
+                (Some(edmos), None)
+                    if edmos
+                        .as_refer_set()
+                        .map_or(false, |a| a.iter().all(|u| expired_groups.contains(u))) =>
+                {
+                    // Ok, only expired memberships remain.
+                }
                 _ => {
                     admin_error!(
                         "MemberOfInvalid directmemberof set and DMO search set differ in size: {}",
@@ -412,7 +465,7 @@ impl MemberOf {
                     .filter_map(|e| {
                         // Is it a group?
                         if e.attribute_equality(Attribute::Class, &EntryClass::Group.into()) {
-                            e.get_ava_as_refuuid(Attribute::Member)
+                            Some(group_members(e))
                         } else {
                             None
                         }
@@ -442,7 +495,7 @@ impl MemberOf {
                     .iter()
                     .filter_map(|pre| {
                         if pre.attribute_equality(Attribute::Class, &EntryClass::Group.into()) {
-                            pre.get_ava_as_refuuid(Attribute::Member)
+                            Some(group_members(pre.as_ref()))
                         } else {
                             None
                         }
@@ -453,7 +506,7 @@ impl MemberOf {
                 cand.iter()
                     .filter_map(|post| {
                         if post.attribute_equality(Attribute::Class, &EntryClass::Group.into()) {
-                            post.get_ava_as_refuuid(Attribute::Member)
+                            Some(group_members(post))
                         } else {
                             None
                         }
@@ -803,6 +856,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_modify_mo_add_expiring() {
+        // A    B -> C
+        // Add expiring member A -> B
+        // A -> B -> C
+        let ea: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EA);
+
+        let mut eb: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EB);
+
+        let ec: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EC);
+
+        eb.add_ava(Attribute::Member, Value::new_refer_s(UUID_C).unwrap());
+
+        let expiry = time::OffsetDateTime::now_utc() + std::time::Duration::from_secs(86400);
+
+        let preload = vec![ea, eb, ec];
+        run_modify_test!(
+            Ok(()),
+            preload,
+            filter!(f_eq(
+                Attribute::Uuid,
+                PartialValue::new_uuid_s(UUID_A).unwrap()
+            )),
+            ModifyList::new_list(vec![Modify::Present(
+                Attribute::MemberExpiring.into(),
+                Value::new_refer_expiring(Uuid::parse_str(UUID_B).unwrap(), expiry)
+            )]),
+            None,
+            |_| {},
+            |qs: &mut QueryServerWriteTransaction| {
+                //                      V-- this uuid is
+                //                                  V-- memberof this UUID
+                assert_memberof!(qs, UUID_B, UUID_A);
+                assert_memberof!(qs, UUID_C, UUID_A);
+                assert_not_memberof!(qs, UUID_A, UUID_B);
+
+                assert_dirmemberof!(qs, UUID_B, UUID_A);
+                assert_not_dirmemberof!(qs, UUID_C, UUID_A);
+            }
+        );
+    }
+
+    #[test]
+    fn test_modify_mo_add_expired() {
+        // A    B -> C
+        // Add expired member A -> B
+        // A    B -> C
+        let ea: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EA);
+
+        let mut eb: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EB);
+
+        let ec: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EC);
+
+        eb.add_ava(Attribute::Member, Value::new_refer_s(UUID_C).unwrap());
+
+        let expiry = time::OffsetDateTime::UNIX_EPOCH + std::time::Duration::from_secs(86400);
+
+        let preload = vec![ea, eb, ec];
+        run_modify_test!(
+            Ok(()),
+            preload,
+            filter!(f_eq(
+                Attribute::Uuid,
+                PartialValue::new_uuid_s(UUID_A).unwrap()
+            )),
+            ModifyList::new_list(vec![Modify::Present(
+                Attribute::MemberExpiring.into(),
+                Value::new_refer_expiring(Uuid::parse_str(UUID_B).unwrap(), expiry)
+            )]),
+            None,
+            |_| {},
+            |qs: &mut QueryServerWriteTransaction| {
+                // The membership has expired, so it is not honoured even though it
+                // has not been purged yet.
+                assert_not_memberof!(qs, UUID_B, UUID_A);
+                assert_not_memberof!(qs, UUID_C, UUID_A);
+                assert_memberof!(qs, UUID_C, UUID_B);
+
+                assert_not_dirmemberof!(qs, UUID_B, UUID_A);
+            }
+        );
+    }
+
     #[test]
     fn test_modify_mo_add_nested_1() {
         // A    B -> C
@@ -0,0 +1,349 @@
+//! Inspection and resolution of replication conflicts.
+//!
+//! When replication is unable to merge changes, an entry involved is moved aside into
+//! a conflict state. This entry is recycled, has the class conflict, and lists the uuids
+//! of the entries it conflicted with in source_uuid. These entries remain until an
+//! administrator decides how the conflict should be resolved.
+//!
+//! Access to conflicts is granted by the access controls that target class=conflict, and
+//! any changes to the live entries are made as the resolving identity.
+
+use crate::event::ReviveRecycledEvent;
+use crate::prelude::*;
+use crate::repl::entry::State;
+use crate::schema::SchemaTransaction;
+use kanidm_proto::v1::{
+    ReplicationConflict, ReplicationConflictAttr, ReplicationConflictEntry,
+    ReplicationConflictResolution,
+};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Syntaxes of credentials and secrets, which are never shown in a conflict view.
+const CONFLICT_SECRET_SYNTAX: [SyntaxType; 13] = [
+    SyntaxType::Credential,
+    SyntaxType::SecretUtf8String,
+    SyntaxType::PrivateBinary,
+    SyntaxType::IntentToken,
+    SyntaxType::Passkey,
+    SyntaxType::DeviceKey,
+    SyntaxType::Session,
+    SyntaxType::JwsKeyEs256,
+    SyntaxType::JwsKeyRs256,
+    SyntaxType::Oauth2Session,
+    SyntaxType::TotpSecret,
+    SyntaxType::ApiToken,
+    SyntaxType::EcKeyPrivate,
+];
+
+/// Attributes that are maintained by the server and so are never taken from a conflict entry.
+const CONFLICT_RESERVED_ATTRS: [Attribute; 7] = [
+    Attribute::Uuid,
+    Attribute::SourceUuid,
+    Attribute::LastModifiedCid,
+    Attribute::MemberOf,
+    Attribute::DirectMemberOf,
+    Attribute::Spn,
+    Attribute::NameHistory,
+];
+
+fn conflict_is_reserved_attr(attr: &str) -> bool {
+    CONFLICT_RESERVED_ATTRS
+        .iter()
+        .any(|reserved| reserved.as_ref() == attr)
+}
+
+fn conflict_source_uuids(entry: &EntrySealedCommitted) -> Vec<Uuid> {
+    entry
+        .get_ava_set(Attribute::SourceUuid)
+        .and_then(|vs| vs.as_uuid_set())
+        .map(|set| set.iter().copied().collect())
+        .unwrap_or_default()
+}
+
+fn conflict_is_secret_attr<S: SchemaTransaction>(schema: &S, attr: &str) -> bool {
+    schema
+        .get_attributes()
+        .get(attr)
+        // An attribute without a schema definition may hold anything.
+        .map_or(true, |sa| CONFLICT_SECRET_SYNTAX.contains(&sa.syntax))
+}
+
+/// The values of an attribute of the conflict entry, as they would be merged into a live entry.
+fn conflict_merge_values(conflict: &EntrySealedCommitted, attr: &str) -> Vec<Value> {
+    conflict
+        .get_ava()
+        .get(attr)
+        .map(|vs| {
+            vs.to_value_iter()
+                .filter(|v| {
+                    attr != Attribute::Class.as_ref()
+                        || (v != &EntryClass::Conflict.to_value()
+                            && v != &EntryClass::Recycled.to_value())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn conflict_entry_view<S: SchemaTransaction>(
+    schema: &S,
+    entry: &EntrySealedCommitted,
+) -> ReplicationConflictEntry {
+    let (at, changes) = match entry.get_changestate().current() {
+        State::Live { at, changes } => (at, Some(changes)),
+        State::Tombstone { at } => (at, None),
+    };
+
+    let attrs = entry
+        .get_ava()
+        .iter()
+        .filter(|(attr, _)| !conflict_is_secret_attr(schema, attr))
+        .map(|(attr, vs)| {
+            let cid = changes.and_then(|changes| changes.get(attr)).unwrap_or(at);
+            (
+                attr.to_string(),
+                ReplicationConflictAttr {
+                    values: vs.to_proto_string_clone_iter().collect(),
+                    cid: cid.to_string(),
+                    server_uuid: cid.s_uuid,
+                },
+            )
+        })
+        .collect();
+
+    ReplicationConflictEntry {
+        uuid: entry.get_uuid(),
+        cid: at.to_string(),
+        server_uuid: at.s_uuid,
+        attrs,
+    }
+}
+
+/// Search the conflict entries that the identity is permitted to access.
+fn conflict_search_filter<'a, T: QueryServerTransaction<'a>>(
+    qs: &mut T,
+    ident: &Identity,
+    filter: Filter<FilterInvalid>,
+) -> Result<Vec<Arc<EntrySealedCommitted>>, OperationError> {
+    let filter = filter
+        .validate(qs.get_schema())
+        .map(|f| f.into_recycled())
+        .map_err(OperationError::SchemaViolation)?;
+    qs.impersonate_search_valid(filter.clone(), filter, ident)
+}
+
+/// Get a conflict entry by uuid, and the live entries it conflicted with that the
+/// identity is able to see.
+fn conflict_search<'a, T: QueryServerTransaction<'a>>(
+    qs: &mut T,
+    ident: &Identity,
+    uuid: Uuid,
+) -> Result<(Arc<EntrySealedCommitted>, Vec<Arc<EntrySealedCommitted>>), OperationError> {
+    let filter = filter!(f_and!([
+        f_eq(Attribute::Class, EntryClass::Conflict.into()),
+        f_eq(Attribute::Uuid, PartialValue::Uuid(uuid))
+    ]));
+
+    let mut conflicts = conflict_search_filter(qs, ident, filter)?;
+    let conflict = match conflicts.pop() {
+        Some(conflict) if conflicts.is_empty() => conflict,
+        _ => {
+            request_error!(%uuid, "no replication conflict matches");
+            return Err(OperationError::NoMatchingEntries);
+        }
+    };
+
+    let mut live = Vec::new();
+    for source_uuid in conflict_source_uuids(&conflict) {
+        // An entry that failed schema lists itself as the source.
+        if source_uuid == uuid {
+            continue;
+        }
+
+        // The source may have been deleted, is itself in conflict, or is not visible
+        // to this identity.
+        let filter = filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(source_uuid)));
+        live.extend(qs.impersonate_search(filter.clone(), filter, ident)?);
+    }
+
+    Ok((conflict, live))
+}
+
+impl<'a> QueryServerReadTransaction<'a> {
+    #[instrument(level = "debug", skip_all)]
+    pub fn repl_conflict_list(
+        &mut self,
+        ident: &Identity,
+    ) -> Result<Vec<ReplicationConflict>, OperationError> {
+        let filter = filter!(f_eq(Attribute::Class, EntryClass::Conflict.into()));
+        let conflicts = conflict_search_filter(self, ident, filter)?;
+
+        conflicts
+            .iter()
+            .map(|conflict| self.repl_conflict_get(ident, conflict.get_uuid()))
+            .collect()
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub fn repl_conflict_get(
+        &mut self,
+        ident: &Identity,
+        uuid: Uuid,
+    ) -> Result<ReplicationConflict, OperationError> {
+        let (conflict, live) = conflict_search(self, ident, uuid)?;
+        let schema = self.get_schema();
+
+        Ok(ReplicationConflict {
+            conflict: conflict_entry_view(schema, &conflict),
+            source_uuids: conflict_source_uuids(&conflict),
+            live: live
+                .iter()
+                .map(|entry| conflict_entry_view(schema, entry))
+                .collect(),
+        })
+    }
+}
+
+impl<'a> QueryServerWriteTransaction<'a> {
+    /// Resolve a replication conflict. The outcome is written as normal changes, so it
+    /// is replicated to all other servers.
+    #[instrument(level = "debug", skip_all)]
+    pub fn repl_conflict_resolve(
+        &mut self,
+        ident: &Identity,
+        uuid: Uuid,
+        resolution: &ReplicationConflictResolution,
+    ) -> Result<(), OperationError> {
+        security_info!(name = %ident, %uuid, ?resolution, "resolving replication conflict");
+
+        let (conflict, live) = conflict_search(self, ident, uuid)?;
+
+        let attrs: Vec<AttrString> = match resolution {
+            ReplicationConflictResolution::Discard => Vec::new(),
+            ReplicationConflictResolution::Revive => {
+                // Revive strips the conflict state from the entry.
+                let filter = filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(uuid)));
+                let re = ReviveRecycledEvent::from_parts(ident.clone(), &filter, self)?;
+                return self.revive_recycled(&re);
+            }
+            ReplicationConflictResolution::Accept => {
+                let mut attrs: BTreeSet<AttrString> = conflict.get_ava().keys().cloned().collect();
+                if let [live_entry] = live.as_slice() {
+                    attrs.extend(live_entry.get_ava().keys().cloned());
+                }
+                let schema = self.get_schema();
+                attrs
+                    .into_iter()
+                    .filter(|attr| schema.is_replicated(attr) && !conflict_is_reserved_attr(attr))
+                    // Secrets are not shown in the conflict view, so they are only merged
+                    // when they are requested by name.
+                    .filter(|attr| !conflict_is_secret_attr(schema, attr))
+                    // Only the attributes that differ are changed, so that access is only
+                    // required to what the conflict actually alters.
+                    .filter(|attr| match live.as_slice() {
+                        [live_entry] => {
+                            let values = conflict_merge_values(&conflict, attr);
+                            let live_values: Vec<Value> = live_entry
+                                .get_ava()
+                                .get(attr)
+                                .map(|vs| vs.to_value_iter().collect())
+                                .unwrap_or_default();
+                            values.len() != live_values.len()
+                                || values.iter().any(|v| !live_values.contains(v))
+                        }
+                        _ => true,
+                    })
+                    .collect()
+            }
+            ReplicationConflictResolution::Merge { attrs } => attrs
+                .iter()
+                .map(|attr| {
+                    self.get_schema()
+                        .normalise_attr_if_exists(attr)
+                        .filter(|attr| !conflict_is_reserved_attr(attr))
+                        .ok_or_else(|| {
+                            request_error!(%attr, "attribute can not be merged");
+                            OperationError::InvalidAttributeName(attr.to_string())
+                        })
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        if !attrs.is_empty() {
+            let live_uuid = match live.as_slice() {
+                [live_entry] => live_entry.get_uuid(),
+                [] => {
+                    request_error!(%uuid, "conflict has no live entry to merge into");
+                    return Err(OperationError::InvalidRequestState);
+                }
+                _ => {
+                    request_error!(%uuid, "conflict has multiple live entries to merge into");
+                    return Err(OperationError::InvalidRequestState);
+                }
+            };
+
+            let mut mods = Vec::with_capacity(attrs.len() * 2);
+            for attr in attrs {
+                let values = conflict_merge_values(&conflict, &attr);
+                mods.push(Modify::Purged(attr.clone()));
+                mods.extend(values.into_iter().map(|v| Modify::Present(attr.clone(), v)));
+            }
+
+            let filter = filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(live_uuid)));
+            self.impersonate_modify(&filter, &filter, &ModifyList::new_list(mods), ident)?;
+        }
+
+        self.repl_conflict_discard(ident, &conflict)
+    }
+
+    fn repl_conflict_discard(
+        &mut self,
+        ident: &Identity,
+        conflict: &Arc<EntrySealedCommitted>,
+    ) -> Result<(), OperationError> {
+        // Discarding is permitted by the ability to remove the conflict class, checked
+        // with a "fake" modify in the same manner as revive.
+        let filter = filter!(f_eq(
+            Attribute::Uuid,
+            PartialValue::Uuid(conflict.get_uuid())
+        ))
+        .validate(self.get_schema())
+        .map(|f| f.into_recycled())
+        .map_err(OperationError::SchemaViolation)?;
+
+        let modlist = ModifyList::new_list(vec![Modify::Removed(
+            Attribute::Class.into(),
+            EntryClass::Conflict.into(),
+        )])
+        .validate(self.get_schema())
+        .map_err(OperationError::SchemaViolation)?;
+
+        let me = ModifyEvent::new_impersonate(ident, filter.clone(), filter, modlist);
+        let op_allow = self
+            .get_accesscontrols()
+            .modify_allow_operation(&me, std::slice::from_ref(conflict))
+            .map_err(|e| {
+                admin_error!("Unable to check modify access {:?}", e);
+                e
+            })?;
+        if !op_allow {
+            return Err(OperationError::AccessDenied);
+        }
+
+        let tombstone = conflict
+            .to_tombstone(self.cid.clone())
+            .validate(&self.schema)
+            .map_err(|e| {
+                admin_error!(
+                    "Schema Violation in repl_conflict_discard validate: {:?}",
+                    e
+                );
+                OperationError::SchemaViolation(e)
+            })?
+            .seal(&self.schema);
+
+        self.be_txn
+            .modify(&self.cid, &[conflict.clone()], &[tombstone])
+    }
+}
@@ -2,6 +2,7 @@ pub(crate) mod cid;
 pub(crate) mod entry;
 pub(crate) mod ruv;
 
+pub(crate) mod conflict;
 pub(crate) mod consumer;
 pub mod proto;
 pub(crate) mod supplier;
@@ -58,7 +58,7 @@ impl From<&ReplCidV1> for Cid {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct ReplCidRange {
     #[serde(rename = "m")]
     pub ts_min: Duration,
@@ -66,7 +66,7 @@ pub struct ReplCidRange {
     pub ts_max: Duration,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub enum ReplRuvRange {
     V1 {
         domain_uuid: Uuid,
@@ -365,6 +365,9 @@ pub enum ReplAttrV1 {
     Reference {
         set: Vec<Uuid>,
     },
+    ReferenceExpiring {
+        set: Vec<(Uuid, String)>,
+    },
     SyntaxType {
         set: Vec<u16>,
     },
@@ -374,6 +377,9 @@ pub enum ReplAttrV1 {
     UiHint {
         set: Vec<u16>,
     },
+    CredentialType {
+        set: Vec<u16>,
+    },
     SshKey {
         set: Vec<(String, String)>,
     },
@@ -287,6 +287,24 @@ pub trait ReplicationUpdateVectorTransaction {
             .collect::<Result<BTreeMap<_, _>, _>>()
     }
 
+    /// For each server that has originated changes, count the changes held in this RUV that
+    /// are newer than the given ranges of a partner. This is how far the partner lags behind
+    /// this server, in changes.
+    fn range_lag(&self, ctx_ranges: &BTreeMap<Uuid, ReplCidRange>) -> BTreeMap<Uuid, usize> {
+        self.range_snapshot()
+            .iter()
+            .map(|(s_uuid, ts_set)| {
+                let lag = match ctx_ranges.get(s_uuid) {
+                    Some(ctx_range) => ts_set
+                        .range((Excluded(ctx_range.ts_max), Unbounded))
+                        .count(),
+                    None => ts_set.len(),
+                };
+                (*s_uuid, lag)
+            })
+            .collect()
+    }
+
     fn range_to_idl(&self, ctx_ranges: &BTreeMap<Uuid, ReplCidRange>) -> IDLBitRange {
         let mut idl = IDLBitRange::new();
         // Force the set to be compressed, saves on seeks during inserts.
@@ -881,6 +899,9 @@ mod tests {
     use super::RangeDiffStatus;
     use super::ReplCidRange;
     use super::ReplicationUpdateVector;
+    use super::ReplicationUpdateVectorTransaction;
+    use crate::repl::cid::Cid;
+    use idlset::v2::IDLBitRange;
     use std::collections::BTreeMap;
     use std::time::Duration;
 
@@ -1221,4 +1242,61 @@ mod tests {
         };
         assert_eq!(result, expect);
     }
+
+    #[test]
+    fn test_ruv_range_lag() {
+        let ruv = ReplicationUpdateVector::default();
+        let mut ruv_write = ruv.write();
+        for ts in 1..=4 {
+            let cid = Cid::new(UUID_A, Duration::from_secs(ts));
+            assert!(ruv_write.insert_change(&cid, IDLBitRange::new()).is_ok());
+        }
+        let cid = Cid::new(UUID_B, Duration::from_secs(2));
+        assert!(ruv_write.insert_change(&cid, IDLBitRange::new()).is_ok());
+        ruv_write.commit();
+
+        let ruv_read = ruv.read();
+
+        // The partner has seen the first two changes of A, and none of B.
+        let ctx = btreemap!((
+            UUID_A,
+            ReplCidRange {
+                ts_min: Duration::from_secs(1),
+                ts_max: Duration::from_secs(2),
+            }
+        ));
+        assert_eq!(
+            ruv_read.range_lag(&ctx),
+            btreemap!((UUID_A, 2), (UUID_B, 1))
+        );
+
+        // A partner that is up to date, and knows of servers we don't, has no lag.
+        let ctx = btreemap!(
+            (
+                UUID_A,
+                ReplCidRange {
+                    ts_min: Duration::from_secs(1),
+                    ts_max: Duration::from_secs(4),
+                }
+            ),
+            (
+                UUID_B,
+                ReplCidRange {
+                    ts_min: Duration::from_secs(2),
+                    ts_max: Duration::from_secs(2),
+                }
+            ),
+            (
+                UUID_C,
+                ReplCidRange {
+                    ts_min: Duration::from_secs(1),
+                    ts_max: Duration::from_secs(8),
+                }
+            )
+        );
+        assert_eq!(
+            ruv_read.range_lag(&ctx),
+            btreemap!((UUID_A, 0), (UUID_B, 0))
+        );
+    }
 }
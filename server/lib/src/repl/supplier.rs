@@ -8,6 +8,7 @@ use crate::prelude::*;
 use crate::be::keystorage::{KeyHandle, KeyHandleId};
 use kanidm_lib_crypto::mtls::build_self_signed_server_and_client_identity;
 use kanidm_lib_crypto::prelude::{PKey, Private, X509};
+use std::collections::BTreeMap;
 
 impl<'a> QueryServerWriteTransaction<'a> {
     fn supplier_generate_key_cert(
@@ -86,6 +87,33 @@ impl<'a> QueryServerReadTransaction<'a> {
     // * The consumer requires a full-reinit.
     // * Which entry attr-states need to be sent, if any
 
+    /// For each server that has originated changes, the number of changes held by this server
+    /// that are not yet present on a consumer with the given RUV.
+    #[instrument(level = "debug", skip_all)]
+    pub fn supplier_consumer_lag(
+        &mut self,
+        ctx_ruv: &ReplRuvRange,
+    ) -> Result<BTreeMap<Uuid, usize>, OperationError> {
+        let ctx_ranges = match ctx_ruv {
+            ReplRuvRange::V1 { ranges, .. } => ranges,
+        };
+
+        Ok(self.get_be_txn().get_ruv().range_lag(ctx_ranges))
+    }
+
+    /// The number of conflict entries held by this server, by the server that originated the
+    /// change which caused the conflict.
+    #[instrument(level = "debug", skip_all)]
+    pub fn replication_conflict_counts(&mut self) -> Result<BTreeMap<Uuid, usize>, OperationError> {
+        let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Conflict.into()));
+
+        let mut counts = BTreeMap::new();
+        for entry in self.internal_search(filter)? {
+            *counts.entry(entry.get_last_changed().s_uuid).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub fn supplier_provide_changes(
         &mut self,
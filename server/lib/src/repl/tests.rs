@@ -8,6 +8,7 @@ use crate::repl::ruv::ReplicationUpdateVectorTransaction;
 use crate::repl::ruv::{RangeDiffStatus, ReplicationUpdateVector};
 use crate::value::{Session, SessionState};
 use kanidm_lib_crypto::CryptoPolicy;
+use kanidm_proto::v1::ReplicationConflictResolution;
 use std::collections::BTreeMap;
 use time::OffsetDateTime;
 
@@ -1325,6 +1326,450 @@ async fn test_repl_increment_creation_uuid_conflict(
     drop(server_a_txn);
 }
 
+// Create a uuid conflict, then resolve it by merging an attribute from the conflict
+// entry into the live entry. The resolution must replicate to the partner.
+#[qs_pair_test]
+async fn test_repl_increment_conflict_resolve_merge(
+    server_a: &QueryServer,
+    server_b: &QueryServer,
+) {
+    let ct = duration_from_epoch_now();
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    assert!(repl_initialise(&mut server_b_txn, &mut server_a_txn).is_ok());
+
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+
+    // Create the same entry on both servers, with differing descriptions.
+    let t_uuid = Uuid::new_v4();
+    let e_init = |description: &str| {
+        entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("testperson1")),
+            (Attribute::Uuid, Value::Uuid(t_uuid)),
+            (Attribute::Description, Value::new_utf8s(description)),
+            (Attribute::DisplayName, Value::new_utf8s("testperson1"))
+        )
+    };
+
+    let mut server_b_txn = server_b.write(ct).await;
+    assert!(server_b_txn.internal_create(vec![e_init("from b")]).is_ok());
+    server_b_txn.commit().expect("Failed to commit");
+
+    let ct = duration_from_epoch_now();
+    let mut server_a_txn = server_a.write(ct).await;
+    assert!(server_a_txn.internal_create(vec![e_init("from a")]).is_ok());
+    server_a_txn.commit().expect("Failed to commit");
+
+    // Replicate B to A. The entry from B wins, and A's entry becomes a conflict.
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    repl_incremental(&mut server_b_txn, &mut server_a_txn);
+
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+
+    let ident = Identity::from_internal();
+    let mut server_a_txn = server_a.read().await;
+    let mut conflicts = server_a_txn
+        .repl_conflict_list(&ident)
+        .expect("Unable to list conflicts");
+    let conflict = conflicts.pop().expect("No conflict entries present");
+    assert!(conflicts.is_empty());
+    assert_eq!(conflict.source_uuids, vec![t_uuid]);
+    assert_eq!(conflict.live.len(), 1);
+    assert_eq!(
+        conflict.conflict.attrs[Attribute::Description.as_ref()].values,
+        vec!["from a".to_string()]
+    );
+    assert_eq!(
+        conflict.live[0].attrs[Attribute::Description.as_ref()].values,
+        vec!["from b".to_string()]
+    );
+    drop(server_a_txn);
+
+    // A conflict can only be merged into a live entry it conflicted with.
+    let mut server_a_txn = server_a.write(duration_from_epoch_now()).await;
+    assert_eq!(
+        server_a_txn.repl_conflict_resolve(
+            &ident,
+            conflict.conflict.uuid,
+            &ReplicationConflictResolution::Merge {
+                attrs: vec![Attribute::Uuid.to_string()]
+            }
+        ),
+        Err(OperationError::InvalidAttributeName(
+            Attribute::Uuid.to_string()
+        ))
+    );
+    assert!(server_a_txn
+        .repl_conflict_resolve(
+            &ident,
+            conflict.conflict.uuid,
+            &ReplicationConflictResolution::Merge {
+                attrs: vec![Attribute::Description.to_string()]
+            }
+        )
+        .is_ok());
+
+    let e1 = server_a_txn
+        .internal_search_uuid(t_uuid)
+        .expect("Unable to access entry.");
+    assert_eq!(
+        e1.get_ava_single_utf8(Attribute::Description),
+        Some("from a")
+    );
+    let cnf_a = server_a_txn
+        .internal_search_all_uuid(conflict.conflict.uuid)
+        .expect("Unable to access conflict entry.");
+    assert!(cnf_a.attribute_equality(Attribute::Class, &EntryClass::Tombstone.into()));
+
+    server_a_txn.commit().expect("Failed to commit");
+
+    // Replicate A to B, the resolution is applied to B.
+    let mut server_a_txn = server_a.read().await;
+    let mut server_b_txn = server_b.write(duration_from_epoch_now()).await;
+
+    repl_incremental(&mut server_a_txn, &mut server_b_txn);
+
+    let e2 = server_b_txn
+        .internal_search_uuid(t_uuid)
+        .expect("Unable to access entry.");
+    assert_eq!(
+        e2.get_ava_single_utf8(Attribute::Description),
+        Some("from a")
+    );
+
+    server_b_txn.commit().expect("Failed to commit");
+    drop(server_a_txn);
+
+    // Neither server has any remaining conflicts.
+    let mut server_a_txn = server_a.read().await;
+    let mut server_b_txn = server_b.read().await;
+    assert!(server_a_txn
+        .repl_conflict_list(&ident)
+        .expect("Unable to list conflicts")
+        .is_empty());
+    assert!(server_b_txn
+        .repl_conflict_list(&ident)
+        .expect("Unable to list conflicts")
+        .is_empty());
+}
+
+// Create an entry with the same uuid on both servers, then replicate B to A so that
+// A's entry becomes a conflict of B's. Returns the uuid of the conflict entry.
+async fn repl_conflict_init(
+    server_a: &QueryServer,
+    server_b: &QueryServer,
+    e_a: EntryInitNew,
+    e_b: EntryInitNew,
+) -> Uuid {
+    let ct = duration_from_epoch_now();
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    assert!(repl_initialise(&mut server_b_txn, &mut server_a_txn).is_ok());
+
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+
+    let mut server_b_txn = server_b.write(ct).await;
+    assert!(server_b_txn.internal_create(vec![e_b]).is_ok());
+    server_b_txn.commit().expect("Failed to commit");
+
+    let ct = duration_from_epoch_now();
+    let mut server_a_txn = server_a.write(ct).await;
+    assert!(server_a_txn.internal_create(vec![e_a]).is_ok());
+    server_a_txn.commit().expect("Failed to commit");
+
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    repl_incremental(&mut server_b_txn, &mut server_a_txn);
+
+    let mut cnf = server_a_txn
+        .internal_search(filter_rec!(f_eq(
+            Attribute::Class,
+            EntryClass::Conflict.into()
+        )))
+        .expect("Unable to search conflict entries.");
+    let cnf_uuid = cnf.pop().expect("No conflict entries present").get_uuid();
+    assert!(cnf.is_empty());
+
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+
+    cnf_uuid
+}
+
+fn repl_conflict_person(t_uuid: Uuid, name: &str, displayname: &str) -> EntryInitNew {
+    entry_init!(
+        (Attribute::Class, EntryClass::Object.to_value()),
+        (Attribute::Class, EntryClass::Person.to_value()),
+        (Attribute::Class, EntryClass::Account.to_value()),
+        (Attribute::Name, Value::new_iname(name)),
+        (Attribute::Uuid, Value::Uuid(t_uuid)),
+        (Attribute::DisplayName, Value::new_utf8s(displayname))
+    )
+}
+
+// Conflicts are only visible to identities that the access controls permit, and
+// credentials are never shown.
+#[qs_pair_test]
+async fn test_repl_conflict_list_get(server_a: &QueryServer, server_b: &QueryServer) {
+    let t_uuid = Uuid::new_v4();
+
+    let p = CryptoPolicy::minimum();
+    let cred = Credential::new_password_only(&p, "test_password").unwrap();
+    let mut e_a = repl_conflict_person(t_uuid, "testperson1", "from a");
+    e_a.add_ava(
+        Attribute::PrimaryCredential,
+        Value::Cred("primary".to_string(), cred),
+    );
+    let e_b = repl_conflict_person(t_uuid, "testperson1", "from b");
+
+    let cnf_uuid = repl_conflict_init(server_a, server_b, e_a, e_b).await;
+
+    let mut server_a_txn = server_a.read().await;
+    let admin = server_a_txn
+        .internal_search_uuid(UUID_ADMIN)
+        .expect("Unable to access admin.");
+    let admin = Identity::from_impersonate_entry_readonly(admin);
+
+    let conflicts = server_a_txn
+        .repl_conflict_list(&admin)
+        .expect("Unable to list conflicts");
+    assert_eq!(conflicts.len(), 1);
+
+    let conflict = server_a_txn
+        .repl_conflict_get(&admin, cnf_uuid)
+        .expect("Unable to get conflict");
+    assert_eq!(conflicts[0], conflict);
+    assert_eq!(conflict.source_uuids, vec![t_uuid]);
+    assert_eq!(conflict.live.len(), 1);
+    assert_eq!(conflict.live[0].uuid, t_uuid);
+    assert_eq!(
+        conflict.conflict.attrs[Attribute::DisplayName.as_ref()].values,
+        vec!["from a".to_string()]
+    );
+    assert!(!conflict
+        .conflict
+        .attrs
+        .contains_key(Attribute::PrimaryCredential.as_ref()));
+
+    // A live entry is not a conflict.
+    assert_eq!(
+        server_a_txn.repl_conflict_get(&admin, t_uuid),
+        Err(OperationError::NoMatchingEntries)
+    );
+
+    // An identity without access can't see the conflict.
+    let person = server_a_txn
+        .internal_search_uuid(t_uuid)
+        .expect("Unable to access entry.");
+    let person = Identity::from_impersonate_entry_readonly(person);
+    assert!(server_a_txn
+        .repl_conflict_list(&person)
+        .expect("Unable to list conflicts")
+        .is_empty());
+    assert_eq!(
+        server_a_txn.repl_conflict_get(&person, cnf_uuid),
+        Err(OperationError::NoMatchingEntries)
+    );
+}
+
+#[qs_pair_test]
+async fn test_repl_conflict_resolve_discard(server_a: &QueryServer, server_b: &QueryServer) {
+    let t_uuid = Uuid::new_v4();
+    let e_a = repl_conflict_person(t_uuid, "testperson1", "from a");
+    let e_b = repl_conflict_person(t_uuid, "testperson1", "from b");
+
+    let cnf_uuid = repl_conflict_init(server_a, server_b, e_a, e_b).await;
+
+    let mut server_a_txn = server_a.write(duration_from_epoch_now()).await;
+    let admin = server_a_txn
+        .internal_search_uuid(UUID_ADMIN)
+        .expect("Unable to access admin.");
+    let person = server_a_txn
+        .internal_search_uuid(t_uuid)
+        .expect("Unable to access entry.");
+
+    // Neither a read only session nor an identity without access may resolve.
+    assert_eq!(
+        server_a_txn.repl_conflict_resolve(
+            &Identity::from_impersonate_entry_readonly(admin.clone()),
+            cnf_uuid,
+            &ReplicationConflictResolution::Discard
+        ),
+        Err(OperationError::AccessDenied)
+    );
+    assert_eq!(
+        server_a_txn.repl_conflict_resolve(
+            &Identity::from_impersonate_entry_readwrite(person),
+            cnf_uuid,
+            &ReplicationConflictResolution::Discard
+        ),
+        Err(OperationError::NoMatchingEntries)
+    );
+
+    assert!(server_a_txn
+        .repl_conflict_resolve(
+            &Identity::from_impersonate_entry_readwrite(admin),
+            cnf_uuid,
+            &ReplicationConflictResolution::Discard
+        )
+        .is_ok());
+
+    let cnf_a = server_a_txn
+        .internal_search_all_uuid(cnf_uuid)
+        .expect("Unable to access conflict entry.");
+    assert!(cnf_a.attribute_equality(Attribute::Class, &EntryClass::Tombstone.into()));
+
+    let e1 = server_a_txn
+        .internal_search_uuid(t_uuid)
+        .expect("Unable to access entry.");
+    assert_eq!(
+        e1.get_ava_single_utf8(Attribute::DisplayName),
+        Some("from b")
+    );
+
+    server_a_txn.commit().expect("Failed to commit");
+}
+
+#[qs_pair_test]
+async fn test_repl_conflict_resolve_revive(server_a: &QueryServer, server_b: &QueryServer) {
+    let t_uuid = Uuid::new_v4();
+    // The names differ so that both entries can be live.
+    let e_a = repl_conflict_person(t_uuid, "testperson2", "from a");
+    let e_b = repl_conflict_person(t_uuid, "testperson1", "from b");
+
+    let cnf_uuid = repl_conflict_init(server_a, server_b, e_a, e_b).await;
+
+    let mut server_a_txn = server_a.write(duration_from_epoch_now()).await;
+    let admin = server_a_txn
+        .internal_search_uuid(UUID_ADMIN)
+        .expect("Unable to access admin.");
+
+    assert!(server_a_txn
+        .repl_conflict_resolve(
+            &Identity::from_impersonate_entry_readwrite(admin),
+            cnf_uuid,
+            &ReplicationConflictResolution::Revive
+        )
+        .is_ok());
+
+    let e1 = server_a_txn
+        .internal_search_uuid(cnf_uuid)
+        .expect("Unable to access revived entry.");
+    assert!(!e1.attribute_equality(Attribute::Class, &EntryClass::Conflict.into()));
+    assert!(!e1.attribute_pres(Attribute::SourceUuid));
+    assert_eq!(
+        e1.get_ava_single_iname(Attribute::Name),
+        Some("testperson2")
+    );
+
+    let e2 = server_a_txn
+        .internal_search_uuid(t_uuid)
+        .expect("Unable to access entry.");
+    assert_eq!(
+        e2.get_ava_single_iname(Attribute::Name),
+        Some("testperson1")
+    );
+
+    server_a_txn.commit().expect("Failed to commit");
+}
+
+#[qs_pair_test]
+async fn test_repl_conflict_resolve_accept(server_a: &QueryServer, server_b: &QueryServer) {
+    let t_uuid = Uuid::new_v4();
+    let e_a = repl_conflict_person(t_uuid, "testperson1", "from a");
+    let e_b = repl_conflict_person(t_uuid, "testperson1", "from b");
+
+    let cnf_uuid = repl_conflict_init(server_a, server_b, e_a, e_b).await;
+
+    let mut server_a_txn = server_a.write(duration_from_epoch_now()).await;
+    let admin = server_a_txn
+        .internal_search_uuid(UUID_ADMIN)
+        .expect("Unable to access admin.");
+
+    // Only the differing display name is changed, which the admin may write.
+    assert!(server_a_txn
+        .repl_conflict_resolve(
+            &Identity::from_impersonate_entry_readwrite(admin),
+            cnf_uuid,
+            &ReplicationConflictResolution::Accept
+        )
+        .is_ok());
+
+    let e1 = server_a_txn
+        .internal_search_uuid(t_uuid)
+        .expect("Unable to access entry.");
+    assert_eq!(
+        e1.get_ava_single_utf8(Attribute::DisplayName),
+        Some("from a")
+    );
+    assert!(e1.attribute_equality(Attribute::Class, &EntryClass::Account.into()));
+
+    let cnf_a = server_a_txn
+        .internal_search_all_uuid(cnf_uuid)
+        .expect("Unable to access conflict entry.");
+    assert!(cnf_a.attribute_equality(Attribute::Class, &EntryClass::Tombstone.into()));
+
+    server_a_txn.commit().expect("Failed to commit");
+}
+
+#[qs_pair_test]
+async fn test_repl_conflict_resolve_merge_denied(server_a: &QueryServer, server_b: &QueryServer) {
+    let t_uuid = Uuid::new_v4();
+    let e_a = repl_conflict_person(t_uuid, "testperson1", "from a");
+    let e_b = repl_conflict_person(t_uuid, "testperson1", "from b");
+
+    let cnf_uuid = repl_conflict_init(server_a, server_b, e_a, e_b).await;
+
+    let mut server_a_txn = server_a.write(duration_from_epoch_now()).await;
+    let admin = server_a_txn
+        .internal_search_uuid(UUID_ADMIN)
+        .expect("Unable to access admin.");
+    let admin = Identity::from_impersonate_entry_readwrite(admin);
+
+    // The merge is made as the resolving identity, so it's bound by their access to
+    // the live entry.
+    assert_eq!(
+        server_a_txn.repl_conflict_resolve(
+            &admin,
+            cnf_uuid,
+            &ReplicationConflictResolution::Merge {
+                attrs: vec![Attribute::Class.to_string()]
+            }
+        ),
+        Err(OperationError::AccessDenied)
+    );
+
+    assert!(server_a_txn
+        .repl_conflict_resolve(
+            &admin,
+            cnf_uuid,
+            &ReplicationConflictResolution::Merge {
+                attrs: vec![Attribute::DisplayName.to_string()]
+            }
+        )
+        .is_ok());
+
+    let e1 = server_a_txn
+        .internal_search_uuid(t_uuid)
+        .expect("Unable to access entry.");
+    assert_eq!(
+        e1.get_ava_single_utf8(Attribute::DisplayName),
+        Some("from a")
+    );
+
+    server_a_txn.commit().expect("Failed to commit");
+}
+
 // both add entry with same uuid, but one becomes ts - ts always wins.
 #[qs_pair_test]
 async fn test_repl_increment_create_tombstone_uuid_conflict(
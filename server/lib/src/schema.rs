@@ -196,7 +196,9 @@ impl SchemaAttribute {
             SyntaxType::SyntaxId => matches!(v, PartialValue::Syntax(_)),
             SyntaxType::IndexId => matches!(v, PartialValue::Index(_)),
             SyntaxType::Uuid => matches!(v, PartialValue::Uuid(_)),
-            SyntaxType::ReferenceUuid => matches!(v, PartialValue::Refer(_)),
+            SyntaxType::ReferenceUuid | SyntaxType::ReferenceUuidExpiring => {
+                matches!(v, PartialValue::Refer(_))
+            }
             SyntaxType::Utf8StringInsensitive => matches!(v, PartialValue::Iutf8(_)),
             SyntaxType::Utf8StringIname => matches!(v, PartialValue::Iname(_)),
             SyntaxType::Utf8String => matches!(v, PartialValue::Utf8(_)),
@@ -218,13 +220,15 @@ impl SchemaAttribute {
             SyntaxType::Passkey => matches!(v, PartialValue::Passkey(_)),
             SyntaxType::DeviceKey => matches!(v, PartialValue::DeviceKey(_)),
             // Allow refer types.
-            SyntaxType::Session => matches!(v, PartialValue::Refer(_)),
-            SyntaxType::ApiToken => matches!(v, PartialValue::Refer(_)),
-            SyntaxType::Oauth2Session => matches!(v, PartialValue::Refer(_)),
+            // Sessions are selected by their id, or ordered by their expiry.
+            SyntaxType::Session | SyntaxType::ApiToken | SyntaxType::Oauth2Session => {
+                matches!(v, PartialValue::Refer(_) | PartialValue::DateTime(_))
+            }
             // These are just insensitive string lookups on the hex-ified kid.
             SyntaxType::JwsKeyEs256 => matches!(v, PartialValue::Iutf8(_)),
             SyntaxType::JwsKeyRs256 => matches!(v, PartialValue::Iutf8(_)),
             SyntaxType::UiHint => matches!(v, PartialValue::UiHint(_)),
+            SyntaxType::CredentialType => matches!(v, PartialValue::CredentialType(_)),
             SyntaxType::EcKeyPrivate => matches!(v, PartialValue::SecretValue),
             // Comparing on the label.
             SyntaxType::TotpSecret => matches!(v, PartialValue::Utf8(_)),
@@ -252,6 +256,7 @@ impl SchemaAttribute {
                 SyntaxType::IndexId => matches!(v, Value::Index(_)),
                 SyntaxType::Uuid => matches!(v, Value::Uuid(_)),
                 SyntaxType::ReferenceUuid => matches!(v, Value::Refer(_)),
+                SyntaxType::ReferenceUuidExpiring => matches!(v, Value::ReferExpiring(_, _)),
                 SyntaxType::Utf8StringInsensitive => matches!(v, Value::Iutf8(_)),
                 SyntaxType::Utf8StringIname => matches!(v, Value::Iname(_)),
                 SyntaxType::Utf8String => matches!(v, Value::Utf8(_)),
@@ -278,6 +283,7 @@ impl SchemaAttribute {
                 SyntaxType::JwsKeyEs256 => matches!(v, Value::JwsKeyEs256(_)),
                 SyntaxType::JwsKeyRs256 => matches!(v, Value::JwsKeyRs256(_)),
                 SyntaxType::UiHint => matches!(v, Value::UiHint(_)),
+                SyntaxType::CredentialType => matches!(v, Value::CredentialType(_)),
                 SyntaxType::TotpSecret => matches!(v, Value::TotpSecret(_, _)),
                 SyntaxType::AuditLogString => matches!(v, Value::Utf8(_)),
                 SyntaxType::EcKeyPrivate => matches!(v, Value::EcKeyPrivate(_)),
@@ -756,6 +762,7 @@ impl<'a> SchemaWriteTransaction<'a> {
         attributetypes.into_iter().for_each(|a| {
             // Update the unique and ref caches.
             if a.syntax == SyntaxType::ReferenceUuid ||
+                a.syntax == SyntaxType::ReferenceUuidExpiring ||
                 a.syntax == SyntaxType::OauthScopeMap ||
                 // So that when an rs is removed we trigger removal of the sessions.
                 a.syntax == SyntaxType::Oauth2Session
@@ -1763,6 +1770,51 @@ impl<'a> SchemaWriteTransaction<'a> {
                 syntax: SyntaxType::Uint32,
             },
         );
+        self.attributes.insert(
+            Attribute::LdapAutomountMapName.into(),
+            SchemaAttribute {
+                name: Attribute::LdapAutomountMapName.into(),
+                uuid: UUID_SCHEMA_ATTR_AUTOMOUNTMAPNAME,
+                description: String::from("An LDAP Compatible automountMapName"),
+                multivalue: false,
+                unique: false,
+                phantom: true,
+                sync_allowed: false,
+                replicated: false,
+                index: vec![],
+                syntax: SyntaxType::Utf8StringIname,
+            },
+        );
+        self.attributes.insert(
+            Attribute::LdapAutomountKey.into(),
+            SchemaAttribute {
+                name: Attribute::LdapAutomountKey.into(),
+                uuid: UUID_SCHEMA_ATTR_AUTOMOUNTKEY,
+                description: String::from("An LDAP Compatible automountKey"),
+                multivalue: false,
+                unique: false,
+                phantom: true,
+                sync_allowed: false,
+                replicated: false,
+                index: vec![],
+                syntax: SyntaxType::Utf8String,
+            },
+        );
+        self.attributes.insert(
+            Attribute::LdapAutomountInformation.into(),
+            SchemaAttribute {
+                name: Attribute::LdapAutomountInformation.into(),
+                uuid: UUID_SCHEMA_ATTR_AUTOMOUNTINFORMATION,
+                description: String::from("An LDAP Compatible automountInformation"),
+                multivalue: false,
+                unique: false,
+                phantom: true,
+                sync_allowed: false,
+                replicated: false,
+                index: vec![],
+                syntax: SyntaxType::Utf8String,
+            },
+        );
         self.attributes.insert(
             Attribute::Image.into(),
             SchemaAttribute {
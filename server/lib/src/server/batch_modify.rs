@@ -1,4 +1,5 @@
 use super::QueryServerWriteTransaction;
+use crate::idm::audit::AuditOperation;
 use crate::prelude::*;
 use crate::server::Plugins;
 use hashbrown::HashMap;
@@ -94,6 +95,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
                 e
             })?;
         if !op_allow {
+            self.audit_access_denied(&me.ident, AuditOperation::Modify);
             return Err(OperationError::AccessDenied);
         }
 
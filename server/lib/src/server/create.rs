@@ -1,3 +1,4 @@
+use crate::idm::audit::AuditOperation;
 use crate::prelude::*;
 use crate::server::CreateEvent;
 use crate::server::Plugins;
@@ -34,6 +35,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
                 e
             })?;
         if !op_allow {
+            self.audit_access_denied(&ce.ident, AuditOperation::Create);
             return Err(OperationError::AccessDenied);
         }
 
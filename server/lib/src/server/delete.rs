@@ -1,3 +1,4 @@
+use crate::idm::audit::AuditOperation;
 use crate::plugins::Plugins;
 use crate::prelude::*;
 use crate::server::DeleteEvent;
@@ -34,6 +35,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
                 e
             })?;
         if !op_allow {
+            self.audit_access_denied(&de.ident, AuditOperation::Delete);
             return Err(OperationError::AccessDenied);
         }
 
@@ -0,0 +1,210 @@
+//! Expiring group memberships are removed once they pass their expiry. Memberof ignores
+//! memberships that have expired whenever it is recalculated, and the purge removes them so
+//! that their members are recalculated.
+
+use crate::idm::audit::AuditEvent;
+use crate::prelude::*;
+use time::OffsetDateTime;
+
+impl<'a> QueryServerWriteTransaction<'a> {
+    #[instrument(level = "debug", skip_all)]
+    pub fn purge_expired_members(&mut self) -> Result<(), OperationError> {
+        // Remove expiring group memberships that have passed their expiry.
+        let now = OffsetDateTime::UNIX_EPOCH + self.get_curtime();
+
+        let groups = self.internal_search(filter!(f_pres(Attribute::MemberExpiring)))?;
+
+        let mut modset = Vec::with_capacity(groups.len());
+        let mut audit_events = Vec::new();
+
+        for group in groups.iter() {
+            let Some(member_map) = group
+                .get_ava_set(Attribute::MemberExpiring)
+                .and_then(|vs| vs.as_refer_expiring_map())
+            else {
+                continue;
+            };
+
+            let removals: Vec<_> = member_map
+                .iter()
+                .filter(|(_, expiry)| **expiry <= now)
+                .map(|(member, expiry)| {
+                    audit_events.push(AuditEvent::GroupMembershipExpired {
+                        group: group.get_uuid(),
+                        member: *member,
+                        expiry: *expiry,
+                        time: now,
+                    });
+                    Modify::Removed(
+                        Attribute::MemberExpiring.into(),
+                        PartialValue::Refer(*member),
+                    )
+                })
+                .collect();
+
+            if !removals.is_empty() {
+                modset.push((group.get_uuid(), ModifyList::new_list(removals)));
+            }
+        }
+
+        if modset.is_empty() {
+            admin_info!("No expired group memberships present - purge operation success");
+            return Ok(());
+        }
+
+        self.internal_batch_modify(modset.into_iter())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Purge expired group memberships operation failed");
+                e
+            })?;
+
+        for event in audit_events {
+            self.audit_event_on_commit(event);
+        }
+
+        admin_info!("Purge expired group memberships operation success");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::CreateEvent;
+    use crate::idm::audit::AuditEvent;
+    use crate::prelude::*;
+    use time::OffsetDateTime;
+
+    const UUID_U1: Uuid = uuid!("22b47373-d123-421f-859e-9ddd8ab14a2a");
+    const UUID_G1: Uuid = uuid!("cca2bbfc-5b43-43f3-be9e-f5b03b3defec");
+
+    fn create_user(name: &str, uuid: Uuid) -> Entry<EntryInit, EntryNew> {
+        entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname(name)),
+            (Attribute::Uuid, Value::Uuid(uuid)),
+            (Attribute::Description, Value::new_utf8s("testperson-entry")),
+            (Attribute::DisplayName, Value::new_utf8s(name))
+        )
+    }
+
+    fn create_group(name: &str, uuid: Uuid) -> Entry<EntryInit, EntryNew> {
+        entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname(name)),
+            (Attribute::Uuid, Value::Uuid(uuid)),
+            (Attribute::Description, Value::new_utf8s("testgroup-entry"))
+        )
+    }
+
+    fn check_entry_has_mo(qs: &mut QueryServerWriteTransaction, uuid: Uuid, mo: Uuid) -> bool {
+        qs.internal_search_uuid(uuid)
+            .expect("failed")
+            .attribute_equality(Attribute::MemberOf, &PartialValue::Refer(mo))
+    }
+
+    #[qs_test]
+    async fn test_purge_expired_members(server: &QueryServer) {
+        let time_p1 = duration_from_epoch_now();
+        let time_p2 = time_p1 + Duration::from_secs(60);
+        let time_p3 = time_p1 + Duration::from_secs(120);
+
+        let mut server_txn = server.write(time_p1).await;
+
+        let u1 = create_user("u1", UUID_U1);
+        let mut g1 = create_group("g1", UUID_G1);
+        g1.add_ava(
+            Attribute::MemberExpiring,
+            Value::new_refer_expiring(UUID_U1, OffsetDateTime::UNIX_EPOCH + time_p2),
+        );
+
+        let ce = CreateEvent::new_internal(vec![u1, g1]);
+        assert!(server_txn.create(&ce).is_ok());
+
+        // Until it expires, the expiring membership is honoured.
+        assert!(check_entry_has_mo(&mut server_txn, UUID_U1, UUID_G1));
+        assert!(server_txn.purge_expired_members().is_ok());
+        assert!(check_entry_has_mo(&mut server_txn, UUID_U1, UUID_G1));
+        assert!(server_txn.commit().is_ok());
+
+        // Once past the expiry, any change to the group no longer honours the membership.
+        let mut server_txn = server.write(time_p3).await;
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_G1,
+                &ModifyList::new_purge_and_set(
+                    Attribute::Description,
+                    Value::new_utf8s("changed-entry")
+                )
+            )
+            .is_ok());
+        assert!(!check_entry_has_mo(&mut server_txn, UUID_U1, UUID_G1));
+
+        // And the purge removes the membership.
+        assert!(server_txn.purge_expired_members().is_ok());
+        assert!(!check_entry_has_mo(&mut server_txn, UUID_U1, UUID_G1));
+
+        let g1 = server_txn.internal_search_uuid(UUID_G1).expect("failed");
+        assert!(!g1.attribute_pres(Attribute::MemberExpiring));
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[idm_test(audit)]
+    async fn test_purge_expired_members_audit(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        const UUID_U2: Uuid = uuid!("5a4e8a4c-0d3c-4f5b-9a55-4a1f3c8e9b71");
+
+        let time_p1 = duration_from_epoch_now();
+        let time_p2 = time_p1 + Duration::from_secs(60);
+        let time_p3 = time_p1 + Duration::from_secs(120);
+        let expiry = OffsetDateTime::UNIX_EPOCH + time_p2;
+
+        let mut idms_prox_write = idms.proxy_write(time_p1).await;
+        let mut g1 = create_group("g1", UUID_G1);
+        g1.add_ava(
+            Attribute::MemberExpiring,
+            Value::new_refer_expiring(UUID_U1, expiry),
+        );
+        g1.add_ava(
+            Attribute::MemberExpiring,
+            Value::new_refer_expiring(UUID_U2, OffsetDateTime::UNIX_EPOCH + time_p3),
+        );
+        let ce = CreateEvent::new_internal(vec![
+            create_user("u1", UUID_U1),
+            create_user("u2", UUID_U2),
+            g1,
+        ]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Nothing has expired yet.
+        let mut idms_prox_write = idms.proxy_write(time_p1).await;
+        assert!(idms_prox_write.qs_write.purge_expired_members().is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+        idms_audit.check_is_empty_or_panic();
+
+        // Only the membership past its expiry is removed and reported.
+        let mut idms_prox_write = idms.proxy_write(time_p2).await;
+        assert!(idms_prox_write.qs_write.purge_expired_members().is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::GroupMembershipExpired {
+                group,
+                member,
+                expiry: event_expiry,
+                ..
+            }) => {
+                assert_eq!(group, UUID_G1);
+                assert_eq!(member, UUID_U1);
+                assert_eq!(event_expiry, expiry);
+            }
+            _ => assert!(false),
+        }
+    }
+}
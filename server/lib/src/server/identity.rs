@@ -21,7 +21,8 @@ use crate::value::Session;
 pub enum Source {
     Internal,
     Https(IpAddr),
-    // Ldaps,
+    Ldaps(IpAddr),
+    Replication(IpAddr),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -111,6 +111,10 @@ impl QueryServer {
             if system_info_version < 15 {
                 write_txn.migrate_14_to_15()?;
             }
+
+            if system_info_version < 16 {
+                write_txn.migrate_15_to_16()?;
+            }
         }
 
         write_txn.reload()?;
@@ -449,6 +453,20 @@ impl<'a> QueryServerWriteTransaction<'a> {
         // Complete
     }
 
+    #[instrument(level = "debug", skip_all)]
+    pub fn migrate_15_to_16(&mut self) -> Result<(), OperationError> {
+        admin_warn!("starting 15 to 16 migration.");
+        let filter = filter!(f_and!([
+            f_eq(Attribute::Class, EntryClass::PosixAccount.into()),
+            f_andnot(f_pres(Attribute::SubidRangeStart)),
+        ]));
+        // Touch posix accounts without a subordinate id range which triggers
+        // one to be allocated.
+        let modlist = ModifyList::new_purge(Attribute::SubidRangeStart);
+        self.internal_modify(&filter, &modlist)
+        // Complete
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub fn initialise_schema_core(&mut self) -> Result<(), OperationError> {
         admin_debug!("initialise_schema_core -> start ...");
@@ -493,12 +511,42 @@ impl<'a> QueryServerWriteTransaction<'a> {
         // List of IDM schemas to init.
         let idm_schema: Vec<EntryInitNew> = vec![
             SCHEMA_ATTR_MAIL.clone().into(),
+            SCHEMA_ATTR_ACCESS_REQUEST_APPROVER.clone().into(),
+            SCHEMA_ATTR_ACCESS_REQUEST_DECIDED_BY.clone().into(),
+            SCHEMA_ATTR_ACCESS_REQUEST_DURATION.clone().into(),
+            SCHEMA_ATTR_ACCESS_REQUEST_GROUP.clone().into(),
+            SCHEMA_ATTR_ACCESS_REQUEST_JUSTIFICATION.clone().into(),
+            SCHEMA_ATTR_ACCESS_REQUEST_REQUESTER.clone().into(),
+            SCHEMA_ATTR_ACCESS_REQUEST_STATE.clone().into(),
+            SCHEMA_ATTR_ACCESS_REVIEW_CAMPAIGN.clone().into(),
+            SCHEMA_ATTR_ACCESS_REVIEW_DECIDED_BY.clone().into(),
+            SCHEMA_ATTR_ACCESS_REVIEW_DECISION.clone().into(),
+            SCHEMA_ATTR_ACCESS_REVIEW_EXPIRY.clone().into(),
+            SCHEMA_ATTR_ACCESS_REVIEW_GROUP.clone().into(),
+            SCHEMA_ATTR_ACCESS_REVIEW_MEMBER.clone().into(),
+            SCHEMA_ATTR_ACCESS_REVIEW_REVIEWER.clone().into(),
+            SCHEMA_ATTR_ACCESS_REVIEW_STATE.clone().into(),
+            SCHEMA_ATTR_ENTRY_MANAGED_BY.clone().into(),
+            SCHEMA_ATTR_SUDO_USER.clone().into(),
+            SCHEMA_ATTR_SUDO_HOST.clone().into(),
+            SCHEMA_ATTR_SUDO_COMMAND.clone().into(),
+            SCHEMA_ATTR_SUDO_RUNAS.clone().into(),
+            SCHEMA_ATTR_SUDO_NOPASSWD.clone().into(),
+            SCHEMA_ATTR_HBAC_USER.clone().into(),
+            SCHEMA_ATTR_HBAC_HOST.clone().into(),
+            SCHEMA_ATTR_HBAC_SERVICE.clone().into(),
+            SCHEMA_ATTR_SUBID_RANGE_START.clone().into(),
+            SCHEMA_ATTR_AUTOMOUNT_MAP.clone().into(),
+            SCHEMA_ATTR_AUTOMOUNT_KEY.clone().into(),
+            SCHEMA_ATTR_AUTOMOUNT_INFORMATION.clone().into(),
             SCHEMA_ATTR_ACCOUNT_EXPIRE.clone().into(),
             SCHEMA_ATTR_ACCOUNT_VALID_FROM.clone().into(),
             SCHEMA_ATTR_API_TOKEN_SESSION.clone().into(),
             SCHEMA_ATTR_AUTH_SESSION_EXPIRY.clone().into(),
             SCHEMA_ATTR_AUTH_PRIVILEGE_EXPIRY.clone().into(),
+            SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_LENGTH.clone().into(),
             SCHEMA_ATTR_BADLIST_PASSWORD.clone().into(),
+            SCHEMA_ATTR_CREDENTIAL_TYPE_ALLOWED.clone().into(),
             SCHEMA_ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN.clone().into(),
             SCHEMA_ATTR_DEVICEKEYS.clone().into(),
             SCHEMA_ATTR_DISPLAYNAME.clone().into(),
@@ -517,15 +565,29 @@ impl<'a> QueryServerWriteTransaction<'a> {
             SCHEMA_ATTR_JWS_ES256_PRIVATE_KEY.clone().into(),
             SCHEMA_ATTR_LEGALNAME.clone().into(),
             SCHEMA_ATTR_LOGINSHELL.clone().into(),
+            SCHEMA_ATTR_MEMBER_EXPIRING.clone().into(),
             SCHEMA_ATTR_NAME_HISTORY.clone().into(),
             SCHEMA_ATTR_NSUNIQUEID.clone().into(),
             SCHEMA_ATTR_OAUTH2_ALLOW_INSECURE_CLIENT_DISABLE_PKCE
                 .clone()
                 .into(),
             SCHEMA_ATTR_OAUTH2_CONSENT_SCOPE_MAP.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_ACCOUNT.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_CLIENT.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_EXPIRY.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_FLOW_ENABLE.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_PARENT_EXPIRY.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_PARENT_SESSION.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_SCOPE.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_STATE.clone().into(),
+            SCHEMA_ATTR_OAUTH2_DEVICE_USER_CODE.clone().into(),
+            SCHEMA_ATTR_OAUTH2_RS_TOKEN_EXCHANGE_SOURCE.clone().into(),
             SCHEMA_ATTR_OAUTH2_JWT_LEGACY_CRYPTO_ENABLE.clone().into(),
             SCHEMA_ATTR_OAUTH2_PREFER_SHORT_USERNAME.clone().into(),
             SCHEMA_ATTR_OAUTH2_RS_BASIC_SECRET.clone().into(),
+            SCHEMA_ATTR_OAUTH2_RS_CLIENT_CREDENTIALS_SCOPES
+                .clone()
+                .into(),
             SCHEMA_ATTR_OAUTH2_RS_IMPLICIT_SCOPES.clone().into(),
             SCHEMA_ATTR_OAUTH2_RS_NAME.clone().into(),
             SCHEMA_ATTR_OAUTH2_RS_ORIGIN_LANDING.clone().into(),
@@ -542,6 +604,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
             SCHEMA_ATTR_SSH_PUBLICKEY.clone().into(),
             SCHEMA_ATTR_SYNC_COOKIE.clone().into(),
             SCHEMA_ATTR_SYNC_TOKEN_SESSION.clone().into(),
+            SCHEMA_ATTR_SYNC_WRITEBACK_COOKIE.clone().into(),
             SCHEMA_ATTR_UNIX_PASSWORD.clone().into(),
             SCHEMA_ATTR_USER_AUTH_TOKEN_SESSION.clone().into(),
         ];
@@ -558,10 +621,20 @@ impl<'a> QueryServerWriteTransaction<'a> {
         debug_assert!(r.is_ok());
 
         let idm_schema_classes: Vec<EntryInitNew> = vec![
+            SCHEMA_CLASS_ACCESS_REQUEST.clone().into(),
+            SCHEMA_CLASS_ACCESS_REVIEW.clone().into(),
+            SCHEMA_CLASS_ACCESS_REVIEW_ITEM.clone().into(),
+            SCHEMA_CLASS_SUDO_RULE.clone().into(),
+            SCHEMA_CLASS_HBAC_RULE.clone().into(),
+            SCHEMA_CLASS_HOST.clone().into(),
+            SCHEMA_CLASS_AUTOMOUNT_MAP.clone().into(),
+            SCHEMA_CLASS_AUTOMOUNT.clone().into(),
             SCHEMA_CLASS_ACCOUNT.clone().into(),
+            SCHEMA_CLASS_ACCOUNT_POLICY.clone().into(),
             SCHEMA_CLASS_DOMAIN_INFO.clone().into(),
             SCHEMA_CLASS_DYNGROUP.clone().into(),
             SCHEMA_CLASS_GROUP.clone().into(),
+            SCHEMA_CLASS_OAUTH2_DEVICE_AUTHORISATION.clone().into(),
             SCHEMA_CLASS_OAUTH2_RS.clone().into(),
             SCHEMA_CLASS_ORGPERSON.clone().into(),
             SCHEMA_CLASS_PERSON.clone().into(),
@@ -630,6 +703,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
         let idm_entries: Vec<&BuiltinGroup> = vec![
             &IDM_ALL_PERSONS,
             &IDM_ALL_ACCOUNTS,
+            &IDM_ALL_HOSTS,
             &IDM_PEOPLE_MANAGE_PRIV_V1,
             &IDM_PEOPLE_ACCOUNT_PASSWORD_IMPORT_PRIV_V1,
             &IDM_PEOPLE_EXTEND_PRIV_V1,
@@ -663,6 +737,9 @@ impl<'a> QueryServerWriteTransaction<'a> {
             &IDM_HP_OAUTH2_MANAGE_PRIV_V1,
             &IDM_HP_SERVICE_ACCOUNT_INTO_PERSON_MIGRATE_PRIV,
             &IDM_HP_SYNC_ACCOUNT_MANAGE_PRIV,
+            &IDM_HP_SUDO_RULE_MANAGE_PRIV,
+            &IDM_HP_HBAC_MANAGE_PRIV,
+            &IDM_HP_AUTOMOUNT_MANAGE_PRIV,
             // All members must exist before we write HP
             &IDM_HIGH_PRIVILEGE_V1,
             // other things
@@ -685,6 +762,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
             // Built in access controls.
             IDM_ADMINS_ACP_RECYCLE_SEARCH_V1.clone(),
             IDM_ADMINS_ACP_REVIVE_V1.clone(),
+            IDM_ADMINS_ACP_REPL_CONFLICT_V1.clone(),
             IDM_ALL_ACP_READ_V1.clone(),
             IDM_SELF_ACP_READ_V1.clone(),
             IDM_SELF_ACP_WRITE_V1.clone(),
@@ -702,6 +780,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
             IDM_ACP_GROUP_MANAGE_PRIV_V1.clone(),
             IDM_ACP_HP_GROUP_WRITE_PRIV_V1.clone(),
             IDM_ACP_HP_GROUP_MANAGE_PRIV_V1.clone(),
+            IDM_ACP_ACCESS_REVIEW_MANAGE_V1.clone(),
             IDM_ACP_SCHEMA_WRITE_ATTRS_PRIV_V1.clone(),
             IDM_ACP_SCHEMA_WRITE_CLASSES_PRIV_V1.clone(),
             IDM_ACP_ACP_MANAGE_PRIV_V1.clone(),
@@ -709,6 +788,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
             IDM_ACP_DOMAIN_ADMIN_PRIV_V1.clone(),
             IDM_ACP_SYSTEM_CONFIG_PRIV_V1.clone(),
             IDM_ACP_SYSTEM_CONFIG_SESSION_EXP_PRIV_V1.clone(),
+            IDM_ACP_GROUP_ACCOUNT_POLICY_MANAGE_PRIV_V1.clone(),
             IDM_ACP_PEOPLE_ACCOUNT_PASSWORD_IMPORT_PRIV_V1.clone(),
             IDM_ACP_PEOPLE_EXTEND_PRIV_V1.clone(),
             IDM_ACP_HP_PEOPLE_READ_PRIV_V1.clone(),
@@ -725,6 +805,13 @@ impl<'a> QueryServerWriteTransaction<'a> {
             E_IDM_HP_ACP_SYNC_ACCOUNT_MANAGE_PRIV_V1.clone(),
             IDM_ACP_ACCOUNT_MAIL_READ_PRIV_V1.clone(),
             IDM_ACCOUNT_SELF_ACP_WRITE_V1.clone(),
+            E_IDM_HP_ACP_SUDO_RULE_MANAGE_PRIV_V1.clone(),
+            IDM_ACP_SUDO_RULE_READ_V1.clone(),
+            E_IDM_HP_ACP_HOST_MANAGE_PRIV_V1.clone(),
+            E_IDM_HP_ACP_HBAC_RULE_MANAGE_PRIV_V1.clone(),
+            IDM_ACP_HBAC_RULE_READ_V1.clone(),
+            E_IDM_HP_ACP_AUTOMOUNT_MANAGE_PRIV_V1.clone(),
+            IDM_ACP_AUTOMOUNT_READ_V1.clone(),
         ];
 
         let res: Result<(), _> = idm_entries
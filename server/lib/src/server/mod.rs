@@ -3,19 +3,25 @@
 
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Instant;
 
 use concread::arcache::{ARCache, ARCacheBuilder, ARCacheReadTxn};
 use concread::cowcell::*;
 use hashbrown::{HashMap, HashSet};
 use std::collections::BTreeSet;
+use time::OffsetDateTime;
+use tokio::sync::mpsc::UnboundedSender as Sender;
 use tokio::sync::{Semaphore, SemaphorePermit};
 use tracing::trace;
 
 use kanidm_proto::v1::{ConsistencyError, UiHint};
 
 use crate::be::{Backend, BackendReadTransaction, BackendTransaction, BackendWriteTransaction};
+use crate::credential::CredentialKind;
 // We use so many, we just import them all ...
 use crate::filter::{Filter, FilterInvalid, FilterValid, FilterValidResolved};
+use crate::idm::audit::{AuditEvent, AuditOperation};
+use crate::metrics::METRICS;
 use crate::plugins::dyngroup::{DynGroup, DynGroupCache};
 use crate::plugins::Plugins;
 use crate::prelude::*;
@@ -41,6 +47,7 @@ pub(crate) mod access;
 pub mod batch_modify;
 pub mod create;
 pub mod delete;
+pub(crate) mod expiry;
 pub mod identity;
 pub(crate) mod migrations;
 pub mod modify;
@@ -80,6 +87,7 @@ pub struct QueryServer {
     resolve_filter_cache:
         Arc<ARCache<(IdentityId, Filter<FilterValid>), Filter<FilterValidResolved>>>,
     dyngroup_cache: Arc<CowCell<DynGroupCache>>,
+    audit_tx: Arc<CowCell<Option<Sender<AuditEvent>>>>,
 }
 
 pub struct QueryServerReadTransaction<'a> {
@@ -123,12 +131,40 @@ pub struct QueryServerWriteTransaction<'a> {
     resolve_filter_cache:
         ARCacheReadTxn<'a, (IdentityId, Filter<FilterValid>), Filter<FilterValidResolved>, ()>,
     dyngroup_cache: CowCellWriteTxn<'a, DynGroupCache>,
+    audit_tx: Option<Sender<AuditEvent>>,
+    // Audit events that are only submitted once this transaction commits.
+    audit_pending: Vec<AuditEvent>,
+    txn_start: Instant,
 }
 
 impl<'a> QueryServerWriteTransaction<'a> {
     pub(crate) fn trim_cid(&self) -> &Cid {
         &self.trim_cid
     }
+
+    /// Submit an audit event immediately, regardless of the outcome of this transaction.
+    /// This is used for events such as access being denied, where the transaction is
+    /// expected to be aborted.
+    pub(crate) fn audit_event(&self, event: AuditEvent) {
+        if let Some(audit_tx) = &self.audit_tx {
+            if audit_tx.send(event).is_err() {
+                error!("Unable to submit audit event to queue");
+            }
+        }
+    }
+
+    pub(crate) fn audit_access_denied(&self, ident: &Identity, operation: AuditOperation) {
+        self.audit_event(AuditEvent::AccessDenied {
+            identity: ident.get_uuid(),
+            operation,
+            time: OffsetDateTime::UNIX_EPOCH + self.curtime,
+        })
+    }
+
+    /// Queue an audit event that is submitted only if this transaction commits.
+    pub fn audit_event_on_commit(&mut self, event: AuditEvent) {
+        self.audit_pending.push(event);
+    }
 }
 
 /// The `QueryServerTransaction` trait provides a set of common read only operations to be
@@ -538,6 +574,18 @@ pub trait QueryServerTransaction<'a> {
                             .unwrap_or(UUID_DOES_NOT_EXIST);
                         Ok(Value::Refer(un))
                     }
+                    SyntaxType::ReferenceUuidExpiring => {
+                        // These are supplied as "<name>,<rfc3339 expiry>"
+                        let (name, expiry) = value.rsplit_once(',')
+                            .ok_or_else(|| OperationError::InvalidAttribute("Invalid ReferenceUuidExpiring syntax - expected <name>,<rfc3339>".to_string()))?;
+                        let expiry = Value::new_datetime_s(expiry.trim())
+                            .and_then(|dt| dt.to_datetime())
+                            .ok_or_else(|| OperationError::InvalidAttribute("Invalid DateTime (rfc3339) syntax".to_string()))?;
+                        let un = self
+                            .name_to_uuid(name.trim())
+                            .unwrap_or(UUID_DOES_NOT_EXIST);
+                        Ok(Value::new_refer_expiring(un, expiry))
+                    }
                     SyntaxType::JsonFilter => Value::new_json_filter_s(value)
                         .ok_or_else(|| OperationError::InvalidAttribute("Invalid Filter syntax".to_string())),
                     SyntaxType::Image => Value::new_image(value),
@@ -572,6 +620,9 @@ pub trait QueryServerTransaction<'a> {
                     SyntaxType::UiHint => UiHint::from_str(value)
                         .map(Value::UiHint)
                         .map_err(|()| OperationError::InvalidAttribute("Invalid uihint syntax".to_string())),
+                    SyntaxType::CredentialType => CredentialKind::from_str(value)
+                        .map(Value::CredentialType)
+                        .map_err(|()| OperationError::InvalidAttribute("Invalid credential type syntax - expected one of password, generatedpassword, passwordmfa or webauthn".to_string())),
                     SyntaxType::TotpSecret => Err(OperationError::InvalidAttribute("TotpSecret Values can not be supplied through modification".to_string())),
                     SyntaxType::AuditLogString => Err(OperationError::InvalidAttribute("Audit logs are generated and not able to be set.".to_string())),
                     SyntaxType::EcKeyPrivate => Err(OperationError::InvalidAttribute("Ec keys are generated and not able to be set.".to_string())),
@@ -620,6 +671,7 @@ pub trait QueryServerTransaction<'a> {
                     // schema.rs for reference type / cache awareness during referential
                     // integrity processing. Exceptions are self-contained value types!
                     SyntaxType::ReferenceUuid
+                    | SyntaxType::ReferenceUuidExpiring
                     | SyntaxType::OauthScopeMap
                     | SyntaxType::Session
                     | SyntaxType::ApiToken
@@ -681,6 +733,13 @@ pub trait QueryServerTransaction<'a> {
                         .map_err(|()| {
                             OperationError::InvalidAttribute("Invalid uihint syntax".to_string())
                         }),
+                    SyntaxType::CredentialType => CredentialKind::from_str(value)
+                        .map(PartialValue::CredentialType)
+                        .map_err(|()| {
+                            OperationError::InvalidAttribute(
+                                "Invalid credential type syntax".to_string(),
+                            )
+                        }),
                     SyntaxType::AuditLogString => Ok(PartialValue::new_utf8s(value)),
                     SyntaxType::EcKeyPrivate => Ok(PartialValue::SecretValue),
                     SyntaxType::Image => Ok(PartialValue::new_utf8s(value)),
@@ -722,6 +781,23 @@ pub trait QueryServerTransaction<'a> {
                 })
                 .collect();
             v
+        } else if let Some(r_map) = value.as_refer_expiring_map() {
+            let v: Result<Vec<_>, _> = r_map
+                .iter()
+                .map(|(u, expiry)| {
+                    let nv = self.uuid_to_spn(*u)?;
+                    let u = match nv {
+                        Some(v) => v.to_proto_string_clone(),
+                        None => uuid_to_proto_string(*u),
+                    };
+                    let expiry = expiry.format(&Rfc3339).map_err(|e| {
+                        admin_error!(?e, "Failed to format timestamp into RFC3339");
+                        OperationError::InvalidValueState
+                    })?;
+                    Ok(format!("{u},{expiry}"))
+                })
+                .collect();
+            v
         } else {
             let v: Vec<_> = value.to_proto_string_clone_iter().collect();
             Ok(v)
@@ -1137,9 +1213,17 @@ impl QueryServer {
             write_ticket: Arc::new(Semaphore::new(1)),
             resolve_filter_cache,
             dyngroup_cache,
+            audit_tx: Arc::new(CowCell::new(None)),
         })
     }
 
+    /// Set the channel that audit events raised by write transactions are submitted to.
+    pub(crate) fn set_audit_tx(&self, audit_tx: Sender<AuditEvent>) {
+        let mut audit_tx_write = self.audit_tx.write();
+        *audit_tx_write = Some(audit_tx);
+        audit_tx_write.commit();
+    }
+
     pub fn try_quiesce(&self) {
         self.be.try_quiesce();
         self.accesscontrols.try_quiesce();
@@ -1240,6 +1324,9 @@ impl QueryServer {
             _write_ticket: write_ticket,
             resolve_filter_cache: self.resolve_filter_cache.read(),
             dyngroup_cache: self.dyngroup_cache.write(),
+            audit_tx: (*self.audit_tx.read()).clone(),
+            audit_pending: Vec::new(),
+            txn_start: Instant::now(),
         }
     }
 
@@ -1657,6 +1744,9 @@ impl<'a> QueryServerWriteTransaction<'a> {
             accesscontrols,
             cid,
             dyngroup_cache,
+            audit_tx,
+            audit_pending,
+            txn_start,
             ..
         } = self;
         debug_assert!(!committed);
@@ -1676,6 +1766,17 @@ impl<'a> QueryServerWriteTransaction<'a> {
             .map(|_| dyngroup_cache.commit())
             .and_then(|_| accesscontrols.commit())
             .and_then(|_| be_txn.commit())
+            .map(|()| {
+                METRICS.write_txn_committed(txn_start.elapsed());
+                // Only now that the changes are durable can the events be reported.
+                if let Some(audit_tx) = audit_tx {
+                    for event in audit_pending {
+                        if audit_tx.send(event).is_err() {
+                            error!("Unable to submit audit event to queue");
+                        }
+                    }
+                }
+            })
     }
     pub(crate) fn get_txn_cid(&self) -> &Cid {
         &self.cid
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use crate::idm::audit::AuditOperation;
 use crate::plugins::Plugins;
 use crate::prelude::*;
 
@@ -89,6 +90,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
                 e
             })?;
         if !op_allow {
+            self.audit_access_denied(&me.ident, AuditOperation::Modify);
             return Err(OperationError::AccessDenied);
         }
 
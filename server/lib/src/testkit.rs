@@ -68,3 +68,26 @@ pub async fn setup_idm_test() -> (IdmServer, IdmServerDelayed, IdmServerAudit) {
         .await
         .expect("Failed to setup idms")
 }
+
+/// A minimal person entry for idm tests.
+pub fn setup_test_person(name: &str, uuid: Uuid) -> EntryInitNew {
+    entry_init!(
+        (Attribute::Class, EntryClass::Object.to_value()),
+        (Attribute::Class, EntryClass::Account.to_value()),
+        (Attribute::Class, EntryClass::Person.to_value()),
+        (Attribute::Name, Value::new_iname(name)),
+        (Attribute::Uuid, Value::Uuid(uuid)),
+        (Attribute::DisplayName, Value::new_utf8s(name))
+    )
+}
+
+/// Impersonate the account with this uuid in a read write session.
+#[allow(clippy::expect_used)]
+pub async fn setup_test_ident_readwrite(idms: &IdmServer, uuid: Uuid) -> Identity {
+    let mut idms_prox_read = idms.proxy_read().await;
+    idms_prox_read
+        .qs_read
+        .internal_search_uuid(uuid)
+        .map(Identity::from_impersonate_entry_readwrite)
+        .expect("Failed to find account")
+}
@@ -30,7 +30,7 @@ use uuid::Uuid;
 use webauthn_rs::prelude::{AttestedPasskey as DeviceKeyV4, Passkey as PasskeyV4};
 
 use crate::be::dbentry::DbIdentSpn;
-use crate::credential::{totp::Totp, Credential};
+use crate::credential::{totp::Totp, Credential, CredentialKind};
 use crate::prelude::*;
 use crate::repl::cid::Cid;
 use crate::server::identity::IdentityId;
@@ -162,6 +162,7 @@ pub enum IndexType {
     Equality,
     Presence,
     SubString,
+    Ordering,
 }
 
 impl TryFrom<&str> for IndexType {
@@ -173,6 +174,7 @@ impl TryFrom<&str> for IndexType {
             "EQUALITY" => Ok(IndexType::Equality),
             "PRESENCE" => Ok(IndexType::Presence),
             "SUBSTRING" => Ok(IndexType::SubString),
+            "ORDERING" => Ok(IndexType::Ordering),
             // UUID map?
             // UUID rev map?
             _ => Err(()),
@@ -186,6 +188,7 @@ impl IndexType {
             IndexType::Equality => "eq",
             IndexType::Presence => "pres",
             IndexType::SubString => "sub",
+            IndexType::Ordering => "ord",
         }
     }
 }
@@ -199,6 +202,7 @@ impl fmt::Display for IndexType {
                 IndexType::Equality => "EQUALITY",
                 IndexType::Presence => "PRESENCE",
                 IndexType::SubString => "SUBSTRING",
+                IndexType::Ordering => "ORDERING",
             }
         )
     }
@@ -257,6 +261,8 @@ pub enum SyntaxType {
     AuditLogString = 32,
     EcKeyPrivate = 33,
     Image = 34,
+    ReferenceUuidExpiring = 35,
+    CredentialType = 36,
 }
 
 impl TryFrom<&str> for SyntaxType {
@@ -300,6 +306,8 @@ impl TryFrom<&str> for SyntaxType {
             "APITOKEN" => Ok(SyntaxType::ApiToken),
             "AUDIT_LOG_STRING" => Ok(SyntaxType::AuditLogString),
             "EC_KEY_PRIVATE" => Ok(SyntaxType::EcKeyPrivate),
+            "REFERENCE_UUID_EXPIRING" => Ok(SyntaxType::ReferenceUuidExpiring),
+            "CREDENTIAL_TYPE" => Ok(SyntaxType::CredentialType),
             _ => Err(()),
         }
     }
@@ -343,6 +351,8 @@ impl fmt::Display for SyntaxType {
             SyntaxType::AuditLogString => "AUDIT_LOG_STRING",
             SyntaxType::EcKeyPrivate => "EC_KEY_PRIVATE",
             SyntaxType::Image => "IMAGE",
+            SyntaxType::ReferenceUuidExpiring => "REFERENCE_UUID_EXPIRING",
+            SyntaxType::CredentialType => "CREDENTIAL_TYPE",
         })
     }
 }
@@ -389,6 +399,7 @@ pub enum PartialValue {
     RestrictedString(String),
     IntentToken(String),
     UiHint(UiHint),
+    CredentialType(CredentialKind),
     Passkey(Uuid),
     DeviceKey(Uuid),
     /// We compare on the value hash
@@ -769,6 +780,7 @@ impl PartialValue {
             PartialValue::PhoneNumber(a) => a.to_string(),
             PartialValue::IntentToken(u) => u.clone(),
             PartialValue::UiHint(u) => (*u as u16).to_string(),
+            PartialValue::CredentialType(k) => (*k as u16).to_string(),
             PartialValue::Image(imagehash) => imagehash.to_owned(),
         }
     }
@@ -777,6 +789,127 @@ impl PartialValue {
     pub fn get_idx_sub_key(&self) -> String {
         unimplemented!();
     }
+
+    /// The key of this value in an ordering index. This is `None` for values that have
+    /// no meaningful order, and so can never be resolved through an ordering index.
+    pub fn get_idx_ord_key(&self) -> Option<String> {
+        match self {
+            PartialValue::Uint32(u) => Some(idx_ord_key_uint32(*u)),
+            PartialValue::DateTime(odt) => Some(idx_ord_key_datetime(odt)),
+            PartialValue::Cid(cid) => Some(idx_ord_key_cid(cid)),
+            _ => None,
+        }
+    }
+}
+
+// Ordering index keys are compared as strings by the backend, so they must sort in the
+// same order as the values they were generated from.
+
+pub(crate) fn idx_ord_key_uint32(u: u32) -> String {
+    format!("{u:010}")
+}
+
+pub(crate) fn idx_ord_key_datetime(odt: &OffsetDateTime) -> String {
+    // Flip the sign bit so that times before the epoch sort before those after it.
+    let nanos = (odt.unix_timestamp_nanos() as u128) ^ (1 << 127);
+    format!("{nanos:032x}")
+}
+
+pub(crate) fn idx_ord_key_cid(cid: &Cid) -> String {
+    // The display of a cid is already zero padded in (ts, s_uuid) order.
+    cid.to_string()
+}
+
+/// A substring assertion in the style of ldap. A value matches if it starts with `initial`,
+/// then contains each element of `any` in order without overlapping, and ends with `final_`.
+/// Every element must match within the same value.
+#[derive(Hash, Debug, Clone, Eq, Ord, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct SubStringPattern {
+    pub initial: Option<String>,
+    pub any: Vec<String>,
+    pub final_: Option<String>,
+}
+
+impl SubStringPattern {
+    pub fn new(initial: Option<String>, any: Vec<String>, final_: Option<String>) -> Self {
+        SubStringPattern {
+            initial,
+            any,
+            final_,
+        }
+    }
+
+    /// A pattern with no elements matches every value.
+    pub fn is_empty(&self) -> bool {
+        self.initial.is_none() && self.any.is_empty() && self.final_.is_none()
+    }
+
+    /// The pattern for an attribute with a case insensitive syntax.
+    pub fn to_lowercase(&self) -> Self {
+        SubStringPattern {
+            initial: self.initial.as_ref().map(|s| s.to_lowercase()),
+            any: self.any.iter().map(|s| s.to_lowercase()).collect(),
+            final_: self.final_.as_ref().map(|s| s.to_lowercase()),
+        }
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        let mut start = 0;
+        let mut end = value.len();
+
+        if let Some(initial) = &self.initial {
+            if !value.starts_with(initial.as_str()) {
+                return false;
+            }
+            start = initial.len();
+        }
+
+        if let Some(final_) = &self.final_ {
+            if !value.ends_with(final_.as_str()) {
+                return false;
+            }
+            end = value.len() - final_.len();
+        }
+
+        // The initial and final elements may not overlap.
+        if end < start {
+            return false;
+        }
+
+        self.any
+            .iter()
+            .try_fold(start, |pos, part| {
+                value
+                    .get(pos..end)
+                    .and_then(|remain| remain.find(part.as_str()))
+                    .map(|idx| pos + idx + part.len())
+            })
+            .is_some()
+    }
+}
+
+/// A rule for comparing strings more loosely than exact equality. Both sides of the comparison
+/// are normalised by the rule before they are compared.
+#[derive(Hash, Debug, Clone, Copy, Eq, Ord, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum MatchingRule {
+    /// Equal when case is ignored.
+    CaseIgnore,
+    /// Equal when case, whitespace and punctuation are ignored, so that "Alice Smith",
+    /// "alice.smith" and "alicesmith" are all approximately equal.
+    Approximate,
+}
+
+impl MatchingRule {
+    pub fn normalise(&self, s: &str) -> String {
+        match self {
+            MatchingRule::CaseIgnore => s.to_lowercase(),
+            MatchingRule::Approximate => s
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect(),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -930,6 +1063,8 @@ pub enum Value {
     Syntax(SyntaxType),
     Index(IndexType),
     Refer(Uuid),
+    /// A reference that is only valid until the datetime has passed.
+    ReferExpiring(Uuid, OffsetDateTime),
     JsonFilt(ProtoFilter),
     Cred(String, Credential),
     SshKey(String, String),
@@ -959,6 +1094,7 @@ pub enum Value {
     JwsKeyEs256(JwsSigner),
     JwsKeyRs256(JwsSigner),
     UiHint(UiHint),
+    CredentialType(CredentialKind),
 
     TotpSecret(String, Totp),
     AuditLogString(Cid, String),
@@ -1002,6 +1138,10 @@ impl PartialEq for Value {
             (Value::Url(a), Value::Url(b)) => a.eq(b),
             // OauthScopeMap
             (Value::OauthScopeMap(a, c), Value::OauthScopeMap(b, d)) => a.eq(b) && c.eq(d),
+            // ReferExpiring
+            (Value::ReferExpiring(a, c), Value::ReferExpiring(b, d)) => a.eq(b) && c.eq(d),
+            // CredentialType
+            (Value::CredentialType(a), Value::CredentialType(b)) => a.eq(b),
 
             (Value::Image(image1), Value::Image(image2)) => {
                 image1.hash_imagevalue().eq(&image2.hash_imagevalue())
@@ -1212,6 +1352,10 @@ impl Value {
         matches!(self, Value::Refer(_))
     }
 
+    pub fn new_refer_expiring(u: Uuid, expiry: OffsetDateTime) -> Self {
+        Value::ReferExpiring(u, expiry.to_offset(time::UtcOffset::UTC))
+    }
+
     pub fn new_json_filter_s(s: &str) -> Option<Self> {
         serde_json::from_str(s).map(Value::JsonFilt).ok()
     }
@@ -1508,6 +1652,7 @@ impl Value {
         match &self {
             Value::Refer(u) => Some(*u),
             Value::OauthScopeMap(u, _) => Some(*u),
+            Value::ReferExpiring(u, _) => Some(*u),
             // We need to assert that our reference to our rs exists.
             Value::Oauth2Session(_, m) => Some(m.rs_uuid),
             _ => None,
@@ -1751,7 +1896,9 @@ impl Value {
             }
             // These have stricter validators so not needed.
             Value::Nsuniqueid(s) => NSUNIQUEID_RE.is_match(s),
-            Value::DateTime(odt) => odt.offset() == time::UtcOffset::UTC,
+            Value::DateTime(odt) | Value::ReferExpiring(_, odt) => {
+                odt.offset() == time::UtcOffset::UTC
+            }
             Value::EmailAddress(mail, _) => VALIDATE_EMAIL_RE.is_match(mail.as_str()),
             Value::OauthScope(s) => OAUTHSCOPE_RE.is_match(s),
             Value::OauthScopeMap(_, m) => m.iter().all(|s| OAUTHSCOPE_RE.is_match(s)),
@@ -1776,7 +1923,8 @@ impl Value {
             | Value::Oauth2Session(_, _)
             | Value::JwsKeyRs256(_)
             | Value::EcKeyPrivate(_)
-            | Value::UiHint(_) => true,
+            | Value::UiHint(_)
+            | Value::CredentialType(_) => true,
         }
     }
 
@@ -1840,6 +1988,9 @@ mod tests {
         let r3 = IndexType::try_from("SUBSTRING");
         assert_eq!(r3, Ok(IndexType::SubString));
 
+        let r5 = IndexType::try_from("ORDERING");
+        assert_eq!(r5, Ok(IndexType::Ordering));
+
         let r4 = IndexType::try_from("thaoeusaneuh");
         assert_eq!(r4, Err(()));
     }
@@ -2028,6 +2179,78 @@ mod tests {
         assert!(val3.validate());
     }
 
+    #[test]
+    fn test_value_idx_ord_key() {
+        // Ordering keys must sort in the same order as their values.
+        let uints: Vec<_> = [0, 9, 10, 4000, u32::MAX]
+            .into_iter()
+            .map(|u| PartialValue::new_uint32(u).get_idx_ord_key().unwrap())
+            .collect();
+        assert!(uints.windows(2).all(|w| w[0] < w[1]));
+
+        let times: Vec<_> = [
+            "1901-01-01T00:00:00Z",
+            "1969-12-31T23:59:59Z",
+            "1970-01-01T00:00:00Z",
+            "2020-09-25T11:22:01+10:00",
+            "2020-09-25T01:22:02Z",
+            "2020-09-25T01:22:02.5Z",
+        ]
+        .into_iter()
+        .map(|s| {
+            PartialValue::new_datetime_s(s)
+                .and_then(|pv| pv.get_idx_ord_key())
+                .unwrap()
+        })
+        .collect();
+        assert!(times.windows(2).all(|w| w[0] < w[1]));
+
+        let cids: Vec<_> = [9, 10, 1_000_000_000]
+            .into_iter()
+            .map(|c| {
+                PartialValue::new_cid(Cid::new_count(c))
+                    .get_idx_ord_key()
+                    .unwrap()
+            })
+            .collect();
+        assert!(cids.windows(2).all(|w| w[0] < w[1]));
+
+        assert!(PartialValue::new_utf8s("a").get_idx_ord_key().is_none());
+    }
+
+    #[test]
+    fn test_value_substring_pattern() {
+        let p = |initial: Option<&str>, any: &[&str], final_: Option<&str>| {
+            SubStringPattern::new(
+                initial.map(str::to_string),
+                any.iter().map(|s| s.to_string()).collect(),
+                final_.map(str::to_string),
+            )
+        };
+
+        assert!(p(None, &[], None).matches("anything"));
+        assert!(p(Some("jo"), &[], None).matches("john"));
+        assert!(!p(Some("jo"), &[], None).matches("ajohn"));
+        assert!(p(None, &[], Some("hn")).matches("john"));
+        assert!(p(Some("j"), &["o", "h"], Some("n")).matches("john"));
+        // Any elements must be in order.
+        assert!(!p(None, &["h", "o"], None).matches("john"));
+        // Elements can't overlap each other.
+        assert!(!p(Some("joh"), &[], Some("hn")).matches("john"));
+        assert!(!p(Some("jo"), &["oh"], None).matches("john"));
+        assert!(p(Some("jo"), &["oh"], None).matches("jooh"));
+        assert!(!p(None, &["o"], Some("hn")).matches("jhn"));
+
+        assert!(p(Some("JO"), &[], None).to_lowercase().matches("john"));
+
+        let approx = MatchingRule::Approximate;
+        assert!(approx.normalise("Alice Smith") == approx.normalise("alice.smith"));
+        assert!(approx.normalise("Alice Smith") != approx.normalise("Alice Smyth"));
+        let ci = MatchingRule::CaseIgnore;
+        assert!(ci.normalise("Alice Smith") == ci.normalise("alice smith"));
+        assert!(ci.normalise("Alice Smith") != ci.normalise("alice.smith"));
+    }
+
     #[test]
     fn test_value_email_address() {
         // https://html.spec.whatwg.org/multipage/forms.html#valid-e-mail-address
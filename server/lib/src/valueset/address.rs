@@ -133,6 +133,10 @@ impl ValueSetT for ValueSetAddress {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -348,14 +352,25 @@ impl ValueSetT for ValueSetEmailAddress {
         }
     }
 
-    fn substring(&self, _pv: &PartialValue) -> bool {
-        false
+    fn substring(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::EmailAddress(s2) => self.set.iter().any(|s1| s1.contains(s2)),
+            _ => false,
+        }
+    }
+
+    fn substring_pattern(&self, pattern: &SubStringPattern) -> bool {
+        self.set.iter().any(|s| pattern.matches(s))
     }
 
     fn lessthan(&self, _pv: &PartialValue) -> bool {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
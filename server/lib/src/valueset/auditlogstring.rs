@@ -88,6 +88,10 @@ impl ValueSetT for ValueSetAuditLogString {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.map.len()
     }
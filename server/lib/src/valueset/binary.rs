@@ -78,6 +78,10 @@ impl ValueSetT for ValueSetPrivateBinary {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -231,6 +235,10 @@ impl ValueSetT for ValueSetPublicBinary {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.map.len()
     }
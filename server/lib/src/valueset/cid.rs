@@ -5,6 +5,7 @@ use crate::prelude::*;
 use crate::repl::cid::Cid;
 use crate::repl::proto::{ReplAttrV1, ReplCidV1};
 use crate::schema::SchemaAttribute;
+use crate::value::idx_ord_key_cid;
 use crate::valueset::{DbValueSetV2, ValueSet};
 
 #[derive(Debug, Clone)]
@@ -93,6 +94,13 @@ impl ValueSetT for ValueSetCid {
         }
     }
 
+    fn greaterthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Cid(c2) => self.set.iter().any(|c1| c1 > c2),
+            _ => false,
+        }
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -101,6 +109,10 @@ impl ValueSetT for ValueSetCid {
         Vec::with_capacity(0)
     }
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        self.set.iter().map(idx_ord_key_cid).collect()
+    }
+
     fn syntax(&self) -> SyntaxType {
         SyntaxType::Cid
     }
@@ -4,6 +4,7 @@ use time::OffsetDateTime;
 use crate::prelude::*;
 use crate::repl::proto::ReplAttrV1;
 use crate::schema::SchemaAttribute;
+use crate::value::idx_ord_key_datetime;
 use crate::valueset::{DbValueSetV2, ValueSet};
 
 #[derive(Debug, Clone)]
@@ -91,8 +92,18 @@ impl ValueSetT for ValueSetDateTime {
         false
     }
 
-    fn lessthan(&self, _pv: &PartialValue) -> bool {
-        false
+    fn lessthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::DateTime(u) => self.set.iter().any(|odt| odt < u),
+            _ => false,
+        }
+    }
+
+    fn greaterthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::DateTime(u) => self.set.iter().any(|odt| odt > u),
+            _ => false,
+        }
     }
 
     fn len(&self) -> usize {
@@ -111,6 +122,10 @@ impl ValueSetT for ValueSetDateTime {
             .collect()
     }
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        self.set.iter().map(idx_ord_key_datetime).collect()
+    }
+
     fn syntax(&self) -> SyntaxType {
         SyntaxType::DateTime
     }
@@ -378,6 +378,10 @@ impl ValueSetT for ValueSetImage {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
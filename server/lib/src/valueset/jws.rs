@@ -105,6 +105,10 @@ impl ValueSetT for ValueSetJwsKeyEs256 {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -286,6 +290,10 @@ impl ValueSetT for ValueSetJwsKeyRs256 {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
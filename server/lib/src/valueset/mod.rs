@@ -17,7 +17,7 @@ use kanidm_proto::v1::Filter as ProtoFilter;
 use kanidm_proto::v1::UiHint;
 
 use crate::be::dbvalue::DbValueSetV2;
-use crate::credential::{totp::Totp, Credential};
+use crate::credential::{totp::Totp, Credential, CredentialKind};
 use crate::prelude::*;
 use crate::repl::{cid::Cid, proto::ReplAttrV1};
 use crate::schema::SchemaAttribute;
@@ -29,6 +29,7 @@ pub use self::binary::{ValueSetPrivateBinary, ValueSetPublicBinary};
 pub use self::bool::ValueSetBool;
 pub use self::cid::ValueSetCid;
 pub use self::cred::{ValueSetCredential, ValueSetDeviceKey, ValueSetIntentToken, ValueSetPasskey};
+pub use self::credtype::ValueSetCredentialType;
 pub use self::datetime::ValueSetDateTime;
 pub use self::eckey::ValueSetEcKeyPrivate;
 use self::image::ValueSetImage;
@@ -50,7 +51,7 @@ pub use self::uihint::ValueSetUiHint;
 pub use self::uint32::ValueSetUint32;
 pub use self::url::ValueSetUrl;
 pub use self::utf8::ValueSetUtf8;
-pub use self::uuid::{ValueSetRefer, ValueSetUuid};
+pub use self::uuid::{ValueSetRefer, ValueSetReferExpiring, ValueSetUuid};
 
 mod address;
 mod auditlogstring;
@@ -58,6 +59,7 @@ mod binary;
 mod bool;
 mod cid;
 mod cred;
+mod credtype;
 mod datetime;
 pub mod eckey;
 pub mod image;
@@ -111,10 +113,27 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
 
     fn lessthan(&self, pv: &PartialValue) -> bool;
 
+    fn greaterthan(&self, pv: &PartialValue) -> bool;
+
+    fn substring_pattern(&self, _pattern: &SubStringPattern) -> bool {
+        // Only string types can be matched by a substring pattern.
+        false
+    }
+
+    fn equality_rule(&self, _rule: MatchingRule, pv: &PartialValue) -> bool {
+        // Most types have no looser form of equality.
+        self.contains(pv)
+    }
+
     fn len(&self) -> usize;
 
     fn generate_idx_eq_keys(&self) -> Vec<String>;
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        // Most types have no meaningful order, so can not be ordering indexed.
+        Vec::with_capacity(0)
+    }
+
     fn syntax(&self) -> SyntaxType;
 
     fn validate(&self, schema_attr: &SchemaAttribute) -> bool;
@@ -228,6 +247,10 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
         None
     }
 
+    fn as_refer_expiring_map(&self) -> Option<&BTreeMap<Uuid, OffsetDateTime>> {
+        None
+    }
+
     fn as_bool_set(&self) -> Option<&SmolSet<[bool; 1]>> {
         debug_assert!(false);
         None
@@ -559,6 +582,11 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
         None
     }
 
+    fn as_credtype_set(&self) -> Option<&BTreeSet<CredentialKind>> {
+        debug_assert!(false);
+        None
+    }
+
     fn as_audit_log_string(&self) -> Option<&BTreeMap<Cid, String>> {
         debug_assert!(false);
         None
@@ -624,6 +652,7 @@ pub fn from_result_value_iter(
         Value::Iname(s) => ValueSetIname::new(&s),
         Value::Uuid(u) => ValueSetUuid::new(u),
         Value::Refer(u) => ValueSetRefer::new(u),
+        Value::ReferExpiring(u, e) => ValueSetReferExpiring::new(u, e),
         Value::Bool(u) => ValueSetBool::new(u),
         Value::Uint32(u) => ValueSetUint32::new(u),
         Value::Syntax(u) => ValueSetSyntax::new(u),
@@ -646,6 +675,7 @@ pub fn from_result_value_iter(
         Value::IntentToken(u, s) => ValueSetIntentToken::new(u, s),
         Value::EmailAddress(a, _) => ValueSetEmailAddress::new(a),
         Value::UiHint(u) => ValueSetUiHint::new(u),
+        Value::CredentialType(k) => ValueSetCredentialType::new(k),
         Value::AuditLogString(c, s) => ValueSetAuditLogString::new((c, s)),
         Value::EcKeyPrivate(k) => ValueSetEcKeyPrivate::new(&k),
         Value::Image(imagevalue) => image::ValueSetImage::new(imagevalue),
@@ -683,6 +713,7 @@ pub fn from_value_iter(mut iter: impl Iterator<Item = Value>) -> Result<ValueSet
         Value::Iname(s) => ValueSetIname::new(&s),
         Value::Uuid(u) => ValueSetUuid::new(u),
         Value::Refer(u) => ValueSetRefer::new(u),
+        Value::ReferExpiring(u, e) => ValueSetReferExpiring::new(u, e),
         Value::Bool(u) => ValueSetBool::new(u),
         Value::Uint32(u) => ValueSetUint32::new(u),
         Value::Syntax(u) => ValueSetSyntax::new(u),
@@ -712,6 +743,7 @@ pub fn from_value_iter(mut iter: impl Iterator<Item = Value>) -> Result<ValueSet
         Value::ApiToken(u, m) => ValueSetApiToken::new(u, m),
         Value::Oauth2Session(u, m) => ValueSetOauth2Session::new(u, m),
         Value::UiHint(u) => ValueSetUiHint::new(u),
+        Value::CredentialType(k) => ValueSetCredentialType::new(k),
         Value::TotpSecret(l, t) => ValueSetTotpSecret::new(l, t),
         Value::AuditLogString(c, s) => ValueSetAuditLogString::new((c, s)),
         Value::EcKeyPrivate(k) => ValueSetEcKeyPrivate::new(&k),
@@ -736,6 +768,7 @@ pub fn from_db_valueset_v2(dbvs: DbValueSetV2) -> Result<ValueSet, OperationErro
         DbValueSetV2::Iname(set) => ValueSetIname::from_dbvs2(set),
         DbValueSetV2::Uuid(set) => ValueSetUuid::from_dbvs2(set),
         DbValueSetV2::Reference(set) => ValueSetRefer::from_dbvs2(set),
+        DbValueSetV2::ReferenceExpiring(set) => ValueSetReferExpiring::from_dbvs2(set),
         DbValueSetV2::Bool(set) => ValueSetBool::from_dbvs2(set),
         DbValueSetV2::Uint32(set) => ValueSetUint32::from_dbvs2(set),
         DbValueSetV2::SyntaxType(set) => ValueSetSyntax::from_dbvs2(set),
@@ -765,6 +798,7 @@ pub fn from_db_valueset_v2(dbvs: DbValueSetV2) -> Result<ValueSet, OperationErro
         DbValueSetV2::JwsKeyEs256(set) => ValueSetJwsKeyEs256::from_dbvs2(&set),
         DbValueSetV2::JwsKeyRs256(set) => ValueSetJwsKeyEs256::from_dbvs2(&set),
         DbValueSetV2::UiHint(set) => ValueSetUiHint::from_dbvs2(set),
+        DbValueSetV2::CredentialType(set) => ValueSetCredentialType::from_dbvs2(set),
         DbValueSetV2::TotpSecret(set) => ValueSetTotpSecret::from_dbvs2(set),
         DbValueSetV2::AuditLogString(set) => ValueSetAuditLogString::from_dbvs2(set),
         DbValueSetV2::EcKeyPrivate(key) => ValueSetEcKeyPrivate::from_dbvs2(&key),
@@ -790,11 +824,13 @@ pub fn from_repl_v1(rv1: &ReplAttrV1) -> Result<ValueSet, OperationError> {
         ReplAttrV1::PrivateBinary { set } => ValueSetPrivateBinary::from_repl_v1(set),
         ReplAttrV1::SecretValue { set } => ValueSetSecret::from_repl_v1(set),
         ReplAttrV1::Reference { set } => ValueSetRefer::from_repl_v1(set),
+        ReplAttrV1::ReferenceExpiring { set } => ValueSetReferExpiring::from_repl_v1(set),
         ReplAttrV1::JwsKeyEs256 { set } => ValueSetJwsKeyEs256::from_repl_v1(set),
         ReplAttrV1::JwsKeyRs256 { set } => ValueSetJwsKeyRs256::from_repl_v1(set),
         ReplAttrV1::Spn { set } => ValueSetSpn::from_repl_v1(set),
         ReplAttrV1::JsonFilter { set } => ValueSetJsonFilter::from_repl_v1(set),
         ReplAttrV1::UiHint { set } => ValueSetUiHint::from_repl_v1(set),
+        ReplAttrV1::CredentialType { set } => ValueSetCredentialType::from_repl_v1(set),
         ReplAttrV1::Address { set } => ValueSetAddress::from_repl_v1(set),
         ReplAttrV1::EmailAddress { primary, set } => {
             ValueSetEmailAddress::from_repl_v1(primary, set)
@@ -84,6 +84,10 @@ impl ValueSetT for ValueSetNsUniqueId {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
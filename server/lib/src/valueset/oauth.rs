@@ -86,6 +86,10 @@ impl ValueSetT for ValueSetOauthScope {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -255,6 +259,10 @@ impl ValueSetT for ValueSetOauthScopeMap {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.map.len()
     }
@@ -85,10 +85,18 @@ impl ValueSetT for ValueSetRestricted {
         }
     }
 
+    fn substring_pattern(&self, pattern: &SubStringPattern) -> bool {
+        self.set.iter().any(|s| pattern.matches(s))
+    }
+
     fn lessthan(&self, _pv: &PartialValue) -> bool {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -14,7 +14,10 @@ use crate::repl::proto::{
     ReplSessionScopeV1, ReplSessionStateV1, ReplSessionV1,
 };
 use crate::schema::SchemaAttribute;
-use crate::value::{ApiToken, ApiTokenScope, Oauth2Session, Session, SessionScope, SessionState};
+use crate::value::{
+    idx_ord_key_datetime, ApiToken, ApiTokenScope, Oauth2Session, Session, SessionScope,
+    SessionState,
+};
 use crate::valueset::{uuid_to_proto_string, DbValueSetV2, ValueSet};
 
 #[derive(Debug, Clone)]
@@ -435,8 +438,24 @@ impl ValueSetT for ValueSetSession {
         false
     }
 
-    fn lessthan(&self, _pv: &PartialValue) -> bool {
-        false
+    fn lessthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::DateTime(odt) => self
+                .map
+                .values()
+                .any(|s| matches!(&s.state, SessionState::ExpiresAt(e) if e < odt)),
+            _ => false,
+        }
+    }
+
+    fn greaterthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::DateTime(odt) => self
+                .map
+                .values()
+                .any(|s| matches!(&s.state, SessionState::ExpiresAt(e) if e > odt)),
+            _ => false,
+        }
     }
 
     fn len(&self) -> usize {
@@ -450,6 +469,16 @@ impl ValueSetT for ValueSetSession {
             .collect()
     }
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        self.map
+            .values()
+            .filter_map(|s| match &s.state {
+                SessionState::ExpiresAt(e) => Some(idx_ord_key_datetime(e)),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn syntax(&self) -> SyntaxType {
         SyntaxType::Session
     }
@@ -1039,8 +1068,24 @@ impl ValueSetT for ValueSetOauth2Session {
         false
     }
 
-    fn lessthan(&self, _pv: &PartialValue) -> bool {
-        false
+    fn lessthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::DateTime(odt) => self
+                .map
+                .values()
+                .any(|s| matches!(&s.state, SessionState::ExpiresAt(e) if e < odt)),
+            _ => false,
+        }
+    }
+
+    fn greaterthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::DateTime(odt) => self
+                .map
+                .values()
+                .any(|s| matches!(&s.state, SessionState::ExpiresAt(e) if e > odt)),
+            _ => false,
+        }
     }
 
     fn len(&self) -> usize {
@@ -1060,6 +1105,16 @@ impl ValueSetT for ValueSetOauth2Session {
         idx_keys
     }
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        self.map
+            .values()
+            .filter_map(|s| match &s.state {
+                SessionState::ExpiresAt(e) => Some(idx_ord_key_datetime(e)),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn syntax(&self) -> SyntaxType {
         SyntaxType::Oauth2Session
     }
@@ -1467,8 +1522,24 @@ impl ValueSetT for ValueSetApiToken {
         false
     }
 
-    fn lessthan(&self, _pv: &PartialValue) -> bool {
-        false
+    fn lessthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::DateTime(odt) => self
+                .map
+                .values()
+                .any(|t| t.expiry.as_ref().map_or(false, |e| e < odt)),
+            _ => false,
+        }
+    }
+
+    fn greaterthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::DateTime(odt) => self
+                .map
+                .values()
+                .any(|t| t.expiry.as_ref().map_or(false, |e| e > odt)),
+            _ => false,
+        }
     }
 
     fn len(&self) -> usize {
@@ -1482,6 +1553,13 @@ impl ValueSetT for ValueSetApiToken {
             .collect()
     }
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        self.map
+            .values()
+            .filter_map(|t| t.expiry.as_ref().map(idx_ord_key_datetime))
+            .collect()
+    }
+
     fn syntax(&self) -> SyntaxType {
         SyntaxType::ApiToken
     }
@@ -1616,10 +1694,11 @@ impl ValueSetT for ValueSetApiToken {
 #[cfg(test)]
 mod tests {
     use super::{ValueSetOauth2Session, ValueSetSession};
-    use crate::prelude::{IdentityId, SessionScope, Uuid};
+    use crate::prelude::{IdentityId, PartialValue, SessionScope, Uuid};
     use crate::repl::cid::Cid;
-    use crate::value::{Oauth2Session, Session, SessionState};
+    use crate::value::{idx_ord_key_datetime, Oauth2Session, Session, SessionState};
     use crate::valueset::ValueSet;
+    use std::time::Duration;
     use time::OffsetDateTime;
 
     #[test]
@@ -1653,6 +1732,51 @@ mod tests {
         assert_eq!(session.state, SessionState::RevokedAt(zero_cid));
     }
 
+    #[test]
+    fn test_valueset_session_expiry_ordering() {
+        let expiry = OffsetDateTime::UNIX_EPOCH + Duration::from_secs(3600);
+
+        let vs: ValueSet = ValueSetSession::from_iter([
+            (
+                Uuid::new_v4(),
+                Session {
+                    label: "hacks".to_string(),
+                    state: SessionState::ExpiresAt(expiry),
+                    issued_at: OffsetDateTime::UNIX_EPOCH,
+                    issued_by: IdentityId::Internal,
+                    cred_id: Uuid::new_v4(),
+                    scope: SessionScope::ReadOnly,
+                },
+            ),
+            (
+                Uuid::new_v4(),
+                Session {
+                    label: "hacks".to_string(),
+                    state: SessionState::NeverExpires,
+                    issued_at: OffsetDateTime::UNIX_EPOCH,
+                    issued_by: IdentityId::Internal,
+                    cred_id: Uuid::new_v4(),
+                    scope: SessionScope::ReadOnly,
+                },
+            ),
+        ])
+        .unwrap();
+
+        // Only sessions with an expiry are ordered.
+        assert_eq!(
+            vs.generate_idx_ord_keys(),
+            vec![idx_ord_key_datetime(&expiry)]
+        );
+
+        let before = PartialValue::DateTime(expiry - Duration::from_secs(1));
+        let after = PartialValue::DateTime(expiry + Duration::from_secs(1));
+
+        assert!(vs.lessthan(&after));
+        assert!(!vs.lessthan(&before));
+        assert!(vs.greaterthan(&before));
+        assert!(!vs.greaterthan(&after));
+    }
+
     #[test]
     fn test_valueset_session_merge_left() {
         let s_uuid = Uuid::new_v4();
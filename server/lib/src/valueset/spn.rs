@@ -83,6 +83,10 @@ impl ValueSetT for ValueSetSpn {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
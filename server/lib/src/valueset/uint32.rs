@@ -3,6 +3,7 @@ use smolset::SmolSet;
 use crate::prelude::*;
 use crate::repl::proto::ReplAttrV1;
 use crate::schema::SchemaAttribute;
+use crate::value::idx_ord_key_uint32;
 use crate::valueset::{DbValueSetV2, ValueSet};
 
 #[derive(Debug, Clone)]
@@ -86,6 +87,13 @@ impl ValueSetT for ValueSetUint32 {
         }
     }
 
+    fn greaterthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Uint32(u) => self.set.iter().any(|i| i > u),
+            _ => false,
+        }
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -94,6 +102,10 @@ impl ValueSetT for ValueSetUint32 {
         self.set.iter().map(|b| b.to_string()).collect()
     }
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        self.set.iter().copied().map(idx_ord_key_uint32).collect()
+    }
+
     fn syntax(&self) -> SyntaxType {
         SyntaxType::Uint32
     }
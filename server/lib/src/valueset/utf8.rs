@@ -71,8 +71,36 @@ impl ValueSetT for ValueSetUtf8 {
         }
     }
 
-    fn lessthan(&self, _pv: &PartialValue) -> bool {
-        false
+    fn substring_pattern(&self, pattern: &SubStringPattern) -> bool {
+        self.set.iter().any(|s| pattern.matches(s))
+    }
+
+    fn equality_rule(&self, rule: MatchingRule, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Utf8(s2) => {
+                let s2 = rule.normalise(s2);
+                self.set.iter().any(|s1| rule.normalise(s1) == s2)
+            }
+            _ => {
+                debug_assert!(false);
+                false
+            }
+        }
+    }
+
+    fn lessthan(&self, pv: &PartialValue) -> bool {
+        // Strings are ordered by their utf8 bytes.
+        match pv {
+            PartialValue::Utf8(s2) => self.set.iter().any(|s1| s1 < s2),
+            _ => false,
+        }
+    }
+
+    fn greaterthan(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Utf8(s2) => self.set.iter().any(|s1| s1 > s2),
+            _ => false,
+        }
     }
 
     fn len(&self) -> usize {
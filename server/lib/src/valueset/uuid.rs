@@ -1,6 +1,8 @@
-use std::collections::BTreeSet;
+use std::collections::btree_map::Entry as BTreeEntry;
+use std::collections::{BTreeMap, BTreeSet};
 
 use smolset::SmolSet;
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 use crate::repl::proto::ReplAttrV1;
@@ -85,6 +87,10 @@ impl ValueSetT for ValueSetUuid {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -245,6 +251,10 @@ impl ValueSetT for ValueSetRefer {
         false
     }
 
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
     fn len(&self) -> usize {
         self.set.len()
     }
@@ -320,3 +330,183 @@ impl ValueSetT for ValueSetRefer {
         Some(Box::new(self.set.iter().copied()))
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct ValueSetReferExpiring {
+    map: BTreeMap<Uuid, OffsetDateTime>,
+}
+
+impl ValueSetReferExpiring {
+    pub fn new(u: Uuid, expiry: OffsetDateTime) -> Box<Self> {
+        let mut map = BTreeMap::new();
+        map.insert(u, expiry);
+        Box::new(ValueSetReferExpiring { map })
+    }
+
+    fn parse_map<'a>(
+        data: impl Iterator<Item = (&'a Uuid, &'a String)>,
+    ) -> Result<BTreeMap<Uuid, OffsetDateTime>, OperationError> {
+        data.map(|(u, expiry)| {
+            OffsetDateTime::parse(expiry, &Rfc3339)
+                .map(|odt| (*u, odt.to_offset(time::UtcOffset::UTC)))
+                .map_err(|_| OperationError::InvalidValueState)
+        })
+        .collect()
+    }
+
+    pub fn from_dbvs2(data: Vec<(Uuid, String)>) -> Result<ValueSet, OperationError> {
+        let map = Self::parse_map(data.iter().map(|(u, expiry)| (u, expiry)))?;
+        Ok(Box::new(ValueSetReferExpiring { map }))
+    }
+
+    pub fn from_repl_v1(data: &[(Uuid, String)]) -> Result<ValueSet, OperationError> {
+        let map = Self::parse_map(data.iter().map(|(u, expiry)| (u, expiry)))?;
+        Ok(Box::new(ValueSetReferExpiring { map }))
+    }
+
+    fn expiry_to_string(expiry: &OffsetDateTime) -> String {
+        #[allow(clippy::expect_used)]
+        expiry
+            .format(&Rfc3339)
+            .expect("Failed to format timestamp into RFC3339")
+    }
+}
+
+impl ValueSetT for ValueSetReferExpiring {
+    fn insert_checked(&mut self, value: Value) -> Result<bool, OperationError> {
+        match value {
+            Value::ReferExpiring(u, expiry) => match self.map.entry(u) {
+                BTreeEntry::Vacant(e) => {
+                    e.insert(expiry);
+                    Ok(true)
+                }
+                // Granting a reference again replaces the expiry, so that it can be
+                // extended or shortened.
+                BTreeEntry::Occupied(mut e) => Ok(e.insert(expiry) != expiry),
+            },
+            _ => {
+                debug_assert!(false);
+                Err(OperationError::InvalidValueState)
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn remove(&mut self, pv: &PartialValue, _cid: &Cid) -> bool {
+        match pv {
+            PartialValue::Refer(u) => self.map.remove(u).is_some(),
+            _ => false,
+        }
+    }
+
+    fn contains(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Refer(u) => self.map.contains_key(u),
+            _ => false,
+        }
+    }
+
+    fn substring(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
+    fn lessthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
+    fn greaterthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn generate_idx_eq_keys(&self) -> Vec<String> {
+        self.map
+            .keys()
+            .map(|u| u.as_hyphenated().to_string())
+            .collect()
+    }
+
+    fn syntax(&self) -> SyntaxType {
+        SyntaxType::ReferenceUuidExpiring
+    }
+
+    fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
+        self.map
+            .values()
+            .all(|odt| odt.offset() == time::UtcOffset::UTC)
+    }
+
+    fn to_proto_string_clone_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(self.map.iter().map(|(u, expiry)| {
+            format!(
+                "{},{}",
+                uuid_to_proto_string(*u),
+                Self::expiry_to_string(expiry)
+            )
+        }))
+    }
+
+    fn to_db_valueset_v2(&self) -> DbValueSetV2 {
+        DbValueSetV2::ReferenceExpiring(
+            self.map
+                .iter()
+                .map(|(u, expiry)| (*u, Self::expiry_to_string(expiry)))
+                .collect(),
+        )
+    }
+
+    fn to_repl_v1(&self) -> ReplAttrV1 {
+        ReplAttrV1::ReferenceExpiring {
+            set: self
+                .map
+                .iter()
+                .map(|(u, expiry)| (*u, Self::expiry_to_string(expiry)))
+                .collect(),
+        }
+    }
+
+    fn to_partialvalue_iter(&self) -> Box<dyn Iterator<Item = PartialValue> + '_> {
+        Box::new(self.map.keys().copied().map(PartialValue::Refer))
+    }
+
+    fn to_value_iter(&self) -> Box<dyn Iterator<Item = Value> + '_> {
+        Box::new(
+            self.map
+                .iter()
+                .map(|(u, expiry)| Value::ReferExpiring(*u, *expiry)),
+        )
+    }
+
+    fn equal(&self, other: &ValueSet) -> bool {
+        if let Some(other) = other.as_refer_expiring_map() {
+            &self.map == other
+        } else {
+            debug_assert!(false);
+            false
+        }
+    }
+
+    fn merge(&mut self, other: &ValueSet) -> Result<(), OperationError> {
+        if let Some(b) = other.as_refer_expiring_map() {
+            mergemaps!(self.map, b)
+        } else {
+            debug_assert!(false);
+            Err(OperationError::InvalidValueState)
+        }
+    }
+
+    fn as_refer_expiring_map(&self) -> Option<&BTreeMap<Uuid, OffsetDateTime>> {
+        Some(&self.map)
+    }
+
+    fn as_ref_uuid_iter(&self) -> Option<Box<dyn Iterator<Item = Uuid> + '_>> {
+        // This is what ties us as a type that can be refint checked.
+        Some(Box::new(self.map.keys().copied()))
+    }
+}
@@ -85,6 +85,19 @@ pub fn pop_oauth2_authorisation_request() -> Option<AuthorisationRequest> {
     l.ok()
 }
 
+pub fn push_oauth2_device_user_code(r: String) {
+    TemporaryStorage::set("oauth2_device_user_code", r)
+        .expect_throw("failed to set oauth2_device_user_code in temporary storage");
+}
+
+pub fn pop_oauth2_device_user_code() -> Option<String> {
+    let l: Result<String, _> = TemporaryStorage::get("oauth2_device_user_code");
+    #[cfg(debug_assertions)]
+    console::debug!(format!("oauth2_device_user_code -> {:?}", l).as_str());
+    TemporaryStorage::delete("oauth2_device_user_code");
+    l.ok()
+}
+
 pub fn push_login_hint(r: String) {
     TemporaryStorage::set("login_hint", r).expect_throw("failed to set login hint");
 }
@@ -0,0 +1,501 @@
+//! The user facing half of the oauth2 device authorisation grant. The user enters the code
+//! displayed on their device, and then confirms that the device may access their account.
+
+use gloo::console;
+use kanidm_proto::oauth2::AuthorisationResponse;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::manager::Route;
+use crate::{do_request, error::*, RequestMethod};
+use crate::{models, utils};
+
+use std::collections::BTreeSet;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct DeviceQuery {
+    user_code: Option<String>,
+}
+
+enum State {
+    LoginRequired,
+    // We are in the process of check the auth token to be sure we can proceed.
+    TokenCheck,
+    EnterCode {
+        invalid: bool,
+    },
+    Processing,
+    Consent {
+        client_name: String,
+        pii_scopes: BTreeSet<String>,
+        consent_token: String,
+    },
+    Approved,
+    Denied,
+    AccessDenied(Option<String>),
+    ErrInvalidRequest,
+}
+
+pub struct Oauth2DeviceApp {
+    state: State,
+}
+
+#[derive(Debug)]
+pub enum Oauth2DeviceMsg {
+    LoginRequired,
+    LoginProceed,
+    TokenValid,
+    SubmitCode(String),
+    CodeInvalid,
+    Consent {
+        client_name: String,
+        pii_scopes: BTreeSet<String>,
+        consent_token: String,
+    },
+    Approve,
+    Deny,
+    Approved,
+    Denied,
+    AccessDenied {
+        kopid: Option<String>,
+    },
+    Error {
+        emsg: String,
+        kopid: Option<String>,
+    },
+}
+
+impl From<FetchError> for Oauth2DeviceMsg {
+    fn from(fe: FetchError) -> Self {
+        Oauth2DeviceMsg::Error {
+            emsg: fe.as_string(),
+            kopid: None,
+        }
+    }
+}
+
+impl Oauth2DeviceApp {
+    async fn fetch_session_valid() -> Result<Oauth2DeviceMsg, FetchError> {
+        let (kopid, status, value, _) =
+            do_request("/v1/auth/valid", RequestMethod::GET, None).await?;
+
+        if status == 200 {
+            Ok(Oauth2DeviceMsg::TokenValid)
+        } else if status == 401 {
+            Ok(Oauth2DeviceMsg::LoginRequired)
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Oauth2DeviceMsg::Error { emsg, kopid })
+        }
+    }
+
+    async fn fetch_user_code(user_code: String) -> Result<Oauth2DeviceMsg, FetchError> {
+        let user_code_jsvalue = serde_json::to_string(&user_code)
+            .map(|s| JsValue::from(&s))
+            .expect_throw("Failed to serialise user_code");
+
+        let (kopid, status, value, _) = do_request(
+            "/oauth2/device/authorise",
+            RequestMethod::POST,
+            Some(user_code_jsvalue),
+        )
+        .await?;
+
+        #[cfg(debug_assertions)]
+        console::debug!(&format!("fetch_user_code {}", status));
+
+        if status == 200 {
+            let state: AuthorisationResponse = serde_wasm_bindgen::from_value(value)
+                .map_err(|e| {
+                    let e_msg = format!("serde error -> {:?}", e);
+                    console::error!(e_msg.as_str());
+                })
+                .expect_throw("Invalid response type");
+            match state {
+                AuthorisationResponse::ConsentRequested {
+                    client_name,
+                    scopes: _,
+                    pii_scopes,
+                    consent_token,
+                } => Ok(Oauth2DeviceMsg::Consent {
+                    client_name,
+                    pii_scopes,
+                    consent_token,
+                }),
+                AuthorisationResponse::Permitted => Ok(Oauth2DeviceMsg::Error {
+                    emsg: "devices must always request consent".to_string(),
+                    kopid,
+                }),
+            }
+        } else if status == 401 {
+            Ok(Oauth2DeviceMsg::LoginRequired)
+        } else if status == 403 {
+            Ok(Oauth2DeviceMsg::AccessDenied { kopid })
+        } else if status == 404 {
+            Ok(Oauth2DeviceMsg::CodeInvalid)
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Oauth2DeviceMsg::Error { emsg, kopid })
+        }
+    }
+
+    async fn fetch_consent_decision(
+        consent_token: String,
+        approve: bool,
+    ) -> Result<Oauth2DeviceMsg, FetchError> {
+        let consentreq_jsvalue = serde_json::to_string(&consent_token)
+            .map(|s| JsValue::from(&s))
+            .expect_throw("Failed to serialise consent_req");
+
+        let uri = if approve {
+            "/oauth2/device/permit"
+        } else {
+            "/oauth2/device/reject"
+        };
+
+        let (kopid, status, value, _) =
+            do_request(uri, RequestMethod::POST, Some(consentreq_jsvalue)).await?;
+
+        if status == 200 {
+            if approve {
+                Ok(Oauth2DeviceMsg::Approved)
+            } else {
+                Ok(Oauth2DeviceMsg::Denied)
+            }
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Oauth2DeviceMsg::Error { emsg, kopid })
+        }
+    }
+}
+
+impl Component for Oauth2DeviceApp {
+    type Message = Oauth2DeviceMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        #[cfg(debug_assertions)]
+        console::debug!("oauth2_device::create");
+
+        // The verification_uri_complete contains the user code, so the user doesn't need
+        // to type it. Keep it aside in case we need to log in first.
+        let query: Option<DeviceQuery> = ctx
+            .link()
+            .location()
+            .expect_throw("Can't access browser current location")
+            .query()
+            .ok();
+
+        if let Some(user_code) = query.and_then(|q| q.user_code) {
+            models::push_oauth2_device_user_code(user_code);
+        }
+
+        add_body_form_classes!();
+
+        ctx.link().send_future(async {
+            match Self::fetch_session_valid().await {
+                Ok(v) => v,
+                Err(v) => v.into(),
+            }
+        });
+
+        Oauth2DeviceApp {
+            state: State::TokenCheck,
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>, _props: &Self::Properties) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!("oauth2_device::change");
+        false
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!(&format!("oauth2_device::update {:?}", msg));
+
+        match msg {
+            Oauth2DeviceMsg::LoginRequired => {
+                self.state = State::LoginRequired;
+                true
+            }
+            Oauth2DeviceMsg::LoginProceed => {
+                models::push_return_location(models::Location::Manager(Route::Oauth2Device));
+
+                ctx.link()
+                    .navigator()
+                    .expect_throw("failed to read history")
+                    .push(&Route::Login);
+                // Don't need to redraw as we are yolo-ing out.
+                false
+            }
+            Oauth2DeviceMsg::TokenValid => {
+                self.state = match models::pop_oauth2_device_user_code() {
+                    Some(user_code) => {
+                        ctx.link().send_future(async {
+                            match Self::fetch_user_code(user_code).await {
+                                Ok(v) => v,
+                                Err(v) => v.into(),
+                            }
+                        });
+                        State::Processing
+                    }
+                    None => State::EnterCode { invalid: false },
+                };
+                true
+            }
+            Oauth2DeviceMsg::SubmitCode(user_code) => {
+                ctx.link().send_future(async {
+                    match Self::fetch_user_code(user_code).await {
+                        Ok(v) => v,
+                        Err(v) => v.into(),
+                    }
+                });
+                self.state = State::Processing;
+                true
+            }
+            Oauth2DeviceMsg::CodeInvalid => {
+                self.state = State::EnterCode { invalid: true };
+                true
+            }
+            Oauth2DeviceMsg::Consent {
+                client_name,
+                pii_scopes,
+                consent_token,
+            } => {
+                self.state = State::Consent {
+                    client_name,
+                    pii_scopes,
+                    consent_token,
+                };
+                true
+            }
+            Oauth2DeviceMsg::Approve | Oauth2DeviceMsg::Deny => {
+                let approve = matches!(msg, Oauth2DeviceMsg::Approve);
+                self.state = match &self.state {
+                    State::Consent { consent_token, .. } => {
+                        let cr_c = consent_token.clone();
+                        ctx.link().send_future(async move {
+                            match Self::fetch_consent_decision(cr_c, approve).await {
+                                Ok(v) => v,
+                                Err(v) => v.into(),
+                            }
+                        });
+                        State::Processing
+                    }
+                    _ => {
+                        console::error!("Invalid state transition");
+                        State::ErrInvalidRequest
+                    }
+                };
+                true
+            }
+            Oauth2DeviceMsg::Approved => {
+                self.state = State::Approved;
+                true
+            }
+            Oauth2DeviceMsg::Denied => {
+                self.state = State::Denied;
+                true
+            }
+            Oauth2DeviceMsg::AccessDenied { kopid } => {
+                console::error!(format!("opid - {:?}", kopid).as_str());
+                self.state = State::AccessDenied(kopid);
+                true
+            }
+            Oauth2DeviceMsg::Error { emsg, kopid } => {
+                self.state = State::ErrInvalidRequest;
+                console::error!(format!("opid - {:?}, msg - {}", kopid, emsg).as_str());
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        #[cfg(debug_assertions)]
+        console::debug!("oauth2_device::view");
+
+        let body_content = match &self.state {
+            State::LoginRequired => {
+                html! {
+                    <form
+                      onsubmit={ ctx.link().callback(|e: SubmitEvent| {
+                          console::debug!("oauth2_device::view -> LoginRequired - prevent_default()");
+                          e.prevent_default();
+                          Oauth2DeviceMsg::LoginProceed
+                      } ) }
+                      action="javascript:void(0);"
+                    >
+                      <h1 class="h3 mb-3 fw-normal">
+                        {"Sign in to connect your device" }
+                        </h1>
+                      <button autofocus=true class="w-100 btn btn-lg btn-primary" type="submit">
+                        { "Sign in" }
+                      </button>
+                    </form>
+                }
+            }
+            State::EnterCode { invalid } => {
+                let invalid_msg = if *invalid {
+                    html! {
+                        <div class="alert alert-danger" role="alert">
+                        { "That code was not recognised, or has expired. Check the code shown on your device and try again." }
+                        </div>
+                    }
+                } else {
+                    html! {}
+                };
+
+                html! {
+                    <form
+                      onsubmit={ ctx.link().callback(|e: SubmitEvent| {
+                          console::debug!("oauth2_device::view -> EnterCode - prevent_default()");
+                          e.prevent_default();
+                          let user_code = utils::get_value_from_element_id("user_code")
+                              .unwrap_or_default();
+                          Oauth2DeviceMsg::SubmitCode(user_code)
+                      } ) }
+                      action="javascript:void(0);"
+                    >
+                      <h2 class="h3 mb-3 fw-normal">{ "Connect a Device" }</h2>
+                      <p>{ "Enter the code shown on your device." }</p>
+                      { invalid_msg }
+                      <div class="mb-3">
+                        <input
+                          autofocus=true
+                          autocomplete="off"
+                          class="form-control"
+                          id="user_code"
+                          name="user_code"
+                          placeholder="XXXX-XXXX"
+                          type="text"
+                        />
+                      </div>
+                      <button class="w-100 btn btn-lg btn-primary" type="submit">
+                        { "Continue" }
+                      </button>
+                    </form>
+                }
+            }
+            State::Consent {
+                client_name,
+                pii_scopes,
+                consent_token: _,
+            } => {
+                let pii_req = if pii_scopes.is_empty() {
+                    html! {
+                      <div>
+                        <p>{ "This device will not have access to your personal information." }</p>
+                      </div>
+                    }
+                } else {
+                    html! {
+                      <div>
+                        <p>{ "This device has requested to see the following personal information." }</p>
+                        <ul>
+                          {
+                            pii_scopes.iter().map(|s| html! { <li>{ s }</li> } ).collect::<Html>()
+                          }
+                        </ul>
+                      </div>
+                    }
+                };
+
+                html! {
+                      <form
+                        onsubmit={ ctx.link().callback(|e: SubmitEvent| {
+                            console::debug!("oauth2_device::view -> Consent - prevent_default()");
+                            e.prevent_default();
+                            Oauth2DeviceMsg::Approve
+                        } ) }
+                        action="javascript:void(0);"
+                      >
+                        <h2 class="h3 mb-3 fw-normal">{ "Connect a device to " }{ client_name }</h2>
+                        { pii_req }
+                        <p>{ "Only continue if you started this request, and the device is in front of you." }</p>
+
+                        <div class="text-center">
+                            <button autofocus=true class="w-100 btn btn-lg btn-primary mb-2" type="submit">{ "Allow" }</button>
+                            <button
+                              class="w-100 btn btn-lg btn-secondary"
+                              type="button"
+                              onclick={ ctx.link().callback(|_| Oauth2DeviceMsg::Deny) }
+                            >{ "Deny" }</button>
+                        </div>
+                      </form>
+                }
+            }
+            State::Approved => {
+                html! {
+                    <div class="alert alert-success" role="alert">
+                        <h2 class="text-center">{ "Your device is now connected" }</h2>
+                        <p class="text-center">{ "You can close this window." }</p>
+                    </div>
+                }
+            }
+            State::Denied => {
+                html! {
+                    <div class="alert alert-light" role="alert">
+                        <h2 class="text-center">{ "The device was denied access" }</h2>
+                        <p class="text-center">{ "You can close this window." }</p>
+                    </div>
+                }
+            }
+            State::Processing | State::TokenCheck => {
+                html! {
+                    <div class="alert alert-light" role="alert">
+                        <h2 class="text-center">{ "Processing ... " }</h2>
+                    </div>
+                }
+            }
+            State::AccessDenied(kopid) => {
+                html! {
+                    <div class="alert alert-danger" role="alert">
+                        <h1>{ "Access Denied" } </h1>
+                        <p>
+                        { "You do not have access to the requested resources." }
+                        </p>
+                        <p>
+                        { if let Some(opid) = kopid {
+                            format!("Operation ID: {}", opid)
+                          } else {
+                            "Operation ID: -".to_string()
+                          }
+                        }
+                        </p>
+                    </div>
+                }
+            }
+            State::ErrInvalidRequest => {
+                html! {
+                    <div class="alert alert-danger" role="alert">
+                        <h1>{ "Invalid request" } </h1>
+                        <p>
+                        { "Please close this window and try again from the beginning." }
+                        </p>
+                    </div>
+                }
+            }
+        };
+        html! {
+        <>
+            <main class="form-signin">
+            <center>
+                <img src="/pkg/img/logo-square.svg" alt="Kanidm" class="kanidm_logo"/>
+            </center>
+            <div class="container">
+            { body_content }
+            </div>
+            </main>
+            { crate::utils::do_footer() }
+        </>
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        console::debug!("oauth2_device::destroy");
+        remove_body_form_classes!();
+    }
+}
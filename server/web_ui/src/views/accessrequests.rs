@@ -0,0 +1,229 @@
+#[cfg(debug_assertions)]
+use gloo::console;
+use yew::prelude::*;
+
+use crate::constants::{CSS_CELL, CSS_PAGE_HEADER, CSS_TABLE};
+use crate::error::FetchError;
+use crate::{do_request, RequestMethod};
+use wasm_bindgen::prelude::*;
+
+use kanidm_proto::v1::{AccessRequest, AccessRequestState};
+use uuid::Uuid;
+
+pub enum Msg {
+    Ready { requests: Vec<AccessRequest> },
+    Decide { request: Uuid, approve: bool },
+    Error { emsg: String, kopid: Option<String> },
+}
+
+impl From<FetchError> for Msg {
+    fn from(fe: FetchError) -> Self {
+        Msg::Error {
+            emsg: fe.as_string(),
+            kopid: None,
+        }
+    }
+}
+
+pub enum State {
+    Waiting,
+    Ready { requests: Vec<AccessRequest> },
+    Error { emsg: String, kopid: Option<String> },
+}
+
+pub struct AccessRequestsApp {
+    state: State,
+}
+
+impl Component for AccessRequestsApp {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        #[cfg(debug_assertions)]
+        console::debug!("views::accessrequests::create");
+
+        ctx.link().send_future(async {
+            match Self::fetch_access_requests().await {
+                Ok(v) => v,
+                Err(v) => v.into(),
+            }
+        });
+
+        let state = State::Waiting;
+
+        AccessRequestsApp { state }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>, _props: &Self::Properties) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!("views::accessrequests::changed");
+        false
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!("views::accessrequests::update");
+        match msg {
+            Msg::Ready { mut requests } => {
+                // Show the requests that are waiting on a decision first.
+                requests.sort_by_key(|r| (r.state != AccessRequestState::Pending, r.uuid));
+                self.state = State::Ready { requests }
+            }
+            Msg::Decide { request, approve } => {
+                ctx.link().send_future(async move {
+                    match Self::decide_access_request(request, approve).await {
+                        Ok(v) => v,
+                        Err(v) => v.into(),
+                    }
+                });
+                self.state = State::Waiting;
+            }
+            Msg::Error { emsg, kopid } => self.state = State::Error { emsg, kopid },
+        }
+
+        true
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, _first_render: bool) {
+        #[cfg(debug_assertions)]
+        console::debug!("views::accessrequests::rendered");
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        match &self.state {
+            State::Waiting => self.view_waiting(),
+            State::Ready { requests } => self.view_ready(ctx, requests.as_slice()),
+            State::Error { emsg, kopid } => self.view_error(ctx, emsg, kopid.as_deref()),
+        }
+    }
+}
+
+impl AccessRequestsApp {
+    fn view_waiting(&self) -> Html {
+        html! {
+            <>
+              <div class="vert-center">
+                <div class="spinner-border text-dark" role="status">
+                  <span class="visually-hidden">{ "Loading..." }</span>
+                </div>
+              </div>
+            </>
+        }
+    }
+
+    fn view_ready(&self, ctx: &Context<Self>, requests: &[AccessRequest]) -> Html {
+        html! {
+            <>
+        <div class={CSS_PAGE_HEADER}>
+        <h2>{ "Access requests" }</h2>
+        </div>
+          if requests.is_empty() {
+            <div>
+              <h5>{ "No access requests" }</h5>
+            </div>
+          } else {
+            <table class={CSS_TABLE}>
+              <thead>
+                <tr>
+                  <th scope="col" class={CSS_CELL}>{ "Group" }</th>
+                  <th scope="col" class={CSS_CELL}>{ "Requester" }</th>
+                  <th scope="col" class={CSS_CELL}>{ "Justification" }</th>
+                  <th scope="col" class={CSS_CELL}>{ "State" }</th>
+                  <th scope="col" class={CSS_CELL}></th>
+                </tr>
+              </thead>
+              <tbody>
+                {
+                    requests.iter().map(|request| {
+                        let uuid = request.uuid;
+                        html!{
+                            <tr>
+                              <td class={CSS_CELL}>{ &request.group }</td>
+                              <td class={CSS_CELL}>{ &request.requester }</td>
+                              <td class={CSS_CELL}>{ &request.justification }</td>
+                              <td class={CSS_CELL}>
+                                { request.state.to_string() }
+                                if let Some(decided_by) = &request.decided_by {
+                                  { format!(" by {}", decided_by) }
+                                }
+                              </td>
+                              <td class={CSS_CELL}>
+                                if request.can_decide {
+                                  <button type="button" class="btn btn-success btn-sm me-1"
+                                    onclick={ ctx.link().callback(move |_| Msg::Decide { request: uuid, approve: true }) }
+                                    >{ "Approve" }</button>
+                                  <button type="button" class="btn btn-danger btn-sm"
+                                    onclick={ ctx.link().callback(move |_| Msg::Decide { request: uuid, approve: false }) }
+                                    >{ "Deny" }</button>
+                                }
+                              </td>
+                            </tr>
+                        }
+                    }).collect::<Html>()
+                }
+              </tbody>
+            </table>
+          }
+        </>
+        }
+    }
+
+    fn view_error(&self, _ctx: &Context<Self>, msg: &str, kopid: Option<&str>) -> Html {
+        html! {
+          <>
+            <p class="text-center">
+                <img src="/pkg/img/logo-square.svg" alt="Kanidm" class="kanidm_logo"/>
+            </p>
+            <div class="alert alert-danger" role="alert">
+              <h2>{ "An Error Occurred 🥺" }</h2>
+            <p>{ msg.to_string() }</p>
+            <p>
+                {
+                    if let Some(opid) = kopid.as_ref() {
+                        format!("Operation ID: {}", opid)
+                    } else {
+                        "Local Error".to_string()
+                    }
+                }
+            </p>
+            </div>
+            <p class="text-center">
+              <a href="/"><button href="/" class="btn btn-secondary" aria-label="Return home">{"Return to the home page"}</button></a>
+            </p>
+          </>
+        }
+    }
+
+    async fn fetch_access_requests() -> Result<Msg, FetchError> {
+        let (kopid, status, value, _) =
+            do_request("/v1/access_request", RequestMethod::GET, None).await?;
+
+        if status == 200 {
+            let requests: Vec<AccessRequest> = serde_wasm_bindgen::from_value(value)
+                .expect_throw("Invalid response type - Vec<AccessRequest>");
+            Ok(Msg::Ready { requests })
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Msg::Error { emsg, kopid })
+        }
+    }
+
+    async fn decide_access_request(request: Uuid, approve: bool) -> Result<Msg, FetchError> {
+        let uri = if approve {
+            format!("/v1/access_request/{}/_approve", request)
+        } else {
+            format!("/v1/access_request/{}/_deny", request)
+        };
+
+        let (kopid, status, value, _) = do_request(&uri, RequestMethod::POST, None).await?;
+
+        if status == 200 {
+            // Refresh the list so that the decision is shown.
+            Self::fetch_access_requests().await
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Msg::Error { emsg, kopid })
+        }
+    }
+}
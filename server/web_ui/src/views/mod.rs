@@ -11,10 +11,12 @@ use crate::manager::Route;
 use crate::models;
 use crate::{do_request, error::*, RequestMethod};
 
+mod accessrequests;
 mod apps;
 pub mod identityverification;
 mod profile;
 
+use accessrequests::AccessRequestsApp;
 use apps::AppsApp;
 use identityverification::IdentityVerificationApp;
 use profile::ProfileApp;
@@ -27,6 +29,9 @@ pub enum ViewRoute {
     #[at("/ui/apps")]
     Apps,
 
+    #[at("/ui/access-requests")]
+    AccessRequests,
+
     #[at("/ui/profile")]
     Profile,
 
@@ -253,6 +258,12 @@ impl ViewsApp {
                         { "Apps" }
                       </Link<ViewRoute>>
                     </li>
+                    <li class="mb-1">
+                      <Link<ViewRoute> classes="nav-link" to={ViewRoute::AccessRequests}>
+                        <span data-feather="file"></span>
+                        { "Access requests" }
+                      </Link<ViewRoute>>
+                    </li>
                     if ui_hint_experimental {
                       <li class="mb-1">
                         <Link<ViewRoute> classes="nav-link" to={ViewRoute::IdentityVerification}>
@@ -326,6 +337,7 @@ impl ViewsApp {
                         #[allow(clippy::let_unit_value)]
                         ViewRoute::IdentityVerification => html! { <IdentityVerificationApp current_user_uat={ current_user_uat.clone() } />},
                         ViewRoute::Apps => html! { <AppsApp /> },
+                        ViewRoute::AccessRequests => html! { <AccessRequestsApp /> },
                         ViewRoute::Profile => html! { <ProfileApp current_user_uat={ current_user_uat.clone() } /> },
                         ViewRoute::NotFound => html! {
                             <Redirect<Route> to={Route::NotFound}/>
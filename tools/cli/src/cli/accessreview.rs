@@ -0,0 +1,85 @@
+use kanidm_proto::v1::{AccessReviewDecision, Filter};
+
+use crate::common::OpType;
+use crate::{handle_client_error, AccessReviewDecideOpt, AccessReviewOpt, OutputMode};
+
+impl AccessReviewOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            AccessReviewOpt::Create(copt) => copt.copt.debug,
+            AccessReviewOpt::List(copt) => copt.debug,
+            AccessReviewOpt::Report(nopt) => nopt.copt.debug,
+            AccessReviewOpt::Keep(dopt) | AccessReviewOpt::Remove(dopt) => dopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            AccessReviewOpt::Create(copt) => {
+                let filter: Filter = match serde_json::from_str(copt.filter.as_str()) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        error!("Error parsing filter -> {:?}", e);
+                        return;
+                    }
+                };
+
+                let client = copt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_access_review_create(copt.name.as_str(), filter, copt.expiry.as_str())
+                    .await
+                {
+                    Ok(review) => println!("Created access review {} ({})", copt.name, review),
+                    Err(e) => handle_client_error(e, &copt.copt.output_mode),
+                }
+            }
+            AccessReviewOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_access_review_list().await {
+                    Ok(r) => match copt.output_mode {
+                        OutputMode::Json => println!(
+                            "{}",
+                            serde_json::to_string(&r).expect("Failed to serialise json")
+                        ),
+                        OutputMode::Text => {
+                            if r.is_empty() {
+                                println!("No access reviews");
+                            }
+                            r.iter().for_each(|review| println!("{}", review))
+                        }
+                    },
+                    Err(e) => handle_client_error(e, &copt.output_mode),
+                }
+            }
+            AccessReviewOpt::Report(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_access_review_report(nopt.name.as_str()).await {
+                    Ok(report) => match nopt.copt.output_mode {
+                        OutputMode::Json => println!(
+                            "{}",
+                            serde_json::to_string(&report).expect("Failed to serialise json")
+                        ),
+                        OutputMode::Text => {
+                            println!("{}", report.review);
+                            report.items.iter().for_each(|item| println!("{}", item))
+                        }
+                    },
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            AccessReviewOpt::Keep(dopt) => decide(dopt, AccessReviewDecision::Keep).await,
+            AccessReviewOpt::Remove(dopt) => decide(dopt, AccessReviewDecision::Remove).await,
+        }
+    }
+}
+
+async fn decide(dopt: &AccessReviewDecideOpt, decision: AccessReviewDecision) {
+    let client = dopt.copt.to_client(OpType::Write).await;
+    match client
+        .idm_access_review_decide(dopt.name.as_str(), dopt.item, decision)
+        .await
+    {
+        Ok(_) => println!("Success"),
+        Err(e) => handle_client_error(e, &dopt.copt.output_mode),
+    }
+}
@@ -0,0 +1,233 @@
+use kanidm_client::{ClientError, KanidmClient};
+use kanidm_proto::constants::{ATTR_AUTOMOUNT_KEY, ATTR_AUTOMOUNT_MAP, ATTR_NAME, ATTR_UUID};
+use kanidm_proto::v1::Entry;
+
+use crate::common::OpType;
+use crate::{handle_client_error, AutomountOpt, OutputMode};
+
+impl AutomountOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            AutomountOpt::List(copt) | AutomountOpt::UnixList(copt) => copt.debug,
+            AutomountOpt::Get(nopt) | AutomountOpt::Create(nopt) | AutomountOpt::Delete(nopt) => {
+                nopt.copt.debug
+            }
+            AutomountOpt::SetKey(kopt) => kopt.copt.debug,
+            AutomountOpt::RemoveKey(kopt) => kopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            AutomountOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_automount_map_list().await {
+                    Ok(r) => match copt.output_mode {
+                        OutputMode::Json => {
+                            let r_attrs: Vec<_> = r.iter().map(|entry| &entry.attrs).collect();
+                            println!(
+                                "{}",
+                                serde_json::to_string(&r_attrs).expect("Failed to serialise json")
+                            );
+                        }
+                        OutputMode::Text => r.iter().for_each(|ent| println!("{}", ent)),
+                    },
+                    Err(e) => handle_client_error(e, &copt.output_mode),
+                }
+            }
+            AutomountOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match map_keys(&client, nopt.name.as_str()).await {
+                    Ok(Some((map, keys))) => match nopt.copt.output_mode {
+                        OutputMode::Json => {
+                            let r_attrs: Vec<_> = std::iter::once(&map)
+                                .chain(keys.iter())
+                                .map(|entry| &entry.attrs)
+                                .collect();
+                            println!(
+                                "{}",
+                                serde_json::to_string(&r_attrs).expect("Failed to serialise json")
+                            );
+                        }
+                        OutputMode::Text => {
+                            println!("{}", map);
+                            keys.iter().for_each(|ent| println!("{}", ent));
+                        }
+                    },
+                    Ok(None) => warn!("No matching automount map '{}'", nopt.name.as_str()),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            AutomountOpt::Create(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_automount_map_create(nopt.name.as_str()).await {
+                    Ok(_) => println!(
+                        "Successfully created automount map '{}'",
+                        nopt.name.as_str()
+                    ),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            AutomountOpt::Delete(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                // Keys must refer to a map, so they are removed first.
+                let keys = match map_keys(&client, nopt.name.as_str()).await {
+                    Ok(Some((_, keys))) => keys,
+                    Ok(None) => {
+                        warn!("No matching automount map '{}'", nopt.name.as_str());
+                        return;
+                    }
+                    Err(e) => {
+                        handle_client_error(e, &nopt.copt.output_mode);
+                        return;
+                    }
+                };
+
+                for key_id in keys.iter().filter_map(entry_uuid) {
+                    if let Err(e) = client.idm_automount_key_delete(key_id).await {
+                        handle_client_error(e, &nopt.copt.output_mode);
+                        return;
+                    }
+                }
+
+                match client.idm_automount_map_delete(nopt.name.as_str()).await {
+                    Ok(_) => println!(
+                        "Successfully deleted automount map '{}'",
+                        nopt.name.as_str()
+                    ),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            AutomountOpt::SetKey(kopt) => {
+                let client = kopt.copt.to_client(OpType::Write).await;
+                let existing = match map_keys(&client, kopt.name.as_str()).await {
+                    Ok(Some((_, keys))) => keys
+                        .iter()
+                        .find(|ent| entry_has_key(ent, kopt.key.as_str()))
+                        .and_then(entry_uuid)
+                        .map(str::to_string),
+                    Ok(None) => {
+                        warn!("No matching automount map '{}'", kopt.name.as_str());
+                        return;
+                    }
+                    Err(e) => {
+                        handle_client_error(e, &kopt.copt.output_mode);
+                        return;
+                    }
+                };
+
+                let res = match existing {
+                    Some(key_id) => {
+                        client
+                            .idm_automount_key_set_information(
+                                key_id.as_str(),
+                                kopt.information.as_str(),
+                            )
+                            .await
+                    }
+                    None => {
+                        client
+                            .idm_automount_key_create(
+                                kopt.name.as_str(),
+                                kopt.key.as_str(),
+                                kopt.information.as_str(),
+                            )
+                            .await
+                    }
+                };
+
+                match res {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &kopt.copt.output_mode),
+                }
+            }
+            AutomountOpt::RemoveKey(kopt) => {
+                let client = kopt.copt.to_client(OpType::Write).await;
+                let existing = match map_keys(&client, kopt.name.as_str()).await {
+                    Ok(Some((_, keys))) => keys
+                        .iter()
+                        .find(|ent| entry_has_key(ent, kopt.key.as_str()))
+                        .and_then(entry_uuid)
+                        .map(str::to_string),
+                    Ok(None) => {
+                        warn!("No matching automount map '{}'", kopt.name.as_str());
+                        return;
+                    }
+                    Err(e) => {
+                        handle_client_error(e, &kopt.copt.output_mode);
+                        return;
+                    }
+                };
+
+                let Some(key_id) = existing else {
+                    warn!(
+                        "No matching key '{}' in automount map '{}'",
+                        kopt.key.as_str(),
+                        kopt.name.as_str()
+                    );
+                    return;
+                };
+
+                match client.idm_automount_key_delete(key_id.as_str()).await {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &kopt.copt.output_mode),
+                }
+            }
+            AutomountOpt::UnixList(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_automount_map_unix_list().await {
+                    Ok(r) => match copt.output_mode {
+                        OutputMode::Json => println!(
+                            "{}",
+                            serde_json::to_string(&r).expect("Failed to serialise json")
+                        ),
+                        OutputMode::Text => r.iter().for_each(|map| println!("{}", map)),
+                    },
+                    Err(e) => handle_client_error(e, &copt.output_mode),
+                }
+            }
+        }
+    }
+}
+
+fn entry_uuid(entry: &Entry) -> Option<&str> {
+    entry
+        .attrs
+        .get(ATTR_UUID)
+        .and_then(|v| v.first())
+        .map(String::as_str)
+}
+
+fn entry_has_key(entry: &Entry, key: &str) -> bool {
+    entry
+        .attrs
+        .get(ATTR_AUTOMOUNT_KEY)
+        .map_or(false, |v| v.iter().any(|k| k == key))
+}
+
+/// Retrieve a map and the keys that belong to it. Keys refer to their map by name.
+async fn map_keys(
+    client: &KanidmClient,
+    name: &str,
+) -> Result<Option<(Entry, Vec<Entry>)>, ClientError> {
+    let Some(map) = client.idm_automount_map_get(name).await? else {
+        return Ok(None);
+    };
+
+    let Some(map_name) = map.attrs.get(ATTR_NAME).and_then(|v| v.first()).cloned() else {
+        return Ok(None);
+    };
+
+    let keys = client
+        .idm_automount_key_list()
+        .await?
+        .into_iter()
+        .filter(|ent| {
+            ent.attrs
+                .get(ATTR_AUTOMOUNT_MAP)
+                .map_or(false, |v| v.contains(&map_name))
+        })
+        .collect();
+
+    Ok(Some((map, keys)))
+}
@@ -1,5 +1,10 @@
 use crate::common::OpType;
-use crate::{handle_client_error, GroupOpt, GroupPosix, OutputMode};
+use crate::{
+    handle_client_error, GroupAccessRequestOpt, GroupAccountPolicyOpt, GroupOpt, GroupPosix,
+    OutputMode,
+};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
 impl GroupOpt {
     pub fn debug(&self) -> bool {
@@ -11,12 +16,31 @@ impl GroupOpt {
             GroupOpt::ListMembers(gcopt) => gcopt.copt.debug,
             GroupOpt::AddMembers(gcopt) => gcopt.copt.debug,
             GroupOpt::RemoveMembers(gcopt) => gcopt.copt.debug,
+            GroupOpt::AddExpiringMembers(gcopt) => gcopt.copt.debug,
+            GroupOpt::RemoveExpiringMembers(gcopt) => gcopt.copt.debug,
+            GroupOpt::SetEntryManager(gcopt) => gcopt.copt.debug,
             GroupOpt::SetMembers(gcopt) => gcopt.copt.debug,
             GroupOpt::PurgeMembers(gcopt) => gcopt.copt.debug,
             GroupOpt::Posix { commands } => match commands {
                 GroupPosix::Show(gcopt) => gcopt.copt.debug,
                 GroupPosix::Set(gcopt) => gcopt.copt.debug,
             },
+            GroupOpt::AccountPolicy { commands } => match commands {
+                GroupAccountPolicyOpt::Enable(gcopt) => gcopt.copt.debug,
+                GroupAccountPolicyOpt::CredentialType(gcopt) => gcopt.copt.debug,
+                GroupAccountPolicyOpt::PasswordMinimumLength(gcopt)
+                | GroupAccountPolicyOpt::AuthSessionExpiry(gcopt)
+                | GroupAccountPolicyOpt::PrivilegedSessionExpiry(gcopt) => gcopt.copt.debug,
+            },
+            GroupOpt::AccessRequest { commands } => match commands {
+                GroupAccessRequestOpt::SetApprovers(gcopt) => gcopt.copt.debug,
+                GroupAccessRequestOpt::SetDuration(gcopt) => gcopt.copt.debug,
+                GroupAccessRequestOpt::Create(gcopt) => gcopt.copt.debug,
+                GroupAccessRequestOpt::List(copt) => copt.debug,
+                GroupAccessRequestOpt::Approve(gcopt) | GroupAccessRequestOpt::Deny(gcopt) => {
+                    gcopt.copt.debug
+                }
+            },
         }
     }
 
@@ -122,6 +146,70 @@ impl GroupOpt {
                 }
             }
 
+            GroupOpt::AddExpiringMembers(gcopt) => {
+                if let Err(e) = OffsetDateTime::parse(gcopt.expiry.as_str(), &Rfc3339) {
+                    error!("Invalid expiry '{}' -> {:?}", gcopt.expiry, e);
+                    return;
+                }
+
+                let client = gcopt.copt.to_client(OpType::Write).await;
+                let new_members: Vec<&str> = gcopt.members.iter().map(String::as_str).collect();
+
+                match client
+                    .idm_group_add_expiring_members(
+                        gcopt.name.as_str(),
+                        &new_members,
+                        gcopt.expiry.as_str(),
+                    )
+                    .await
+                {
+                    Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                    Ok(_) => println!(
+                        "Successfully added {:?} to group \"{}\" until {}",
+                        &new_members,
+                        gcopt.name.as_str(),
+                        gcopt.expiry
+                    ),
+                }
+            }
+
+            GroupOpt::RemoveExpiringMembers(gcopt) => {
+                let client = gcopt.copt.to_client(OpType::Write).await;
+                let remove_members: Vec<&str> = gcopt.members.iter().map(String::as_str).collect();
+
+                match client
+                    .idm_group_remove_expiring_members(gcopt.name.as_str(), &remove_members)
+                    .await
+                {
+                    Err(e) => {
+                        error!("Failed to remove expiring members!");
+                        handle_client_error(e, &gcopt.copt.output_mode)
+                    }
+                    Ok(_) => println!(
+                        "Successfully removed expiring members from {}",
+                        gcopt.name.as_str()
+                    ),
+                }
+            }
+
+            GroupOpt::SetEntryManager(gcopt) => {
+                let client = gcopt.copt.to_client(OpType::Write).await;
+
+                match client
+                    .idm_group_set_entry_managed_by(
+                        gcopt.name.as_str(),
+                        gcopt.entry_managed_by.as_str(),
+                    )
+                    .await
+                {
+                    Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                    Ok(_) => println!(
+                        "Successfully set entry manager for group {}",
+                        gcopt.name.as_str()
+                    ),
+                }
+            }
+
             GroupOpt::SetMembers(gcopt) => {
                 let client = gcopt.copt.to_client(OpType::Write).await;
                 let new_members: Vec<&str> = gcopt.members.iter().map(String::as_str).collect();
@@ -156,6 +244,157 @@ impl GroupOpt {
                     }
                 }
             },
+            GroupOpt::AccountPolicy { commands } => match commands {
+                GroupAccountPolicyOpt::Enable(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_group_account_policy_enable(gcopt.name.as_str())
+                        .await
+                    {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => {
+                            println!("Enabled account policy for group {}", gcopt.name.as_str())
+                        }
+                    }
+                }
+                GroupAccountPolicyOpt::CredentialType(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    let credential_types: Vec<&str> =
+                        gcopt.credential_types.iter().map(String::as_str).collect();
+                    match client
+                        .idm_group_account_policy_credential_type_allowed(
+                            gcopt.name.as_str(),
+                            &credential_types,
+                        )
+                        .await
+                    {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => println!(
+                            "Updated allowed credential types for group {}",
+                            gcopt.name.as_str()
+                        ),
+                    }
+                }
+                GroupAccountPolicyOpt::PasswordMinimumLength(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_group_account_policy_password_minimum_length(
+                            gcopt.name.as_str(),
+                            gcopt.value,
+                        )
+                        .await
+                    {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => println!(
+                            "Updated password minimum length for group {}",
+                            gcopt.name.as_str()
+                        ),
+                    }
+                }
+                GroupAccountPolicyOpt::AuthSessionExpiry(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_group_account_policy_authsession_expiry(
+                            gcopt.name.as_str(),
+                            gcopt.value,
+                        )
+                        .await
+                    {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => println!(
+                            "Updated authsession expiry for group {}",
+                            gcopt.name.as_str()
+                        ),
+                    }
+                }
+                GroupAccountPolicyOpt::PrivilegedSessionExpiry(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_group_account_policy_privilege_expiry(gcopt.name.as_str(), gcopt.value)
+                        .await
+                    {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => {
+                            println!("Updated privilege expiry for group {}", gcopt.name.as_str())
+                        }
+                    }
+                }
+            },
+            GroupOpt::AccessRequest { commands } => match commands {
+                GroupAccessRequestOpt::SetApprovers(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    let approvers: Vec<&str> = gcopt.approvers.iter().map(String::as_str).collect();
+                    match client
+                        .idm_group_access_request_set_approvers(gcopt.name.as_str(), &approvers)
+                        .await
+                    {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => println!(
+                            "Updated access request approvers for group {}",
+                            gcopt.name.as_str()
+                        ),
+                    }
+                }
+                GroupAccessRequestOpt::SetDuration(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_group_access_request_set_duration(gcopt.name.as_str(), gcopt.value)
+                        .await
+                    {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => println!(
+                            "Updated access request duration for group {}",
+                            gcopt.name.as_str()
+                        ),
+                    }
+                }
+                GroupAccessRequestOpt::Create(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_group_access_request_create(
+                            gcopt.name.as_str(),
+                            gcopt.justification.as_str(),
+                        )
+                        .await
+                    {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(request) => println!(
+                            "Requested membership of group {} - request id {}",
+                            gcopt.name.as_str(),
+                            request
+                        ),
+                    }
+                }
+                GroupAccessRequestOpt::List(copt) => {
+                    let client = copt.to_client(OpType::Read).await;
+                    match client.idm_access_request_list().await {
+                        Ok(requests) => match copt.output_mode {
+                            OutputMode::Json => println!(
+                                "{}",
+                                serde_json::to_string(&requests).expect("Failed to serialise json")
+                            ),
+                            OutputMode::Text => {
+                                requests.iter().for_each(|request| println!("{}", request))
+                            }
+                        },
+                        Err(e) => handle_client_error(e, &copt.output_mode),
+                    }
+                }
+                GroupAccessRequestOpt::Approve(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    match client.idm_access_request_approve(gcopt.id.as_str()).await {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => println!("Approved access request {}", gcopt.id.as_str()),
+                    }
+                }
+                GroupAccessRequestOpt::Deny(gcopt) => {
+                    let client = gcopt.copt.to_client(OpType::Write).await;
+                    match client.idm_access_request_deny(gcopt.id.as_str()).await {
+                        Err(e) => handle_client_error(e, &gcopt.copt.output_mode),
+                        Ok(_) => println!("Denied access request {}", gcopt.id.as_str()),
+                    }
+                }
+            },
         } // end match
     }
 }
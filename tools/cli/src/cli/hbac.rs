@@ -0,0 +1,106 @@
+use kanidm_proto::constants::{ATTR_HBAC_HOST, ATTR_HBAC_SERVICE, ATTR_HBAC_USER};
+
+use crate::common::OpType;
+use crate::{handle_client_error, HbacRuleOpt, HbacRuleValuesOpt, OutputMode};
+
+impl HbacRuleOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            HbacRuleOpt::List(copt) => copt.debug,
+            HbacRuleOpt::Get(nopt) | HbacRuleOpt::Create(nopt) | HbacRuleOpt::Delete(nopt) => {
+                nopt.copt.debug
+            }
+            HbacRuleOpt::AddUsers(vopt)
+            | HbacRuleOpt::RemoveUsers(vopt)
+            | HbacRuleOpt::AddHosts(vopt)
+            | HbacRuleOpt::RemoveHosts(vopt)
+            | HbacRuleOpt::AddServices(vopt)
+            | HbacRuleOpt::RemoveServices(vopt) => vopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            HbacRuleOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_hbac_rule_list().await {
+                    Ok(r) => match copt.output_mode {
+                        OutputMode::Json => {
+                            let r_attrs: Vec<_> = r.iter().map(|entry| &entry.attrs).collect();
+                            println!(
+                                "{}",
+                                serde_json::to_string(&r_attrs).expect("Failed to serialise json")
+                            );
+                        }
+                        OutputMode::Text => r.iter().for_each(|ent| println!("{}", ent)),
+                    },
+                    Err(e) => handle_client_error(e, &copt.output_mode),
+                }
+            }
+            HbacRuleOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_hbac_rule_get(nopt.name.as_str()).await {
+                    Ok(Some(e)) => match nopt.copt.output_mode {
+                        OutputMode::Json => println!(
+                            "{}",
+                            serde_json::to_string(&e.attrs).expect("Failed to serialise json")
+                        ),
+                        OutputMode::Text => println!("{}", e),
+                    },
+                    Ok(None) => warn!("No matching host access rule '{}'", nopt.name.as_str()),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            HbacRuleOpt::Create(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_hbac_rule_create(nopt.name.as_str()).await {
+                    Ok(_) => println!(
+                        "Successfully created host access rule '{}'",
+                        nopt.name.as_str()
+                    ),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            HbacRuleOpt::Delete(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_hbac_rule_delete(nopt.name.as_str()).await {
+                    Ok(_) => println!(
+                        "Successfully deleted host access rule '{}'",
+                        nopt.name.as_str()
+                    ),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            HbacRuleOpt::AddUsers(vopt) => add_values(vopt, ATTR_HBAC_USER).await,
+            HbacRuleOpt::RemoveUsers(vopt) => remove_values(vopt, ATTR_HBAC_USER).await,
+            HbacRuleOpt::AddHosts(vopt) => add_values(vopt, ATTR_HBAC_HOST).await,
+            HbacRuleOpt::RemoveHosts(vopt) => remove_values(vopt, ATTR_HBAC_HOST).await,
+            HbacRuleOpt::AddServices(vopt) => add_values(vopt, ATTR_HBAC_SERVICE).await,
+            HbacRuleOpt::RemoveServices(vopt) => remove_values(vopt, ATTR_HBAC_SERVICE).await,
+        }
+    }
+}
+
+async fn add_values(vopt: &HbacRuleValuesOpt, attr: &str) {
+    let client = vopt.copt.to_client(OpType::Write).await;
+    let values: Vec<&str> = vopt.values.iter().map(String::as_str).collect();
+    match client
+        .idm_hbac_rule_add_attr(vopt.name.as_str(), attr, &values)
+        .await
+    {
+        Ok(_) => println!("Success"),
+        Err(e) => handle_client_error(e, &vopt.copt.output_mode),
+    }
+}
+
+async fn remove_values(vopt: &HbacRuleValuesOpt, attr: &str) {
+    let client = vopt.copt.to_client(OpType::Write).await;
+    let values: Vec<&str> = vopt.values.iter().map(String::as_str).collect();
+    match client
+        .idm_hbac_rule_remove_attr(vopt.name.as_str(), attr, &values)
+        .await
+    {
+        Ok(_) => println!("Success"),
+        Err(e) => handle_client_error(e, &vopt.copt.output_mode),
+    }
+}
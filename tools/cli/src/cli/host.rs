@@ -0,0 +1,63 @@
+use crate::common::OpType;
+use crate::{handle_client_error, HostOpt, OutputMode};
+
+impl HostOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            HostOpt::List(copt) => copt.debug,
+            HostOpt::Get(nopt) | HostOpt::Create(nopt) | HostOpt::Delete(nopt) => nopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            HostOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_host_list().await {
+                    Ok(r) => match copt.output_mode {
+                        OutputMode::Json => {
+                            let r_attrs: Vec<_> = r.iter().map(|entry| &entry.attrs).collect();
+                            println!(
+                                "{}",
+                                serde_json::to_string(&r_attrs).expect("Failed to serialise json")
+                            );
+                        }
+                        OutputMode::Text => r.iter().for_each(|ent| println!("{}", ent)),
+                    },
+                    Err(e) => handle_client_error(e, &copt.output_mode),
+                }
+            }
+            HostOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_host_get(nopt.name.as_str()).await {
+                    Ok(Some(e)) => match nopt.copt.output_mode {
+                        OutputMode::Json => println!(
+                            "{}",
+                            serde_json::to_string(&e.attrs).expect("Failed to serialise json")
+                        ),
+                        OutputMode::Text => println!("{}", e),
+                    },
+                    Ok(None) => warn!("No matching host '{}'", nopt.name.as_str()),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            HostOpt::Create(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_host_create(nopt.name.as_str(), nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Successfully created host '{}'", nopt.name.as_str()),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            HostOpt::Delete(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_host_delete(nopt.name.as_str()).await {
+                    Ok(_) => println!("Successfully deleted host '{}'", nopt.name.as_str()),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+        }
+    }
+}
@@ -27,19 +27,25 @@ use uuid::Uuid;
 
 include!("../opt/kanidm.rs");
 
+pub mod accessreview;
+pub mod automount;
 pub mod badlist;
 pub mod common;
 pub mod domain;
 pub mod group;
+pub mod hbac;
+pub mod host;
 #[cfg(feature = "idv-tui")]
 mod identify_user_tui;
 pub mod oauth2;
 pub mod person;
 pub mod raw;
 pub mod recycle;
+pub mod replication;
 pub mod serviceaccount;
 pub mod session;
 pub mod session_expiry;
+pub mod sudo;
 pub mod synch;
 mod webauthn;
 
@@ -148,6 +154,7 @@ impl SystemOpt {
             SystemOpt::Oauth2 { commands } => commands.debug(),
             SystemOpt::Domain { commands } => commands.debug(),
             SystemOpt::Synch { commands } => commands.debug(),
+            SystemOpt::Replication { commands } => commands.debug(),
             SystemOpt::AuthSessionExpiry { commands } => commands.debug(),
             SystemOpt::PrivilegedSessionExpiry { commands } => commands.debug(),
         }
@@ -159,6 +166,7 @@ impl SystemOpt {
             SystemOpt::Oauth2 { commands } => commands.exec().await,
             SystemOpt::Domain { commands } => commands.exec().await,
             SystemOpt::Synch { commands } => commands.exec().await,
+            SystemOpt::Replication { commands } => commands.exec().await,
             SystemOpt::AuthSessionExpiry { commands } => commands.exec().await,
             SystemOpt::PrivilegedSessionExpiry { commands } => commands.exec().await,
         }
@@ -179,6 +187,11 @@ impl KanidmClientOpt {
             KanidmClientOpt::ServiceAccount { commands } => commands.debug(),
             KanidmClientOpt::System { commands } => commands.debug(),
             KanidmClientOpt::Recycle { commands } => commands.debug(),
+            KanidmClientOpt::AccessReview { commands } => commands.debug(),
+            KanidmClientOpt::SudoRule { commands } => commands.debug(),
+            KanidmClientOpt::Host { commands } => commands.debug(),
+            KanidmClientOpt::HbacRule { commands } => commands.debug(),
+            KanidmClientOpt::Automount { commands } => commands.debug(),
             KanidmClientOpt::Version {} => {
                 println!("kanidm {}", env!("KANIDM_PKG_VERSION"));
                 true
@@ -199,6 +212,11 @@ impl KanidmClientOpt {
             KanidmClientOpt::Group { commands } => commands.exec().await,
             KanidmClientOpt::System { commands } => commands.exec().await,
             KanidmClientOpt::Recycle { commands } => commands.exec().await,
+            KanidmClientOpt::AccessReview { commands } => commands.exec().await,
+            KanidmClientOpt::SudoRule { commands } => commands.exec().await,
+            KanidmClientOpt::Host { commands } => commands.exec().await,
+            KanidmClientOpt::HbacRule { commands } => commands.exec().await,
+            KanidmClientOpt::Automount { commands } => commands.exec().await,
             KanidmClientOpt::Version {} => (),
         }
     }
@@ -12,6 +12,10 @@ impl Oauth2Opt {
             Oauth2Opt::DeleteScopeMap(cbopt) => cbopt.nopt.copt.debug,
             Oauth2Opt::UpdateSupScopeMap(cbopt) => cbopt.nopt.copt.debug,
             Oauth2Opt::DeleteSupScopeMap(cbopt) => cbopt.nopt.copt.debug,
+            Oauth2Opt::UpdateClientCredentialsScopes(cbopt) => cbopt.nopt.copt.debug,
+            Oauth2Opt::DeleteClientCredentialsScopes(nopt) => nopt.copt.debug,
+            Oauth2Opt::AddTokenExchangeSource(topt) => topt.nopt.copt.debug,
+            Oauth2Opt::RemoveTokenExchangeSource(topt) => topt.nopt.copt.debug,
             Oauth2Opt::ResetSecrets(cbopt) => cbopt.copt.debug,
             // Should this be renamed to show client id? client secrets?
             Oauth2Opt::ShowBasicSecret(nopt) => nopt.copt.debug,
@@ -23,6 +27,8 @@ impl Oauth2Opt {
             Oauth2Opt::DisablePkce(nopt) => nopt.copt.debug,
             Oauth2Opt::EnableLegacyCrypto(nopt) => nopt.copt.debug,
             Oauth2Opt::DisableLegacyCrypto(nopt) => nopt.copt.debug,
+            Oauth2Opt::EnableDeviceFlow(nopt) => nopt.copt.debug,
+            Oauth2Opt::DisableDeviceFlow(nopt) => nopt.copt.debug,
             Oauth2Opt::PreferShortUsername(nopt) => nopt.copt.debug,
             Oauth2Opt::PreferSPNUsername(nopt) => nopt.copt.debug,
             Oauth2Opt::CreateBasic { copt, .. } | Oauth2Opt::CreatePublic { copt, .. } => {
@@ -149,6 +155,55 @@ impl Oauth2Opt {
                     Err(e) => handle_client_error(e, &cbopt.nopt.copt.output_mode),
                 }
             }
+            Oauth2Opt::UpdateClientCredentialsScopes(cbopt) => {
+                let client = cbopt.nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_update_client_credentials_scopes(
+                        cbopt.nopt.name.as_str(),
+                        cbopt.scopes.iter().map(|s| s.as_str()).collect(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &cbopt.nopt.copt.output_mode),
+                }
+            }
+            Oauth2Opt::DeleteClientCredentialsScopes(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_delete_client_credentials_scopes(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            Oauth2Opt::AddTokenExchangeSource(topt) => {
+                let client = topt.nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_add_token_exchange_source(
+                        topt.nopt.name.as_str(),
+                        topt.source.as_str(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &topt.nopt.copt.output_mode),
+                }
+            }
+            Oauth2Opt::RemoveTokenExchangeSource(topt) => {
+                let client = topt.nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_remove_token_exchange_source(
+                        topt.nopt.name.as_str(),
+                        topt.source.as_str(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &topt.nopt.copt.output_mode),
+                }
+            }
             Oauth2Opt::ResetSecrets(cbopt) => {
                 let client = cbopt.copt.to_client(OpType::Write).await;
                 match client
@@ -285,6 +340,26 @@ impl Oauth2Opt {
                     Err(e) => handle_client_error(e, &nopt.copt.output_mode),
                 }
             }
+            Oauth2Opt::EnableDeviceFlow(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_enable_device_flow(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            Oauth2Opt::DisableDeviceFlow(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_disable_device_flow(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
             Oauth2Opt::PreferShortUsername(nopt) => {
                 let client = nopt.copt.to_client(OpType::Write).await;
                 match client
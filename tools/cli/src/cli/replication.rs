@@ -0,0 +1,103 @@
+use kanidm_proto::v1::ReplicationConflictResolution;
+
+use crate::common::OpType;
+use crate::{handle_client_error, OutputMode, ReplicationConflictOpt, ReplicationOpt};
+
+impl ReplicationOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            ReplicationOpt::Conflicts { commands } => commands.debug(),
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            ReplicationOpt::Conflicts { commands } => commands.exec().await,
+        }
+    }
+}
+
+impl ReplicationConflictOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            ReplicationConflictOpt::List(copt) => copt.debug,
+            ReplicationConflictOpt::Get(nopt)
+            | ReplicationConflictOpt::Discard(nopt)
+            | ReplicationConflictOpt::Accept(nopt)
+            | ReplicationConflictOpt::Revive(nopt) => nopt.copt.debug,
+            ReplicationConflictOpt::Merge(mopt) => mopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            ReplicationConflictOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.system_replication_conflict_list().await {
+                    Ok(r) => match copt.output_mode {
+                        OutputMode::Json => println!(
+                            "{}",
+                            serde_json::to_string(&r).expect("Failed to serialise json")
+                        ),
+                        OutputMode::Text => {
+                            if r.is_empty() {
+                                println!("No replication conflicts");
+                            }
+                            r.iter().for_each(|c| println!("{}", c))
+                        }
+                    },
+                    Err(e) => handle_client_error(e, &copt.output_mode),
+                }
+            }
+            ReplicationConflictOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client
+                    .system_replication_conflict_get(nopt.name.as_str())
+                    .await
+                {
+                    Ok(c) => match nopt.copt.output_mode {
+                        OutputMode::Json => println!(
+                            "{}",
+                            serde_json::to_string(&c).expect("Failed to serialise json")
+                        ),
+                        OutputMode::Text => println!("{}", c),
+                    },
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            ReplicationConflictOpt::Discard(nopt) => {
+                resolve(nopt, ReplicationConflictResolution::Discard).await
+            }
+            ReplicationConflictOpt::Accept(nopt) => {
+                resolve(nopt, ReplicationConflictResolution::Accept).await
+            }
+            ReplicationConflictOpt::Revive(nopt) => {
+                resolve(nopt, ReplicationConflictResolution::Revive).await
+            }
+            ReplicationConflictOpt::Merge(mopt) => {
+                let client = mopt.copt.to_client(OpType::Write).await;
+                let resolution = ReplicationConflictResolution::Merge {
+                    attrs: mopt.attrs.clone(),
+                };
+                match client
+                    .system_replication_conflict_resolve(mopt.name.as_str(), resolution)
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, &mopt.copt.output_mode),
+                }
+            }
+        }
+    }
+}
+
+async fn resolve(nopt: &crate::Named, resolution: ReplicationConflictResolution) {
+    let client = nopt.copt.to_client(OpType::Write).await;
+    match client
+        .system_replication_conflict_resolve(nopt.name.as_str(), resolution)
+        .await
+    {
+        Ok(_) => println!("Success"),
+        Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+    }
+}
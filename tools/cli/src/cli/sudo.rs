@@ -0,0 +1,119 @@
+use kanidm_proto::constants::{ATTR_SUDO_COMMAND, ATTR_SUDO_HOST, ATTR_SUDO_RUNAS, ATTR_SUDO_USER};
+
+use crate::common::OpType;
+use crate::{handle_client_error, Named, OutputMode, SudoRuleOpt, SudoRuleValuesOpt};
+
+impl SudoRuleOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            SudoRuleOpt::List(copt) => copt.debug,
+            SudoRuleOpt::Get(nopt)
+            | SudoRuleOpt::Create(nopt)
+            | SudoRuleOpt::Delete(nopt)
+            | SudoRuleOpt::EnableNopasswd(nopt)
+            | SudoRuleOpt::DisableNopasswd(nopt) => nopt.copt.debug,
+            SudoRuleOpt::AddUsers(vopt)
+            | SudoRuleOpt::RemoveUsers(vopt)
+            | SudoRuleOpt::AddHosts(vopt)
+            | SudoRuleOpt::RemoveHosts(vopt)
+            | SudoRuleOpt::AddCommands(vopt)
+            | SudoRuleOpt::RemoveCommands(vopt)
+            | SudoRuleOpt::AddRunas(vopt)
+            | SudoRuleOpt::RemoveRunas(vopt) => vopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            SudoRuleOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_sudo_rule_list().await {
+                    Ok(r) => match copt.output_mode {
+                        OutputMode::Json => {
+                            let r_attrs: Vec<_> = r.iter().map(|entry| &entry.attrs).collect();
+                            println!(
+                                "{}",
+                                serde_json::to_string(&r_attrs).expect("Failed to serialise json")
+                            );
+                        }
+                        OutputMode::Text => r.iter().for_each(|ent| println!("{}", ent)),
+                    },
+                    Err(e) => handle_client_error(e, &copt.output_mode),
+                }
+            }
+            SudoRuleOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_sudo_rule_get(nopt.name.as_str()).await {
+                    Ok(Some(e)) => match nopt.copt.output_mode {
+                        OutputMode::Json => println!(
+                            "{}",
+                            serde_json::to_string(&e.attrs).expect("Failed to serialise json")
+                        ),
+                        OutputMode::Text => println!("{}", e),
+                    },
+                    Ok(None) => warn!("No matching sudo rule '{}'", nopt.name.as_str()),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            SudoRuleOpt::Create(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_sudo_rule_create(nopt.name.as_str()).await {
+                    Ok(_) => println!("Successfully created sudo rule '{}'", nopt.name.as_str()),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            SudoRuleOpt::Delete(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_sudo_rule_delete(nopt.name.as_str()).await {
+                    Ok(_) => println!("Successfully deleted sudo rule '{}'", nopt.name.as_str()),
+                    Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+                }
+            }
+            SudoRuleOpt::AddUsers(vopt) => add_values(vopt, ATTR_SUDO_USER).await,
+            SudoRuleOpt::RemoveUsers(vopt) => remove_values(vopt, ATTR_SUDO_USER).await,
+            SudoRuleOpt::AddHosts(vopt) => add_values(vopt, ATTR_SUDO_HOST).await,
+            SudoRuleOpt::RemoveHosts(vopt) => remove_values(vopt, ATTR_SUDO_HOST).await,
+            SudoRuleOpt::AddCommands(vopt) => add_values(vopt, ATTR_SUDO_COMMAND).await,
+            SudoRuleOpt::RemoveCommands(vopt) => remove_values(vopt, ATTR_SUDO_COMMAND).await,
+            SudoRuleOpt::AddRunas(vopt) => add_values(vopt, ATTR_SUDO_RUNAS).await,
+            SudoRuleOpt::RemoveRunas(vopt) => remove_values(vopt, ATTR_SUDO_RUNAS).await,
+            SudoRuleOpt::EnableNopasswd(nopt) => set_nopasswd(nopt, true).await,
+            SudoRuleOpt::DisableNopasswd(nopt) => set_nopasswd(nopt, false).await,
+        }
+    }
+}
+
+async fn add_values(vopt: &SudoRuleValuesOpt, attr: &str) {
+    let client = vopt.copt.to_client(OpType::Write).await;
+    let values: Vec<&str> = vopt.values.iter().map(String::as_str).collect();
+    match client
+        .idm_sudo_rule_add_attr(vopt.name.as_str(), attr, &values)
+        .await
+    {
+        Ok(_) => println!("Success"),
+        Err(e) => handle_client_error(e, &vopt.copt.output_mode),
+    }
+}
+
+async fn remove_values(vopt: &SudoRuleValuesOpt, attr: &str) {
+    let client = vopt.copt.to_client(OpType::Write).await;
+    let values: Vec<&str> = vopt.values.iter().map(String::as_str).collect();
+    match client
+        .idm_sudo_rule_remove_attr(vopt.name.as_str(), attr, &values)
+        .await
+    {
+        Ok(_) => println!("Success"),
+        Err(e) => handle_client_error(e, &vopt.copt.output_mode),
+    }
+}
+
+async fn set_nopasswd(nopt: &Named, nopasswd: bool) {
+    let client = nopt.copt.to_client(OpType::Write).await;
+    match client
+        .idm_sudo_rule_set_nopasswd(nopt.name.as_str(), nopasswd)
+        .await
+    {
+        Ok(_) => println!("Success"),
+        Err(e) => handle_client_error(e, &nopt.copt.output_mode),
+    }
+}
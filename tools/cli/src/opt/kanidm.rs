@@ -67,6 +67,18 @@ pub struct GroupNamedMembers {
     copt: CommonOpt,
 }
 
+#[derive(Debug, Args)]
+pub struct GroupNamedExpiringMembers {
+    name: String,
+    /// The rfc3339 time at which these memberships expire, such as 2024-01-01T00:00:00+10:00
+    #[clap(long)]
+    expiry: String,
+    #[clap(required = true, num_args(1..))]
+    members: Vec<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
 #[derive(Debug, Args)]
 pub struct GroupPosixOpt {
     name: String,
@@ -86,6 +98,102 @@ pub enum GroupPosix {
     Set(GroupPosixOpt),
 }
 
+#[derive(Debug, Args)]
+pub struct GroupAccountPolicyCredentialTypeOpt {
+    name: String,
+    /// The credential types to allow: password, generatedpassword, passwordmfa or webauthn.
+    #[clap(required = true, num_args(1..))]
+    credential_types: Vec<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupAccountPolicyValueOpt {
+    name: String,
+    value: u32,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum GroupAccountPolicyOpt {
+    /// Enable account policy for this group, allowing it to constrain its members
+    #[clap(name = "enable")]
+    Enable(Named),
+    /// Set the credential types that members of this group may authenticate with
+    #[clap(name = "credential-type")]
+    CredentialType(GroupAccountPolicyCredentialTypeOpt),
+    /// Set the minimum length of passwords for members of this group
+    #[clap(name = "password-minimum-length")]
+    PasswordMinimumLength(GroupAccountPolicyValueOpt),
+    /// Set the maximum time in seconds that an authentication session of a member may last
+    #[clap(name = "auth-expiry")]
+    AuthSessionExpiry(GroupAccountPolicyValueOpt),
+    /// Set the maximum time in seconds that a member may hold privileges for
+    #[clap(name = "privilege-expiry")]
+    PrivilegedSessionExpiry(GroupAccountPolicyValueOpt),
+}
+
+#[derive(Debug, Args)]
+pub struct GroupEntryManagerOpt {
+    name: String,
+    /// The account or group that owns this group, and reviews its memberships
+    entry_managed_by: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupAccessRequestApproversOpt {
+    name: String,
+    /// The groups whose members may approve or deny requests for membership of this group
+    #[clap(required = true, num_args(1..))]
+    approvers: Vec<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupAccessRequestCreateOpt {
+    name: String,
+    /// Why you need to be a member of this group
+    #[clap(long)]
+    justification: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupAccessRequestIdOpt {
+    /// The uuid of the access request
+    id: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum GroupAccessRequestOpt {
+    /// Set the groups whose members may approve access requests for this group
+    #[clap(name = "set-approvers")]
+    SetApprovers(GroupAccessRequestApproversOpt),
+    /// Set the time in seconds that approved requests grant membership for
+    #[clap(name = "set-duration")]
+    SetDuration(GroupAccountPolicyValueOpt),
+    /// Request to be granted membership of this group
+    #[clap(name = "create")]
+    Create(GroupAccessRequestCreateOpt),
+    /// List your access requests, and the requests you are able to approve
+    #[clap(name = "list")]
+    List(CommonOpt),
+    /// Approve an access request, granting the requester membership of the group
+    #[clap(name = "approve")]
+    Approve(GroupAccessRequestIdOpt),
+    /// Deny an access request
+    #[clap(name = "deny")]
+    Deny(GroupAccessRequestIdOpt),
+}
+
 #[derive(Debug, Subcommand)]
 pub enum GroupOpt {
     /// List all groups
@@ -116,12 +224,33 @@ pub enum GroupOpt {
     /// Remove the named members from this group
     #[clap(name = "remove-members")]
     RemoveMembers(GroupNamedMembers),
+    /// Add members to a group that are automatically removed once their membership expires
+    #[clap(name = "add-expiring-members")]
+    AddExpiringMembers(GroupNamedExpiringMembers),
+    /// Remove the named expiring members from this group before their membership expires
+    #[clap(name = "remove-expiring-members")]
+    RemoveExpiringMembers(GroupNamedMembers),
+    /// Set the account or group that owns this group
+    #[clap(name = "set-entry-manager")]
+    SetEntryManager(GroupEntryManagerOpt),
     /// Manage posix extensions for this group allowing groups to be used on unix/linux systems
     #[clap(name = "posix")]
     Posix {
         #[clap(subcommand)]
         commands: GroupPosix,
     },
+    /// Manage the account policy that this group applies to its members
+    #[clap(name = "account-policy")]
+    AccountPolicy {
+        #[clap(subcommand)]
+        commands: GroupAccountPolicyOpt,
+    },
+    /// Manage and make just-in-time requests for membership of this group
+    #[clap(name = "access-request")]
+    AccessRequest {
+        #[clap(subcommand)]
+        commands: GroupAccessRequestOpt,
+    },
 }
 
 #[derive(Debug, Args)]
@@ -535,6 +664,256 @@ pub enum RecycleOpt {
     Revive(Named),
 }
 
+#[derive(Debug, Args)]
+pub struct AccessReviewCreateOpt {
+    /// The name of the access review
+    pub name: String,
+    /// A json filter selecting the groups to review, such as '{"eq": ["name", "demo_group"]}'
+    #[clap(long)]
+    pub filter: String,
+    /// The rfc3339 time at which the review closes, such as 2024-01-01T00:00:00+10:00
+    #[clap(long)]
+    pub expiry: String,
+    #[clap(flatten)]
+    pub copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct AccessReviewDecideOpt {
+    /// The name of the access review
+    pub name: String,
+    /// The uuid of the review item to decide
+    pub item: Uuid,
+    #[clap(flatten)]
+    pub copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccessReviewOpt {
+    #[clap(name = "create")]
+    /// Start a review of the memberships of a set of groups
+    Create(AccessReviewCreateOpt),
+    #[clap(name = "list")]
+    /// List the access reviews that you administer or have items to review in
+    List(CommonOpt),
+    #[clap(name = "report")]
+    /// Show the review items and decisions of an access review
+    Report(Named),
+    #[clap(name = "keep")]
+    /// Attest that a membership is still required
+    Keep(AccessReviewDecideOpt),
+    #[clap(name = "remove")]
+    /// Remove a membership that is no longer required
+    Remove(AccessReviewDecideOpt),
+}
+
+#[derive(Debug, Args)]
+pub struct SudoRuleValuesOpt {
+    /// The name of the sudo rule
+    pub name: String,
+    #[clap(required = true, num_args(1..))]
+    pub values: Vec<String>,
+    #[clap(flatten)]
+    pub copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SudoRuleOpt {
+    #[clap(name = "list")]
+    /// List all sudo rules
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// Display a sudo rule
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a new sudo rule
+    Create(Named),
+    #[clap(name = "delete")]
+    /// Delete a sudo rule
+    Delete(Named),
+    #[clap(name = "add-users")]
+    /// Add accounts or groups that this rule applies to
+    AddUsers(SudoRuleValuesOpt),
+    #[clap(name = "remove-users")]
+    /// Remove accounts or groups from this rule
+    RemoveUsers(SudoRuleValuesOpt),
+    #[clap(name = "add-hosts")]
+    /// Add hosts or groups of hosts that this rule applies on
+    AddHosts(SudoRuleValuesOpt),
+    #[clap(name = "remove-hosts")]
+    /// Remove hosts or groups of hosts from this rule
+    RemoveHosts(SudoRuleValuesOpt),
+    #[clap(name = "add-commands")]
+    /// Add commands that may be run. "ALL" permits any command.
+    AddCommands(SudoRuleValuesOpt),
+    #[clap(name = "remove-commands")]
+    /// Remove commands from this rule
+    RemoveCommands(SudoRuleValuesOpt),
+    #[clap(name = "add-runas")]
+    /// Add accounts that commands may be run as. If none are set, only root is permitted.
+    AddRunas(SudoRuleValuesOpt),
+    #[clap(name = "remove-runas")]
+    /// Remove accounts that commands may be run as
+    RemoveRunas(SudoRuleValuesOpt),
+    #[clap(name = "enable-nopasswd")]
+    /// Allow commands to be run without reauthenticating
+    EnableNopasswd(Named),
+    #[clap(name = "disable-nopasswd")]
+    /// Require reauthentication before commands are run (default)
+    DisableNopasswd(Named),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HostOpt {
+    #[clap(name = "list")]
+    /// List all hosts
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// Display a host
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a new host. Use `service-account api-token generate` to issue the
+    /// credential that the host's kanidm_unixd authenticates with.
+    Create(Named),
+    #[clap(name = "delete")]
+    /// Delete a host
+    Delete(Named),
+}
+
+#[derive(Debug, Args)]
+pub struct HbacRuleValuesOpt {
+    /// The name of the host access rule
+    pub name: String,
+    #[clap(required = true, num_args(1..))]
+    pub values: Vec<String>,
+    #[clap(flatten)]
+    pub copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HbacRuleOpt {
+    #[clap(name = "list")]
+    /// List all host access rules
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// Display a host access rule
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a new host access rule
+    Create(Named),
+    #[clap(name = "delete")]
+    /// Delete a host access rule
+    Delete(Named),
+    #[clap(name = "add-users")]
+    /// Add accounts or groups that this rule applies to
+    AddUsers(HbacRuleValuesOpt),
+    #[clap(name = "remove-users")]
+    /// Remove accounts or groups from this rule
+    RemoveUsers(HbacRuleValuesOpt),
+    #[clap(name = "add-hosts")]
+    /// Add hosts or groups of hosts that this rule applies on
+    AddHosts(HbacRuleValuesOpt),
+    #[clap(name = "remove-hosts")]
+    /// Remove hosts or groups of hosts from this rule
+    RemoveHosts(HbacRuleValuesOpt),
+    #[clap(name = "add-services")]
+    /// Add PAM services that this rule allows. "ALL" matches any service.
+    AddServices(HbacRuleValuesOpt),
+    #[clap(name = "remove-services")]
+    /// Remove PAM services from this rule
+    RemoveServices(HbacRuleValuesOpt),
+}
+
+#[derive(Debug, Args)]
+pub struct AutomountSetKeyOpt {
+    /// The name of the automount map
+    pub name: String,
+    /// The key within the map, such as the directory to mount. "*" matches any key.
+    pub key: String,
+    /// The mount options and location, such as "-rw nfs.example.com:/home/&"
+    pub information: String,
+    #[clap(flatten)]
+    pub copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct AutomountKeyOpt {
+    /// The name of the automount map
+    pub name: String,
+    /// The key within the map
+    pub key: String,
+    #[clap(flatten)]
+    pub copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AutomountOpt {
+    #[clap(name = "list")]
+    /// List all automount maps
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// Display an automount map and its keys
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a new automount map
+    Create(Named),
+    #[clap(name = "delete")]
+    /// Delete an automount map and all of its keys
+    Delete(Named),
+    #[clap(name = "set-key")]
+    /// Add a key to an automount map, or replace the information of an existing key
+    SetKey(AutomountSetKeyOpt),
+    #[clap(name = "remove-key")]
+    /// Remove a key from an automount map
+    RemoveKey(AutomountKeyOpt),
+    #[clap(name = "unix-list")]
+    /// Show the automount maps as they are provided to unix hosts
+    UnixList(CommonOpt),
+}
+
+#[derive(Debug, Args)]
+pub struct ReplicationConflictMergeOpt {
+    /// The uuid of the conflict entry
+    pub name: String,
+    /// An attribute to copy from the conflict entry to the live entry. May be repeated.
+    #[clap(long = "attr", required = true)]
+    pub attrs: Vec<String>,
+    #[clap(flatten)]
+    pub copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReplicationConflictOpt {
+    #[clap(name = "list")]
+    /// List entries that are in a replication conflict
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// Display a conflict entry and the live entries it conflicts with
+    Get(Named),
+    #[clap(name = "discard")]
+    /// Discard the conflict entry, keeping the live entry as is
+    Discard(Named),
+    #[clap(name = "accept")]
+    /// Replace the content of the live entry with the conflict entry
+    Accept(Named),
+    #[clap(name = "merge")]
+    /// Copy selected attributes from the conflict entry to the live entry
+    Merge(ReplicationConflictMergeOpt),
+    #[clap(name = "revive")]
+    /// Revive the conflict entry as a live entry of its own
+    Revive(Named),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReplicationOpt {
+    #[clap(name = "conflicts")]
+    /// Inspect and resolve replication conflicts
+    Conflicts {
+        #[clap(subcommand)]
+        commands: ReplicationConflictOpt,
+    },
+}
+
 #[derive(Debug, Args)]
 pub struct LoginOpt {
     #[clap(flatten)]
@@ -632,6 +1011,23 @@ pub struct Oauth2SetImplicitScopes {
     scopes: Vec<String>,
 }
 
+#[derive(Debug, Args)]
+pub struct Oauth2SetClientCredentialsScopes {
+    #[clap(flatten)]
+    nopt: Named,
+    #[clap(name = "scopes", required = true)]
+    scopes: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct Oauth2TokenExchangeSourceOpt {
+    #[clap(flatten)]
+    nopt: Named,
+    /// The resource server that may exchange its tokens for tokens to this resource server
+    #[clap(name = "source")]
+    source: String,
+}
+
 #[derive(Debug, Args)]
 pub struct Oauth2CreateScopeMapOpt {
     #[clap(flatten)]
@@ -703,6 +1099,21 @@ pub enum Oauth2Opt {
     /// Remove a mapping from groups to scopes
     DeleteSupScopeMap(Oauth2DeleteScopeMapOpt),
 
+    #[clap(name = "update-client-credentials-scopes")]
+    /// Set the scopes that this resource server may be granted for itself with the
+    /// client credentials grant. This is only possible for confidential resource servers.
+    UpdateClientCredentialsScopes(Oauth2SetClientCredentialsScopes),
+    #[clap(name = "delete-client-credentials-scopes")]
+    /// Remove the client credentials scopes, disabling the client credentials grant
+    DeleteClientCredentialsScopes(Named),
+    #[clap(name = "add-token-exchange-source")]
+    /// Allow another resource server to exchange the access tokens it has been issued for
+    /// access tokens to this resource server, acting on behalf of the user
+    AddTokenExchangeSource(Oauth2TokenExchangeSourceOpt),
+    #[clap(name = "remove-token-exchange-source")]
+    /// Prevent a resource server from exchanging tokens for this resource server
+    RemoveTokenExchangeSource(Oauth2TokenExchangeSourceOpt),
+
     #[clap(name = "reset-secrets")]
     /// Reset the secrets associated to this resource server
     ResetSecrets(Named),
@@ -749,6 +1160,13 @@ pub enum Oauth2Opt {
     /// Disable legacy signing crypto on this oauth2 resource server. This is the default.
     #[clap(name = "disable-legacy-crypto")]
     DisableLegacyCrypto(Named),
+    /// Allow devices with limited input, such as TVs or command line tools, to
+    /// authenticate to this oauth2 resource server with the device authorisation grant.
+    #[clap(name = "enable-device-flow")]
+    EnableDeviceFlow(Named),
+    /// Disable the device authorisation grant on this oauth2 resource server. This is the default.
+    #[clap(name = "disable-device-flow")]
+    DisableDeviceFlow(Named),
     #[clap(name = "prefer-short-username")]
     /// Use the 'name' attribute instead of 'spn' for the preferred_username
     PreferShortUsername(Named),
@@ -982,6 +1400,12 @@ pub enum SystemOpt {
         #[clap(subcommand)]
         commands: SynchOpt,
     },
+    #[clap(name = "replication")]
+    /// Manage replication between servers
+    Replication {
+        #[clap(subcommand)]
+        commands: ReplicationOpt,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -1031,6 +1455,36 @@ pub enum KanidmClientOpt {
         #[clap(subcommand)]
         commands: RecycleOpt,
     },
+    #[clap(name = "access-review")]
+    /// Review and attest to the memberships of groups
+    AccessReview {
+        #[clap(subcommand)]
+        commands: AccessReviewOpt,
+    },
+    #[clap(name = "sudo-rule")]
+    /// Manage the sudo rules that are enforced by unix hosts
+    SudoRule {
+        #[clap(subcommand)]
+        commands: SudoRuleOpt,
+    },
+    #[clap(name = "host")]
+    /// Manage the hosts that authenticate to Kanidm
+    Host {
+        #[clap(subcommand)]
+        commands: HostOpt,
+    },
+    #[clap(name = "hbac-rule")]
+    /// Manage the rules that control which accounts may access unix hosts
+    HbacRule {
+        #[clap(subcommand)]
+        commands: HbacRuleOpt,
+    },
+    #[clap(name = "automount")]
+    /// Manage the automount maps that are used by autofs on unix hosts
+    Automount {
+        #[clap(subcommand)]
+        commands: AutomountOpt,
+    },
     /// Unsafe - low level, raw database queries and operations.
     #[clap(hide = true)]
     Raw {
@@ -0,0 +1,47 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use url::Url;
+use uuid::Uuid;
+
+use ldap3_client::proto::LdapFilter;
+
+fn ad_filter() -> LdapFilter {
+    // Deleted objects lose most of their attributes (such as objectCategory) so this
+    // filter must remain broad enough to still match them. Computer accounts are
+    // users, and are skipped when the entries are processed.
+    LdapFilter::Or(vec![
+        LdapFilter::Equality("objectClass".to_string(), "user".to_string()),
+        LdapFilter::Equality("objectClass".to_string(), "group".to_string()),
+    ])
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub sync_token: String,
+    pub schedule: Option<String>,
+    pub status_bind: Option<String>,
+    pub ad_uri: Url,
+    pub ad_ca: String,
+    pub ad_sync_dn: String,
+    pub ad_sync_pw: String,
+    /// DirSync can only be performed from the root of a naming context, so this must be
+    /// the base dn of the domain.
+    pub ad_sync_base_dn: String,
+
+    #[serde(default = "ad_filter")]
+    pub ad_filter: LdapFilter,
+
+    #[serde(flatten)]
+    pub entry_map: BTreeMap<Uuid, EntryConfig>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct EntryConfig {
+    // Default false
+    #[serde(default)]
+    pub exclude: bool,
+
+    pub map_uuid: Option<Uuid>,
+    pub map_name: Option<String>,
+    pub map_gidnumber: Option<u32>,
+}
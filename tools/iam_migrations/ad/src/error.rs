@@ -0,0 +1,11 @@
+#[derive(Clone, Debug)]
+pub enum SyncError {
+    ClientConfig,
+    LdapConn,
+    LdapAuth,
+    LdapDirSync,
+    LdapSearch,
+    SyncStatus,
+    SyncUpdate,
+    Preprocess,
+}
@@ -0,0 +1,1289 @@
+#![deny(warnings)]
+#![warn(unused_extern_crates)]
+#![deny(clippy::todo)]
+#![deny(clippy::unimplemented)]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::panic)]
+#![deny(clippy::unreachable)]
+#![deny(clippy::await_holding_lock)]
+#![deny(clippy::needless_pass_by_value)]
+#![deny(clippy::trivially_copy_pass_by_ref)]
+// We allow expect since it forces good error messages at the least.
+#![allow(clippy::expect_used)]
+
+mod config;
+mod error;
+
+use crate::config::{Config, EntryConfig};
+use crate::error::SyncError;
+use base64urlsafedata::Base64UrlSafeData;
+use chrono::{SecondsFormat, TimeZone, Utc};
+use clap::Parser;
+use cron::Schedule;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::metadata;
+use std::fs::File;
+use std::io::Read;
+#[cfg(target_family = "unix")]
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::runtime;
+use tokio::sync::broadcast;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+
+use kanidm_client::KanidmClientBuilder;
+use kanidm_lib_file_permissions::readonly as file_permissions_readonly;
+use kanidm_proto::scim_v1::{
+    MultiValueAttr, ScimEntry, ScimExternalMember, ScimSyncGroup, ScimSyncPerson, ScimSyncRequest,
+    ScimSyncRetentionMode, ScimSyncState,
+};
+
+#[cfg(target_family = "unix")]
+use kanidm_utils_users::{get_current_gid, get_current_uid, get_effective_gid, get_effective_uid};
+
+use ldap3_client::proto::LdapFilter;
+use ldap3_client::{LdapClient, LdapClientBuilder, LdapEntry, LdapSyncRepl, LdapSyncReplEntry};
+
+/// The userAccountControl flag that is set on disabled accounts.
+const UF_ACCOUNTDISABLE: u32 = 0x0002;
+
+/// Seconds from the start of the windows FILETIME epoch (1601-01-01) to the unix epoch.
+const FILETIME_UNIX_EPOCH_OFFSET: i64 = 11_644_473_600;
+
+/// The account expiry of a disabled account. Kanidm has no disabled state, so the account
+/// is instead expired from the start of time until it is enabled again.
+const ACCOUNT_DISABLED_EXPIRE: &str = "1970-01-01T00:00:00Z";
+
+include!("./opt.rs");
+
+async fn driver_main(opt: Opt) {
+    debug!("Starting kanidm ad sync driver.");
+
+    let mut f = match File::open(&opt.ad_sync_config) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("Unable to open profile file [{:?}] 🥺", e);
+            return;
+        }
+    };
+
+    let mut contents = String::new();
+    if let Err(e) = f.read_to_string(&mut contents) {
+        error!("unable to read profile contents {:?}", e);
+        return;
+    };
+
+    let sync_config: Config = match toml::from_str(contents.as_str()) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("unable to parse config {:?}", e);
+            return;
+        }
+    };
+
+    debug!(?sync_config);
+
+    let cb = match KanidmClientBuilder::new().read_options_from_optional_config(&opt.client_config)
+    {
+        Ok(v) => v,
+        Err(_) => {
+            error!("Failed to parse {}", opt.client_config.to_string_lossy());
+            return;
+        }
+    };
+
+    let expression = sync_config.schedule.as_deref().unwrap_or("0 */5 * * * * *");
+
+    let schedule = match Schedule::from_str(expression) {
+        Ok(s) => s,
+        Err(_) => {
+            error!("Failed to parse cron schedule expression");
+            return;
+        }
+    };
+
+    if opt.schedule {
+        let last_op_status = Arc::new(AtomicBool::new(true));
+        let (broadcast_tx, mut broadcast_rx) = broadcast::channel(4);
+
+        let last_op_status_c = last_op_status.clone();
+
+        // Can we setup the socket for status?
+
+        let status_handle = if let Some(sb) = sync_config.status_bind.as_deref() {
+            // Can we bind?
+            let listener = match TcpListener::bind(sb).await {
+                Ok(l) => l,
+                Err(e) => {
+                    error!(?e, "Failed to bind status socket");
+                    return;
+                }
+            };
+
+            info!("Status listener is started on {:?}", sb);
+            // Detach a status listener.
+            let status_rx = broadcast_tx.subscribe();
+            Some(tokio::spawn(async move {
+                status_task(listener, status_rx, last_op_status_c).await
+            }))
+        } else {
+            warn!("No status listener configured, this will prevent you monitoring the sync tool");
+            None
+        };
+
+        // main driver loop
+        let driver_handle = tokio::spawn(async move {
+            loop {
+                let now = Utc::now();
+                let next_time = match schedule.after(&now).next() {
+                    Some(v) => v,
+                    None => {
+                        error!("Failed to access any future scheduled events, terminating.");
+                        break;
+                    }
+                };
+
+                // If we don't do 1 + here we can trigger the event multiple times
+                // rapidly since we are in the same second.
+                let wait_seconds = 1 + (next_time - now).num_seconds() as u64;
+                info!("next sync on {}, wait_time = {}s", next_time, wait_seconds);
+
+                tokio::select! {
+                    _ = broadcast_rx.recv() => {
+                        // stop the event loop!
+                        break;
+                    }
+                    _ = sleep(Duration::from_secs(wait_seconds)) => {
+                        info!("starting sync ...");
+                        match run_sync(cb.clone(), &sync_config, &opt).await {
+                            Ok(_) => last_op_status.store(true, Ordering::Relaxed),
+                            Err(e) => {
+                                error!(?e, "sync completed with error");
+                                last_op_status.store(false, Ordering::Relaxed)
+                            }
+                        };
+                    }
+                }
+            }
+            info!("Stopped sync driver");
+        });
+
+        // Block on signals now.
+        loop {
+            #[cfg(target_family = "unix")]
+            {
+                tokio::select! {
+                    Ok(()) = tokio::signal::ctrl_c() => {
+                        break
+                    }
+                    Some(()) = async move {
+                        let sigterm = tokio::signal::unix::SignalKind::terminate();
+                        #[allow(clippy::unwrap_used)]
+                        tokio::signal::unix::signal(sigterm).unwrap().recv().await
+                    } => {
+                        break
+                    }
+                    Some(()) = async move {
+                        let sigterm = tokio::signal::unix::SignalKind::alarm();
+                        #[allow(clippy::unwrap_used)]
+                        tokio::signal::unix::signal(sigterm).unwrap().recv().await
+                    } => {
+                        // Ignore
+                    }
+                    Some(()) = async move {
+                        let sigterm = tokio::signal::unix::SignalKind::hangup();
+                        #[allow(clippy::unwrap_used)]
+                        tokio::signal::unix::signal(sigterm).unwrap().recv().await
+                    } => {
+                        // Ignore
+                    }
+                    Some(()) = async move {
+                        let sigterm = tokio::signal::unix::SignalKind::user_defined1();
+                        #[allow(clippy::unwrap_used)]
+                        tokio::signal::unix::signal(sigterm).unwrap().recv().await
+                    } => {
+                        // Ignore
+                    }
+                    Some(()) = async move {
+                        let sigterm = tokio::signal::unix::SignalKind::user_defined2();
+                        #[allow(clippy::unwrap_used)]
+                        tokio::signal::unix::signal(sigterm).unwrap().recv().await
+                    } => {
+                        // Ignore
+                    }
+                }
+            }
+            #[cfg(target_family = "windows")]
+            {
+                tokio::select! {
+                    Ok(()) = tokio::signal::ctrl_c() => {
+                        break
+                    }
+                }
+            }
+        }
+
+        broadcast_tx
+            .send(true)
+            .expect("Failed to trigger a clean shutdown!");
+
+        let _ = driver_handle.await;
+        if let Some(sh) = status_handle {
+            let _ = sh.await;
+        }
+    } else if let Err(e) = run_sync(cb, &sync_config, &opt).await {
+        error!(?e, "Sync completed with error");
+    }
+}
+
+async fn run_sync(
+    cb: KanidmClientBuilder,
+    sync_config: &Config,
+    opt: &Opt,
+) -> Result<(), SyncError> {
+    let rsclient = match cb.build() {
+        Ok(rsc) => rsc,
+        Err(_e) => {
+            error!("Failed to build async client");
+            return Err(SyncError::ClientConfig);
+        }
+    };
+
+    rsclient.set_token(sync_config.sync_token.clone()).await;
+
+    // Preflight check.
+    //  * can we connect to ad?
+    let mut ldap_client = match LdapClientBuilder::new(&sync_config.ad_uri)
+        .add_tls_ca(&sync_config.ad_ca)
+        .build()
+        .await
+    {
+        Ok(lc) => lc,
+        Err(e) => {
+            error!(?e, "Failed to connect to active directory");
+            return Err(SyncError::LdapConn);
+        }
+    };
+
+    match ldap_client
+        .bind(
+            sync_config.ad_sync_dn.clone(),
+            sync_config.ad_sync_pw.clone(),
+        )
+        .await
+    {
+        Ok(()) => {
+            debug!(ad_sync_dn = ?sync_config.ad_sync_dn, ad_uri = %sync_config.ad_uri);
+        }
+        Err(e) => {
+            error!(?e, "Failed to bind (authenticate) to active directory");
+            return Err(SyncError::LdapAuth);
+        }
+    };
+
+    //  * can we connect to kanidm?
+    // - get the current sync cookie from kanidm.
+    let scim_sync_status = match rsclient.scim_v1_sync_status().await {
+        Ok(s) => s,
+        Err(e) => {
+            error!(?e, "Failed to access scim sync status");
+            return Err(SyncError::SyncStatus);
+        }
+    };
+
+    debug!(state=?scim_sync_status);
+
+    // === Everything is connected! ===
+
+    let cookie = match &scim_sync_status {
+        ScimSyncState::Refresh => None,
+        ScimSyncState::Active { cookie } => Some(cookie.0.clone()),
+    };
+
+    let filter = sync_config.ad_filter.clone();
+
+    debug!(ad_sync_base_dn = ?sync_config.ad_sync_base_dn, ?cookie, ?filter);
+    let sync_result = match ldap_client
+        .ad_dirsync(sync_config.ad_sync_base_dn.clone(), filter, cookie)
+        .await
+    {
+        Ok(results) => results,
+        Err(e) => {
+            error!(?e, "Failed to perform dirsync from active directory");
+            return Err(SyncError::LdapDirSync);
+        }
+    };
+
+    if opt.proto_dump {
+        let stdout = std::io::stdout();
+        if let Err(e) = serde_json::to_writer_pretty(stdout, &sync_result) {
+            error!(?e, "Failed to serialise dirsync response");
+        }
+    }
+
+    let scim_sync_request = match sync_result {
+        LdapSyncRepl::Success {
+            cookie,
+            refresh_deletes: _,
+            entries,
+            delete_uuids,
+            present_uuids: _,
+        } => {
+            let to_state = if let Some(cookie) = cookie {
+                ScimSyncState::Active { cookie }
+            } else {
+                info!("no changes required");
+                return Ok(());
+            };
+
+            let (retain, changed_uuids) = ad_dirsync_changes(entries, delete_uuids);
+
+            let ad_entries = read_changed_entries(&mut ldap_client, changed_uuids).await?;
+
+            let entries = process_ad_sync_result(&mut ldap_client, ad_entries, sync_config).await?;
+
+            ScimSyncRequest {
+                from_state: scim_sync_status,
+                to_state,
+                entries,
+                retain,
+            }
+        }
+        LdapSyncRepl::RefreshRequired => {
+            let to_state = ScimSyncState::Refresh;
+
+            ScimSyncRequest {
+                from_state: scim_sync_status,
+                to_state,
+                entries: Vec::new(),
+                retain: ScimSyncRetentionMode::Ignore,
+            }
+        }
+    };
+
+    if opt.proto_dump {
+        let stdout = std::io::stdout();
+        // write it out.
+        if let Err(e) = serde_json::to_writer_pretty(stdout, &scim_sync_request) {
+            error!(?e, "Failed to serialise scim sync request");
+        };
+        Ok(())
+    } else if opt.dry_run {
+        info!("dry-run complete");
+        info!("Success!");
+        Ok(())
+    } else if let Err(e) = rsclient.scim_v1_sync_update(&scim_sync_request).await {
+        error!(
+            ?e,
+            "Failed to submit scim sync update - see the kanidmd server log for more details."
+        );
+        Err(SyncError::SyncUpdate)
+    } else {
+        info!("Success!");
+        Ok(())
+    }
+    // done!
+}
+
+/// Split the entries returned by dirsync into the objects that were deleted, and the objects
+/// that changed. Deleted objects are returned by dirsync as tombstones, which have isDeleted set.
+fn ad_dirsync_changes(
+    entries: Vec<LdapSyncReplEntry>,
+    delete_uuids: Option<Vec<Uuid>>,
+) -> (ScimSyncRetentionMode, BTreeSet<Uuid>) {
+    let mut delete_uuids = delete_uuids.unwrap_or_default();
+    let mut changed_uuids = BTreeSet::new();
+
+    for lentry in entries {
+        if ad_is_deleted(&lentry.entry) {
+            delete_uuids.push(lentry.entry_uuid);
+        } else {
+            changed_uuids.insert(lentry.entry_uuid);
+        }
+    }
+
+    let retain = if delete_uuids.is_empty() {
+        ScimSyncRetentionMode::Ignore
+    } else {
+        ScimSyncRetentionMode::Delete(delete_uuids)
+    };
+
+    (retain, changed_uuids)
+}
+
+/// Dirsync only returns the attributes of an object that changed, so each changed object
+/// is read again in full to build its scim entry.
+async fn read_changed_entries(
+    ldap_client: &mut LdapClient,
+    changed_uuids: BTreeSet<Uuid>,
+) -> Result<BTreeMap<Uuid, LdapEntry>, SyncError> {
+    let mut ad_entries = BTreeMap::new();
+
+    for entry_uuid in changed_uuids {
+        // AD allows an object to be used as a search base by its objectGUID.
+        let basedn = format!("<GUID={}>", entry_uuid);
+        let filter = LdapFilter::Present("objectClass".to_string());
+
+        let mut result = ldap_client.search(basedn, filter).await.map_err(|e| {
+            error!(?e, %entry_uuid, "Failed to read changed entry");
+            SyncError::LdapSearch
+        })?;
+
+        match result.entries.pop() {
+            Some(entry) => {
+                ad_entries.insert(entry_uuid, entry);
+            }
+            None => {
+                warn!(%entry_uuid, "Changed entry no longer exists, skipping");
+            }
+        }
+    }
+
+    Ok(ad_entries)
+}
+
+async fn process_ad_sync_result(
+    ldap_client: &mut LdapClient,
+    mut ad_entries: BTreeMap<Uuid, LdapEntry>,
+    sync_config: &Config,
+) -> Result<Vec<ScimEntry>, SyncError> {
+    // A group does not list the users it is the primary group of in member, and kanidm
+    // replaces the members of a group on each sync. So when a user changes, their primary
+    // group must be sent again to add them. If the primary group of a user changed, they
+    // remain a member of their previous primary group until that group is next changed.
+    let primary_group_sids: BTreeSet<AdSid> = ad_entries
+        .values()
+        .filter(|entry| ad_is_person(entry))
+        .filter_map(ad_primary_group_sid)
+        .collect();
+
+    let known_sids: BTreeSet<AdSid> = ad_entries.values().filter_map(ad_object_sid).collect();
+
+    for sid in primary_group_sids.difference(&known_sids) {
+        // AD accepts the string form of a sid when filtering on objectSid.
+        let filter = LdapFilter::Equality("objectSid".to_string(), sid.to_string());
+
+        let result = ldap_client
+            .search(sync_config.ad_sync_base_dn.clone(), filter)
+            .await
+            .map_err(|e| {
+                error!(?e, %sid, "Failed to read primary group");
+                SyncError::LdapSearch
+            })?;
+
+        for entry in result.entries {
+            match ad_attr_single(&entry, "objectguid").and_then(ad_object_guid) {
+                Some(entry_uuid) => {
+                    ad_entries.insert(entry_uuid, entry);
+                }
+                None => {
+                    warn!(dn = %entry.dn, "Invalid entry - no objectGUID, skipping");
+                }
+            }
+        }
+    }
+
+    let mut scim_entries = Vec::with_capacity(ad_entries.len());
+
+    for (entry_uuid, entry) in ad_entries {
+        let e_config = sync_config
+            .entry_map
+            .get(&entry_uuid)
+            .cloned()
+            .unwrap_or_default();
+
+        let primary_members = match ad_object_sid(&entry) {
+            Some(sid) if ad_has_objectclass(&entry, "group") => {
+                ad_primary_group_members(ldap_client, &sid, sync_config).await?
+            }
+            _ => Vec::new(),
+        };
+
+        if let Some(scim_entry) = ad_to_scim_entry(entry_uuid, entry, primary_members, &e_config)
+            .map_err(|()| {
+                error!("Failed to process AD entries to SCIM");
+                SyncError::Preprocess
+            })?
+        {
+            scim_entries.push(scim_entry);
+        }
+    }
+
+    Ok(scim_entries)
+}
+
+/// The dns of the users that have this group as their primary group.
+async fn ad_primary_group_members(
+    ldap_client: &mut LdapClient,
+    group_sid: &AdSid,
+    sync_config: &Config,
+) -> Result<Vec<String>, SyncError> {
+    let Some(rid) = group_sid.rid() else {
+        return Ok(Vec::new());
+    };
+
+    let filter = LdapFilter::And(vec![
+        LdapFilter::Equality("objectClass".to_string(), "user".to_string()),
+        LdapFilter::Equality("primaryGroupID".to_string(), rid.to_string()),
+    ]);
+
+    let result = ldap_client
+        .search(sync_config.ad_sync_base_dn.clone(), filter)
+        .await
+        .map_err(|e| {
+            error!(?e, %group_sid, "Failed to search for primary group members");
+            SyncError::LdapSearch
+        })?;
+
+    Ok(result.entries.into_iter().map(|entry| entry.dn).collect())
+}
+
+fn ad_to_scim_entry(
+    entry_uuid: Uuid,
+    mut entry: LdapEntry,
+    primary_members: Vec<String>,
+    entry_config: &EntryConfig,
+) -> Result<Option<ScimEntry>, ()> {
+    debug!("{:#?}", entry);
+
+    let dn = entry.dn.clone();
+
+    // Is this an entry we need to observe/look at?
+    if entry_config.exclude {
+        info!("entry_config excludes {}", dn);
+        return Ok(None);
+    }
+
+    if !entry.attrs.contains_key("objectclass") {
+        error!("Invalid entry - no object class {}", dn);
+        return Err(());
+    }
+
+    if ad_is_person(&entry) {
+        let id = if let Some(map_uuid) = &entry_config.map_uuid {
+            *map_uuid
+        } else {
+            entry_uuid
+        };
+
+        let user_name = if let Some(name) = entry_config.map_name.clone() {
+            name
+        } else {
+            entry.remove_ava_single("samaccountname").ok_or_else(|| {
+                error!("Missing required attribute sAMAccountName {}", dn);
+            })?
+        };
+
+        // Service accounts commonly have no display name.
+        let display_name = entry
+            .remove_ava_single("displayname")
+            .unwrap_or_else(|| user_name.clone());
+
+        let gidnumber = if let Some(number) = entry_config.map_gidnumber {
+            Some(number)
+        } else {
+            entry
+                .remove_ava_single("uidnumber")
+                .map(|gid| {
+                    u32::from_str(&gid).map_err(|_| {
+                        error!("Invalid uidNumber - {} is not a u32", gid);
+                    })
+                })
+                .transpose()?
+        };
+
+        let mail: Vec<_> = entry
+            .remove_ava("mail")
+            .map(|set| {
+                set.into_iter()
+                    .map(|addr| MultiValueAttr {
+                        type_: None,
+                        primary: None,
+                        display: None,
+                        ref_: None,
+                        value: addr,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let login_shell = entry.remove_ava_single("loginshell");
+
+        let account_expire = ad_account_expire(&mut entry)?;
+
+        let external_id = Some(entry.dn);
+
+        Ok(Some(
+            ScimSyncPerson {
+                id,
+                external_id,
+                user_name,
+                display_name,
+                gidnumber,
+                // AD will never disclose password hashes over ldap.
+                password_import: None,
+                totp_import: Vec::default(),
+                login_shell,
+                mail,
+                ssh_publickey: Vec::default(),
+                account_expire,
+                // AD has no equivalent of a time an account becomes valid from.
+                account_valid_from: None,
+            }
+            .into(),
+        ))
+    } else if ad_has_objectclass(&entry, "group") {
+        let id = entry_uuid;
+
+        let name = entry.remove_ava_single("samaccountname").ok_or_else(|| {
+            error!("Missing required attribute sAMAccountName {}", dn);
+        })?;
+
+        let description = entry.remove_ava_single("description");
+
+        let gidnumber = entry
+            .remove_ava_single("gidnumber")
+            .map(|gid| {
+                u32::from_str(&gid).map_err(|_| {
+                    error!("Invalid gidNumber - {} is not a u32", gid);
+                })
+            })
+            .transpose()?;
+
+        // Nested groups are members of their parent group, and kanidm resolves the nested
+        // memberships of users (the same as tokenGroups in AD) from these.
+        let members: Vec<_> = entry
+            .remove_ava("member")
+            .unwrap_or_default()
+            .into_iter()
+            .chain(primary_members)
+            .map(|external_id| ScimExternalMember { external_id })
+            .collect();
+
+        let external_id = Some(entry.dn);
+
+        Ok(Some(
+            ScimSyncGroup {
+                id,
+                external_id,
+                name,
+                description,
+                gidnumber,
+                members,
+            }
+            .into(),
+        ))
+    } else {
+        debug!(
+            "Skipping entry {} with oc {:?}",
+            dn,
+            entry.attrs.get("objectclass")
+        );
+        Ok(None)
+    }
+}
+
+/// Determine when an account expires from its userAccountControl and accountExpires.
+fn ad_account_expire(entry: &mut LdapEntry) -> Result<Option<String>, ()> {
+    let account_control = entry
+        .remove_ava_single("useraccountcontrol")
+        .map(|uac| {
+            u32::from_str(&uac).map_err(|_| {
+                error!("Invalid userAccountControl - {} is not a u32", uac);
+            })
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    if account_control & UF_ACCOUNTDISABLE != 0 {
+        return Ok(Some(ACCOUNT_DISABLED_EXPIRE.to_string()));
+    }
+
+    entry
+        .remove_ava_single("accountexpires")
+        .map(|expires| ad_filetime_to_rfc3339(&expires))
+        .transpose()
+        .map(Option::flatten)
+}
+
+/// Convert a windows FILETIME (100ns intervals since 1601-01-01) to an rfc3339 datetime. AD
+/// uses both 0 and the maximum value to indicate a time that is never reached.
+fn ad_filetime_to_rfc3339(value: &str) -> Result<Option<String>, ()> {
+    let filetime = i64::from_str(value).map_err(|_| {
+        error!("Invalid FILETIME - {} is not an integer", value);
+    })?;
+
+    if filetime <= 0 || filetime == i64::MAX {
+        return Ok(None);
+    }
+
+    let secs = filetime / 10_000_000 - FILETIME_UNIX_EPOCH_OFFSET;
+
+    Utc.timestamp_opt(secs, 0)
+        .single()
+        .map(|dt| Some(dt.to_rfc3339_opts(SecondsFormat::Secs, true)))
+        .ok_or_else(|| {
+            error!("Invalid FILETIME - {} is out of range", value);
+        })
+}
+
+fn ad_attr_single<'a>(entry: &'a LdapEntry, attr: &str) -> Option<&'a str> {
+    entry
+        .attrs
+        .get(attr)
+        .and_then(|values| values.first())
+        .map(String::as_str)
+}
+
+fn ad_has_objectclass(entry: &LdapEntry, class: &str) -> bool {
+    entry
+        .attrs
+        .get("objectclass")
+        .map(|values| values.iter().any(|oc| oc.eq_ignore_ascii_case(class)))
+        .unwrap_or(false)
+}
+
+/// Computers are also users in AD, but are not synchronised.
+fn ad_is_person(entry: &LdapEntry) -> bool {
+    ad_has_objectclass(entry, "user") && !ad_has_objectclass(entry, "computer")
+}
+
+fn ad_is_deleted(entry: &LdapEntry) -> bool {
+    ad_attr_single(entry, "isdeleted")
+        .map(|deleted| deleted.eq_ignore_ascii_case("TRUE"))
+        .unwrap_or(false)
+}
+
+/// objectGUID is a binary attribute, where the first three fields of the uuid are little endian.
+fn ad_object_guid(value: &str) -> Option<Uuid> {
+    Base64UrlSafeData::try_from(value)
+        .ok()
+        .and_then(|bytes| <[u8; 16]>::try_from(bytes.0.as_slice()).ok())
+        .map(Uuid::from_bytes_le)
+}
+
+fn ad_object_sid(entry: &LdapEntry) -> Option<AdSid> {
+    ad_attr_single(entry, "objectsid").and_then(AdSid::from_base64)
+}
+
+/// The sid of the primary group of a user, which is in the same domain as the user.
+fn ad_primary_group_sid(entry: &LdapEntry) -> Option<AdSid> {
+    let rid = ad_attr_single(entry, "primarygroupid").and_then(|rid| u32::from_str(rid).ok())?;
+    ad_object_sid(entry).map(|sid| sid.with_rid(rid))
+}
+
+/// A windows security identifier, such as the objectSid of a user or group.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct AdSid {
+    revision: u8,
+    authority: u64,
+    sub_authorities: Vec<u32>,
+}
+
+impl AdSid {
+    fn from_base64(value: &str) -> Option<Self> {
+        let bytes = Base64UrlSafeData::try_from(value).ok()?.0;
+
+        let (&revision, bytes) = bytes.split_first()?;
+        let (&count, bytes) = bytes.split_first()?;
+
+        if bytes.len() != 6 + 4 * count as usize {
+            return None;
+        }
+
+        let (authority, sub_authorities) = bytes.split_at(6);
+
+        // The authority is big endian, but the sub authorities are little endian.
+        let authority = authority
+            .iter()
+            .fold(0, |acc, byte| (acc << 8) | u64::from(*byte));
+
+        let sub_authorities = sub_authorities
+            .chunks_exact(4)
+            .map(|chunk| <[u8; 4]>::try_from(chunk).ok().map(u32::from_le_bytes))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(AdSid {
+            revision,
+            authority,
+            sub_authorities,
+        })
+    }
+
+    /// The relative id of the object within its domain.
+    fn rid(&self) -> Option<u32> {
+        self.sub_authorities.last().copied()
+    }
+
+    /// The sid of another object in the same domain.
+    fn with_rid(&self, rid: u32) -> Self {
+        let mut sid = self.clone();
+        if let Some(last) = sid.sub_authorities.last_mut() {
+            *last = rid;
+        }
+        sid
+    }
+}
+
+impl std::fmt::Display for AdSid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "S-{}-{}", self.revision, self.authority)?;
+        for sub_authority in self.sub_authorities.iter() {
+            write!(f, "-{}", sub_authority)?;
+        }
+        Ok(())
+    }
+}
+
+async fn status_task(
+    listener: TcpListener,
+    mut status_rx: broadcast::Receiver<bool>,
+    last_op_status: Arc<AtomicBool>,
+) {
+    loop {
+        tokio::select! {
+            _ = status_rx.recv() => {
+                break;
+            }
+            maybe_sock = listener.accept() => {
+                let mut stream = match maybe_sock {
+                    Ok((sock, addr)) => {
+                        debug!("accept from {:?}", addr);
+                        sock
+                    }
+                    Err(e) => {
+                        error!(?e, "Failed to accept status connection");
+                        continue;
+                    }
+                };
+
+                let sr = if last_op_status.load(Ordering::Relaxed) {
+                     stream.write_all(b"Ok\n").await
+                } else {
+                     stream.write_all(b"Err\n").await
+                };
+                if let Err(e) = sr {
+                    error!(?e, "Failed to send status");
+                }
+            }
+        }
+    }
+    info!("Stopped status task");
+}
+
+fn config_security_checks(cfg_path: &Path) -> bool {
+    let cfg_path_str = cfg_path.to_string_lossy();
+
+    if !cfg_path.exists() {
+        // there's no point trying to start up if we can't read a usable config!
+        error!(
+            "Config missing from {} - cannot start up. Quitting.",
+            cfg_path_str
+        );
+        false
+    } else {
+        let cfg_meta = match metadata(cfg_path) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(
+                    "Unable to read metadata for '{}' during security checks - {:?}",
+                    cfg_path_str, e
+                );
+                return false;
+            }
+        };
+        if !file_permissions_readonly(&cfg_meta) {
+            warn!("permissions on {} may not be secure. Should be readonly to running uid. This could be a security risk ...",
+                cfg_path_str
+                );
+        }
+
+        #[cfg(target_family = "unix")]
+        if cfg_meta.uid() == get_current_uid() || cfg_meta.uid() == get_effective_uid() {
+            warn!("WARNING: {} owned by the current uid, which may allow file permission changes. This could be a security risk ...",
+                cfg_path_str
+            );
+        }
+
+        true
+    }
+}
+
+fn main() {
+    let opt = Opt::parse();
+
+    let fmt_layer = fmt::layer().with_writer(std::io::stderr);
+
+    let filter_layer = if opt.debug {
+        match EnvFilter::try_new("kanidm_client=debug,kanidm_ad_sync=debug,ldap3_client=debug") {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("ERROR! Unable to start tracing {:?}", e);
+                return;
+            }
+        }
+    } else {
+        match EnvFilter::try_from_default_env() {
+            Ok(f) => f,
+            Err(_) => EnvFilter::new("kanidm_client=warn,kanidm_ad_sync=info,ldap3_client=warn"),
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(fmt_layer)
+        .init();
+
+    // Startup sanity checks.
+    #[cfg(target_family = "unix")]
+    if opt.skip_root_check {
+        warn!("Skipping root user check, if you're running this for testing, ensure you clean up temporary files.")
+    } else if get_current_uid() == 0
+        || get_effective_uid() == 0
+        || get_current_gid() == 0
+        || get_effective_gid() == 0
+    {
+        error!("Refusing to run - this process must not operate as root.");
+        return;
+    };
+
+    if !config_security_checks(&opt.client_config) || !config_security_checks(&opt.ad_sync_config) {
+        return;
+    }
+
+    let par_count = thread::available_parallelism()
+        .expect("Failed to determine available parallelism")
+        .get();
+
+    let rt = runtime::Builder::new_current_thread()
+        // We configure this as we use parallel workers at some points.
+        .max_blocking_threads(par_count)
+        .enable_all()
+        .build()
+        .expect("Failed to initialise tokio runtime!");
+
+    tracing::debug!("Using {} worker threads", par_count);
+
+    rt.block_on(async move { driver_main(opt).await });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ad_account_expire, ad_dirsync_changes, ad_filetime_to_rfc3339, ad_object_guid,
+        ad_to_scim_entry, AdSid, EntryConfig, ACCOUNT_DISABLED_EXPIRE,
+    };
+    use kanidm_proto::scim_v1::{
+        MultiValueAttr, ScimEntry, ScimExternalMember, ScimSyncGroup, ScimSyncPerson,
+        ScimSyncRetentionMode,
+    };
+    use ldap3_client::{LdapEntry, LdapSyncReplEntry, LdapSyncStateValue};
+    use std::collections::BTreeSet;
+    use uuid::{uuid, Uuid};
+
+    const TEST_USER_DN: &str = "CN=Test User,CN=Users,DC=example,DC=com";
+    const TEST_GROUP_DN: &str = "CN=Test Group,CN=Users,DC=example,DC=com";
+
+    fn ad_entry(dn: &str, attrs: &[(&str, &[&str])]) -> LdapEntry {
+        LdapEntry {
+            dn: dn.to_string(),
+            attrs: attrs
+                .iter()
+                .map(|(attr, values)| {
+                    (
+                        attr.to_string(),
+                        values.iter().map(|v| v.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn dirsync_entry(entry_uuid: Uuid, entry: LdapEntry) -> LdapSyncReplEntry {
+        LdapSyncReplEntry {
+            entry_uuid,
+            state: LdapSyncStateValue::Modify,
+            entry,
+        }
+    }
+
+    fn scim_json(entry: Option<ScimEntry>) -> Option<serde_json::Value> {
+        entry.map(|e| serde_json::to_value(e).expect("Failed to serialise scim entry"))
+    }
+
+    #[test]
+    fn test_ad_object_guid() {
+        assert_eq!(
+            ad_object_guid("MyIRAFVEd2aImaq7zN3u/w=="),
+            Some(uuid!("00112233-4455-6677-8899-aabbccddeeff"))
+        );
+        // Too short
+        assert_eq!(ad_object_guid("MyIRAFVEd2aImaq7zN3u"), None);
+    }
+
+    #[test]
+    fn test_ad_object_sid() {
+        let sid = AdSid::from_base64("AQUAAAAAAAUVAAAA3PTcO4M9K0aCi6YoUAQAAA==")
+            .expect("Failed to parse sid");
+
+        assert_eq!(
+            sid.to_string(),
+            "S-1-5-21-1004336348-1177238915-682003330-1104"
+        );
+        assert_eq!(sid.rid(), Some(1104));
+        assert_eq!(
+            sid.with_rid(513).to_string(),
+            "S-1-5-21-1004336348-1177238915-682003330-513"
+        );
+
+        // The sub authority count does not match the length.
+        assert!(AdSid::from_base64("AQYAAAAAAAUVAAAA3PTcO4M9K0aCi6YoUAQAAA==").is_none());
+    }
+
+    #[test]
+    fn test_ad_filetime() {
+        assert_eq!(
+            ad_filetime_to_rfc3339("133300512000000000"),
+            Ok(Some("2023-06-01T00:00:00Z".to_string()))
+        );
+        // Never expires
+        assert_eq!(ad_filetime_to_rfc3339("0"), Ok(None));
+        assert_eq!(ad_filetime_to_rfc3339("9223372036854775807"), Ok(None));
+
+        assert!(ad_filetime_to_rfc3339("never").is_err());
+    }
+
+    #[test]
+    fn test_ad_dirsync_changes() {
+        let changed_uuid = Uuid::new_v4();
+        let tombstone_uuid = Uuid::new_v4();
+        let deleted_uuid = Uuid::new_v4();
+
+        let entries = vec![
+            dirsync_entry(
+                changed_uuid,
+                ad_entry(TEST_USER_DN, &[("displayname", &["Test User"])]),
+            ),
+            dirsync_entry(
+                tombstone_uuid,
+                ad_entry(
+                    "CN=Old User\\0ADEL:1234,CN=Deleted Objects,DC=example,DC=com",
+                    &[("isdeleted", &["TRUE"])],
+                ),
+            ),
+        ];
+
+        let (retain, changed_uuids) = ad_dirsync_changes(entries, Some(vec![deleted_uuid]));
+        assert_eq!(
+            retain,
+            ScimSyncRetentionMode::Delete(vec![deleted_uuid, tombstone_uuid])
+        );
+        assert_eq!(changed_uuids, BTreeSet::from([changed_uuid]));
+
+        // Without any deletions the existing entries are retained.
+        let entries = vec![dirsync_entry(
+            changed_uuid,
+            ad_entry(TEST_USER_DN, &[("isdeleted", &["FALSE"])]),
+        )];
+
+        let (retain, changed_uuids) = ad_dirsync_changes(entries, None);
+        assert_eq!(retain, ScimSyncRetentionMode::Ignore);
+        assert_eq!(changed_uuids, BTreeSet::from([changed_uuid]));
+    }
+
+    #[test]
+    fn test_ad_account_expire() {
+        // A normal account that never expires.
+        let mut entry = ad_entry(
+            TEST_USER_DN,
+            &[("useraccountcontrol", &["512"]), ("accountexpires", &["0"])],
+        );
+        assert_eq!(ad_account_expire(&mut entry), Ok(None));
+
+        // Other flags, such as DONT_EXPIRE_PASSWORD, do not affect the expiry.
+        let mut entry = ad_entry(
+            TEST_USER_DN,
+            &[
+                ("useraccountcontrol", &["66048"]),
+                ("accountexpires", &["133300512000000000"]),
+            ],
+        );
+        assert_eq!(
+            ad_account_expire(&mut entry),
+            Ok(Some("2023-06-01T00:00:00Z".to_string()))
+        );
+
+        // A disabled account is expired, regardless of accountExpires.
+        let mut entry = ad_entry(
+            TEST_USER_DN,
+            &[
+                ("useraccountcontrol", &["514"]),
+                ("accountexpires", &["9223372036854775807"]),
+            ],
+        );
+        assert_eq!(
+            ad_account_expire(&mut entry),
+            Ok(Some(ACCOUNT_DISABLED_EXPIRE.to_string()))
+        );
+
+        // Without userAccountControl only accountExpires is used.
+        let mut entry = ad_entry(TEST_USER_DN, &[("accountexpires", &["133300512000000000"])]);
+        assert_eq!(
+            ad_account_expire(&mut entry),
+            Ok(Some("2023-06-01T00:00:00Z".to_string()))
+        );
+
+        let mut entry = ad_entry(TEST_USER_DN, &[("useraccountcontrol", &["disabled"])]);
+        assert!(ad_account_expire(&mut entry).is_err());
+    }
+
+    #[test]
+    fn test_ad_to_scim_person() {
+        let entry_uuid = Uuid::new_v4();
+        let person = || {
+            ad_entry(
+                TEST_USER_DN,
+                &[
+                    (
+                        "objectclass",
+                        &["top", "person", "organizationalPerson", "user"],
+                    ),
+                    ("samaccountname", &["testuser"]),
+                    ("displayname", &["Test User"]),
+                    ("mail", &["testuser@example.com"]),
+                    ("useraccountcontrol", &["514"]),
+                ],
+            )
+        };
+
+        let expect = ScimSyncPerson {
+            id: entry_uuid,
+            external_id: Some(TEST_USER_DN.to_string()),
+            user_name: "testuser".to_string(),
+            display_name: "Test User".to_string(),
+            gidnumber: None,
+            password_import: None,
+            totp_import: Vec::default(),
+            login_shell: None,
+            mail: vec![MultiValueAttr {
+                type_: None,
+                primary: None,
+                display: None,
+                ref_: None,
+                value: "testuser@example.com".to_string(),
+            }],
+            ssh_publickey: Vec::default(),
+            account_expire: Some(ACCOUNT_DISABLED_EXPIRE.to_string()),
+            account_valid_from: None,
+        };
+
+        assert_eq!(
+            scim_json(
+                ad_to_scim_entry(entry_uuid, person(), Vec::new(), &EntryConfig::default())
+                    .expect("Failed to map person")
+            ),
+            scim_json(Some(expect.into()))
+        );
+
+        // Excluded entries are not synchronised.
+        let e_config = EntryConfig {
+            exclude: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            scim_json(
+                ad_to_scim_entry(entry_uuid, person(), Vec::new(), &e_config)
+                    .expect("Failed to map person")
+            ),
+            None
+        );
+
+        // Computers are users, but are not synchronised.
+        let entry = ad_entry(
+            "CN=TESTHOST,CN=Computers,DC=example,DC=com",
+            &[
+                (
+                    "objectclass",
+                    &["top", "person", "organizationalPerson", "user", "computer"],
+                ),
+                ("samaccountname", &["TESTHOST$"]),
+            ],
+        );
+        assert_eq!(
+            scim_json(
+                ad_to_scim_entry(entry_uuid, entry, Vec::new(), &EntryConfig::default())
+                    .expect("Failed to map computer")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ad_to_scim_group() {
+        let entry_uuid = Uuid::new_v4();
+        let entry = ad_entry(
+            TEST_GROUP_DN,
+            &[
+                ("objectclass", &["top", "group"]),
+                ("samaccountname", &["testgroup"]),
+                ("description", &["Test Group"]),
+                ("gidnumber", &["12345"]),
+                ("member", &[TEST_USER_DN]),
+            ],
+        );
+
+        let primary_member = "CN=Other User,CN=Users,DC=example,DC=com".to_string();
+
+        let expect = ScimSyncGroup {
+            id: entry_uuid,
+            external_id: Some(TEST_GROUP_DN.to_string()),
+            name: "testgroup".to_string(),
+            description: Some("Test Group".to_string()),
+            gidnumber: Some(12345),
+            members: vec![
+                ScimExternalMember {
+                    external_id: TEST_USER_DN.to_string(),
+                },
+                ScimExternalMember {
+                    external_id: primary_member.clone(),
+                },
+            ],
+        };
+
+        assert_eq!(
+            scim_json(
+                ad_to_scim_entry(
+                    entry_uuid,
+                    entry,
+                    vec![primary_member],
+                    &EntryConfig::default()
+                )
+                .expect("Failed to map group")
+            ),
+            scim_json(Some(expect.into()))
+        );
+
+        let entry = ad_entry(
+            TEST_GROUP_DN,
+            &[
+                ("objectclass", &["top", "group"]),
+                ("samaccountname", &["testgroup"]),
+                ("gidnumber", &["-1"]),
+            ],
+        );
+        assert!(ad_to_scim_entry(entry_uuid, entry, Vec::new(), &EntryConfig::default()).is_err());
+    }
+}
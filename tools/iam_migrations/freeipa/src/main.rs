@@ -900,6 +900,8 @@ fn ipa_to_scim_entry(
                 login_shell,
                 mail,
                 ssh_publickey,
+                account_expire: None,
+                account_valid_from: None,
             }
             .into(),
         ))
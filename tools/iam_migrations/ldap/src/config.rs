@@ -100,6 +100,11 @@ pub struct Config {
     #[serde(default = "group_attr_member")]
     pub group_attr_member: String,
 
+    /// Kanidm attributes to write back to ldap when they are changed in Kanidm, keyed by the
+    /// Kanidm attribute name. The sync agreement must yield authority over these attributes.
+    #[serde(default)]
+    pub writeback: BTreeMap<String, WritebackConfig>,
+
     #[serde(flatten)]
     pub entry_map: BTreeMap<Uuid, EntryConfig>,
 }
@@ -114,3 +119,22 @@ pub struct EntryConfig {
     pub map_name: Option<String>,
     pub map_gidnumber: Option<u32>,
 }
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WritebackConflict {
+    /// The value from Kanidm always replaces the value in ldap.
+    #[default]
+    Kanidm,
+    /// If the entry was also changed in ldap since the last sync and the values differ, the
+    /// value in ldap is kept and the change in Kanidm is not written back.
+    Ldap,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WritebackConfig {
+    /// The ldap attribute that the value is written to.
+    pub ldap_attr: String,
+    #[serde(default)]
+    pub conflict: WritebackConflict,
+}
@@ -7,5 +7,7 @@ pub enum SyncError {
     LdapStateInvalid,
     SyncStatus,
     SyncUpdate,
+    SyncWriteback,
+    LdapWriteback,
     Preprocess,
 }
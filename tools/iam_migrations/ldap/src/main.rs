@@ -14,12 +14,13 @@
 mod config;
 mod error;
 
-use crate::config::{Config, EntryConfig};
+use crate::config::{Config, EntryConfig, WritebackConflict};
 use crate::error::SyncError;
 use chrono::Utc;
 use clap::Parser;
 use cron::Schedule;
 use kanidm_proto::constants::ATTR_OBJECTCLASS;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::metadata;
 use std::fs::File;
 use std::io::Read;
@@ -42,17 +43,24 @@ use tracing::{debug, error, info, warn};
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{fmt, EnvFilter};
 
-use kanidm_client::KanidmClientBuilder;
+use kanidm_client::{KanidmClient, KanidmClientBuilder};
 use kanidm_lib_file_permissions::readonly as file_permissions_readonly;
 use kanidm_proto::scim_v1::{
     MultiValueAttr, ScimEntry, ScimExternalMember, ScimSshPubKey, ScimSyncGroup, ScimSyncPerson,
-    ScimSyncRequest, ScimSyncRetentionMode, ScimSyncState,
+    ScimSyncRequest, ScimSyncRetentionMode, ScimSyncState, ScimSyncWritebackAck,
+    ScimSyncWritebackEntry,
 };
 
 #[cfg(target_family = "unix")]
 use kanidm_utils_users::{get_current_gid, get_current_uid, get_effective_gid, get_effective_uid};
 
-use ldap3_client::{proto, LdapClientBuilder, LdapSyncRepl, LdapSyncReplEntry, LdapSyncStateValue};
+use ldap3_client::{
+    proto, LdapClient, LdapClientBuilder, LdapSyncRepl, LdapSyncReplEntry, LdapSyncStateValue,
+};
+
+/// The attributes of entries that were changed in ldap during this sync, keyed by
+/// their lowercased dn.
+type LdapChangedEntries = BTreeMap<String, BTreeMap<String, BTreeSet<String>>>;
 
 include!("./opt.rs");
 
@@ -324,6 +332,8 @@ async fn run_sync(
         }
     }
 
+    let mut ldap_changed = LdapChangedEntries::new();
+
     let scim_sync_request = match sync_result {
         LdapSyncRepl::Success {
             cookie,
@@ -343,7 +353,8 @@ async fn run_sync(
                 ScimSyncState::Active { cookie }
             } else {
                 info!("no changes required");
-                return Ok(());
+                return run_writeback(&rsclient, &mut ldap_client, sync_config, &ldap_changed, opt)
+                    .await;
             };
 
             let retain = match (delete_uuids, present_uuids) {
@@ -378,6 +389,20 @@ async fn run_sync(
                 }
             };
 
+            // Retain what changed in ldap, so that writeback can detect conflicts.
+            ldap_changed = entries
+                .iter()
+                .map(|lentry| {
+                    let attrs = lentry
+                        .entry
+                        .attrs
+                        .iter()
+                        .map(|(attr, values)| (attr.clone(), values.iter().cloned().collect()))
+                        .collect();
+                    (lentry.entry.dn.to_lowercase(), attrs)
+                })
+                .collect();
+
             let entries = match process_ldap_sync_result(entries, sync_config).await {
                 Ok(ssr) => ssr,
                 Err(()) => {
@@ -414,6 +439,7 @@ async fn run_sync(
         Ok(())
     } else if opt.dry_run {
         info!("dry-run complete");
+        run_writeback(&rsclient, &mut ldap_client, sync_config, &ldap_changed, opt).await?;
         info!("Success!");
         Ok(())
     } else if let Err(e) = rsclient.scim_v1_sync_update(&scim_sync_request).await {
@@ -423,12 +449,131 @@ async fn run_sync(
         );
         Err(SyncError::SyncUpdate)
     } else {
+        run_writeback(&rsclient, &mut ldap_client, sync_config, &ldap_changed, opt).await?;
         info!("Success!");
         Ok(())
     }
     // done!
 }
 
+/// Write changes that were made in Kanidm back to ldap. Only attributes that are configured
+/// for writeback are written, and the sync agreement must have yielded authority over them in
+/// Kanidm, as these are the only attributes that can change without the sync tool.
+async fn run_writeback(
+    rsclient: &KanidmClient,
+    ldap_client: &mut LdapClient,
+    sync_config: &Config,
+    ldap_changed: &LdapChangedEntries,
+    opt: &Opt,
+) -> Result<(), SyncError> {
+    if sync_config.writeback.is_empty() {
+        return Ok(());
+    }
+
+    let writeback = rsclient.scim_v1_sync_writeback().await.map_err(|e| {
+        error!(?e, "Failed to access scim sync writeback");
+        SyncError::SyncWriteback
+    })?;
+
+    debug!(from_cookie = ?writeback.from_cookie, entries = %writeback.entries.len());
+
+    for wb_entry in writeback.entries.iter() {
+        let Some(dn) = wb_entry.external_id.as_ref() else {
+            warn!(id = %wb_entry.id, "Unable to write back to an entry with no external id");
+            continue;
+        };
+
+        let changes = writeback_changes(dn, wb_entry, sync_config, ldap_changed);
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        let attrs: Vec<String> = changes
+            .iter()
+            .map(|m| m.modification.atype.clone())
+            .collect();
+
+        if opt.dry_run {
+            info!(%dn, ?attrs, "dry-run - would write back to ldap");
+            continue;
+        }
+
+        ldap_client.modify(dn.clone(), changes).await.map_err(|e| {
+            error!(?e, %dn, "Failed to write back to ldap");
+            SyncError::LdapWriteback
+        })?;
+
+        info!(%dn, ?attrs, "wrote back to ldap");
+    }
+
+    if opt.dry_run {
+        return Ok(());
+    }
+
+    // Only once everything is written do we acknowledge, so that a failure is retried.
+    let ack = ScimSyncWritebackAck {
+        from_cookie: writeback.from_cookie,
+        to_cookie: writeback.to_cookie,
+    };
+
+    rsclient.scim_v1_sync_writeback_ack(&ack).await.map_err(|e| {
+        error!(
+            ?e,
+            "Failed to acknowledge scim sync writeback - see the kanidmd server log for more details."
+        );
+        SyncError::SyncWriteback
+    })
+}
+
+/// The modifications that write the changes of an entry in Kanidm back to ldap.
+fn writeback_changes(
+    dn: &str,
+    wb_entry: &ScimSyncWritebackEntry,
+    sync_config: &Config,
+    ldap_changed: &LdapChangedEntries,
+) -> Vec<proto::LdapModify> {
+    let changed_entry = ldap_changed.get(&dn.to_lowercase());
+
+    wb_entry
+        .attrs
+        .iter()
+        .filter_map(|(attr, wb_attr)| {
+            let wb_config = sync_config.writeback.get(attr)?;
+
+            let ldap_values =
+                changed_entry.and_then(|attrs| attrs.get(&wb_config.ldap_attr.to_lowercase()));
+
+            if let Some(ldap_values) = ldap_values {
+                let kanidm_values: BTreeSet<String> = wb_attr.values.iter().cloned().collect();
+                if ldap_values == &kanidm_values {
+                    debug!(%dn, %attr, "ldap is already up to date");
+                    return None;
+                }
+                if wb_config.conflict == WritebackConflict::Ldap {
+                    warn!(
+                        %dn, %attr, changed = %wb_attr.changed,
+                        "Attribute was changed in both kanidm and ldap, keeping the ldap value"
+                    );
+                    return None;
+                }
+            }
+
+            Some(proto::LdapModify {
+                operation: proto::LdapModifyType::Replace,
+                modification: proto::LdapPartialAttribute {
+                    atype: wb_config.ldap_attr.clone(),
+                    vals: wb_attr
+                        .values
+                        .iter()
+                        .map(|v| v.as_bytes().to_vec())
+                        .collect(),
+                },
+            })
+        })
+        .collect()
+}
+
 async fn process_ldap_sync_result(
     ldap_entries: Vec<LdapSyncReplEntry>,
     sync_config: &Config,
@@ -585,6 +730,8 @@ fn ldap_to_scim_entry(
                 login_shell,
                 mail,
                 ssh_publickey,
+                account_expire: None,
+                account_valid_from: None,
             }
             .into(),
         ))
@@ -781,3 +928,144 @@ fn main() {
 
     rt.block_on(async move { driver_main(opt).await });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{writeback_changes, Config, LdapChangedEntries};
+    use kanidm_proto::scim_v1::{ScimSyncWritebackAttr, ScimSyncWritebackEntry};
+    use std::collections::{BTreeMap, BTreeSet};
+    use uuid::Uuid;
+
+    const TEST_DN: &str = "uid=testuser,ou=people,dc=example,dc=com";
+
+    fn test_config() -> Config {
+        toml::from_str(
+            r#"
+            sync_token = "token"
+            ldap_uri = "ldaps://ldap.example.com"
+            ldap_ca = "/path/to/ca.pem"
+            ldap_sync_dn = "cn=Directory Manager"
+            ldap_sync_pw = "password"
+            ldap_sync_base_dn = "dc=example,dc=com"
+            ldap_filter = "(objectclass=person)"
+
+            [writeback.mail]
+            ldap_attr = "mail"
+
+            [writeback.ssh_publickey]
+            ldap_attr = "sshPublicKey"
+            conflict = "ldap"
+            "#,
+        )
+        .expect("Failed to parse config")
+    }
+
+    fn test_entry(attrs: &[(&str, &[&str])]) -> ScimSyncWritebackEntry {
+        ScimSyncWritebackEntry {
+            id: Uuid::new_v4(),
+            external_id: Some(TEST_DN.to_string()),
+            attrs: attrs
+                .iter()
+                .map(|(attr, values)| {
+                    (
+                        attr.to_string(),
+                        ScimSyncWritebackAttr {
+                            values: values.iter().map(|v| v.to_string()).collect(),
+                            changed: time::OffsetDateTime::UNIX_EPOCH,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn ldap_changed(attrs: &[(&str, &[&str])]) -> LdapChangedEntries {
+        let attrs = attrs
+            .iter()
+            .map(|(attr, values)| {
+                (
+                    attr.to_string(),
+                    values
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<BTreeSet<_>>(),
+                )
+            })
+            .collect();
+        BTreeMap::from([(TEST_DN.to_string(), attrs)])
+    }
+
+    fn modified_attrs(
+        dn: &str,
+        entry: &ScimSyncWritebackEntry,
+        changed: &LdapChangedEntries,
+    ) -> Vec<(String, Vec<Vec<u8>>)> {
+        writeback_changes(dn, entry, &test_config(), changed)
+            .into_iter()
+            .map(|m| (m.modification.atype, m.modification.vals))
+            .collect()
+    }
+
+    #[test]
+    fn test_writeback_changes() {
+        let entry = test_entry(&[
+            ("mail", &["test@example.com", "alt@example.com"]),
+            ("ssh_publickey", &["ssh-ed25519 AAAA test"]),
+            // Not configured for writeback.
+            ("legalname", &["Test User"]),
+        ]);
+
+        // Nothing changed in ldap, so every configured attribute is replaced.
+        assert_eq!(
+            modified_attrs(TEST_DN, &entry, &LdapChangedEntries::new()),
+            vec![
+                (
+                    "mail".to_string(),
+                    vec![b"test@example.com".to_vec(), b"alt@example.com".to_vec()]
+                ),
+                (
+                    "sshPublicKey".to_string(),
+                    vec![b"ssh-ed25519 AAAA test".to_vec()]
+                ),
+            ]
+        );
+
+        // Removed values are written as an empty replace.
+        let entry = test_entry(&[("mail", &[])]);
+        assert_eq!(
+            modified_attrs(TEST_DN, &entry, &LdapChangedEntries::new()),
+            vec![("mail".to_string(), Vec::new())]
+        );
+    }
+
+    #[test]
+    fn test_writeback_changes_conflict() {
+        let entry = test_entry(&[
+            ("mail", &["test@example.com"]),
+            ("ssh_publickey", &["ssh-ed25519 AAAA kanidm"]),
+        ]);
+
+        // Both were changed in ldap. Kanidm wins for mail, but ldap is kept for ssh keys.
+        let changed = ldap_changed(&[
+            ("mail", &["ldap@example.com"]),
+            ("sshpublickey", &["ssh-ed25519 AAAA ldap"]),
+        ]);
+        assert_eq!(
+            modified_attrs(TEST_DN, &entry, &changed),
+            vec![("mail".to_string(), vec![b"test@example.com".to_vec()])]
+        );
+
+        // The dn is matched regardless of case.
+        assert_eq!(
+            modified_attrs(&TEST_DN.to_uppercase(), &entry, &changed),
+            vec![("mail".to_string(), vec![b"test@example.com".to_vec()])]
+        );
+
+        // Ldap already has the values of kanidm, so there is nothing to write.
+        let changed = ldap_changed(&[
+            ("mail", &["test@example.com"]),
+            ("sshpublickey", &["ssh-ed25519 AAAA kanidm"]),
+        ]);
+        assert!(modified_attrs(TEST_DN, &entry, &changed).is_empty());
+    }
+}
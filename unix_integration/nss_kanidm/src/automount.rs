@@ -0,0 +1,157 @@
+//! The autofs sss lookup interface. autofs loads `libsss_autofs.so` from its sss modules
+//! directory for `automount: sss` in /etc/nsswitch.conf, so this library is also installed
+//! under that name. These functions mirror the ones provided by sssd, and return errno values.
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use kanidm_unix_common::client_sync::DaemonClientBlocking;
+use kanidm_unix_common::constants::DEFAULT_CONFIG_PATH;
+use kanidm_unix_common::unix_config::KanidmUnixdConfig;
+use kanidm_unix_common::unix_proto::{ClientRequest, ClientResponse, NssAutomountEntry};
+use libc::{c_char, c_int, c_void, EHOSTDOWN, EINVAL, EIO, ENOENT, ENOMEM};
+
+/// The state of a map that is being read, between setautomntent and endautomntent.
+struct AutomountContext {
+    entries: Vec<NssAutomountEntry>,
+    next: usize,
+}
+
+fn call_daemon(req: &ClientRequest) -> Result<ClientResponse, c_int> {
+    let cfg = KanidmUnixdConfig::new()
+        .read_options_from_optional_config(DEFAULT_CONFIG_PATH)
+        .map_err(|_| EHOSTDOWN)?;
+
+    let mut daemon_client =
+        DaemonClientBlocking::new(cfg.sock_path.as_str()).map_err(|_| EHOSTDOWN)?;
+
+    daemon_client
+        .call_and_wait(req, cfg.unix_sock_timeout)
+        .map_err(|_| EHOSTDOWN)
+}
+
+/// Copy a string into memory from malloc, as the caller releases it with free.
+unsafe fn copy_to_caller(value: &str, out: *mut *mut c_char) -> c_int {
+    let Ok(value) = CString::new(value) else {
+        return EINVAL;
+    };
+
+    let buf = libc::strdup(value.as_ptr());
+    if buf.is_null() {
+        return ENOMEM;
+    }
+
+    *out = buf;
+    0
+}
+
+/// Load the map `mapname` and store it in `context` for the other functions.
+///
+/// # Safety
+///
+/// `mapname` must be a valid C string, and `context` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn _sss_setautomntent(
+    mapname: *const c_char,
+    context: *mut *mut c_void,
+) -> c_int {
+    if mapname.is_null() || context.is_null() {
+        return EINVAL;
+    }
+
+    let Ok(mapname) = CStr::from_ptr(mapname).to_str() else {
+        return ENOENT;
+    };
+
+    let entries = match call_daemon(&ClientRequest::NssAutomountMapByName(mapname.to_string())) {
+        Ok(ClientResponse::NssAutomountMap(Some(entries))) => entries,
+        Ok(ClientResponse::NssAutomountMap(None)) => return ENOENT,
+        Ok(_) => return EIO,
+        Err(status) => return status,
+    };
+
+    let ctx = Box::new(AutomountContext { entries, next: 0 });
+    *context = Box::into_raw(ctx).cast::<c_void>();
+    0
+}
+
+/// Return the next key and value of the map. The caller frees `key` and `value`.
+///
+/// # Safety
+///
+/// `context` must have been set by `_sss_setautomntent`, and `key` and `value` must be valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn _sss_getautomntent_r(
+    key: *mut *mut c_char,
+    value: *mut *mut c_char,
+    context: *mut c_void,
+) -> c_int {
+    if key.is_null() || value.is_null() || context.is_null() {
+        return EINVAL;
+    }
+
+    let ctx = &mut *context.cast::<AutomountContext>();
+    let Some(entry) = ctx.entries.get(ctx.next) else {
+        return ENOENT;
+    };
+    ctx.next += 1;
+
+    let status = copy_to_caller(entry.key.as_str(), key);
+    if status != 0 {
+        return status;
+    }
+
+    let status = copy_to_caller(entry.information.as_str(), value);
+    if status != 0 {
+        libc::free((*key).cast::<c_void>());
+        *key = ptr::null_mut();
+    }
+    status
+}
+
+/// Return the value of `key` within the map. The caller frees `value`.
+///
+/// # Safety
+///
+/// `key` must be a valid C string, `context` must have been set by `_sss_setautomntent`,
+/// and `value` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn _sss_getautomntbyname_r(
+    key: *const c_char,
+    value: *mut *mut c_char,
+    context: *mut c_void,
+) -> c_int {
+    if key.is_null() || value.is_null() || context.is_null() {
+        return EINVAL;
+    }
+
+    let Ok(key) = CStr::from_ptr(key).to_str() else {
+        return ENOENT;
+    };
+
+    let ctx = &*context.cast::<AutomountContext>();
+    match ctx.entries.iter().find(|e| e.key == key) {
+        Some(entry) => copy_to_caller(entry.information.as_str(), value),
+        None => ENOENT,
+    }
+}
+
+/// Release the map stored in `context` by `_sss_setautomntent`.
+///
+/// # Safety
+///
+/// `context` must be valid for writes, and contain a value set by `_sss_setautomntent`
+/// or null.
+#[no_mangle]
+pub unsafe extern "C" fn _sss_endautomntent(context: *mut *mut c_void) -> c_int {
+    if context.is_null() {
+        return EINVAL;
+    }
+
+    if !(*context).is_null() {
+        drop(Box::from_raw((*context).cast::<AutomountContext>()));
+        *context = ptr::null_mut();
+    }
+    0
+}
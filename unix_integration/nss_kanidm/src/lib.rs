@@ -22,3 +22,17 @@ mod implementation;
 
 #[cfg(target_family = "unix")]
 pub use implementation::*;
+
+// The subid functions are looked up by name by libsubid, so they must be exported.
+#[cfg(target_family = "unix")]
+mod subid;
+
+#[cfg(target_family = "unix")]
+pub use subid::*;
+
+// The autofs functions are looked up by name by the autofs sss module.
+#[cfg(target_family = "unix")]
+mod automount;
+
+#[cfg(target_family = "unix")]
+pub use automount::*;
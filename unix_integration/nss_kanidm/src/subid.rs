@@ -0,0 +1,214 @@
+//! The shadow-utils subid interface. For the `subid: kanidm` entry of /etc/nsswitch.conf
+//! libsubid loads `libsubid_kanidm.so`, so this library is also installed under that name.
+//! The types and functions here are defined by subid.h from shadow-utils.
+
+use std::ffi::CStr;
+use std::mem::size_of;
+use std::ptr;
+
+use kanidm_unix_common::client_sync::DaemonClientBlocking;
+use kanidm_unix_common::constants::DEFAULT_CONFIG_PATH;
+use kanidm_unix_common::unix_config::KanidmUnixdConfig;
+use kanidm_unix_common::unix_proto::{ClientRequest, ClientResponse, NssSubidRange};
+use libc::{c_char, c_int, c_ulong, c_void, uid_t};
+
+// enum subid_type
+const ID_TYPE_UID: c_int = 1;
+const ID_TYPE_GID: c_int = 2;
+
+// enum subid_status
+const SUBID_STATUS_SUCCESS: c_int = 0;
+const SUBID_STATUS_UNKNOWN_USER: c_int = 1;
+const SUBID_STATUS_ERROR_CONN: c_int = 2;
+const SUBID_STATUS_ERROR: c_int = 3;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SubidRange {
+    pub start: c_ulong,
+    pub count: c_ulong,
+}
+
+fn call_daemon(req: &ClientRequest) -> Result<ClientResponse, c_int> {
+    let cfg = KanidmUnixdConfig::new()
+        .read_options_from_optional_config(DEFAULT_CONFIG_PATH)
+        .map_err(|_| SUBID_STATUS_ERROR_CONN)?;
+
+    let mut daemon_client =
+        DaemonClientBlocking::new(cfg.sock_path.as_str()).map_err(|_| SUBID_STATUS_ERROR_CONN)?;
+
+    daemon_client
+        .call_and_wait(req, cfg.unix_sock_timeout)
+        .map_err(|_| SUBID_STATUS_ERROR_CONN)
+}
+
+/// Accounts have a single range that is used for both subordinate uids and gids, so
+/// the id type only needs to be valid.
+unsafe fn owner_ranges(owner: *const c_char, id_type: c_int) -> Result<Vec<NssSubidRange>, c_int> {
+    if owner.is_null() || (id_type != ID_TYPE_UID && id_type != ID_TYPE_GID) {
+        return Err(SUBID_STATUS_ERROR);
+    }
+
+    let owner = CStr::from_ptr(owner)
+        .to_str()
+        .map_err(|_| SUBID_STATUS_UNKNOWN_USER)?;
+
+    match call_daemon(&ClientRequest::NssSubidRangesByName(owner.to_string()))? {
+        ClientResponse::NssSubidRanges(Some(ranges)) => Ok(ranges),
+        ClientResponse::NssSubidRanges(None) => Err(SUBID_STATUS_UNKNOWN_USER),
+        _ => Err(SUBID_STATUS_ERROR),
+    }
+}
+
+/// Copy values into memory from malloc, as the caller releases it with free.
+unsafe fn copy_to_caller<T: Copy>(values: &[T], out: *mut *mut T, count: *mut c_int) -> c_int {
+    let Ok(len) = c_int::try_from(values.len()) else {
+        return SUBID_STATUS_ERROR;
+    };
+
+    if values.is_empty() {
+        *out = ptr::null_mut();
+        *count = 0;
+        return SUBID_STATUS_SUCCESS;
+    }
+
+    let Some(size) = size_of::<T>().checked_mul(values.len()) else {
+        return SUBID_STATUS_ERROR;
+    };
+
+    let buf = libc::malloc(size).cast::<T>();
+    if buf.is_null() {
+        return SUBID_STATUS_ERROR;
+    }
+
+    ptr::copy_nonoverlapping(values.as_ptr(), buf, values.len());
+    *out = buf;
+    *count = len;
+    SUBID_STATUS_SUCCESS
+}
+
+/// Set `result` to whether `owner` has any subordinate id range.
+///
+/// # Safety
+///
+/// `owner` must be a valid C string, and `result` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn shadow_subid_has_any_range(
+    owner: *const c_char,
+    id_type: c_int,
+    result: *mut bool,
+) -> c_int {
+    if result.is_null() {
+        return SUBID_STATUS_ERROR;
+    }
+
+    match owner_ranges(owner, id_type) {
+        Ok(ranges) => {
+            *result = !ranges.is_empty();
+            SUBID_STATUS_SUCCESS
+        }
+        Err(status) => status,
+    }
+}
+
+/// Set `result` to whether the ids from `start` to `start + count` are wholly within one of
+/// the subordinate id ranges of `owner`.
+///
+/// # Safety
+///
+/// `owner` must be a valid C string, and `result` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn shadow_subid_has_range(
+    owner: *const c_char,
+    start: c_ulong,
+    count: c_ulong,
+    id_type: c_int,
+    result: *mut bool,
+) -> c_int {
+    if result.is_null() {
+        return SUBID_STATUS_ERROR;
+    }
+
+    let end = start.saturating_add(count);
+
+    match owner_ranges(owner, id_type) {
+        Ok(ranges) => {
+            *result = ranges.iter().any(|r| {
+                let r_start = c_ulong::from(r.start);
+                start >= r_start && end <= r_start.saturating_add(c_ulong::from(r.count))
+            });
+            SUBID_STATUS_SUCCESS
+        }
+        Err(status) => status,
+    }
+}
+
+/// List the subordinate id ranges of `owner`. The caller frees `ranges`.
+///
+/// # Safety
+///
+/// `owner` must be a valid C string, and `ranges` and `count` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn shadow_subid_list_owner_ranges(
+    owner: *const c_char,
+    id_type: c_int,
+    ranges: *mut *mut SubidRange,
+    count: *mut c_int,
+) -> c_int {
+    if ranges.is_null() || count.is_null() {
+        return SUBID_STATUS_ERROR;
+    }
+
+    match owner_ranges(owner, id_type) {
+        Ok(owned) => {
+            let owned: Vec<_> = owned
+                .iter()
+                .map(|r| SubidRange {
+                    start: c_ulong::from(r.start),
+                    count: c_ulong::from(r.count),
+                })
+                .collect();
+            copy_to_caller(&owned, ranges, count)
+        }
+        Err(status) => status,
+    }
+}
+
+/// List the uids of the accounts whose subordinate id range contains `id`. The caller
+/// frees `uids`.
+///
+/// # Safety
+///
+/// `uids` and `count` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn shadow_subid_find_subid_owners(
+    id: c_ulong,
+    id_type: c_int,
+    uids: *mut *mut uid_t,
+    count: *mut c_int,
+) -> c_int {
+    if uids.is_null() || count.is_null() || (id_type != ID_TYPE_UID && id_type != ID_TYPE_GID) {
+        return SUBID_STATUS_ERROR;
+    }
+
+    // Ranges are never allocated beyond the 32 bit id space.
+    let Ok(id) = u32::try_from(id) else {
+        return copy_to_caller::<uid_t>(&[], uids, count);
+    };
+
+    match call_daemon(&ClientRequest::NssSubidOwners(id)) {
+        Ok(ClientResponse::NssSubidOwners(owners)) => copy_to_caller(&owners, uids, count),
+        Ok(_) => SUBID_STATUS_ERROR,
+        Err(status) => status,
+    }
+}
+
+/// Free memory that was returned to the caller by this library.
+///
+/// # Safety
+///
+/// `ptr` must have been returned by this library, and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn shadow_subid_free(ptr: *mut c_void) {
+    libc::free(ptr);
+}
@@ -39,7 +39,7 @@ use kanidm_unix_common::client_sync::DaemonClientBlocking;
 use kanidm_unix_common::constants::DEFAULT_CONFIG_PATH;
 use kanidm_unix_common::unix_config::KanidmUnixdConfig;
 use kanidm_unix_common::unix_proto::{
-    ClientRequest, ClientResponse, PamAuthRequest, PamAuthResponse,
+    ClientRequest, ClientResponse, PamAuthRequest, PamAuthResponse, PamChangeAuthTokenResponse,
 };
 
 use crate::pam::constants::*;
@@ -74,6 +74,20 @@ fn install_subscriber(debug: bool) {
         .try_init();
 }
 
+fn prompt_password(conv: &PamConv, msg: &str) -> Result<String, PamResultCode> {
+    match conv.send(PAM_PROMPT_ECHO_OFF, msg) {
+        Ok(Some(cred)) => Ok(cred),
+        Ok(None) => {
+            debug!("no password");
+            Err(PamResultCode::PAM_AUTHTOK_ERR)
+        }
+        Err(err) => {
+            debug!("unable to get password");
+            Err(err)
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Options {
     debug: bool,
@@ -117,8 +131,10 @@ impl PamHooks for PamKanidm {
 
         let tty = pamh.get_tty();
         let rhost = pamh.get_rhost();
+        // Host access rules may only allow some services.
+        let service = pamh.get_service().ok().flatten();
 
-        debug!(?args, ?opts, ?tty, ?rhost, "acct_mgmt");
+        debug!(?args, ?opts, ?tty, ?rhost, ?service, "acct_mgmt");
 
         let account_id = match pamh.get_user(None) {
             Ok(aid) => aid,
@@ -132,7 +148,10 @@ impl PamHooks for PamKanidm {
             Ok(cfg) => cfg,
             Err(e) => return e,
         };
-        let req = ClientRequest::PamAccountAllowed(account_id);
+        let req = ClientRequest::PamAccountAllowed {
+            account_id,
+            service,
+        };
         // PamResultCode::PAM_IGNORE
 
         let mut daemon_client = match DaemonClientBlocking::new(cfg.sock_path.as_str()) {
@@ -323,7 +342,7 @@ impl PamHooks for PamKanidm {
         } // while true, continue calling PamAuthenticateStep until we get a decision.
     }
 
-    fn sm_chauthtok(_pamh: &PamHandle, args: Vec<&CStr>, _flags: PamFlag) -> PamResultCode {
+    fn sm_chauthtok(pamh: &PamHandle, args: Vec<&CStr>, flags: PamFlag) -> PamResultCode {
         let opts = match Options::try_from(&args) {
             Ok(o) => o,
             Err(_) => return PamResultCode::PAM_SERVICE_ERR,
@@ -331,9 +350,149 @@ impl PamHooks for PamKanidm {
 
         install_subscriber(opts.debug);
 
-        debug!(?args, ?opts, "sm_chauthtok");
+        debug!(?args, ?opts, ?flags, "sm_chauthtok");
+
+        let account_id = match pamh.get_user(None) {
+            Ok(aid) => aid,
+            Err(err) => {
+                error!(?err, "get_user");
+                return err;
+            }
+        };
+
+        let cfg = match get_cfg() {
+            Ok(cfg) => cfg,
+            Err(e) => return e,
+        };
+
+        let mut daemon_client = match DaemonClientBlocking::new(cfg.sock_path.as_str()) {
+            Ok(dc) => dc,
+            Err(e) => {
+                error!(err = ?e, "Error DaemonClientBlocking::new()");
+                return PamResultCode::PAM_SERVICE_ERR;
+            }
+        };
+
+        if flags & PAM_PRELIM_CHECK != 0 {
+            // Only check that this is an account we are responsible for. The tokens are
+            // requested in the update phase.
+            let req = ClientRequest::NssAccountByName(account_id);
+            return match daemon_client.call_and_wait(&req, cfg.unix_sock_timeout) {
+                Ok(ClientResponse::NssAccount(Some(_))) => PamResultCode::PAM_SUCCESS,
+                Ok(ClientResponse::NssAccount(None)) => {
+                    if opts.ignore_unknown_user {
+                        PamResultCode::PAM_IGNORE
+                    } else {
+                        PamResultCode::PAM_USER_UNKNOWN
+                    }
+                }
+                other => {
+                    error!(err = ?other, "PAM_TRY_AGAIN, unexpected resolver response");
+                    PamResultCode::PAM_TRY_AGAIN
+                }
+            };
+        }
+
+        if flags & PAM_UPDATE_AUTHTOK == 0 {
+            error!("sm_chauthtok called without a prelim check or update flag");
+            return PamResultCode::PAM_SERVICE_ERR;
+        }
 
-        PamResultCode::PAM_IGNORE
+        let conv = match pamh.get_item::<PamConv>() {
+            Ok(conv) => conv,
+            Err(err) => {
+                error!(?err, "pam_conv");
+                return err;
+            }
+        };
+
+        // As in sm_authenticate, use the tokens of an earlier module in the stack if they
+        // are present, and only prompt if we are allowed to.
+        let current_password = match pamh.get_oldauthtok() {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                if opts.use_first_pass {
+                    debug!("Don't have an oldauthtok, returning PAM_AUTHTOK_RECOVERY_ERR");
+                    return PamResultCode::PAM_AUTHTOK_RECOVERY_ERR;
+                }
+                match prompt_password(conv, "Current password: ") {
+                    Ok(v) => v,
+                    Err(err) => return err,
+                }
+            }
+            Err(err) => {
+                error!(?err, "get_oldauthtok");
+                return err;
+            }
+        };
+
+        let new_password = match pamh.get_authtok() {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                if opts.use_first_pass {
+                    debug!("Don't have an authtok, returning PAM_AUTHTOK_ERR");
+                    return PamResultCode::PAM_AUTHTOK_ERR;
+                }
+                let new_password = match prompt_password(conv, "New password: ") {
+                    Ok(v) => v,
+                    Err(err) => return err,
+                };
+                let confirm_password = match prompt_password(conv, "Retype new password: ") {
+                    Ok(v) => v,
+                    Err(err) => return err,
+                };
+                if new_password != confirm_password {
+                    let _ = conv.send(PAM_ERROR_MSG, "Passwords do not match.");
+                    return PamResultCode::PAM_AUTHTOK_ERR;
+                }
+                new_password
+            }
+            Err(err) => {
+                error!(?err, "get_authtok");
+                return err;
+            }
+        };
+
+        let req = ClientRequest::PamChangeAuthToken {
+            account_id,
+            current_password,
+            new_password,
+        };
+
+        match daemon_client.call_and_wait(&req, cfg.unix_sock_timeout) {
+            Ok(ClientResponse::PamChangeAuthTokenResponse(r)) => match r {
+                PamChangeAuthTokenResponse::Success => PamResultCode::PAM_SUCCESS,
+                PamChangeAuthTokenResponse::Denied => {
+                    let _ = conv.send(PAM_ERROR_MSG, "Current password was not accepted.");
+                    PamResultCode::PAM_PERM_DENIED
+                }
+                PamChangeAuthTokenResponse::Unknown => {
+                    if opts.ignore_unknown_user {
+                        PamResultCode::PAM_IGNORE
+                    } else {
+                        PamResultCode::PAM_USER_UNKNOWN
+                    }
+                }
+                PamChangeAuthTokenResponse::Offline => {
+                    let _ = conv.send(
+                        PAM_ERROR_MSG,
+                        "Unable to contact the server, your password can not be changed now.",
+                    );
+                    PamResultCode::PAM_TRY_AGAIN
+                }
+                PamChangeAuthTokenResponse::PasswordQuality { feedback } => {
+                    let _ = conv.send(PAM_ERROR_MSG, "The new password was rejected:");
+                    for msg in feedback.iter() {
+                        let _ = conv.send(PAM_ERROR_MSG, msg);
+                    }
+                    PamResultCode::PAM_AUTHTOK_ERR
+                }
+            },
+            other => {
+                error!(err = ?other, "PAM_AUTHTOK_ERR, unexpected resolver response");
+                PamResultCode::PAM_AUTHTOK_ERR
+            }
+        }
     }
 
     fn sm_close_session(_pamh: &PamHandle, args: Vec<&CStr>, _flags: PamFlag) -> PamResultCode {
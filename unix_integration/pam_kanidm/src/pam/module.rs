@@ -5,7 +5,10 @@ use std::{mem, ptr};
 
 use libc::c_char;
 
-use crate::pam::constants::{PamFlag, PamItemType, PamResultCode, PAM_AUTHTOK, PAM_RHOST, PAM_TTY};
+use crate::pam::constants::{
+    PamFlag, PamItemType, PamResultCode, PAM_AUTHTOK, PAM_OLDAUTHTOK, PAM_RHOST, PAM_SERVICE,
+    PAM_TTY,
+};
 
 /// Opaque type, used as a pointer when making pam API calls.
 ///
@@ -213,6 +216,25 @@ impl PamHandle {
         }
     }
 
+    pub fn get_oldauthtok(&self) -> PamResult<Option<String>> {
+        let mut ptr: *const PamItemT = ptr::null();
+        let (res, item) = unsafe {
+            let r = pam_get_item(self, PAM_OLDAUTHTOK, &mut ptr);
+            let t = if PamResultCode::PAM_SUCCESS == r && !ptr.is_null() {
+                let typed_ptr: *const c_char = ptr as *const c_char;
+                Some(CStr::from_ptr(typed_ptr).to_string_lossy().into_owned())
+            } else {
+                None
+            };
+            (r, t)
+        };
+        if PamResultCode::PAM_SUCCESS == res {
+            Ok(item)
+        } else {
+            Err(res)
+        }
+    }
+
     pub fn get_tty(&self) -> PamResult<Option<String>> {
         let mut ptr: *const PamItemT = ptr::null();
         let (res, item) = unsafe {
@@ -232,6 +254,25 @@ impl PamHandle {
         }
     }
 
+    pub fn get_service(&self) -> PamResult<Option<String>> {
+        let mut ptr: *const PamItemT = ptr::null();
+        let (res, item) = unsafe {
+            let r = pam_get_item(self, PAM_SERVICE, &mut ptr);
+            let t = if PamResultCode::PAM_SUCCESS == r && !ptr.is_null() {
+                let typed_ptr: *const c_char = ptr as *const c_char;
+                Some(CStr::from_ptr(typed_ptr).to_string_lossy().into_owned())
+            } else {
+                None
+            };
+            (r, t)
+        };
+        if PamResultCode::PAM_SUCCESS == res {
+            Ok(item)
+        } else {
+            Err(res)
+        }
+    }
+
     pub fn get_rhost(&self) -> PamResult<Option<String>> {
         let mut ptr: *const PamItemT = ptr::null();
         let (res, item) = unsafe {
@@ -279,6 +279,39 @@ async fn handle_client(
                         ClientResponse::NssGroup(None)
                     })
             }
+            ClientRequest::NssSubidRangesByName(account_id) => {
+                debug!("nsssubidrangesbyname req");
+                cachelayer
+                    .get_nsssubid_ranges(account_id.as_str())
+                    .await
+                    .map(ClientResponse::NssSubidRanges)
+                    .unwrap_or_else(|_| {
+                        error!("unable to load subid ranges, returning error.");
+                        ClientResponse::Error
+                    })
+            }
+            ClientRequest::NssSubidOwners(id) => {
+                debug!("nsssubidowners req");
+                cachelayer
+                    .get_nsssubid_owners(id)
+                    .await
+                    .map(ClientResponse::NssSubidOwners)
+                    .unwrap_or_else(|_| {
+                        error!("unable to load subid owners, returning error.");
+                        ClientResponse::Error
+                    })
+            }
+            ClientRequest::NssAutomountMapByName(map_name) => {
+                debug!("nssautomountmapbyname req");
+                cachelayer
+                    .get_nssautomount_map(map_name.as_str())
+                    .await
+                    .map(ClientResponse::NssAutomountMap)
+                    .unwrap_or_else(|_| {
+                        error!("unable to load automount map, returning error.");
+                        ClientResponse::Error
+                    })
+            }
             ClientRequest::PamAuthenticateInit(account_id) => {
                 debug!("pam authenticate init");
 
@@ -318,10 +351,13 @@ async fn handle_client(
                     }
                 }
             }
-            ClientRequest::PamAccountAllowed(account_id) => {
+            ClientRequest::PamAccountAllowed {
+                account_id,
+                service,
+            } => {
                 debug!("pam account allowed");
                 cachelayer
-                    .pam_account_allowed(account_id.as_str())
+                    .pam_account_allowed(account_id.as_str(), service.as_deref())
                     .await
                     .map(ClientResponse::PamStatus)
                     .unwrap_or(ClientResponse::Error)
@@ -369,6 +405,44 @@ async fn handle_client(
                     _ => ClientResponse::Error,
                 }
             }
+            ClientRequest::PamChangeAuthToken {
+                account_id,
+                current_password,
+                new_password,
+            } => {
+                debug!("pam change auth token");
+                cachelayer
+                    .pam_account_change_authtok(
+                        account_id.as_str(),
+                        current_password.as_str(),
+                        new_password.as_str(),
+                    )
+                    .await
+                    .map(|pam_change_response| pam_change_response.into())
+                    .unwrap_or(ClientResponse::Error)
+            }
+            ClientRequest::SudoCheck {
+                account_id,
+                runas,
+                command,
+            } => {
+                debug!("sudo check");
+                cachelayer
+                    .sudo_check(account_id.as_str(), runas.as_str(), command.as_slice())
+                    .await
+                    .map(|sudo_check_response| sudo_check_response.into())
+                    .unwrap_or(ClientResponse::Error)
+            }
+            ClientRequest::SudoList { account_id } => {
+                debug!("sudo list");
+                cachelayer
+                    .sudo_list(account_id.as_str())
+                    .await
+                    .map(|privileges| {
+                        ClientResponse::SudoPrivileges(privileges.unwrap_or_default())
+                    })
+                    .unwrap_or(ClientResponse::Error)
+            }
             ClientRequest::InvalidateCache => {
                 debug!("invalidate cache");
                 cachelayer
@@ -700,7 +774,29 @@ async fn main() -> ExitCode {
                 }
             };
 
-            let idprovider = KanidmProvider::new(rsclient);
+            // If this host has been issued an api token, authenticate as the host so that
+            // the host access and sudo rules that apply to it can be retrieved.
+            let host_token = match cfg.host_token_path.as_ref() {
+                Some(token_path) => {
+                    if let Ok(t_meta) = metadata(token_path) {
+                        if t_meta.mode() & 0o007 != 0 {
+                            warn!("WARNING: host token {} has 'everyone' permission bits in the mode. This could be a security risk ...", token_path);
+                        }
+                    }
+
+                    match tokio::fs::read_to_string(token_path).await {
+                        Ok(token) => Some(token.trim().to_string()),
+                        Err(e) => {
+                            error!("Unable to read host token from {} - {:?}", token_path, e);
+                            return ExitCode::FAILURE
+                        }
+                    }
+                }
+                None => None,
+            };
+            let host_identity = host_token.is_some();
+
+            let idprovider = KanidmProvider::new(rsclient, host_token);
 
             let db = match Db::new(cfg.db_path.as_str(), &cfg.tpm_policy) {
                 Ok(db) => db,
@@ -722,6 +818,7 @@ async fn main() -> ExitCode {
                 cfg.uid_attr_map,
                 cfg.gid_attr_map,
                 cfg.allow_local_account_override.clone(),
+                host_identity,
             )
             .await
             {
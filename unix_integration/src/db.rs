@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::time::Duration;
 
-use crate::idprovider::interface::{GroupToken, Id, UserToken};
+use crate::idprovider::interface::{AutomountMap, GroupToken, HbacRule, Id, SudoRule, UserToken};
 use crate::unix_config::TpmPolicy;
 use async_trait::async_trait;
 use kanidm_lib_crypto::CryptoPolicy;
@@ -63,6 +63,18 @@ pub trait CacheTxn {
     fn update_group(&self, grp: &GroupToken, expire: u64) -> Result<(), CacheError>;
 
     fn delete_group(&self, g_uuid: Uuid) -> Result<(), CacheError>;
+
+    fn get_sudo_rules(&self) -> Result<Option<(Vec<SudoRule>, u64)>, CacheError>;
+
+    fn update_sudo_rules(&self, rules: &[SudoRule], expire: u64) -> Result<(), CacheError>;
+
+    fn get_hbac_rules(&self) -> Result<Option<(Vec<HbacRule>, u64)>, CacheError>;
+
+    fn update_hbac_rules(&self, rules: &[HbacRule], expire: u64) -> Result<(), CacheError>;
+
+    fn get_automount_maps(&self) -> Result<Option<(Vec<AutomountMap>, u64)>, CacheError>;
+
+    fn update_automount_maps(&self, maps: &[AutomountMap], expire: u64) -> Result<(), CacheError>;
 }
 
 pub struct Db {
@@ -311,6 +323,47 @@ impl<'a> CacheTxn for DbTxn<'a> {
             )
             .map_err(|e| self.sqlite_error("memberof_t create error", &e))?;
 
+        // Sudo rules are always replaced as a complete set, so they are stored as a single
+        // row. This also lets us remember the expiry when the idp has no rules at all.
+        self.conn
+            .execute(
+                "CREATE TABLE IF NOT EXISTS sudo_rule_t (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                rules BLOB NOT NULL,
+                expiry NUMERIC NOT NULL
+            )
+            ",
+                [],
+            )
+            .map_err(|e| self.sqlite_error("sudo_rule_t create error", &e))?;
+
+        // As with sudo rules, the host access rules are replaced as a complete set.
+        self.conn
+            .execute(
+                "CREATE TABLE IF NOT EXISTS hbac_rule_t (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                rules BLOB NOT NULL,
+                expiry NUMERIC NOT NULL
+            )
+            ",
+                [],
+            )
+            .map_err(|e| self.sqlite_error("hbac_rule_t create error", &e))?;
+
+        // Automount maps are small, and autofs reads whole maps, so they are also stored as
+        // a complete set.
+        self.conn
+            .execute(
+                "CREATE TABLE IF NOT EXISTS automount_map_t (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                maps BLOB NOT NULL,
+                expiry NUMERIC NOT NULL
+            )
+            ",
+                [],
+            )
+            .map_err(|e| self.sqlite_error("automount_map_t create error", &e))?;
+
         Ok(())
     }
 
@@ -337,6 +390,18 @@ impl<'a> CacheTxn for DbTxn<'a> {
             .execute("UPDATE account_t SET expiry = 0", [])
             .map_err(|e| self.sqlite_error("update account_t", &e))?;
 
+        self.conn
+            .execute("UPDATE sudo_rule_t SET expiry = 0", [])
+            .map_err(|e| self.sqlite_error("update sudo_rule_t", &e))?;
+
+        self.conn
+            .execute("UPDATE hbac_rule_t SET expiry = 0", [])
+            .map_err(|e| self.sqlite_error("update hbac_rule_t", &e))?;
+
+        self.conn
+            .execute("UPDATE automount_map_t SET expiry = 0", [])
+            .map_err(|e| self.sqlite_error("update automount_map_t", &e))?;
+
         Ok(())
     }
 
@@ -353,6 +418,18 @@ impl<'a> CacheTxn for DbTxn<'a> {
             .execute("DELETE FROM account_t", [])
             .map_err(|e| self.sqlite_error("delete group_t", &e))?;
 
+        self.conn
+            .execute("DELETE FROM sudo_rule_t", [])
+            .map_err(|e| self.sqlite_error("delete sudo_rule_t", &e))?;
+
+        self.conn
+            .execute("DELETE FROM hbac_rule_t", [])
+            .map_err(|e| self.sqlite_error("delete hbac_rule_t", &e))?;
+
+        self.conn
+            .execute("DELETE FROM automount_map_t", [])
+            .map_err(|e| self.sqlite_error("delete automount_map_t", &e))?;
+
         Ok(())
     }
 
@@ -771,6 +848,171 @@ impl<'a> CacheTxn for DbTxn<'a> {
             .map(|_| ())
             .map_err(|e| self.sqlite_error("group_t delete", &e))
     }
+
+    fn get_sudo_rules(&self) -> Result<Option<(Vec<SudoRule>, u64)>, CacheError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT rules, expiry FROM sudo_rule_t WHERE id = 1")
+            .map_err(|e| self.sqlite_error("select prepare", &e))?;
+
+        let data_iter = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| self.sqlite_error("query_map", &e))?;
+        let data: Result<Vec<(Vec<u8>, i64)>, _> = data_iter
+            .map(|v| v.map_err(|e| self.sqlite_error("map", &e)))
+            .collect();
+
+        let Some((rules, expiry)) = data?.pop() else {
+            return Ok(None);
+        };
+
+        let expiry = u64::try_from(expiry).map_err(|e| {
+            error!("u64 convert error -> {:?}", e);
+            CacheError::Parse
+        })?;
+
+        serde_json::from_slice(rules.as_slice())
+            .map(|rules| Some((rules, expiry)))
+            .map_err(|e| {
+                error!("json error -> {:?}", e);
+                CacheError::SerdeJson
+            })
+    }
+
+    fn update_sudo_rules(&self, rules: &[SudoRule], expire: u64) -> Result<(), CacheError> {
+        let data = serde_json::to_vec(rules).map_err(|e| {
+            error!("json error -> {:?}", e);
+            CacheError::SerdeJson
+        })?;
+        let expire = i64::try_from(expire).map_err(|e| {
+            error!("i64 convert error -> {:?}", e);
+            CacheError::Parse
+        })?;
+
+        let mut stmt = self
+            .conn
+            .prepare("INSERT OR REPLACE INTO sudo_rule_t (id, rules, expiry) VALUES (1, :rules, :expiry)")
+            .map_err(|e| self.sqlite_error("prepare", &e))?;
+
+        stmt.execute(named_params! {
+            ":rules": &data,
+            ":expiry": &expire,
+        })
+        .map(|r| {
+            debug!("insert -> {:?}", r);
+        })
+        .map_err(|e| self.sqlite_error("execute", &e))
+    }
+
+    fn get_hbac_rules(&self) -> Result<Option<(Vec<HbacRule>, u64)>, CacheError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT rules, expiry FROM hbac_rule_t WHERE id = 1")
+            .map_err(|e| self.sqlite_error("select prepare", &e))?;
+
+        let data_iter = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| self.sqlite_error("query_map", &e))?;
+        let data: Result<Vec<(Vec<u8>, i64)>, _> = data_iter
+            .map(|v| v.map_err(|e| self.sqlite_error("map", &e)))
+            .collect();
+
+        let Some((rules, expiry)) = data?.pop() else {
+            return Ok(None);
+        };
+
+        let expiry = u64::try_from(expiry).map_err(|e| {
+            error!("u64 convert error -> {:?}", e);
+            CacheError::Parse
+        })?;
+
+        serde_json::from_slice(rules.as_slice())
+            .map(|rules| Some((rules, expiry)))
+            .map_err(|e| {
+                error!("json error -> {:?}", e);
+                CacheError::SerdeJson
+            })
+    }
+
+    fn update_hbac_rules(&self, rules: &[HbacRule], expire: u64) -> Result<(), CacheError> {
+        let data = serde_json::to_vec(rules).map_err(|e| {
+            error!("json error -> {:?}", e);
+            CacheError::SerdeJson
+        })?;
+        let expire = i64::try_from(expire).map_err(|e| {
+            error!("i64 convert error -> {:?}", e);
+            CacheError::Parse
+        })?;
+
+        let mut stmt = self
+            .conn
+            .prepare("INSERT OR REPLACE INTO hbac_rule_t (id, rules, expiry) VALUES (1, :rules, :expiry)")
+            .map_err(|e| self.sqlite_error("prepare", &e))?;
+
+        stmt.execute(named_params! {
+            ":rules": &data,
+            ":expiry": &expire,
+        })
+        .map(|r| {
+            debug!("insert -> {:?}", r);
+        })
+        .map_err(|e| self.sqlite_error("execute", &e))
+    }
+
+    fn get_automount_maps(&self) -> Result<Option<(Vec<AutomountMap>, u64)>, CacheError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT maps, expiry FROM automount_map_t WHERE id = 1")
+            .map_err(|e| self.sqlite_error("select prepare", &e))?;
+
+        let data_iter = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| self.sqlite_error("query_map", &e))?;
+        let data: Result<Vec<(Vec<u8>, i64)>, _> = data_iter
+            .map(|v| v.map_err(|e| self.sqlite_error("map", &e)))
+            .collect();
+
+        let Some((maps, expiry)) = data?.pop() else {
+            return Ok(None);
+        };
+
+        let expiry = u64::try_from(expiry).map_err(|e| {
+            error!("u64 convert error -> {:?}", e);
+            CacheError::Parse
+        })?;
+
+        serde_json::from_slice(maps.as_slice())
+            .map(|maps| Some((maps, expiry)))
+            .map_err(|e| {
+                error!("json error -> {:?}", e);
+                CacheError::SerdeJson
+            })
+    }
+
+    fn update_automount_maps(&self, maps: &[AutomountMap], expire: u64) -> Result<(), CacheError> {
+        let data = serde_json::to_vec(maps).map_err(|e| {
+            error!("json error -> {:?}", e);
+            CacheError::SerdeJson
+        })?;
+        let expire = i64::try_from(expire).map_err(|e| {
+            error!("i64 convert error -> {:?}", e);
+            CacheError::Parse
+        })?;
+
+        let mut stmt = self
+            .conn
+            .prepare("INSERT OR REPLACE INTO automount_map_t (id, maps, expiry) VALUES (1, :maps, :expiry)")
+            .map_err(|e| self.sqlite_error("prepare", &e))?;
+
+        stmt.execute(named_params! {
+            ":maps": &data,
+            ":expiry": &expire,
+        })
+        .map(|r| {
+            debug!("insert -> {:?}", r);
+        })
+        .map_err(|e| self.sqlite_error("execute", &e))
+    }
 }
 
 impl<'a> fmt::Debug for DbTxn<'a> {
@@ -1151,7 +1393,9 @@ pub(crate) mod tpm {
 mod tests {
     // use std::assert_matches::assert_matches;
     use super::{Cache, CacheTxn, Db};
-    use crate::idprovider::interface::{GroupToken, Id, UserToken};
+    use crate::idprovider::interface::{
+        AutomountEntry, AutomountMap, GroupToken, HbacRule, Id, SudoRule, UserToken,
+    };
     use crate::unix_config::TpmPolicy;
 
     const TESTACCOUNT1_PASSWORD_A: &str = "password a for account1 test";
@@ -1174,6 +1418,7 @@ mod tests {
             groups: Vec::new(),
             sshkeys: vec!["key-a".to_string()],
             valid: true,
+            subid_range: None,
         };
 
         let id_name = Id::Name("testuser".to_string());
@@ -1347,6 +1592,7 @@ mod tests {
             groups: vec![gt1.clone(), gt2],
             sshkeys: vec!["key-a".to_string()],
             valid: true,
+            subid_range: None,
         };
 
         // First, add the groups.
@@ -1410,6 +1656,7 @@ mod tests {
             groups: Vec::new(),
             sshkeys: vec!["key-a".to_string()],
             valid: true,
+            subid_range: None,
         };
 
         // Test that with no account, is false
@@ -1534,6 +1781,7 @@ mod tests {
             groups: Vec::new(),
             sshkeys: vec!["key-a".to_string()],
             valid: true,
+            subid_range: None,
         };
 
         let ut2 = UserToken {
@@ -1546,6 +1794,7 @@ mod tests {
             groups: Vec::new(),
             sshkeys: vec!["key-a".to_string()],
             valid: true,
+            subid_range: None,
         };
 
         let id_name = Id::Name("testuser".to_string());
@@ -1581,4 +1830,111 @@ mod tests {
 
         assert!(dbtxn.commit().is_ok());
     }
+
+    #[tokio::test]
+    async fn test_cache_db_sudo_rules() {
+        sketching::test_init();
+        let db = Db::new("", &TpmPolicy::default()).expect("failed to create.");
+        let dbtxn = db.write().await;
+        assert!(dbtxn.migrate().is_ok());
+
+        // Nothing has been cached yet.
+        assert!(dbtxn.get_sudo_rules().unwrap().is_none());
+
+        let rule = SudoRule {
+            name: "test_rule".to_string(),
+            uuid: uuid::uuid!("0302b99c-f0f6-41ab-9492-852692b0fd16"),
+            users: vec![uuid::uuid!("b500be97-8552-42a5-aca0-668bc5625705")],
+            commands: vec!["/usr/bin/systemctl".to_string()],
+            runas: Vec::new(),
+            nopasswd: false,
+        };
+
+        dbtxn.update_sudo_rules(&[rule.clone()], 10).unwrap();
+        let (rules, expiry) = dbtxn.get_sudo_rules().unwrap().unwrap();
+        assert_eq!(rules, vec![rule]);
+        assert_eq!(expiry, 10);
+
+        // An empty set of rules replaces the previous set, and is still cached.
+        dbtxn.update_sudo_rules(&[], 20).unwrap();
+        let (rules, expiry) = dbtxn.get_sudo_rules().unwrap().unwrap();
+        assert!(rules.is_empty());
+        assert_eq!(expiry, 20);
+
+        // Invalidate expires the rules, but keeps them for offline use.
+        assert!(dbtxn.invalidate().is_ok());
+        let (_, expiry) = dbtxn.get_sudo_rules().unwrap().unwrap();
+        assert_eq!(expiry, 0);
+
+        assert!(dbtxn.clear().is_ok());
+        assert!(dbtxn.get_sudo_rules().unwrap().is_none());
+
+        assert!(dbtxn.commit().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cache_db_hbac_rules() {
+        sketching::test_init();
+        let db = Db::new("", &TpmPolicy::default()).expect("failed to create.");
+        let dbtxn = db.write().await;
+        assert!(dbtxn.migrate().is_ok());
+
+        assert!(dbtxn.get_hbac_rules().unwrap().is_none());
+
+        let rule = HbacRule {
+            name: "test_rule".to_string(),
+            uuid: uuid::uuid!("0302b99c-f0f6-41ab-9492-852692b0fd16"),
+            users: vec![uuid::uuid!("b500be97-8552-42a5-aca0-668bc5625705")],
+            services: vec!["sshd".to_string()],
+        };
+
+        dbtxn.update_hbac_rules(&[rule.clone()], 10).unwrap();
+        let (rules, expiry) = dbtxn.get_hbac_rules().unwrap().unwrap();
+        assert_eq!(rules, vec![rule]);
+        assert_eq!(expiry, 10);
+
+        // Invalidate expires the rules, but keeps them for offline use.
+        assert!(dbtxn.invalidate().is_ok());
+        let (_, expiry) = dbtxn.get_hbac_rules().unwrap().unwrap();
+        assert_eq!(expiry, 0);
+
+        assert!(dbtxn.clear().is_ok());
+        assert!(dbtxn.get_hbac_rules().unwrap().is_none());
+
+        assert!(dbtxn.commit().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cache_db_automount_maps() {
+        sketching::test_init();
+        let db = Db::new("", &TpmPolicy::default()).expect("failed to create.");
+        let dbtxn = db.write().await;
+        assert!(dbtxn.migrate().is_ok());
+
+        assert!(dbtxn.get_automount_maps().unwrap().is_none());
+
+        let map = AutomountMap {
+            name: "auto.home".to_string(),
+            uuid: uuid::uuid!("5bd0ad1b-7af0-4e6b-a45e-0d6e1a4f9a8b"),
+            entries: vec![AutomountEntry {
+                key: "*".to_string(),
+                information: "-rw nfs.example.com:/home/&".to_string(),
+            }],
+        };
+
+        dbtxn.update_automount_maps(&[map.clone()], 10).unwrap();
+        let (maps, expiry) = dbtxn.get_automount_maps().unwrap().unwrap();
+        assert_eq!(maps, vec![map]);
+        assert_eq!(expiry, 10);
+
+        // Invalidate expires the maps, but keeps them for offline use.
+        assert!(dbtxn.invalidate().is_ok());
+        let (_, expiry) = dbtxn.get_automount_maps().unwrap().unwrap();
+        assert_eq!(expiry, 0);
+
+        assert!(dbtxn.clear().is_ok());
+        assert!(dbtxn.get_automount_maps().unwrap().is_none());
+
+        assert!(dbtxn.commit().is_ok());
+    }
 }
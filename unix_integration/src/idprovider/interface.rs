@@ -37,6 +37,18 @@ pub struct GroupToken {
     pub gidnumber: u32,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SubidRange {
+    pub start: u32,
+    pub count: u32,
+}
+
+impl SubidRange {
+    pub fn contains(&self, id: u32) -> bool {
+        id >= self.start && id - self.start < self.count
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserToken {
     pub name: String,
@@ -50,6 +62,44 @@ pub struct UserToken {
     pub sshkeys: Vec<String>,
     // Defaults to false.
     pub valid: bool,
+    // Tokens cached before ranges were supported do not have this.
+    #[serde(default)]
+    pub subid_range: Option<SubidRange>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SudoRule {
+    pub name: String,
+    pub uuid: Uuid,
+    // The accounts and groups that this rule applies to.
+    pub users: Vec<Uuid>,
+    pub commands: Vec<String>,
+    // If empty, only root is permitted.
+    pub runas: Vec<String>,
+    pub nopasswd: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HbacRule {
+    pub name: String,
+    pub uuid: Uuid,
+    // The accounts and groups that this rule applies to.
+    pub users: Vec<Uuid>,
+    // The PAM services this rule allows, or ALL.
+    pub services: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AutomountEntry {
+    pub key: String,
+    pub information: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AutomountMap {
+    pub name: String,
+    pub uuid: Uuid,
+    pub entries: Vec<AutomountEntry>,
 }
 
 #[derive(Debug)]
@@ -86,6 +136,17 @@ pub enum AuthCacheAction {
     PasswordHashUpdate { cred: String },
 }
 
+pub enum PasswordChangeResult {
+    Success,
+    /// The current credential was not accepted, or the account may not change
+    /// its own password.
+    Denied,
+    /// The idp rejected the new password, with feedback about why.
+    PasswordQuality {
+        feedback: Vec<String>,
+    },
+}
+
 #[async_trait]
 pub trait IdProvider {
     async fn provider_authenticate(&self) -> Result<(), IdpError>;
@@ -139,5 +200,26 @@ pub trait IdProvider {
     ) -> Result<AuthResult, IdpError>;
     */
 
+    /// Change the unix password of an account. The change is made with the account's
+    /// own authority, so the current credential must be provided.
+    async fn unix_user_change_password(
+        &self,
+        _account_id: &str,
+        _current_cred: &str,
+        _new_cred: &str,
+    ) -> Result<PasswordChangeResult, IdpError>;
+
     async fn unix_group_get(&self, id: &Id) -> Result<GroupToken, IdpError>;
+
+    /// Retrieve all sudo rules from the idp. The resolver evaluates these locally so
+    /// that they can be enforced while offline.
+    async fn unix_sudo_rules_get(&self) -> Result<Vec<SudoRule>, IdpError>;
+
+    /// Retrieve the host access rules that apply to this host. These are cached so that
+    /// account access can still be decided while offline.
+    async fn unix_hbac_rules_get(&self) -> Result<Vec<HbacRule>, IdpError>;
+
+    /// Retrieve all automount maps from the idp. These are cached so that mounts can
+    /// still be resolved while offline.
+    async fn unix_automount_maps_get(&self) -> Result<Vec<AutomountMap>, IdpError>;
 }
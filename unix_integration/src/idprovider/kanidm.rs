@@ -1,22 +1,29 @@
 use async_trait::async_trait;
 use kanidm_client::{ClientError, KanidmClient, StatusCode};
-use kanidm_proto::v1::{OperationError, UnixGroupToken, UnixUserToken};
+use kanidm_proto::v1::{
+    OperationError, UnixAutomountEntry, UnixAutomountMap, UnixGroupToken, UnixHbacRule,
+    UnixSudoRule, UnixUserToken,
+};
 use tokio::sync::RwLock;
 
 use super::interface::{
-    AuthCacheAction, AuthCredHandler, AuthRequest, AuthResult, GroupToken, Id, IdProvider,
-    IdpError, UserToken,
+    AuthCacheAction, AuthCredHandler, AuthRequest, AuthResult, AutomountEntry, AutomountMap,
+    GroupToken, HbacRule, Id, IdProvider, IdpError, PasswordChangeResult, SubidRange, SudoRule,
+    UserToken,
 };
 use crate::unix_proto::PamAuthRequest;
 
 pub struct KanidmProvider {
     client: RwLock<KanidmClient>,
+    // The api token of this host. If not set, the provider authenticates anonymously.
+    host_token: Option<String>,
 }
 
 impl KanidmProvider {
-    pub fn new(client: KanidmClient) -> Self {
+    pub fn new(client: KanidmClient, host_token: Option<String>) -> Self {
         KanidmProvider {
             client: RwLock::new(client),
+            host_token,
         }
     }
 }
@@ -33,10 +40,16 @@ impl From<UnixUserToken> for UserToken {
             groups,
             sshkeys,
             valid,
+            subid_range,
         } = value;
 
         let groups = groups.into_iter().map(GroupToken::from).collect();
 
+        let subid_range = subid_range.map(|r| SubidRange {
+            start: r.start,
+            count: r.count,
+        });
+
         UserToken {
             name,
             spn,
@@ -47,6 +60,7 @@ impl From<UnixUserToken> for UserToken {
             groups,
             sshkeys,
             valid,
+            subid_range,
         }
     }
 }
@@ -69,12 +83,96 @@ impl From<UnixGroupToken> for GroupToken {
     }
 }
 
+impl From<UnixHbacRule> for HbacRule {
+    fn from(value: UnixHbacRule) -> HbacRule {
+        let UnixHbacRule {
+            name,
+            uuid,
+            users,
+            services,
+        } = value;
+
+        HbacRule {
+            name,
+            uuid,
+            users,
+            services,
+        }
+    }
+}
+
+impl From<UnixAutomountMap> for AutomountMap {
+    fn from(value: UnixAutomountMap) -> AutomountMap {
+        let UnixAutomountMap {
+            name,
+            uuid,
+            entries,
+        } = value;
+
+        let entries = entries
+            .into_iter()
+            .map(|UnixAutomountEntry { key, information }| AutomountEntry { key, information })
+            .collect();
+
+        AutomountMap {
+            name,
+            uuid,
+            entries,
+        }
+    }
+}
+
+impl From<UnixSudoRule> for SudoRule {
+    fn from(value: UnixSudoRule) -> SudoRule {
+        let UnixSudoRule {
+            name,
+            uuid,
+            users,
+            commands,
+            runas,
+            nopasswd,
+        } = value;
+
+        SudoRule {
+            name,
+            uuid,
+            users,
+            commands,
+            runas,
+            nopasswd,
+        }
+    }
+}
+
 #[async_trait]
 impl IdProvider for KanidmProvider {
     // Needs .read on all types except re-auth.
     async fn provider_authenticate(&self) -> Result<(), IdpError> {
-        match self.client.write().await.auth_anonymous().await {
-            Ok(_uat) => Ok(()),
+        let client = self.client.write().await;
+
+        let Some(host_token) = self.host_token.as_ref() else {
+            return match client.auth_anonymous().await {
+                Ok(_uat) => Ok(()),
+                Err(err) => {
+                    error!(?err, "Provider authentication failed");
+                    Err(IdpError::ProviderUnauthorised)
+                }
+            };
+        };
+
+        // The host token also identifies this host to the server, which is required to
+        // retrieve the host access and sudo rules.
+        client.set_token(host_token.clone()).await;
+        match client.whoami().await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => {
+                error!("Provider authentication failed - the host token was not accepted");
+                Err(IdpError::ProviderUnauthorised)
+            }
+            Err(ClientError::Transport(err)) => {
+                error!(?err);
+                Err(IdpError::Transport)
+            }
             Err(err) => {
                 error!(?err, "Provider authentication failed");
                 Err(IdpError::ProviderUnauthorised)
@@ -260,6 +358,71 @@ impl IdProvider for KanidmProvider {
     }
     */
 
+    async fn unix_user_change_password(
+        &self,
+        account_id: &str,
+        current_cred: &str,
+        new_cred: &str,
+    ) -> Result<PasswordChangeResult, IdpError> {
+        // The current unix password authenticates the change, so it is made through
+        // the provider session rather than a session of the account.
+        let result = self
+            .client
+            .read()
+            .await
+            .idm_account_unix_cred_change(account_id, current_cred, new_cred)
+            .await;
+
+        match result {
+            Ok(()) => Ok(PasswordChangeResult::Success),
+            Err(ClientError::Transport(err)) => {
+                error!(?err);
+                Err(IdpError::Transport)
+            }
+            Err(ClientError::AuthenticationFailed)
+            | Err(ClientError::Http(StatusCode::UNAUTHORIZED, _, _))
+            | Err(ClientError::Http(StatusCode::FORBIDDEN, _, _)) => {
+                warn!(?account_id, "password change denied");
+                Ok(PasswordChangeResult::Denied)
+            }
+            Err(ClientError::Http(
+                StatusCode::BAD_REQUEST,
+                Some(OperationError::PasswordQuality(feedback)),
+                opid,
+            )) => {
+                debug!(?opid, "new password rejected by quality checks");
+                Ok(PasswordChangeResult::PasswordQuality {
+                    feedback: feedback.iter().map(|f| f.to_string()).collect(),
+                })
+            }
+            Err(ClientError::Http(
+                StatusCode::BAD_REQUEST,
+                Some(OperationError::NoMatchingEntries),
+                opid,
+            ))
+            | Err(ClientError::Http(
+                StatusCode::NOT_FOUND,
+                Some(OperationError::NoMatchingEntries),
+                opid,
+            ))
+            | Err(ClientError::Http(
+                StatusCode::BAD_REQUEST,
+                Some(OperationError::InvalidAccountState(_)),
+                opid,
+            )) => {
+                error!(
+                    "unknown account or is not a valid posix account - eventid {}",
+                    opid
+                );
+                Err(IdpError::NotFound)
+            }
+            Err(err) => {
+                error!(?err, "client error");
+                Err(IdpError::BadRequest)
+            }
+        }
+    }
+
     async fn unix_group_get(&self, id: &Id) -> Result<GroupToken, IdpError> {
         match self
             .client
@@ -317,4 +480,67 @@ impl IdProvider for KanidmProvider {
             }
         }
     }
+
+    async fn unix_sudo_rules_get(&self) -> Result<Vec<SudoRule>, IdpError> {
+        match self.client.read().await.idm_sudo_rule_unix_list().await {
+            Ok(rules) => Ok(rules.into_iter().map(SudoRule::from).collect()),
+            Err(ClientError::Transport(err)) => {
+                error!(?err);
+                Err(IdpError::Transport)
+            }
+            Err(ClientError::Http(StatusCode::UNAUTHORIZED, reason, opid)) => {
+                error!(
+                    "authentication error {:?}, moving to offline - eventid {}",
+                    reason, opid
+                );
+                Err(IdpError::ProviderUnauthorised)
+            }
+            Err(err) => {
+                error!(?err, "client error");
+                Err(IdpError::BadRequest)
+            }
+        }
+    }
+
+    async fn unix_hbac_rules_get(&self) -> Result<Vec<HbacRule>, IdpError> {
+        match self.client.read().await.idm_hbac_rule_unix_list().await {
+            Ok(rules) => Ok(rules.into_iter().map(HbacRule::from).collect()),
+            Err(ClientError::Transport(err)) => {
+                error!(?err);
+                Err(IdpError::Transport)
+            }
+            Err(ClientError::Http(StatusCode::UNAUTHORIZED, reason, opid)) => {
+                error!(
+                    "authentication error {:?}, moving to offline - eventid {}",
+                    reason, opid
+                );
+                Err(IdpError::ProviderUnauthorised)
+            }
+            Err(err) => {
+                error!(?err, "client error");
+                Err(IdpError::BadRequest)
+            }
+        }
+    }
+
+    async fn unix_automount_maps_get(&self) -> Result<Vec<AutomountMap>, IdpError> {
+        match self.client.read().await.idm_automount_map_unix_list().await {
+            Ok(maps) => Ok(maps.into_iter().map(AutomountMap::from).collect()),
+            Err(ClientError::Transport(err)) => {
+                error!(?err);
+                Err(IdpError::Transport)
+            }
+            Err(ClientError::Http(StatusCode::UNAUTHORIZED, reason, opid)) => {
+                error!(
+                    "authentication error {:?}, moving to offline - eventid {}",
+                    reason, opid
+                );
+                Err(IdpError::ProviderUnauthorised)
+            }
+            Err(err) => {
+                error!(?err, "client error");
+                Err(IdpError::BadRequest)
+            }
+        }
+    }
 }
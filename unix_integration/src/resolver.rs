@@ -13,10 +13,14 @@ use uuid::Uuid;
 
 use crate::db::{Cache, CacheTxn, Db};
 use crate::idprovider::interface::{
-    AuthCacheAction, AuthCredHandler, AuthResult, GroupToken, Id, IdProvider, IdpError, UserToken,
+    AuthCacheAction, AuthCredHandler, AuthResult, AutomountMap, GroupToken, HbacRule, Id,
+    IdProvider, IdpError, PasswordChangeResult, SudoRule, UserToken,
 };
 use crate::unix_config::{HomeAttr, UidAttr};
-use crate::unix_proto::{HomeDirectoryInfo, NssGroup, NssUser, PamAuthRequest, PamAuthResponse};
+use crate::unix_proto::{
+    HomeDirectoryInfo, NssAutomountEntry, NssGroup, NssSubidRange, NssUser, PamAuthRequest,
+    PamAuthResponse, PamChangeAuthTokenResponse, SudoCheckResponse, SudoPrivilege,
+};
 
 // use crate::unix_passwd::{EtcUser, EtcGroup};
 
@@ -63,6 +67,9 @@ where
     uid_attr_map: UidAttr,
     gid_attr_map: UidAttr,
     allow_id_overrides: HashSet<Id>,
+    // The provider authenticates as this host, so the host access and sudo rules that
+    // apply to it can be retrieved.
+    host_identity: bool,
     nxset: Mutex<HashSet<Id>>,
     nxcache: Mutex<LruCache<Id, SystemTime>>,
 }
@@ -94,6 +101,7 @@ where
         uid_attr_map: UidAttr,
         gid_attr_map: UidAttr,
         allow_id_overrides: Vec<String>,
+        host_identity: bool,
     ) -> Result<Self, ()> {
         // setup and do a migrate.
         {
@@ -102,7 +110,7 @@ where
             dbtxn.commit().map_err(|_| ())?;
         }
 
-        if pam_allow_groups.is_empty() {
+        if pam_allow_groups.is_empty() && !host_identity {
             eprintln!("Will not be able to authorise user logins, pam_allow_groups config is not configured.");
         }
 
@@ -121,6 +129,7 @@ where
             uid_attr_map,
             gid_attr_map,
             allow_id_overrides: allow_id_overrides.into_iter().map(Id::Name).collect(),
+            host_identity,
             nxset: Mutex::new(HashSet::new()),
             nxcache: Mutex::new(LruCache::new(NXCACHE_SIZE)),
         })
@@ -699,6 +708,51 @@ where
         self.get_nssaccount(Id::Gid(gid)).await
     }
 
+    pub async fn get_nsssubid_ranges(
+        &self,
+        account_id: &str,
+    ) -> Result<Option<Vec<NssSubidRange>>, ()> {
+        let token = self.get_usertoken(Id::Name(account_id.to_string())).await?;
+        Ok(token.map(|tok| {
+            tok.subid_range
+                .into_iter()
+                .map(|r| NssSubidRange {
+                    start: r.start,
+                    count: r.count,
+                })
+                .collect()
+        }))
+    }
+
+    /// Find the uids of the cached accounts whose subordinate id range contains this id.
+    pub async fn get_nsssubid_owners(&self, id: u32) -> Result<Vec<u32>, ()> {
+        self.get_cached_usertokens().await.map(|l| {
+            l.into_iter()
+                .filter(|tok| tok.subid_range.map_or(false, |r| r.contains(id)))
+                .map(|tok| tok.gidnumber)
+                .collect()
+        })
+    }
+
+    pub async fn get_nssautomount_map(
+        &self,
+        map_name: &str,
+    ) -> Result<Option<Vec<NssAutomountEntry>>, ()> {
+        let maps = self.get_automount_maps().await?;
+        Ok(maps
+            .into_iter()
+            .find(|map| map.name.eq_ignore_ascii_case(map_name))
+            .map(|map| {
+                map.entries
+                    .into_iter()
+                    .map(|e| NssAutomountEntry {
+                        key: e.key,
+                        information: e.information,
+                    })
+                    .collect()
+            }))
+    }
+
     #[inline(always)]
     fn token_gidattr(&self, token: &GroupToken) -> String {
         match self.gid_attr_map {
@@ -872,32 +926,64 @@ where
     }
     */
 
-    pub async fn pam_account_allowed(&self, account_id: &str) -> Result<Option<bool>, ()> {
+    /// Decide if an account may access this host through a PAM service. The account is
+    /// allowed if it is a member of one of the pam_allowed_login_groups, or if a host
+    /// access rule from the idp permits it. If the service is not known, only rules that
+    /// allow ALL services apply.
+    pub async fn pam_account_allowed(
+        &self,
+        account_id: &str,
+        service: Option<&str>,
+    ) -> Result<Option<bool>, ()> {
         let token = self.get_usertoken(Id::Name(account_id.to_string())).await?;
 
-        if self.pam_allow_groups.is_empty() {
+        if self.pam_allow_groups.is_empty() && !self.host_identity {
             // can't allow anything if the group list is zero...
             eprintln!("Cannot authenticate users, no allowed groups in configuration!");
-            Ok(Some(false))
-        } else {
-            Ok(token.map(|tok| {
-                let user_set: BTreeSet<_> = tok
-                    .groups
-                    .iter()
-                    .flat_map(|g| [g.name.clone(), g.uuid.hyphenated().to_string()])
-                    .collect();
+            return Ok(Some(false));
+        }
 
-                debug!(
-                    "Checking if user is in allowed groups ({:?}) -> {:?}",
-                    self.pam_allow_groups, user_set,
-                );
-                let intersection_count = user_set.intersection(&self.pam_allow_groups).count();
-                debug!("Number of intersecting groups: {}", intersection_count);
-                debug!("User has valid token: {}", tok.valid);
+        let Some(tok) = token else {
+            return Ok(None);
+        };
 
-                intersection_count > 0 && tok.valid
-            }))
+        debug!("User has valid token: {}", tok.valid);
+        if !tok.valid {
+            return Ok(Some(false));
         }
+
+        let user_set: BTreeSet<_> = tok
+            .groups
+            .iter()
+            .flat_map(|g| [g.name.clone(), g.uuid.hyphenated().to_string()])
+            .collect();
+
+        debug!(
+            "Checking if user is in allowed groups ({:?}) -> {:?}",
+            self.pam_allow_groups, user_set,
+        );
+        let intersection_count = user_set.intersection(&self.pam_allow_groups).count();
+        debug!("Number of intersecting groups: {}", intersection_count);
+
+        if intersection_count > 0 {
+            return Ok(Some(true));
+        }
+
+        if !self.host_identity {
+            return Ok(Some(false));
+        }
+
+        // The user private group carries the account's uuid, so this matches rules that
+        // name the account directly as well as those naming one of its groups.
+        let uuid_set: BTreeSet<_> = tok.groups.iter().map(|g| g.uuid).collect();
+
+        let rules = self.get_hbac_rules().await?;
+        let allowed = rules.iter().any(|rule| {
+            rule.users.iter().any(|u| uuid_set.contains(u)) && hbac_service_matches(rule, service)
+        });
+        debug!(?service, "Host access rules allow access: {}", allowed);
+
+        Ok(Some(allowed))
     }
 
     pub async fn pam_account_authenticate_init(
@@ -1156,6 +1242,405 @@ where
         }
     }
 
+    pub async fn pam_account_change_authtok(
+        &self,
+        account_id: &str,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<PamChangeAuthTokenResponse, ()> {
+        // This also excludes accounts that are masked by the nxset.
+        let token = match self.get_usertoken(Id::Name(account_id.to_string())).await? {
+            Some(token) => token,
+            None => return Ok(PamChangeAuthTokenResponse::Unknown),
+        };
+
+        // A password change can only be made by the idp, so we must be online.
+        let state = self.get_cachestate().await;
+        let online = if !matches!(state, CacheState::Online) {
+            self.test_connection().await
+        } else {
+            true
+        };
+
+        if !online {
+            return Ok(PamChangeAuthTokenResponse::Offline);
+        }
+
+        match self
+            .client
+            .unix_user_change_password(account_id, current_password, new_password)
+            .await
+        {
+            Ok(PasswordChangeResult::Success) => {
+                // Replace the cached hash, else offline auth would still accept the
+                // former password until the next online authentication.
+                self.set_cache_userpassword(token.uuid, new_password)
+                    .await?;
+                Ok(PamChangeAuthTokenResponse::Success)
+            }
+            Ok(PasswordChangeResult::Denied) => Ok(PamChangeAuthTokenResponse::Denied),
+            Ok(PasswordChangeResult::PasswordQuality { feedback }) => {
+                Ok(PamChangeAuthTokenResponse::PasswordQuality { feedback })
+            }
+            Err(IdpError::NotFound) => Ok(PamChangeAuthTokenResponse::Unknown),
+            Err(IdpError::ProviderUnauthorised) | Err(IdpError::Transport) => {
+                error!("transport error, moving to offline");
+                // Something went wrong, mark offline.
+                let time = SystemTime::now().add(Duration::from_secs(15));
+                self.set_cachestate(CacheState::OfflineNextCheck(time))
+                    .await;
+                Ok(PamChangeAuthTokenResponse::Offline)
+            }
+            Err(IdpError::BadRequest) => Err(()),
+        }
+    }
+
+    async fn get_cached_sudo_rules(&self) -> Result<(bool, Vec<SudoRule>), ()> {
+        let dbtxn = self.db.write().await;
+        let r = dbtxn.get_sudo_rules().map_err(|_| ())?;
+
+        match r {
+            Some((rules, ex)) => {
+                let ex_time = SystemTime::UNIX_EPOCH + Duration::from_secs(ex);
+                Ok((SystemTime::now() >= ex_time, rules))
+            }
+            // Never retrieved, so we have nothing to enforce yet.
+            None => Ok((true, Vec::new())),
+        }
+    }
+
+    async fn set_cache_sudo_rules(&self, rules: &[SudoRule]) -> Result<(), ()> {
+        // Set an expiry
+        let ex_time = SystemTime::now() + Duration::from_secs(self.timeout_seconds);
+        let offset = ex_time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| {
+                error!("time conversion error - ex_time less than epoch? {:?}", e);
+            })?;
+
+        let dbtxn = self.db.write().await;
+        dbtxn
+            .update_sudo_rules(rules, offset.as_secs())
+            .and_then(|_| dbtxn.commit())
+            .map_err(|_| ())
+    }
+
+    async fn refresh_sudo_rules(&self, rules: Vec<SudoRule>) -> Result<Vec<SudoRule>, ()> {
+        match self.client.unix_sudo_rules_get().await {
+            Ok(n_rules) => {
+                self.set_cache_sudo_rules(&n_rules).await?;
+                Ok(n_rules)
+            }
+            Err(IdpError::Transport) => {
+                error!("transport error, moving to offline");
+                // Something went wrong, mark offline.
+                let time = SystemTime::now().add(Duration::from_secs(15));
+                self.set_cachestate(CacheState::OfflineNextCheck(time))
+                    .await;
+                Ok(rules)
+            }
+            Err(IdpError::ProviderUnauthorised) => {
+                // Something went wrong, mark offline to force a re-auth ASAP.
+                let time = SystemTime::now().sub(Duration::from_secs(1));
+                self.set_cachestate(CacheState::OfflineNextCheck(time))
+                    .await;
+                Ok(rules)
+            }
+            Err(IdpError::NotFound) | Err(IdpError::BadRequest) => {
+                // Some other transient error, continue with the cached rules.
+                Ok(rules)
+            }
+        }
+    }
+
+    async fn get_sudo_rules(&self) -> Result<Vec<SudoRule>, ()> {
+        debug!("get_sudo_rules");
+        let (expired, rules) = self.get_cached_sudo_rules().await.map_err(|e| {
+            debug!("get_sudo_rules error -> {:?}", e);
+        })?;
+
+        let state = self.get_cachestate().await;
+
+        match (expired, state) {
+            (_, CacheState::Offline) | (false, _) => {
+                debug!("offline or valid, returning cached rules");
+                Ok(rules)
+            }
+            (true, CacheState::OfflineNextCheck(time)) => {
+                debug!("offline expired, next check {:?}, refresh cache", time);
+                if SystemTime::now() >= time && self.test_connection().await {
+                    // We brought ourselves online, lets go
+                    self.refresh_sudo_rules(rules).await
+                } else {
+                    // Unable to bring up connection, return cache.
+                    Ok(rules)
+                }
+            }
+            (true, CacheState::Online) => {
+                debug!("online expired, refresh cache");
+                self.refresh_sudo_rules(rules).await
+            }
+        }
+    }
+
+    async fn get_cached_hbac_rules(&self) -> Result<(bool, Vec<HbacRule>), ()> {
+        let dbtxn = self.db.write().await;
+        let r = dbtxn.get_hbac_rules().map_err(|_| ())?;
+
+        match r {
+            Some((rules, ex)) => {
+                let ex_time = SystemTime::UNIX_EPOCH + Duration::from_secs(ex);
+                Ok((SystemTime::now() >= ex_time, rules))
+            }
+            // Never retrieved, so no rules can allow access yet.
+            None => Ok((true, Vec::new())),
+        }
+    }
+
+    async fn set_cache_hbac_rules(&self, rules: &[HbacRule]) -> Result<(), ()> {
+        // Set an expiry
+        let ex_time = SystemTime::now() + Duration::from_secs(self.timeout_seconds);
+        let offset = ex_time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| {
+                error!("time conversion error - ex_time less than epoch? {:?}", e);
+            })?;
+
+        let dbtxn = self.db.write().await;
+        dbtxn
+            .update_hbac_rules(rules, offset.as_secs())
+            .and_then(|_| dbtxn.commit())
+            .map_err(|_| ())
+    }
+
+    async fn refresh_hbac_rules(&self, rules: Vec<HbacRule>) -> Result<Vec<HbacRule>, ()> {
+        match self.client.unix_hbac_rules_get().await {
+            Ok(n_rules) => {
+                self.set_cache_hbac_rules(&n_rules).await?;
+                Ok(n_rules)
+            }
+            Err(IdpError::Transport) => {
+                error!("transport error, moving to offline");
+                // Something went wrong, mark offline.
+                let time = SystemTime::now().add(Duration::from_secs(15));
+                self.set_cachestate(CacheState::OfflineNextCheck(time))
+                    .await;
+                Ok(rules)
+            }
+            Err(IdpError::ProviderUnauthorised) => {
+                // Something went wrong, mark offline to force a re-auth ASAP.
+                let time = SystemTime::now().sub(Duration::from_secs(1));
+                self.set_cachestate(CacheState::OfflineNextCheck(time))
+                    .await;
+                Ok(rules)
+            }
+            Err(IdpError::NotFound) | Err(IdpError::BadRequest) => {
+                // Some other transient error, continue with the cached rules.
+                Ok(rules)
+            }
+        }
+    }
+
+    async fn get_hbac_rules(&self) -> Result<Vec<HbacRule>, ()> {
+        debug!("get_hbac_rules");
+        let (expired, rules) = self.get_cached_hbac_rules().await.map_err(|e| {
+            debug!("get_hbac_rules error -> {:?}", e);
+        })?;
+
+        let state = self.get_cachestate().await;
+
+        match (expired, state) {
+            (_, CacheState::Offline) | (false, _) => {
+                debug!("offline or valid, returning cached rules");
+                Ok(rules)
+            }
+            (true, CacheState::OfflineNextCheck(time)) => {
+                debug!("offline expired, next check {:?}, refresh cache", time);
+                if SystemTime::now() >= time && self.test_connection().await {
+                    // We brought ourselves online, lets go
+                    self.refresh_hbac_rules(rules).await
+                } else {
+                    // Unable to bring up connection, return cache.
+                    Ok(rules)
+                }
+            }
+            (true, CacheState::Online) => {
+                debug!("online expired, refresh cache");
+                self.refresh_hbac_rules(rules).await
+            }
+        }
+    }
+
+    async fn get_cached_automount_maps(&self) -> Result<(bool, Vec<AutomountMap>), ()> {
+        let dbtxn = self.db.write().await;
+        let r = dbtxn.get_automount_maps().map_err(|_| ())?;
+
+        match r {
+            Some((maps, ex)) => {
+                let ex_time = SystemTime::UNIX_EPOCH + Duration::from_secs(ex);
+                Ok((SystemTime::now() >= ex_time, maps))
+            }
+            // Never retrieved, so there are no maps yet.
+            None => Ok((true, Vec::new())),
+        }
+    }
+
+    async fn set_cache_automount_maps(&self, maps: &[AutomountMap]) -> Result<(), ()> {
+        // Set an expiry
+        let ex_time = SystemTime::now() + Duration::from_secs(self.timeout_seconds);
+        let offset = ex_time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| {
+                error!("time conversion error - ex_time less than epoch? {:?}", e);
+            })?;
+
+        let dbtxn = self.db.write().await;
+        dbtxn
+            .update_automount_maps(maps, offset.as_secs())
+            .and_then(|_| dbtxn.commit())
+            .map_err(|_| ())
+    }
+
+    async fn refresh_automount_maps(
+        &self,
+        maps: Vec<AutomountMap>,
+    ) -> Result<Vec<AutomountMap>, ()> {
+        match self.client.unix_automount_maps_get().await {
+            Ok(n_maps) => {
+                self.set_cache_automount_maps(&n_maps).await?;
+                Ok(n_maps)
+            }
+            Err(IdpError::Transport) => {
+                error!("transport error, moving to offline");
+                // Something went wrong, mark offline.
+                let time = SystemTime::now().add(Duration::from_secs(15));
+                self.set_cachestate(CacheState::OfflineNextCheck(time))
+                    .await;
+                Ok(maps)
+            }
+            Err(IdpError::ProviderUnauthorised) => {
+                // Something went wrong, mark offline to force a re-auth ASAP.
+                let time = SystemTime::now().sub(Duration::from_secs(1));
+                self.set_cachestate(CacheState::OfflineNextCheck(time))
+                    .await;
+                Ok(maps)
+            }
+            Err(IdpError::NotFound) | Err(IdpError::BadRequest) => {
+                // Some other transient error, continue with the cached maps.
+                Ok(maps)
+            }
+        }
+    }
+
+    async fn get_automount_maps(&self) -> Result<Vec<AutomountMap>, ()> {
+        debug!("get_automount_maps");
+        let (expired, maps) = self.get_cached_automount_maps().await.map_err(|e| {
+            debug!("get_automount_maps error -> {:?}", e);
+        })?;
+
+        let state = self.get_cachestate().await;
+
+        match (expired, state) {
+            (_, CacheState::Offline) | (false, _) => {
+                debug!("offline or valid, returning cached maps");
+                Ok(maps)
+            }
+            (true, CacheState::OfflineNextCheck(time)) => {
+                debug!("offline expired, next check {:?}, refresh cache", time);
+                if SystemTime::now() >= time && self.test_connection().await {
+                    // We brought ourselves online, lets go
+                    self.refresh_automount_maps(maps).await
+                } else {
+                    // Unable to bring up connection, return cache.
+                    Ok(maps)
+                }
+            }
+            (true, CacheState::Online) => {
+                debug!("online expired, refresh cache");
+                self.refresh_automount_maps(maps).await
+            }
+        }
+    }
+
+    /// Find the rules that apply to this account on this host.
+    async fn get_sudo_rules_for(&self, account_id: &str) -> Result<Option<Vec<SudoRule>>, ()> {
+        // This also excludes accounts that are masked by the nxset.
+        let token = match self.get_usertoken(Id::Name(account_id.to_string())).await? {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+
+        if !token.valid {
+            // Expired or locked accounts are never permitted.
+            return Ok(Some(Vec::new()));
+        }
+
+        if !self.host_identity {
+            // Rules are only provided to the hosts they apply on, so none can be retrieved.
+            return Ok(Some(Vec::new()));
+        }
+
+        // The user private group carries the account's uuid, so this matches rules that
+        // name the account directly as well as those naming one of its groups.
+        let user_set: BTreeSet<_> = token.groups.iter().map(|g| g.uuid).collect();
+
+        let rules = self.get_sudo_rules().await?;
+        Ok(Some(
+            rules
+                .into_iter()
+                .filter(|rule| rule.users.iter().any(|u| user_set.contains(u)))
+                .collect(),
+        ))
+    }
+
+    pub async fn sudo_check(
+        &self,
+        account_id: &str,
+        runas: &str,
+        command: &[String],
+    ) -> Result<SudoCheckResponse, ()> {
+        let rules = match self.get_sudo_rules_for(account_id).await? {
+            Some(rules) => rules,
+            None => return Ok(SudoCheckResponse::Unknown),
+        };
+
+        let mut matched = rules.iter().filter(|rule| {
+            sudo_runas_matches(rule, runas)
+                && rule
+                    .commands
+                    .iter()
+                    .any(|c| sudo_command_matches(c, command))
+        });
+
+        // If any rule permits the command without a password, that is honoured.
+        Ok(match matched.next() {
+            Some(first) => {
+                let nopasswd = first.nopasswd || matched.any(|rule| rule.nopasswd);
+                SudoCheckResponse::Allowed { nopasswd }
+            }
+            None => SudoCheckResponse::Denied,
+        })
+    }
+
+    pub async fn sudo_list(&self, account_id: &str) -> Result<Option<Vec<SudoPrivilege>>, ()> {
+        let rules = self.get_sudo_rules_for(account_id).await?;
+        Ok(rules.map(|rules| {
+            rules
+                .into_iter()
+                .map(|rule| SudoPrivilege {
+                    rule: rule.name,
+                    runas: if rule.runas.is_empty() {
+                        vec!["root".to_string()]
+                    } else {
+                        rule.runas
+                    },
+                    commands: rule.commands,
+                    nopasswd: rule.nopasswd,
+                })
+                .collect()
+        }))
+    }
+
     pub async fn pam_account_beginsession(
         &self,
         account_id: &str,
@@ -1201,3 +1686,145 @@ where
         }
     }
 }
+
+fn hbac_service_matches(rule: &HbacRule, service: Option<&str>) -> bool {
+    rule.services.iter().any(|s| {
+        s.eq_ignore_ascii_case("ALL") || service.map_or(false, |svc| s.eq_ignore_ascii_case(svc))
+    })
+}
+
+fn sudo_runas_matches(rule: &SudoRule, runas: &str) -> bool {
+    if rule.runas.is_empty() {
+        // Like sudoers, when no runas is given only root is permitted.
+        runas == "root"
+    } else {
+        rule.runas
+            .iter()
+            .any(|r| r.eq_ignore_ascii_case("ALL") || r == runas)
+    }
+}
+
+/// Match a command from a sudo rule against the command being run, where the first
+/// element is the absolute path to the command.
+///
+/// Rules follow the sudoers conventions - "ALL" matches any command, a path ending in
+/// "/" matches any command in that directory, a path alone permits any arguments, and
+/// a path followed by arguments requires exactly those arguments. `""` as the only
+/// argument means that no arguments are permitted.
+fn sudo_command_matches(rule_command: &str, command: &[String]) -> bool {
+    let Some((path, args)) = command.split_first() else {
+        return false;
+    };
+
+    let mut rule_iter = rule_command.split_whitespace();
+    let Some(rule_path) = rule_iter.next() else {
+        return false;
+    };
+    let rule_args: Vec<&str> = rule_iter.collect();
+
+    if rule_path.eq_ignore_ascii_case("ALL") {
+        return true;
+    }
+
+    if rule_path.ends_with('/') {
+        // Only commands directly in this directory, not sub directories.
+        return path
+            .strip_prefix(rule_path)
+            .map_or(false, |name| !name.is_empty() && !name.contains('/'));
+    }
+
+    if rule_path != path {
+        return false;
+    }
+
+    match rule_args.as_slice() {
+        [] => true,
+        ["\"\""] => args.is_empty(),
+        rule_args => rule_args.iter().eq(args.iter()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hbac_service_matches, sudo_command_matches};
+    use crate::idprovider::interface::HbacRule;
+
+    fn cmd(c: &[&str]) -> Vec<String> {
+        c.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sudo_command_matches() {
+        assert!(sudo_command_matches("ALL", &cmd(&["/usr/bin/id"])));
+
+        // A path alone permits any arguments.
+        assert!(sudo_command_matches(
+            "/usr/bin/systemctl",
+            &cmd(&["/usr/bin/systemctl", "restart", "nginx"])
+        ));
+        assert!(!sudo_command_matches(
+            "/usr/bin/systemctl",
+            &cmd(&["/usr/bin/systemd-run"])
+        ));
+
+        // Arguments must match exactly.
+        assert!(sudo_command_matches(
+            "/usr/bin/systemctl restart nginx",
+            &cmd(&["/usr/bin/systemctl", "restart", "nginx"])
+        ));
+        assert!(!sudo_command_matches(
+            "/usr/bin/systemctl restart nginx",
+            &cmd(&["/usr/bin/systemctl", "restart", "sshd"])
+        ));
+        assert!(!sudo_command_matches(
+            "/usr/bin/systemctl restart nginx",
+            &cmd(&["/usr/bin/systemctl", "restart", "nginx", "sshd"])
+        ));
+
+        // "" forbids any arguments.
+        assert!(sudo_command_matches(
+            "/usr/bin/id \"\"",
+            &cmd(&["/usr/bin/id"])
+        ));
+        assert!(!sudo_command_matches(
+            "/usr/bin/id \"\"",
+            &cmd(&["/usr/bin/id", "root"])
+        ));
+
+        // Directories match their direct children only.
+        assert!(sudo_command_matches(
+            "/usr/sbin/",
+            &cmd(&["/usr/sbin/reboot"])
+        ));
+        assert!(!sudo_command_matches(
+            "/usr/sbin/",
+            &cmd(&["/usr/sbin/sub/reboot"])
+        ));
+        assert!(!sudo_command_matches("/usr/sbin/", &cmd(&["/usr/bin/id"])));
+
+        assert!(!sudo_command_matches("/usr/bin/id", &[]));
+    }
+    #[test]
+    fn test_hbac_service_matches() {
+        let mut rule = HbacRule {
+            name: "test_rule".to_string(),
+            uuid: uuid::uuid!("0302b99c-f0f6-41ab-9492-852692b0fd16"),
+            users: Vec::new(),
+            services: vec!["sshd".to_string()],
+        };
+
+        assert!(hbac_service_matches(&rule, Some("sshd")));
+        assert!(hbac_service_matches(&rule, Some("SSHD")));
+        assert!(!hbac_service_matches(&rule, Some("login")));
+        // An unknown service only matches ALL.
+        assert!(!hbac_service_matches(&rule, None));
+
+        rule.services = vec!["ALL".to_string()];
+        assert!(hbac_service_matches(&rule, Some("login")));
+        assert!(hbac_service_matches(&rule, None));
+
+        // A rule without services allows nothing.
+        rule.services = Vec::new();
+        assert!(!hbac_service_matches(&rule, Some("sshd")));
+    }
+}
@@ -109,7 +109,10 @@ async fn main() -> ExitCode {
                 }
             }
 
-            let sereq = ClientRequest::PamAccountAllowed(account_id);
+            let sereq = ClientRequest::PamAccountAllowed {
+                account_id,
+                service: None,
+            };
 
             match call_daemon(cfg.sock_path.as_str(), sereq, cfg.unix_sock_timeout).await {
                 Ok(r) => match r {
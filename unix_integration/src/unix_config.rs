@@ -35,6 +35,7 @@ struct ConfigInt {
     selinux: Option<bool>,
     #[serde(default)]
     allow_local_account_override: Vec<String>,
+    host_token_path: Option<String>,
     tpm_tcti_name: Option<String>,
     tpm_policy: Option<String>,
 }
@@ -120,6 +121,7 @@ pub struct KanidmUnixdConfig {
     pub selinux: bool,
     pub tpm_policy: TpmPolicy,
     pub allow_local_account_override: Vec<String>,
+    pub host_token_path: Option<String>,
 }
 
 impl Default for KanidmUnixdConfig {
@@ -158,7 +160,11 @@ impl Display for KanidmUnixdConfig {
             f,
             "allow_local_account_override: {:#?}",
             self.allow_local_account_override
-        )
+        )?;
+        match &self.host_token_path {
+            Some(val) => writeln!(f, "host_token_path: {}", val),
+            None => writeln!(f, "host_token_path: unset"),
+        }
     }
 }
 
@@ -186,6 +192,7 @@ impl KanidmUnixdConfig {
             selinux: DEFAULT_SELINUX,
             tpm_policy: TpmPolicy::default(),
             allow_local_account_override: Vec::default(),
+            host_token_path: None,
         }
     }
 
@@ -319,6 +326,7 @@ impl KanidmUnixdConfig {
                 })
                 .unwrap_or(self.tpm_policy),
             allow_local_account_override: config.allow_local_account_override,
+            host_token_path: config.host_token_path.or(self.host_token_path),
         })
     }
 }
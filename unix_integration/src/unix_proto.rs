@@ -16,6 +16,20 @@ pub struct NssGroup {
     pub members: Vec<String>,
 }
 
+/// A range of subordinate ids. The same range is used for both subordinate uids and gids.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NssSubidRange {
+    pub start: u32,
+    pub count: u32,
+}
+
+/// An entry of an automount map, in the form that autofs consumes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NssAutomountEntry {
+    pub key: String,
+    pub information: String,
+}
+
 /* RFC8628: 3.2. Device Authorization Response */
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DeviceAuthorizationResponse {
@@ -54,6 +68,41 @@ pub enum PamAuthRequest {
                                                                     */
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub enum PamChangeAuthTokenResponse {
+    Unknown,
+    Success,
+    Denied,
+    /// The resolver is offline, and the change can not be sent to the idp.
+    Offline,
+    /// The new password was rejected by the idp. The feedback explains why, and
+    /// should be displayed to the user.
+    PasswordQuality {
+        feedback: Vec<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum SudoCheckResponse {
+    /// The account is not known to the resolver.
+    Unknown,
+    Denied,
+    /// The command is permitted. If `nopasswd` is false, the account must authenticate
+    /// before the command is run.
+    Allowed {
+        nopasswd: bool,
+    },
+}
+
+/// The commands an account may run on this host, as shown by `sudo -l`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SudoPrivilege {
+    pub rule: String,
+    pub runas: Vec<String>,
+    pub commands: Vec<String>,
+    pub nopasswd: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ClientRequest {
     SshKey(String),
@@ -63,10 +112,34 @@ pub enum ClientRequest {
     NssGroups,
     NssGroupByGid(u32),
     NssGroupByName(String),
+    NssSubidRangesByName(String),
+    /// Find the uids of the accounts whose subordinate id range contains this id.
+    NssSubidOwners(u32),
+    NssAutomountMapByName(String),
     PamAuthenticateInit(String),
     PamAuthenticateStep(PamAuthRequest),
-    PamAccountAllowed(String),
+    /// Check if an account may access this host. `service` is the name of the PAM
+    /// service, if it is known.
+    PamAccountAllowed {
+        account_id: String,
+        service: Option<String>,
+    },
     PamAccountBeginSession(String),
+    PamChangeAuthToken {
+        account_id: String,
+        current_password: String,
+        new_password: String,
+    },
+    /// Check if an account may run a command. The first element of `command` must be
+    /// the absolute path of the command, followed by its arguments.
+    SudoCheck {
+        account_id: String,
+        runas: String,
+        command: Vec<String>,
+    },
+    SudoList {
+        account_id: String,
+    },
     InvalidateCache,
     ClearCache,
     Status,
@@ -79,9 +152,18 @@ pub enum ClientResponse {
     NssAccount(Option<NssUser>),
     NssGroups(Vec<NssGroup>),
     NssGroup(Option<NssGroup>),
+    /// The subordinate id ranges of an account, or None if the account is not known.
+    NssSubidRanges(Option<Vec<NssSubidRange>>),
+    NssSubidOwners(Vec<u32>),
+    /// The entries of an automount map, or None if the map is not known.
+    NssAutomountMap(Option<Vec<NssAutomountEntry>>),
 
     PamStatus(Option<bool>),
     PamAuthenticateStepResponse(PamAuthResponse),
+    PamChangeAuthTokenResponse(PamChangeAuthTokenResponse),
+
+    SudoCheckResponse(SudoCheckResponse),
+    SudoPrivileges(Vec<SudoPrivilege>),
 
     Ok,
     Error,
@@ -93,6 +175,18 @@ impl From<PamAuthResponse> for ClientResponse {
     }
 }
 
+impl From<PamChangeAuthTokenResponse> for ClientResponse {
+    fn from(pcr: PamChangeAuthTokenResponse) -> Self {
+        ClientResponse::PamChangeAuthTokenResponse(pcr)
+    }
+}
+
+impl From<SudoCheckResponse> for ClientResponse {
+    fn from(scr: SudoCheckResponse) -> Self {
+        ClientResponse::SudoCheckResponse(scr)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct HomeDirectoryInfo {
     pub gid: u32,
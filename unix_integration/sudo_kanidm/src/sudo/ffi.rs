@@ -0,0 +1,97 @@
+//! Types from sudo_plugin.h. Only the parts of the policy plugin interface that this
+//! plugin uses are defined. See sudo_plugin(5) for the details of each member.
+
+use libc::{c_char, c_int, c_uint, c_void};
+
+pub const SUDO_POLICY_PLUGIN: c_uint = 1;
+
+// We declare API 1.12 which is understood by all supported sudo versions. Later
+// versions only add members to the end of the plugin struct and extra arguments,
+// so sudo will not use them with us.
+pub const SUDO_API_VERSION_MAJOR: c_uint = 1;
+pub const SUDO_API_VERSION_MINOR: c_uint = 12;
+pub const SUDO_API_VERSION: c_uint = (SUDO_API_VERSION_MAJOR << 16) | SUDO_API_VERSION_MINOR;
+
+pub const SUDO_CONV_PROMPT_ECHO_OFF: c_int = 0x0001;
+pub const SUDO_CONV_ERROR_MSG: c_int = 0x0003;
+pub const SUDO_CONV_INFO_MSG: c_int = 0x0004;
+
+// Return values of the plugin functions.
+pub const SUDO_RC_ACCEPT: c_int = 1;
+pub const SUDO_RC_REJECT: c_int = 0;
+pub const SUDO_RC_ERROR: c_int = -1;
+pub const SUDO_RC_USAGE_ERROR: c_int = -2;
+
+#[repr(C)]
+pub struct SudoConvMessage {
+    pub msg_type: c_int,
+    pub timeout: c_int,
+    pub msg: *const c_char,
+}
+
+#[repr(C)]
+pub struct SudoConvReply {
+    pub reply: *mut c_char,
+}
+
+pub type SudoConvFn = unsafe extern "C" fn(
+    num_msgs: c_int,
+    msgs: *const SudoConvMessage,
+    replies: *mut SudoConvReply,
+    callback: *mut c_void,
+) -> c_int;
+
+pub type SudoPrintfFn = unsafe extern "C" fn(msg_type: c_int, fmt: *const c_char, ...) -> c_int;
+
+pub type OpenFn = unsafe extern "C" fn(
+    version: c_uint,
+    conversation: Option<SudoConvFn>,
+    sudo_printf: Option<SudoPrintfFn>,
+    settings: *const *const c_char,
+    user_info: *const *const c_char,
+    user_env: *const *const c_char,
+    plugin_options: *const *const c_char,
+) -> c_int;
+
+pub type CloseFn = unsafe extern "C" fn(exit_status: c_int, error: c_int);
+
+pub type ShowVersionFn = unsafe extern "C" fn(verbose: c_int) -> c_int;
+
+pub type CheckPolicyFn = unsafe extern "C" fn(
+    argc: c_int,
+    argv: *const *const c_char,
+    env_add: *const *const c_char,
+    command_info: *mut *const *const c_char,
+    argv_out: *mut *const *const c_char,
+    user_env_out: *mut *const *const c_char,
+) -> c_int;
+
+pub type ListFn = unsafe extern "C" fn(
+    argc: c_int,
+    argv: *const *const c_char,
+    verbose: c_int,
+    list_user: *const c_char,
+) -> c_int;
+
+pub type ValidateFn = unsafe extern "C" fn() -> c_int;
+
+pub type InvalidateFn = unsafe extern "C" fn(rmcred: c_int);
+
+pub type InitSessionFn =
+    unsafe extern "C" fn(pwd: *mut libc::passwd, user_env_out: *mut *const *const c_char) -> c_int;
+
+#[repr(C)]
+pub struct PolicyPlugin {
+    pub plugin_type: c_uint,
+    pub version: c_uint,
+    pub open: Option<OpenFn>,
+    pub close: Option<CloseFn>,
+    pub show_version: Option<ShowVersionFn>,
+    pub check_policy: Option<CheckPolicyFn>,
+    pub list: Option<ListFn>,
+    pub validate: Option<ValidateFn>,
+    pub invalidate: Option<InvalidateFn>,
+    pub init_session: Option<InitSessionFn>,
+    pub register_hooks: Option<unsafe extern "C" fn()>,
+    pub deregister_hooks: Option<unsafe extern "C" fn()>,
+}
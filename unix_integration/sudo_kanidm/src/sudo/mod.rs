@@ -0,0 +1,921 @@
+//! A sudo policy plugin that enforces the sudo rules defined in Kanidm.
+//!
+//! The plugin does not evaluate rules itself. It asks kanidm_unixd over its socket, which
+//! evaluates the rules from its cache so that sudo continues to work while offline. When
+//! a rule requires reauthentication, the user is authenticated by kanidm_unixd in the same
+//! manner as pam_kanidm.
+//!
+//! The plugin is configured in sudo.conf with:
+//!
+//! ```text
+//! Plugin kanidm_policy sudo_kanidm.so [debug]
+//! ```
+
+pub mod ffi;
+
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::Mutex;
+
+use kanidm_unix_common::client_sync::DaemonClientBlocking;
+use kanidm_unix_common::constants::DEFAULT_CONFIG_PATH;
+use kanidm_unix_common::unix_config::KanidmUnixdConfig;
+use kanidm_unix_common::unix_proto::{
+    ClientRequest, ClientResponse, PamAuthRequest, PamAuthResponse, SudoCheckResponse,
+    SudoPrivilege,
+};
+use libc::{c_char, c_int, c_uint};
+use users::os::unix::UserExt;
+
+use tracing::{debug, error};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt;
+use tracing_subscriber::prelude::*;
+
+use crate::sudo::ffi::*;
+
+/// The PATH given to commands, as user controlled paths must not be trusted.
+const SECURE_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// The environment variables that are passed through to commands.
+const ENV_KEEP: [&str; 7] = [
+    "COLORTERM",
+    "DISPLAY",
+    "LANG",
+    "LANGUAGE",
+    "TERM",
+    "TZ",
+    "XAUTHORITY",
+];
+
+/// A null terminated array of strings that is handed to sudo. It must outlive the
+/// call that returns it, so it is stored in the plugin state until close.
+#[derive(Default)]
+struct CStringArray {
+    _strings: Vec<CString>,
+    ptrs: Vec<*const c_char>,
+}
+
+// The pointers only refer to the strings that this struct owns.
+unsafe impl Send for CStringArray {}
+
+impl CStringArray {
+    fn new(strings: Vec<String>) -> Option<Self> {
+        let strings: Vec<CString> = strings
+            .into_iter()
+            .map(CString::new)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        let ptrs = strings
+            .iter()
+            .map(|s| s.as_ptr())
+            .chain(std::iter::once(ptr::null()))
+            .collect();
+        Some(CStringArray {
+            _strings: strings,
+            ptrs,
+        })
+    }
+
+    fn as_ptr(&self) -> *const *const c_char {
+        self.ptrs.as_ptr()
+    }
+}
+
+struct PluginState {
+    conversation: SudoConvFn,
+    sudo_printf: SudoPrintfFn,
+    settings: BTreeMap<String, String>,
+    user_info: BTreeMap<String, String>,
+    user_env: Vec<String>,
+    command_info: CStringArray,
+    argv_out: CStringArray,
+    env_out: CStringArray,
+}
+
+static STATE: Mutex<Option<PluginState>> = Mutex::new(None);
+
+#[no_mangle]
+#[allow(non_upper_case_globals)]
+pub static kanidm_policy: PolicyPlugin = PolicyPlugin {
+    plugin_type: SUDO_POLICY_PLUGIN,
+    version: SUDO_API_VERSION,
+    open: Some(policy_open),
+    close: Some(policy_close),
+    show_version: Some(policy_show_version),
+    check_policy: Some(policy_check),
+    list: Some(policy_list),
+    validate: Some(policy_validate),
+    invalidate: Some(policy_invalidate),
+    init_session: Some(policy_init_session),
+    register_hooks: None,
+    deregister_hooks: None,
+};
+
+fn install_subscriber(debug: bool) {
+    let fmt_layer = fmt::layer().with_target(false);
+
+    let filter_layer = if debug {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::ERROR
+    };
+
+    let _ = tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(fmt_layer)
+        .try_init();
+}
+
+fn get_cfg() -> Option<KanidmUnixdConfig> {
+    KanidmUnixdConfig::new()
+        .read_options_from_optional_config(DEFAULT_CONFIG_PATH)
+        .map_err(|_| error!("unable to read unixd configuration"))
+        .ok()
+}
+
+/// Collect a null terminated array of C strings.
+unsafe fn c_str_array(arr: *const *const c_char) -> Vec<String> {
+    let mut out = Vec::new();
+    if arr.is_null() {
+        return out;
+    }
+    let mut i = 0;
+    loop {
+        let item = *arr.add(i);
+        if item.is_null() {
+            break;
+        }
+        out.push(CStr::from_ptr(item).to_string_lossy().into_owned());
+        i += 1;
+    }
+    out
+}
+
+/// Settings and user_info are provided as key=value pairs.
+fn key_values(items: Vec<String>) -> BTreeMap<String, String> {
+    items
+        .into_iter()
+        .filter_map(|kv| {
+            kv.split_once('=')
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+impl PluginState {
+    fn conv(&self, msg_type: c_int, msg: &str) -> Option<String> {
+        let msg = CString::new(msg).ok()?;
+        let message = SudoConvMessage {
+            msg_type,
+            timeout: 0,
+            msg: msg.as_ptr(),
+        };
+        let mut reply = SudoConvReply {
+            reply: ptr::null_mut(),
+        };
+
+        let rc = unsafe { (self.conversation)(1, &message, &mut reply, ptr::null_mut()) };
+        if rc != 0 {
+            debug!("conversation failed");
+            return None;
+        }
+
+        if reply.reply.is_null() {
+            return Some(String::new());
+        }
+
+        // We own the reply, and it may contain a password, so clear it before it is freed.
+        unsafe {
+            let response = CStr::from_ptr(reply.reply).to_string_lossy().into_owned();
+            let len = libc::strlen(reply.reply);
+            ptr::write_bytes(reply.reply, 0, len);
+            libc::free(reply.reply as *mut libc::c_void);
+            Some(response)
+        }
+    }
+
+    fn print(&self, msg_type: c_int, msg: &str) {
+        if let Ok(msg) = CString::new(msg) {
+            unsafe {
+                (self.sudo_printf)(msg_type, b"%s\0".as_ptr() as *const c_char, msg.as_ptr());
+            }
+        }
+    }
+
+    fn error(&self, msg: &str) {
+        self.print(SUDO_CONV_ERROR_MSG, &format!("sudo: {}\n", msg));
+    }
+
+    fn user(&self) -> Option<&str> {
+        self.user_info.get("user").map(String::as_str)
+    }
+
+    fn uid(&self) -> Option<u32> {
+        self.user_info.get("uid").and_then(|u| u.parse().ok())
+    }
+
+    fn host(&self) -> &str {
+        self.user_info
+            .get("host")
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    fn runas_user(&self) -> &str {
+        self.settings
+            .get("runas_user")
+            .map(String::as_str)
+            .unwrap_or("root")
+    }
+
+    fn user_env_get(&self, key: &str) -> Option<&str> {
+        self.user_env.iter().find_map(|kv| {
+            kv.split_once('=')
+                .and_then(|(k, v)| if k == key { Some(v) } else { None })
+        })
+    }
+
+    fn daemon_call(&self, req: &ClientRequest) -> Option<ClientResponse> {
+        let cfg = get_cfg()?;
+        let mut daemon_client = DaemonClientBlocking::new(cfg.sock_path.as_str())
+            .map_err(|e| error!(err = ?e, "Error DaemonClientBlocking::new()"))
+            .ok()?;
+        daemon_client
+            .call_and_wait(req, cfg.unix_sock_timeout)
+            .map_err(|err| error!(?err, "unixd call failed"))
+            .ok()
+    }
+
+    /// Authenticate the invoking user with kanidm_unixd. This follows the same steps
+    /// as pam_kanidm.
+    fn authenticate(&self, account_id: &str) -> bool {
+        let cfg = match get_cfg() {
+            Some(cfg) => cfg,
+            None => return false,
+        };
+
+        let mut daemon_client = match DaemonClientBlocking::new(cfg.sock_path.as_str()) {
+            Ok(dc) => dc,
+            Err(e) => {
+                error!(err = ?e, "Error DaemonClientBlocking::new()");
+                return false;
+            }
+        };
+
+        let mut timeout = cfg.unix_sock_timeout;
+        let mut req = ClientRequest::PamAuthenticateInit(account_id.to_string());
+
+        loop {
+            match daemon_client.call_and_wait(&req, timeout) {
+                Ok(ClientResponse::PamAuthenticateStepResponse(PamAuthResponse::Success)) => {
+                    return true;
+                }
+                Ok(ClientResponse::PamAuthenticateStepResponse(PamAuthResponse::Password)) => {
+                    let prompt = format!("[sudo] password for {}: ", account_id);
+                    let cred = match self.conv(SUDO_CONV_PROMPT_ECHO_OFF, &prompt) {
+                        Some(cred) => cred,
+                        None => return false,
+                    };
+                    timeout = cfg.unix_sock_timeout;
+                    req = ClientRequest::PamAuthenticateStep(PamAuthRequest::Password { cred });
+                }
+                Ok(ClientResponse::PamAuthenticateStepResponse(
+                    PamAuthResponse::DeviceAuthorizationGrant { data },
+                )) => {
+                    let msg = match &data.message {
+                        Some(msg) => msg.clone(),
+                        None => format!(
+                            "Using a browser on another device, visit:\n{}\nAnd enter the code:\n{}",
+                            data.verification_uri, data.user_code
+                        ),
+                    };
+                    self.print(SUDO_CONV_INFO_MSG, &format!("{}\n", msg));
+                    timeout = u64::from(data.expires_in);
+                    req = ClientRequest::PamAuthenticateStep(
+                        PamAuthRequest::DeviceAuthorizationGrant { data },
+                    );
+                }
+                Ok(ClientResponse::PamAuthenticateStepResponse(PamAuthResponse::Denied)) => {
+                    self.error("Sorry, try again.");
+                    return false;
+                }
+                Ok(r) => {
+                    error!(err = ?r, "unexpected resolver response");
+                    return false;
+                }
+                Err(err) => {
+                    error!(?err, "unixd call failed");
+                    return false;
+                }
+            }
+        }
+    }
+
+    fn sudo_check(&self, account_id: &str, command: &[String]) -> Option<SudoCheckResponse> {
+        let req = ClientRequest::SudoCheck {
+            account_id: account_id.to_string(),
+            runas: self.runas_user().to_string(),
+            command: command.to_vec(),
+        };
+        match self.daemon_call(&req) {
+            Some(ClientResponse::SudoCheckResponse(r)) => Some(r),
+            r => {
+                error!(err = ?r, "unexpected resolver response");
+                None
+            }
+        }
+    }
+
+    /// Find the command in the same manner as a shell would, returning its real path.
+    fn resolve_command(&self, cmd: &str) -> Option<PathBuf> {
+        let candidate = |p: PathBuf| -> Option<PathBuf> {
+            // The real path is matched against the rules, so that neither symbolic links
+            // nor relative components can make a command appear to be within a directory
+            // that a rule permits.
+            let p = std::fs::canonicalize(p).ok()?;
+            let executable = std::fs::metadata(&p)
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false);
+            if executable {
+                Some(p)
+            } else {
+                None
+            }
+        };
+
+        if cmd.contains('/') {
+            let p = Path::new(cmd);
+            if p.is_absolute() {
+                candidate(p.to_path_buf())
+            } else {
+                let cwd = self.user_info.get("cwd")?;
+                candidate(Path::new(cwd).join(p))
+            }
+        } else {
+            self.user_env_get("PATH")
+                .unwrap_or(SECURE_PATH)
+                .split(':')
+                .filter(|dir| dir.starts_with('/'))
+                .find_map(|dir| candidate(Path::new(dir).join(cmd)))
+        }
+    }
+
+    fn build_env(&self, runas: &users::User, command: &[String]) -> Vec<String> {
+        let mut env: Vec<String> = self
+            .user_env
+            .iter()
+            .filter(|kv| {
+                kv.split_once('=').map_or(false, |(k, _)| {
+                    ENV_KEEP.contains(&k) || k.starts_with("LC_")
+                })
+            })
+            .cloned()
+            .collect();
+
+        let name = runas.name().to_string_lossy();
+        env.push(format!("PATH={}", SECURE_PATH));
+        env.push(format!("HOME={}", runas.home_dir().display()));
+        env.push(format!("SHELL={}", runas.shell().display()));
+        env.push(format!("USER={}", name));
+        env.push(format!("LOGNAME={}", name));
+        env.push(format!("SUDO_COMMAND={}", command.join(" ")));
+        if let Some(user) = self.user() {
+            env.push(format!("SUDO_USER={}", user));
+        }
+        if let Some(uid) = self.user_info.get("uid") {
+            env.push(format!("SUDO_UID={}", uid));
+        }
+        if let Some(gid) = self.user_info.get("gid") {
+            env.push(format!("SUDO_GID={}", gid));
+        }
+        env
+    }
+}
+
+fn with_state<F>(f: F) -> c_int
+where
+    F: FnOnce(&mut PluginState) -> c_int,
+{
+    match STATE.lock() {
+        Ok(mut guard) => match guard.as_mut() {
+            Some(state) => f(state),
+            None => {
+                error!("plugin was not opened");
+                SUDO_RC_ERROR
+            }
+        },
+        Err(_) => SUDO_RC_ERROR,
+    }
+}
+
+unsafe extern "C" fn policy_open(
+    version: c_uint,
+    conversation: Option<SudoConvFn>,
+    sudo_printf: Option<SudoPrintfFn>,
+    settings: *const *const c_char,
+    user_info: *const *const c_char,
+    user_env: *const *const c_char,
+    plugin_options: *const *const c_char,
+) -> c_int {
+    let plugin_options = c_str_array(plugin_options);
+    install_subscriber(plugin_options.iter().any(|o| o == "debug"));
+
+    let (Some(conversation), Some(sudo_printf)) = (conversation, sudo_printf) else {
+        return SUDO_RC_ERROR;
+    };
+
+    if version >> 16 != SUDO_API_VERSION_MAJOR {
+        sudo_printf(
+            SUDO_CONV_ERROR_MSG,
+            b"sudo_kanidm: incompatible major version of the sudo plugin API\n\0".as_ptr()
+                as *const c_char,
+        );
+        return SUDO_RC_ERROR;
+    }
+
+    let state = PluginState {
+        conversation,
+        sudo_printf,
+        settings: key_values(c_str_array(settings)),
+        user_info: key_values(c_str_array(user_info)),
+        user_env: c_str_array(user_env),
+        command_info: CStringArray::default(),
+        argv_out: CStringArray::default(),
+        env_out: CStringArray::default(),
+    };
+
+    debug!(settings = ?state.settings, user_info = ?state.user_info, "open");
+
+    match STATE.lock() {
+        Ok(mut guard) => {
+            *guard = Some(state);
+            SUDO_RC_ACCEPT
+        }
+        Err(_) => SUDO_RC_ERROR,
+    }
+}
+
+unsafe extern "C" fn policy_close(_exit_status: c_int, _error: c_int) {
+    if let Ok(mut guard) = STATE.lock() {
+        *guard = None;
+    }
+}
+
+unsafe extern "C" fn policy_show_version(_verbose: c_int) -> c_int {
+    with_state(|state| {
+        state.print(
+            SUDO_CONV_INFO_MSG,
+            &format!(
+                "Kanidm sudo policy plugin version {}\n",
+                env!("CARGO_PKG_VERSION")
+            ),
+        );
+        SUDO_RC_ACCEPT
+    })
+}
+
+unsafe extern "C" fn policy_check(
+    argc: c_int,
+    argv: *const *const c_char,
+    env_add: *const *const c_char,
+    command_info: *mut *const *const c_char,
+    argv_out: *mut *const *const c_char,
+    user_env_out: *mut *const *const c_char,
+) -> c_int {
+    let argv = c_str_array(argv);
+    let env_add = c_str_array(env_add);
+
+    with_state(|state| {
+        if argc <= 0 || argv.is_empty() {
+            state.error("no command specified");
+            return SUDO_RC_USAGE_ERROR;
+        }
+
+        if state
+            .settings
+            .get("sudoedit")
+            .map_or(false, |v| v == "true")
+        {
+            state.error("sudoedit is not supported by the kanidm policy");
+            return SUDO_RC_REJECT;
+        }
+
+        if state.settings.contains_key("runas_group") {
+            state.error("running commands as a group is not supported by the kanidm policy");
+            return SUDO_RC_REJECT;
+        }
+
+        if !env_add.is_empty() {
+            state.error("setting environment variables is not permitted by the kanidm policy");
+            return SUDO_RC_REJECT;
+        }
+
+        let (Some(user), Some(uid)) = (state.user().map(str::to_string), state.uid()) else {
+            state.error("unable to determine the invoking user");
+            return SUDO_RC_ERROR;
+        };
+
+        let Some(path) = state.resolve_command(&argv[0]) else {
+            state.error(&format!("{}: command not found", argv[0]));
+            return SUDO_RC_REJECT;
+        };
+        let path = path.to_string_lossy().into_owned();
+
+        let mut command = vec![path.clone()];
+        command.extend(argv.iter().skip(1).cloned());
+
+        let runas_name = state.runas_user().to_string();
+        let Some(runas) = users::get_user_by_name(&runas_name) else {
+            state.error(&format!("unknown user {}", runas_name));
+            return SUDO_RC_REJECT;
+        };
+
+        // Root is always permitted, as with the default sudoers policy.
+        if uid != 0 {
+            match state.sudo_check(&user, &command) {
+                Some(SudoCheckResponse::Allowed { nopasswd }) => {
+                    if !nopasswd && !state.authenticate(&user) {
+                        return SUDO_RC_REJECT;
+                    }
+                }
+                Some(SudoCheckResponse::Denied) | Some(SudoCheckResponse::Unknown) => {
+                    state.error(&format!(
+                        "Sorry, user {} is not allowed to execute '{}' as {} on {}.",
+                        user,
+                        command.join(" "),
+                        runas_name,
+                        state.host()
+                    ));
+                    return SUDO_RC_REJECT;
+                }
+                None => {
+                    state.error("unable to contact kanidm_unixd");
+                    return SUDO_RC_ERROR;
+                }
+            }
+        }
+
+        let info = vec![
+            format!("command={}", path),
+            format!("runas_uid={}", runas.uid()),
+            format!("runas_gid={}", runas.primary_group_id()),
+        ];
+        let env = state.build_env(&runas, &command);
+
+        let (Some(info), Some(args), Some(env)) = (
+            CStringArray::new(info),
+            CStringArray::new(argv.clone()),
+            CStringArray::new(env),
+        ) else {
+            return SUDO_RC_ERROR;
+        };
+
+        state.command_info = info;
+        state.argv_out = args;
+        state.env_out = env;
+
+        *command_info = state.command_info.as_ptr();
+        *argv_out = state.argv_out.as_ptr();
+        *user_env_out = state.env_out.as_ptr();
+
+        SUDO_RC_ACCEPT
+    })
+}
+
+fn format_privilege(p: &SudoPrivilege) -> String {
+    let tag = if p.nopasswd { "NOPASSWD: " } else { "" };
+    format!(
+        "    ({}) {}{}\n",
+        p.runas.join(", "),
+        tag,
+        p.commands.join(", ")
+    )
+}
+
+unsafe extern "C" fn policy_list(
+    argc: c_int,
+    argv: *const *const c_char,
+    _verbose: c_int,
+    list_user: *const c_char,
+) -> c_int {
+    let argv = c_str_array(argv);
+    let list_user = if list_user.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(list_user).to_string_lossy().into_owned())
+    };
+
+    with_state(|state| {
+        let (Some(user), Some(uid)) = (state.user().map(str::to_string), state.uid()) else {
+            state.error("unable to determine the invoking user");
+            return SUDO_RC_ERROR;
+        };
+
+        // Only root may ask about the privileges of other users on this host.
+        let account_id = match list_user {
+            Some(list_user) if list_user != user && uid != 0 => {
+                state.error("only root may list the privileges of other users");
+                return SUDO_RC_REJECT;
+            }
+            Some(list_user) => list_user,
+            None => user,
+        };
+
+        if argc > 0 && !argv.is_empty() {
+            // Check a single command, displaying the full path if it is permitted.
+            let Some(path) = state.resolve_command(&argv[0]) else {
+                return SUDO_RC_REJECT;
+            };
+            let path = path.to_string_lossy().into_owned();
+            let mut command = vec![path];
+            command.extend(argv.iter().skip(1).cloned());
+
+            return match state.sudo_check(&account_id, &command) {
+                Some(SudoCheckResponse::Allowed { .. }) => {
+                    state.print(SUDO_CONV_INFO_MSG, &format!("{}\n", command.join(" ")));
+                    SUDO_RC_ACCEPT
+                }
+                _ => SUDO_RC_REJECT,
+            };
+        }
+
+        let req = ClientRequest::SudoList {
+            account_id: account_id.clone(),
+        };
+
+        match state.daemon_call(&req) {
+            Some(ClientResponse::SudoPrivileges(privileges)) => {
+                if privileges.is_empty() {
+                    state.print(
+                        SUDO_CONV_INFO_MSG,
+                        &format!(
+                            "User {} is not allowed to run sudo on {}.\n",
+                            account_id,
+                            state.host()
+                        ),
+                    );
+                } else {
+                    state.print(
+                        SUDO_CONV_INFO_MSG,
+                        &format!(
+                            "User {} may run the following commands on {}:\n",
+                            account_id,
+                            state.host()
+                        ),
+                    );
+                    privileges
+                        .iter()
+                        .for_each(|p| state.print(SUDO_CONV_INFO_MSG, &format_privilege(p)));
+                }
+                SUDO_RC_ACCEPT
+            }
+            r => {
+                error!(err = ?r, "unexpected resolver response");
+                state.error("unable to contact kanidm_unixd");
+                SUDO_RC_ERROR
+            }
+        }
+    })
+}
+
+unsafe extern "C" fn policy_validate() -> c_int {
+    with_state(|state| {
+        let (Some(user), Some(uid)) = (state.user().map(str::to_string), state.uid()) else {
+            return SUDO_RC_ERROR;
+        };
+
+        if uid == 0 || state.authenticate(&user) {
+            SUDO_RC_ACCEPT
+        } else {
+            SUDO_RC_REJECT
+        }
+    })
+}
+
+unsafe extern "C" fn policy_invalidate(_rmcred: c_int) {
+    // Authentication is not cached between invocations, so there is nothing to remove.
+}
+
+unsafe extern "C" fn policy_init_session(
+    _pwd: *mut libc::passwd,
+    _user_env_out: *mut *const *const c_char,
+) -> c_int {
+    SUDO_RC_ACCEPT
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    use libc::c_void;
+
+    use super::*;
+
+    static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    unsafe extern "C" fn test_conversation(
+        _num_msgs: c_int,
+        _msgs: *const SudoConvMessage,
+        _replies: *mut SudoConvReply,
+        _callback: *mut c_void,
+    ) -> c_int {
+        -1
+    }
+
+    // The plugin only calls sudo_printf with "%s" and a single string.
+    unsafe extern "C" fn test_printf(
+        _msg_type: c_int,
+        _fmt: *const c_char,
+        msg: *const c_char,
+    ) -> c_int {
+        if let Ok(mut messages) = MESSAGES.lock() {
+            messages.push(CStr::from_ptr(msg).to_string_lossy().into_owned());
+        }
+        0
+    }
+
+    fn test_printf_fn() -> SudoPrintfFn {
+        // Variadic functions can't be defined in stable rust, but the fixed arguments are
+        // passed in the same manner on the platforms that this plugin supports.
+        unsafe {
+            std::mem::transmute::<
+                unsafe extern "C" fn(c_int, *const c_char, *const c_char) -> c_int,
+                SudoPrintfFn,
+            >(test_printf)
+        }
+    }
+
+    fn test_state(user_info: &[&str], user_env: &[&str]) -> PluginState {
+        PluginState {
+            conversation: test_conversation,
+            sudo_printf: test_printf_fn(),
+            settings: BTreeMap::new(),
+            user_info: key_values(user_info.iter().map(|s| s.to_string()).collect()),
+            user_env: user_env.iter().map(|s| s.to_string()).collect(),
+            command_info: CStringArray::default(),
+            argv_out: CStringArray::default(),
+            env_out: CStringArray::default(),
+        }
+    }
+
+    fn c_array(items: &[&str]) -> CStringArray {
+        CStringArray::new(items.iter().map(|s| s.to_string()).collect())
+            .expect("invalid test string")
+    }
+
+    fn write_command(path: &Path, mode: u32) {
+        fs::write(path, "#!/bin/sh\n").expect("failed to write command");
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+            .expect("failed to set permissions");
+    }
+
+    /// Open the plugin in the same manner as sudo, and check a command. Returns the result
+    /// and the command info that is given to sudo.
+    unsafe fn check(
+        settings: &[&str],
+        user_info: &[&str],
+        argv: &[&str],
+        env_add: &[&str],
+    ) -> (c_int, Vec<String>) {
+        let settings = c_array(settings);
+        let user_info = c_array(user_info);
+        let user_env = c_array(&["PATH=/usr/bin:/bin"]);
+        let plugin_options = c_array(&[]);
+
+        let rc = policy_open(
+            SUDO_API_VERSION,
+            Some(test_conversation),
+            Some(test_printf_fn()),
+            settings.as_ptr(),
+            user_info.as_ptr(),
+            user_env.as_ptr(),
+            plugin_options.as_ptr(),
+        );
+        assert_eq!(rc, SUDO_RC_ACCEPT);
+
+        let argv_in = c_array(argv);
+        let env_add = c_array(env_add);
+        let mut command_info = ptr::null();
+        let mut argv_out = ptr::null();
+        let mut user_env_out = ptr::null();
+
+        let rc = policy_check(
+            argv.len() as c_int,
+            argv_in.as_ptr(),
+            env_add.as_ptr(),
+            &mut command_info,
+            &mut argv_out,
+            &mut user_env_out,
+        );
+
+        // The command info is owned by the plugin, so it must be read before close.
+        let info = if rc == SUDO_RC_ACCEPT {
+            c_str_array(command_info)
+        } else {
+            Vec::new()
+        };
+
+        policy_close(0, 0);
+        (rc, info)
+    }
+
+    #[test]
+    fn test_resolve_command() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        // The tempdir may itself be reached through a symbolic link.
+        let root = fs::canonicalize(dir.path()).expect("failed to resolve tempdir");
+        let bin = root.join("bin");
+        let sbin = root.join("sbin");
+        fs::create_dir(&bin).expect("failed to create bin");
+        fs::create_dir(&sbin).expect("failed to create sbin");
+
+        let tool = bin.join("tool");
+        write_command(&tool, 0o755);
+        write_command(&bin.join("data"), 0o644);
+        symlink(&tool, sbin.join("link")).expect("failed to create symlink");
+
+        let cwd = format!("cwd={}", root.display());
+        let path = format!("PATH=relative:{}:{}", sbin.display(), bin.display());
+        let state = test_state(&[cwd.as_str()], &[path.as_str()]);
+
+        assert_eq!(
+            state.resolve_command(&tool.to_string_lossy()),
+            Some(tool.clone())
+        );
+
+        // Symbolic links and relative components are resolved to the real path, which
+        // is what the rules are matched against.
+        assert_eq!(
+            state.resolve_command(&sbin.join("link").to_string_lossy()),
+            Some(tool.clone())
+        );
+        assert_eq!(
+            state.resolve_command(&format!("{}/../bin/tool", sbin.display())),
+            Some(tool.clone())
+        );
+
+        // Paths are relative to the working directory.
+        assert_eq!(state.resolve_command("bin/tool"), Some(tool.clone()));
+        assert_eq!(state.resolve_command("./sbin/link"), Some(tool.clone()));
+
+        // Names are found through the PATH, ignoring directories that are not absolute.
+        assert_eq!(state.resolve_command("link"), Some(tool.clone()));
+        assert_eq!(state.resolve_command("tool"), Some(tool));
+
+        // Files that are not executable, directories and missing commands are not found.
+        assert_eq!(state.resolve_command("data"), None);
+        assert_eq!(state.resolve_command(&bin.to_string_lossy()), None);
+        assert_eq!(state.resolve_command("missing"), None);
+    }
+
+    #[test]
+    fn test_policy_check() {
+        let root = ["user=root", "uid=0", "cwd=/"];
+        let sh = fs::canonicalize("/bin/sh").expect("failed to resolve /bin/sh");
+
+        // Root is permitted without asking kanidm_unixd. The command is run by its
+        // real path, as root.
+        let (rc, info) = unsafe { check(&[], &root, &["sh", "-c", "true"], &[]) };
+        assert_eq!(rc, SUDO_RC_ACCEPT);
+        assert!(info.contains(&format!("command={}", sh.display())));
+        assert!(info.contains(&"runas_uid=0".to_string()));
+
+        let (rc, _) = unsafe { check(&[], &root, &[], &[]) };
+        assert_eq!(rc, SUDO_RC_USAGE_ERROR);
+
+        // Features of sudo that the policy does not support are rejected.
+        let (rc, _) = unsafe { check(&["sudoedit=true"], &root, &["sh"], &[]) };
+        assert_eq!(rc, SUDO_RC_REJECT);
+
+        let (rc, _) = unsafe { check(&["runas_group=root"], &root, &["sh"], &[]) };
+        assert_eq!(rc, SUDO_RC_REJECT);
+
+        let (rc, _) = unsafe { check(&[], &root, &["sh"], &["FOO=bar"]) };
+        assert_eq!(rc, SUDO_RC_REJECT);
+
+        let (rc, _) = unsafe { check(&["runas_user=no_such_user"], &root, &["sh"], &[]) };
+        assert_eq!(rc, SUDO_RC_REJECT);
+
+        if let Ok(mut messages) = MESSAGES.lock() {
+            messages.clear();
+        }
+        let (rc, _) = unsafe { check(&[], &root, &["no_such_command"], &[]) };
+        assert_eq!(rc, SUDO_RC_REJECT);
+        assert!(MESSAGES.lock().map_or(false, |messages| messages
+            .contains(&"sudo: no_such_command: command not found\n".to_string())));
+
+        // The invoking user must be known.
+        let (rc, _) = unsafe { check(&[], &["cwd=/"], &["sh"], &[]) };
+        assert_eq!(rc, SUDO_RC_ERROR);
+
+        // Any other user must be permitted by kanidm_unixd, which is not running.
+        let (rc, info) = unsafe { check(&[], &["user=test", "uid=1000", "cwd=/"], &["sh"], &[]) };
+        assert_ne!(rc, SUDO_RC_ACCEPT);
+        assert!(info.is_empty());
+    }
+}
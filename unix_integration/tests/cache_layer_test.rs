@@ -5,7 +5,10 @@ use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use kanidm_client::{KanidmClient, KanidmClientBuilder};
-use kanidm_proto::constants::ATTR_ACCOUNT_EXPIRE;
+use kanidm_proto::constants::{
+    ATTR_ACCOUNT_EXPIRE, ATTR_HBAC_HOST, ATTR_HBAC_SERVICE, ATTR_HBAC_USER, ATTR_SUDO_COMMAND,
+    ATTR_SUDO_HOST, ATTR_SUDO_USER,
+};
 use kanidm_unix_common::constants::{
     DEFAULT_GID_ATTR_MAP, DEFAULT_HOME_ALIAS, DEFAULT_HOME_ATTR, DEFAULT_HOME_PREFIX,
     DEFAULT_SHELL, DEFAULT_UID_ATTR_MAP,
@@ -15,6 +18,9 @@ use kanidm_unix_common::idprovider::interface::Id;
 use kanidm_unix_common::idprovider::kanidm::KanidmProvider;
 use kanidm_unix_common::resolver::Resolver;
 use kanidm_unix_common::unix_config::TpmPolicy;
+use kanidm_unix_common::unix_proto::{
+    NssAutomountEntry, PamChangeAuthTokenResponse, SudoCheckResponse,
+};
 use kanidmd_core::config::{Configuration, IntegrationTestConfig, ServerRole};
 use kanidmd_core::create_server_core;
 use kanidmd_testkit::{is_free_port, PORT_ALLOC};
@@ -95,7 +101,7 @@ async fn setup_test(fix_fn: Fixture) -> (Resolver<KanidmProvider>, KanidmClient)
         .build()
         .expect("Failed to build client");
 
-    let idprovider = KanidmProvider::new(rsclient);
+    let idprovider = KanidmProvider::new(rsclient, None);
 
     let db = Db::new(
         "", // The sqlite db path, this is in memory.
@@ -115,6 +121,7 @@ async fn setup_test(fix_fn: Fixture) -> (Resolver<KanidmProvider>, KanidmClient)
         DEFAULT_UID_ATTR_MAP,
         DEFAULT_GID_ATTR_MAP,
         vec!["masked_group".to_string()],
+        false,
     )
     .await
     .expect("Failed to build cache layer.");
@@ -125,6 +132,48 @@ async fn setup_test(fix_fn: Fixture) -> (Resolver<KanidmProvider>, KanidmClient)
     // let the tables hit the floor
 }
 
+/// Register a host and build a resolver that authenticates as it. No login groups are
+/// allowed, so that only the rules that apply on the host are enforced. The admin client
+/// must already be authenticated.
+async fn setup_host_resolver(adminclient: &KanidmClient, host: &str) -> Resolver<KanidmProvider> {
+    adminclient.idm_host_create(host, host).await.unwrap();
+    let host_token = adminclient
+        .idm_service_account_generate_api_token(host, "unixd", None, false)
+        .await
+        .unwrap();
+
+    let rsclient = KanidmClientBuilder::new()
+        .address(adminclient.get_url().to_string())
+        .no_proxy()
+        .build()
+        .expect("Failed to build client");
+
+    let idprovider = KanidmProvider::new(rsclient, Some(host_token));
+
+    let db = Db::new("", &TpmPolicy::default()).expect("Failed to setup DB");
+
+    let cachelayer = Resolver::new(
+        db,
+        idprovider,
+        300,
+        Vec::new(),
+        DEFAULT_SHELL.to_string(),
+        DEFAULT_HOME_PREFIX.to_string(),
+        DEFAULT_HOME_ATTR,
+        DEFAULT_HOME_ALIAS,
+        DEFAULT_UID_ATTR_MAP,
+        DEFAULT_GID_ATTR_MAP,
+        Vec::new(),
+        true,
+    )
+    .await
+    .expect("Failed to build cache layer.");
+
+    cachelayer.attempt_online().await;
+    assert!(cachelayer.test_connection().await);
+    cachelayer
+}
+
 /// This is the test fixture. It sets up the following:
 /// - adds admin to idm_admins
 /// - creates a test account (testaccount1)
@@ -502,6 +551,73 @@ async fn test_cache_account_password() {
     assert!(a8 == Some(true));
 }
 
+#[tokio::test]
+async fn test_cache_account_password_change() {
+    let (cachelayer, _adminclient) = setup_test(fixture(test_fixture)).await;
+    cachelayer.attempt_online().await;
+
+    // The change is authenticated by the current unix password, the account has no primary
+    // password. A weak password is rejected with feedback.
+    let c1 = cachelayer
+        .pam_account_change_authtok("testaccount1", TESTACCOUNT1_PASSWORD_A, "password")
+        .await
+        .expect("failed to change password");
+    assert!(matches!(
+        c1,
+        PamChangeAuthTokenResponse::PasswordQuality { feedback } if !feedback.is_empty()
+    ));
+
+    // The change is denied without the current password.
+    let c2 = cachelayer
+        .pam_account_change_authtok(
+            "testaccount1",
+            TESTACCOUNT1_PASSWORD_INC,
+            TESTACCOUNT1_PASSWORD_B,
+        )
+        .await
+        .expect("failed to change password");
+    assert!(matches!(c2, PamChangeAuthTokenResponse::Denied));
+
+    // We have to wait due to softlocking.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let c3 = cachelayer
+        .pam_account_change_authtok(
+            "testaccount1",
+            TESTACCOUNT1_PASSWORD_A,
+            TESTACCOUNT1_PASSWORD_B,
+        )
+        .await
+        .expect("failed to change password");
+    assert!(matches!(c3, PamChangeAuthTokenResponse::Success));
+
+    // Go offline, the cached hash must already be the new password.
+    cachelayer.mark_offline().await;
+
+    let a1 = cachelayer
+        .pam_account_authenticate("testaccount1", TESTACCOUNT1_PASSWORD_B)
+        .await
+        .expect("failed to authenticate");
+    assert!(a1 == Some(true));
+
+    let a2 = cachelayer
+        .pam_account_authenticate("testaccount1", TESTACCOUNT1_PASSWORD_A)
+        .await
+        .expect("failed to authenticate");
+    assert!(a2 == Some(false));
+
+    // Changes can't be made while offline.
+    let c4 = cachelayer
+        .pam_account_change_authtok(
+            "testaccount1",
+            TESTACCOUNT1_PASSWORD_B,
+            TESTACCOUNT1_PASSWORD_A,
+        )
+        .await
+        .expect("failed to change password");
+    assert!(matches!(c4, PamChangeAuthTokenResponse::Offline));
+}
+
 #[tokio::test]
 async fn test_cache_account_pam_allowed() {
     let (cachelayer, adminclient) = setup_test(fixture(test_fixture)).await;
@@ -509,7 +625,7 @@ async fn test_cache_account_pam_allowed() {
 
     // Should fail
     let a1 = cachelayer
-        .pam_account_allowed("testaccount1")
+        .pam_account_allowed("testaccount1", None)
         .await
         .expect("failed to authenticate");
     assert!(a1 == Some(false));
@@ -528,19 +644,329 @@ async fn test_cache_account_pam_allowed() {
 
     // Should pass
     let a2 = cachelayer
-        .pam_account_allowed("testaccount1")
+        .pam_account_allowed("testaccount1", None)
         .await
         .expect("failed to authenticate");
     assert!(a2 == Some(true));
 }
 
+#[tokio::test]
+async fn test_cache_sudo_rules() {
+    let (anon_cachelayer, adminclient) = setup_test(fixture(test_fixture)).await;
+
+    adminclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await
+        .expect("failed to auth as admin");
+
+    let cachelayer = setup_host_resolver(&adminclient, "testhost1").await;
+    adminclient
+        .idm_host_create("testhost2", "testhost2")
+        .await
+        .unwrap();
+
+    let id_cmd = vec!["/usr/bin/id".to_string()];
+
+    // No rules exist yet.
+    let r1 = cachelayer
+        .sudo_check("testaccount1", "root", &id_cmd)
+        .await
+        .expect("failed to check sudo");
+    assert_eq!(r1, SudoCheckResponse::Denied);
+
+    adminclient
+        .idm_sudo_rule_create("test_sudo_rule")
+        .await
+        .unwrap();
+    adminclient
+        .idm_sudo_rule_add_attr("test_sudo_rule", ATTR_SUDO_USER, &["testgroup1"])
+        .await
+        .unwrap();
+    adminclient
+        .idm_sudo_rule_add_attr("test_sudo_rule", ATTR_SUDO_HOST, &["testhost1"])
+        .await
+        .unwrap();
+    adminclient
+        .idm_sudo_rule_add_attr("test_sudo_rule", ATTR_SUDO_COMMAND, &["/usr/bin/id"])
+        .await
+        .unwrap();
+
+    // A rule that applies on another host is never provided to this one.
+    adminclient
+        .idm_sudo_rule_create("test_sudo_rule_other")
+        .await
+        .unwrap();
+    adminclient
+        .idm_sudo_rule_add_attr("test_sudo_rule_other", ATTR_SUDO_USER, &["testgroup1"])
+        .await
+        .unwrap();
+    adminclient
+        .idm_sudo_rule_add_attr("test_sudo_rule_other", ATTR_SUDO_HOST, &["testhost2"])
+        .await
+        .unwrap();
+    adminclient
+        .idm_sudo_rule_add_attr("test_sudo_rule_other", ATTR_SUDO_COMMAND, &["ALL"])
+        .await
+        .unwrap();
+
+    // Invalidate cache to force a refresh
+    assert!(cachelayer.invalidate().await.is_ok());
+
+    let r2 = cachelayer
+        .sudo_check("testaccount1", "root", &id_cmd)
+        .await
+        .expect("failed to check sudo");
+    assert_eq!(r2, SudoCheckResponse::Allowed { nopasswd: false });
+
+    // Commands and runas accounts not in the rule are denied.
+    let r3 = cachelayer
+        .sudo_check("testaccount1", "root", &["/usr/bin/bash".to_string()])
+        .await
+        .expect("failed to check sudo");
+    assert_eq!(r3, SudoCheckResponse::Denied);
+
+    let r4 = cachelayer
+        .sudo_check("testaccount1", "nobody", &id_cmd)
+        .await
+        .expect("failed to check sudo");
+    assert_eq!(r4, SudoCheckResponse::Denied);
+
+    // Unknown accounts are reported as such.
+    let r5 = cachelayer
+        .sudo_check("nonexist", "root", &id_cmd)
+        .await
+        .expect("failed to check sudo");
+    assert_eq!(r5, SudoCheckResponse::Unknown);
+
+    // Without a host identity, no rules apply.
+    anon_cachelayer.attempt_online().await;
+    let r6 = anon_cachelayer
+        .sudo_check("testaccount1", "root", &id_cmd)
+        .await
+        .expect("failed to check sudo");
+    assert_eq!(r6, SudoCheckResponse::Denied);
+
+    // The rules are still enforced from the cache while offline.
+    cachelayer.mark_offline().await;
+
+    let r7 = cachelayer
+        .sudo_check("testaccount1", "root", &id_cmd)
+        .await
+        .expect("failed to check sudo");
+    assert_eq!(r7, SudoCheckResponse::Allowed { nopasswd: false });
+
+    let privileges = cachelayer
+        .sudo_list("testaccount1")
+        .await
+        .expect("failed to list sudo privileges")
+        .expect("account not found");
+    assert_eq!(privileges.len(), 1);
+    assert_eq!(privileges[0].rule, "test_sudo_rule");
+    assert_eq!(privileges[0].commands, id_cmd);
+}
+
+#[tokio::test]
+async fn test_cache_hbac_rules() {
+    let (_cachelayer, adminclient) = setup_test(fixture(test_fixture)).await;
+
+    adminclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await
+        .expect("failed to auth as admin");
+
+    let cachelayer = setup_host_resolver(&adminclient, "testhost1").await;
+
+    adminclient
+        .idm_hbac_rule_create("test_hbac_rule")
+        .await
+        .unwrap();
+    adminclient
+        .idm_hbac_rule_add_attr("test_hbac_rule", ATTR_HBAC_USER, &["testgroup1"])
+        .await
+        .unwrap();
+    adminclient
+        .idm_hbac_rule_add_attr("test_hbac_rule", ATTR_HBAC_HOST, &["testhost1"])
+        .await
+        .unwrap();
+    adminclient
+        .idm_hbac_rule_add_attr("test_hbac_rule", ATTR_HBAC_SERVICE, &["sshd"])
+        .await
+        .unwrap();
+
+    let a1 = cachelayer
+        .pam_account_allowed("testaccount1", Some("sshd"))
+        .await
+        .expect("failed to check account");
+    assert_eq!(a1, Some(true));
+
+    // Services that are not named by the rule are denied.
+    let a2 = cachelayer
+        .pam_account_allowed("testaccount1", Some("login"))
+        .await
+        .expect("failed to check account");
+    assert_eq!(a2, Some(false));
+
+    let a3 = cachelayer
+        .pam_account_allowed("testaccount1", None)
+        .await
+        .expect("failed to check account");
+    assert_eq!(a3, Some(false));
+
+    // The cached rules still apply while offline.
+    cachelayer.mark_offline().await;
+
+    let a4 = cachelayer
+        .pam_account_allowed("testaccount1", Some("sshd"))
+        .await
+        .expect("failed to check account");
+    assert_eq!(a4, Some(true));
+}
+
+#[tokio::test]
+async fn test_cache_hbac_rules_offline_empty() {
+    let (_cachelayer, adminclient) = setup_test(fixture(test_fixture)).await;
+
+    adminclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await
+        .expect("failed to auth as admin");
+
+    let cachelayer = setup_host_resolver(&adminclient, "testhost1").await;
+
+    adminclient
+        .idm_hbac_rule_create("test_hbac_rule")
+        .await
+        .unwrap();
+    adminclient
+        .idm_hbac_rule_add_attr("test_hbac_rule", ATTR_HBAC_USER, &["testgroup1"])
+        .await
+        .unwrap();
+    adminclient
+        .idm_hbac_rule_add_attr("test_hbac_rule", ATTR_HBAC_HOST, &["testhost1"])
+        .await
+        .unwrap();
+    adminclient
+        .idm_hbac_rule_add_attr("test_hbac_rule", ATTR_HBAC_SERVICE, &["sshd"])
+        .await
+        .unwrap();
+
+    // Cache the account, but not the rules.
+    let ut = cachelayer
+        .get_nssaccount_name("testaccount1")
+        .await
+        .expect("Failed to get from cache");
+    assert!(ut.is_some());
+
+    // The rule allows access, but was never retrieved, so the host must deny.
+    cachelayer.mark_offline().await;
+
+    let a1 = cachelayer
+        .pam_account_allowed("testaccount1", Some("sshd"))
+        .await
+        .expect("failed to check account");
+    assert_eq!(a1, Some(false));
+}
+
+#[tokio::test]
+async fn test_cache_subid_ranges() {
+    let (cachelayer, _adminclient) = setup_test(fixture(test_fixture)).await;
+    cachelayer.attempt_online().await;
+
+    // Posix accounts are allocated a range automatically.
+    let ranges = cachelayer
+        .get_nsssubid_ranges("testaccount1")
+        .await
+        .expect("failed to get subid ranges")
+        .expect("account not found");
+    assert_eq!(ranges.len(), 1);
+    let range = ranges[0];
+    assert_eq!(range.count, 65536);
+
+    let owners = cachelayer
+        .get_nsssubid_owners(range.start + range.count - 1)
+        .await
+        .expect("failed to get subid owners");
+    assert_eq!(owners, vec![20000]);
+
+    let owners = cachelayer
+        .get_nsssubid_owners(range.start + range.count)
+        .await
+        .expect("failed to get subid owners");
+    assert!(owners.is_empty());
+
+    let ranges = cachelayer
+        .get_nsssubid_ranges("NO_SUCH_ACCOUNT")
+        .await
+        .expect("failed to get subid ranges");
+    assert!(ranges.is_none());
+
+    // The ranges remain available while offline.
+    cachelayer.mark_offline().await;
+
+    let ranges = cachelayer
+        .get_nsssubid_ranges("testaccount1")
+        .await
+        .expect("failed to get subid ranges")
+        .expect("account not found");
+    assert_eq!(ranges, vec![range]);
+}
+
+#[tokio::test]
+async fn test_cache_automount_maps() {
+    let (cachelayer, adminclient) = setup_test(fixture(test_fixture)).await;
+    cachelayer.attempt_online().await;
+
+    // The map does not exist yet.
+    let m1 = cachelayer
+        .get_nssautomount_map("auto.home")
+        .await
+        .expect("failed to get automount map");
+    assert!(m1.is_none());
+
+    adminclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await
+        .expect("failed to auth as admin");
+    adminclient
+        .idm_automount_map_create("auto.home")
+        .await
+        .unwrap();
+    adminclient
+        .idm_automount_key_create("auto.home", "*", "-rw nfs.example.com:/home/&")
+        .await
+        .unwrap();
+
+    // Invalidate cache to force a refresh
+    assert!(cachelayer.invalidate().await.is_ok());
+
+    let expect = vec![NssAutomountEntry {
+        key: "*".to_string(),
+        information: "-rw nfs.example.com:/home/&".to_string(),
+    }];
+
+    let m2 = cachelayer
+        .get_nssautomount_map("auto.home")
+        .await
+        .expect("failed to get automount map");
+    assert_eq!(m2, Some(expect.clone()));
+
+    // The maps remain available while offline.
+    cachelayer.mark_offline().await;
+
+    let m3 = cachelayer
+        .get_nssautomount_map("auto.home")
+        .await
+        .expect("failed to get automount map");
+    assert_eq!(m3, Some(expect));
+}
+
 #[tokio::test]
 async fn test_cache_account_pam_nonexist() {
     let (cachelayer, _adminclient) = setup_test(fixture(test_fixture)).await;
     cachelayer.attempt_online().await;
 
     let a1 = cachelayer
-        .pam_account_allowed("NO_SUCH_ACCOUNT")
+        .pam_account_allowed("NO_SUCH_ACCOUNT", None)
         .await
         .expect("failed to authenticate");
     assert!(a1.is_none());
@@ -554,7 +980,7 @@ async fn test_cache_account_pam_nonexist() {
     cachelayer.mark_offline().await;
 
     let a1 = cachelayer
-        .pam_account_allowed("NO_SUCH_ACCOUNT")
+        .pam_account_allowed("NO_SUCH_ACCOUNT", None)
         .await
         .expect("failed to authenticate");
     assert!(a1.is_none());
@@ -606,7 +1032,7 @@ async fn test_cache_account_expiry() {
 
     // Pam account allowed should be denied.
     let a3 = cachelayer
-        .pam_account_allowed("testaccount1")
+        .pam_account_allowed("testaccount1", None)
         .await
         .expect("failed to authenticate");
     assert!(a3 == Some(false));
@@ -630,7 +1056,7 @@ async fn test_cache_account_expiry() {
 
     // Pam account allowed should be denied.
     let a5 = cachelayer
-        .pam_account_allowed("testaccount1")
+        .pam_account_allowed("testaccount1", None)
         .await
         .expect("failed to authenticate");
     assert!(a5 == Some(false));